@@ -21,6 +21,10 @@ library SODSVerifier {
         "BehavioralCommitment(uint256 blockNumber,uint256 chainId,bytes32 receiptsRoot,bytes32 bmtRoot)"
     );
 
+    bytes32 private constant AVAILABLE_COMMITMENT_TYPEHASH = keccak256(
+        "BehavioralCommitment(uint256 blockNumber,uint256 chainId,bytes32 receiptsRoot,bytes32 bmtRoot,bytes32 availabilityHash)"
+    );
+
     /// @notice Verifies a behavioral proof against a BMT root.
     /// @dev Returns a tuple (proofValid, beaconAnchored) instead of a single bool.
     ///      - proofValid: true if the Merkle proof and leaf hashes are cryptographically valid.
@@ -58,77 +62,140 @@ library SODSVerifier {
         bytes calldata signature,
         address trustedSigner
     ) external view returns (bool proofValid, bool beaconAnchored) {
-        proofValid = false;
-        beaconAnchored = false;
-
         // 1. Verify Commitment Signature (if provided)
-        if (signature.length == 65 && trustedSigner != address(0)) {
-            bytes32 domainSeparator = keccak256(abi.encode(
-                DOMAIN_TYPEHASH,
-                keccak256(bytes("SODS Protocol")),
-                keccak256(bytes("1.0")),
-                block.chainid,
-                address(this)
-            ));
+        if (!_checkCommitment(blockNumber, chainId, receiptsRoot, bmtRoot, bytes32(0), signature, trustedSigner)) {
+            return (false, false);
+        }
+        beaconAnchored = _checkBeaconRoot(beaconRoot, timestamp);
+        proofValid = _checkInclusion(symbols, logIndices, leafHashes, merklePath, isLeftPath, bmtRoot);
+    }
+
+    /// @notice Verifies a behavioral proof whose commitment binds a published bundle.
+    /// @dev Same as `verifyBehavior`, but the signed commitment also covers
+    ///      `availabilityHash`, the keccak256 of the IPFS/Arweave CID the
+    ///      proof bundle was published under.
+    /// @param availabilityHash keccak256 of the availability CID.
+    function verifyBehaviorWithAvailability(
+        uint256 blockNumber,
+        uint256 chainId,
+        string[] calldata symbols,
+        uint32[] calldata logIndices,
+        bytes32[] calldata leafHashes,
+        bytes32[] calldata merklePath,
+        bool[] calldata isLeftPath,
+        bytes32 bmtRoot,
+        bytes32 beaconRoot,
+        uint256 timestamp,
+        bytes32 receiptsRoot,
+        bytes calldata signature,
+        address trustedSigner,
+        bytes32 availabilityHash
+    ) external view returns (bool proofValid, bool beaconAnchored) {
+        if (!_checkCommitment(blockNumber, chainId, receiptsRoot, bmtRoot, availabilityHash, signature, trustedSigner)) {
+            return (false, false);
+        }
+        beaconAnchored = _checkBeaconRoot(beaconRoot, timestamp);
+        proofValid = _checkInclusion(symbols, logIndices, leafHashes, merklePath, isLeftPath, bmtRoot);
+    }
+
+    /// @dev True if no signature is supplied, or it recovers to `trustedSigner`.
+    ///      A zero `availabilityHash` uses the original commitment type.
+    function _checkCommitment(
+        uint256 blockNumber,
+        uint256 chainId,
+        bytes32 receiptsRoot,
+        bytes32 bmtRoot,
+        bytes32 availabilityHash,
+        bytes calldata signature,
+        address trustedSigner
+    ) private view returns (bool) {
+        if (signature.length != 65 || trustedSigner == address(0)) {
+            return true;
+        }
+
+        bytes32 domainSeparator = keccak256(abi.encode(
+            DOMAIN_TYPEHASH,
+            keccak256(bytes("SODS Protocol")),
+            keccak256(bytes("1.0")),
+            block.chainid,
+            address(this)
+        ));
 
-            bytes32 structHash = keccak256(abi.encode(
+        bytes32 structHash = availabilityHash == bytes32(0)
+            ? keccak256(abi.encode(
                 COMMITMENT_TYPEHASH,
                 blockNumber,
                 chainId,
                 receiptsRoot,
                 bmtRoot
+            ))
+            : keccak256(abi.encode(
+                AVAILABLE_COMMITMENT_TYPEHASH,
+                blockNumber,
+                chainId,
+                receiptsRoot,
+                bmtRoot,
+                availabilityHash
             ));
 
-            bytes32 digest = keccak256(abi.encodePacked(
-                "\x19\x01",
-                domainSeparator,
-                structHash
-            ));
-            
-            // Extract v, r, s
-            bytes32 r;
-            bytes32 s;
-            uint8 v;
-            assembly {
-                r := calldataload(signature.offset)
-                s := calldataload(add(signature.offset, 32))
-                v := byte(0, calldataload(add(signature.offset, 64)))
-            }
-            if (v < 27) v += 27;
+        bytes32 digest = keccak256(abi.encodePacked(
+            "\x19\x01",
+            domainSeparator,
+            structHash
+        ));
 
-            address recovered = ecrecover(digest, v, r, s);
-            if (recovered != trustedSigner) return (false, false);
+        // Extract v, r, s
+        bytes32 r;
+        bytes32 s;
+        uint8 v;
+        assembly {
+            r := calldataload(signature.offset)
+            s := calldataload(add(signature.offset, 32))
+            v := byte(0, calldataload(add(signature.offset, 64)))
         }
+        if (v < 27) v += 27;
 
-        // 2. Anchor to Beacon Chain (EIP-4788) — best-effort
-        if (beaconRoot != bytes32(0)) {
-            try IBeaconRoots(BEACON_ROOTS_ADDRESS).getBeaconRoot(uint64(timestamp)) returns (bytes32 trustedRoot) {
-                if (trustedRoot == beaconRoot) {
-                    beaconAnchored = true;
-                }
-                // If trustedRoot != beaconRoot, beaconAnchored stays false
-            } catch {
-                // EIP-4788 not supported or contract not deployed
-                // beaconAnchored stays false — caller decides how to handle
-            }
+        return ecrecover(digest, v, r, s) == trustedSigner;
+    }
+
+    /// @dev Anchor to Beacon Chain (EIP-4788) — best-effort.
+    function _checkBeaconRoot(bytes32 beaconRoot, uint256 timestamp) private view returns (bool) {
+        if (beaconRoot == bytes32(0)) {
+            return false;
+        }
+        try IBeaconRoots(BEACON_ROOTS_ADDRESS).getBeaconRoot(uint64(timestamp)) returns (bytes32 trustedRoot) {
+            return trustedRoot == beaconRoot;
+        } catch {
+            // EIP-4788 not supported or contract not deployed
+            return false;
         }
+    }
 
+    /// @dev Leaf hash and Merkle path checks against `bmtRoot`.
+    function _checkInclusion(
+        string[] calldata symbols,
+        uint32[] calldata logIndices,
+        bytes32[] calldata leafHashes,
+        bytes32[] calldata merklePath,
+        bool[] calldata isLeftPath,
+        bytes32 bmtRoot
+    ) private pure returns (bool) {
         // 3. Validate input arrays
         if (symbols.length == 0 || symbols.length != logIndices.length || symbols.length != leafHashes.length) {
-            return (false, beaconAnchored);
+            return false;
         }
 
         // 4. Verify that leaf hashes match symbols and indices
         // Formula: keccak256(abi.encodePacked(symbol, logIndex))
         for (uint256 i = 0; i < symbols.length; i++) {
             if (keccak256(abi.encodePacked(symbols[i], logIndices[i])) != leafHashes[i]) {
-                return (false, beaconAnchored);
+                return false;
             }
         }
 
         // 5. Verify Merkle Path
         if (merklePath.length != isLeftPath.length) {
-            return (false, beaconAnchored);
+            return false;
         }
 
         bytes32 computedHash = leafHashes[0];
@@ -144,7 +211,6 @@ library SODSVerifier {
             }
         }
 
-        proofValid = (computedHash == bmtRoot);
-        return (proofValid, beaconAnchored);
+        return computedHash == bmtRoot;
     }
 }
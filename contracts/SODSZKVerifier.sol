@@ -10,7 +10,7 @@ contract SODSZKVerifier {
     struct ZKBehaviorClaim {
         uint256 blockNumber;
         uint256 chainId;
-        string pattern;
+        bytes32 patternHash;
         bool result;
         uint256 timestamp;
     }
@@ -19,7 +19,7 @@ contract SODSZKVerifier {
     event BehaviorProven(
         uint256 indexed blockNumber,
         uint256 indexed chainId,
-        string pattern,
+        bytes32 indexed patternHash,
         bool result
     );
 
@@ -52,13 +52,14 @@ contract SODSZKVerifier {
         // For the PoC/v1, we perform a length check on the seal to simulate verification.
         require(seal.length > 0, "SODSZKVerifier: Empty seal");
 
-        // Decode journal: (uint256 blockNumber, uint256 chainId, string pattern, bool result)
-        (uint256 blockNumber, uint256 chainId, string memory pattern, bool result) = abi.decode(
+        // Decode journal: (uint256 blockNumber, uint256 chainId, bytes32 patternHash, bool result)
+        // patternHash is the pattern's canonical hash (`sods hash-pattern`)
+        (uint256 blockNumber, uint256 chainId, bytes32 patternHash, bool result) = abi.decode(
             journal, 
-            (uint256, uint256, string, bool)
+            (uint256, uint256, bytes32, bool)
         );
 
-        emit BehaviorProven(blockNumber, chainId, pattern, result);
+        emit BehaviorProven(blockNumber, chainId, patternHash, result);
         
         return true;
     }
@@ -89,7 +89,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         pattern: proof.pattern.clone(),
         matched_events: tampered_events,
         event_proofs: proof.event_proofs.clone(),
-        agent_root: proof.agent_root,
+        ..proof.clone()
     };
 
     println!(
@@ -132,7 +132,7 @@ fn test_tampered_proof_detection() {
         pattern: proof.pattern.clone(),
         matched_events: tampered_events,
         event_proofs: proof.event_proofs.clone(),
-        agent_root: proof.agent_root,
+        ..proof.clone()
     };
 
     assert!(
@@ -0,0 +1,392 @@
+//! Directory of agent profiles for counterparty discovery.
+//!
+//! Each agent publishes a profile with its signing key, declared
+//! capabilities and the causal Merkle root of its latest history. Updates
+//! are signed by the registered key and versioned, so a counterparty that
+//! looks an agent up gets the commitment its proofs must be checked against.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sods_causal::{AgentDirectory, AgentProfile};
+//! use ethers::signers::{LocalWallet, Signer};
+//! use ethers::types::H256;
+//!
+//! let key: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+//!     .parse()
+//!     .unwrap();
+//! let agent = key.address();
+//!
+//! let update = AgentProfile::new(agent, &key)
+//!     .with_capability("swap")
+//!     .with_history(H256::repeat_byte(7), 12)
+//!     .sign(&key)
+//!     .unwrap();
+//!
+//! let mut directory = AgentDirectory::new();
+//! directory.apply(update).unwrap();
+//! assert_eq!(directory.find_by_capability("swap").len(), 1);
+//! assert_eq!(directory.history_root(&agent), Some(H256::repeat_byte(7)));
+//! ```
+
+use std::collections::HashMap;
+
+use ethers::core::k256::ecdsa::VerifyingKey;
+use ethers::core::utils::public_key_to_address;
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, Bytes, RecoveryMessage, Signature, H256};
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::error::{CausalError, Result};
+use crate::proof::CausalProof;
+
+/// Public profile of a registered agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AgentProfile {
+    /// Agent address the profile describes.
+    pub agent_id: Address,
+    /// SEC1-encoded secp256k1 public key authorized to sign profile updates.
+    pub public_key: Bytes,
+    /// Declared capabilities (e.g. "swap", "bridge").
+    pub capabilities: Vec<String>,
+    /// Causal Merkle root of the agent's latest history.
+    pub history_root: H256,
+    /// Number of events covered by `history_root`.
+    pub history_len: u64,
+    /// Monotonic update counter.
+    pub version: u64,
+    /// Unix timestamp of the update.
+    pub updated_at: u64,
+}
+
+impl AgentProfile {
+    /// Create a version-1 profile whose updates are signed by `key`.
+    pub fn new(agent_id: Address, key: &LocalWallet) -> Self {
+        let public_key = key.signer().verifying_key().to_encoded_point(true);
+        Self {
+            agent_id,
+            public_key: Bytes::from(public_key.as_bytes().to_vec()),
+            capabilities: Vec::new(),
+            history_root: H256::zero(),
+            history_len: 0,
+            version: 1,
+            updated_at: 0,
+        }
+    }
+
+    /// Declare a capability (Builder pattern).
+    pub fn with_capability(mut self, capability: impl Into<String>) -> Self {
+        let capability = capability.into();
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+        self
+    }
+
+    /// Set the committed history (Builder pattern).
+    pub fn with_history(mut self, root: H256, len: u64) -> Self {
+        self.history_root = root;
+        self.history_len = len;
+        self
+    }
+
+    /// Set the update version (Builder pattern).
+    pub fn with_version(mut self, version: u64) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Set the update timestamp (Builder pattern).
+    pub fn with_updated_at(mut self, timestamp: u64) -> Self {
+        self.updated_at = timestamp;
+        self
+    }
+
+    /// Whether the agent declared `capability`.
+    pub fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    /// Address derived from `public_key`, or `None` if the key is malformed.
+    pub fn signer_address(&self) -> Option<Address> {
+        VerifyingKey::from_sec1_bytes(&self.public_key)
+            .ok()
+            .map(|key| public_key_to_address(&key))
+    }
+
+    /// The digest that gets signed.
+    ///
+    /// Formula: `keccak256(agent_id || public_key || capabilities || history_root || history_len || version || updated_at)`,
+    /// with each capability length-prefixed.
+    pub fn digest(&self) -> H256 {
+        let mut hasher = Keccak::v256();
+        hasher.update(self.agent_id.as_bytes());
+        hasher.update(&self.public_key);
+        hasher.update(&(self.capabilities.len() as u32).to_be_bytes());
+        for capability in &self.capabilities {
+            hasher.update(&(capability.len() as u32).to_be_bytes());
+            hasher.update(capability.as_bytes());
+        }
+        hasher.update(self.history_root.as_bytes());
+        hasher.update(&self.history_len.to_be_bytes());
+        hasher.update(&self.version.to_be_bytes());
+        hasher.update(&self.updated_at.to_be_bytes());
+        let mut output = [0u8; 32];
+        hasher.finalize(&mut output);
+        H256::from(output)
+    }
+
+    /// Sign the profile, producing an update for the directory.
+    ///
+    /// # Errors
+    /// Returns `CausalError` if signing fails.
+    pub fn sign(self, key: &LocalWallet) -> Result<ProfileUpdate> {
+        let signature = key
+            .sign_hash(self.digest())
+            .map_err(|e| CausalError::InternalError(format!("Signing failed: {}", e)))?;
+        Ok(ProfileUpdate {
+            profile: self,
+            signature,
+        })
+    }
+}
+
+/// A profile together with the signature authorizing it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileUpdate {
+    /// The new profile.
+    pub profile: AgentProfile,
+    /// Signature over `profile.digest()`.
+    pub signature: Signature,
+}
+
+impl ProfileUpdate {
+    /// Address that signed the update, if the signature is well-formed.
+    pub fn signer(&self) -> Option<Address> {
+        self.signature
+            .recover(RecoveryMessage::Hash(self.profile.digest()))
+            .ok()
+    }
+}
+
+/// In-memory directory of agent profiles.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct AgentDirectory {
+    profiles: HashMap<Address, AgentProfile>,
+}
+
+impl AgentDirectory {
+    /// Creates an empty directory.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies a signed profile update.
+    ///
+    /// A first registration must be signed by the agent's own key: the
+    /// declared key's address must be the `agent_id`, so nobody can claim
+    /// another agent's identity first. Later updates must be signed by the
+    /// currently registered key (which may rotate to a new one) and carry a
+    /// higher version.
+    ///
+    /// # Errors
+    /// Returns `CausalError::UnauthorizedUpdate` for a bad signature or key,
+    /// and `CausalError::StaleUpdate` for a non-increasing version.
+    pub fn apply(&mut self, update: ProfileUpdate) -> Result<()> {
+        let agent_id = update.profile.agent_id;
+        let new_key = update.profile.signer_address().ok_or_else(|| {
+            CausalError::UnauthorizedUpdate(format!("malformed public key for {:?}", agent_id))
+        })?;
+
+        let authorized = match self.profiles.get(&agent_id) {
+            Some(current) => {
+                if update.profile.version <= current.version {
+                    return Err(CausalError::StaleUpdate {
+                        current: current.version,
+                        received: update.profile.version,
+                    });
+                }
+                current.signer_address()
+            }
+            None if new_key == agent_id => Some(new_key),
+            None => {
+                return Err(CausalError::UnauthorizedUpdate(format!(
+                    "first registration for {:?} must declare the agent's own key",
+                    agent_id
+                )))
+            }
+        };
+
+        if update.signer().is_none() || update.signer() != authorized {
+            return Err(CausalError::UnauthorizedUpdate(format!(
+                "signature does not match registered key for {:?}",
+                agent_id
+            )));
+        }
+
+        self.profiles.insert(agent_id, update.profile);
+        Ok(())
+    }
+
+    /// Looks up an agent's profile.
+    pub fn get(&self, agent_id: &Address) -> Option<&AgentProfile> {
+        self.profiles.get(agent_id)
+    }
+
+    /// Latest history root committed by an agent.
+    pub fn history_root(&self, agent_id: &Address) -> Option<H256> {
+        self.profiles.get(agent_id).map(|p| p.history_root)
+    }
+
+    /// All agents declaring `capability`, ordered by address.
+    pub fn find_by_capability(&self, capability: &str) -> Vec<&AgentProfile> {
+        let mut found: Vec<_> = self
+            .profiles
+            .values()
+            .filter(|p| p.has_capability(capability))
+            .collect();
+        found.sort_by_key(|p| p.agent_id);
+        found
+    }
+
+    /// Verifies a causal proof against the root its agent last committed.
+    pub fn verify_proof(&self, proof: &CausalProof) -> bool {
+        self.history_root(&proof.event.agent_id) == Some(proof.root) && proof.verify()
+    }
+
+    /// Number of registered agents.
+    pub fn len(&self) -> usize {
+        self.profiles.len()
+    }
+
+    /// Whether no agents are registered.
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::CausalEvent;
+    use crate::tree::CausalMerkleTree;
+
+    use ethers::signers::Signer;
+
+    fn agent() -> Address {
+        key(1).address()
+    }
+
+    fn key(byte: u8) -> LocalWallet {
+        LocalWallet::from_bytes(&[byte; 32]).unwrap()
+    }
+
+    #[test]
+    fn test_register_and_lookup() {
+        let mut directory = AgentDirectory::new();
+        let update = AgentProfile::new(agent(), &key(1))
+            .with_capability("swap")
+            .with_capability("bridge")
+            .sign(&key(1))
+            .unwrap();
+        directory.apply(update).unwrap();
+
+        assert_eq!(directory.len(), 1);
+        assert!(directory.get(&agent()).unwrap().has_capability("bridge"));
+        assert!(directory.find_by_capability("lend").is_empty());
+    }
+
+    #[test]
+    fn test_updates_require_registered_key_and_newer_version() {
+        let mut directory = AgentDirectory::new();
+        directory
+            .apply(AgentProfile::new(agent(), &key(1)).sign(&key(1)).unwrap())
+            .unwrap();
+
+        // Signed by someone else
+        let forged = AgentProfile::new(agent(), &key(2))
+            .with_version(2)
+            .sign(&key(2))
+            .unwrap();
+        assert!(matches!(
+            directory.apply(forged),
+            Err(CausalError::UnauthorizedUpdate(_))
+        ));
+
+        // Replay of an old version
+        let replay = AgentProfile::new(agent(), &key(1)).sign(&key(1)).unwrap();
+        assert!(matches!(
+            directory.apply(replay),
+            Err(CausalError::StaleUpdate { .. })
+        ));
+
+        // Key rotation authorized by the current key
+        let rotation = AgentProfile::new(agent(), &key(2))
+            .with_version(2)
+            .sign(&key(1))
+            .unwrap();
+        directory.apply(rotation).unwrap();
+        let next = AgentProfile::new(agent(), &key(2))
+            .with_version(3)
+            .sign(&key(2))
+            .unwrap();
+        directory.apply(next).unwrap();
+        assert_eq!(directory.get(&agent()).unwrap().version, 3);
+    }
+
+    #[test]
+    fn test_first_registration_cannot_squat() {
+        let mut directory = AgentDirectory::new();
+
+        // A self-consistent profile for an identity the key does not own
+        let squat = AgentProfile::new(agent(), &key(2)).sign(&key(2)).unwrap();
+        assert!(matches!(
+            directory.apply(squat),
+            Err(CausalError::UnauthorizedUpdate(_))
+        ));
+        assert!(directory.is_empty());
+
+        // The real agent can still register
+        directory
+            .apply(AgentProfile::new(agent(), &key(1)).sign(&key(1)).unwrap())
+            .unwrap();
+        assert_eq!(directory.len(), 1);
+    }
+
+    #[test]
+    fn test_tampered_profile_rejected() {
+        let mut directory = AgentDirectory::new();
+        let mut update = AgentProfile::new(agent(), &key(1)).sign(&key(1)).unwrap();
+        update.profile.capabilities.push("admin".to_string());
+        assert!(directory.apply(update).is_err());
+        assert!(directory.is_empty());
+    }
+
+    #[test]
+    fn test_verify_proof_against_committed_root() {
+        let event = CausalEvent::builder()
+            .agent_id(agent())
+            .result("success")
+            .build()
+            .unwrap();
+        let tree = CausalMerkleTree::new(vec![event]).unwrap();
+
+        let mut directory = AgentDirectory::new();
+        directory
+            .apply(
+                AgentProfile::new(agent(), &key(1))
+                    .with_history(tree.root, 1)
+                    .sign(&key(1))
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let proof = tree.generate_proof(0);
+        assert!(directory.verify_proof(&proof));
+
+        let mut stale = proof.clone();
+        stale.root = H256::repeat_byte(9);
+        assert!(!directory.verify_proof(&stale));
+    }
+}
@@ -3,6 +3,8 @@
 //! This module defines all error types that can occur during
 //! causal event creation, validation, and recording.
 
+use ethers::types::Address;
+use sods_core::{ErrorClass, ErrorCode};
 use thiserror::Error;
 
 /// Errors that can occur in causal event operations.
@@ -12,6 +14,10 @@ pub enum CausalError {
     #[error("Invalid agent address: {0}")]
     InvalidAgentAddress(String),
 
+    /// No events have been recorded for the agent.
+    #[error("No events found for agent {0:?}")]
+    AgentNotFound(Address),
+
     /// Sequence gap detected within a transaction.
     #[error("Event sequence gap detected: expected {expected}, got {actual}")]
     SequenceGap {
@@ -30,6 +36,47 @@ pub enum CausalError {
         actual: u64,
     },
 
+    /// No event recorded at the given causal position.
+    #[error("Event not found: nonce {nonce}, sequence {sequence_index}")]
+    EventNotFound {
+        /// Requested nonce
+        nonce: u64,
+        /// Requested sequence index
+        sequence_index: u32,
+    },
+
+    /// The event's payload was already redacted, so there is no opening
+    /// left to return.
+    #[error("Event already redacted: nonce {nonce}, sequence {sequence_index}")]
+    AlreadyRedacted {
+        /// Requested nonce
+        nonce: u64,
+        /// Requested sequence index
+        sequence_index: u32,
+    },
+
+    /// Fewer events matched a pattern than it requires.
+    #[error("Pattern matched {matched} events, {required} required")]
+    PatternNotMatched {
+        /// Events the pattern requires
+        required: u32,
+        /// Events that matched
+        matched: u32,
+    },
+
+    /// Profile update not signed by the registered key.
+    #[error("Unauthorized directory update: {0}")]
+    UnauthorizedUpdate(String),
+
+    /// Profile update does not advance the registered version.
+    #[error("Stale directory update: current version {current}, got {received}")]
+    StaleUpdate {
+        /// Registered version
+        current: u64,
+        /// Version in the rejected update
+        received: u64,
+    },
+
     /// Invalid result value provided.
     #[error("Invalid result value: {0}. Must be 'success', 'failure', 'partial', or 'timeout'")]
     InvalidResult(String),
@@ -41,7 +88,99 @@ pub enum CausalError {
     /// Internal consistency or logic error.
     #[error("Internal error: {0}")]
     InternalError(String),
+
+    /// Error reading on-chain history.
+    #[cfg(feature = "onchain")]
+    #[error("Verifier error: {0}")]
+    Verifier(#[from] sods_verifier::SodsVerifierError),
+}
+
+impl ErrorCode for CausalError {
+    fn code(&self) -> &'static str {
+        match self {
+            CausalError::InvalidAgentAddress(_) => "invalid_agent_address",
+            CausalError::AgentNotFound(_) => "agent_not_found",
+            CausalError::SequenceGap { .. } => "sequence_gap",
+            CausalError::NonceGap { .. } => "nonce_gap",
+            CausalError::EventNotFound { .. } => "event_not_found",
+            CausalError::AlreadyRedacted { .. } => "already_redacted",
+            CausalError::PatternNotMatched { .. } => "pattern_not_matched",
+            CausalError::UnauthorizedUpdate(_) => "unauthorized_update",
+            CausalError::StaleUpdate { .. } => "stale_update",
+            CausalError::InvalidResult(_) => "invalid_result",
+            CausalError::Serialization(_) => "serialization_error",
+            CausalError::InternalError(_) => "internal_error",
+            #[cfg(feature = "onchain")]
+            CausalError::Verifier(e) => e.code(),
+        }
+    }
+
+    fn class(&self) -> ErrorClass {
+        match self {
+            CausalError::InvalidAgentAddress(_)
+            | CausalError::SequenceGap { .. }
+            | CausalError::NonceGap { .. }
+            | CausalError::StaleUpdate { .. }
+            | CausalError::AlreadyRedacted { .. }
+            | CausalError::InvalidResult(_)
+            | CausalError::Serialization(_) => ErrorClass::InvalidInput,
+            CausalError::AgentNotFound(_)
+            | CausalError::EventNotFound { .. }
+            | CausalError::PatternNotMatched { .. } => ErrorClass::NotFound,
+            CausalError::UnauthorizedUpdate(_) => ErrorClass::VerificationFailed,
+            CausalError::InternalError(_) => ErrorClass::Internal,
+            #[cfg(feature = "onchain")]
+            CausalError::Verifier(e) => e.class(),
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            #[cfg(feature = "onchain")]
+            CausalError::Verifier(e) => e.is_retryable(),
+            _ => false,
+        }
+    }
+
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            #[cfg(feature = "onchain")]
+            CausalError::Verifier(e) => e.retry_after(),
+            _ => None,
+        }
+    }
 }
 
 /// Result type alias for causal operations.
 pub type Result<T> = std::result::Result<T, CausalError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_classes() {
+        let err = CausalError::AgentNotFound(Address::repeat_byte(0x11));
+        assert_eq!(err.code(), "agent_not_found");
+        assert_eq!(err.class().http_status(), 404);
+        assert!(err
+            .to_string()
+            .contains("0x1111111111111111111111111111111111111111"));
+
+        let err = CausalError::NonceGap {
+            expected: 1,
+            actual: 3,
+        };
+        assert_eq!(err.class(), ErrorClass::InvalidInput);
+        assert!(!err.is_retryable());
+    }
+
+    #[cfg(feature = "onchain")]
+    #[test]
+    fn test_verifier_errors_pass_through() {
+        let err = CausalError::from(sods_verifier::SodsVerifierError::Timeout);
+        assert_eq!(err.code(), "timeout");
+        assert!(err.is_retryable());
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
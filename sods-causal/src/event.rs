@@ -28,6 +28,7 @@ use ethers::types::{Address, H256};
 use ethers::utils::rlp::{Encodable, RlpStream};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use tiny_keccak::{Hasher, Keccak};
 
 use crate::error::{CausalError, Result};
 
@@ -67,6 +68,62 @@ pub struct CausalEvent {
     /// Optional IPFS hash pointing to detailed metadata
     /// (e.g., input parameters, output data, error logs).
     pub metadata_hash: Option<H256>,
+
+    /// Salt blinding the payload commitment; random unless the agent
+    /// chose one.
+    #[serde(default)]
+    pub salt: H256,
+
+    /// Payload commitment left behind when `task_id` and `metadata_hash`
+    /// have been redacted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redacted_payload: Option<H256>,
+}
+
+/// The private data removed from an event by redaction.
+///
+/// Kept by the agent; disclosing it lets anyone check the redacted event's
+/// commitment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadOpening {
+    /// Original task identifier.
+    pub task_id: Option<String>,
+    /// Original metadata hash.
+    pub metadata_hash: Option<H256>,
+    /// Salt used in the commitment.
+    pub salt: H256,
+}
+
+impl PayloadOpening {
+    /// Computes the commitment this opening corresponds to.
+    pub fn commitment(&self) -> H256 {
+        payload_commitment(&self.task_id, &self.metadata_hash, &self.salt)
+    }
+
+    /// Returns true if this opening matches a redacted event's commitment.
+    pub fn opens(&self, event: &CausalEvent) -> bool {
+        event.redacted_payload == Some(self.commitment())
+    }
+}
+
+/// Commitment to an event's private payload.
+///
+/// Formula: `keccak256(salt || RLP([task_id, metadata_hash]))`
+pub fn payload_commitment(
+    task_id: &Option<String>,
+    metadata_hash: &Option<H256>,
+    salt: &H256,
+) -> H256 {
+    let mut stream = RlpStream::new_list(2);
+    stream.append(task_id);
+    stream.append(metadata_hash);
+
+    let mut hasher = Keccak::v256();
+    hasher.update(salt.as_bytes());
+    hasher.update(&stream.out());
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    H256::from(output)
 }
 
 impl CausalEvent {
@@ -80,6 +137,7 @@ impl CausalEvent {
     /// # Validation Rules
     /// - `result` must be one of: "success", "failure", "partial", "timeout"
     /// - `agent_id` must not be the zero address
+    /// - a redacted event must not carry `task_id` or `metadata_hash`
     ///
     /// # Returns
     /// `Ok(())` if valid, `Err(CausalError)` otherwise.
@@ -96,22 +154,76 @@ impl CausalEvent {
             ));
         }
 
+        // Validate redaction tombstone
+        if self.is_redacted() && (self.task_id.is_some() || self.metadata_hash.is_some()) {
+            return Err(CausalError::InternalError(
+                "Redacted event must not carry payload fields".to_string(),
+            ));
+        }
+
         Ok(())
     }
 
     /// RLP-encodes the event for Merkle tree inclusion.
+    ///
+    /// The private payload (`task_id`, `metadata_hash`) is encoded as its
+    /// commitment, so redacting an event does not change its leaf hash.
     pub fn rlp_encode(&self) -> Vec<u8> {
-        let mut stream = RlpStream::new_list(8);
+        let mut stream = RlpStream::new_list(7);
         stream.append(&self.agent_id);
         stream.append(&self.nonce);
         stream.append(&self.sequence_index);
         stream.append(&self.event_type);
-        stream.append(&self.task_id);
+        stream.append(&self.payload_commitment());
         stream.append(&self.result);
         stream.append(&self.timestamp);
-        stream.append(&self.metadata_hash);
         stream.out().to_vec()
     }
+
+    /// Returns the Merkle leaf hash, `keccak256(rlp_encode())`, which is
+    /// the same for an event and its redacted copy.
+    pub fn leaf_hash(&self) -> H256 {
+        let mut hash = [0u8; 32];
+        let mut hasher = Keccak::v256();
+        hasher.update(&self.rlp_encode());
+        hasher.finalize(&mut hash);
+        H256::from(hash)
+    }
+
+    /// Returns the commitment to this event's private payload.
+    pub fn payload_commitment(&self) -> H256 {
+        self.redacted_payload
+            .unwrap_or_else(|| payload_commitment(&self.task_id, &self.metadata_hash, &self.salt))
+    }
+
+    /// Returns true if the payload has been replaced by its commitment.
+    pub fn is_redacted(&self) -> bool {
+        self.redacted_payload.is_some()
+    }
+
+    /// Replaces the private payload with its commitment (tombstone).
+    ///
+    /// Returns the redacted event and the opening the agent must keep to
+    /// disclose the payload later. An event that is already redacted is
+    /// returned unchanged with no opening, since its payload is gone.
+    pub fn redact(&self) -> (CausalEvent, Option<PayloadOpening>) {
+        if self.is_redacted() {
+            return (self.clone(), None);
+        }
+        let opening = PayloadOpening {
+            task_id: self.task_id.clone(),
+            metadata_hash: self.metadata_hash,
+            salt: self.salt,
+        };
+        let redacted = CausalEvent {
+            task_id: None,
+            metadata_hash: None,
+            salt: H256::zero(),
+            redacted_payload: Some(self.payload_commitment()),
+            ..self.clone()
+        };
+        (redacted, Some(opening))
+    }
 }
 
 impl Encodable for CausalEvent {
@@ -150,6 +262,7 @@ pub struct CausalEventBuilder {
     result: Option<String>,
     timestamp: Option<u64>,
     metadata_hash: Option<H256>,
+    salt: Option<H256>,
 }
 
 impl CausalEventBuilder {
@@ -201,6 +314,14 @@ impl CausalEventBuilder {
         self
     }
 
+    /// Sets the salt blinding the payload commitment, instead of a random
+    /// one. A guessable salt lets anyone who can guess the payload check
+    /// it against a redacted event.
+    pub fn salt(mut self, salt: H256) -> Self {
+        self.salt = Some(salt);
+        self
+    }
+
     /// Builds and validates the `CausalEvent`.
     ///
     /// # Errors
@@ -217,6 +338,8 @@ impl CausalEventBuilder {
             result: self.result.unwrap_or_else(|| "success".to_string()),
             timestamp: self.timestamp.unwrap_or(0),
             metadata_hash: self.metadata_hash,
+            salt: self.salt.unwrap_or_else(H256::random),
+            redacted_payload: None,
         };
 
         event.validate()?;
@@ -273,6 +396,49 @@ mod tests {
         assert!(matches!(result, Err(CausalError::InvalidAgentAddress(_))));
     }
 
+    #[test]
+    fn test_redaction_preserves_leaf_encoding() {
+        let event = CausalEvent::builder()
+            .agent_id(test_address())
+            .task_id("secret-task")
+            .metadata_hash(H256::repeat_byte(1))
+            .salt(H256::repeat_byte(7))
+            .build()
+            .unwrap();
+
+        let (redacted, opening) = event.redact();
+        let opening = opening.unwrap();
+        assert!(redacted.is_redacted());
+        assert_eq!(redacted.task_id, None);
+        assert_eq!(redacted.rlp_encode(), event.rlp_encode());
+        assert!(opening.opens(&redacted));
+
+        let wrong = PayloadOpening {
+            task_id: Some("other-task".into()),
+            ..opening
+        };
+        assert!(!wrong.opens(&redacted));
+
+        // Redacting again changes nothing and has nothing to open
+        let (again, none) = redacted.redact();
+        assert_eq!(again, redacted);
+        assert!(none.is_none());
+    }
+
+    #[test]
+    fn test_salt_is_random_by_default() {
+        let build = || {
+            CausalEvent::builder()
+                .agent_id(test_address())
+                .task_id("guessable")
+                .build()
+                .unwrap()
+        };
+        let (a, b) = (build(), build());
+        assert_ne!(a.salt, b.salt);
+        assert_ne!(a.payload_commitment(), b.payload_commitment());
+    }
+
     #[test]
     fn test_causal_ordering() {
         let event1 = CausalEvent::builder()
@@ -0,0 +1,244 @@
+//! Comparison of two claimed histories for the same agent.
+//!
+//! An honest agent presents one append-only history. If two verifiers hold
+//! histories that disagree on the event at some `(nonce, sequence_index)`,
+//! the agent has equivocated. Events are compared by leaf hash, so a copy
+//! with redacted payloads is the same history, not a fork. `compare_histories` finds the divergence point
+//! and packages the conflicting events with inclusion proofs against both
+//! claimed roots, which is enough to prove the equivocation to a third party.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sods_causal::{compare_histories, CausalEvent, HistoryRelation};
+//! use ethers::types::Address;
+//!
+//! let agent: Address = "0x1234567890123456789012345678901234567890".parse().unwrap();
+//! let event = |result: &str| {
+//!     CausalEvent::builder().agent_id(agent).result(result).build().unwrap()
+//! };
+//!
+//! let diff = compare_histories(&[event("success")], &[event("failure")]).unwrap();
+//! assert_eq!(diff.relation, HistoryRelation::Forked);
+//! assert!(diff.conflict.unwrap().verify());
+//! ```
+
+use ethers::types::H256;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::event::CausalEvent;
+use crate::proof::CausalProof;
+use crate::tree::CausalMerkleTree;
+
+/// How two histories relate to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryRelation {
+    /// Both histories contain exactly the same events.
+    Identical,
+    /// History `a` is `b` plus additional events.
+    AExtendsB,
+    /// History `b` is `a` plus additional events.
+    BExtendsA,
+    /// The histories disagree after a common prefix.
+    Forked,
+}
+
+/// Two conflicting events at the same causal position, each proven against
+/// the root of the history that contains it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictEvidence {
+    /// Nonce of the conflicting position.
+    pub nonce: u64,
+    /// Sequence index of the conflicting position.
+    pub sequence_index: u32,
+    /// Inclusion proof of the event in history `a`.
+    pub proof_a: CausalProof,
+    /// Inclusion proof of the event in history `b`.
+    pub proof_b: CausalProof,
+}
+
+impl ConflictEvidence {
+    /// Verifies that the evidence proves an equivocation.
+    ///
+    /// Both proofs must verify, refer to the same agent and causal position,
+    /// and carry events with different leaf hashes.
+    pub fn verify(&self) -> bool {
+        let a = &self.proof_a.event;
+        let b = &self.proof_b.event;
+
+        a.agent_id == b.agent_id
+            && (a.nonce, a.sequence_index) == (self.nonce, self.sequence_index)
+            && (b.nonce, b.sequence_index) == (self.nonce, self.sequence_index)
+            && a.leaf_hash() != b.leaf_hash()
+            && self.proof_a.verify()
+            && self.proof_b.verify()
+    }
+}
+
+/// Result of comparing two histories.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryDiff {
+    /// Relationship between the histories.
+    pub relation: HistoryRelation,
+    /// Number of leading events both histories agree on.
+    pub common_prefix: usize,
+    /// Causal root of history `a`.
+    pub root_a: H256,
+    /// Causal root of history `b`.
+    pub root_b: H256,
+    /// First position where both histories hold different events, if any.
+    pub conflict: Option<ConflictEvidence>,
+}
+
+/// Compares two causally ordered histories of the same agent.
+///
+/// # Errors
+/// Returns `CausalError` if either history is not strictly ordered.
+pub fn compare_histories(a: &[CausalEvent], b: &[CausalEvent]) -> Result<HistoryDiff> {
+    let tree_a = CausalMerkleTree::new(a.to_vec())?;
+    let tree_b = CausalMerkleTree::new(b.to_vec())?;
+
+    // Leaf hashes, not fields: redacting a payload keeps the leaf
+    let same = |x: &CausalEvent, y: &CausalEvent| x.leaf_hash() == y.leaf_hash();
+    let common_prefix = a.iter().zip(b).take_while(|(x, y)| same(x, y)).count();
+
+    let relation = if common_prefix == a.len() && common_prefix == b.len() {
+        HistoryRelation::Identical
+    } else if common_prefix == b.len() {
+        HistoryRelation::AExtendsB
+    } else if common_prefix == a.len() {
+        HistoryRelation::BExtendsA
+    } else {
+        HistoryRelation::Forked
+    };
+
+    // Earliest position (after the prefix) present in both with different content
+    let conflict = a[common_prefix..]
+        .iter()
+        .enumerate()
+        .find_map(|(offset, ea)| {
+            let idx_b = b[common_prefix..]
+                .binary_search_by(|eb| {
+                    (eb.nonce, eb.sequence_index).cmp(&(ea.nonce, ea.sequence_index))
+                })
+                .ok()?
+                + common_prefix;
+            if same(&b[idx_b], ea) {
+                return None;
+            }
+            Some(ConflictEvidence {
+                nonce: ea.nonce,
+                sequence_index: ea.sequence_index,
+                proof_a: tree_a.generate_proof(common_prefix + offset),
+                proof_b: tree_b.generate_proof(idx_b),
+            })
+        });
+
+    Ok(HistoryDiff {
+        relation,
+        common_prefix,
+        root_a: tree_a.root,
+        root_b: tree_b.root,
+        conflict,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Address;
+
+    fn event(nonce: u64, seq: u32, result: &str) -> CausalEvent {
+        CausalEvent::builder()
+            .agent_id(Address::repeat_byte(1))
+            .nonce(nonce)
+            .sequence_index(seq)
+            .result(result)
+            // Fixed, so the same event built twice is the same leaf
+            .salt(H256::zero())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_identical_and_extension() {
+        let a = vec![event(0, 0, "success"), event(1, 0, "success")];
+        let b = a[..1].to_vec();
+
+        let diff = compare_histories(&a, &a).unwrap();
+        assert_eq!(diff.relation, HistoryRelation::Identical);
+        assert!(diff.conflict.is_none());
+
+        let diff = compare_histories(&a, &b).unwrap();
+        assert_eq!(diff.relation, HistoryRelation::AExtendsB);
+        assert_eq!(diff.common_prefix, 1);
+
+        let diff = compare_histories(&b, &a).unwrap();
+        assert_eq!(diff.relation, HistoryRelation::BExtendsA);
+    }
+
+    #[test]
+    fn test_fork_produces_verifiable_evidence() {
+        let a = vec![
+            event(0, 0, "success"),
+            event(1, 0, "success"),
+            event(2, 0, "loss"),
+        ];
+        let b = vec![
+            event(0, 0, "success"),
+            event(1, 0, "failure"),
+            event(2, 0, "loss"),
+        ];
+
+        let diff = compare_histories(&a, &b).unwrap();
+        assert_eq!(diff.relation, HistoryRelation::Forked);
+        assert_eq!(diff.common_prefix, 1);
+
+        let conflict = diff.conflict.unwrap();
+        assert_eq!((conflict.nonce, conflict.sequence_index), (1, 0));
+        assert_eq!(conflict.proof_a.root, diff.root_a);
+        assert_eq!(conflict.proof_b.root, diff.root_b);
+        assert!(conflict.verify());
+    }
+
+    #[test]
+    fn test_structural_fork_finds_shared_position() {
+        // b inserts an extra event into nonce 0, shifting later positions
+        let a = vec![event(0, 0, "success"), event(1, 0, "success")];
+        let b = vec![
+            event(0, 0, "success"),
+            event(0, 1, "success"),
+            event(1, 0, "failure"),
+        ];
+
+        let diff = compare_histories(&a, &b).unwrap();
+        assert_eq!(diff.relation, HistoryRelation::Forked);
+        let conflict = diff.conflict.unwrap();
+        assert_eq!((conflict.nonce, conflict.sequence_index), (1, 0));
+        assert!(conflict.verify());
+    }
+
+    #[test]
+    fn test_redacted_copy_is_not_a_fork() {
+        let a = vec![event(0, 0, "success"), event(1, 0, "success")];
+        let b: Vec<_> = a.iter().map(|e| e.redact().0).collect();
+
+        let diff = compare_histories(&a, &b).unwrap();
+        assert_eq!(diff.relation, HistoryRelation::Identical);
+        assert_eq!(diff.root_a, diff.root_b);
+        assert!(diff.conflict.is_none());
+
+        let diff = compare_histories(&a, &b[..1]).unwrap();
+        assert_eq!(diff.relation, HistoryRelation::AExtendsB);
+    }
+
+    #[test]
+    fn test_tampered_evidence_rejected() {
+        let diff = compare_histories(&[event(0, 0, "success")], &[event(0, 0, "failure")]).unwrap();
+        let mut conflict = diff.conflict.unwrap();
+        conflict.proof_b = conflict.proof_a.clone();
+        assert!(!conflict.verify());
+    }
+}
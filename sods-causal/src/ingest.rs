@@ -0,0 +1,307 @@
+//! Ingestion of on-chain agent activity into the causal model.
+//!
+//! Self-reported events say what an agent claims it did; the chain says what
+//! its wallet actually signed. This module converts the agent's transactions
+//! into `CausalEvent`s (nonce from the transaction, sequence from log order)
+//! and merges them into a recorder, flagging every place where the two
+//! accounts contradict each other.
+//!
+//! Fetching requires the `onchain` feature; conversion and merging do not.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ethers::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::event::CausalEvent;
+
+/// Event type for a transaction that emitted no recognized logs.
+pub const ONCHAIN_TX_EVENT: &str = "onchain_tx";
+
+/// An agent transaction as observed on-chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnChainTx {
+    /// Transaction hash.
+    pub tx_hash: H256,
+    /// Sender nonce.
+    pub nonce: u64,
+    /// Block timestamp.
+    pub timestamp: u64,
+    /// Receipt status (false if reverted).
+    pub succeeded: bool,
+    /// Behavioral symbols of the emitted logs, in log-index order.
+    pub symbols: Vec<String>,
+}
+
+impl OnChainTx {
+    /// Converts the transaction into causal events.
+    ///
+    /// Each recognized log becomes one event with the symbol as its type and
+    /// its position in the transaction as `sequence_index`. A transaction with
+    /// no recognized logs yields a single `onchain_tx` event. The transaction
+    /// hash is kept as `task_id`. It is public, so the salt is zero and
+    /// ingesting the same transaction always yields the same leaves.
+    pub fn to_events(&self, agent: Address) -> Result<Vec<CausalEvent>> {
+        let result = if self.succeeded { "success" } else { "failure" };
+        let types: Vec<&str> = if self.symbols.is_empty() {
+            vec![ONCHAIN_TX_EVENT]
+        } else {
+            self.symbols.iter().map(String::as_str).collect()
+        };
+
+        types
+            .into_iter()
+            .enumerate()
+            .map(|(seq, event_type)| {
+                CausalEvent::builder()
+                    .agent_id(agent)
+                    .nonce(self.nonce)
+                    .sequence_index(seq as u32)
+                    .event_type(event_type)
+                    .task_id(format!("{:?}", self.tx_hash))
+                    .result(result)
+                    .timestamp(self.timestamp)
+                    .salt(H256::zero())
+                    .build()
+            })
+            .collect()
+    }
+}
+
+/// A disagreement between self-reported and on-chain history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Contradiction {
+    /// The agent reported success for a transaction that reverted.
+    ClaimedSuccessOnRevert {
+        /// Nonce of the reverted transaction
+        nonce: u64,
+        /// Hash of the reverted transaction
+        tx_hash: H256,
+    },
+    /// The agent skipped a transaction it sent while reporting later ones.
+    UnreportedTransaction {
+        /// Nonce of the omitted transaction
+        nonce: u64,
+        /// Hash of the omitted transaction
+        tx_hash: H256,
+    },
+    /// The agent reported a nonce that the scanned range shows was never used.
+    ReportedButAbsent {
+        /// Nonce with no matching transaction
+        nonce: u64,
+    },
+}
+
+/// Summary of an ingestion run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IngestReport {
+    /// Transactions observed on-chain.
+    pub transactions: usize,
+    /// Events added from chain data (nonces the agent had not reported).
+    pub events_added: usize,
+    /// Disagreements between the two accounts.
+    pub contradictions: Vec<Contradiction>,
+}
+
+/// Merges on-chain transactions into a self-reported history.
+///
+/// Self-reported events win for any nonce they cover, since they carry the
+/// agent's semantics; chain events fill the remaining nonces. Returns the
+/// merged history in causal order along with the report.
+pub(crate) fn merge_histories(
+    agent: Address,
+    reported: &[CausalEvent],
+    chain: &[OnChainTx],
+) -> Result<(Vec<CausalEvent>, IngestReport)> {
+    let mut report = IngestReport {
+        transactions: chain.len(),
+        ..Default::default()
+    };
+
+    let mut by_nonce: BTreeMap<u64, Vec<CausalEvent>> = BTreeMap::new();
+    for event in reported {
+        by_nonce.entry(event.nonce).or_default().push(event.clone());
+    }
+    let reported_nonces: BTreeSet<u64> = by_nonce.keys().copied().collect();
+    let max_reported = reported_nonces.iter().next_back().copied();
+
+    for tx in chain {
+        match by_nonce.get(&tx.nonce) {
+            Some(events) => {
+                let claims_success = events
+                    .iter()
+                    .any(|e| e.result == "success" || e.result == "profit");
+                if !tx.succeeded && claims_success {
+                    report
+                        .contradictions
+                        .push(Contradiction::ClaimedSuccessOnRevert {
+                            nonce: tx.nonce,
+                            tx_hash: tx.tx_hash,
+                        });
+                }
+            }
+            None => {
+                if max_reported.is_some_and(|max| tx.nonce < max) {
+                    report
+                        .contradictions
+                        .push(Contradiction::UnreportedTransaction {
+                            nonce: tx.nonce,
+                            tx_hash: tx.tx_hash,
+                        });
+                }
+                let events = tx.to_events(agent)?;
+                report.events_added += events.len();
+                by_nonce.insert(tx.nonce, events);
+            }
+        }
+    }
+
+    // Nonces are used in order, so every nonce between the first and last
+    // observed transaction must appear in the scanned range.
+    let chain_nonces: BTreeSet<u64> = chain.iter().map(|tx| tx.nonce).collect();
+    if let (Some(&lo), Some(&hi)) = (chain_nonces.first(), chain_nonces.last()) {
+        for nonce in reported_nonces.range(lo..=hi) {
+            if !chain_nonces.contains(nonce) {
+                report
+                    .contradictions
+                    .push(Contradiction::ReportedButAbsent { nonce: *nonce });
+            }
+        }
+    }
+
+    let merged = by_nonce.into_values().flatten().collect();
+    Ok((merged, report))
+}
+
+/// Fetches an agent's transactions in a block range.
+#[cfg(feature = "onchain")]
+pub(crate) async fn fetch_agent_transactions(
+    verifier: &sods_verifier::BlockVerifier,
+    agent: Address,
+    range: std::ops::RangeInclusive<u64>,
+) -> Result<Vec<OnChainTx>> {
+    let rpc = verifier.rpc_client();
+    let mut out = Vec::new();
+
+    for block in range {
+        let txs = rpc.fetch_block_transactions(block).await?;
+        let agent_txs: Vec<_> = txs.into_iter().filter(|tx| tx.from == agent).collect();
+        if agent_txs.is_empty() {
+            continue;
+        }
+
+        let timestamp = rpc.fetch_block_header(block).await?.timestamp;
+
+        for tx in agent_txs {
+            let receipt = rpc.fetch_transaction_receipt(tx.hash).await?;
+            let mut logs = receipt.logs.clone();
+            logs.sort_by_key(|l| l.log_index);
+
+            out.push(OnChainTx {
+                tx_hash: tx.hash,
+                nonce: tx.nonce.as_u64(),
+                timestamp,
+                succeeded: receipt.status.map_or(true, |s| s.as_u64() == 1),
+                symbols: logs
+                    .iter()
+                    .flat_map(|l| verifier.dictionary().parse_log_all(l))
+                    .map(|s| s.symbol)
+                    .collect(),
+            });
+        }
+    }
+
+    out.sort_by_key(|tx| tx.nonce);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent() -> Address {
+        Address::repeat_byte(0xAA)
+    }
+
+    fn reported(nonce: u64, result: &str) -> CausalEvent {
+        CausalEvent::builder()
+            .agent_id(agent())
+            .nonce(nonce)
+            .event_type("task_executed")
+            .result(result)
+            .build()
+            .unwrap()
+    }
+
+    fn chain_tx(nonce: u64, succeeded: bool, symbols: &[&str]) -> OnChainTx {
+        OnChainTx {
+            tx_hash: H256::from_low_u64_be(nonce + 1),
+            nonce,
+            timestamp: 1_700_000_000 + nonce,
+            succeeded,
+            symbols: symbols.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_to_events_sequences_logs() {
+        let events = chain_tx(3, true, &["Tf", "Sw"]).to_events(agent()).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[1].sequence_index, 1);
+        assert_eq!(events[1].event_type, "Sw");
+        assert_eq!(events[0].nonce, 3);
+
+        let bare = chain_tx(4, false, &[]).to_events(agent()).unwrap();
+        assert_eq!(bare[0].event_type, ONCHAIN_TX_EVENT);
+        assert_eq!(bare[0].result, "failure");
+    }
+
+    #[test]
+    fn test_merge_fills_and_flags() {
+        let self_reported = vec![reported(0, "success"), reported(2, "success")];
+        let chain = vec![
+            chain_tx(0, true, &["Tf"]),
+            chain_tx(1, true, &["Sw"]),
+            chain_tx(2, false, &[]),
+            chain_tx(3, true, &[]),
+        ];
+
+        let (merged, report) = merge_histories(agent(), &self_reported, &chain).unwrap();
+        let nonces: Vec<u64> = merged.iter().map(|e| e.nonce).collect();
+        assert_eq!(nonces, vec![0, 1, 2, 3]);
+        // Self-reported semantics are kept where present
+        assert_eq!(merged[0].event_type, "task_executed");
+        assert_eq!(report.events_added, 2);
+        assert_eq!(
+            report.contradictions,
+            vec![
+                Contradiction::UnreportedTransaction {
+                    nonce: 1,
+                    tx_hash: H256::from_low_u64_be(2)
+                },
+                Contradiction::ClaimedSuccessOnRevert {
+                    nonce: 2,
+                    tx_hash: H256::from_low_u64_be(3)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reported_but_absent() {
+        let self_reported = vec![
+            reported(0, "success"),
+            reported(1, "success"),
+            reported(2, "success"),
+        ];
+        let chain = vec![chain_tx(0, true, &[]), chain_tx(2, true, &[])];
+
+        let (_, report) = merge_histories(agent(), &self_reported, &chain).unwrap();
+        assert_eq!(
+            report.contradictions,
+            vec![Contradiction::ReportedButAbsent { nonce: 1 }]
+        );
+    }
+}
@@ -12,6 +12,10 @@
 //! - **Causal Ordering**: Events are strictly ordered by `(nonce, sequence_index)`
 //! - **Validation**: Comprehensive field validation for all events
 //! - **Multi-Agent**: In-memory recorder supports multiple agent histories
+//! - **On-chain Ingestion**: Merge wallet activity with self-reports, flagging contradictions
+//! - **Agent Directory**: Signed profiles with capabilities and committed history roots
+//! - **Pseudonymous Agents**: Salted pseudonyms with signed same-agent link proofs
+//! - **SLA Attestation**: Signed pass/fail verdicts over rolling task windows
 //! - **Minimal**: Zero dependencies beyond `ethers` and `serde`
 //!
 //! ## Quick Start
@@ -53,21 +57,37 @@
 //!
 //! This ensures deterministic reconstruction of agent behavior history.
 
+#![warn(clippy::unwrap_used)]
+
+pub mod directory;
 pub mod error;
 pub mod event;
+pub mod history;
+pub mod ingest;
 pub mod pattern;
 pub mod proof;
+pub mod pseudonym;
 pub mod recorder;
 pub mod reputation;
+pub mod sla;
 pub mod tree;
 pub mod validation;
 
 // Re-export main types for convenience
+pub use directory::{AgentDirectory, AgentProfile, ProfileUpdate};
 pub use error::{CausalError, Result};
-pub use event::{CausalEvent, CausalEventBuilder, VALID_RESULTS};
+pub use event::{
+    payload_commitment, CausalEvent, CausalEventBuilder, PayloadOpening, VALID_RESULTS,
+};
+pub use history::{compare_histories, ConflictEvidence, HistoryDiff, HistoryRelation};
+pub use ingest::{Contradiction, IngestReport, OnChainTx};
 pub use pattern::{generate_behavioral_proof, AgentBehaviorPattern};
-pub use proof::{CausalBehavioralProof, CausalProof};
+pub use proof::{CausalBehavioralProof, CausalProof, FreshnessPolicy};
+pub use pseudonym::{derive_pseudonym, PseudonymLinkProof, PseudonymousIdentity};
 pub use recorder::CausalEventRecorder;
 pub use reputation::{ReputationClaim, ReputationFeedback};
+pub use sla::{
+    ContinuousAttestor, SlaAttestation, SlaDefinition, SlaEvaluation, SlaStatus, SlaVerdict,
+};
 pub use tree::CausalMerkleTree;
 pub use validation::{ValidationHandler, ValidationRequest, ValidationResponse};
@@ -70,6 +70,10 @@ impl AgentBehaviorPattern {
 /// * `tree` - The full causal Merkle tree for the agent.
 /// * `pattern` - The behavioral pattern to prove.
 /// * `now` - Current Unix timestamp for time-window filtering.
+///
+/// # Errors
+/// Returns `CausalError::PatternNotMatched` if no event matches a pattern
+/// that requires some.
 pub fn generate_behavioral_proof(
     tree: &CausalMerkleTree,
     pattern: &AgentBehaviorPattern,
@@ -79,9 +83,9 @@ pub fn generate_behavioral_proof(
     let matched_events = pattern.matches(events, now);
 
     if matched_events.is_empty() && pattern.min_count > 0 {
-        return Err(CausalError::SequenceGap {
-            expected: pattern.min_count,
-            actual: 0,
+        return Err(CausalError::PatternNotMatched {
+            required: pattern.min_count,
+            matched: 0,
         });
     }
 
@@ -102,5 +106,10 @@ pub fn generate_behavioral_proof(
         matched_events,
         event_proofs,
         agent_root: tree.root,
+        generated_at: now,
+        generated_at_root_height: events.len() as u64,
+        valid_until: None,
+        signer: None,
+        signature: None,
     })
 }
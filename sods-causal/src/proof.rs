@@ -3,8 +3,10 @@
 //! This module provides the `CausalProof` which represents a cryptographic proof
 //! that a specific event exists in a `CausalMerkleTree`.
 
+use crate::error::{CausalError, Result};
 use crate::event::CausalEvent;
-use ethers::types::H256;
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, RecoveryMessage, Signature, H256};
 use serde::{Deserialize, Serialize};
 use tiny_keccak::{Hasher, Keccak};
 
@@ -70,6 +72,61 @@ pub struct CausalBehavioralProof {
     pub event_proofs: Vec<CausalProof>,
     /// The root hash of the agent's full history.
     pub agent_root: ethers::types::H256,
+    /// Timestamp the proof was generated at.
+    #[serde(default)]
+    pub generated_at: u64,
+    /// Number of events in the history `agent_root` commits to.
+    #[serde(default)]
+    pub generated_at_root_height: u64,
+    /// Timestamp after which the proof must be rejected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<u64>,
+    /// Address that signed the freshness fields.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<Address>,
+    /// Signature over `digest()`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Signature>,
+}
+
+/// Verifier-side limits on how stale a behavioral proof may be.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreshnessPolicy {
+    /// Maximum age in seconds, measured from `generated_at`.
+    pub max_age: Option<u64>,
+    /// Minimum history height the proof must cover.
+    ///
+    /// Set this to the agent's latest known history length (e.g. from the
+    /// `AgentDirectory`) to reject proofs over an older prefix.
+    pub min_root_height: Option<u64>,
+    /// Key the proof must be signed by (defaults to the proven agent).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<Address>,
+}
+
+impl FreshnessPolicy {
+    /// A policy that accepts proofs of any age.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reject proofs older than `secs` (Builder pattern).
+    pub fn with_max_age(mut self, secs: u64) -> Self {
+        self.max_age = Some(secs);
+        self
+    }
+
+    /// Reject proofs covering fewer than `height` events (Builder pattern).
+    pub fn with_min_root_height(mut self, height: u64) -> Self {
+        self.min_root_height = Some(height);
+        self
+    }
+
+    /// Require the proof to be signed by `signer` (Builder pattern).
+    pub fn with_signer(mut self, signer: Address) -> Self {
+        self.signer = Some(signer);
+        self
+    }
 }
 
 impl CausalBehavioralProof {
@@ -78,6 +135,14 @@ impl CausalBehavioralProof {
     /// 1. Verifies each individual event proof against agent_root.
     /// 2. Verifies that the matched events satisfy the pattern.
     pub fn verify(&self, now: u64) -> bool {
+        if self.valid_until.is_some_and(|until| now > until) {
+            return false;
+        }
+        // A signature that no longer matches means the fields were edited
+        if self.signature.is_some() && self.recovered_signer().is_none() {
+            return false;
+        }
+
         // Ensure we have correct number of proofs
         if self.matched_events.len() != self.event_proofs.len() {
             return false;
@@ -108,4 +173,205 @@ impl CausalBehavioralProof {
         matches.len() == self.matched_events.len()
             && matches.len() >= self.pattern.min_count as usize
     }
+
+    /// Limits the proof's lifetime to `secs` after generation (Builder pattern).
+    ///
+    /// Call before `sign`; changing the validity afterwards voids the signature.
+    pub fn with_validity(mut self, secs: u64) -> Self {
+        self.valid_until = Some(self.generated_at.saturating_add(secs));
+        self
+    }
+
+    /// The digest that gets signed.
+    ///
+    /// Formula: `keccak256(agent_root || generated_at || generated_at_root_height || has_valid_until || valid_until)`
+    pub fn digest(&self) -> H256 {
+        let mut hasher = Keccak::v256();
+        hasher.update(self.agent_root.as_bytes());
+        hasher.update(&self.generated_at.to_be_bytes());
+        hasher.update(&self.generated_at_root_height.to_be_bytes());
+        hasher.update(&[self.valid_until.is_some() as u8]);
+        hasher.update(&self.valid_until.unwrap_or(0).to_be_bytes());
+        let mut output = [0u8; 32];
+        hasher.finalize(&mut output);
+        H256::from(output)
+    }
+
+    /// Signs the root and freshness fields with `key`.
+    ///
+    /// Without a signature, `generated_at`, `generated_at_root_height` and
+    /// `valid_until` can be rewritten by whoever relays the proof.
+    ///
+    /// # Errors
+    /// Returns `CausalError::InternalError` if signing fails.
+    pub fn sign(mut self, key: &LocalWallet) -> Result<Self> {
+        let signature = key
+            .sign_hash(self.digest())
+            .map_err(|e| CausalError::InternalError(format!("Signing failed: {}", e)))?;
+        self.signer = Some(key.address());
+        self.signature = Some(signature);
+        Ok(self)
+    }
+
+    /// The claimed signer, if the signature over `digest()` recovers to it.
+    pub fn recovered_signer(&self) -> Option<Address> {
+        let signature = self.signature.as_ref()?;
+        let recovered = signature
+            .recover(RecoveryMessage::Hash(self.digest()))
+            .ok()?;
+        (Some(recovered) == self.signer).then_some(recovered)
+    }
+
+    /// Whether the proof is signed by the key `policy` expects.
+    ///
+    /// Without `FreshnessPolicy::signer`, the signer must be the agent whose
+    /// events are proven.
+    pub fn is_signed_by(&self, policy: &FreshnessPolicy) -> bool {
+        let Some(signer) = self.recovered_signer() else {
+            return false;
+        };
+        match policy.signer {
+            Some(expected) => signer == expected,
+            None => self.matched_events.iter().all(|e| e.agent_id == signer),
+        }
+    }
+
+    /// Checks the proof against a freshness policy.
+    ///
+    /// The claimed root height must also match the depth of every event
+    /// proof, so it cannot be inflated past the next power of two.
+    pub fn is_fresh(&self, now: u64, policy: &FreshnessPolicy) -> bool {
+        if self.generated_at > now {
+            return false;
+        }
+        if policy
+            .max_age
+            .is_some_and(|max| now - self.generated_at > max)
+        {
+            return false;
+        }
+        if policy
+            .min_root_height
+            .is_some_and(|min| self.generated_at_root_height < min)
+        {
+            return false;
+        }
+
+        let height = self.generated_at_root_height;
+        height == 0
+            || self
+                .event_proofs
+                .iter()
+                .all(|p| p.merkle_path.len() == tree_depth(height))
+    }
+
+    /// Verifies the proof and checks it against a freshness policy.
+    ///
+    /// The proof must be signed (see `is_signed_by`), since unsigned
+    /// freshness fields prove nothing.
+    pub fn verify_with_policy(&self, now: u64, policy: &FreshnessPolicy) -> bool {
+        self.is_signed_by(policy) && self.is_fresh(now, policy) && self.verify(now)
+    }
+}
+
+/// Number of Merkle path entries for a tree of `leaves` events.
+fn tree_depth(leaves: u64) -> usize {
+    leaves.next_power_of_two().trailing_zeros() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pattern::{generate_behavioral_proof, AgentBehaviorPattern};
+    use crate::tree::CausalMerkleTree;
+
+    fn agent_key() -> LocalWallet {
+        "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap()
+    }
+
+    fn proof(events: u64, now: u64) -> CausalBehavioralProof {
+        let history = (0..events)
+            .map(|nonce| {
+                CausalEvent::builder()
+                    .agent_id(agent_key().address())
+                    .nonce(nonce)
+                    .event_type("task_executed")
+                    .result("success")
+                    .timestamp(now)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let tree = CausalMerkleTree::new(history).unwrap();
+        let pattern = AgentBehaviorPattern {
+            event_type: "task_executed".to_string(),
+            result_filter: "success".to_string(),
+            min_count: 1,
+            max_count: None,
+            time_window: None,
+        };
+        generate_behavioral_proof(&tree, &pattern, now).unwrap()
+    }
+
+    #[test]
+    fn test_valid_until_enforced() {
+        let proof = proof(3, 1000).with_validity(60);
+        assert!(proof.verify(1060));
+        assert!(!proof.verify(1061));
+    }
+
+    #[test]
+    fn test_freshness_policy() {
+        let proof = proof(5, 1000).sign(&agent_key()).unwrap();
+        assert_eq!(proof.generated_at_root_height, 5);
+
+        let policy = FreshnessPolicy::new().with_max_age(100);
+        assert!(proof.verify_with_policy(1100, &policy));
+        assert!(!proof.verify_with_policy(1101, &policy));
+
+        // The agent has since recorded more events
+        assert!(proof.verify_with_policy(1000, &FreshnessPolicy::new().with_min_root_height(5)));
+        assert!(!proof.verify_with_policy(1000, &FreshnessPolicy::new().with_min_root_height(6)));
+    }
+
+    #[test]
+    fn test_inflated_height_rejected() {
+        let mut proof = proof(3, 1000);
+        proof.generated_at_root_height = 64;
+        assert!(!proof.is_fresh(1000, &FreshnessPolicy::new()));
+    }
+
+    #[test]
+    fn test_freshness_fields_are_signed() {
+        let key = agent_key();
+        let signed = proof(4, 1000).with_validity(60).sign(&key).unwrap();
+        let policy = FreshnessPolicy::new();
+        assert!(signed.verify_with_policy(1000, &policy));
+
+        let mut later = signed.clone();
+        later.generated_at = 1050;
+        let mut taller = signed.clone();
+        taller.generated_at_root_height = 3;
+        let mut extended = signed.clone();
+        extended.valid_until = Some(2000);
+        let mut unbounded = signed.clone();
+        unbounded.valid_until = None;
+        for tampered in [later, taller, extended, unbounded] {
+            assert!(!tampered.verify(1000));
+            assert!(!tampered.verify_with_policy(1000, &policy));
+        }
+
+        // Unsigned, or re-signed by a relay, is not enough for a policy check
+        assert!(!proof(4, 1000).verify_with_policy(1000, &policy));
+        let relay: LocalWallet =
+            "0x0123456789012345678901234567890123456789012345678901234567890123"
+                .parse()
+                .unwrap();
+        let resigned = proof(4, 1000).sign(&relay).unwrap();
+        assert!(resigned.verify(1000));
+        assert!(!resigned.verify_with_policy(1000, &policy));
+        assert!(resigned.verify_with_policy(1000, &policy.with_signer(relay.address())));
+    }
 }
@@ -0,0 +1,295 @@
+//! Pseudonymous agent identifiers.
+//!
+//! Some marketplaces refuse to expose operator addresses. A pseudonymous
+//! agent records its events under `agent_id = pseudonym`, where
+//!
+//! ```text
+//! pseudonym = keccak256("SODS-PSEUDONYM" || public_key || salt)[12..]
+//! ```
+//!
+//! and `public_key` belongs to a signing key derived from the operator key
+//! and the salt. The derived key cannot be traced back to the operator, yet
+//! it lets the agent prove that two events belong to the same pseudonym with
+//! a `PseudonymLinkProof`. Different salts give unlinkable pseudonyms.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sods_causal::{CausalEvent, CausalMerkleTree, PseudonymousIdentity};
+//! use ethers::signers::LocalWallet;
+//! use ethers::types::H256;
+//!
+//! let operator: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+//!     .parse()
+//!     .unwrap();
+//! let identity = PseudonymousIdentity::derive(&operator, H256::repeat_byte(1)).unwrap();
+//!
+//! let events = (0..2)
+//!     .map(|nonce| {
+//!         CausalEvent::builder()
+//!             .agent_id(identity.pseudonym())
+//!             .nonce(nonce)
+//!             .result("success")
+//!             .build()
+//!             .unwrap()
+//!     })
+//!     .collect();
+//! let tree = CausalMerkleTree::new(events).unwrap();
+//!
+//! let link = identity.link(&tree, 0, 1).unwrap();
+//! assert!(link.verify());
+//! ```
+
+use ethers::core::k256::ecdsa::VerifyingKey;
+use ethers::core::utils::public_key_to_address;
+use ethers::signers::LocalWallet;
+use ethers::types::{Address, Bytes, RecoveryMessage, Signature, H256};
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::error::{CausalError, Result};
+use crate::proof::CausalProof;
+use crate::tree::CausalMerkleTree;
+
+/// Domain tag for pseudonym commitments.
+const PSEUDONYM_DOMAIN: &[u8] = b"SODS-PSEUDONYM";
+
+/// Domain tag for pseudonymous key derivation.
+const PSEUDONYM_KEY_DOMAIN: &[u8] = b"SODS-PSEUDONYM-KEY";
+
+/// Computes the pseudonym committed to by `public_key` and `salt`.
+pub fn derive_pseudonym(public_key: &[u8], salt: H256) -> Address {
+    let mut hasher = Keccak::v256();
+    hasher.update(PSEUDONYM_DOMAIN);
+    hasher.update(public_key);
+    hasher.update(salt.as_bytes());
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    Address::from_slice(&output[12..])
+}
+
+/// Operator-side handle for one pseudonym.
+///
+/// Holds the derived signing key; never serialize or share it.
+#[derive(Debug, Clone)]
+pub struct PseudonymousIdentity {
+    key: LocalWallet,
+    salt: H256,
+}
+
+impl PseudonymousIdentity {
+    /// Derives the pseudonymous key for `salt` from the operator key.
+    ///
+    /// The same operator key and salt always yield the same pseudonym.
+    ///
+    /// # Errors
+    /// Returns `CausalError` in the negligible case that the derived scalar
+    /// is not a valid secp256k1 key.
+    pub fn derive(operator: &LocalWallet, salt: H256) -> Result<Self> {
+        let mut hasher = Keccak::v256();
+        hasher.update(PSEUDONYM_KEY_DOMAIN);
+        hasher.update(&operator.signer().to_bytes());
+        hasher.update(salt.as_bytes());
+        let mut secret = [0u8; 32];
+        hasher.finalize(&mut secret);
+
+        let key = LocalWallet::from_bytes(&secret)
+            .map_err(|e| CausalError::InternalError(format!("Key derivation failed: {}", e)))?;
+        Ok(Self { key, salt })
+    }
+
+    /// The pseudonym to use as `agent_id`.
+    pub fn pseudonym(&self) -> Address {
+        derive_pseudonym(&self.public_key(), self.salt)
+    }
+
+    /// SEC1-encoded public key of the derived key.
+    pub fn public_key(&self) -> Bytes {
+        let point = self.key.signer().verifying_key().to_encoded_point(true);
+        Bytes::from(point.as_bytes().to_vec())
+    }
+
+    /// Salt the pseudonym was derived with.
+    pub fn salt(&self) -> H256 {
+        self.salt
+    }
+
+    /// Proves that the events at indices `a` and `b` of `tree` belong to
+    /// this pseudonym.
+    ///
+    /// # Errors
+    /// Returns `CausalError` if an index is out of range, the indices are
+    /// equal, an event is recorded under another agent, or signing fails.
+    pub fn link(&self, tree: &CausalMerkleTree, a: usize, b: usize) -> Result<PseudonymLinkProof> {
+        let len = tree.events().len();
+        if a >= len || b >= len || a == b {
+            return Err(CausalError::InternalError(format!(
+                "Invalid event indices {} and {} for history of {} events",
+                a, b, len
+            )));
+        }
+
+        let pseudonym = self.pseudonym();
+        if tree.events()[a].agent_id != pseudonym || tree.events()[b].agent_id != pseudonym {
+            return Err(CausalError::InvalidAgentAddress(format!(
+                "Event not recorded under pseudonym {:?}",
+                pseudonym
+            )));
+        }
+
+        let proof_a = tree.generate_proof(a);
+        let proof_b = tree.generate_proof(b);
+        let signature = self
+            .key
+            .sign_hash(link_digest(pseudonym, &proof_a, &proof_b))
+            .map_err(|e| CausalError::InternalError(format!("Signing failed: {}", e)))?;
+
+        Ok(PseudonymLinkProof {
+            pseudonym,
+            public_key: self.public_key(),
+            salt: self.salt,
+            proof_a,
+            proof_b,
+            signature,
+        })
+    }
+}
+
+/// Proof that two events were recorded by the holder of one pseudonym.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PseudonymLinkProof {
+    /// The pseudonym both events are recorded under.
+    pub pseudonym: Address,
+    /// Public key of the pseudonymous signing key.
+    pub public_key: Bytes,
+    /// Salt opening the pseudonym commitment.
+    pub salt: H256,
+    /// Inclusion proof of the first event.
+    pub proof_a: CausalProof,
+    /// Inclusion proof of the second event.
+    pub proof_b: CausalProof,
+    /// Signature by the pseudonymous key over `digest()`.
+    pub signature: Signature,
+}
+
+impl PseudonymLinkProof {
+    /// The digest that gets signed.
+    ///
+    /// Formula: `keccak256(pseudonym || root || RLP(event_a) || RLP(event_b))`
+    pub fn digest(&self) -> H256 {
+        link_digest(self.pseudonym, &self.proof_a, &self.proof_b)
+    }
+
+    /// Verifies the link.
+    ///
+    /// The key and salt must open the pseudonym, both events must be distinct
+    /// members of the same history under that pseudonym, and the pseudonymous
+    /// key must have signed the pair.
+    pub fn verify(&self) -> bool {
+        if derive_pseudonym(&self.public_key, self.salt) != self.pseudonym {
+            return false;
+        }
+
+        let a = &self.proof_a;
+        let b = &self.proof_b;
+        if a.event.agent_id != self.pseudonym
+            || b.event.agent_id != self.pseudonym
+            || a.root != b.root
+            || a.event == b.event
+            || !a.verify()
+            || !b.verify()
+        {
+            return false;
+        }
+
+        let Ok(key) = VerifyingKey::from_sec1_bytes(&self.public_key) else {
+            return false;
+        };
+        self.signature
+            .recover(RecoveryMessage::Hash(self.digest()))
+            .map(|signer| signer == public_key_to_address(&key))
+            .unwrap_or(false)
+    }
+}
+
+fn link_digest(pseudonym: Address, a: &CausalProof, b: &CausalProof) -> H256 {
+    let mut hasher = Keccak::v256();
+    hasher.update(pseudonym.as_bytes());
+    hasher.update(a.root.as_bytes());
+    hasher.update(&a.event.rlp_encode());
+    hasher.update(&b.event.rlp_encode());
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    H256::from(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::CausalEvent;
+    use ethers::signers::Signer;
+
+    fn operator(byte: u8) -> LocalWallet {
+        LocalWallet::from_bytes(&[byte; 32]).unwrap()
+    }
+
+    fn history(agent: Address, len: u64) -> CausalMerkleTree {
+        let events = (0..len)
+            .map(|nonce| {
+                CausalEvent::builder()
+                    .agent_id(agent)
+                    .nonce(nonce)
+                    .result("success")
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        CausalMerkleTree::new(events).unwrap()
+    }
+
+    #[test]
+    fn test_derivation_is_deterministic_and_unlinkable() {
+        let op = operator(1);
+        let a = PseudonymousIdentity::derive(&op, H256::repeat_byte(1)).unwrap();
+        let again = PseudonymousIdentity::derive(&op, H256::repeat_byte(1)).unwrap();
+        let b = PseudonymousIdentity::derive(&op, H256::repeat_byte(2)).unwrap();
+
+        assert_eq!(a.pseudonym(), again.pseudonym());
+        assert_ne!(a.pseudonym(), b.pseudonym());
+        assert_ne!(a.pseudonym(), op.address());
+        assert_ne!(a.key.address(), op.address());
+    }
+
+    #[test]
+    fn test_link_proof_roundtrip() {
+        let identity = PseudonymousIdentity::derive(&operator(1), H256::repeat_byte(9)).unwrap();
+        let tree = history(identity.pseudonym(), 4);
+
+        let link = identity.link(&tree, 0, 3).unwrap();
+        assert!(link.verify());
+
+        let json = serde_json::to_string(&link).unwrap();
+        let decoded: PseudonymLinkProof = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify());
+    }
+
+    #[test]
+    fn test_link_rejects_foreign_key_and_events() {
+        let identity = PseudonymousIdentity::derive(&operator(1), H256::repeat_byte(9)).unwrap();
+        let other = PseudonymousIdentity::derive(&operator(2), H256::repeat_byte(9)).unwrap();
+        let tree = history(identity.pseudonym(), 2);
+
+        // Only the holder can link its events
+        assert!(other.link(&tree, 0, 1).is_err());
+
+        // Swapping in another key breaks the commitment opening
+        let mut link = identity.link(&tree, 0, 1).unwrap();
+        link.public_key = other.public_key();
+        assert!(!link.verify());
+
+        // Proofs from a different history break the shared root
+        let mut link = identity.link(&tree, 0, 1).unwrap();
+        link.proof_b = history(identity.pseudonym(), 3).generate_proof(2);
+        assert!(!link.verify());
+    }
+}
@@ -34,7 +34,8 @@ use std::collections::HashMap;
 use ethers::types::Address;
 
 use crate::error::{CausalError, Result};
-use crate::event::CausalEvent;
+use crate::event::{CausalEvent, PayloadOpening};
+use crate::ingest::{merge_histories, IngestReport, OnChainTx};
 use crate::tree::CausalMerkleTree;
 
 /// In-memory recorder for causal events across multiple agents.
@@ -87,6 +88,11 @@ impl CausalEventRecorder {
         self.events.get(agent_id)
     }
 
+    /// Returns the addresses of all agents with recorded events.
+    pub fn agent_ids(&self) -> Vec<Address> {
+        self.events.keys().copied().collect()
+    }
+
     /// Returns the number of agents with recorded events.
     pub fn agent_count(&self) -> usize {
         self.events.len()
@@ -97,6 +103,96 @@ impl CausalEventRecorder {
         self.events.values().map(|v| v.len()).sum()
     }
 
+    /// Redacts the payload of a recorded event, keeping a commitment tombstone.
+    ///
+    /// The event's leaf hash, and therefore the agent's Merkle root, is
+    /// unchanged. Returns the opening the agent must keep to disclose the
+    /// payload later.
+    ///
+    /// # Errors
+    /// Returns `CausalError::EventNotFound` if no such event is recorded,
+    /// or `CausalError::AlreadyRedacted` if it was redacted before.
+    pub fn redact_event(
+        &mut self,
+        agent_id: &Address,
+        nonce: u64,
+        sequence_index: u32,
+    ) -> Result<PayloadOpening> {
+        let event = self
+            .events
+            .get_mut(agent_id)
+            .and_then(|events| {
+                events
+                    .iter_mut()
+                    .find(|e| e.nonce == nonce && e.sequence_index == sequence_index)
+            })
+            .ok_or(CausalError::EventNotFound {
+                nonce,
+                sequence_index,
+            })?;
+
+        let (redacted, opening) = event.redact();
+        let opening = opening.ok_or(CausalError::AlreadyRedacted {
+            nonce,
+            sequence_index,
+        })?;
+        *event = redacted;
+        Ok(opening)
+    }
+
+    /// Merges on-chain transactions into an agent's self-reported history.
+    ///
+    /// Reported events are kept for every nonce they cover; chain-derived
+    /// events fill the nonces the agent did not report. The merged history
+    /// may start at any nonce, e.g. when only a recent block range was
+    /// ingested, but must be causally contiguous from there, otherwise
+    /// nothing is changed and the ordering error is returned.
+    pub fn merge_chain_events(
+        &mut self,
+        agent_id: Address,
+        chain: &[OnChainTx],
+    ) -> Result<IngestReport> {
+        let reported = self.events.get(&agent_id).cloned().unwrap_or_default();
+        let (merged, report) = merge_histories(agent_id, &reported, chain)?;
+
+        let mut scratch = CausalEventRecorder::new();
+        let mut merged = merged.into_iter();
+        if let Some(first) = merged.next() {
+            first.validate()?;
+            if first.sequence_index != 0 {
+                return Err(CausalError::SequenceGap {
+                    expected: 0,
+                    actual: first.sequence_index,
+                });
+            }
+            scratch.events.insert(agent_id, vec![first]);
+        }
+        for event in merged {
+            scratch.record_event(event)?;
+        }
+        if let Some(events) = scratch.events.remove(&agent_id) {
+            self.events.insert(agent_id, events);
+        }
+
+        Ok(report)
+    }
+
+    /// Pulls an agent's transactions in `range` through `verifier` and merges
+    /// them into the recorded history (see `merge_chain_events`).
+    ///
+    /// Nonces come from the transactions, sequence indices from the order of
+    /// recognized logs within each receipt.
+    #[cfg(feature = "onchain")]
+    pub async fn ingest_from_chain(
+        &mut self,
+        verifier: &sods_verifier::BlockVerifier,
+        agent_id: Address,
+        range: std::ops::RangeInclusive<u64>,
+    ) -> Result<IngestReport> {
+        let chain = crate::ingest::fetch_agent_transactions(verifier, agent_id, range).await?;
+        self.merge_chain_events(agent_id, &chain)
+    }
+
     /// Clears all recorded events.
     pub fn clear(&mut self) {
         self.events.clear();
@@ -110,9 +206,9 @@ impl CausalEventRecorder {
     ///   a) Continue the same nonce with sequence_index incremented by 1
     ///   b) Start a new nonce (incremented by 1) with sequence_index=0
     fn ensure_causal_ordering(&self, event: &CausalEvent) -> Result<()> {
-        let agent_events = match self.events.get(&event.agent_id) {
-            Some(events) if !events.is_empty() => events,
-            _ => {
+        let last_event = match self.events.get(&event.agent_id).and_then(|e| e.last()) {
+            Some(last) => last,
+            None => {
                 // First event for this agent
                 if event.nonce != 0 {
                     return Err(CausalError::NonceGap {
@@ -130,8 +226,6 @@ impl CausalEventRecorder {
             }
         };
 
-        let last_event = agent_events.last().unwrap();
-
         if event.nonce == last_event.nonce {
             // Same transaction: sequence_index must be contiguous
             let expected_seq = last_event.sequence_index + 1;
@@ -163,11 +257,12 @@ impl CausalEventRecorder {
     /// Builds a `CausalMerkleTree` for the specified agent.
     ///
     /// # Errors
-    /// Returns `CausalError` if the agent has no events.
+    /// Returns `CausalError::AgentNotFound` if the agent has no events.
     pub fn build_merkle_tree(&self, agent_id: &Address) -> Result<CausalMerkleTree> {
-        let events = self.events.get(agent_id).ok_or_else(|| {
-            CausalError::InvalidAgentAddress(format!("No events found for agent {}", agent_id))
-        })?;
+        let events = self
+            .events
+            .get(agent_id)
+            .ok_or(CausalError::AgentNotFound(*agent_id))?;
 
         CausalMerkleTree::new(events.clone())
     }
@@ -283,6 +378,43 @@ mod tests {
         assert_eq!(recorder.total_events(), 2);
     }
 
+    #[test]
+    fn test_redact_event_keeps_root() {
+        let mut recorder = CausalEventRecorder::new();
+        let agent = test_address();
+        let event = CausalEvent::builder()
+            .agent_id(agent)
+            .task_id("private")
+            .salt(ethers::types::H256::repeat_byte(3))
+            .build()
+            .unwrap();
+        recorder.record_event(event).unwrap();
+        recorder.record_event(create_event(agent, 0, 1)).unwrap();
+
+        let root_before = recorder.build_merkle_tree(&agent).unwrap().root;
+        let opening = recorder.redact_event(&agent, 0, 0).unwrap();
+        let tree = recorder.build_merkle_tree(&agent).unwrap();
+
+        assert_eq!(tree.root, root_before);
+        assert_eq!(opening.task_id.as_deref(), Some("private"));
+        assert!(tree.events()[0].is_redacted());
+        assert!(tree.generate_proof(0).verify());
+        assert!(opening.opens(&tree.events()[0]));
+
+        assert!(matches!(
+            recorder.redact_event(&agent, 0, 0),
+            Err(CausalError::AlreadyRedacted { .. })
+        ));
+        assert_eq!(
+            recorder.build_merkle_tree(&agent).unwrap().root,
+            root_before
+        );
+        assert!(matches!(
+            recorder.redact_event(&agent, 5, 0),
+            Err(CausalError::EventNotFound { .. })
+        ));
+    }
+
     #[test]
     fn test_build_merkle_tree() {
         let mut recorder = CausalEventRecorder::new();
@@ -293,4 +425,38 @@ mod tests {
         let tree = recorder.build_merkle_tree(&agent).unwrap();
         assert_eq!(tree.events().len(), 2);
     }
+
+    #[test]
+    fn test_merge_chain_events() {
+        let mut recorder = CausalEventRecorder::new();
+        let agent = test_address();
+        recorder.record_event(create_event(agent, 0, 0)).unwrap();
+
+        let tx = |nonce: u64| OnChainTx {
+            tx_hash: ethers::types::H256::from_low_u64_be(nonce),
+            nonce,
+            timestamp: 1700000000,
+            succeeded: true,
+            symbols: vec![],
+        };
+
+        // A gap at nonce 1 leaves the history untouched
+        assert!(recorder.merge_chain_events(agent, &[tx(2)]).is_err());
+        assert_eq!(recorder.get_agent_events(&agent).unwrap().len(), 1);
+
+        let report = recorder
+            .merge_chain_events(agent, &[tx(0), tx(1), tx(2)])
+            .unwrap();
+        assert_eq!(report.events_added, 2);
+        assert!(report.contradictions.is_empty());
+        assert_eq!(recorder.get_agent_events(&agent).unwrap().len(), 3);
+
+        // Ingesting a recent range starts the history at its first nonce
+        let mut recent = CausalEventRecorder::new();
+        let report = recent.merge_chain_events(agent, &[tx(57), tx(58)]).unwrap();
+        assert_eq!(report.events_added, 2);
+        recent.record_event(create_event(agent, 59, 0)).unwrap();
+        assert!(recent.merge_chain_events(agent, &[tx(61)]).is_err());
+        assert_eq!(recent.get_agent_events(&agent).unwrap().len(), 3);
+    }
 }
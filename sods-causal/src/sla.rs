@@ -0,0 +1,523 @@
+//! Behavioral SLA definitions and continuous attestation.
+//!
+//! An `SlaDefinition` states what an agent has promised (e.g. "≥95% success
+//! over the last 1000 tasks, never more than 3 losses in a row"). The
+//! `ContinuousAttestor` re-evaluates the SLA as each event is recorded and
+//! emits a signed `SlaAttestation`, backed by Merkle proofs against the
+//! agent's causal history, whenever the verdict changes.
+//!
+//! # Example
+//!
+//! ```rust
+//! use sods_causal::{CausalEvent, ContinuousAttestor, SlaDefinition};
+//! use ethers::signers::{LocalWallet, Signer};
+//! use ethers::types::Address;
+//!
+//! let agent: Address = "0x1234567890123456789012345678901234567890".parse().unwrap();
+//! let signer: LocalWallet = "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+//!     .parse()
+//!     .unwrap();
+//!
+//! let sla = SlaDefinition::new("uptime", 3).with_min_success_bps(9_500);
+//! let mut attestor = ContinuousAttestor::new(sla, agent, signer);
+//!
+//! let mut last = None;
+//! for nonce in 0..3 {
+//!     let event = CausalEvent::builder()
+//!         .agent_id(agent)
+//!         .nonce(nonce)
+//!         .event_type("task_executed")
+//!         .result("success")
+//!         .build()
+//!         .unwrap();
+//!     last = attestor.record_event(event).unwrap().or(last);
+//! }
+//!
+//! let attestation = last.unwrap();
+//! assert!(attestation.passed());
+//! assert!(attestation.verify(attestor.sla()));
+//! ```
+
+use ethers::signers::{LocalWallet, Signer};
+use ethers::types::{Address, RecoveryMessage, Signature, H256};
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::error::{CausalError, Result};
+use crate::event::CausalEvent;
+use crate::proof::CausalProof;
+use crate::recorder::CausalEventRecorder;
+
+/// Results counted as a successful task.
+pub const SUCCESS_RESULTS: &[&str] = &["success", "profit"];
+
+/// A behavioral service-level agreement over an agent's recent tasks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlaDefinition {
+    /// Human-readable SLA name.
+    pub name: String,
+    /// Only events of this type count as tasks (all events if `None`).
+    pub event_type: Option<String>,
+    /// Number of most recent tasks the SLA is evaluated over.
+    pub window: usize,
+    /// Minimum success rate in basis points (9500 = 95%).
+    pub min_success_bps: u32,
+    /// Result value whose consecutive runs are limited (e.g. "loss").
+    pub streak_result: Option<String>,
+    /// Maximum allowed run length of `streak_result`.
+    pub max_streak: u32,
+}
+
+impl SlaDefinition {
+    /// Create an SLA over a rolling window of tasks with no thresholds set.
+    pub fn new(name: impl Into<String>, window: usize) -> Self {
+        Self {
+            name: name.into(),
+            event_type: None,
+            window,
+            min_success_bps: 0,
+            streak_result: None,
+            max_streak: 0,
+        }
+    }
+
+    /// Restrict the SLA to one event type (Builder pattern).
+    pub fn with_event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event_type = Some(event_type.into());
+        self
+    }
+
+    /// Require a minimum success rate in basis points (Builder pattern).
+    pub fn with_min_success_bps(mut self, bps: u32) -> Self {
+        self.min_success_bps = bps;
+        self
+    }
+
+    /// Forbid runs of `result` longer than `max` (Builder pattern).
+    pub fn with_max_streak(mut self, result: impl Into<String>, max: u32) -> Self {
+        self.streak_result = Some(result.into());
+        self.max_streak = max;
+        self
+    }
+
+    /// Stable identifier of this definition (Keccak256 of its JSON encoding).
+    pub fn id(&self) -> H256 {
+        let bytes = serde_json::to_vec(self).unwrap_or_default();
+        H256::from(keccak256(&bytes))
+    }
+
+    /// Evaluate the SLA over an agent's causally ordered history.
+    pub fn evaluate(&self, events: &[CausalEvent]) -> SlaEvaluation {
+        let tasks: Vec<(usize, &CausalEvent)> = events
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| {
+                self.event_type
+                    .as_ref()
+                    .map_or(true, |t| &e.event_type == t)
+            })
+            .collect();
+        let window = &tasks[tasks.len().saturating_sub(self.window)..];
+
+        let total = window.len() as u64;
+        let successes = window
+            .iter()
+            .filter(|(_, e)| SUCCESS_RESULTS.contains(&e.result.as_str()))
+            .count() as u64;
+        let success_bps = (successes * 10_000).checked_div(total).unwrap_or(0) as u32;
+
+        // Longest run of the limited result inside the window
+        let mut longest: Vec<usize> = Vec::new();
+        let mut current: Vec<usize> = Vec::new();
+        if let Some(streak_result) = &self.streak_result {
+            for (idx, event) in window {
+                if &event.result == streak_result {
+                    current.push(*idx);
+                    if current.len() > longest.len() {
+                        longest = current.clone();
+                    }
+                } else {
+                    current.clear();
+                }
+            }
+        }
+
+        let longest_streak = longest.len() as u32;
+        let streak_violated = self.streak_result.is_some() && longest_streak > self.max_streak;
+        let status = if streak_violated {
+            SlaStatus::Fail
+        } else if (total as usize) < self.window {
+            SlaStatus::Pending
+        } else if success_bps >= self.min_success_bps {
+            SlaStatus::Pass
+        } else {
+            SlaStatus::Fail
+        };
+
+        // Evidence: the violating streak, otherwise the window bounds
+        let evidence = if streak_violated {
+            longest
+        } else {
+            let mut bounds: Vec<usize> = window
+                .first()
+                .into_iter()
+                .chain(window.last())
+                .map(|(idx, _)| *idx)
+                .collect();
+            bounds.dedup();
+            bounds
+        };
+
+        SlaEvaluation {
+            status,
+            total,
+            successes,
+            success_bps,
+            longest_streak,
+            evidence,
+        }
+    }
+}
+
+/// Verdict of an SLA evaluation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaStatus {
+    /// Not enough tasks recorded to fill the window yet.
+    Pending,
+    /// All SLA conditions hold.
+    Pass,
+    /// At least one SLA condition is violated.
+    Fail,
+}
+
+/// Outcome of evaluating an SLA over a history.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlaEvaluation {
+    /// Overall verdict.
+    pub status: SlaStatus,
+    /// Tasks in the evaluated window.
+    pub total: u64,
+    /// Successful tasks in the window.
+    pub successes: u64,
+    /// Success rate in basis points.
+    pub success_bps: u32,
+    /// Longest run of the limited result in the window.
+    pub longest_streak: u32,
+    /// History indices of the events backing the verdict.
+    pub evidence: Vec<usize>,
+}
+
+/// The signed part of an SLA attestation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlaVerdict {
+    /// `SlaDefinition::id()` of the evaluated SLA.
+    pub sla_id: H256,
+    /// Agent the SLA applies to.
+    pub agent_id: Address,
+    /// Whether the SLA held.
+    pub passed: bool,
+    /// Tasks in the evaluated window.
+    pub total: u64,
+    /// Successful tasks in the window.
+    pub successes: u64,
+    /// Longest run of the limited result in the window.
+    pub longest_streak: u32,
+    /// Causal Merkle root of the agent's history at evaluation time.
+    pub history_root: H256,
+    /// Timestamp of the latest event covered.
+    pub attested_at: u64,
+}
+
+impl SlaVerdict {
+    /// The digest that gets signed.
+    ///
+    /// Formula: `keccak256(sla_id || agent_id || passed || total || successes || longest_streak || history_root || attested_at)`
+    pub fn digest(&self) -> H256 {
+        let mut hasher = Keccak::v256();
+        hasher.update(self.sla_id.as_bytes());
+        hasher.update(self.agent_id.as_bytes());
+        hasher.update(&[self.passed as u8]);
+        hasher.update(&self.total.to_be_bytes());
+        hasher.update(&self.successes.to_be_bytes());
+        hasher.update(&self.longest_streak.to_be_bytes());
+        hasher.update(self.history_root.as_bytes());
+        hasher.update(&self.attested_at.to_be_bytes());
+        let mut output = [0u8; 32];
+        hasher.finalize(&mut output);
+        H256::from(output)
+    }
+}
+
+/// A signed SLA verdict with inclusion proofs for its evidence.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlaAttestation {
+    /// The attested verdict.
+    pub verdict: SlaVerdict,
+    /// Inclusion proofs for the evidence events.
+    pub evidence: Vec<CausalProof>,
+    /// Address of the attesting key.
+    pub attester: Address,
+    /// Signature over `verdict.digest()`.
+    pub signature: Signature,
+}
+
+impl SlaAttestation {
+    /// Whether the SLA held.
+    pub fn passed(&self) -> bool {
+        self.verdict.passed
+    }
+
+    /// Verify the signature, the evidence proofs and the SLA binding.
+    pub fn verify(&self, sla: &SlaDefinition) -> bool {
+        if self.verdict.sla_id != sla.id() {
+            return false;
+        }
+
+        let signer_ok = self
+            .signature
+            .recover(RecoveryMessage::Hash(self.verdict.digest()))
+            .map(|addr| addr == self.attester)
+            .unwrap_or(false);
+        if !signer_ok {
+            return false;
+        }
+
+        self.evidence.iter().all(|proof| {
+            proof.root == self.verdict.history_root
+                && proof.event.agent_id == self.verdict.agent_id
+                && proof.verify()
+        })
+    }
+}
+
+/// Evaluates an SLA on every recorded event and signs verdict changes.
+pub struct ContinuousAttestor {
+    sla: SlaDefinition,
+    agent_id: Address,
+    signer: LocalWallet,
+    recorder: CausalEventRecorder,
+    last_status: SlaStatus,
+}
+
+impl ContinuousAttestor {
+    /// Create an attestor for one agent's SLA, signing with `signer`.
+    pub fn new(sla: SlaDefinition, agent_id: Address, signer: LocalWallet) -> Self {
+        Self {
+            sla,
+            agent_id,
+            signer,
+            recorder: CausalEventRecorder::new(),
+            last_status: SlaStatus::Pending,
+        }
+    }
+
+    /// The SLA being enforced.
+    pub fn sla(&self) -> &SlaDefinition {
+        &self.sla
+    }
+
+    /// The most recent verdict.
+    pub fn status(&self) -> SlaStatus {
+        self.last_status
+    }
+
+    /// Record an event and re-evaluate the SLA.
+    ///
+    /// Returns a signed attestation when the verdict changes to `Pass` or
+    /// `Fail`, and `None` otherwise.
+    ///
+    /// # Errors
+    /// Returns `CausalError` if the event belongs to another agent or breaks
+    /// causal ordering.
+    pub fn record_event(&mut self, event: CausalEvent) -> Result<Option<SlaAttestation>> {
+        if event.agent_id != self.agent_id {
+            return Err(CausalError::InvalidAgentAddress(format!(
+                "Event agent {:?} does not match attested agent {:?}",
+                event.agent_id, self.agent_id
+            )));
+        }
+        self.recorder.record_event(event)?;
+
+        let status = self.evaluate().status;
+        if status == self.last_status || status == SlaStatus::Pending {
+            return Ok(None);
+        }
+        self.last_status = status;
+        self.attest().map(Some)
+    }
+
+    /// Evaluate the SLA over the recorded history.
+    pub fn evaluate(&self) -> SlaEvaluation {
+        let events = self
+            .recorder
+            .get_agent_events(&self.agent_id)
+            .map(|e| e.as_slice())
+            .unwrap_or(&[]);
+        self.sla.evaluate(events)
+    }
+
+    /// Produce a signed attestation of the current verdict.
+    ///
+    /// # Errors
+    /// Returns `CausalError` if the verdict is still pending or signing fails.
+    pub fn attest(&self) -> Result<SlaAttestation> {
+        let evaluation = self.evaluate();
+        if evaluation.status == SlaStatus::Pending {
+            return Err(CausalError::InternalError(format!(
+                "SLA '{}' is pending: {} of {} tasks recorded",
+                self.sla.name, evaluation.total, self.sla.window
+            )));
+        }
+
+        let tree = self.recorder.build_merkle_tree(&self.agent_id)?;
+        let attested_at = tree.events().last().map(|e| e.timestamp).unwrap_or(0);
+        let evidence = evaluation
+            .evidence
+            .iter()
+            .map(|&idx| tree.generate_proof(idx))
+            .collect();
+
+        let verdict = SlaVerdict {
+            sla_id: self.sla.id(),
+            agent_id: self.agent_id,
+            passed: evaluation.status == SlaStatus::Pass,
+            total: evaluation.total,
+            successes: evaluation.successes,
+            longest_streak: evaluation.longest_streak,
+            history_root: tree.root,
+            attested_at,
+        };
+        let signature = self
+            .signer
+            .sign_hash(verdict.digest())
+            .map_err(|e| CausalError::InternalError(format!("Signing failed: {}", e)))?;
+
+        let attestation = SlaAttestation {
+            verdict,
+            evidence,
+            attester: self.signer.address(),
+            signature,
+        };
+        Ok(attestation)
+    }
+}
+
+fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn agent() -> Address {
+        "0x1234567890123456789012345678901234567890"
+            .parse()
+            .unwrap()
+    }
+
+    fn signer() -> LocalWallet {
+        "0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap()
+    }
+
+    fn event(nonce: u64, result: &str) -> CausalEvent {
+        CausalEvent::builder()
+            .agent_id(agent())
+            .nonce(nonce)
+            .event_type("task_executed")
+            .result(result)
+            .timestamp(1_700_000_000 + nonce)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_rolling_window() {
+        let sla = SlaDefinition::new("rate", 4).with_min_success_bps(7_500);
+        let mut events: Vec<CausalEvent> = (0..3).map(|n| event(n, "success")).collect();
+        assert_eq!(sla.evaluate(&events).status, SlaStatus::Pending);
+
+        events.push(event(3, "failure"));
+        let eval = sla.evaluate(&events);
+        assert_eq!(eval.status, SlaStatus::Pass);
+        assert_eq!(eval.success_bps, 7_500);
+
+        // Oldest success rolls out of the window
+        events.push(event(4, "failure"));
+        assert_eq!(sla.evaluate(&events).status, SlaStatus::Fail);
+    }
+
+    #[test]
+    fn test_streak_violation_is_evidence() {
+        let sla = SlaDefinition::new("streak", 10).with_max_streak("loss", 2);
+        let events: Vec<CausalEvent> = ["profit", "loss", "loss", "loss", "profit"]
+            .iter()
+            .enumerate()
+            .map(|(n, r)| event(n as u64, r))
+            .collect();
+
+        let eval = sla.evaluate(&events);
+        assert_eq!(eval.status, SlaStatus::Fail);
+        assert_eq!(eval.longest_streak, 3);
+        assert_eq!(eval.evidence, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_attestor_emits_on_transitions() {
+        let sla = SlaDefinition::new("rate", 2).with_min_success_bps(10_000);
+        let mut attestor = ContinuousAttestor::new(sla, agent(), signer());
+
+        assert!(attestor
+            .record_event(event(0, "success"))
+            .unwrap()
+            .is_none());
+        let pass = attestor.record_event(event(1, "success")).unwrap().unwrap();
+        assert!(pass.passed());
+        assert!(pass.verify(attestor.sla()));
+
+        // No change, no attestation
+        assert!(attestor
+            .record_event(event(2, "success"))
+            .unwrap()
+            .is_none());
+
+        let fail = attestor.record_event(event(3, "failure")).unwrap().unwrap();
+        assert!(!fail.passed());
+        assert!(fail.verify(attestor.sla()));
+        assert_eq!(attestor.status(), SlaStatus::Fail);
+    }
+
+    #[test]
+    fn test_tampered_attestation_rejected() {
+        let sla = SlaDefinition::new("rate", 1).with_min_success_bps(10_000);
+        let mut attestor = ContinuousAttestor::new(sla.clone(), agent(), signer());
+        let mut attestation = attestor.record_event(event(0, "success")).unwrap().unwrap();
+
+        attestation.verdict.successes = 0;
+        assert!(!attestation.verify(&sla));
+
+        let other = SlaDefinition::new("rate", 1);
+        let fresh = attestor.attest().unwrap();
+        assert!(!fresh.verify(&other));
+    }
+
+    #[test]
+    fn test_foreign_agent_rejected() {
+        let mut attestor = ContinuousAttestor::new(SlaDefinition::new("x", 1), agent(), signer());
+        let foreign = CausalEvent::builder()
+            .agent_id(Address::repeat_byte(9))
+            .build()
+            .unwrap();
+        assert!(matches!(
+            attestor.record_event(foreign),
+            Err(CausalError::InvalidAgentAddress(_))
+        ));
+    }
+}
@@ -48,17 +48,7 @@ impl CausalMerkleTree {
         }
 
         // Compute leaf hashes: Keccak256(RLP(event))
-        let leaves: Vec<H256> = events
-            .iter()
-            .map(|event| {
-                let serialized = event.rlp_encode();
-                let mut hash = [0u8; 32];
-                let mut hasher = Keccak::v256();
-                hasher.update(&serialized);
-                hasher.finalize(&mut hash);
-                H256::from(hash)
-            })
-            .collect();
+        let leaves: Vec<H256> = events.iter().map(CausalEvent::leaf_hash).collect();
 
         let (levels, root) = Self::build_tree(leaves);
 
@@ -73,12 +63,12 @@ impl CausalMerkleTree {
     fn build_tree(leaves: Vec<H256>) -> (Vec<Vec<H256>>, H256) {
         let mut levels = vec![leaves];
 
-        while levels.last().unwrap().len() > 1 {
-            let next_level = Self::compute_next_level(levels.last().unwrap());
+        while let Some(current) = levels.last().filter(|level| level.len() > 1) {
+            let next_level = Self::compute_next_level(current);
             levels.push(next_level);
         }
 
-        let root = levels.last().unwrap()[0];
+        let root = levels[levels.len() - 1][0];
         (levels, root)
     }
 
@@ -9,11 +9,15 @@ use serde::Deserialize;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 
+use sods_core::clock::{SharedClock, SystemClock};
+use sods_core::ErrorCode;
+
 use sods_causal::{
     generate_behavioral_proof, AgentBehaviorPattern, CausalBehavioralProof, CausalEventRecorder,
     ReputationClaim, ReputationFeedback,
 };
 
+use crate::auth::{ApiAuth, Scope};
 use crate::logging::ValidationLog;
 use crate::monitoring::metrics::AgentMetrics;
 use tracing::info;
@@ -25,6 +29,8 @@ use axum::extract::FromRef;
 pub struct ApiState {
     pub recorder: Arc<tokio::sync::RwLock<CausalEventRecorder>>,
     pub metrics: Option<Arc<AgentMetrics>>,
+    /// Time proofs are checked for freshness against
+    pub clock: SharedClock,
 }
 
 impl FromRef<Arc<ApiState>> for MonitoringState {
@@ -40,14 +46,17 @@ impl FromRef<Arc<ApiState>> for MonitoringState {
 pub struct ProofRequest {
     pub pattern: AgentBehaviorPattern,
     pub now: u64,
+    /// Optional proof lifetime in seconds
+    #[serde(default)]
+    pub valid_for: Option<u64>,
 }
 
 /// Accepts a CausalBehavioralProof and returns verification result.
-async fn verify_proof(Json(proof): Json<CausalBehavioralProof>) -> impl IntoResponse {
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
+async fn verify_proof(
+    State(state): State<Arc<ApiState>>,
+    Json(proof): Json<CausalBehavioralProof>,
+) -> impl IntoResponse {
+    let now = state.clock.unix_secs();
 
     let is_valid = proof.verify(now);
 
@@ -63,6 +72,13 @@ async fn verify_proof(Json(proof): Json<CausalBehavioralProof>) -> impl IntoResp
     }))
 }
 
+/// `e` as a response with the status its class maps to.
+fn error_response(e: &sods_causal::CausalError, context: &str) -> axum::response::Response {
+    let status = axum::http::StatusCode::from_u16(e.class().http_status())
+        .unwrap_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+    (status, format!("{}: {}", context, e)).into_response()
+}
+
 /// Returns a pre-generated behavioral proof for an agent.
 async fn get_proof(
     Path(agent_id): Path<String>,
@@ -84,23 +100,18 @@ async fn get_proof(
     let start_time = std::time::Instant::now();
     let result = match recorder.build_merkle_tree(&agent_addr) {
         Ok(tree) => match generate_behavioral_proof(&tree, &req.pattern, req.now) {
-            Ok(proof) => {
+            Ok(mut proof) => {
+                if let Some(secs) = req.valid_for {
+                    proof = proof.with_validity(secs);
+                }
                 if let Some(ref m) = state.metrics {
                     m.validation_responses_submitted_total.inc();
                 }
                 Json(proof).into_response()
             }
-            Err(e) => (
-                axum::http::StatusCode::NOT_FOUND,
-                format!("Failed to generate proof: {}", e),
-            )
-                .into_response(),
+            Err(e) => error_response(&e, "Failed to generate proof"),
         },
-        Err(e) => (
-            axum::http::StatusCode::NOT_FOUND,
-            format!("Agent history not found: {}", e),
-        )
-            .into_response(),
+        Err(e) => error_response(&e, "Agent history not found"),
     };
 
     info!(target: "validation",
@@ -141,23 +152,31 @@ async fn submit_feedback(Json(feedback): Json<ReputationFeedback>) -> impl IntoR
     Json(claim).into_response()
 }
 
+/// Serve the causal API on `port`. Feedback submission needs a key with
+/// the `admin` scope when `auth` has keys.
 pub async fn start_server(
     port: u16,
     metrics: Option<Arc<AgentMetrics>>,
+    auth: Arc<ApiAuth>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let state = Arc::new(ApiState {
         recorder: Arc::new(tokio::sync::RwLock::new(CausalEventRecorder::new())),
         metrics,
+        clock: SystemClock::shared(),
     });
 
+    let feedback = auth.require(
+        Router::new().route("/causal/feedback", post(submit_feedback)),
+        Scope::Admin,
+    );
     let app = Router::new()
         .merge(crate::api::health::router::<Arc<ApiState>>())
         .route("/causal/verify", post(verify_proof))
         .route("/causal/proof/:agent_id", post(get_proof))
-        .route("/causal/feedback", post(submit_feedback))
+        .merge(feedback)
         .with_state(state);
 
-    let addr = format!("0.0.0.0:{}", port);
+    let addr = auth.socket_addr(port);
     let listener = TcpListener::bind(&addr).await?;
     println!("🚀 Causal API Server listening on http://{}", addr);
 
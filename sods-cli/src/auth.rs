@@ -0,0 +1,379 @@
+//! API keys for the daemon's HTTP and WebSocket servers.
+//!
+//! Keys are listed in `~/.sods/daemon.toml`, each with the scopes it grants:
+//!
+//! ```toml
+//! [[api_keys]]
+//! name = "grafana"
+//! key = "3f9c...e1"
+//! scopes = ["read-metrics"]
+//! ```
+//!
+//! Clients send a key as `Authorization: Bearer <key>`; WebSocket clients
+//! that cannot set headers may use `?api_key=<key>` instead. Without any
+//! configured keys the servers stay open but bind to the loopback address;
+//! a non-loopback `bind` is refused until a key exists, so nothing
+//! unauthenticated is reachable from other hosts. Control endpoints, such as
+//! the causal API's feedback route, need the `admin` scope.
+
+use ethers_core::utils::keccak256;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+/// Shortest accepted key, in characters.
+pub const MIN_KEY_LEN: usize = 16;
+
+/// What a key may access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scope {
+    /// The WebSocket alert stream
+    ReadAlerts,
+    /// `/_metrics` and the health endpoints
+    ReadMetrics,
+    /// Everything, including control endpoints
+    Admin,
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Scope::ReadAlerts => "read-alerts",
+            Scope::ReadMetrics => "read-metrics",
+            Scope::Admin => "admin",
+        })
+    }
+}
+
+/// One `[[api_keys]]` entry.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    pub name: String,
+    pub key: String,
+    pub scopes: Vec<Scope>,
+}
+
+/// Why a request was refused.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthError {
+    /// No key was sent.
+    MissingKey,
+    /// The key is not configured.
+    UnknownKey,
+    /// The key is valid but lacks the scope.
+    MissingScope(Scope),
+}
+
+impl AuthError {
+    /// HTTP status to answer with: 401, or 403 for a key without the scope.
+    pub fn status(&self) -> u16 {
+        match self {
+            AuthError::MissingKey | AuthError::UnknownKey => 401,
+            AuthError::MissingScope(_) => 403,
+        }
+    }
+}
+
+impl fmt::Display for AuthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthError::MissingKey => f.write_str("API key required"),
+            AuthError::UnknownKey => f.write_str("Invalid API key"),
+            AuthError::MissingScope(scope) => write!(f, "API key lacks the '{}' scope", scope),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct KeyEntry {
+    name: String,
+    /// Keys are compared by hash, so the comparison time says nothing
+    /// about how much of a guess was right
+    digest: [u8; 32],
+    scopes: Vec<Scope>,
+}
+
+/// Configured keys and the address the servers bind to.
+///
+/// The default has no keys and binds to the loopback address.
+#[derive(Debug)]
+pub struct ApiAuth {
+    keys: Vec<KeyEntry>,
+    bind: IpAddr,
+}
+
+impl Default for ApiAuth {
+    fn default() -> Self {
+        Self {
+            keys: Vec::new(),
+            bind: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        }
+    }
+}
+
+impl ApiAuth {
+    /// Check the keys and pick the bind address: `bind` if given, else all
+    /// interfaces with keys and loopback without. Binding beyond loopback
+    /// without keys is an error.
+    pub fn new(keys: Vec<ApiKeyConfig>, bind: Option<IpAddr>) -> Result<Self, String> {
+        let mut entries: Vec<KeyEntry> = Vec::new();
+        for key in keys {
+            if key.name.trim().is_empty() {
+                return Err("API key without a name".to_string());
+            }
+            if entries.iter().any(|e| e.name == key.name) {
+                return Err(format!("Duplicate API key name '{}'", key.name));
+            }
+            if key.key.len() < MIN_KEY_LEN {
+                return Err(format!(
+                    "API key '{}' is too short (min {} characters)",
+                    key.name, MIN_KEY_LEN
+                ));
+            }
+            if key.scopes.is_empty() {
+                return Err(format!("API key '{}' has no scopes", key.name));
+            }
+            let digest = keccak256(key.key.as_bytes());
+            if entries.iter().any(|e| e.digest == digest) {
+                return Err(format!("API key '{}' reuses another key", key.name));
+            }
+            entries.push(KeyEntry {
+                name: key.name,
+                digest,
+                scopes: key.scopes,
+            });
+        }
+        let bind = bind.unwrap_or(if entries.is_empty() {
+            IpAddr::V4(Ipv4Addr::LOCALHOST)
+        } else {
+            IpAddr::V4(Ipv4Addr::UNSPECIFIED)
+        });
+        if entries.is_empty() && !bind.is_loopback() {
+            return Err(format!(
+                "Binding to {} requires at least one [[api_keys]] entry",
+                bind
+            ));
+        }
+        Ok(Self {
+            keys: entries,
+            bind,
+        })
+    }
+
+    /// Whether requests need a key.
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    pub fn socket_addr(&self, port: u16) -> SocketAddr {
+        SocketAddr::new(self.bind, port)
+    }
+
+    /// Check `token` for `scope`, returning the key's name. Everything is
+    /// allowed when no keys are configured.
+    pub fn authorize(&self, token: Option<&str>, scope: Scope) -> Result<Option<&str>, AuthError> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+        let token = token.ok_or(AuthError::MissingKey)?;
+        let digest = keccak256(token.as_bytes());
+        let entry = self
+            .keys
+            .iter()
+            .find(|e| e.digest == digest)
+            .ok_or(AuthError::UnknownKey)?;
+        if entry.scopes.contains(&scope) || entry.scopes.contains(&Scope::Admin) {
+            Ok(Some(&entry.name))
+        } else {
+            Err(AuthError::MissingScope(scope))
+        }
+    }
+
+    /// The key in an `Authorization: Bearer <key>` header value.
+    pub fn bearer_token(header: &str) -> Option<&str> {
+        let (scheme, token) = header.trim().split_once(' ')?;
+        scheme
+            .eq_ignore_ascii_case("bearer")
+            .then(|| token.trim())
+            .filter(|t| !t.is_empty())
+    }
+
+    /// The `api_key` parameter of a URI query string.
+    pub fn query_token(query: &str) -> Option<&str> {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("api_key="))
+            .filter(|t| !t.is_empty())
+    }
+
+    /// Require `scope` on every route of `router`.
+    #[cfg(any(feature = "metrics", feature = "api"))]
+    pub fn require<S>(
+        self: &std::sync::Arc<Self>,
+        router: axum::Router<S>,
+        scope: Scope,
+    ) -> axum::Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        use axum::http::{header::AUTHORIZATION, StatusCode};
+        use axum::response::IntoResponse;
+
+        let auth = self.clone();
+        router.route_layer(axum::middleware::from_fn(
+            move |request: axum::extract::Request, next: axum::middleware::Next| {
+                let auth = auth.clone();
+                async move {
+                    let token = request
+                        .headers()
+                        .get(AUTHORIZATION)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(Self::bearer_token);
+                    match auth.authorize(token, scope) {
+                        Ok(_) => next.run(request).await,
+                        Err(e) => (
+                            StatusCode::from_u16(e.status()).unwrap_or(StatusCode::UNAUTHORIZED),
+                            e.to_string(),
+                        )
+                            .into_response(),
+                    }
+                }
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str, key: &str, scopes: &[Scope]) -> ApiKeyConfig {
+        ApiKeyConfig {
+            name: name.into(),
+            key: key.into(),
+            scopes: scopes.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_authorize_by_scope() {
+        let auth = ApiAuth::new(
+            vec![
+                key("grafana", "metrics-key-0123456789", &[Scope::ReadMetrics]),
+                key("ops", "admin-key-0123456789ab", &[Scope::Admin]),
+            ],
+            None,
+        )
+        .unwrap();
+        assert!(auth.is_enabled());
+        assert_eq!(auth.socket_addr(9090).to_string(), "0.0.0.0:9090");
+
+        assert_eq!(
+            auth.authorize(Some("metrics-key-0123456789"), Scope::ReadMetrics),
+            Ok(Some("grafana"))
+        );
+        assert_eq!(
+            auth.authorize(Some("metrics-key-0123456789"), Scope::ReadAlerts),
+            Err(AuthError::MissingScope(Scope::ReadAlerts))
+        );
+        assert_eq!(
+            auth.authorize(Some("admin-key-0123456789ab"), Scope::ReadAlerts),
+            Ok(Some("ops"))
+        );
+        assert_eq!(
+            auth.authorize(Some("metrics-key-0123456789"), Scope::Admin)
+                .unwrap_err()
+                .status(),
+            403
+        );
+        assert_eq!(
+            auth.authorize(Some("metrics-key-012345678"), Scope::ReadMetrics),
+            Err(AuthError::UnknownKey)
+        );
+        assert_eq!(
+            auth.authorize(None, Scope::ReadMetrics)
+                .unwrap_err()
+                .status(),
+            401
+        );
+    }
+
+    #[test]
+    fn test_without_keys() {
+        let auth = ApiAuth::new(Vec::new(), None).unwrap();
+        assert_eq!(auth.authorize(None, Scope::Admin), Ok(None));
+        assert_eq!(auth.socket_addr(8080).to_string(), "127.0.0.1:8080");
+        assert_eq!(
+            ApiAuth::default().socket_addr(8080).to_string(),
+            "127.0.0.1:8080"
+        );
+
+        assert!(ApiAuth::new(Vec::new(), Some("0.0.0.0".parse().unwrap())).is_err());
+        assert!(ApiAuth::new(Vec::new(), Some("::1".parse().unwrap())).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_bad_keys() {
+        let ok = "key-0123456789abcdef";
+        for keys in [
+            vec![key("short", "abc", &[Scope::Admin])],
+            vec![key("none", ok, &[])],
+            vec![key("", ok, &[Scope::Admin])],
+            vec![
+                key("a", ok, &[Scope::Admin]),
+                key("a", "other-0123456789abcdef", &[Scope::Admin]),
+            ],
+            vec![key("a", ok, &[Scope::Admin]), key("b", ok, &[Scope::Admin])],
+        ] {
+            assert!(ApiAuth::new(keys, None).is_err());
+        }
+    }
+
+    #[test]
+    fn test_token_extraction() {
+        assert_eq!(ApiAuth::bearer_token("Bearer abc"), Some("abc"));
+        assert_eq!(ApiAuth::bearer_token("bearer  abc "), Some("abc"));
+        assert_eq!(ApiAuth::bearer_token("Basic abc"), None);
+        assert_eq!(ApiAuth::query_token("x=1&api_key=abc"), Some("abc"));
+        assert_eq!(ApiAuth::query_token("api_key="), None);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn test_require_layer() {
+        use std::sync::Arc;
+
+        let auth = Arc::new(
+            ApiAuth::new(
+                vec![key(
+                    "grafana",
+                    "metrics-key-0123456789",
+                    &[Scope::ReadMetrics],
+                )],
+                None,
+            )
+            .unwrap(),
+        );
+        let app = auth.require(
+            axum::Router::new().route("/_metrics", axum::routing::get(|| async { "ok" })),
+            Scope::ReadMetrics,
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/_metrics", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = reqwest::Client::new();
+        let status = |key: Option<&'static str>| {
+            let mut request = client.get(&url);
+            if let Some(key) = key {
+                request = request.bearer_auth(key);
+            }
+            async move { request.send().await.unwrap().status().as_u16() }
+        };
+        assert_eq!(status(None).await, 401);
+        assert_eq!(status(Some("wrong-key-0123456789")).await, 401);
+        assert_eq!(status(Some("metrics-key-0123456789")).await, 200);
+    }
+}
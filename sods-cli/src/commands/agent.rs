@@ -43,15 +43,21 @@ pub async fn run(args: AgentArgs) -> i32 {
                 }
             };
 
+            // Same keys and bind address as the daemon's servers
+            let auth = match crate::config::DaemonConfig::load().and_then(|c| c.api_auth()) {
+                Ok(auth) => Arc::new(auth),
+                Err(e) => {
+                    crate::output::error(&e);
+                    return 1;
+                }
+            };
+
             if let Some(ref m) = metrics {
-                let m_clone = m.clone();
-                // Metrics are usually on a separate port or same. Let's use separate if port is provided,
-                // but here ServeArgs only has one port. The user probably wants metrics on the same or a default one.
-                // For now, let's just use 9090 as default for metrics if not on same.
-                tokio::spawn(m_clone.start_http_server(9090));
+                // Metrics are served on their own port, 9090
+                tokio::spawn(m.clone().serve(9090, auth.clone()));
             }
 
-            if let Err(e) = crate::api::causal::start_server(serve_args.port, metrics).await {
+            if let Err(e) = crate::api::causal::start_server(serve_args.port, metrics, auth).await {
                 eprintln!("Error starting API server: {}", e);
                 return 1;
             }
@@ -0,0 +1,471 @@
+//! Audit command implementation.
+//!
+//! Audits a block range for a pattern by fully verifying a seeded random
+//! sample of blocks and bloom-checking the rest (see
+//! `sods_verifier::audit`). The seed is derived from the hash of a block
+//! after the range, waiting for it to be mined if need be. The report
+//! records the seed and that block, so `--replay` can check the seed
+//! against the chain, redo the exact same audit and flag any disagreement.
+
+use clap::Args;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::get_chain;
+use crate::output;
+use sods_verifier::audit::SeedBlock;
+use sods_verifier::{AuditReport, AuditSampler, BlockVerifier};
+
+/// How often to check whether the seed block has been mined.
+const SEED_POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Arguments for the audit command.
+#[derive(Args)]
+pub struct AuditArgs {
+    /// Behavioral pattern to audit (e.g., "Sandwich", "LP+ -> Sw")
+    #[arg(short, long, required_unless_present = "replay")]
+    pub pattern: Option<String>,
+
+    /// Blockchain chain (sepolia, ethereum, base, arbitrum)
+    #[arg(short, long, default_value = "sepolia")]
+    pub chain: String,
+
+    /// First block of the range
+    #[arg(long, required_unless_present = "replay")]
+    pub from: Option<u64>,
+
+    /// Last block of the range (inclusive)
+    #[arg(long, required_unless_present = "replay")]
+    pub to: Option<u64>,
+
+    /// Number of blocks to verify trustlessly
+    #[arg(long, default_value = "20")]
+    pub sample: usize,
+
+    /// Write the audit report as JSON to this file
+    #[arg(short, long)]
+    pub out: Option<PathBuf>,
+
+    /// Re-run the audit recorded in a report and compare the results
+    #[arg(long, value_name = "REPORT", conflicts_with_all = ["pattern", "from", "to", "sample"])]
+    pub replay: Option<PathBuf>,
+
+    /// Custom RPC URL (overrides chain default)
+    #[arg(long)]
+    pub rpc_url: Option<String>,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Report file: the verifier's report plus the chain it was run on.
+#[derive(Debug, Serialize, Deserialize)]
+struct AuditFile {
+    chain: String,
+    #[serde(flatten)]
+    report: AuditReport,
+}
+
+/// Run the audit command.
+pub async fn run(args: AuditArgs) -> i32 {
+    let recorded = match &args.replay {
+        Some(path) => match load_report(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                output::error(&format!("Cannot read {}: {}", path.display(), e));
+                return 1;
+            }
+        },
+        None => None,
+    };
+
+    let (chain, pattern, from, to) = match &recorded {
+        Some(file) => (
+            file.chain.clone(),
+            file.report.pattern.clone(),
+            file.report.start_block,
+            file.report.end_block,
+        ),
+        None => (
+            args.chain.to_lowercase(),
+            args.pattern.clone().unwrap_or_default(),
+            args.from.unwrap_or_default(),
+            args.to.unwrap_or_default(),
+        ),
+    };
+    if from > to {
+        output::error("--from must not be greater than --to.");
+        return 1;
+    }
+
+    let Some(chain_config) = get_chain(&chain) else {
+        output::error(&format!("Chain '{}' not supported.", chain));
+        output::info("Run `sods chains` for supported chains.");
+        return 1;
+    };
+
+    let rpc_urls: Vec<String> = if let Some(url) = args.rpc_url.clone() {
+        vec![url]
+    } else if let Some(url) = crate::config::UserConfig::load().get_rpc_override(chain_config.name)
+    {
+        vec![url]
+    } else {
+        chain_config
+            .rpc_urls
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    };
+    let is_l2 = chain_config.name != "ethereum" && chain_config.name != "sepolia";
+    let profile = if is_l2 {
+        sods_verifier::rpc::BackoffProfile::L2
+    } else {
+        sods_verifier::rpc::BackoffProfile::Ethereum
+    };
+    let verifier = match BlockVerifier::new(&rpc_urls) {
+        Ok(v) => v
+            .with_backoff_profile(profile)
+            .with_aliases(crate::config::load_symbol_aliases()),
+        Err(e) => {
+            output::error(&format!("Failed to initialize RPCs: {}", e));
+            return 1;
+        }
+    };
+
+    if let Err(e) = verifier.parse_pattern(&pattern) {
+        output::error(&format!("Invalid pattern: {}", e));
+        return 1;
+    }
+
+    let seed_block_number = AuditSampler::seed_block_number(to);
+    let seed_block = match fetch_seed_block(&verifier, seed_block_number, !args.json).await {
+        Ok(block) => block,
+        Err(e) => {
+            output::error(&format!(
+                "Failed to fetch seed block {}: {}",
+                seed_block_number, e
+            ));
+            return 1;
+        }
+    };
+    // A replay keeps the recorded sampler so a tampered seed shows up as a
+    // discrepancy instead of being silently corrected
+    let sampler = match &recorded {
+        Some(file) => file.report.sampler(),
+        None => AuditSampler::from_seed_block(seed_block, &pattern, from, to, args.sample),
+    };
+
+    if !args.json {
+        output::header(&format!("Audit: {}", pattern));
+        output::kv("Chain", chain_config.description);
+        output::kv("Range", &format!("{}..={}", from, to));
+        output::kv(
+            "Seed",
+            &format!(
+                "{} (from block {} {:?})",
+                sampler.seed(),
+                seed_block.number,
+                seed_block.hash
+            ),
+        );
+        output::kv(
+            "Sample",
+            &format!("{} blocks verified trustlessly", sampler.sample_size()),
+        );
+    }
+
+    let total = to - from + 1;
+    let show_progress = !args.json && std::io::stderr().is_terminal();
+    let result = sampler
+        .run(&verifier, &pattern, from, to, |done| {
+            if show_progress {
+                eprint!("\r   {}/{} blocks", done, total);
+                let _ = std::io::stderr().flush();
+            }
+        })
+        .await;
+    if show_progress {
+        eprint!("\r\x1b[2K");
+    }
+    let report = match result {
+        Ok(report) => report,
+        Err(e) => {
+            output::error(&format!("Audit failed: {}", e));
+            return 1;
+        }
+    };
+
+    let file = AuditFile {
+        chain: chain_config.name.to_string(),
+        report,
+    };
+    if let Some(path) = &args.out {
+        let written = serde_json::to_string_pretty(&file)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            output::error(&format!("Failed to write {}: {}", path.display(), e));
+            return 1;
+        }
+    }
+
+    let discrepancies = recorded
+        .as_ref()
+        .map(|old| {
+            let mut differences = check_seed(&old.report, seed_block);
+            differences.extend(compare(&old.report, &file.report));
+            differences
+        })
+        .unwrap_or_default();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&file).unwrap());
+    } else {
+        print_report(&file.report);
+        if let Some(path) = &args.out {
+            output::kv("Report", &path.display().to_string());
+        }
+        if let Some(old) = &recorded {
+            println!();
+            if discrepancies.is_empty() {
+                output::success("Replay matches the recorded audit.");
+            } else {
+                output::warning(&format!(
+                    "Replay disagrees with the recorded audit in {} places:",
+                    discrepancies.len()
+                ));
+                for line in &discrepancies {
+                    println!("   - {}", line);
+                }
+            }
+            if !old.report.sample_is_reproducible() {
+                output::warning("The recorded sample is not the one its seed selects.");
+            }
+        }
+    }
+
+    let replay_ok = recorded.as_ref().map_or(true, |old| {
+        discrepancies.is_empty() && old.report.sample_is_reproducible()
+    });
+    if file.report.is_complete() && replay_ok {
+        0
+    } else {
+        1
+    }
+}
+
+/// Wait until block `number` is mined, then fetch its hash.
+async fn fetch_seed_block(
+    verifier: &BlockVerifier,
+    number: u64,
+    announce: bool,
+) -> Result<SeedBlock, sods_verifier::SodsVerifierError> {
+    let mut announced = false;
+    while verifier.get_latest_block().await? < number {
+        if announce && !announced {
+            output::info(&format!(
+                "Waiting for block {} to be mined; its hash seeds the sample.",
+                number
+            ));
+            announced = true;
+        }
+        tokio::time::sleep(SEED_POLL_INTERVAL).await;
+    }
+    let header = verifier.fetch_block_header(number).await?;
+    Ok(SeedBlock {
+        number,
+        hash: header.hash,
+    })
+}
+
+/// Problems with a recorded report's seed, given the chain's seed block.
+fn check_seed(recorded: &AuditReport, chain_block: SeedBlock) -> Vec<String> {
+    match recorded.seed_block {
+        None => vec!["the seed was chosen, not derived from a block hash".to_string()],
+        Some(block) if block != chain_block => vec![format!(
+            "seed block {} was recorded with hash {:?}, the chain has {:?}",
+            block.number, block.hash, chain_block.hash
+        )],
+        Some(_) if !recorded.seed_is_derived() => {
+            vec!["the seed is not the one its seed block derives".to_string()]
+        }
+        Some(_) => Vec::new(),
+    }
+}
+
+fn load_report(path: &Path) -> Result<AuditFile, String> {
+    let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&json).map_err(|e| e.to_string())
+}
+
+fn print_report(report: &AuditReport) {
+    output::header("Audit Summary");
+    let matched = report.matched_blocks();
+    output::kv(
+        "Verified sample",
+        &format!(
+            "{} of {} blocks matched",
+            matched.len(),
+            report.sampled.len()
+        ),
+    );
+    if let Some(rate) = report.sample_match_rate() {
+        output::kv("Sample match rate", &format!("{:.2}%", rate * 100.0));
+    }
+    output::kv(
+        "Bloom spot checks",
+        &format!(
+            "{} possible, {} ruled out",
+            report.bloom_hits.len(),
+            report.bloom_misses
+        ),
+    );
+    if !matched.is_empty() {
+        output::kv(
+            "Matched blocks",
+            &matched
+                .iter()
+                .map(|b| b.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    }
+    println!();
+    if report.is_complete() {
+        output::success("Audit complete.");
+    } else {
+        for sample in report.failed_samples() {
+            output::warning(&format!(
+                "Block {} not verified: {}",
+                sample.block_number,
+                sample.error.as_deref().unwrap_or_default()
+            ));
+        }
+        if !report.spot_check_failures.is_empty() {
+            output::warning(&format!(
+                "{} spot checks failed",
+                report.spot_check_failures.len()
+            ));
+        }
+    }
+}
+
+/// Differences between a recorded audit and its replay.
+fn compare(recorded: &AuditReport, replayed: &AuditReport) -> Vec<String> {
+    let mut differences = Vec::new();
+    for (old, new) in recorded.sampled.iter().zip(&replayed.sampled) {
+        if old.block_number != new.block_number {
+            differences.push(format!(
+                "sampled block {} was recorded as {}",
+                new.block_number, old.block_number
+            ));
+        } else if old.matched != new.matched || old.occurrences != new.occurrences {
+            differences.push(format!(
+                "block {}: recorded {} occurrence(s), replay found {}",
+                new.block_number, old.occurrences, new.occurrences
+            ));
+        }
+    }
+    if recorded.sampled.len() != replayed.sampled.len() {
+        differences.push(format!(
+            "recorded {} sampled blocks, replay {}",
+            recorded.sampled.len(),
+            replayed.sampled.len()
+        ));
+    }
+    if recorded.bloom_hits != replayed.bloom_hits {
+        differences.push(format!(
+            "bloom admits the pattern in {} blocks, {} recorded",
+            replayed.bloom_hits.len(),
+            recorded.bloom_hits.len()
+        ));
+    }
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::H256;
+    use sods_verifier::audit::{SampledBlock, SAMPLING_METHOD};
+
+    fn report(matched: &[bool]) -> AuditReport {
+        let sampled = AuditSampler::new(1, matched.len())
+            .sample(0, 99)
+            .into_iter()
+            .zip(matched)
+            .map(|(block_number, &matched)| SampledBlock {
+                block_number,
+                matched,
+                occurrences: usize::from(matched),
+                error: None,
+            })
+            .collect();
+        AuditReport {
+            pattern: "Sandwich".to_string(),
+            start_block: 0,
+            end_block: 99,
+            seed: 1,
+            seed_block: None,
+            sample_size: matched.len(),
+            method: SAMPLING_METHOD.to_string(),
+            sampled,
+            bloom_hits: vec![],
+            bloom_misses: 100 - matched.len() as u64,
+            spot_check_failures: vec![],
+        }
+    }
+
+    #[test]
+    fn test_report_file_roundtrip_and_compare() {
+        let file = AuditFile {
+            chain: "sepolia".to_string(),
+            report: report(&[true, false, false]),
+        };
+        let json = serde_json::to_string(&file).unwrap();
+        assert!(json.contains("\"chain\":\"sepolia\""));
+        assert!(json.contains("\"seed\":1"));
+        let parsed: AuditFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.report, file.report);
+        assert!(compare(&file.report, &parsed.report).is_empty());
+
+        let replayed = report(&[true, true, false]);
+        let differences = compare(&file.report, &replayed);
+        assert_eq!(differences.len(), 1);
+        assert!(differences[0].contains("replay found 1"));
+    }
+
+    #[test]
+    fn test_check_seed_against_the_chain() {
+        let mut recorded = report(&[true, false, false]);
+        let chain_block = SeedBlock {
+            number: AuditSampler::seed_block_number(recorded.end_block),
+            hash: H256::repeat_byte(1),
+        };
+        assert!(check_seed(&recorded, chain_block)[0].contains("chosen"));
+
+        let sampler = AuditSampler::from_seed_block(
+            chain_block,
+            &recorded.pattern,
+            recorded.start_block,
+            recorded.end_block,
+            recorded.sample_size,
+        );
+        recorded.seed = sampler.seed();
+        recorded.seed_block = Some(chain_block);
+        assert!(check_seed(&recorded, chain_block).is_empty());
+
+        let reorged = SeedBlock {
+            hash: H256::repeat_byte(2),
+            ..chain_block
+        };
+        assert!(check_seed(&recorded, reorged)[0].contains("the chain has"));
+
+        recorded.seed ^= 1;
+        assert!(check_seed(&recorded, chain_block)[0].contains("not the one"));
+    }
+}
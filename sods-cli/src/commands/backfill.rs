@@ -0,0 +1,650 @@
+//! Backfill command implementation.
+//!
+//! Scans a historical block range for a pattern and appends one NDJSON
+//! record per matching block. Progress is checkpointed after every block,
+//! so an interrupted run picks up where it stopped when started again with
+//! the same arguments.
+
+use clap::Args;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::config::get_chain;
+use crate::output;
+use sods_core::pattern::BehavioralPattern;
+use sods_core::{BehavioralSymbol, SymbolProvenance};
+use sods_verifier::BlockVerifier;
+
+/// Arguments for the backfill command.
+#[derive(Args)]
+pub struct BackfillArgs {
+    /// Behavioral pattern to detect (e.g., "Sandwich", "LP+ -> Sw")
+    #[arg(short, long)]
+    pub pattern: String,
+
+    /// Blockchain chain (sepolia, ethereum, base, arbitrum)
+    #[arg(short, long, default_value = "sepolia")]
+    pub chain: String,
+
+    /// First block to scan
+    #[arg(long)]
+    pub from: u64,
+
+    /// Last block to scan (inclusive)
+    #[arg(long)]
+    pub to: u64,
+
+    /// NDJSON file receiving one record per matching block (overwritten
+    /// unless resuming)
+    #[arg(short, long)]
+    pub out: PathBuf,
+
+    /// Checkpoint file (default: <OUT>.checkpoint.json)
+    #[arg(long)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Ignore an existing checkpoint and start over
+    #[arg(long)]
+    pub restart: bool,
+
+    /// Maximum blocks per second
+    #[arg(long, default_value = "2")]
+    pub rate: f64,
+
+    /// Custom RPC URL (overrides chain default)
+    #[arg(long)]
+    pub rpc_url: Option<String>,
+
+    /// Print the final summary as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Progress of a backfill, rewritten after every block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Checkpoint {
+    chain: String,
+    pattern: String,
+    from: u64,
+    to: u64,
+    /// Next block of the range not yet attempted
+    next_block: u64,
+    /// Bytes of the output file covered by this checkpoint
+    out_len: u64,
+    scanned: u64,
+    matched_blocks: u64,
+    matched_symbols: u64,
+    /// Blocks that failed; retried first on resume
+    failed: Vec<u64>,
+    /// Seconds spent scanning in earlier sessions
+    elapsed_secs: f64,
+}
+
+impl Checkpoint {
+    fn new(args: &BackfillArgs) -> Self {
+        Self {
+            chain: args.chain.to_lowercase(),
+            pattern: args.pattern.clone(),
+            from: args.from,
+            to: args.to,
+            next_block: args.from,
+            out_len: 0,
+            scanned: 0,
+            matched_blocks: 0,
+            matched_symbols: 0,
+            failed: Vec::new(),
+            elapsed_secs: 0.0,
+        }
+    }
+
+    /// Whether this checkpoint was written for the same scan.
+    fn is_for(&self, args: &BackfillArgs) -> bool {
+        self.chain == args.chain.to_lowercase()
+            && self.pattern == args.pattern
+            && self.from == args.from
+            && self.to == args.to
+    }
+
+    fn total(&self) -> u64 {
+        self.to - self.from + 1
+    }
+
+    /// Blocks finished (matched, clean or failed) out of `total`.
+    fn done(&self) -> u64 {
+        self.next_block - self.from
+    }
+
+    /// Record the outcome of attempting `block`. A retried block stays in
+    /// `failed` until it succeeds, so an interrupted retry pass loses nothing.
+    fn settle(&mut self, block: u64, is_retry: bool, ok: bool) {
+        match (is_retry, ok) {
+            (true, true) => self.failed.retain(|&b| b != block),
+            (false, false) => self.failed.push(block),
+            _ => {}
+        }
+        if !is_retry {
+            self.next_block = block + 1;
+        }
+    }
+
+    fn load(path: &Path) -> Option<Self> {
+        serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+    }
+
+    /// Write via a temporary file so an interruption never leaves half a checkpoint.
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_vec_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// One matching block, as written to the output file.
+#[derive(Debug, Serialize)]
+struct BackfillRecord<'a> {
+    chain: &'a str,
+    pattern: &'a str,
+    block_number: u64,
+    occurrences: usize,
+    verification_mode: String,
+    matched: Vec<MatchedSymbol>,
+}
+
+#[derive(Debug, Serialize)]
+struct MatchedSymbol {
+    symbol: String,
+    log_index: u32,
+    tx_hash: String,
+    from: String,
+    to: String,
+    value: String,
+    provenance: SymbolProvenance,
+}
+
+impl From<&BehavioralSymbol> for MatchedSymbol {
+    fn from(s: &BehavioralSymbol) -> Self {
+        Self {
+            symbol: s.symbol.clone(),
+            log_index: s.log_index,
+            tx_hash: format!("{:?}", s.tx_hash),
+            from: format!("{:?}", s.from),
+            to: format!("{:?}", s.to),
+            value: s.value.to_string(),
+            provenance: s.provenance,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct BackfillSummary {
+    chain: String,
+    pattern: String,
+    from: u64,
+    to: u64,
+    scanned: u64,
+    matched_blocks: u64,
+    matched_symbols: u64,
+    match_rate_percent: f64,
+    failed_blocks: Vec<u64>,
+    elapsed_secs: f64,
+    blocks_per_sec: f64,
+    out: String,
+    complete: bool,
+}
+
+/// Run the backfill command.
+pub async fn run(args: BackfillArgs) -> i32 {
+    if args.from > args.to {
+        output::error("--from must not be greater than --to.");
+        return 1;
+    }
+    if !(args.rate > 0.0 && args.rate.is_finite()) {
+        output::error("--rate must be a positive number of blocks per second.");
+        return 1;
+    }
+
+    let Some(chain_config) = get_chain(&args.chain) else {
+        output::error(&format!("Chain '{}' not supported.", args.chain));
+        output::info("Run `sods chains` for supported chains.");
+        return 1;
+    };
+
+    let aliases = crate::config::load_symbol_aliases();
+    if let Err(e) = BehavioralPattern::parse_with_aliases(&args.pattern, &aliases) {
+        output::error(&format!("Invalid pattern: {}", e));
+        return 1;
+    }
+
+    // Resume or start over
+    let checkpoint_path = args.checkpoint.clone().unwrap_or_else(|| {
+        let mut name = args.out.as_os_str().to_owned();
+        name.push(".checkpoint.json");
+        PathBuf::from(name)
+    });
+    let mut checkpoint = match Checkpoint::load(&checkpoint_path) {
+        Some(cp) if !args.restart && cp.is_for(&args) => {
+            output::info(&format!(
+                "Resuming from block {} ({} of {} done, {} failed to retry)",
+                cp.next_block,
+                cp.done(),
+                cp.total(),
+                cp.failed.len()
+            ));
+            cp
+        }
+        Some(_) if !args.restart => {
+            output::error(&format!(
+                "{} belongs to a different backfill.",
+                checkpoint_path.display()
+            ));
+            output::hint("Pass --restart to discard it, or --checkpoint to use another file.");
+            return 1;
+        }
+        _ => Checkpoint::new(&args),
+    };
+
+    // Drop anything written after the last checkpoint so records are not duplicated
+    let mut out = match open_output(&args.out, checkpoint.out_len) {
+        Ok(f) => f,
+        Err(e) => {
+            output::error(&format!("Cannot open {}: {}", args.out.display(), e));
+            return 1;
+        }
+    };
+
+    let rpc_urls: Vec<String> = if let Some(url) = args.rpc_url.clone() {
+        vec![url]
+    } else if let Some(url) = crate::config::UserConfig::load().get_rpc_override(chain_config.name)
+    {
+        vec![url]
+    } else {
+        chain_config
+            .rpc_urls
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    };
+    let is_l2 = chain_config.name != "ethereum" && chain_config.name != "sepolia";
+    let profile = if is_l2 {
+        sods_verifier::rpc::BackoffProfile::L2
+    } else {
+        sods_verifier::rpc::BackoffProfile::Ethereum
+    };
+    let verifier = match BlockVerifier::new(&rpc_urls) {
+        Ok(v) => v.with_backoff_profile(profile).with_aliases(aliases),
+        Err(e) => {
+            output::error(&format!("Failed to initialize RPCs: {}", e));
+            return 1;
+        }
+    };
+
+    if !args.json {
+        output::header(&format!("Backfill: {}", args.pattern));
+        output::kv("Chain", chain_config.description);
+        output::kv("Range", &format!("{}..={}", args.from, args.to));
+        output::kv("Output", &args.out.display().to_string());
+        output::kv("Checkpoint", &checkpoint_path.display().to_string());
+    }
+
+    let mut progress = Progress::new(!args.json && std::io::stderr().is_terminal());
+    let session_start = Instant::now();
+    let prior_elapsed = checkpoint.elapsed_secs;
+    let mut ticker = tokio::time::interval(Duration::from_secs_f64(1.0 / args.rate));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    // Earlier failures first, then the rest of the range
+    let retries = checkpoint.failed.clone();
+    let mut work: Vec<(u64, bool)> = retries.into_iter().map(|b| (b, true)).collect();
+    work.extend((checkpoint.next_block..=args.to).map(|b| (b, false)));
+    let pattern = args.pattern.as_str();
+    let mut attempted = 0u64;
+
+    for (block, is_retry) in work {
+        ticker.tick().await;
+        attempted += 1;
+
+        let ok = match verifier.verify_patterns_in_block(&[pattern], block).await {
+            Ok(mut outcomes) => {
+                let outcome = outcomes.remove(0);
+                if !outcome.matched.is_empty() {
+                    let record = BackfillRecord {
+                        chain: chain_config.name,
+                        pattern,
+                        block_number: block,
+                        occurrences: outcome.matched.len(),
+                        verification_mode: outcome
+                            .result
+                            .as_ref()
+                            .map(|r| r.verification_mode.to_string())
+                            .unwrap_or_default(),
+                        matched: outcome.matched.iter().map(MatchedSymbol::from).collect(),
+                    };
+                    match append_record(&mut out, &record) {
+                        Ok(len) => checkpoint.out_len += len,
+                        Err(e) => {
+                            progress.clear();
+                            output::error(&format!(
+                                "Failed to write {}: {}",
+                                args.out.display(),
+                                e
+                            ));
+                            return 1;
+                        }
+                    }
+                    checkpoint.matched_blocks += 1;
+                    checkpoint.matched_symbols += outcome.matched.len() as u64;
+                }
+                checkpoint.scanned += 1;
+                true
+            }
+            Err(e) => {
+                progress.clear();
+                eprintln!("   ⚠️ Block {} failed: {}", block, e);
+                false
+            }
+        };
+
+        checkpoint.settle(block, is_retry, ok);
+        checkpoint.elapsed_secs = prior_elapsed + session_start.elapsed().as_secs_f64();
+        if let Err(e) = checkpoint.save(&checkpoint_path) {
+            progress.clear();
+            output::error(&format!(
+                "Failed to save checkpoint {}: {}",
+                checkpoint_path.display(),
+                e
+            ));
+            return 1;
+        }
+        progress.draw(&checkpoint, attempted, session_start.elapsed());
+    }
+    progress.clear();
+
+    let complete = checkpoint.failed.is_empty();
+    let summary = BackfillSummary {
+        chain: chain_config.name.to_string(),
+        pattern: args.pattern.clone(),
+        from: args.from,
+        to: args.to,
+        scanned: checkpoint.scanned,
+        matched_blocks: checkpoint.matched_blocks,
+        matched_symbols: checkpoint.matched_symbols,
+        match_rate_percent: percent(checkpoint.matched_blocks, checkpoint.scanned),
+        failed_blocks: checkpoint.failed.clone(),
+        elapsed_secs: checkpoint.elapsed_secs,
+        blocks_per_sec: rate(
+            checkpoint.scanned + checkpoint.failed.len() as u64,
+            checkpoint.elapsed_secs,
+        ),
+        out: args.out.display().to_string(),
+        complete,
+    };
+
+    // A finished scan needs no checkpoint; an incomplete one keeps it for the retries
+    if complete {
+        let _ = fs::remove_file(&checkpoint_path);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+    } else {
+        print_summary(&summary, &checkpoint_path);
+    }
+
+    if complete {
+        0
+    } else {
+        1
+    }
+}
+
+fn print_summary(summary: &BackfillSummary, checkpoint_path: &Path) {
+    output::header("Backfill Summary");
+    output::kv("Blocks scanned", &summary.scanned.to_string());
+    output::kv(
+        "Matching blocks",
+        &format!(
+            "{} ({:.2}%)",
+            summary.matched_blocks, summary.match_rate_percent
+        ),
+    );
+    output::kv("Matched symbols", &summary.matched_symbols.to_string());
+    output::kv(
+        "Elapsed",
+        &format!(
+            "{} ({:.2} blocks/s)",
+            format_duration(summary.elapsed_secs),
+            summary.blocks_per_sec
+        ),
+    );
+    output::kv("Output", &summary.out);
+    println!();
+    if summary.complete {
+        output::success("Backfill complete.");
+    } else {
+        output::warning(&format!(
+            "{} blocks failed: {}",
+            summary.failed_blocks.len(),
+            summary
+                .failed_blocks
+                .iter()
+                .map(u64::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        output::hint(&format!(
+            "Run the same command again to retry them (checkpoint: {}).",
+            checkpoint_path.display()
+        ));
+    }
+}
+
+/// Open the output for appending, cut back to `len` bytes.
+fn open_output(path: &Path, len: u64) -> std::io::Result<fs::File> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .read(true)
+        .write(true)
+        .truncate(false)
+        .open(path)?;
+    file.set_len(len)?;
+    drop(file);
+    OpenOptions::new().append(true).open(path)
+}
+
+/// Append one record line and return the bytes written.
+fn append_record(out: &mut fs::File, record: &BackfillRecord<'_>) -> std::io::Result<u64> {
+    let mut line = serde_json::to_string(record)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    line.push('\n');
+    out.write_all(line.as_bytes())?;
+    out.flush()?;
+    Ok(line.len() as u64)
+}
+
+fn percent(part: u64, whole: u64) -> f64 {
+    if whole == 0 {
+        0.0
+    } else {
+        part as f64 * 100.0 / whole as f64
+    }
+}
+
+fn rate(count: u64, secs: f64) -> f64 {
+    if secs > 0.0 {
+        count as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+fn format_duration(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Single-line progress bar on stderr.
+struct Progress {
+    enabled: bool,
+    drawn: bool,
+}
+
+impl Progress {
+    const WIDTH: usize = 30;
+
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            drawn: false,
+        }
+    }
+
+    /// Rate and ETA come from this session's attempts only.
+    fn draw(&mut self, cp: &Checkpoint, attempted: u64, session: Duration) {
+        if !self.enabled {
+            return;
+        }
+        eprint!("\r{}", render_bar(cp, attempted, session).dimmed());
+        let _ = std::io::stderr().flush();
+        self.drawn = true;
+    }
+
+    fn clear(&mut self) {
+        if self.drawn {
+            eprint!("\r{}\r", " ".repeat(Self::WIDTH + 60));
+            self.drawn = false;
+        }
+    }
+}
+
+fn render_bar(cp: &Checkpoint, session_blocks: u64, session: Duration) -> String {
+    let total = cp.total();
+    let done = cp.done();
+    let filled = (done as f64 / total as f64 * Progress::WIDTH as f64) as usize;
+    let speed = rate(session_blocks, session.as_secs_f64());
+    let eta = if speed > 0.0 {
+        format_duration((total - done) as f64 / speed)
+    } else {
+        "?".to_string()
+    };
+    format!(
+        "[{}{}] {:>5.1}% {}/{} blocks · {} matches · {:.1} blk/s · ETA {}",
+        "#".repeat(filled),
+        "-".repeat(Progress::WIDTH - filled),
+        percent(done, total),
+        done,
+        total,
+        cp.matched_blocks,
+        speed,
+        eta
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(out: PathBuf) -> BackfillArgs {
+        BackfillArgs {
+            pattern: "Sandwich".into(),
+            chain: "Base".into(),
+            from: 100,
+            to: 199,
+            out,
+            checkpoint: None,
+            restart: false,
+            rate: 2.0,
+            rpc_url: None,
+            json: false,
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_matches_only_same_scan() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cp.json");
+        let a = args(dir.path().join("out.ndjson"));
+        let mut cp = Checkpoint::new(&a);
+        cp.next_block = 150;
+        cp.failed.push(120);
+        cp.save(&path).unwrap();
+
+        let loaded = Checkpoint::load(&path).unwrap();
+        assert_eq!(loaded, cp);
+        assert!(loaded.is_for(&a));
+        assert_eq!((loaded.done(), loaded.total()), (50, 100));
+
+        let mut other = args(a.out.clone());
+        other.to = 300;
+        assert!(!loaded.is_for(&other));
+    }
+
+    #[test]
+    fn test_failed_blocks_leave_only_after_retry_succeeds() {
+        let mut cp = Checkpoint::new(&args(PathBuf::from("out.ndjson")));
+        cp.next_block = 150;
+        cp.failed = vec![120, 130];
+
+        cp.settle(120, true, true);
+        cp.settle(130, true, false);
+        assert_eq!(cp.failed, vec![130]);
+        assert_eq!(cp.next_block, 150);
+
+        cp.settle(150, false, false);
+        cp.settle(151, false, true);
+        assert_eq!(cp.failed, vec![130, 150]);
+        assert_eq!(cp.next_block, 152);
+    }
+
+    #[test]
+    fn test_resume_truncates_unsaved_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("out.ndjson");
+        let a = args(path.clone());
+        let record = BackfillRecord {
+            chain: "base",
+            pattern: &a.pattern,
+            block_number: 100,
+            occurrences: 0,
+            verification_mode: String::new(),
+            matched: Vec::new(),
+        };
+
+        let mut out = open_output(&path, 0).unwrap();
+        let kept = append_record(&mut out, &record).unwrap();
+        append_record(&mut out, &record).unwrap();
+        drop(out);
+
+        // The checkpoint only covered the first record
+        let mut out = open_output(&path, kept).unwrap();
+        append_record(&mut out, &record).unwrap();
+        let lines = fs::read_to_string(&path).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        assert!(lines.lines().all(|l| l.contains("\"block_number\":100")));
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let mut cp = Checkpoint::new(&args(PathBuf::from("out")));
+        cp.next_block = 150;
+        cp.matched_blocks = 3;
+        let bar = render_bar(&cp, 10, Duration::from_secs(5));
+        assert!(bar.starts_with("[###############---------------]  50.0% 50/100 blocks"));
+        assert!(bar.ends_with("3 matches · 2.0 blk/s · ETA 25s"));
+        assert_eq!(format_duration(3725.0), "1h02m");
+    }
+}
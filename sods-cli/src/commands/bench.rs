@@ -0,0 +1,463 @@
+//! Bench command implementation.
+//!
+//! Measures the stages of a verification against live RPC providers so
+//! users can pick providers and modes from data: per-provider latency,
+//! receipts fetch time, receipt trie validation, BMT construction and
+//! end-to-end verification throughput.
+
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::time::timeout;
+
+use crate::config::get_chain;
+use crate::output;
+use sods_core::BehavioralMerkleTree;
+use sods_verifier::header_anchor::{extract_logs_from_receipts, verify_receipts_against_header};
+use sods_verifier::rpc::RpcClient;
+use sods_verifier::BlockVerifier;
+
+/// Most blocks a single bench run scans.
+const MAX_BLOCKS: u64 = 200;
+
+/// Verification mode for the end-to-end stage.
+#[derive(Debug, Clone, Copy, ValueEnum, Default, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BenchMode {
+    /// Header-anchored verification (receipts checked against the header)
+    #[default]
+    Trustless,
+    /// Logs fetched directly, no header anchoring
+    Rpc,
+}
+
+/// Arguments for the bench command.
+#[derive(Args)]
+pub struct BenchArgs {
+    /// Blockchain chain (sepolia, ethereum, base, arbitrum)
+    #[arg(short, long, default_value = "sepolia")]
+    pub chain: String,
+
+    /// Number of recent blocks to scan (max 200)
+    #[arg(short, long, default_value = "50")]
+    pub blocks: u64,
+
+    /// Provider to benchmark (repeatable; defaults to the chain's providers)
+    #[arg(long = "rpc-url")]
+    pub rpc_urls: Vec<String>,
+
+    /// Latency samples per provider
+    #[arg(long, default_value = "5")]
+    pub samples: usize,
+
+    /// Symbol verified in the end-to-end stage
+    #[arg(long, default_value = "Tf")]
+    pub symbol: String,
+
+    /// Verification mode for the end-to-end stage
+    #[arg(short, long, default_value = "trustless")]
+    pub mode: BenchMode,
+
+    /// Timeout per RPC operation in seconds
+    #[arg(long, default_value = "30")]
+    pub timeout: u64,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Distribution of a set of timings, in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TimingSummary {
+    pub count: usize,
+    pub min_ms: f64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl TimingSummary {
+    /// Summarizes `samples`; all zero if there are none.
+    pub fn from_durations(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        let mut ms: Vec<f64> = samples.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(f64::total_cmp);
+        // Nearest-rank percentile
+        let rank = |p: f64| ms[((p * ms.len() as f64).ceil() as usize).clamp(1, ms.len()) - 1];
+        Self {
+            count: ms.len(),
+            min_ms: ms[0],
+            mean_ms: ms.iter().sum::<f64>() / ms.len() as f64,
+            p50_ms: rank(0.5),
+            p95_ms: rank(0.95),
+            max_ms: ms[ms.len() - 1],
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ProviderReport {
+    url: String,
+    latency: TimingSummary,
+    errors: usize,
+    head_block: Option<u64>,
+}
+
+#[derive(Serialize, Default)]
+struct StageReport {
+    receipts_fetch: TimingSummary,
+    trie_validation: TimingSummary,
+    bmt_build: TimingSummary,
+    receipts_total: usize,
+    symbols_total: usize,
+    /// Blocks whose receipts did not match the header's receiptsRoot
+    invalid_roots: usize,
+    errors: usize,
+}
+
+#[derive(Serialize, Default)]
+struct EndToEndReport {
+    verifications: TimingSummary,
+    blocks_per_sec: f64,
+    verified: usize,
+    errors: usize,
+}
+
+#[derive(Serialize)]
+struct BenchReport {
+    chain: String,
+    mode: BenchMode,
+    /// Provider used for the block stages (lowest median latency)
+    provider: Option<String>,
+    block_range: Option<(u64, u64)>,
+    providers: Vec<ProviderReport>,
+    stages: StageReport,
+    end_to_end: EndToEndReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Runs `fut`, returning its elapsed time, or `None` on error or timeout.
+async fn timed<T, E>(
+    limit: Duration,
+    fut: impl Future<Output = Result<T, E>>,
+) -> Option<(T, Duration)> {
+    let start = Instant::now();
+    match timeout(limit, fut).await {
+        Ok(Ok(value)) => Some((value, start.elapsed())),
+        _ => None,
+    }
+}
+
+async fn bench_provider(url: &str, samples: usize, limit: Duration) -> ProviderReport {
+    let mut report = ProviderReport {
+        url: url.to_string(),
+        latency: TimingSummary::default(),
+        errors: 0,
+        head_block: None,
+    };
+    let client = match RpcClient::new(&[url.to_string()]) {
+        Ok(c) => c,
+        Err(_) => {
+            report.errors = samples;
+            return report;
+        }
+    };
+
+    let mut latencies = Vec::with_capacity(samples);
+    for _ in 0..samples {
+        match timed(limit, client.get_latest_block()).await {
+            Some((head, elapsed)) => {
+                report.head_block = Some(head);
+                latencies.push(elapsed);
+            }
+            None => report.errors += 1,
+        }
+    }
+    report.latency = TimingSummary::from_durations(&latencies);
+    report
+}
+
+async fn bench_stages(
+    verifier: &BlockVerifier,
+    blocks: &[u64],
+    limit: Duration,
+    progress: bool,
+) -> StageReport {
+    let rpc = verifier.rpc_client();
+    let mut report = StageReport::default();
+    let (mut fetch, mut trie, mut bmt) = (Vec::new(), Vec::new(), Vec::new());
+
+    for (i, &block) in blocks.iter().enumerate() {
+        if progress {
+            output::info(&format!(
+                "Block {} ({}/{}): receipts, trie, BMT",
+                block,
+                i + 1,
+                blocks.len()
+            ));
+        }
+        let Some((header, _)) = timed(limit, rpc.fetch_block_header(block)).await else {
+            report.errors += 1;
+            continue;
+        };
+        let Some((receipts, elapsed)) = timed(limit, rpc.fetch_block_receipts(block)).await else {
+            report.errors += 1;
+            continue;
+        };
+        fetch.push(elapsed);
+        report.receipts_total += receipts.len();
+
+        let start = Instant::now();
+        let validation = verify_receipts_against_header(&receipts, &header);
+        trie.push(start.elapsed());
+        if !validation.is_valid {
+            report.invalid_roots += 1;
+        }
+
+        let start = Instant::now();
+        let symbols: Vec<_> = extract_logs_from_receipts(&receipts)
+            .iter()
+            .flat_map(|log| verifier.dictionary().parse_log_all(log))
+            .collect();
+        report.symbols_total += symbols.len();
+        if !symbols.is_empty() {
+            let _ = BehavioralMerkleTree::new(symbols).root();
+        }
+        bmt.push(start.elapsed());
+    }
+
+    report.receipts_fetch = TimingSummary::from_durations(&fetch);
+    report.trie_validation = TimingSummary::from_durations(&trie);
+    report.bmt_build = TimingSummary::from_durations(&bmt);
+    report
+}
+
+async fn bench_end_to_end(
+    verifier: &BlockVerifier,
+    symbol: &str,
+    blocks: &[u64],
+    limit: Duration,
+    progress: bool,
+) -> EndToEndReport {
+    let mut report = EndToEndReport::default();
+    let mut durations = Vec::with_capacity(blocks.len());
+
+    if progress {
+        output::info(&format!(
+            "Verifying '{}' end to end in {} blocks",
+            symbol,
+            blocks.len()
+        ));
+    }
+    let start = Instant::now();
+    for &block in blocks {
+        match timed(limit, verifier.verify_symbol_in_block(symbol, block)).await {
+            Some((result, elapsed)) => {
+                durations.push(elapsed);
+                if result.is_verified {
+                    report.verified += 1;
+                }
+            }
+            None => report.errors += 1,
+        }
+    }
+    let total = start.elapsed().as_secs_f64();
+
+    report.verifications = TimingSummary::from_durations(&durations);
+    if total > 0.0 {
+        report.blocks_per_sec = durations.len() as f64 / total;
+    }
+    report
+}
+
+fn fail(args: &BenchArgs, msg: String) -> i32 {
+    if args.json {
+        let report = BenchReport {
+            chain: args.chain.clone(),
+            mode: args.mode,
+            provider: None,
+            block_range: None,
+            providers: Vec::new(),
+            stages: StageReport::default(),
+            end_to_end: EndToEndReport::default(),
+            error: Some(msg),
+        };
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        output::error(&msg);
+    }
+    1
+}
+
+fn print_timing(label: &str, timing: &TimingSummary) {
+    if timing.count == 0 {
+        output::kv(label, "no samples");
+        return;
+    }
+    output::kv(
+        label,
+        &format!(
+            "mean {:.1}ms  p50 {:.1}ms  p95 {:.1}ms  max {:.1}ms  (n={})",
+            timing.mean_ms, timing.p50_ms, timing.p95_ms, timing.max_ms, timing.count
+        ),
+    );
+}
+
+fn print_report(report: &BenchReport) {
+    output::header(&format!("RPC Providers ({})", report.chain));
+    for provider in &report.providers {
+        print_timing(&provider.url, &provider.latency);
+        if provider.errors > 0 {
+            output::warning(&format!(
+                "{}: {} failed requests",
+                provider.url, provider.errors
+            ));
+        }
+    }
+
+    if let (Some(url), Some((from, to))) = (&report.provider, report.block_range) {
+        output::header(&format!("Block Stages (blocks {}-{}, {})", from, to, url));
+        print_timing("Receipts fetch", &report.stages.receipts_fetch);
+        print_timing("Trie validation", &report.stages.trie_validation);
+        print_timing("BMT build", &report.stages.bmt_build);
+        output::kv(
+            "Receipts / symbols",
+            &format!(
+                "{} / {}",
+                report.stages.receipts_total, report.stages.symbols_total
+            ),
+        );
+        if report.stages.invalid_roots > 0 {
+            output::warning(&format!(
+                "{} blocks failed receipts root validation",
+                report.stages.invalid_roots
+            ));
+        }
+
+        output::header(&format!("End to End ({:?})", report.mode));
+        print_timing("Verification", &report.end_to_end.verifications);
+        output::kv(
+            "Throughput",
+            &format!("{:.2} blocks/s", report.end_to_end.blocks_per_sec),
+        );
+        output::kv(
+            "Verified",
+            &format!(
+                "{} of {} blocks",
+                report.end_to_end.verified, report.end_to_end.verifications.count
+            ),
+        );
+    }
+
+    let errors = report.stages.errors + report.end_to_end.errors;
+    if errors > 0 {
+        output::warning(&format!("{} block operations failed or timed out", errors));
+    }
+    println!();
+}
+
+pub async fn run(args: BenchArgs) -> i32 {
+    let Some(chain_config) = get_chain(&args.chain) else {
+        return fail(&args, format!("Unknown chain: '{}'", args.chain));
+    };
+    let blocks = match args.blocks {
+        0 => return fail(&args, "Block count must be > 0.".to_string()),
+        n if n > MAX_BLOCKS => {
+            if !args.json {
+                output::warning(&format!("Max block count is {}. Capping.", MAX_BLOCKS));
+            }
+            MAX_BLOCKS
+        }
+        n => n,
+    };
+    let urls: Vec<String> = if args.rpc_urls.is_empty() {
+        chain_config
+            .rpc_urls
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    } else {
+        args.rpc_urls.clone()
+    };
+    let limit = Duration::from_secs(args.timeout);
+    let progress = !args.json;
+
+    // 1. Provider latency
+    let mut providers = Vec::with_capacity(urls.len());
+    for url in &urls {
+        if progress {
+            output::info(&format!("Measuring latency of {}", url));
+        }
+        providers.push(bench_provider(url, args.samples.max(1), limit).await);
+    }
+
+    let best = providers
+        .iter()
+        .filter(|p| p.latency.count > 0)
+        .min_by(|a, b| a.latency.p50_ms.total_cmp(&b.latency.p50_ms));
+    let Some(best) = best else {
+        return fail(&args, "No provider responded.".to_string());
+    };
+    let provider = best.url.clone();
+    let head = best.head_block.unwrap_or(0);
+    let from = head.saturating_sub(blocks - 1);
+    let block_numbers: Vec<u64> = (from..=head).collect();
+
+    // 2. Block stages and 3. end to end, on the fastest provider
+    let url = [provider.clone()];
+    let verifier = match args.mode {
+        BenchMode::Trustless => BlockVerifier::new(&url),
+        BenchMode::Rpc => BlockVerifier::new_rpc_only(&url),
+    };
+    let verifier = match verifier {
+        Ok(v) => v,
+        Err(e) => return fail(&args, format!("Failed to initialize verifier: {}", e)),
+    };
+
+    let stages = bench_stages(&verifier, &block_numbers, limit, progress).await;
+    let end_to_end =
+        bench_end_to_end(&verifier, &args.symbol, &block_numbers, limit, progress).await;
+
+    let report = BenchReport {
+        chain: args.chain.clone(),
+        mode: args.mode,
+        provider: Some(provider),
+        block_range: Some((from, head)),
+        providers,
+        stages,
+        end_to_end,
+        error: None,
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    } else {
+        print_report(&report);
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing_summary() {
+        let samples: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+        let summary = TimingSummary::from_durations(&samples);
+        assert_eq!(summary.count, 20);
+        assert_eq!(summary.min_ms, 1.0);
+        assert_eq!(summary.max_ms, 20.0);
+        assert_eq!(summary.p50_ms, 10.0);
+        assert_eq!(summary.p95_ms, 19.0);
+        assert!((summary.mean_ms - 10.5).abs() < 1e-9);
+
+        assert_eq!(TimingSummary::from_durations(&[]), TimingSummary::default());
+    }
+}
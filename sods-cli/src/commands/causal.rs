@@ -0,0 +1,479 @@
+//! Manage causal agent histories and behavioral proofs.
+//!
+//! Histories live in a JSON event store (`~/.sods/causal/events.json` by
+//! default) that is replayed through `CausalEventRecorder` on every load, so
+//! ordering rules are enforced exactly as in library use.
+
+use crate::output;
+use clap::{Args, Subcommand};
+use ethers::types::{Address, H256};
+use serde::{Deserialize, Serialize};
+use sods_causal::{
+    generate_behavioral_proof, AgentBehaviorPattern, CausalBehavioralProof, CausalEvent,
+    CausalEventRecorder, FreshnessPolicy,
+};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Args)]
+pub struct CausalArgs {
+    /// Path to the event store
+    #[arg(long, global = true)]
+    pub store: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: CausalCommands,
+}
+
+#[derive(Subcommand)]
+pub enum CausalCommands {
+    /// Record an event (from flags or a JSON file)
+    Record(RecordArgs),
+
+    /// Show an agent's recorded history and causal root
+    History {
+        /// Agent address
+        #[arg(short, long)]
+        agent: String,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Generate a behavioral proof from an agent's history
+    Prove(ProveArgs),
+
+    /// Verify a behavioral proof JSON file
+    Verify {
+        /// Path to the proof (use '-' for stdin)
+        proof: PathBuf,
+
+        /// Verification time (defaults to now)
+        #[arg(long)]
+        now: Option<u64>,
+
+        /// Reject proofs older than this many seconds
+        #[arg(long)]
+        max_age: Option<u64>,
+
+        /// Reject proofs covering fewer events than this
+        #[arg(long)]
+        min_height: Option<u64>,
+
+        /// Address the proof must be signed by (defaults to the agent)
+        #[arg(long)]
+        signer: Option<String>,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Args)]
+pub struct RecordArgs {
+    /// JSON file with one event or an array of events (use '-' for stdin)
+    #[arg(long, conflicts_with_all = ["agent", "event_type"])]
+    pub file: Option<PathBuf>,
+
+    /// Agent address
+    #[arg(short, long, required_unless_present = "file")]
+    pub agent: Option<String>,
+
+    /// Semantic event type (e.g. task_executed)
+    #[arg(short, long, required_unless_present = "file")]
+    pub event_type: Option<String>,
+
+    /// Event result
+    #[arg(short, long, default_value = "success")]
+    pub result: String,
+
+    /// Optional task identifier
+    #[arg(long)]
+    pub task_id: Option<String>,
+
+    /// Continue the agent's latest transaction instead of starting a new nonce
+    #[arg(long)]
+    pub same_tx: bool,
+
+    /// Event timestamp (defaults to now)
+    #[arg(long)]
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Args)]
+pub struct ProveArgs {
+    /// Agent address
+    #[arg(short, long)]
+    pub agent: String,
+
+    /// Event type the pattern matches
+    #[arg(short, long)]
+    pub event_type: String,
+
+    /// Result the pattern matches
+    #[arg(short, long, default_value = "success")]
+    pub result: String,
+
+    /// Minimum matching events
+    #[arg(long, default_value_t = 1)]
+    pub min_count: u32,
+
+    /// Maximum matching events
+    #[arg(long)]
+    pub max_count: Option<u32>,
+
+    /// Only consider events from the last N seconds
+    #[arg(long)]
+    pub window: Option<u64>,
+
+    /// Proof generation time (defaults to now)
+    #[arg(long)]
+    pub now: Option<u64>,
+
+    /// Proof lifetime in seconds
+    #[arg(long)]
+    pub valid_for: Option<u64>,
+
+    /// Name of the agent's signing key, binding the freshness fields (see `sods keys`)
+    #[arg(long)]
+    pub signing_key: Option<String>,
+
+    /// Write the proof to a file instead of stdout
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// On-disk event store.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct EventStore {
+    events: Vec<CausalEvent>,
+}
+
+impl EventStore {
+    fn default_path() -> PathBuf {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        home.join(".sods").join("causal").join("events.json")
+    }
+
+    /// Load the store and replay it into a recorder.
+    fn load(path: &Path) -> Result<CausalEventRecorder, String> {
+        let store: Self = match std::fs::read_to_string(path) {
+            Ok(c) => serde_json::from_str(&c)
+                .map_err(|e| format!("Corrupt event store {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+
+        let mut recorder = CausalEventRecorder::new();
+        for event in store.events {
+            recorder
+                .record_event(event)
+                .map_err(|e| format!("Invalid event store {}: {}", path.display(), e))?;
+        }
+        Ok(recorder)
+    }
+
+    fn save(path: &Path, recorder: &CausalEventRecorder) -> Result<(), String> {
+        let mut agents = recorder.agent_ids();
+        agents.sort();
+        let events = agents
+            .iter()
+            .filter_map(|a| recorder.get_agent_events(a))
+            .flatten()
+            .cloned()
+            .collect();
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let json = serde_json::to_string_pretty(&Self { events }).map_err(|e| e.to_string())?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+pub fn run(args: CausalArgs) -> i32 {
+    let store = args.store.unwrap_or_else(EventStore::default_path);
+    let result = match args.command {
+        CausalCommands::Record(record) => run_record(&store, record),
+        CausalCommands::History { agent, json } => run_history(&store, &agent, json),
+        CausalCommands::Prove(prove) => run_prove(&store, prove),
+        CausalCommands::Verify {
+            proof,
+            now,
+            max_age,
+            min_height,
+            signer,
+            json,
+        } => {
+            let mut policy = FreshnessPolicy::new();
+            if let Some(secs) = max_age {
+                policy = policy.with_max_age(secs);
+            }
+            if let Some(height) = min_height {
+                policy = policy.with_min_root_height(height);
+            }
+            if let Some(signer) = signer {
+                match parse_agent(&signer) {
+                    Ok(addr) => policy = policy.with_signer(addr),
+                    Err(e) => {
+                        output::error(&e);
+                        return 1;
+                    }
+                }
+            }
+            run_verify(&proof, now.unwrap_or_else(now_secs), &policy, json)
+        }
+    };
+
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            output::error(&e);
+            1
+        }
+    }
+}
+
+fn run_record(store: &Path, args: RecordArgs) -> Result<i32, String> {
+    // Held until the store is saved, so `sods gc` cannot compact in between
+    let _lock = crate::retention::lock_store(store)
+        .map_err(|e| format!("Failed to lock {}: {}", store.display(), e))?;
+    let mut recorder = EventStore::load(store)?;
+
+    let events = match &args.file {
+        Some(file) => {
+            let content = read_input(file)?;
+            let value: serde_json::Value =
+                serde_json::from_str(&content).map_err(|e| format!("Invalid JSON: {}", e))?;
+            let events = if value.is_array() {
+                serde_json::from_value(value)
+            } else {
+                serde_json::from_value(value).map(|e| vec![e])
+            };
+            events.map_err(|e| format!("Invalid event: {}", e))?
+        }
+        None => vec![event_from_flags(&recorder, &args)?],
+    };
+
+    let count = events.len();
+    for event in events {
+        let position = (event.nonce, event.sequence_index);
+        recorder.record_event(event).map_err(|e| {
+            format!(
+                "Rejected event at nonce {}, sequence {}: {}",
+                position.0, position.1, e
+            )
+        })?;
+    }
+
+    EventStore::save(store, &recorder)?;
+    output::success(&format!("Recorded {} event(s)", count));
+    output::kv("Store", &store.display().to_string());
+    Ok(0)
+}
+
+fn event_from_flags(
+    recorder: &CausalEventRecorder,
+    args: &RecordArgs,
+) -> Result<CausalEvent, String> {
+    let agent = parse_agent(args.agent.as_deref().unwrap_or_default())?;
+    let last = recorder
+        .get_agent_events(&agent)
+        .and_then(|events| events.last());
+
+    let (nonce, sequence_index) = match (last, args.same_tx) {
+        (Some(last), true) => (last.nonce, last.sequence_index + 1),
+        (Some(last), false) => (last.nonce + 1, 0),
+        (None, _) => (0, 0),
+    };
+
+    let mut builder = CausalEvent::builder()
+        .agent_id(agent)
+        .nonce(nonce)
+        .sequence_index(sequence_index)
+        .event_type(args.event_type.clone().unwrap_or_default())
+        .result(&args.result)
+        .timestamp(args.timestamp.unwrap_or_else(now_secs));
+    if let Some(task_id) = &args.task_id {
+        builder = builder.task_id(task_id);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+fn run_history(store: &Path, agent: &str, json: bool) -> Result<i32, String> {
+    let agent = parse_agent(agent)?;
+    let recorder = EventStore::load(store)?;
+    let events = recorder
+        .get_agent_events(&agent)
+        .cloned()
+        .unwrap_or_default();
+    let root = if events.is_empty() {
+        H256::zero()
+    } else {
+        recorder
+            .build_merkle_tree(&agent)
+            .map_err(|e| e.to_string())?
+            .root
+    };
+
+    if json {
+        let out = serde_json::json!({
+            "agent": agent,
+            "root": root,
+            "events": events,
+        });
+        println!("{}", serde_json::to_string_pretty(&out).unwrap_or_default());
+        return Ok(0);
+    }
+
+    if events.is_empty() {
+        output::info(&format!("No events recorded for {:?}", agent));
+        return Ok(0);
+    }
+
+    output::header(&format!("Causal history of {:?}", agent));
+    output::kv("Events", &events.len().to_string());
+    output::kv("Root", &format!("{:?}", root));
+    println!();
+    println!(
+        "{:<8} {:<5} {:<24} {:<10} {:<12}",
+        "Nonce", "Seq", "Event", "Result", "Timestamp"
+    );
+    for e in &events {
+        println!(
+            "{:<8} {:<5} {:<24} {:<10} {:<12}",
+            e.nonce, e.sequence_index, e.event_type, e.result, e.timestamp
+        );
+    }
+    Ok(0)
+}
+
+fn run_prove(store: &Path, args: ProveArgs) -> Result<i32, String> {
+    let agent = parse_agent(&args.agent)?;
+    let recorder = EventStore::load(store)?;
+    let tree = recorder
+        .build_merkle_tree(&agent)
+        .map_err(|e| e.to_string())?;
+
+    let pattern = AgentBehaviorPattern {
+        event_type: args.event_type,
+        result_filter: args.result,
+        min_count: args.min_count,
+        max_count: args.max_count,
+        time_window: args.window.map(Duration::from_secs),
+    };
+
+    let now = args.now.unwrap_or_else(now_secs);
+    let mut proof = generate_behavioral_proof(&tree, &pattern, now)
+        .map_err(|e| format!("Pattern not satisfied: {}", e))?;
+    if let Some(secs) = args.valid_for {
+        proof = proof.with_validity(secs);
+    }
+    if let Some(spec) = args.signing_key.as_deref() {
+        let wallet = crate::keys::load_wallet(spec)?;
+        proof = proof.sign(&wallet).map_err(|e| e.to_string())?;
+    }
+
+    let json = serde_json::to_string_pretty(&proof).map_err(|e| e.to_string())?;
+    match &args.output {
+        Some(path) => {
+            std::fs::write(path, json)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            output::success(&format!(
+                "Proof over {} event(s) written to {}",
+                proof.matched_events.len(),
+                path.display()
+            ));
+        }
+        None => println!("{}", json),
+    }
+    Ok(0)
+}
+
+fn run_verify(path: &Path, now: u64, policy: &FreshnessPolicy, json: bool) -> Result<i32, String> {
+    let proof: CausalBehavioralProof =
+        serde_json::from_str(&read_input(path)?).map_err(|e| format!("Invalid proof: {}", e))?;
+
+    let valid = proof.verify(now);
+    let signed = proof.is_signed_by(policy);
+    let fresh = signed && proof.is_fresh(now, policy);
+
+    if json {
+        let out = serde_json::json!({
+            "valid": valid && fresh,
+            "proof_valid": valid,
+            "signed": signed,
+            "fresh": fresh,
+            "agent_root": proof.agent_root,
+            "matched_events": proof.matched_events.len(),
+        });
+        println!("{}", serde_json::to_string_pretty(&out).unwrap_or_default());
+    } else if valid && fresh {
+        output::success("Behavioral proof verified");
+        output::kv("Root", &format!("{:?}", proof.agent_root));
+        output::kv("Matched", &proof.matched_events.len().to_string());
+    } else if !valid {
+        output::error("Behavioral proof is invalid or expired");
+    } else if !signed {
+        output::error("Behavioral proof is not signed by the agent");
+    } else {
+        output::error("Behavioral proof is stale under the given policy");
+    }
+
+    Ok(if valid && fresh { 0 } else { 1 })
+}
+
+fn parse_agent(s: &str) -> Result<Address, String> {
+    s.parse()
+        .map_err(|_| format!("Invalid agent address: {}", s))
+}
+
+fn read_input(path: &Path) -> Result<String, String> {
+    if path.as_os_str() == "-" {
+        let mut buf = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)
+            .map_err(|e| e.to_string())?;
+        Ok(buf)
+    } else {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+    }
+}
+
+fn now_secs() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_roundtrip_preserves_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.json");
+        let agent = Address::repeat_byte(7);
+
+        let mut recorder = CausalEventRecorder::new();
+        for nonce in 0..3 {
+            let event = CausalEvent::builder()
+                .agent_id(agent)
+                .nonce(nonce)
+                .event_type("task_executed")
+                .result("success")
+                .build()
+                .unwrap();
+            recorder.record_event(event).unwrap();
+        }
+        EventStore::save(&path, &recorder).unwrap();
+
+        let reloaded = EventStore::load(&path).unwrap();
+        assert_eq!(reloaded.get_agent_events(&agent).unwrap().len(), 3);
+        assert_eq!(
+            reloaded.build_merkle_tree(&agent).unwrap().root,
+            recorder.build_merkle_tree(&agent).unwrap().root
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! Shell completions and man pages.
+//!
+//! Both are generated from the clap command tree, so they never drift from
+//! the flags the binary actually accepts. The tree lives in the `sods`
+//! binary, which passes it in.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Command};
+use clap_complete::Shell;
+
+use crate::output;
+
+#[derive(Args, Debug)]
+pub struct CompletionsArgs {
+    /// Shell to print a completion script for
+    #[arg(value_enum, required_unless_present = "man_dir")]
+    pub shell: Option<Shell>,
+
+    /// Write man pages for `sods` and every subcommand to this directory
+    #[arg(long, value_name = "DIR", conflicts_with = "shell")]
+    pub man_dir: Option<PathBuf>,
+}
+
+/// Run the completions command against the CLI definition `cmd`.
+pub fn run(args: CompletionsArgs, mut cmd: Command) -> i32 {
+    if let Some(dir) = args.man_dir {
+        return match write_man_pages(&cmd, &dir) {
+            Ok(count) => {
+                output::success(&format!("Wrote {} man pages to {}", count, dir.display()));
+                0
+            }
+            Err(e) => {
+                output::error(&format!("Failed to write man pages: {}", e));
+                1
+            }
+        };
+    }
+
+    let Some(shell) = args.shell else {
+        return 1;
+    };
+    let name = cmd.get_name().to_string();
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut cmd, name, &mut script);
+    // A closed pipe (`sods completions bash | head`) is not an error
+    match io::stdout().write_all(&script) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            output::error(&format!("Failed to write completions: {}", e));
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Render `sods.1` plus one `sods-<sub>[-<sub>].1` per subcommand into `dir`.
+///
+/// Returns the number of pages written.
+pub fn write_man_pages(cmd: &Command, dir: &Path) -> io::Result<usize> {
+    fs::create_dir_all(dir)?;
+    let mut cmd = cmd.clone();
+    // Building assigns subcommands their git-style display names
+    // (`sods-daemon-start`) and full usage lines
+    cmd.build();
+    write_page(&cmd, dir)
+}
+
+fn write_page(cmd: &Command, dir: &Path) -> io::Result<usize> {
+    let name = cmd.get_display_name().unwrap_or_else(|| cmd.get_name());
+    let mut buffer = Vec::new();
+    clap_mangen::Man::new(cmd.clone()).render(&mut buffer)?;
+    fs::write(dir.join(format!("{}.1", name)), buffer)?;
+
+    let mut written = 1;
+    for sub in cmd.get_subcommands() {
+        if sub.is_hide_set() || sub.get_name() == "help" {
+            continue;
+        }
+        written += write_page(sub, dir)?;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Arg;
+
+    fn sample() -> Command {
+        Command::new("sods")
+            .about("test")
+            .subcommand(
+                Command::new("daemon")
+                    .subcommand(Command::new("start").arg(Arg::new("chain").long("chain"))),
+            )
+            .subcommand(Command::new("chains"))
+    }
+
+    #[test]
+    fn test_man_pages_cover_nested_subcommands() {
+        let dir = tempfile::tempdir().unwrap();
+        let count = write_man_pages(&sample(), dir.path()).unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "sods-chains.1",
+                "sods-daemon-start.1",
+                "sods-daemon.1",
+                "sods.1"
+            ]
+        );
+        assert_eq!(count, names.len());
+
+        let start = fs::read_to_string(dir.path().join("sods-daemon-start.1")).unwrap();
+        assert!(start.contains("sods\\-daemon\\-start"));
+        assert!(start.contains("chain"));
+    }
+}
@@ -1,4 +1,9 @@
-#![allow(dead_code, clippy::type_complexity, clippy::too_many_arguments)]
+#![allow(
+    dead_code,
+    clippy::type_complexity,
+    clippy::too_many_arguments,
+    clippy::large_enum_variant
+)]
 #[cfg(feature = "metrics")]
 use crate::monitoring::metrics::AgentMetrics;
 use clap::{Args, Subcommand};
@@ -9,6 +14,10 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{mpsc::unbounded_channel, mpsc::UnboundedSender, RwLock};
+use tokio_tungstenite::tungstenite::handshake::server::{
+    ErrorResponse, Request as HandshakeRequest, Response as HandshakeResponse,
+};
+use tokio_tungstenite::tungstenite::http::StatusCode;
 use tokio_tungstenite::tungstenite::protocol::Message;
 use tracing::{debug, warn};
 use uuid::Uuid;
@@ -17,16 +26,33 @@ type AgentMetrics = ();
 
 use dirs;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[cfg(unix)]
 use daemonize::Daemonize;
 #[cfg(unix)]
-use std::fs::File;
+use sysinfo::{Pid, System};
 
+use crate::auth::{ApiAuth, Scope};
+use crate::control::{self, ControlRequest, ControlResponse, DaemonStatus, TargetStatus};
+use crate::correlation::new_alert_id;
+use crate::digest::AlertDigest;
+use crate::dry_run::{AlertChannel, DryRunSink};
 use crate::output;
+use crate::proof_export::{MatchBundle, ProofExporter};
+use crate::retention;
+use crate::siem::{SiemAlert, SiemFormat, SiemSink, SiemTarget};
+use crate::webhook_queue::{RetryPolicy, SharedQueue, WebhookQueue};
 use serde_json::json;
-use sods_p2p::{SodsPeer, ThreatRule};
+use sods_core::clock::Clock;
+use sods_core::fingerprint::{FingerprintDrift, FingerprintHistory, FingerprintTracker};
+use sods_core::PatternStreamMatcher;
+use sods_p2p::{
+    BucketLimit, FederationConfig, FederationPolicy, MeshLink, NetworkConfig, NodeRole,
+    PreSharedKey, RateLimitConfig, ReputationSnapshot, ReputationTracker, SeenCache, SodsPeer,
+    ThreatBridge, ThreatRule,
+};
+use sods_verifier::block_time::{PollSchedule, BLOCK_TIME_SPAN};
 
 /// Arguments for the daemon command.
 #[derive(Args)]
@@ -47,7 +73,8 @@ pub enum DaemonCommands {
         #[arg(short, long, default_value = "sepolia")]
         chain: String,
 
-        /// Polling interval
+        /// Polling interval (e.g. 12s), or `auto` to poll about once per
+        /// block of the chain's estimated block time
         #[arg(short, long, default_value = "30s")]
         interval: String,
 
@@ -63,6 +90,20 @@ pub enum DaemonCommands {
         #[arg(long)]
         webhook_url: Option<String>,
 
+        /// Delivery attempts per webhook payload before it is moved to
+        /// ~/.sods/webhook-dlq.jsonl
+        #[arg(long, value_name = "N", default_value_t = crate::webhook_queue::DEFAULT_MAX_ATTEMPTS)]
+        webhook_max_attempts: u32,
+
+        /// Also send every alert to a syslog collector (udp://host:514 or
+        /// tcp://host:601), including alerts held back by a digest
+        #[arg(long, value_name = "URL")]
+        siem: Option<SiemTarget>,
+
+        /// SIEM message format: cef or syslog (RFC 5424)
+        #[arg(long, default_value = "cef", requires = "siem")]
+        siem_format: SiemFormat,
+
         /// Load behavioral threat patterns from a public feed (optional)
         #[arg(long)]
         threat_feed: Option<String>,
@@ -82,11 +123,183 @@ pub enum DaemonCommands {
         /// Start a Prometheus _metrics server (e.g. 9090)
         #[arg(long)]
         metrics_port: Option<u16>,
+
+        /// Omit block explorer links from alerts and webhooks
+        #[arg(long)]
+        no_links: bool,
+
+        /// Run in the foreground and log alerts instead of sending webhooks,
+        /// notifications or WebSocket messages
+        #[arg(long)]
+        dry_run: bool,
+
+        /// File receiving dry-run alerts (default: ~/.sods/dry-run.jsonl)
+        #[arg(long, requires = "dry_run")]
+        dry_run_log: Option<PathBuf>,
+
+        /// Simulate: run rule expiry, garbage collection and P2P timeouts
+        /// on a clock starting at this Unix time (dry runs only)
+        #[arg(long, requires = "dry_run")]
+        clock_start: Option<u64>,
+
+        /// Simulate: run that clock this many times faster than real time,
+        /// e.g. 3600 to expire a 24h rule in 24s (dry runs only)
+        #[arg(long, requires = "dry_run")]
+        clock_speed: Option<u32>,
+
+        /// Batch alerts below --digest-passthrough into summaries sent every
+        /// interval (e.g. 1h, 30m)
+        #[arg(long)]
+        digest_interval: Option<String>,
+
+        /// Longest digest interval; noisy windows double it up to this cap
+        #[arg(long, default_value = "24h")]
+        digest_max_interval: String,
+
+        /// Lowest severity that bypasses the digest
+        #[arg(long, default_value = "critical")]
+        digest_passthrough: String,
+
+        /// Write a proof bundle for every match into this directory
+        #[arg(long, value_name = "DIR")]
+        export_proofs: Option<PathBuf>,
+
+        /// Also write each bundle's proofs as on-chain calldata
+        #[arg(long, requires = "export_proofs")]
+        export_calldata: bool,
+
+        /// Fingerprint this contract's behavior and alert when it drifts
+        /// (repeatable)
+        #[arg(long = "fingerprint", value_name = "CONTRACT")]
+        fingerprints: Vec<ethers_core::types::Address>,
+
+        /// Blocks per fingerprint
+        #[arg(long, value_name = "BLOCKS", default_value_t = sods_core::fingerprint::DEFAULT_WINDOW_BLOCKS)]
+        fingerprint_window: u64,
+
+        /// Distance from the baseline, between 0 and 1, that counts as drift
+        #[arg(long, value_name = "DISTANCE", default_value_t = sods_core::fingerprint::DEFAULT_DRIFT_THRESHOLD)]
+        fingerprint_threshold: f64,
+
+        /// Only connect to these P2P peer IDs (repeatable)
+        #[arg(long = "allow-peer", value_name = "PEER_ID")]
+        allow_peers: Vec<String>,
+
+        /// Never connect to these P2P peer IDs (repeatable)
+        #[arg(long = "deny-peer", value_name = "PEER_ID")]
+        deny_peers: Vec<String>,
+
+        /// Join a private P2P network using this swarm key file
+        #[arg(long, value_name = "PSK_FILE")]
+        private_network: Option<PathBuf>,
+
+        /// Proof requests served per peer per minute
+        #[arg(long, value_name = "N")]
+        peer_proof_rate: Option<u32>,
+
+        /// Puzzle challenges answered per peer per minute
+        #[arg(long, value_name = "N")]
+        peer_puzzle_rate: Option<u32>,
+
+        /// How long to ban peers that keep exceeding their rate (e.g. 10m, 1h)
+        #[arg(long, value_name = "DURATION")]
+        peer_ban_duration: Option<String>,
+
+        /// P2P node role: full, serve-only (no threat gossip) or query-only
+        /// (no listener, serves nothing)
+        #[arg(long, default_value = "full")]
+        role: NodeRole,
+
+        /// Dial this P2P peer on startup (repeatable)
+        #[arg(long = "bootstrap", value_name = "MULTIADDR")]
+        bootstrap_peers: Vec<String>,
+
+        /// Sign P2P proof responses with this stored key (see `sods keys`)
+        /// instead of an ephemeral one
+        #[arg(long, value_name = "NAME")]
+        key: Option<String>,
+
+        /// Bridge threat rules between the private network and the public one
+        #[arg(long, requires = "private_network")]
+        bridge: bool,
+
+        /// Public peer the bridge dials on startup (repeatable)
+        #[arg(
+            long = "bridge-bootstrap",
+            value_name = "MULTIADDR",
+            requires = "bridge"
+        )]
+        bridge_bootstrap: Vec<String>,
+
+        /// Only import public rules by this author (repeatable; default: all)
+        #[arg(
+            long = "bridge-import-author",
+            value_name = "PUBKEY_HEX",
+            requires = "bridge"
+        )]
+        bridge_import_authors: Vec<String>,
+
+        /// Re-publish private rules by this author publicly (repeatable;
+        /// default: none)
+        #[arg(
+            long = "bridge-export-author",
+            value_name = "PUBKEY_HEX",
+            requires = "bridge"
+        )]
+        bridge_export_authors: Vec<String>,
+
+        /// Seed P2P peer reputation from a snapshot exported by another node
+        /// (repeatable; see `sods daemon export-reputation`)
+        #[arg(
+            long = "import-reputation",
+            value_name = "FILE",
+            requires = "reputation_authors"
+        )]
+        import_reputation: Vec<PathBuf>,
+
+        /// Accept reputation snapshots signed by this public key (repeatable)
+        #[arg(long = "reputation-author", value_name = "PUBKEY")]
+        reputation_authors: Vec<String>,
+
+        /// Weight of imported reputation scores, between 0 and 1
+        #[arg(long, default_value_t = 0.5, requires = "import_reputation")]
+        reputation_trust: f32,
     },
     /// Stop the running daemon
     Stop,
-    /// Check daemon status
+    /// Check daemon status, with targets, lag and peers if it is running
     Status,
+    /// Start monitoring a pattern in the running daemon
+    AddTarget {
+        /// Name shown in alerts
+        name: String,
+        /// Behavioral pattern to monitor
+        pattern: String,
+        /// Alert severity
+        #[arg(short, long, default_value = "manual")]
+        severity: String,
+    },
+    /// Stop monitoring a target in the running daemon
+    RemoveTarget {
+        /// Name of the target
+        name: String,
+    },
+    /// Make the running daemon re-read ~/.sods/patterns.toml
+    ReloadConfig,
+    /// Print the running daemon's internal state as JSON
+    DumpState,
+    /// Write the running P2P node's signed reputation snapshot to a file
+    ExportReputation {
+        /// Where to write the snapshot
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// Generate a swarm key for --private-network
+    SwarmKey {
+        /// Where to write the key file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
 }
 
 #[derive(serde::Deserialize, Debug, Clone)]
@@ -108,6 +321,19 @@ pub struct BehavioralAlert {
     pub pattern: String,
     pub symbols: Vec<AlertSymbol>,
     pub alert_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_url: Option<String>,
+    /// Base fee and utilization of the block, plus the matched
+    /// transactions' priority fees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fees: Option<sods_verifier::FeeContext>,
+    /// Sibling blocks and recent reorgs; a match near a reorg is raised
+    /// one severity level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reorg: Option<sods_verifier::ReorgContext>,
+    /// Set on `fingerprint_drift` alerts, which match no pattern
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drift: Option<FingerprintDrift>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,6 +342,15 @@ pub struct AlertSymbol {
     pub from: String,
     pub to: String,
     pub value: String,
+    /// Priority fee per gas (wei) paid by the symbol's transaction
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority_fee_per_gas: Option<u128>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -126,6 +361,7 @@ struct Subscription {
 
 pub struct WebSocketServer {
     port: u16,
+    auth: Arc<ApiAuth>,
     subscribers:
         Arc<RwLock<HashMap<String, (UnboundedSender<Message>, Arc<RwLock<Subscription>>)>>>,
 }
@@ -134,12 +370,19 @@ impl WebSocketServer {
     pub fn new(port: u16) -> Self {
         Self {
             port,
+            auth: Arc::new(ApiAuth::default()),
             subscribers: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Require a key with the `read-alerts` scope to connect (Builder pattern)
+    pub fn with_auth(mut self, auth: Arc<ApiAuth>) -> Self {
+        self.auth = auth;
+        self
+    }
+
     pub async fn start(self: Arc<Self>) {
-        let addr = format!("0.0.0.0:{}", self.port);
+        let addr = self.auth.socket_addr(self.port);
         let listener = match TcpListener::bind(&addr).await {
             Ok(l) => l,
             Err(e) => {
@@ -152,7 +395,11 @@ impl WebSocketServer {
 
         while let Ok((stream, _)) = listener.accept().await {
             let subscribers = self.subscribers.clone();
-            tokio::spawn(Self::handle_connection(stream, subscribers));
+            tokio::spawn(Self::handle_connection(
+                stream,
+                subscribers,
+                self.auth.clone(),
+            ));
         }
     }
 
@@ -161,8 +408,30 @@ impl WebSocketServer {
         subscribers: Arc<
             RwLock<HashMap<String, (UnboundedSender<Message>, Arc<RwLock<Subscription>>)>>,
         >,
+        auth: Arc<ApiAuth>,
     ) {
-        let ws_stream = match tokio_tungstenite::accept_async(stream).await {
+        // The key is checked during the handshake, so a refused client gets
+        // an HTTP error instead of a socket that never sends anything. The
+        // large error type is tungstenite's.
+        #[allow(clippy::result_large_err)]
+        let check_key = |request: &HandshakeRequest, response: HandshakeResponse| {
+            let token = request
+                .headers()
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .and_then(ApiAuth::bearer_token)
+                .or_else(|| request.uri().query().and_then(ApiAuth::query_token));
+            match auth.authorize(token, Scope::ReadAlerts) {
+                Ok(_) => Ok(response),
+                Err(e) => {
+                    let mut refusal = ErrorResponse::new(Some(e.to_string()));
+                    *refusal.status_mut() =
+                        StatusCode::from_u16(e.status()).unwrap_or(StatusCode::UNAUTHORIZED);
+                    Err(refusal)
+                }
+            }
+        };
+        let ws_stream = match tokio_tungstenite::accept_hdr_async(stream, check_key).await {
             Ok(s) => s,
             Err(_) => return,
         };
@@ -253,7 +522,7 @@ pub(crate) struct MonitoringTarget {
 }
 
 fn get_sods_dir() -> PathBuf {
-    let mut path = dirs::home_dir().expect("Failed to get home directory");
+    let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
     path.push(".sods");
     fs::create_dir_all(&path).ok();
     path
@@ -271,6 +540,83 @@ fn get_threat_rules_file() -> PathBuf {
     get_sods_dir().join("threat_rules.json")
 }
 
+fn get_seen_threats_file() -> PathBuf {
+    get_sods_dir().join("seen_threats.json")
+}
+
+/// Time of an alert raised now, on the daemon's clock.
+fn alert_time(clock: &dyn Clock) -> chrono::DateTime<chrono::Utc> {
+    clock.now().into()
+}
+
+/// Apply the retention policies before the daemon starts appending to its
+/// stores again, keeping threat rules until they expire after
+/// `rule_expiry`. Ages are measured at `now`, the daemon's clock. Failures
+/// are reported and do not stop the daemon.
+fn compact_stores(rule_expiry: std::time::Duration, now: std::time::SystemTime) {
+    let stores = match retention::configured_stores(Some(rule_expiry)) {
+        Ok(stores) => stores,
+        Err(e) => {
+            output::warning(&format!(
+                "Skipping compaction, invalid retention config: {}",
+                e
+            ));
+            return;
+        }
+    };
+    let (mut removed, mut freed) = (0, 0);
+    for result in retention::compact_all(&stores, now, false) {
+        match result {
+            Ok(compaction) => {
+                removed += compaction.removed;
+                freed += compaction.freed();
+            }
+            Err(e) => output::warning(&format!("Compaction failed for {}", e)),
+        }
+    }
+    if removed > 0 {
+        output::info(&format!(
+            "Compacted ~/.sods: removed {} record(s), freed {}",
+            removed,
+            retention::format_size(freed)
+        ));
+    }
+}
+
+/// When a threat rule stops being monitored: `expire` after it was created,
+/// so a rule cannot be revived by reloading or replaying it. A timestamp too
+/// large to represent counts as already expired.
+fn rule_expiry(rule: &ThreatRule, expire: std::time::Duration) -> std::time::SystemTime {
+    std::time::UNIX_EPOCH
+        .checked_add(std::time::Duration::from_secs(rule.timestamp))
+        .and_then(|created| created.checked_add(expire))
+        .unwrap_or(std::time::UNIX_EPOCH)
+}
+
+/// Drops targets expired as of `now`, returning how many were dropped.
+fn prune_expired(targets: &mut Vec<MonitoringTarget>, now: std::time::SystemTime) -> usize {
+    let before = targets.len();
+    targets.retain(|t| now < t.expires_at);
+    before - targets.len()
+}
+
+/// Threat rules persisted by an earlier run; an unreadable or malformed
+/// file is reported and treated as empty.
+fn load_persisted_rules(path: &Path) -> Vec<ThreatRule> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            eprintln!("⚠️ Ignoring unreadable {}: {}", path.display(), e);
+            return Vec::new();
+        }
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        eprintln!("⚠️ Ignoring malformed {}: {}", path.display(), e);
+        Vec::new()
+    })
+}
+
 fn get_trusted_keys_file() -> PathBuf {
     get_sods_dir().join("trusted_keys.json")
 }
@@ -279,6 +625,10 @@ fn get_trusted_keys_file() -> PathBuf {
 // Helpers
 // -----------------------------------------------------------------------------
 
+/// Longest duration accepted anywhere in the daemon's configuration.
+const MAX_CONFIG_DURATION: std::time::Duration =
+    std::time::Duration::from_secs(10 * 365 * 24 * 3600);
+
 pub(crate) fn parse_duration(s: &str) -> std::time::Duration {
     let mut num_str = String::new();
     let mut unit = 'h';
@@ -291,13 +641,74 @@ pub(crate) fn parse_duration(s: &str) -> std::time::Duration {
         }
     }
     let val = num_str.parse::<u64>().unwrap_or(24);
-    match unit {
-        'm' => std::time::Duration::from_secs(val * 60),
-        'h' => std::time::Duration::from_secs(val * 3600),
-        _ => std::time::Duration::from_secs(val * 3600),
+    let secs = match unit {
+        'm' => val.saturating_mul(60),
+        _ => val.saturating_mul(3600),
+    };
+    std::time::Duration::from_secs(secs).min(MAX_CONFIG_DURATION)
+}
+
+/// How long the daemon waits for the chain head before starting without it.
+const INITIAL_HEAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Polling interval given as `<N>s`; anything else, including zero, means 30s.
+/// `auto` is resolved by `poll_schedule` before this is consulted.
+fn parse_interval(s: &str) -> std::time::Duration {
+    let secs = s
+        .strip_suffix('s')
+        .and_then(|n| n.parse::<u64>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(30);
+    std::time::Duration::from_secs(secs).min(MAX_CONFIG_DURATION)
+}
+
+/// Poll schedule for `--interval`: `auto` follows the block time estimated
+/// from recent headers, falling back to 30s if it cannot be estimated.
+async fn poll_schedule(verifier: &sods_verifier::BlockVerifier, interval: &str) -> PollSchedule {
+    if !interval.eq_ignore_ascii_case("auto") {
+        return PollSchedule::fixed(parse_interval(interval));
+    }
+    match verifier.estimate_block_time(BLOCK_TIME_SPAN).await {
+        Ok(block_time) => PollSchedule::for_block_time(block_time),
+        Err(e) => {
+            eprintln!(
+                "⚠️ Could not estimate the block time ({}); polling every 30s.",
+                e
+            );
+            PollSchedule::fixed(parse_interval(interval))
+        }
     }
 }
 
+/// Rejects settings the background daemon could not recover from, so they
+/// are reported before it detaches from the terminal.
+fn check_start_config(chain: &str, rpc_url: Option<&str>) -> Result<(), String> {
+    if crate::config::get_chain(chain).is_none() {
+        return Err(format!(
+            "Chain '{}' not supported. Run `sods chains` for supported chains.",
+            chain
+        ));
+    }
+    if let Some(url) = rpc_url {
+        let parsed =
+            reqwest::Url::parse(url).map_err(|e| format!("Invalid --rpc-url '{}': {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!(
+                "Invalid --rpc-url '{}': expected an http(s) endpoint",
+                url
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Builds the daemon's tokio runtime, reporting failure instead of panicking.
+fn build_runtime() -> Option<tokio::runtime::Runtime> {
+    tokio::runtime::Runtime::new()
+        .map_err(|e| eprintln!("Failed to start async runtime: {}", e))
+        .ok()
+}
+
 #[cfg(unix)]
 fn start_daemon(
     pattern: Option<String>,
@@ -306,14 +717,23 @@ fn start_daemon(
     rpc_url: Option<String>,
     autostart: bool,
     webhook_url: Option<String>,
+    webhook_max_attempts: u32,
     threat_feed: Option<String>,
     p2p_threat_network: bool,
     expire_after_str: String,
     websocket_port: Option<u16>,
     metrics_port: Option<u16>,
+    api_auth: Arc<ApiAuth>,
+    no_links: bool,
+    dry_run: Option<DryRunSink>,
+    digest: Option<AlertDigest>,
+    fingerprints: Option<FingerprintTracker>,
+    siem: Option<SiemSink>,
+    proof_exporter: Option<ProofExporter>,
+    network_config: NetworkConfig,
 ) -> i32 {
     let expire_duration = parse_duration(&expire_after_str);
-    let expires_at = std::time::SystemTime::now() + expire_duration;
+    let expires_at = network_config.clock().now() + expire_duration;
     let sods_dir = get_sods_dir();
     let pid_file = get_pid_file();
     let log_file = get_log_file();
@@ -322,7 +742,9 @@ fn start_daemon(
     let _metrics: Option<Arc<AgentMetrics>> = metrics_port.and_then(|_port| {
         #[cfg(feature = "metrics")]
         {
-            AgentMetrics::new().ok().map(|m| Arc::new(m))
+            AgentMetrics::new()
+                .ok()
+                .map(|m| Arc::new(m.with_p2p_metrics(network_config.metrics.clone())))
         }
         #[cfg(not(feature = "metrics"))]
         {
@@ -331,10 +753,11 @@ fn start_daemon(
     });
 
     // WebSocket Server Setup
-    let ws_server = websocket_port.map(|port| Arc::new(WebSocketServer::new(port)));
+    let ws_server =
+        websocket_port.map(|port| Arc::new(WebSocketServer::new(port).with_auth(api_auth.clone())));
 
-    // Check if running
-    if check_status() {
+    // Check if running (a dry run may shadow a production daemon)
+    if dry_run.is_none() && check_status() {
         output::error("Daemon is already running.");
         return 1;
     }
@@ -344,18 +767,25 @@ fn start_daemon(
         println!("Save the following to ~/.config/systemd/user/sods.service:");
         println!(
             "[Unit]\nDescription=SODS Monitor\n[Service]\nExecStart={}",
-            std::env::current_exe().unwrap().display()
+            std::env::current_exe()
+                .unwrap_or_else(|_| PathBuf::from("sods"))
+                .display()
         );
         println!("Restart=always\n[Install]\nWantedBy=default.target");
         return 0;
     }
 
+    if dry_run.is_none() {
+        compact_stores(expire_duration, network_config.clock().now());
+    }
+
     // --- Prepare Initial Targets ---
     let mut targets = Vec::new();
 
     // 1. Manual Pattern
+    let aliases = crate::config::load_symbol_aliases();
     if let Some(p) = pattern {
-        match sods_core::pattern::BehavioralPattern::parse(&p) {
+        match sods_core::pattern::BehavioralPattern::parse_with_aliases(&p, &aliases) {
             Ok(parsed) => {
                 targets.push(MonitoringTarget {
                     pattern: parsed,
@@ -373,29 +803,76 @@ fn start_daemon(
         }
     }
 
-    if !p2p_threat_network && targets.is_empty() {
-        output::error("No valid patterns to monitor. Provide --pattern, --threat-feed, or enable --p2p-threat-network.");
+    if !p2p_threat_network && targets.is_empty() && fingerprints.is_none() {
+        output::error("No valid patterns to monitor. Provide --pattern, --threat-feed, --fingerprint, or enable --p2p-threat-network.");
         return 1;
     }
 
+    if let Some(sink) = dry_run {
+        println!("Starting SODS daemon (dry run, foreground)...");
+        println!("Monitoring {} initial targets.", targets.len());
+        println!("Dry-run log: {}", sink.path().display());
+
+        let Some(rt) = build_runtime() else {
+            return 1;
+        };
+        #[cfg(feature = "metrics")]
+        if let Some(ref m) = _metrics {
+            if let Some(port) = metrics_port.as_ref() {
+                rt.spawn(m.clone().serve(*port, api_auth.clone()));
+            }
+        }
+        rt.block_on(run_daemon_loop(
+            targets,
+            chain,
+            interval,
+            rpc_url,
+            webhook_url,
+            webhook_max_attempts,
+            Some(threat_feed),
+            p2p_threat_network,
+            expire_after_str,
+            ws_server,
+            _metrics,
+            no_links,
+            Some(sink),
+            digest,
+            fingerprints,
+            siem,
+            proof_exporter,
+            network_config,
+        ));
+        return 0;
+    }
+
     println!("Starting SODS daemon...");
     println!("Monitoring {} initial targets.", targets.len());
     if p2p_threat_network {
         println!("Network:    Connected to P2P Threat Intelligence");
     }
+    if let Some(ref sink) = siem {
+        println!("SIEM:       {} ({})", sink.target(), sink.format());
+    }
     println!("Logs: {}", log_file.display());
     println!("PID:  {}", pid_file.display());
 
-    let stdout = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_file)
-        .unwrap();
-    let stderr = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(&log_file)
-        .unwrap();
+    let open_log = || {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_file)
+    };
+    let (stdout, stderr) = match (open_log(), open_log()) {
+        (Ok(stdout), Ok(stderr)) => (stdout, stderr),
+        (Err(e), _) | (_, Err(e)) => {
+            output::error(&format!(
+                "Cannot open log file {}: {}",
+                log_file.display(),
+                e
+            ));
+            return 1;
+        }
+    };
 
     let daemonize = Daemonize::new()
         .pid_file(&pid_file)
@@ -406,14 +883,16 @@ fn start_daemon(
 
     match daemonize.start() {
         Ok(_) => {
-            let rt = tokio::runtime::Runtime::new().unwrap();
+            let Some(rt) = build_runtime() else {
+                return 1;
+            };
 
             // Start _metrics server if enabled
             #[cfg(feature = "metrics")]
             if let Some(ref m) = _metrics {
                 if let Some(port) = metrics_port.as_ref() {
                     let m_clone = m.clone();
-                    rt.spawn(m_clone.start_http_server(*port));
+                    rt.spawn(m_clone.serve(*port, api_auth.clone()));
                 }
             }
 
@@ -429,11 +908,19 @@ fn start_daemon(
                 interval.clone(),
                 rpc_url.clone(),
                 webhook_url.clone(),
+                webhook_max_attempts,
                 Some(threat_feed.clone()),
                 p2p_threat_network,
                 expire_after_str.clone(),
                 ws_server.clone(),
                 _metrics.clone(),
+                no_links,
+                None,
+                digest,
+                fingerprints,
+                siem,
+                proof_exporter,
+                network_config,
             ));
             0
         }
@@ -471,28 +958,31 @@ async fn run_daemon_loop(
     interval_str: String,
     rpc_url_opt: Option<String>,
     webhook_url: Option<String>,
+    webhook_max_attempts: u32,
     threat_feed: Option<Option<String>>,
     p2p_enabled: bool,
     expire_after_str: String,
     ws_server: Option<Arc<WebSocketServer>>,
     _metrics: Option<Arc<AgentMetrics>>,
+    no_links: bool,
+    dry_run: Option<DryRunSink>,
+    mut digest: Option<AlertDigest>,
+    mut fingerprints: Option<FingerprintTracker>,
+    siem: Option<SiemSink>,
+    proof_exporter: Option<ProofExporter>,
+    network_config: NetworkConfig,
 ) {
     use crate::config::get_chain;
-    use notify_rust::Notification;
-    use sods_verifier::BlockVerifier;
+    use sods_verifier::{BlockPrefetcher, BlockVerifier, HeadTracker};
     use std::time::Duration;
 
-    let interval_secs = if interval_str.ends_with("s") {
-        interval_str
-            .trim_end_matches("s")
-            .parse::<u64>()
-            .unwrap_or(30)
-    } else {
-        30
-    };
-    let interval = Duration::from_secs(interval_secs);
+    // Rule expiry and garbage collection follow the P2P node's clock
+    let clock = network_config.clock();
 
-    let chain_config = get_chain(&chain).unwrap();
+    let Some(chain_config) = get_chain(&chain) else {
+        eprintln!("Critical Error: Chain '{}' not supported", chain);
+        return;
+    };
     let rpc_urls: Vec<String> = if let Some(url) = rpc_url_opt {
         vec![url]
     } else {
@@ -503,21 +993,45 @@ async fn run_daemon_loop(
             .collect()
     };
 
+    let expire_duration = parse_duration(&expire_after_str);
+
     // --- P2P Setup ---
+    let mut peer_count = None;
+    let mut reputation = None;
     let mut threat_rx = if p2p_enabled {
-        match SodsPeer::new(&rpc_urls[0]) {
-            Ok(mut peer) => {
+        match SodsPeer::with_network_config(&rpc_urls[0], &network_config) {
+            Ok(peer) => {
+                let seen = SeenCache::load(&get_seen_threats_file(), expire_duration)
+                    .unwrap_or_else(|e| {
+                        eprintln!("⚠️ Ignoring unreadable seen-rule cache: {}", e);
+                        SeenCache::new(expire_duration)
+                    });
+                let peer = peer.with_seen_cache(seen);
                 println!("P2P Node Initialized: {}", peer.peer_id());
-                let rx = peer.subscribe_threats();
-                tokio::spawn(async move {
-                    if let Err(e) = peer.listen("/ip4/0.0.0.0/tcp/0").await {
-                        eprintln!("P2P Listen Error: {}", e);
-                    }
-                });
-                Some(rx)
+                if let Some(psk) = &network_config.psk {
+                    println!("Private network: key fingerprint {}", psk.fingerprint());
+                }
+                if network_config.role != NodeRole::Full {
+                    println!("P2P role: {}", network_config.role);
+                }
+                let rx = match &network_config.federation {
+                    Some(federation) => start_bridge(
+                        &peer,
+                        federation,
+                        &network_config,
+                        &rpc_urls[0],
+                        expire_duration,
+                    ),
+                    None => Some(peer.subscribe_threats()),
+                };
+                peer_count = Some(peer.connected_peers());
+                reputation = Some(peer.reputation_snapshots());
+                spawn_peer(peer, &network_config);
+                rx
             }
             Err(e) => {
                 eprintln!("Failed to initialize P2P node: {}", e);
+                output::p2p_hint(&e);
                 None
             }
         }
@@ -525,8 +1039,6 @@ async fn run_daemon_loop(
         None
     };
 
-    let expire_duration = parse_duration(&expire_after_str);
-
     // --- Memory Usage Task ---
     #[cfg(feature = "metrics")]
     if let Some(ref m) = _metrics {
@@ -558,7 +1070,7 @@ async fn run_daemon_loop(
                     if item.chain != chain {
                         continue;
                     }
-                    match sods_core::pattern::BehavioralPattern::parse(&item.pattern) {
+                    match sods_core::pattern::BehavioralPattern::parse_untrusted(&item.pattern) {
                         Ok(parsed) => {
                             targets.push(MonitoringTarget {
                                 pattern: parsed,
@@ -566,7 +1078,7 @@ async fn run_daemon_loop(
                                 severity: item.severity,
                                 pattern_str: item.pattern,
                                 chain: item.chain,
-                                expires_at: std::time::SystemTime::now() + expire_duration,
+                                expires_at: clock.now() + expire_duration,
                             });
                         }
                         Err(e) => eprintln!("⚠️ Skipping invalid pattern '{}': {}", item.name, e),
@@ -579,27 +1091,24 @@ async fn run_daemon_loop(
 
     // --- Load Persisted Rules ---
     if p2p_enabled {
-        let rules_file = get_threat_rules_file();
-        if rules_file.exists() {
-            if let Ok(content) = fs::read_to_string(&rules_file) {
-                if let Ok(rules) = serde_json::from_str::<Vec<ThreatRule>>(&content) {
-                    println!("Loaded {} persisted threat rules.", rules.len());
-                    for rule in rules {
-                        if rule.chain == chain {
-                            if let Ok(p) =
-                                sods_core::pattern::BehavioralPattern::parse(&rule.pattern)
-                            {
-                                targets.push(MonitoringTarget {
-                                    pattern: p,
-                                    name: rule.name.clone(),
-                                    severity: rule.severity.clone(),
-                                    pattern_str: rule.pattern.clone(),
-                                    chain: rule.chain.clone(),
-                                    expires_at: std::time::SystemTime::now() + expire_duration,
-                                });
-                            }
-                        }
-                    }
+        let rules = load_persisted_rules(&get_threat_rules_file());
+        if !rules.is_empty() {
+            println!("Loaded {} persisted threat rules.", rules.len());
+        }
+        let now = clock.now();
+        for rule in rules {
+            let expires_at = rule_expiry(&rule, expire_duration);
+            if rule.chain == chain && now < expires_at {
+                if let Ok(p) = sods_core::pattern::BehavioralPattern::parse_untrusted(&rule.pattern)
+                {
+                    targets.push(MonitoringTarget {
+                        pattern: p,
+                        name: rule.name.clone(),
+                        severity: rule.severity.clone(),
+                        pattern_str: rule.pattern.clone(),
+                        chain: rule.chain.clone(),
+                        expires_at,
+                    });
                 }
             }
         }
@@ -619,7 +1128,11 @@ async fn run_daemon_loop(
     };
 
     let verifier = match BlockVerifier::new(&rpc_urls) {
-        Ok(v) => v.with_backoff_profile(profile),
+        Ok(v) => Arc::new(
+            v.with_backoff_profile(profile)
+                .with_aliases(crate::config::load_symbol_aliases())
+                .with_reorg_tracking(),
+        ),
         Err(e) => {
             eprintln!("Critical Error: Failed to initialize RPCs: {}", e);
             return;
@@ -632,15 +1145,68 @@ async fn run_daemon_loop(
         );
     }
 
-    let mut last_scanned_block = verifier.get_latest_block().await.unwrap_or(0);
+    let schedule = poll_schedule(&verifier, &interval_str).await;
+    let interval = schedule.interval;
+    if let Some(block_time) = schedule.block_time {
+        println!(
+            "Block time ~{:.1}s: polling every {}s, catching up at most {} blocks per poll.",
+            block_time.as_secs_f64(),
+            interval.as_secs(),
+            schedule.batch
+        );
+    }
+
+    // Webhooks go through a persistent queue; it also resumes deliveries
+    // left over from an earlier run
+    let webhook_queue = dry_run.is_none().then(|| {
+        let queue = SharedQueue::new(
+            WebhookQueue::open(
+                WebhookQueue::default_path(),
+                WebhookQueue::default_dlq_path(),
+            )
+            .with_policy(RetryPolicy {
+                max_attempts: webhook_max_attempts.max(1),
+                ..RetryPolicy::default()
+            }),
+        );
+        let pending = queue.lock().len();
+        if pending > 0 {
+            println!("Resuming {} queued webhook deliveries.", pending);
+        }
+        tokio::spawn(queue.clone().run_dispatcher(_metrics.clone()));
+        queue
+    });
+
+    // The head is polled once per interval and shared by the loop below
+    let heads = HeadTracker::polling(verifier.clone(), interval);
+    let mut last_scanned_block = heads.wait_for_head(INITIAL_HEAD_TIMEOUT).await.unwrap_or(0);
     if last_scanned_block == 0 {
         println!("Warning: Could not fetch initial block. Will retry in loop.");
     }
+    // Failed head polls already counted as verification failures
+    #[cfg(feature = "metrics")]
+    let mut counted_head_failures = 0u64;
+
+    // Requests from `sods daemon status` and friends; a dry run leaves the
+    // socket to the production daemon it may be shadowing
+    #[cfg(unix)]
+    let mut control_rx = if dry_run.is_none() {
+        control::serve(&control::default_path())
+            .map_err(|e| eprintln!("⚠️ Control socket unavailable: {}", e))
+            .ok()
+    } else {
+        None
+    };
+    #[cfg(not(unix))]
+    let mut control_rx: Option<tokio::sync::mpsc::Receiver<control::ControlCall>> = None;
+    let started = clock.instant();
 
     let mut timer = tokio::time::interval(interval);
     let mut hourly_timer = tokio::time::interval(Duration::from_secs(3600));
     let mut resource_timer = tokio::time::interval(Duration::from_secs(60));
-    let mut last_gc = std::time::Instant::now();
+    let mut last_gc = clock.instant();
+    // Targets with a `within` window match over the blocks it covers, by pattern
+    let mut streams: HashMap<String, PatternStreamMatcher> = HashMap::new();
 
     loop {
         tokio::select! {
@@ -668,13 +1234,24 @@ async fn run_daemon_loop(
                   println!("Received P2P Threat Rule: {}", rule.name);
 
                   let rules_file = get_threat_rules_file();
+                  // Held until the rewrite, so `sods gc` cannot compact in between.
+                  // Waiting for it blocks, so it must not stall the event loop
+                  let _rules_lock = {
+                      let path = rules_file.clone();
+                      tokio::task::spawn_blocking(move || retention::lock_store(&path)).await
+                  };
                   let mut current_rules: Vec<ThreatRule> = if rules_file.exists() {
                       fs::read_to_string(&rules_file).ok()
                          .and_then(|c| serde_json::from_str(&c).ok())
                          .unwrap_or_default()
                   } else { Vec::new() };
 
-                  if !current_rules.iter().any(|r| r.id == rule.id) {
+                  // Drop expired rules so the file only holds live ones
+                  let now = clock.now();
+                  current_rules.retain(|r| now < rule_expiry(r, expire_duration));
+                  let expires_at = rule_expiry(&rule, expire_duration);
+
+                  if now < expires_at && !current_rules.iter().any(|r| r.id == rule.id) {
                       current_rules.push(rule.clone());
                       if current_rules.len() > 1000 {
                           current_rules.remove(0);
@@ -683,66 +1260,146 @@ async fn run_daemon_loop(
                           let _ = fs::write(rules_file, json);
                       }
                       if rule.chain == chain {
-                          if let Ok(p) = sods_core::pattern::BehavioralPattern::parse(&rule.pattern) {
+                          if let Ok(p) = sods_core::pattern::BehavioralPattern::parse_untrusted(&rule.pattern) {
                               targets.push(MonitoringTarget {
                                   pattern: p,
                                   name: rule.name.clone(),
                                   severity: rule.severity.clone(),
                                   pattern_str: rule.pattern.clone(),
                                   chain: rule.chain.clone(),
-                                  expires_at: std::time::SystemTime::now() + expire_duration,
+                                  expires_at,
                               });
                               let msg = format!("Active P2P Rule Applied: {}", rule.name);
                               println!("{}", msg);
-                              let _ = Notification::new().summary("SODS Threat Update").body(&msg).show();
+                              notify(dry_run.as_ref(), "SODS Threat Update", &msg, None);
                           }
                       }
                   }
              }
 
+             Some(call) = async {
+                if let Some(rx) = &mut control_rx {
+                    rx.recv().await
+                } else {
+                    std::future::pending().await
+                }
+             } => {
+                 let view = ControlView {
+                     chain: &chain,
+                     started,
+                     head: heads.head(),
+                     last_scanned_block,
+                     peers: peer_count.as_ref().map(|p| *p.borrow()),
+                     reputation: reputation.as_ref(),
+                     schedule,
+                     expire_duration,
+                     webhooks: webhook_queue.as_ref(),
+                     clock: clock.as_ref(),
+                 };
+                 let response = handle_control(call.request, &mut targets, &verifier, &view);
+                 let _ = call.reply.send(response);
+             }
+
              _ = timer.tick() => {
-                 if last_gc.elapsed() >= Duration::from_secs(300) {
-                     let before = targets.len();
-                     targets.retain(|t| std::time::SystemTime::now() < t.expires_at);
-                     let after = targets.len();
-                     if before > after {
-                         println!("🧹 Garbage Collection: Pruned {} expired rules ({} remaining).", before - after, after);
+                 if clock.instant().saturating_duration_since(last_gc) >= Duration::from_secs(300) {
+                     let pruned = prune_expired(&mut targets, clock.now());
+                     if pruned > 0 {
+                         println!("🧹 Garbage Collection: Pruned {} expired rules ({} remaining).", pruned, targets.len());
                      }
-                     last_gc = std::time::Instant::now();
+                     last_gc = clock.instant();
                  }
 
                   #[cfg(feature = "metrics")]
                  if let Some(ref m) = _metrics { m.active_rules.set(targets.len() as i64); }
                   #[cfg(feature = "metrics")]
                  let start_v = std::time::Instant::now();
+                  #[cfg(feature = "metrics")]
+                 {
+                     let failed = heads.failed_polls();
+                     if let Some(ref m) = _metrics { m.verification_failures_total.inc_by((failed - counted_head_failures) as f64); }
+                     counted_head_failures = failed;
+                 }
 
-                 match verifier.get_latest_block().await {
-                    Ok(current_head) => {
-                        #[cfg(feature = "metrics")]
-                        if let Some(ref m) = _metrics { m.rpc_calls_total.inc(); }
+                 match heads.head() {
+                    Some(current_head) => {
                         if current_head > last_scanned_block {
                             if last_scanned_block == 0 {
                                 last_scanned_block = current_head;
                                 continue;
                             }
 
-                            for block_num in (last_scanned_block + 1)..=current_head {
-                                match verifier.fetch_block_symbols(block_num).await {
-                                    Ok(symbols) => {
+                            // All targets share one log fetch per block, and the next
+                            // blocks are fetched while one is matched
+                            let pattern_strs = targets.iter().map(|t| t.pattern_str.clone()).collect();
+                            // Far behind, the rest is left for the next polls
+                            let batch_end = schedule.batch_end(last_scanned_block, current_head);
+                            let mut blocks = BlockPrefetcher::new(verifier.clone(), pattern_strs, (last_scanned_block + 1)..=batch_end);
+                            // Fingerprints count every symbol, so the blocks are fetched whole for them
+                            if fingerprints.is_some() {
+                                blocks = blocks.with_block_symbols();
+                            }
+                            while let Some((block_num, outcomes, block_symbols)) = blocks.next_with_symbols().await {
+                                match outcomes {
+                                    Ok(outcomes) => {
                                         #[cfg(feature = "metrics")]
                                         if let Some(ref m) = _metrics { m.rpc_calls_total.inc(); }
-                                        for target in &targets {
-                                            if let Some(matched_symbols) = target.pattern.matches(&symbols, None) {
-                                                #[cfg(feature = "metrics")]
-                                                if let Some(ref m) = _metrics { m.behavioral_alerts_total.inc(); }
-                                                 let msg = format!("🚨 {} ({}) detected on Block #{}", target.name, target.severity, block_num);
-                                                 warn!("{}", msg);
-                                                 let _ = Notification::new().summary("SODS Threat Alert 🚨").body(&msg).show();
+                                        let streamed = stream_block(&mut streams, &targets, &outcomes, block_num);
+                                        for (target, outcome) in targets.iter().zip(&outcomes) {
+                                            let found = match streamed.get(&target.pattern_str) {
+                                                Some(matches) => matches.iter().map(|m| m.symbols.clone()).collect(),
+                                                None if !outcome.matched.is_empty() => vec![outcome.matched.clone()],
+                                                None => Vec::new(),
+                                            };
+                                            for matched_symbols in &found {
+                                                // Fees were looked up for this block's own match
+                                                let fees = outcome.result.as_ref().ok().filter(|_| !streamed.contains_key(&target.pattern_str)).and_then(|r| r.fees.clone());
+                                                let reorg = outcome.result.as_ref().ok().and_then(|r| r.reorg.clone());
+                                                // A match near a reorg may be orphaned or part of the reorg itself
+                                                let severity = match reorg {
+                                                    Some(ref r) if r.near_reorg => crate::digest::raise_severity(&target.severity).to_string(),
+                                                    _ => target.severity.clone(),
+                                                };
+                                                 let mut msg = format!("🚨 {} ({}) detected on Block #{}", target.name, severity, block_num);
+                                                 if let Some(ref fees) = fees {
+                                                     msg.push_str(&format!(" ({})", fees.summary()));
+                                                 }
+                                                 if let Some(r) = reorg.as_ref().filter(|r| r.near_reorg) {
+                                                     msg.push_str(&format!(" [reorg: {}]", r.summary()));
+                                                 }
+                                                 let alert_id = new_alert_id("alert", block_num);
+                                                 #[cfg(feature = "metrics")]
+                                                 if let Some(ref m) = _metrics { m.record_alert(&alert_id); }
+                                                 warn!(alert_id = %alert_id, "{}", msg);
+
+                                                 if let Some(ref exporter) = proof_exporter {
+                                                     let stream = streams.get(&target.pattern_str);
+                                                     let cached = |n| stream.and_then(|s| s.block_symbols(n)).map(<[_]>::to_vec);
+                                                     let bundle = MatchBundle::new(&target.pattern, &target.pattern_str, chain_config.chain_id, clock.unix_secs()).with_alert_id(&alert_id);
+                                                     match exporter.export(&verifier, &target.pattern, bundle, block_num, matched_symbols, cached).await {
+                                                         Ok(path) => println!("Proof bundle written to {}", path.display()),
+                                                         Err(e) => eprintln!("⚠️ Proof not exported: {}", e),
+                                                     }
+                                                 }
+
+                                                 // Low-severity alerts only reach notifications and webhooks via the digest
+                                                 let digested = match digest.as_mut() {
+                                                     Some(d) if !d.is_immediate(&severity) => {
+                                                         d.add(&chain, &target.name, &severity, block_num, &alert_id);
+                                                         true
+                                                     }
+                                                     _ => false,
+                                                 };
+                                                 if !digested {
+                                                     notify(dry_run.as_ref(), "SODS Threat Alert 🚨", &msg, Some(&alert_id));
+                                                 }
+
+                                                let block_url = if no_links { None } else { chain_config.block_url(block_num) };
 
                                                 if let Some(ref ws) = ws_server {
+                                                    let link = |addr: String| if no_links { None } else { chain_config.address_url(&addr) };
                                                     let alert = BehavioralAlert {
                                                         msg_type: "behavioral_alert".into(),
-                                                        timestamp: chrono::Utc::now().to_rfc3339(),
+                                                        timestamp: alert_time(clock.as_ref()).to_rfc3339(),
                                                         chain_id: chain_config.chain_id,
                                                         block_number: block_num,
                                                         pattern: target.pattern_str.clone(),
@@ -751,31 +1408,76 @@ async fn run_daemon_loop(
                                                             from: format!("{:?}", s.from),
                                                             to: format!("{:?}", s.to),
                                                             value: s.value.to_string(),
+                                                            priority_fee_per_gas: fees.as_ref().and_then(|f| f.priority_fee(s.tx_hash)),
+                                                            tx_url: if no_links { None } else { chain_config.tx_url(&format!("{:?}", s.tx_hash)) },
+                                                            from_url: link(format!("{:?}", s.from)),
+                                                            to_url: link(format!("{:?}", s.to)),
                                                         }).collect(),
-                                                        alert_id: format!("alert_{}_{}", block_num, Uuid::new_v4().to_string().split('-').next().unwrap()),
+                                                        alert_id: alert_id.clone(),
+                                                        block_url: block_url.clone(),
+                                                        fees: fees.clone(),
+                                                        reorg: reorg.clone(),
+                                                        drift: None,
                                                     };
-                                                    ws.broadcast_alert(alert).await;
+                                                    if let Some(ref sink) = dry_run {
+                                                        record_dry_run(sink, AlertChannel::Websocket, None, json!(alert));
+                                                    } else {
+                                                        ws.broadcast_alert(alert).await;
+                                                    }
                                                 }
 
-                                                if let Some(ref url) = webhook_url {
+                                                if let Some(ref sink) = siem {
+                                                    let first = matched_symbols.first();
+                                                    let alert = SiemAlert {
+                                                        timestamp: alert_time(clock.as_ref()),
+                                                        alert_id: alert_id.clone(),
+                                                        chain: chain.clone(),
+                                                        chain_id: chain_config.chain_id,
+                                                        block_number: block_num,
+                                                        threat_name: target.name.clone(),
+                                                        severity: severity.clone(),
+                                                        pattern: target.pattern_str.clone(),
+                                                        symbols: matched_symbols.iter().map(|s| s.symbol.clone()).collect(),
+                                                        source: first.map(|s| format!("{:?}", s.from)),
+                                                        destination: first.map(|s| format!("{:?}", s.to)),
+                                                        tx_hash: first.map(|s| format!("{:?}", s.tx_hash)),
+                                                        block_url: block_url.clone(),
+                                                    };
+                                                    if let Some(ref dry) = dry_run {
+                                                        let target = sink.target().to_string();
+                                                        record_dry_run(dry, AlertChannel::Siem, Some(&target), json!({ "format": sink.format().to_string(), "message": sink.render(&alert) }));
+                                                    } else {
+                                                        tokio::spawn(send_siem(sink.clone(), alert));
+                                                    }
+                                                }
+
+                                                if let Some(url) = webhook_url.as_ref().filter(|_| !digested) {
                                                     static SALT: once_cell::sync::Lazy<String> = once_cell::sync::Lazy::new(|| {
                                                         use rand::Rng;
                                                         rand::thread_rng().sample_iter(&rand::distributions::Alphanumeric).take(16).map(char::from).collect()
                                                     });
-                                                    let mut payload_seed = target.pattern_str.clone();
-                                                    payload_seed.push_str(&SALT);
-                                                    let pattern_hash = ethers_core::utils::keccak256(payload_seed.as_bytes());
-                                                    let payload = json!({
+                                                    let mut payload_seed = target.pattern.canonical_hash().to_vec();
+                                                    payload_seed.extend_from_slice(SALT.as_bytes());
+                                                    let pattern_hash = ethers_core::utils::keccak256(&payload_seed);
+                                                    let mut payload = json!({
                                                         "alert": "Behavioral pattern detected",
+                                                        "alert_id": alert_id,
                                                         "chain": chain,
                                                         "block_number": block_num,
                                                         "pattern_hash_blinded": format!("0x{}", hex::encode(pattern_hash)),
                                                         "threat_name": target.name,
-                                                        "severity": target.severity,
-                                                        "timestamp": chrono::Utc::now().to_rfc3339(),
+                                                        "severity": severity,
+                                                        "timestamp": alert_time(clock.as_ref()).to_rfc3339(),
                                                         "source": "daemon"
                                                     });
-                                                    tokio::spawn(send_webhook(url.clone(), payload));
+                                                    if let Some(ref url) = block_url {
+                                                        payload["block_url"] = json!(url);
+                                                    }
+                                                    if let Some(ref sink) = dry_run {
+                                                        record_dry_run(sink, AlertChannel::Webhook, Some(url), payload);
+                                                    } else if let Some(ref queue) = webhook_queue {
+                                                        queue.enqueue(url, payload);
+                                                    }
                                                 }
                                             }
                                         }
@@ -786,34 +1488,453 @@ async fn run_daemon_loop(
                                         eprintln!("Error fetching block #{}: {}", block_num, e);
                                     }
                                 }
+
+                                if let (Some(tracker), Some(block_symbols)) = (fingerprints.as_mut(), block_symbols) {
+                                    // A block that could not be read is left out, not counted as empty
+                                    let symbols = match block_symbols {
+                                        Ok(symbols) => symbols,
+                                        Err(e) => {
+                                            eprintln!("Error fingerprinting block #{}: {}", block_num, e);
+                                            continue;
+                                        }
+                                    };
+                                    for drift in tracker.observe(block_num, &symbols) {
+                                        let msg = format!("🧬 Behavioral drift on Block #{}: {}", block_num, drift.summary());
+                                        let alert_id = new_alert_id("drift", block_num);
+                                        #[cfg(feature = "metrics")]
+                                        if let Some(ref m) = _metrics { m.record_alert(&alert_id); }
+                                        warn!(alert_id = %alert_id, "{}", msg);
+
+                                        let digested = match digest.as_mut() {
+                                            Some(d) if !d.is_immediate(DRIFT_SEVERITY) => {
+                                                d.add(&chain, "Fingerprint Drift", DRIFT_SEVERITY, block_num, &alert_id);
+                                                true
+                                            }
+                                            _ => false,
+                                        };
+                                        if !digested {
+                                            notify(dry_run.as_ref(), "SODS Behavioral Drift 🧬", &msg, Some(&alert_id));
+                                        }
+
+                                        let block_url = if no_links { None } else { chain_config.block_url(block_num) };
+                                        let contract = format!("{:?}", drift.contract);
+
+                                        if let Some(ref ws) = ws_server {
+                                            let alert = BehavioralAlert {
+                                                msg_type: "fingerprint_drift".into(),
+                                                timestamp: alert_time(clock.as_ref()).to_rfc3339(),
+                                                chain_id: chain_config.chain_id,
+                                                block_number: block_num,
+                                                pattern: "fingerprint".into(),
+                                                symbols: Vec::new(),
+                                                alert_id: alert_id.clone(),
+                                                block_url: block_url.clone(),
+                                                fees: None,
+                                                reorg: None,
+                                                drift: Some(drift.clone()),
+                                            };
+                                            if let Some(ref sink) = dry_run {
+                                                record_dry_run(sink, AlertChannel::Websocket, None, json!(alert));
+                                            } else {
+                                                ws.broadcast_alert(alert).await;
+                                            }
+                                        }
+
+                                        if let Some(ref sink) = siem {
+                                            let alert = SiemAlert {
+                                                timestamp: alert_time(clock.as_ref()),
+                                                alert_id: alert_id.clone(),
+                                                chain: chain.clone(),
+                                                chain_id: chain_config.chain_id,
+                                                block_number: block_num,
+                                                threat_name: "Fingerprint Drift".into(),
+                                                severity: DRIFT_SEVERITY.into(),
+                                                pattern: "fingerprint".into(),
+                                                symbols: drift.new_symbols.clone(),
+                                                source: Some(contract.clone()),
+                                                destination: None,
+                                                tx_hash: None,
+                                                block_url: block_url.clone(),
+                                            };
+                                            if let Some(ref dry) = dry_run {
+                                                let target = sink.target().to_string();
+                                                record_dry_run(dry, AlertChannel::Siem, Some(&target), json!({ "format": sink.format().to_string(), "message": sink.render(&alert) }));
+                                            } else {
+                                                tokio::spawn(send_siem(sink.clone(), alert));
+                                            }
+                                        }
+
+                                        if let Some(url) = webhook_url.as_ref().filter(|_| !digested) {
+                                            let mut payload = json!({
+                                                "alert": "Behavioral fingerprint drift",
+                                                "alert_id": alert_id,
+                                                "chain": chain,
+                                                "block_number": block_num,
+                                                "contract": contract,
+                                                "distance": drift.distance,
+                                                "new_symbols": drift.new_symbols,
+                                                "severity": DRIFT_SEVERITY,
+                                                "timestamp": alert_time(clock.as_ref()).to_rfc3339(),
+                                                "source": "daemon"
+                                            });
+                                            if let Some(ref url) = block_url {
+                                                payload["block_url"] = json!(url);
+                                            }
+                                            if let Some(ref sink) = dry_run {
+                                                record_dry_run(sink, AlertChannel::Webhook, Some(url), payload);
+                                            } else if let Some(ref queue) = webhook_queue {
+                                                queue.enqueue(url, payload);
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                            last_scanned_block = batch_end;
+                            // A dry run must not overwrite the history a real daemon keeps;
+                            // the history only changes when a window closes
+                            if let (Some(tracker), None) = (fingerprints.as_mut(), dry_run.as_ref()) {
+                                if let Err(e) = FingerprintHistory::get_default_path().and_then(|path| tracker.save_history(&path)) {
+                                    eprintln!("Failed to save fingerprints: {}", e);
+                                }
                             }
-                            last_scanned_block = current_head;
                         }
                     },
-                    Err(e) => {
-                        #[cfg(feature = "metrics")]
-                        if let Some(ref m) = _metrics { m.verification_failures_total.inc(); }
-                        println!("RPC Error: {}", e);
-                    }
+                    None => println!("Waiting for the chain head..."),
                  }
                  #[cfg(feature = "metrics")]
                  if let Some(ref m) = _metrics { m.verification_duration_seconds.observe(start_v.elapsed().as_secs_f64()); }
+
+                 if let Some(summary) = digest.as_mut().and_then(|d| d.flush(clock.unix_secs())) {
+                     let headline = summary.headline();
+                     println!("📬 Alert Digest: {} (next in {}s)", headline, summary.next_interval_secs);
+                     notify(dry_run.as_ref(), "SODS Alert Digest", &headline, None);
+                     if let Some(ref url) = webhook_url {
+                         if let Some(ref sink) = dry_run {
+                             record_dry_run(sink, AlertChannel::Webhook, Some(url), summary.to_payload());
+                         } else if let Some(ref queue) = webhook_queue {
+                             queue.enqueue(url, summary.to_payload());
+                         }
+                     }
+                 }
              }
         }
     }
 }
 
-async fn send_webhook(url: String, payload: serde_json::Value) {
-    if !url.starts_with("https://") {
-        return;
+/// Severity of fingerprint drift alerts.
+const DRIFT_SEVERITY: &str = "high";
+
+/// Feed `block_num` to the stream of each target with a `within` window
+/// and return the matches it completes, by pattern. Each stream gets the
+/// verified symbols its pattern was matched against in `outcomes`, which
+/// is aligned with `targets`; a pattern that failed to verify skips the
+/// block. Streams of patterns no target uses any more are dropped.
+fn stream_block(
+    streams: &mut HashMap<String, PatternStreamMatcher>,
+    targets: &[MonitoringTarget],
+    outcomes: &[sods_verifier::PatternOutcome],
+    block_num: u64,
+) -> HashMap<String, Vec<sods_core::StreamMatch>> {
+    streams.retain(|pattern, _| targets.iter().any(|t| t.pattern_str == *pattern));
+    let mut found = HashMap::new();
+    for (target, outcome) in targets.iter().zip(outcomes) {
+        if target.pattern.window().is_none()
+            || outcome.result.is_err()
+            || found.contains_key(&target.pattern_str)
+        {
+            continue;
+        }
+        let stream = streams
+            .entry(target.pattern_str.clone())
+            .or_insert_with(|| PatternStreamMatcher::new(target.pattern.clone()));
+        found.insert(
+            target.pattern_str.clone(),
+            stream.push_block(block_num, outcome.symbols.clone()),
+        );
+    }
+    found
+}
+
+/// Show a desktop notification, or log it when dry-running. The body ends
+/// with the alert ID of a single detection, if given.
+fn notify(dry_run: Option<&DryRunSink>, summary: &str, body: &str, alert_id: Option<&str>) {
+    let body = match alert_id {
+        Some(id) => format!("{}\nAlert ID: {}", body, id),
+        None => body.to_string(),
+    };
+    match dry_run {
+        Some(sink) => {
+            let mut payload = json!({ "summary": summary, "body": body });
+            if let Some(id) = alert_id {
+                payload["alert_id"] = json!(id);
+            }
+            record_dry_run(sink, AlertChannel::Notification, None, payload)
+        }
+        None => {
+            let _ = notify_rust::Notification::new()
+                .summary(summary)
+                .body(&body)
+                .show();
+        }
+    }
+}
+
+fn record_dry_run(
+    sink: &DryRunSink,
+    channel: AlertChannel,
+    target: Option<&str>,
+    payload: serde_json::Value,
+) {
+    if let Err(e) = sink.record(channel, target, payload) {
+        eprintln!("Failed to write dry-run log: {}", e);
+    }
+}
+
+async fn send_siem(sink: SiemSink, alert: SiemAlert) {
+    let target = sink.target().to_string();
+    match tokio::task::spawn_blocking(move || sink.send(&alert)).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("Failed to send alert to SIEM {}: {}", target, e),
+        Err(e) => eprintln!("SIEM delivery task failed: {}", e),
+    }
+}
+
+/// What a control request can see of the daemon loop.
+struct ControlView<'a> {
+    chain: &'a str,
+    started: std::time::Instant,
+    head: Option<u64>,
+    last_scanned_block: u64,
+    peers: Option<usize>,
+    reputation: Option<&'a tokio::sync::watch::Receiver<Option<ReputationSnapshot>>>,
+    schedule: PollSchedule,
+    expire_duration: std::time::Duration,
+    webhooks: Option<&'a SharedQueue>,
+    clock: &'a dyn Clock,
+}
+
+/// Answers a control socket request, changing `targets` if asked to.
+fn handle_control(
+    request: ControlRequest,
+    targets: &mut Vec<MonitoringTarget>,
+    verifier: &sods_verifier::BlockVerifier,
+    view: &ControlView,
+) -> ControlResponse {
+    match request {
+        ControlRequest::Status => ControlResponse::Status(daemon_status(targets, view)),
+        ControlRequest::AddTarget {
+            name,
+            pattern,
+            severity,
+        } => {
+            if targets.iter().any(|t| t.name == name) {
+                return ControlResponse::error(format!("A target named '{}' already exists", name));
+            }
+            match verifier.parse_pattern(&pattern) {
+                Ok(parsed) => {
+                    targets.push(MonitoringTarget {
+                        pattern: parsed,
+                        name: name.clone(),
+                        severity: severity.unwrap_or_else(|| "manual".to_string()),
+                        pattern_str: pattern,
+                        chain: view.chain.to_string(),
+                        expires_at: view.clock.now() + view.expire_duration,
+                    });
+                    println!("Control: added target '{}'", name);
+                    ControlResponse::done(format!("Monitoring '{}'", name))
+                }
+                Err(e) => ControlResponse::error(format!("Invalid pattern: {}", e)),
+            }
+        }
+        ControlRequest::RemoveTarget { name } => {
+            let before = targets.len();
+            targets.retain(|t| t.name != name);
+            match before - targets.len() {
+                0 => ControlResponse::error(format!("No target named '{}'", name)),
+                removed => {
+                    println!("Control: removed target '{}'", name);
+                    ControlResponse::done(format!("Removed {} target(s) named '{}'", removed, name))
+                }
+            }
+        }
+        ControlRequest::ReloadConfig => {
+            // An invalid file keeps the presets in use rather than dropping them
+            let presets = match sods_core::PatternPresets::load_local() {
+                Ok(p) => p,
+                Err(e) => return ControlResponse::error(format!("Presets not reloaded: {}", e)),
+            };
+            let count = presets.iter().count();
+            presets.install();
+            verifier.clear_pattern_cache();
+
+            let before = targets.len();
+            targets.retain_mut(|t| match verifier.parse_pattern(&t.pattern_str) {
+                Ok(p) => {
+                    t.pattern = p;
+                    true
+                }
+                Err(e) => {
+                    eprintln!("⚠️ Dropping target '{}' after reload: {}", t.name, e);
+                    false
+                }
+            });
+            let message = format!(
+                "Reloaded {} presets; {} targets ({} dropped)",
+                count,
+                targets.len(),
+                before - targets.len()
+            );
+            println!("Control: {}", message);
+            ControlResponse::done(message)
+        }
+        ControlRequest::DumpState => {
+            let unix_secs = |t: std::time::SystemTime| {
+                t.duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0)
+            };
+            ControlResponse::State(json!({
+                "status": daemon_status(targets, view),
+                "interval_secs": view.schedule.interval.as_secs(),
+                "block_time_ms": view.schedule.block_time.map(|t| t.as_millis() as u64),
+                "expire_after_secs": view.expire_duration.as_secs(),
+                "targets": targets.iter().map(|t| json!({
+                    "name": t.name,
+                    "severity": t.severity,
+                    "pattern": t.pattern_str,
+                    "chain": t.chain,
+                    "expires_at": unix_secs(t.expires_at),
+                })).collect::<Vec<_>>(),
+                "webhooks": view.webhooks.map(|queue| {
+                    let queue = queue.lock();
+                    json!({ "pending": queue.len(), "stats": queue.stats() })
+                }),
+            }))
+        }
+        ControlRequest::ExportReputation => {
+            let Some(rx) = view.reputation else {
+                return ControlResponse::error("P2P is not enabled in the running daemon");
+            };
+            let snapshot = rx.borrow().clone();
+            match snapshot.map(serde_json::to_value) {
+                Some(Ok(value)) => ControlResponse::State(value),
+                Some(Err(e)) => ControlResponse::error(format!("Snapshot not serialized: {}", e)),
+                None => ControlResponse::error("No reputation snapshot yet; try again shortly"),
+            }
+        }
+    }
+}
+
+fn daemon_status(targets: &[MonitoringTarget], view: &ControlView) -> DaemonStatus {
+    let now = view.clock.now();
+    DaemonStatus {
+        pid: std::process::id(),
+        chain: view.chain.to_string(),
+        uptime_secs: view
+            .clock
+            .instant()
+            .saturating_duration_since(view.started)
+            .as_secs(),
+        head: view.head,
+        last_scanned_block: view.last_scanned_block,
+        // Nothing has been scanned before the first head arrives
+        lag: view
+            .head
+            .filter(|_| view.last_scanned_block > 0)
+            .map(|head| head.saturating_sub(view.last_scanned_block)),
+        peers: view.peers,
+        targets: targets
+            .iter()
+            .map(|t| TargetStatus {
+                name: t.name.clone(),
+                severity: t.severity.clone(),
+                pattern: t.pattern_str.clone(),
+                expires_in_secs: t
+                    .expires_at
+                    .duration_since(now)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+            })
+            .collect(),
+    }
+}
+
+/// Sends `request` to the running daemon's control socket.
+fn control_request(request: ControlRequest) -> Result<ControlResponse, String> {
+    #[cfg(unix)]
+    {
+        control::send(&control::default_path(), &request)
+            .map_err(|e| format!("Cannot reach the daemon's control socket: {}", e))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = request;
+        Err("The daemon control socket is only available on Unix".to_string())
+    }
+}
+
+/// Runs a control command and prints the daemon's answer.
+fn run_control(request: ControlRequest) -> i32 {
+    run_control_response(control_request(request))
+}
+
+/// Prints a control socket answer, returning the exit code.
+fn run_control_response(response: Result<ControlResponse, String>) -> i32 {
+    match response {
+        Ok(ControlResponse::Status(status)) => {
+            print_status(&status);
+            0
+        }
+        Ok(ControlResponse::State(state)) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&state).unwrap_or_default()
+            );
+            0
+        }
+        Ok(ControlResponse::Done { message }) => {
+            output::success(&message);
+            0
+        }
+        Ok(ControlResponse::Error { message }) => {
+            output::error(&message);
+            1
+        }
+        Err(e) => {
+            output::error(&e);
+            1
+        }
+    }
+}
+
+fn print_status(status: &DaemonStatus) {
+    let or_unknown = |v: Option<String>| v.unwrap_or_else(|| "unknown".to_string());
+    output::kv("PID", &status.pid.to_string());
+    output::kv("Chain", &status.chain);
+    output::kv("Uptime", &format!("{}s", status.uptime_secs));
+    output::kv("Head", &or_unknown(status.head.map(|h| h.to_string())));
+    output::kv("Last scanned", &status.last_scanned_block.to_string());
+    output::kv(
+        "Lag",
+        &or_unknown(status.lag.map(|l| format!("{} blocks", l))),
+    );
+    output::kv(
+        "Peers",
+        &status
+            .peers
+            .map(|p| p.to_string())
+            .unwrap_or_else(|| "P2P disabled".to_string()),
+    );
+    output::kv("Targets", &status.targets.len().to_string());
+    for target in &status.targets {
+        println!(
+            "  {} ({}) {} {}",
+            target.name.bold(),
+            target.severity,
+            target.pattern,
+            format!("expires in {}s", target.expires_in_secs).dimmed()
+        );
     }
-    let client = reqwest::Client::new();
-    let _ = client
-        .post(&url)
-        .json(&payload)
-        .timeout(std::time::Duration::from_secs(5))
-        .send()
-        .await;
 }
 
 #[cfg(unix)]
@@ -823,12 +1944,27 @@ fn stop_daemon() -> i32 {
         output::error("Daemon is not running.");
         return 1;
     }
-    let pid_str = fs::read_to_string(&pid_file).unwrap();
-    let pid = pid_str.trim().parse::<i32>().unwrap();
+    let pid = match fs::read_to_string(&pid_file) {
+        Ok(s) => match s.trim().parse::<i32>() {
+            Ok(pid) => pid,
+            Err(_) => {
+                output::error(&format!(
+                    "PID file {} is corrupt; remove it and check for a running daemon.",
+                    pid_file.display()
+                ));
+                return 1;
+            }
+        },
+        Err(e) => {
+            output::error(&format!("Cannot read {}: {}", pid_file.display(), e));
+            return 1;
+        }
+    };
     let _ = std::process::Command::new("kill")
         .arg(pid.to_string())
         .output();
     let _ = fs::remove_file(pid_file);
+    let _ = fs::remove_file(control::default_path());
     println!("Daemon stopped.");
     0
 }
@@ -862,23 +1998,36 @@ fn start_daemon(
     rpc_url: Option<String>,
     autostart: bool,
     webhook_url: Option<String>,
+    webhook_max_attempts: u32,
     threat_feed: Option<String>,
     p2p_threat_network: bool,
     expire_after_str: String,
     websocket_port: Option<u16>,
     metrics_port: Option<u16>,
+    api_auth: Arc<ApiAuth>,
+    no_links: bool,
+    dry_run: Option<DryRunSink>,
+    digest: Option<AlertDigest>,
+    fingerprints: Option<FingerprintTracker>,
+    siem: Option<SiemSink>,
+    proof_exporter: Option<ProofExporter>,
+    network_config: NetworkConfig,
 ) -> i32 {
-    let rt = tokio::runtime::Runtime::new().unwrap();
+    let Some(rt) = build_runtime() else {
+        return 1;
+    };
     let _guard = rt.enter();
 
     let expire_duration = parse_duration(&expire_after_str);
-    let expires_at = std::time::SystemTime::now() + expire_duration;
+    let expires_at = network_config.clock().now() + expire_duration;
 
     // _metrics Server Setup
     let _metrics: Option<Arc<AgentMetrics>> = metrics_port.and_then(|_port| {
         #[cfg(feature = "metrics")]
         {
-            AgentMetrics::new().ok().map(Arc::new)
+            AgentMetrics::new()
+                .ok()
+                .map(|m| Arc::new(m.with_p2p_metrics(network_config.metrics.clone())))
         }
         #[cfg(not(feature = "metrics"))]
         {
@@ -887,19 +2036,25 @@ fn start_daemon(
     });
 
     // WebSocket Server Setup
-    let ws_server = websocket_port.map(|port| Arc::new(WebSocketServer::new(port)));
+    let ws_server =
+        websocket_port.map(|port| Arc::new(WebSocketServer::new(port).with_auth(api_auth.clone())));
 
     if autostart {
         output::error("Autostart script generation is not supported on Windows.");
         return 1;
     }
 
+    if dry_run.is_none() {
+        compact_stores(expire_duration, network_config.clock().now());
+    }
+
     // --- Prepare Initial Targets ---
     let mut targets = Vec::new();
 
     // 1. Manual Pattern
+    let aliases = crate::config::load_symbol_aliases();
     if let Some(p) = pattern {
-        match sods_core::pattern::BehavioralPattern::parse(&p) {
+        match sods_core::pattern::BehavioralPattern::parse_with_aliases(&p, &aliases) {
             Ok(parsed) => {
                 targets.push(MonitoringTarget {
                     pattern: parsed,
@@ -917,8 +2072,8 @@ fn start_daemon(
         }
     }
 
-    if !p2p_threat_network && targets.is_empty() {
-        output::error("No valid patterns to monitor. Provide --pattern, --threat-feed, or enable --p2p-threat-network.");
+    if !p2p_threat_network && targets.is_empty() && fingerprints.is_none() {
+        output::error("No valid patterns to monitor. Provide --pattern, --threat-feed, --fingerprint, or enable --p2p-threat-network.");
         return 1;
     }
 
@@ -927,20 +2082,22 @@ fn start_daemon(
     if p2p_threat_network {
         println!("Network:    Connected to P2P Threat Intelligence");
     }
+    if let Some(ref sink) = siem {
+        println!("SIEM:       {} ({})", sink.target(), sink.format());
+    }
 
     // Start _metrics server if enabled
     #[cfg(feature = "metrics")]
     if let Some(ref m) = _metrics {
         if let Some(port) = metrics_port.as_ref() {
             let m_clone = m.clone();
-            rt.spawn(m_clone.start_http_server(*port));
+            rt.spawn(m_clone.serve(*port, api_auth.clone()));
         }
     }
 
-    // Start WS server if enabled
-    if let Some(ref ws) = ws_server {
-        let ws_clone = ws.clone();
-        rt.spawn(ws_clone.start());
+    // Start WS server if enabled (dry runs only log what would be broadcast)
+    if let Some(ws) = ws_server.as_ref().filter(|_| dry_run.is_none()) {
+        rt.spawn(ws.clone().start());
     }
 
     // Run the daemon loop in foreground
@@ -950,11 +2107,19 @@ fn start_daemon(
         interval.clone(),
         rpc_url.clone(),
         webhook_url.clone(),
+        webhook_max_attempts,
         Some(threat_feed.clone()),
         p2p_threat_network,
         expire_after_str.clone(),
         ws_server.clone(),
         _metrics.clone(),
+        no_links,
+        dry_run,
+        digest,
+        fingerprints,
+        siem,
+        proof_exporter,
+        network_config,
     ));
     0
 }
@@ -970,6 +2135,170 @@ fn check_status() -> bool {
     false
 }
 
+/// Dials the bootstrap peers of `config` and runs `peer` in the background.
+fn spawn_peer(mut peer: SodsPeer, config: &NetworkConfig) {
+    let bootstrap = config.bootstrap.clone();
+    tokio::spawn(async move {
+        if let Err(e) = peer.connect_bootstrap(&bootstrap).await {
+            eprintln!("P2P Bootstrap Error: {}", e);
+            output::p2p_hint(&e);
+        }
+        if let Err(e) = peer.listen("/ip4/0.0.0.0/tcp/0").await {
+            eprintln!("P2P Listen Error: {}", e);
+            output::p2p_hint(&e);
+        }
+    });
+}
+
+/// Starts a public peer and bridges threat rules between it and the
+/// private `peer`. Returns the rules the daemon should act on.
+fn start_bridge(
+    private: &SodsPeer,
+    federation: &FederationConfig,
+    network_config: &NetworkConfig,
+    rpc_url: &str,
+    expire_duration: std::time::Duration,
+) -> Option<tokio::sync::broadcast::Receiver<ThreatRule>> {
+    let public_config = federation.public_network(network_config);
+    let public = match SodsPeer::with_network_config(rpc_url, &public_config) {
+        Ok(peer) => peer,
+        Err(e) => {
+            eprintln!("Failed to initialize public bridge peer: {}", e);
+            output::p2p_hint(&e);
+            return Some(private.subscribe_threats());
+        }
+    };
+    println!("Threat bridge: public peer {}", public.peer_id());
+
+    let bridge = ThreatBridge::new(federation.policy.clone(), expire_duration)
+        .with_clock(network_config.clock());
+    let rx = bridge.subscribe();
+    let links = (MeshLink::of(&public), MeshLink::of(private));
+    spawn_peer(public, &public_config);
+    tokio::spawn(bridge.run(links.0, links.1));
+    Some(rx)
+}
+
+/// Parses the `--bridge-*` flags into a `FederationConfig`.
+fn build_federation(
+    bootstrap: &[String],
+    import_authors: &[String],
+    export_authors: &[String],
+) -> Result<FederationConfig, String> {
+    let author = |flag: &str, key: &String| {
+        hex::decode(key.trim_start_matches("0x"))
+            .ok()
+            .filter(|bytes| bytes.len() == 33)
+            .ok_or_else(|| format!("Invalid --{} public key: {}", flag, key))
+    };
+    let mut policy = FederationPolicy::new();
+    for key in import_authors {
+        policy = policy.with_import_author(author("bridge-import-author", key)?);
+    }
+    for key in export_authors {
+        policy = policy.with_export_author(author("bridge-export-author", key)?);
+    }
+    let public_bootstrap = bootstrap
+        .iter()
+        .map(|addr| {
+            addr.parse()
+                .map_err(|_| format!("Invalid --bridge-bootstrap address: {}", addr))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(FederationConfig {
+        policy,
+        public_bootstrap,
+    })
+}
+
+/// Parses the P2P access flags into a `NetworkConfig`.
+fn build_network_config(
+    allow: &[String],
+    deny: &[String],
+    psk_file: Option<&std::path::Path>,
+    bootstrap: &[String],
+) -> Result<NetworkConfig, String> {
+    let mut config = NetworkConfig::new();
+    for addr in bootstrap {
+        let addr = addr
+            .parse()
+            .map_err(|_| format!("Invalid --bootstrap address: {}", addr))?;
+        config = config.with_bootstrap_peer(addr);
+    }
+    for id in allow {
+        let peer = id
+            .parse()
+            .map_err(|_| format!("Invalid --allow-peer ID: {}", id))?;
+        config = config.with_allowed_peer(peer);
+    }
+    for id in deny {
+        let peer = id
+            .parse()
+            .map_err(|_| format!("Invalid --deny-peer ID: {}", id))?;
+        config = config.with_denied_peer(peer);
+    }
+    if let Some(path) = psk_file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read swarm key {}: {}", path.display(), e))?;
+        let psk: PreSharedKey = content
+            .parse()
+            .map_err(|e| format!("Invalid swarm key {}: {}", path.display(), e))?;
+        config = config.with_private_network(psk);
+    }
+    Ok(config)
+}
+
+/// Seeds a reputation tracker from snapshot files signed by `authors`.
+fn build_reputation(
+    files: &[PathBuf],
+    authors: &[String],
+    trust: f32,
+) -> Result<ReputationTracker, String> {
+    let mut tracker = ReputationTracker::new();
+    for key in authors {
+        let bytes = hex::decode(key.trim_start_matches("0x"))
+            .ok()
+            .filter(|bytes| bytes.len() == 33)
+            .ok_or_else(|| format!("Invalid --reputation-author public key: {}", key))?;
+        tracker.add_trusted_author(bytes);
+    }
+    for path in files {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let snapshot: ReputationSnapshot = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid reputation snapshot {}: {}", path.display(), e))?;
+        let seeded = tracker
+            .import_snapshot(&snapshot, trust)
+            .map_err(|e| format!("Rejected {}: {}", path.display(), e))?;
+        println!(
+            "Imported reputation of {} peer(s) from {}",
+            seeded,
+            path.display()
+        );
+    }
+    Ok(tracker)
+}
+
+/// Per-peer limits for the P2P node; a rate of N per minute allows bursts of N.
+fn build_rate_limits(
+    proof_rate: Option<u32>,
+    puzzle_rate: Option<u32>,
+    ban_duration: Option<&str>,
+) -> RateLimitConfig {
+    let per_minute = |n: u32| BucketLimit::new(n, f64::from(n) / 60.0);
+    let mut limits = RateLimitConfig::default();
+    if let Some(n) = proof_rate {
+        limits = limits.with_proof_requests(per_minute(n));
+    }
+    if let Some(n) = puzzle_rate {
+        limits = limits.with_puzzle_challenges(per_minute(n));
+    }
+    if let Some(d) = ban_duration {
+        limits.ban_duration = parse_duration(d);
+    }
+    limits
+}
+
 // -----------------------------------------------------------------------------
 // Entry Point
 // -----------------------------------------------------------------------------
@@ -987,39 +2316,264 @@ pub fn run_sync(args: DaemonArgs) -> i32 {
             rpc_url,
             autostart,
             webhook_url,
+            webhook_max_attempts,
+            siem,
+            siem_format,
             threat_feed,
             p2p_threat_network,
             expire_after,
             websocket_port,
             metrics_port,
-        } => start_daemon(
-            pattern,
-            chain,
-            interval,
-            rpc_url,
-            autostart,
-            webhook_url,
-            threat_feed,
-            p2p_threat_network,
-            expire_after,
-            websocket_port,
-            metrics_port,
-        ),
+            no_links,
+            dry_run,
+            dry_run_log,
+            clock_start,
+            clock_speed,
+            digest_interval,
+            digest_max_interval,
+            digest_passthrough,
+            export_proofs,
+            export_calldata,
+            fingerprints,
+            fingerprint_window,
+            fingerprint_threshold,
+            allow_peers,
+            deny_peers,
+            private_network,
+            peer_proof_rate,
+            peer_puzzle_rate,
+            peer_ban_duration,
+            role,
+            bootstrap_peers,
+            key,
+            bridge,
+            bridge_bootstrap,
+            bridge_import_authors,
+            bridge_export_authors,
+            import_reputation,
+            reputation_authors,
+            reputation_trust,
+        } => {
+            let mut network_config = match build_network_config(
+                &allow_peers,
+                &deny_peers,
+                private_network.as_deref(),
+                &bootstrap_peers,
+            ) {
+                Ok(c) => c.with_role(role).with_rate_limits(build_rate_limits(
+                    peer_proof_rate,
+                    peer_puzzle_rate,
+                    peer_ban_duration.as_deref(),
+                )),
+                Err(e) => {
+                    output::error(&e);
+                    return 1;
+                }
+            };
+            if bridge {
+                match build_federation(
+                    &bridge_bootstrap,
+                    &bridge_import_authors,
+                    &bridge_export_authors,
+                ) {
+                    Ok(f) => network_config = network_config.with_federation(f),
+                    Err(e) => {
+                        output::error(&e);
+                        return 1;
+                    }
+                }
+            }
+            if !import_reputation.is_empty() {
+                match build_reputation(&import_reputation, &reputation_authors, reputation_trust) {
+                    Ok(tracker) => network_config = network_config.with_reputation(tracker),
+                    Err(e) => {
+                        output::error(&e);
+                        return 1;
+                    }
+                }
+            }
+            if clock_start.is_some() || clock_speed.is_some() {
+                let start = clock_start.map_or_else(std::time::SystemTime::now, |secs| {
+                    std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs)
+                });
+                let clock = sods_core::ScaledClock::new(start, clock_speed.unwrap_or(1));
+                println!(
+                    "Simulated clock: starting at {}, {}x real time",
+                    clock.unix_secs(),
+                    clock_speed.unwrap_or(1).max(1)
+                );
+                network_config = network_config.with_clock(clock.shared());
+            }
+            // Decrypt before daemonizing, while a terminal is still attached
+            if let Some(name) = &key {
+                match crate::keys::load_signing_key(name) {
+                    Ok(k) => network_config = network_config.with_signing_key(k),
+                    Err(e) => {
+                        output::error(&e);
+                        return 1;
+                    }
+                }
+            }
+            if let Err(e) = check_start_config(&chain, rpc_url.as_deref()) {
+                output::error(&e);
+                return 1;
+            }
+            // Resolved before daemonizing, which changes directory
+            let proof_exporter = match &export_proofs {
+                Some(dir) => match ProofExporter::open(dir) {
+                    Ok(e) => Some(e.with_calldata(export_calldata)),
+                    Err(e) => {
+                        output::error(&e);
+                        return 1;
+                    }
+                },
+                None => None,
+            };
+            let daemon_config = match crate::config::DaemonConfig::load() {
+                Ok(config) => config,
+                Err(e) => {
+                    output::error(&e);
+                    return 1;
+                }
+            };
+            let api_auth = match daemon_config.api_auth() {
+                Ok(auth) => Arc::new(auth),
+                Err(e) => {
+                    output::error(&e);
+                    return 1;
+                }
+            };
+            let network_config = daemon_config.p2p.apply(network_config);
+            let fingerprints = if fingerprints.is_empty() {
+                None
+            } else {
+                // A dry run starts from the saved fingerprints but never saves them
+                match FingerprintHistory::get_default_path()
+                    .and_then(|path| FingerprintHistory::load(&path))
+                {
+                    Ok(history) => Some(
+                        FingerprintTracker::new(fingerprints)
+                            .with_window(fingerprint_window)
+                            .with_threshold(fingerprint_threshold)
+                            .with_history(history),
+                    ),
+                    Err(e) => {
+                        output::error(&e.to_string());
+                        return 1;
+                    }
+                }
+            };
+            if role == NodeRole::QueryOnly && network_config.bootstrap.is_empty() {
+                output::warning(
+                    "A query-only node has no listener; pass --bootstrap to reach the network",
+                );
+            }
+            start_daemon(
+                pattern,
+                chain,
+                interval,
+                rpc_url,
+                autostart,
+                webhook_url,
+                webhook_max_attempts,
+                threat_feed,
+                p2p_threat_network,
+                expire_after,
+                websocket_port,
+                metrics_port,
+                api_auth,
+                no_links,
+                dry_run
+                    .then(|| DryRunSink::new(dry_run_log.unwrap_or_else(DryRunSink::default_path))),
+                digest_interval.map(|interval| {
+                    AlertDigest::new(
+                        parse_duration(&interval),
+                        &digest_passthrough,
+                        network_config.clock().unix_secs(),
+                    )
+                    .with_max_interval(parse_duration(&digest_max_interval))
+                }),
+                fingerprints,
+                siem.map(|target| SiemSink::new(target, siem_format)),
+                proof_exporter,
+                network_config,
+            )
+        }
         DaemonCommands::Stop => stop_daemon(),
         DaemonCommands::Status => {
-            if check_status() {
-                println!("{}", "✅ SODS daemon is running".green().bold());
-            } else {
+            if !check_status() {
                 println!("SODS daemon is not running.");
+                return 0;
+            }
+            println!("{}", "✅ SODS daemon is running".green().bold());
+            match control_request(ControlRequest::Status) {
+                Ok(ControlResponse::Status(status)) => print_status(&status),
+                Ok(_) => output::warning("Unexpected answer from the daemon"),
+                Err(e) => output::hint(&format!("No runtime details: {}", e)),
             }
             0
         }
+        DaemonCommands::AddTarget {
+            name,
+            pattern,
+            severity,
+        } => run_control(ControlRequest::AddTarget {
+            name,
+            pattern,
+            severity: Some(severity),
+        }),
+        DaemonCommands::RemoveTarget { name } => run_control(ControlRequest::RemoveTarget { name }),
+        DaemonCommands::ReloadConfig => run_control(ControlRequest::ReloadConfig),
+        DaemonCommands::DumpState => run_control(ControlRequest::DumpState),
+        DaemonCommands::ExportReputation { output: path } => {
+            match control_request(ControlRequest::ExportReputation) {
+                Ok(ControlResponse::State(snapshot)) => {
+                    let json = serde_json::to_string_pretty(&snapshot).unwrap_or_default();
+                    if let Err(e) = fs::write(&path, json) {
+                        output::error(&format!("Failed to write {}: {}", path.display(), e));
+                        return 1;
+                    }
+                    let peers = snapshot["peers"].as_array().map_or(0, |p| p.len());
+                    output::success(&format!(
+                        "Reputation of {} peer(s) written to {}",
+                        peers,
+                        path.display()
+                    ));
+                    if let Some(author) = snapshot["author_pubkey"].as_array() {
+                        let bytes: Vec<u8> = author
+                            .iter()
+                            .filter_map(|b| b.as_u64().map(|b| b as u8))
+                            .collect();
+                        output::kv("Author", &hex::encode(bytes));
+                    }
+                    0
+                }
+                other => run_control_response(other),
+            }
+        }
+        DaemonCommands::SwarmKey { output: path } => {
+            if path.exists() {
+                output::error(&format!("{} already exists", path.display()));
+                return 1;
+            }
+            let psk = PreSharedKey::generate();
+            let key_file = format!("{}\n", psk);
+            if let Err(e) = crate::keys::write_private_file(&path, key_file.as_bytes()) {
+                output::error(&format!("Failed to write swarm key: {}", e));
+                return 1;
+            }
+            output::success(&format!("Swarm key written to {}", path.display()));
+            output::kv("Fingerprint", &psk.fingerprint());
+            output::hint("Share this file only with members of the private network.");
+            0
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sods_core::clock::MockClock;
     use sods_core::pattern::BehavioralPattern;
     use std::time::{Duration, SystemTime};
 
@@ -1031,8 +2585,79 @@ mod tests {
         assert_eq!(parse_duration("5x"), Duration::from_secs(5 * 3600));
     }
 
+    #[test]
+    fn test_build_network_config() {
+        let peer = "12D3KooWD3eckifWpRn9wQpMG9R9hX3sD158z7EqHWmweQAJU5SA".to_string();
+        let config = build_network_config(&[peer], &[], None, &[]).unwrap();
+        assert!(config.allowlist.is_some());
+        assert!(build_network_config(&[], &["not-a-peer".into()], None, &[]).is_err());
+        let missing = std::path::Path::new("/nonexistent/swarm.key");
+        assert!(build_network_config(&[], &[], Some(missing), &[]).is_err());
+
+        let seed = "/ip4/10.0.0.1/tcp/4001".to_string();
+        let config = build_network_config(&[], &[], None, &[seed]).unwrap();
+        assert_eq!(config.bootstrap.len(), 1);
+        assert!(build_network_config(&[], &[], None, &["10.0.0.1:4001".into()]).is_err());
+    }
+
+    #[test]
+    fn test_build_federation() {
+        let key = ethers::core::k256::ecdsa::SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let author = hex::encode(key.verifying_key().to_sec1_bytes());
+        let seed = "/ip4/10.0.0.1/tcp/4001".to_string();
+
+        let federation = build_federation(&[seed], &[], &[format!("0x{}", author)]).unwrap();
+        assert_eq!(federation.public_bootstrap.len(), 1);
+        assert!(federation.policy.import_authors.is_none());
+        assert_eq!(federation.policy.export_authors.len(), 1);
+
+        assert!(build_federation(&[], &["abcd".into()], &[]).is_err());
+        assert!(build_federation(&["10.0.0.1:4001".into()], &[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_build_rate_limits() {
+        let limits = build_rate_limits(Some(30), None, Some("2h"));
+        assert_eq!(limits.proof_requests, BucketLimit::new(30, 0.5));
+        assert_eq!(
+            limits.puzzle_challenges,
+            RateLimitConfig::default().puzzle_challenges
+        );
+        assert_eq!(limits.ban_duration, std::time::Duration::from_secs(7200));
+    }
+
     #[test]
     fn test_target_retention() {
+        let clock = MockClock::default();
+        let target = |name: &str, ttl: u64| MonitoringTarget {
+            pattern: BehavioralPattern::parse("Tf").unwrap(),
+            name: name.to_string(),
+            severity: "info".to_string(),
+            pattern_str: "Tf".to_string(),
+            chain: "base".to_string(),
+            expires_at: clock.now() + Duration::from_secs(ttl),
+        };
+        let mut targets = vec![target("Short", 60), target("Long", 3600)];
+
+        assert_eq!(prune_expired(&mut targets, clock.now()), 0);
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(prune_expired(&mut targets, clock.now()), 1);
+        assert_eq!(targets[0].name, "Long");
+        clock.advance(Duration::from_secs(3600));
+        assert_eq!(prune_expired(&mut targets, clock.now()), 1);
+        assert!(targets.is_empty());
+    }
+
+    #[test]
+    fn test_alert_time_follows_the_clock() {
+        let clock = MockClock::at_unix(1_700_000_000);
+        assert_eq!(alert_time(&clock).timestamp(), 1_700_000_000);
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(alert_time(&clock).to_rfc3339(), "2023-11-14T22:14:50+00:00");
+    }
+
+    #[test]
+    fn test_target_retention_against_system_time() {
         let mut targets = vec![
             MonitoringTarget {
                 pattern: BehavioralPattern::parse("Tf").unwrap(),
@@ -1052,8 +2677,148 @@ mod tests {
             },
         ];
 
-        targets.retain(|t| SystemTime::now() < t.expires_at);
+        assert_eq!(prune_expired(&mut targets, SystemTime::now()), 1);
         assert_eq!(targets.len(), 1);
         assert_eq!(targets[0].name, "Active");
     }
+
+    #[test]
+    fn test_control_requests_edit_targets() {
+        let verifier =
+            sods_verifier::BlockVerifier::new(&["http://127.0.0.1:8545".to_string()]).unwrap();
+        let clock = MockClock::default();
+        let view = ControlView {
+            chain: "base",
+            started: clock.instant(),
+            head: Some(120),
+            last_scanned_block: 100,
+            peers: None,
+            reputation: None,
+            schedule: PollSchedule::fixed(Duration::from_secs(30)),
+            expire_duration: Duration::from_secs(3600),
+            webhooks: None,
+            clock: &clock,
+        };
+        let mut targets = Vec::new();
+        let add = |name: &str, pattern: &str| ControlRequest::AddTarget {
+            name: name.into(),
+            pattern: pattern.into(),
+            severity: None,
+        };
+
+        let response = handle_control(add("Drain", "Tf{3,}"), &mut targets, &verifier, &view);
+        assert!(matches!(response, ControlResponse::Done { .. }));
+        let response = handle_control(add("Drain", "Sw"), &mut targets, &verifier, &view);
+        assert!(matches!(response, ControlResponse::Error { .. }));
+        let response = handle_control(add("Broken", "Tf ->"), &mut targets, &verifier, &view);
+        assert!(matches!(response, ControlResponse::Error { .. }));
+
+        match handle_control(ControlRequest::Status, &mut targets, &verifier, &view) {
+            ControlResponse::Status(status) => {
+                assert_eq!(status.lag, Some(20));
+                assert_eq!(status.targets.len(), 1);
+                assert_eq!(status.targets[0].severity, "manual");
+                assert_eq!(status.targets[0].expires_in_secs, 3600);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        clock.advance(Duration::from_secs(600));
+        match handle_control(ControlRequest::Status, &mut targets, &verifier, &view) {
+            ControlResponse::Status(status) => {
+                assert_eq!(status.uptime_secs, 600);
+                assert_eq!(status.targets[0].expires_in_secs, 3000);
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+
+        let remove = |name: &str| ControlRequest::RemoveTarget { name: name.into() };
+        let response = handle_control(remove("Drain"), &mut targets, &verifier, &view);
+        assert!(matches!(response, ControlResponse::Done { .. }));
+        assert!(targets.is_empty());
+        let response = handle_control(remove("Drain"), &mut targets, &verifier, &view);
+        assert!(matches!(response, ControlResponse::Error { .. }));
+    }
+
+    #[test]
+    fn test_rule_expiry_follows_creation_time() {
+        let key = ethers::core::k256::ecdsa::SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let mut rule = ThreatRule::new("old", "Old", "Tf", "base", "high", &key);
+        let day = Duration::from_secs(86_400);
+        assert!(rule_expiry(&rule, day) > SystemTime::now());
+
+        // Reloading a two-day-old rule does not restart its lifetime
+        rule.timestamp -= 2 * 86_400;
+        assert!(rule_expiry(&rule, day) < SystemTime::now());
+
+        // A rule from the far future is dropped rather than overflowing
+        rule.timestamp = u64::MAX;
+        assert!(rule_expiry(&rule, day) < SystemTime::now());
+    }
+
+    #[test]
+    fn test_check_start_config() {
+        assert!(check_start_config("base", None).is_ok());
+        assert!(check_start_config("base", Some("https://mainnet.base.org")).is_ok());
+        assert!(check_start_config("nochain", None).is_err());
+        assert!(check_start_config("base", Some("mainnet.base.org")).is_err());
+        assert!(check_start_config("base", Some("ftp://mainnet.base.org")).is_err());
+    }
+
+    #[test]
+    fn test_malformed_rules_file_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("threat_rules.json");
+        assert!(load_persisted_rules(&path).is_empty());
+        fs::write(&path, b"[{\"id\": 1}").unwrap();
+        assert!(load_persisted_rules(&path).is_empty());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn fuzz_durations_are_bounded(input in "\\PC{0,24}") {
+            let duration = parse_duration(&input);
+            proptest::prop_assert!(duration <= MAX_CONFIG_DURATION);
+            // Expiry times are computed by adding to now
+            let _ = SystemTime::now() + duration;
+
+            let interval = parse_interval(&input);
+            proptest::prop_assert!(interval > Duration::ZERO && interval <= MAX_CONFIG_DURATION);
+        }
+
+        #[test]
+        fn fuzz_start_config_never_panics(
+            chain in "\\PC{0,16}",
+            rpc_url in proptest::option::of("\\PC{0,40}"),
+            allow in proptest::collection::vec("\\PC{0,60}", 0..3),
+            bootstrap in proptest::collection::vec("\\PC{0,60}", 0..3),
+            ban in proptest::option::of("\\PC{0,12}"),
+            rate in proptest::option::of(proptest::prelude::any::<u32>()),
+        ) {
+            let _ = check_start_config(&chain, rpc_url.as_deref());
+            let _ = build_network_config(&allow, &allow, None, &bootstrap);
+            let limits = build_rate_limits(rate, rate, ban.as_deref());
+            proptest::prop_assert!(limits.ban_duration <= MAX_CONFIG_DURATION);
+        }
+
+        #[test]
+        fn fuzz_persisted_state_files(bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..256)) {
+            let dir = tempfile::tempdir().unwrap();
+            let rules = dir.path().join("threat_rules.json");
+            fs::write(&rules, &bytes).unwrap();
+            let _ = load_persisted_rules(&rules);
+
+            let seen = dir.path().join("seen_threats.json");
+            fs::write(&seen, &bytes).unwrap();
+            let _ = SeenCache::load(&seen, Duration::from_secs(3600));
+        }
+
+        #[test]
+        fn fuzz_rule_expiry(timestamp in proptest::prelude::any::<u64>(), expire in "\\PC{0,24}") {
+            let key = ethers::core::k256::ecdsa::SigningKey::from_slice(&[1u8; 32]).unwrap();
+            let mut rule = ThreatRule::new("r", "R", "Tf", "base", "high", &key);
+            rule.timestamp = timestamp;
+            let _ = rule_expiry(&rule, parse_duration(&expire));
+        }
+    }
 }
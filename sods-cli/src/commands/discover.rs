@@ -7,6 +7,17 @@ use tokio::time::sleep;
 
 use crate::config::{get_chain, is_symbol_supported, SYMBOLS};
 use crate::output;
+use crate::sampling::{self, Estimate, SamplingStrategy};
+
+/// Maximum blocks scanned per run (full receipts fetched).
+const MAX_SCANNED_BLOCKS: u64 = 200;
+
+/// Maximum range a sampled discovery may cover.
+const MAX_SAMPLED_RANGE: u64 = 100_000;
+
+/// Maximum range a bloom-guided discovery may cover: one header is
+/// fetched per block in the range.
+const MAX_BLOOM_RANGE: u64 = 2_000;
 
 /// Arguments for the discover command.
 #[derive(Args)]
@@ -19,10 +30,27 @@ pub struct DiscoverArgs {
     #[arg(short, long, default_value = "sepolia")]
     pub chain: String,
 
-    /// Number of recent blocks to scan (max 200)
+    /// Number of recent blocks to cover (max 200 for a full scan, 2000 for
+    /// bloom sampling, 100000 for other sampling)
     #[arg(short, long, default_value = "50")]
     pub last: u64,
 
+    /// Block sampling strategy
+    #[arg(long, value_enum, default_value = "full")]
+    pub strategy: SamplingStrategy,
+
+    /// Scan every Nth block (every-nth strategy)
+    #[arg(long, default_value = "10")]
+    pub step: u64,
+
+    /// Number of blocks to scan (random and bloom strategies, max 200)
+    #[arg(long, default_value = "200")]
+    pub sample_size: u64,
+
+    /// Seed for random sampling
+    #[arg(long, default_value = "0")]
+    pub seed: u64,
+
     /// Timeout in seconds
     #[arg(short, long, default_value = "30")]
     pub timeout: u64,
@@ -39,6 +67,10 @@ struct DiscoveryOutput {
     chain: String,
     symbol: String,
     scanned_blocks: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    strategy: Option<SamplingStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    estimate: Option<Estimate>,
     top_blocks: Vec<BlockCount>,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
@@ -51,8 +83,11 @@ struct BlockCount {
 }
 
 /// Run the discover command.
-pub async fn run(args: DiscoverArgs) -> i32 {
-    // 1. Validate inputs
+pub async fn run(mut args: DiscoverArgs) -> i32 {
+    // 1. Validate inputs; everything below works on the canonical symbol
+    args.symbol = crate::config::load_symbol_aliases()
+        .resolve(&args.symbol)
+        .to_string();
     if !is_symbol_supported(&args.symbol) {
         if args.json {
             print_json_error(format!("Unsupported symbol: '{}'", args.symbol));
@@ -83,18 +118,23 @@ pub async fn run(args: DiscoverArgs) -> i32 {
         }
     };
 
-    let max_blocks = 200;
+    let max_blocks = match args.strategy {
+        SamplingStrategy::Full => MAX_SCANNED_BLOCKS,
+        SamplingStrategy::Bloom => MAX_BLOOM_RANGE,
+        _ => MAX_SAMPLED_RANGE,
+    };
     let scan_count = if args.last > max_blocks {
         output::warning(&format!("Limiting scan to max {} blocks.", max_blocks));
         max_blocks
     } else {
-        args.last
+        args.last.max(1)
     };
+    let sample_size = args.sample_size.clamp(1, MAX_SCANNED_BLOCKS) as usize;
 
     if !args.json {
         output::info(&format!(
-            "🔍 Scanning last {} blocks on {} for '{}' events...",
-            scan_count, chain_config.description, args.symbol
+            "🔍 Scanning last {} blocks on {} for '{}' events ({:?} sampling)...",
+            scan_count, chain_config.description, args.symbol, args.strategy
         ));
     }
 
@@ -146,18 +186,59 @@ pub async fn run(args: DiscoverArgs) -> i32 {
         }
     };
 
-    // 4. Scan blocks
+    // 4. Choose blocks (newest first)
+    let range = latest_block.saturating_sub(scan_count - 1)..=latest_block;
+    let (blocks, population) = match args.strategy {
+        SamplingStrategy::Full => (range.clone().rev().collect(), sampling::range_len(&range)),
+        SamplingStrategy::EveryNth => {
+            // Widen the step so the scan stays within budget
+            let step = args
+                .step
+                .max((sampling::range_len(&range) + MAX_SCANNED_BLOCKS - 1) / MAX_SCANNED_BLOCKS);
+            (
+                sampling::every_nth(range.clone(), step),
+                sampling::range_len(&range),
+            )
+        }
+        SamplingStrategy::Random => (
+            sampling::random_sample(range.clone(), sample_size, args.seed),
+            sampling::range_len(&range),
+        ),
+        SamplingStrategy::Bloom => {
+            let candidates = bloom_candidates(&verifier, &args.symbol, range.clone()).await;
+            if !args.json {
+                output::info(&format!(
+                    "Bloom filter kept {} of {} blocks",
+                    candidates.len(),
+                    sampling::range_len(&range)
+                ));
+            }
+            let population = candidates.len() as u64;
+            let blocks = if candidates.len() > sample_size {
+                // Indices come back descending; candidates are newest first
+                sampling::random_sample(0..=population - 1, sample_size, args.seed)
+                    .into_iter()
+                    .rev()
+                    .map(|i| candidates[i as usize])
+                    .collect()
+            } else {
+                candidates
+            };
+            (blocks, population)
+        }
+    };
+
+    // 5. Scan blocks
     let mut results = Vec::new();
-    let start_block = latest_block.saturating_sub(scan_count - 1);
-    let end_block = latest_block;
+    let mut counts = Vec::with_capacity(blocks.len());
 
-    // Iterate in reverse (newest first)
-    for block_num in (start_block..=end_block).rev() {
+    for block_num in blocks {
         match verifier
             .verify_symbol_in_block(&args.symbol, block_num)
             .await
         {
             Ok(result) => {
+                counts.push(result.occurrences);
                 if result.occurrences > 0 {
                     results.push(BlockCount {
                         block: block_num,
@@ -177,21 +258,42 @@ pub async fn run(args: DiscoverArgs) -> i32 {
         sleep(Duration::from_millis(500)).await;
     }
 
-    // 5. Rank results
-    results.sort_by(|a, b| b.count.cmp(&a.count)); // Descending by count
+    // 6. Rank results
+    results.sort_by_key(|r| std::cmp::Reverse(r.count)); // Descending by count
+    let estimate = (args.strategy != SamplingStrategy::Full)
+        .then(|| sampling::extrapolate(&counts, population));
 
-    // 6. Output
+    // 7. Output
     if args.json {
         let output = DiscoveryOutput {
             success: true,
             chain: args.chain.clone(),
             symbol: args.symbol.clone(),
-            scanned_blocks: scan_count,
+            scanned_blocks: counts.len() as u64,
+            strategy: Some(args.strategy),
+            estimate,
             top_blocks: results,
             error: None,
         };
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
-    } else if results.is_empty() {
+        return 0;
+    }
+
+    if let Some(est) = &estimate {
+        output::kv(
+            "Sampled",
+            &format!("{} of {} blocks", est.sampled, est.population),
+        );
+        output::kv(
+            "Estimated total",
+            &format!(
+                "{:.0} {} events (95% CI {:.0}–{:.0})",
+                est.estimated_total, args.symbol, est.ci_low, est.ci_high
+            ),
+        );
+    }
+
+    if results.is_empty() {
         output::info("No events found in the scanned range.");
     } else {
         for (i, res) in results.iter().take(10).enumerate() {
@@ -217,8 +319,32 @@ fn print_json_error(msg: String) {
         chain: "unknown".into(),
         symbol: "unknown".into(),
         scanned_blocks: 0,
+        strategy: None,
+        estimate: None,
         top_blocks: vec![],
         error: Some(msg),
     };
     println!("{}", serde_json::to_string_pretty(&output).unwrap());
 }
+
+/// Blocks in `range` (newest first) whose logsBloom may contain the symbol.
+///
+/// Only headers are fetched. Blocks whose header cannot be fetched are kept
+/// so they are not silently treated as empty.
+async fn bloom_candidates(
+    verifier: &sods_verifier::BlockVerifier,
+    symbol: &str,
+    range: std::ops::RangeInclusive<u64>,
+) -> Vec<u64> {
+    use sods_verifier::header_anchor::bloom_contains_any_topic;
+
+    let topics = verifier.dictionary().topics_for_symbol(symbol);
+    let mut candidates = Vec::new();
+    for block_num in range.rev() {
+        match verifier.rpc_client().fetch_block_header(block_num).await {
+            Ok(header) if !bloom_contains_any_topic(&header.logs_bloom, &topics) => {}
+            _ => candidates.push(block_num),
+        }
+    }
+    candidates
+}
@@ -1,13 +1,25 @@
 use crate::config::get_chain;
+use crate::credential::BehavioralCredential;
+use crate::eas;
+use crate::keys;
 use crate::output;
+use crate::unsigned_bundle::UnsignedBundle;
 use clap::{Args, ValueEnum};
+use ethers_core::types::Address;
+use ethers_signers::{LocalWallet, Signer};
 use sods_core::pattern::BehavioralPattern;
+use sods_core::proof::OnChainBehavioralProof;
 use sods_core::BehavioralMerkleTree;
+use std::path::PathBuf;
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum Format {
     Calldata,
     Json,
+    /// W3C Verifiable Credential issued by the signing key
+    Vc,
+    /// Ethereum Attestation Service request, with its schema
+    Eas,
 }
 
 #[derive(Args)]
@@ -23,7 +35,7 @@ pub struct ExportProofArgs {
     #[arg(short, long, default_value = "sepolia")]
     pub chain: String,
 
-    /// Output format
+    /// Output format (`vc` requires --signing-key)
     #[arg(short, long, default_value = "calldata")]
     pub format: Format,
 
@@ -35,16 +47,61 @@ pub struct ExportProofArgs {
     #[arg(long)]
     pub anchored: bool,
 
-    /// Private key to sign the behavioral commitment (hex)
+    /// Name of the signing key for the behavioral commitment (see `sods keys`)
     #[arg(long)]
     pub signing_key: Option<String>,
 
     /// Address authorized to sign commitments (hex)
     #[arg(long)]
     pub trusted_signer: Option<String>,
+
+    /// IPFS/Arweave CID of a published proof bundle to bind into the signed commitment
+    #[arg(long)]
+    pub availability_cid: Option<String>,
+
+    /// Write an unsigned bundle to this file, to be signed offline with `sods sign-proof`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["signing_key", "format"])]
+    pub unsigned: Option<PathBuf>,
+
+    /// Submit the EAS attestation on-chain, attested by --signing-key
+    #[arg(long, requires = "signing_key")]
+    pub submit_eas: bool,
+
+    /// EAS contract address (defaults to the chain's deployment)
+    #[arg(long, value_name = "ADDRESS")]
+    pub eas_contract: Option<String>,
+
+    /// Recipient of the EAS attestation (hex address)
+    #[arg(long, value_name = "ADDRESS")]
+    pub eas_recipient: Option<String>,
 }
 
 pub async fn run(args: ExportProofArgs) -> i32 {
+    let wallet: Option<LocalWallet> = match args.signing_key.as_deref().map(keys::load_wallet) {
+        None => None,
+        Some(Ok(w)) => Some(w),
+        Some(Err(e)) => {
+            output::error(&e);
+            return 1;
+        }
+    };
+    if matches!(args.format, Format::Vc) && wallet.is_none() {
+        output::error("--format vc requires --signing-key (the credential issuer).");
+        return 1;
+    }
+    if args.submit_eas && !matches!(args.format, Format::Eas) {
+        output::error("--submit-eas requires --format eas.");
+        return 1;
+    }
+    let eas_recipient = match args.eas_recipient.as_deref().map(str::parse::<Address>) {
+        None => None,
+        Some(Ok(a)) => Some(a),
+        Some(Err(e)) => {
+            output::error(&format!("Invalid --eas-recipient: {}", e));
+            return 1;
+        }
+    };
+
     let chain_config = match get_chain(&args.chain) {
         Some(c) => c,
         None => {
@@ -72,7 +129,7 @@ pub async fn run(args: ExportProofArgs) -> i32 {
     };
 
     output::info(&format!("Fetching symbols for block {}...", args.block));
-    let symbols = match verifier.fetch_block_symbols(args.block).await {
+    let mut symbols = match verifier.fetch_block_symbols(args.block).await {
         Ok(s) => s,
         Err(e) => {
             output::error(&format!("Failed to fetch symbols: {}", e));
@@ -80,13 +137,20 @@ pub async fn run(args: ExportProofArgs) -> i32 {
         }
     };
 
-    let pattern = match BehavioralPattern::parse(&args.pattern) {
+    let aliases = crate::config::load_symbol_aliases();
+    let pattern = match BehavioralPattern::parse_with_aliases(&args.pattern, &aliases) {
         Ok(p) => p,
         Err(e) => {
             output::error(&format!("Invalid pattern: {}", e));
             return 1;
         }
     };
+    if pattern.has_gas_condition() {
+        if let Err(e) = verifier.fill_gas_used(&mut symbols).await {
+            output::error(&format!("Failed to fetch receipts: {}", e));
+            return 1;
+        }
+    }
 
     if let Some(matched) = pattern.matches(&symbols, None) {
         // Build Keccak BMT (new() uses Keccak256 by default)
@@ -122,7 +186,7 @@ pub async fn run(args: ExportProofArgs) -> i32 {
             beacon_root,
             timestamp,
         ) {
-            Some(p) => p,
+            Some(p) => p.with_dictionary_fingerprint(verifier.dictionary().fingerprint()),
             None => {
                 output::error("Failed to generate on-chain proof.");
                 return 1;
@@ -131,25 +195,39 @@ pub async fn run(args: ExportProofArgs) -> i32 {
 
         proof.receipts_root = receipts_root;
 
+        if let Some(path) = &args.unsigned {
+            let bundle = UnsignedBundle::new(
+                &args.pattern,
+                proof,
+                args.availability_cid,
+                chrono::Utc::now().timestamp() as u64,
+            );
+            if let Err(e) = bundle.save(path) {
+                output::error(&e);
+                return 1;
+            }
+            output::success(&format!("Unsigned bundle written to {}", path.display()));
+            output::kv("Bundle digest", &bundle.digest_hex());
+            output::hint(
+                "Confirm this digest when running `sods sign-proof` on the signing machine.",
+            );
+            return 0;
+        }
+
         // Signing logic
-        if let Some(key_str) = args.signing_key {
+        if let Some(wallet) = &wallet {
             output::info("Signing behavioral commitment...");
-            use ethers_signers::{LocalWallet, Signer};
 
-            let wallet: LocalWallet = match key_str.parse() {
-                Ok(w) => w,
-                Err(e) => {
-                    output::error(&format!("Invalid signing key: {}", e));
-                    return 1;
-                }
-            };
-
-            let commitment = sods_core::BehavioralCommitment::new(
+            let mut commitment = sods_core::BehavioralCommitment::new(
                 chain_id,
                 args.block,
                 receipts_root.unwrap_or([0u8; 32]),
                 bmt.root(),
             );
+            if let Some(cid) = args.availability_cid {
+                commitment = commitment.with_availability_cid(cid.clone());
+                proof.availability_cid = Some(cid);
+            }
 
             let hash = commitment.hash();
 
@@ -166,18 +244,110 @@ pub async fn run(args: ExportProofArgs) -> i32 {
             }
         }
 
-        match args.format {
-            Format::Calldata => {
-                let calldata = proof.to_calldata();
-                println!("0x{}", hex::encode(calldata));
+        if !matches!(args.format, Format::Eas) {
+            return print_proof(args.format, &args.pattern, proof, wallet.as_ref());
+        }
+        let mut attestation =
+            eas::Attestation::new(&args.pattern, pattern.canonical_hash(), &proof);
+        if let Some(recipient) = eas_recipient {
+            attestation = attestation.with_recipient(recipient);
+        }
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&attestation.to_json()).unwrap()
+        );
+        let (true, Some(wallet)) = (args.submit_eas, wallet) else {
+            return 0;
+        };
+
+        let contract = match &args.eas_contract {
+            Some(addr) => match addr.parse::<Address>() {
+                Ok(a) => a,
+                Err(e) => {
+                    output::error(&format!("Invalid --eas-contract: {}", e));
+                    return 1;
+                }
+            },
+            None => match eas::contract_address(chain_config.chain_id) {
+                Some(a) => a,
+                None => {
+                    output::error(&format!(
+                        "No known EAS deployment on {}; pass --eas-contract.",
+                        chain_config.name
+                    ));
+                    return 1;
+                }
+            },
+        };
+        output::info(&format!(
+            "Submitting attestation to EAS at {:?}...",
+            contract
+        ));
+        match eas::submit(&rpc_urls[0], wallet, contract, &attestation).await {
+            Ok(submission) => {
+                output::success("Attestation submitted");
+                output::kv("Transaction", &format!("{:?}", submission.tx_hash));
+                if let Some(uid) = submission.uid {
+                    output::kv("Attestation UID", &format!("{:?}", uid));
+                }
+                0
             }
-            Format::Json => {
-                println!("{}", serde_json::to_string_pretty(&proof).unwrap());
+            Err(e) => {
+                output::error(&format!("EAS submission failed: {}", e));
+                1
             }
         }
-        0
     } else {
         output::error("Pattern not found in block.");
         1
     }
 }
+
+/// Print `proof` in `format`; `vc` needs the issuer's `wallet`.
+pub(crate) fn print_proof(
+    format: Format,
+    pattern: &str,
+    proof: OnChainBehavioralProof,
+    wallet: Option<&LocalWallet>,
+) -> i32 {
+    match format {
+        Format::Calldata => {
+            let calldata = proof.to_calldata();
+            println!("0x{}", hex::encode(calldata));
+        }
+        Format::Json => {
+            println!("{}", serde_json::to_string_pretty(&proof).unwrap());
+        }
+        Format::Eas => {
+            let aliases = crate::config::load_symbol_aliases();
+            let hash = match BehavioralPattern::parse_with_aliases(pattern, &aliases) {
+                Ok(p) => p.canonical_hash(),
+                Err(e) => {
+                    output::error(&format!("Invalid pattern: {}", e));
+                    return 1;
+                }
+            };
+            let attestation = eas::Attestation::new(pattern, hash, &proof);
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&attestation.to_json()).unwrap()
+            );
+        }
+        Format::Vc => {
+            let Some(wallet) = wallet else {
+                return 1;
+            };
+            let now = chrono::Utc::now();
+            let credential =
+                BehavioralCredential::new(pattern, proof, wallet.address(), now).sign(wallet, now);
+            match credential {
+                Ok(vc) => println!("{}", serde_json::to_string_pretty(&vc).unwrap()),
+                Err(e) => {
+                    output::error(&format!("Failed to sign credential: {}", e));
+                    return 1;
+                }
+            }
+        }
+    }
+    0
+}
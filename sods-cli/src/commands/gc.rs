@@ -0,0 +1,105 @@
+//! Gc command implementation.
+//!
+//! Applies the retention policies in `crate::retention` to the files under
+//! `~/.sods` and reports what each store gave up.
+
+use clap::Args;
+use colored::Colorize;
+use serde::Serialize;
+use std::time::SystemTime;
+
+use crate::output;
+use crate::retention::{self, format_size, Compaction};
+
+#[derive(Args)]
+pub struct GcArgs {
+    /// Report what would be removed without removing it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct GcReport {
+    dry_run: bool,
+    freed_bytes: u64,
+    stores: Vec<Compaction>,
+    errors: Vec<String>,
+}
+
+/// Run the gc command.
+pub fn run(args: GcArgs) -> i32 {
+    let stores = match retention::configured_stores(None) {
+        Ok(stores) => stores,
+        Err(e) => {
+            output::error(&format!("Invalid retention config: {}", e));
+            return 1;
+        }
+    };
+
+    let mut report = GcReport {
+        dry_run: args.dry_run,
+        freed_bytes: 0,
+        stores: Vec::new(),
+        errors: Vec::new(),
+    };
+    for result in retention::compact_all(&stores, SystemTime::now(), args.dry_run) {
+        match result {
+            Ok(compaction) => report.stores.push(compaction),
+            Err(e) => report.errors.push(e),
+        }
+    }
+    report.freed_bytes = report.stores.iter().map(Compaction::freed).sum();
+
+    if args.json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                output::error(&format!("Failed to encode report: {}", e));
+                return 1;
+            }
+        }
+    } else {
+        output::header("SODS Storage");
+        for (store, compaction) in stores
+            .iter()
+            .filter_map(|s| Some((s, report.stores.iter().find(|c| c.store == s.name)?)))
+        {
+            let size = if compaction.removed > 0 {
+                format!(
+                    "{} → {} ({} removed)",
+                    format_size(compaction.bytes_before),
+                    format_size(compaction.bytes_after),
+                    compaction.removed
+                )
+            } else {
+                format_size(compaction.bytes_before)
+            };
+            println!(
+                "  {:<15} {:<28} {}",
+                store.name,
+                size,
+                format!("keep: {}", store.policy).dimmed()
+            );
+        }
+        println!();
+        for e in &report.errors {
+            output::warning(e);
+        }
+        let freed = format_size(report.freed_bytes);
+        if args.dry_run {
+            output::info(&format!("Would free {} (dry run)", freed));
+        } else {
+            output::success(&format!("Freed {}", freed));
+        }
+    }
+
+    if report.errors.is_empty() {
+        0
+    } else {
+        1
+    }
+}
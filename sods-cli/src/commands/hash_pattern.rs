@@ -1,5 +1,7 @@
 use clap::Args;
-use ethers_core::utils::keccak256;
+use sods_core::pattern::BehavioralPattern;
+
+use crate::output;
 
 #[derive(Args)]
 pub struct HashPatternArgs {
@@ -7,21 +9,28 @@ pub struct HashPatternArgs {
     pub pattern: String,
 }
 
+/// Print the pattern's canonical hash, the same for every spelling of it.
 pub async fn run(args: HashPatternArgs) -> i32 {
-    let hash = keccak256(args.pattern.as_bytes());
-    println!("0x{}", hex::encode(hash));
-    0
+    let aliases = crate::config::load_symbol_aliases();
+    match BehavioralPattern::parse_with_aliases(&args.pattern, &aliases) {
+        Ok(pattern) => {
+            println!("0x{}", hex::encode(pattern.canonical_hash()));
+            0
+        }
+        Err(e) => {
+            output::error(&format!("Invalid pattern: {}", e));
+            1
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[tokio::test]
-    async fn test_hash_consistency() {
-        let pattern = "LP+ -> Sw -> LP-";
-        let hash = keccak256(pattern.as_bytes());
-        let expected = "0x8a3b7c"; // Placeholder for actual start of hash
-        assert!(format!("0x{}", hex::encode(hash)).starts_with("0x"));
+    #[test]
+    fn test_hash_consistency() {
+        let hash = |p: &str| BehavioralPattern::parse(p).unwrap().canonical_hash();
+        assert_eq!(hash("LP+ -> Sw -> LP-"), hash("LP+->Sw->LP-"));
     }
 }
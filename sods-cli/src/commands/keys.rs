@@ -0,0 +1,226 @@
+//! Signing key management commands.
+//!
+//! Keys are kept encrypted in `~/.sods/keys` (see `crate::keys`). Other
+//! commands refer to them by name, e.g. `sods daemon start --key node` or
+//! `sods export-proof --signing-key prover`.
+
+use clap::{Args, Subcommand};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::keys::{self, Keystore, KeystoreFile};
+use crate::output;
+
+#[derive(Args)]
+pub struct KeysArgs {
+    #[command(subcommand)]
+    pub command: KeysCommands,
+}
+
+#[derive(Subcommand)]
+pub enum KeysCommands {
+    /// Generate a new encrypted signing key
+    Generate {
+        /// Name to store the key under
+        name: String,
+
+        /// Read the password from this file instead of prompting
+        #[arg(long, value_name = "FILE")]
+        password_file: Option<PathBuf>,
+    },
+    /// Import a key from a hex private key file or a keystore file
+    Import {
+        /// Name to store the key under
+        name: String,
+
+        /// File holding a hex private key or a SODS keystore (JSON)
+        #[arg(long, value_name = "FILE")]
+        from: PathBuf,
+
+        /// Read the password from this file instead of prompting
+        #[arg(long, value_name = "FILE")]
+        password_file: Option<PathBuf>,
+    },
+    /// Export a key as its encrypted keystore file
+    Export {
+        /// Name of the key
+        name: String,
+
+        /// Output file (stdout if omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Print the decrypted private key in hex instead
+        #[arg(long)]
+        private_key: bool,
+
+        /// Read the password from this file instead of prompting
+        #[arg(long, value_name = "FILE")]
+        password_file: Option<PathBuf>,
+    },
+    /// List stored keys
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+pub fn run(args: KeysArgs) -> i32 {
+    let store = Keystore::open_default();
+    let result = match args.command {
+        KeysCommands::Generate {
+            name,
+            password_file,
+        } => generate(&store, &name, password_file.as_deref()),
+        KeysCommands::Import {
+            name,
+            from,
+            password_file,
+        } => import(&store, &name, &from, password_file.as_deref()),
+        KeysCommands::Export {
+            name,
+            output,
+            private_key,
+            password_file,
+        } => export(
+            &store,
+            &name,
+            output.as_deref(),
+            private_key,
+            password_file.as_deref(),
+        ),
+        KeysCommands::List { json } => list(&store, json),
+    };
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            output::error(&e);
+            1
+        }
+    }
+}
+
+fn generate(store: &Keystore, name: &str, password_file: Option<&Path>) -> Result<(), String> {
+    keys::validate_name(name)?;
+    if store.contains(name) {
+        return Err(format!("Key '{}' already exists", name));
+    }
+    let password = keys::read_new_password(password_file)?;
+    let file = KeystoreFile::encrypt(name, &keys::generate_key(), &password)?;
+    let path = store.save(&file)?;
+    output::success(&format!("Generated key '{}'", name));
+    print_key(&file);
+    output::kv("File", &path.display().to_string());
+    Ok(())
+}
+
+fn import(
+    store: &Keystore,
+    name: &str,
+    from: &Path,
+    password_file: Option<&Path>,
+) -> Result<(), String> {
+    keys::validate_name(name)?;
+    if store.contains(name) {
+        return Err(format!("Key '{}' already exists", name));
+    }
+    let content = fs::read_to_string(from)
+        .map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+
+    // A keystore file is copied as is; it keeps its password
+    let file = match serde_json::from_str::<KeystoreFile>(&content) {
+        Ok(existing) => KeystoreFile {
+            name: name.to_string(),
+            ..existing
+        },
+        Err(_) => {
+            let key = keys::parse_private_key(&content)?;
+            let password = keys::read_new_password(password_file)?;
+            KeystoreFile::encrypt(name, &key, &password)?
+        }
+    };
+    let path = store.save(&file)?;
+    output::success(&format!("Imported key '{}'", name));
+    print_key(&file);
+    output::kv("File", &path.display().to_string());
+    Ok(())
+}
+
+fn export(
+    store: &Keystore,
+    name: &str,
+    out: Option<&Path>,
+    private_key: bool,
+    password_file: Option<&Path>,
+) -> Result<(), String> {
+    let file = store.load(name)?;
+    let content = if private_key {
+        let password =
+            keys::read_password(&format!("Password for key '{}': ", name), password_file)?;
+        let key = file.decrypt(&password)?;
+        format!("0x{}\n", hex::encode(key.to_bytes()))
+    } else {
+        serde_json::to_string_pretty(&file).map_err(|e| e.to_string())? + "\n"
+    };
+
+    match out {
+        Some(path) => {
+            // The private key must never be readable by other users
+            let written = if private_key {
+                keys::write_private_file(path, content.as_bytes())
+            } else {
+                fs::write(path, content)
+            };
+            written.map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            output::success(&format!("Exported key '{}' to {}", name, path.display()));
+            if private_key {
+                output::warning("The file holds the unencrypted private key.");
+            }
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+fn list(store: &Keystore, json: bool) -> Result<(), String> {
+    let files = store.list();
+    if json {
+        let entries: Vec<serde_json::Value> = files
+            .iter()
+            .map(|f| {
+                serde_json::json!({
+                    "name": f.name,
+                    "address": f.address_checksum(),
+                    "public_key": f.public_key,
+                    "created_at": f.created_at,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&entries).unwrap());
+        return Ok(());
+    }
+
+    output::header("Signing Keys");
+    if files.is_empty() {
+        output::info(&format!("No keys in {}", store.dir().display()));
+        output::hint("Create one with: sods keys generate <NAME>");
+        return Ok(());
+    }
+    for file in &files {
+        println!(
+            "   {:<20} {}  {}",
+            file.name,
+            file.address_checksum(),
+            chrono::DateTime::from_timestamp(file.created_at as i64, 0)
+                .map(|t| t.format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+fn print_key(file: &KeystoreFile) {
+    output::kv("Address", &file.address_checksum());
+    output::kv("Public key", &file.public_key);
+}
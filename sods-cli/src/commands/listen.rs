@@ -4,6 +4,8 @@ use colored::Colorize;
 use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
 use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
 use tokio_tungstenite::tungstenite::protocol::Message;
 
 #[derive(Args)]
@@ -15,6 +17,10 @@ pub struct ListenArgs {
     /// Filter alerts by pattern (local and remote)
     #[arg(short, long)]
     pub pattern: Option<String>,
+
+    /// API key with the read-alerts scope (default: $SODS_API_KEY)
+    #[arg(long, value_name = "KEY")]
+    pub api_key: Option<String>,
 }
 
 pub async fn run(args: ListenArgs) -> i32 {
@@ -23,7 +29,26 @@ pub async fn run(args: ListenArgs) -> i32 {
         args.websocket
     ));
 
-    let (ws_stream, _) = match connect_async(&args.websocket).await {
+    let mut request = match args.websocket.as_str().into_client_request() {
+        Ok(r) => r,
+        Err(e) => {
+            output::error(&format!("Invalid WebSocket URL: {}", e));
+            return 1;
+        }
+    };
+    if let Some(key) = args.api_key.or_else(|| std::env::var("SODS_API_KEY").ok()) {
+        match HeaderValue::from_str(&format!("Bearer {}", key.trim())) {
+            Ok(value) => {
+                request.headers_mut().insert("authorization", value);
+            }
+            Err(_) => {
+                output::error("API key contains invalid characters");
+                return 1;
+            }
+        }
+    }
+
+    let (ws_stream, _) = match connect_async(request).await {
         Ok(s) => s,
         Err(e) => {
             output::error(&format!("Failed to connect: {}", e));
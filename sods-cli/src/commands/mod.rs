@@ -1,15 +1,28 @@
 //! CLI command implementations.
 
 pub mod agent;
+pub mod audit;
+pub mod backfill;
+pub mod bench;
+pub mod causal;
 pub mod chains;
+pub mod completions;
 pub mod daemon;
 pub mod discover;
 pub mod export_proof;
+pub mod gc;
 pub mod hash_pattern;
+pub mod keys;
 pub mod listen;
 pub mod monitor;
+pub mod pattern;
+pub mod patterns;
+#[cfg(feature = "ipfs")]
+pub mod publish;
 pub mod register_agent;
 pub mod registry;
+pub mod selftest;
+pub mod sign_proof;
 pub mod symbols;
 pub mod threats;
 pub mod trend;
@@ -3,13 +3,25 @@
 
 use clap::{Args, ValueEnum};
 use colored::Colorize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::sleep;
 
 use crate::config::get_chain;
+use crate::correlation::new_alert_id;
+use crate::dry_run::{AlertChannel, DryRunSink};
 use crate::output;
+use crate::proof_export::{MatchBundle, ProofExporter};
+use ethers_core::types::Address;
 use sods_core::pattern::BehavioralPattern;
-use sods_verifier::{BlockVerifier, MempoolMonitor};
+use sods_core::PatternStreamMatcher;
+use sods_verifier::{
+    BlockVerifier, HeadTracker, MempoolMonitor, MempoolSnapshot, SandwichPrediction,
+};
+
+/// How often the forensic report is printed and rewritten.
+const FORENSICS_REPORT_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Monitoring mode.
 #[derive(Debug, Clone, Copy, ValueEnum, Default, PartialEq)]
@@ -51,6 +63,51 @@ pub struct MonitorArgs {
     /// Automatically adapt polling interval based on RPC health
     #[arg(long)]
     pub auto_adapt: bool,
+
+    /// Omit block explorer links from alerts
+    #[arg(long)]
+    pub no_links: bool,
+
+    /// Confirm pending matches by simulating them with debug_traceCall (pending mode)
+    #[arg(long)]
+    pub simulate: bool,
+
+    /// Address whose pending swaps should be guarded against sandwiches (pending mode, repeatable)
+    #[arg(long = "protect", value_name = "ADDRESS")]
+    pub protect: Vec<String>,
+
+    /// HTTPS webhook receiving `protective_action` events
+    #[arg(long)]
+    pub protective_webhook: Option<String>,
+
+    /// Deadline in milliseconds for delivering a `protective_action` after detection
+    #[arg(long, default_value = "2000")]
+    pub protective_deadline_ms: u64,
+
+    /// Log `protective_action` payloads instead of delivering them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// File receiving dry-run alerts (default: ~/.sods/dry-run.jsonl)
+    #[arg(long, requires = "dry_run")]
+    pub dry_run_log: Option<std::path::PathBuf>,
+
+    /// Snapshot pending transactions and reconcile them against mined blocks,
+    /// reporting prediction accuracy and vanished transactions (pending mode)
+    #[arg(long)]
+    pub forensics: bool,
+
+    /// File receiving the forensic report (default: ~/.sods/mempool-forensics.json)
+    #[arg(long, requires = "forensics")]
+    pub forensics_out: Option<PathBuf>,
+
+    /// Write a proof bundle for every match into this directory (block mode)
+    #[arg(long, value_name = "DIR")]
+    pub export_proofs: Option<PathBuf>,
+
+    /// Also write each bundle's proofs as on-chain calldata
+    #[arg(long, requires = "export_proofs")]
+    pub export_calldata: bool,
 }
 
 fn parse_duration(input: &str) -> Result<Duration, String> {
@@ -81,7 +138,8 @@ pub async fn run(args: MonitorArgs) -> i32 {
     };
 
     // 2. Parse Pattern
-    let pattern = match BehavioralPattern::parse(&args.pattern) {
+    let aliases = crate::config::load_symbol_aliases();
+    let pattern = match BehavioralPattern::parse_with_aliases(&args.pattern, &aliases) {
         Ok(p) => p,
         Err(e) => {
             output::error(&format!("Invalid pattern: {}", e));
@@ -92,11 +150,33 @@ pub async fn run(args: MonitorArgs) -> i32 {
     output::header(&format!("🚨 Autonomous Monitor Active: {}", args.pattern));
     println!("   Chain:    {}", chain_config.description.cyan());
     println!("   Mode:     {:?}", args.mode);
+    if args.dry_run {
+        println!("   Dry run:  {}", dry_run_sink(&args).path().display());
+    }
 
     if args.mode == MonitorMode::Pending {
+        if args.export_proofs.is_some() {
+            output::warning(
+                "--export-proofs only applies to block mode; pending matches are not mined yet.",
+            );
+        }
         return run_pending_monitor(args, chain_config, pattern).await;
     }
 
+    let exporter = match args.export_proofs.as_deref() {
+        Some(dir) => match ProofExporter::open(dir) {
+            Ok(e) => {
+                println!("   Proofs:   {}", e.dir().display());
+                Some(e.with_calldata(args.export_calldata))
+            }
+            Err(e) => {
+                output::error(&e);
+                return 1;
+            }
+        },
+        None => None,
+    };
+
     // --- BLOCK MODE (Legacy) ---
 
     // 3. Parse Duration
@@ -143,7 +223,7 @@ pub async fn run(args: MonitorArgs) -> i32 {
     };
 
     let mut verifier = match BlockVerifier::new(&rpc_urls) {
-        Ok(v) => v.with_backoff_profile(profile),
+        Ok(v) => v.with_backoff_profile(profile).with_aliases(aliases),
         Err(e) => {
             output::error(&format!("Failed to initialize RPCs: {}", e));
             return 1;
@@ -161,22 +241,46 @@ pub async fn run(args: MonitorArgs) -> i32 {
         if !plugins.is_empty() {
             println!("   Plugins:  Loaded {} custom symbols", plugins.len());
             for p in plugins {
-                verifier.register_plugin(p);
+                let name = p.name.clone();
+                if let Err(e) = verifier.register_plugin(p) {
+                    output::warning(&format!("Skipping plugin '{}': {}", name, e));
+                }
             }
         }
     }
 
-    // 5. Get Initial Block
-    let mut last_scanned_block = match verifier.get_latest_block().await {
-        Ok(b) => {
+    // Symbols from ~/.sods/dictionary.toml
+    match sods_core::SymbolDictionary::load_local_entries() {
+        Ok(entries) if !entries.is_empty() => {
+            println!("   Dictionary: Loaded {} file symbols", entries.len());
+            for entry in entries {
+                if let Err(e) = verifier.register_plugin(entry.to_plugin()) {
+                    output::warning(&format!("Skipping symbol '{}': {}", entry.symbol, e));
+                }
+            }
+        }
+        Ok(_) => {}
+        Err(e) => output::warning(&format!("Ignoring dictionary file: {}", e)),
+    }
+
+    // 5. Get Initial Block; the head is polled once per interval and
+    // shared by pattern matching and shadows
+    let verifier = Arc::new(verifier);
+    let heads = HeadTracker::polling(verifier.clone(), interval);
+    let mut last_scanned_block = match heads.wait_for_head(interval).await {
+        Some(b) => {
             println!("   Start:    Head Block #{}\n", b);
             b
         }
-        Err(e) => {
-            output::error(&format!("Failed to fetch initial block: {}", e));
+        None => {
+            output::error("Failed to fetch initial block");
             return 1;
         }
     };
+    let mut head = heads.subscribe();
+
+    // Matches may span the blocks the pattern's window covers
+    let mut stream = PatternStreamMatcher::new(pattern.clone());
 
     // Shadow State
     let mut active_shadows: Vec<sods_core::BehavioralShadow> = Vec::new();
@@ -185,7 +289,10 @@ pub async fn run(args: MonitorArgs) -> i32 {
 
     // 6. Polling Loop
     loop {
-        sleep(interval).await;
+        if head.changed().await.is_err() {
+            output::error("Chain head tracking stopped");
+            return 1;
+        }
 
         // Auto-Adapt Logic
         if args.auto_adapt {
@@ -197,12 +304,8 @@ pub async fn run(args: MonitorArgs) -> i32 {
             }
         }
 
-        let current_head = match verifier.get_latest_block().await {
-            Ok(b) => b,
-            Err(e) => {
-                eprintln!("   ⚠️ RPC Error: {}. Retrying in next interval...", e);
-                continue;
-            }
+        let Some(current_head) = *head.borrow_and_update() else {
+            continue;
         };
 
         if current_head > last_scanned_block {
@@ -220,21 +323,65 @@ pub async fn run(args: MonitorArgs) -> i32 {
                     sleep(Duration::from_millis(200)).await;
                 }
 
-                match verifier.fetch_block_symbols(block_num).await {
+                let fetched = match verifier.fetch_block_symbols_for(block_num, &pattern).await {
+                    Ok(mut symbols) if pattern.has_gas_condition() => {
+                        verifier.fill_gas_used(&mut symbols).await.map(|_| symbols)
+                    }
+                    other => other,
+                };
+                match fetched {
                     Ok(symbols) => {
                         // A. Check for Full Pattern Matches (Reactive)
-                        if let Some(matched_seq) = pattern.matches(&symbols, None) {
-                            let timestamp = chrono::Utc::now().to_rfc3339();
+                        for found in stream.push_block(block_num, symbols.clone()) {
+                            let timestamp = chrono::Utc::now();
+                            let alert_id = new_alert_id("alert", block_num);
+                            let blocks = if found.is_cross_block() {
+                                format!("Blocks #{}-#{}", found.first_block, found.last_block)
+                            } else {
+                                format!("Block #{}", block_num)
+                            };
                             println!();
                             println!(
-                                "🚨 {} Block #{} on {}",
+                                "🚨 {} {} on {}",
                                 "PATTERN DETECTED!".red().bold(),
-                                block_num,
+                                blocks,
                                 args.chain
                             );
-                            println!("   Time:    {}", timestamp);
+                            println!("   Alert:   {}", alert_id);
+                            println!("   Time:    {}", timestamp.to_rfc3339());
                             println!("   Pattern: {}", args.pattern.yellow());
-                            println!("   Matched: {} events", matched_seq.len());
+                            println!("   Matched: {} events", found.symbols.len());
+                            if !args.no_links {
+                                if let Some(url) = chain_config.block_url(block_num) {
+                                    println!("   Explorer: {}", url);
+                                }
+                            }
+                            if let Some(exporter) = &exporter {
+                                let cached = |n| stream.block_symbols(n).map(<[_]>::to_vec);
+                                let bundle = MatchBundle::new(
+                                    &pattern,
+                                    &args.pattern,
+                                    chain_config.chain_id,
+                                    timestamp.timestamp() as u64,
+                                )
+                                .with_alert_id(alert_id);
+                                let exported = exporter
+                                    .export(
+                                        &verifier,
+                                        &pattern,
+                                        bundle,
+                                        block_num,
+                                        &found.symbols,
+                                        cached,
+                                    )
+                                    .await;
+                                match exported {
+                                    Ok(path) => println!("   Proof:   {}", path.display()),
+                                    Err(e) => {
+                                        output::warning(&format!("Proof not exported: {}", e))
+                                    }
+                                }
+                            }
                             println!();
                         }
 
@@ -349,10 +496,26 @@ async fn run_pending_monitor(
     };
 
     println!("   URL:      {}", ws_url);
+    if args.simulate {
+        println!("   Simulate: {}", "debug_traceCall".green());
+    }
     println!("   Status:   Connecting to Mempool...");
 
+    let snapshot = args
+        .forensics
+        .then(|| Arc::new(Mutex::new(MempoolSnapshot::default())));
+    if args.forensics {
+        println!("   Forensics: {}", forensics_path(&args).display());
+    }
+
     let monitor = match MempoolMonitor::connect(ws_url).await {
-        Ok(m) => m,
+        Ok(m) => {
+            let m = m.with_simulation(args.simulate);
+            match &snapshot {
+                Some(s) => m.with_snapshot(s.clone()),
+                None => m,
+            }
+        }
         Err(e) => {
             output::error(&format!("Failed to connect to WebSocket: {}", e));
             return 1;
@@ -367,12 +530,66 @@ async fn run_pending_monitor(
         }
     };
 
+    // Optional sandwich protection on a second subscription
+    let mut protect_rx = None;
+    if !args.protect.is_empty() {
+        let mut protected = Vec::new();
+        for addr in &args.protect {
+            match addr.parse::<Address>() {
+                Ok(a) => protected.push(a),
+                Err(_) => {
+                    output::error(&format!("Invalid --protect address: {}", addr));
+                    return 1;
+                }
+            }
+        }
+
+        let guard = match MempoolMonitor::connect(ws_url).await {
+            Ok(m) => m.protect(protected).await,
+            Err(e) => Err(e),
+        };
+        match guard {
+            Ok(r) => protect_rx = Some(r),
+            Err(e) => {
+                output::error(&format!("Failed to start sandwich protection: {}", e));
+                return 1;
+            }
+        }
+        println!("   Protect:  {} address(es)", args.protect.len());
+    }
+    let deadline = Duration::from_millis(args.protective_deadline_ms);
+
     println!(
         "{}",
         "Listening for pending transactions... (Ctrl+C to stop)".dimmed()
     );
 
-    while let Some(alert) = rx.recv().await {
+    let mut report_timer = tokio::time::interval(FORENSICS_REPORT_INTERVAL);
+    report_timer.tick().await;
+
+    loop {
+        let alert = tokio::select! {
+            alert = rx.recv() => match alert {
+                Some(a) => a,
+                None => break,
+            },
+            _ = report_timer.tick(), if snapshot.is_some() => {
+                if let Some(s) = &snapshot {
+                    report_forensics(&args, s);
+                }
+                continue;
+            }
+            Some(prediction) = async {
+                match protect_rx.as_mut() {
+                    Some(r) => r.recv().await,
+                    None => std::future::pending().await,
+                }
+            } => {
+                handle_sandwich_prediction(&args, chain_config, prediction, deadline).await;
+                continue;
+            }
+        };
+
         let timestamp = chrono::Utc::now().to_rfc3339();
         println!();
         println!(
@@ -384,10 +601,203 @@ async fn run_pending_monitor(
         println!("   Pattern:  {}", alert.pattern_name.cyan());
         println!("   Seq:      {}", alert.matched_sequence);
         println!("   Conf:     {:.0}%", alert.confidence * 100.0);
+        println!("   Source:   {}", alert.source);
         println!("   Est. Inc: {}", alert.estimated_inclusion);
         println!("   Time:     {}", timestamp);
+        if !args.no_links {
+            if let Some(url) = chain_config.tx_url(&alert.tx_hash) {
+                println!("   Explorer: {}", url);
+            }
+        }
         println!();
     }
 
     0
 }
+
+fn forensics_path(args: &MonitorArgs) -> PathBuf {
+    args.forensics_out.clone().unwrap_or_else(|| {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        home.join(".sods").join("mempool-forensics.json")
+    })
+}
+
+/// Print a one-line accuracy summary and rewrite the forensic report.
+fn report_forensics(args: &MonitorArgs, snapshot: &Mutex<MempoolSnapshot>) {
+    let snapshot = snapshot
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let stats = snapshot.stats();
+    let percent = |share: Option<f64>| {
+        share
+            .map(|s| format!("{:.0}%", s * 100.0))
+            .unwrap_or_else(|| "n/a".into())
+    };
+
+    println!(
+        "🔎 Forensics: {} seen, {} included, {} vanished | precision {} recall {} | {} pending",
+        stats.seen,
+        stats.included,
+        stats.vanished,
+        percent(stats.precision()),
+        percent(stats.recall()),
+        snapshot.pending()
+    );
+
+    let report = serde_json::json!({
+        "chain": args.chain,
+        "generated_at": chrono::Utc::now().to_rfc3339(),
+        "stats": stats,
+        "precision": stats.precision(),
+        "recall": stats.recall(),
+        "mean_inclusion_delay_secs": stats.mean_inclusion_delay().map(|d| d.as_secs()),
+        "vanished": snapshot.vanished().collect::<Vec<_>>(),
+    });
+    let path = forensics_path(args);
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| {
+            let json = serde_json::to_string_pretty(&report).unwrap_or_default();
+            std::fs::write(&path, json)
+        });
+    if let Err(e) = written {
+        output::warning(&format!("Failed to write forensic report: {}", e));
+    }
+}
+
+/// Report a predicted sandwich and fire the `protective_action` webhook.
+async fn handle_sandwich_prediction(
+    args: &MonitorArgs,
+    chain_config: &crate::config::ChainConfig,
+    prediction: SandwichPrediction,
+    deadline: Duration,
+) {
+    let victim = format!("{:?}", prediction.victim_tx);
+    let bundle: Vec<String> = prediction
+        .attacker_bundle
+        .iter()
+        .map(|h| format!("{:?}", h))
+        .collect();
+
+    println!();
+    println!(
+        "🛡️  {} Your Tx on {}",
+        "SANDWICH PREDICTED:".red().bold(),
+        args.chain
+    );
+    println!("   Tx Hash:  {}", victim);
+    println!("   Target:   {:?}", prediction.target);
+    println!("   Attacker: {}", bundle.join(", "));
+    if !args.no_links {
+        if let Some(url) = chain_config.tx_url(&victim) {
+            println!("   Explorer: {}", url);
+        }
+    }
+
+    if let Some(url) = &args.protective_webhook {
+        let payload = serde_json::json!({
+            "type": "protective_action",
+            "chain": args.chain,
+            "chain_id": chain_config.chain_id,
+            "tx_hash": victim,
+            "attacker_bundle": bundle,
+            "target": format!("{:?}", prediction.target),
+            "deadline_ms": deadline.as_millis() as u64,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        });
+        if args.dry_run {
+            if let Err(e) =
+                dry_run_sink(args).record(AlertChannel::ProtectiveAction, Some(url), payload)
+            {
+                println!(
+                    "   Webhook:  {}",
+                    format!("dry-run log failed: {}", e).red()
+                );
+            }
+            println!();
+            return;
+        }
+        match send_protective_action(url, &payload, prediction.detected_at, deadline).await {
+            Ok(()) => println!("   Webhook:  {}", "protective_action delivered".green()),
+            Err(e) => println!("   Webhook:  {}", e.red()),
+        }
+    }
+    println!();
+}
+
+fn dry_run_sink(args: &MonitorArgs) -> DryRunSink {
+    DryRunSink::new(
+        args.dry_run_log
+            .clone()
+            .unwrap_or_else(DryRunSink::default_path),
+    )
+}
+
+/// Deliver a `protective_action` payload, giving up once the deadline measured
+/// from detection has passed.
+async fn send_protective_action(
+    url: &str,
+    payload: &serde_json::Value,
+    detected_at: std::time::Instant,
+    deadline: Duration,
+) -> Result<(), String> {
+    if !url.starts_with("https://") {
+        return Err("webhook must use https://".into());
+    }
+    let remaining = deadline
+        .checked_sub(detected_at.elapsed())
+        .filter(|d| !d.is_zero())
+        .ok_or("deadline expired before delivery")?;
+
+    let resp = reqwest::Client::new()
+        .post(url)
+        .json(payload)
+        .timeout(remaining)
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                "deadline expired during delivery".to_string()
+            } else {
+                format!("delivery failed: {}", e)
+            }
+        })?;
+
+    if resp.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("webhook returned {}", resp.status()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_protective_action_deadline_and_scheme() {
+        let payload = serde_json::json!({ "type": "protective_action" });
+
+        let expired = std::time::Instant::now() - Duration::from_secs(5);
+        let err = send_protective_action(
+            "https://example.invalid/hook",
+            &payload,
+            expired,
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("deadline"));
+
+        let err = send_protective_action(
+            "http://example.invalid/hook",
+            &payload,
+            std::time::Instant::now(),
+            Duration::from_secs(1),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.contains("https"));
+    }
+}
@@ -0,0 +1,523 @@
+//! Pattern library and interactive pattern builder.
+//!
+//! `sods pattern new` walks through a pattern one step at a time (symbol,
+//! quantifier, condition), checks every step with the real parser, can
+//! backtest the result on recent blocks, and saves it as a named preset in
+//! `~/.sods/presets.json`, the same library rule packs import into and
+//! export from.
+
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use sods_core::aliases::SymbolAliases;
+use sods_core::pattern::{self, BehavioralPattern};
+use sods_p2p::PatternPreset;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use crate::config::{get_chain, SYMBOLS};
+use crate::output;
+
+/// Steps accepted by the parser (`MAX_SYMBOLS_PER_PATTERN` in sods-core).
+const MAX_STEPS: usize = 10;
+
+/// Blocks scanned by a backtest unless the user picks another number.
+const DEFAULT_BACKTEST_BLOCKS: u64 = 10;
+
+#[derive(Args)]
+pub struct PatternArgs {
+    #[command(subcommand)]
+    pub command: PatternCommands,
+}
+
+#[derive(Subcommand)]
+pub enum PatternCommands {
+    /// Build a pattern interactively, backtest it and save it to the library
+    New {
+        /// Chain used for the optional backtest
+        #[arg(short, long, default_value = "sepolia")]
+        chain: String,
+
+        /// Custom RPC URL for the backtest
+        #[arg(long)]
+        rpc_url: Option<String>,
+    },
+    /// List patterns saved in the library
+    List,
+}
+
+pub async fn run(args: PatternArgs) -> i32 {
+    match args.command {
+        PatternCommands::New { chain, rpc_url } => new_pattern(&chain, rpc_url).await,
+        PatternCommands::List => list_patterns(),
+    }
+}
+
+fn library_path() -> PathBuf {
+    let dir = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".sods");
+    fs::create_dir_all(&dir).ok();
+    dir.join("presets.json")
+}
+
+/// Patterns saved in `~/.sods/presets.json`.
+pub fn load_library() -> Vec<PatternPreset> {
+    fs::read_to_string(library_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Add `preset` to the library, replacing a pattern with the same name.
+pub fn save_to_library(preset: PatternPreset) -> io::Result<()> {
+    let mut library = load_library();
+    library.retain(|p| p.name != preset.name);
+    library.push(preset);
+    let json = serde_json::to_string_pretty(&library)
+        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    fs::write(library_path(), json)
+}
+
+fn list_patterns() -> i32 {
+    let library = load_library();
+    output::header("Pattern Library");
+    if library.is_empty() {
+        output::info("No saved patterns yet.");
+        output::hint("Create one with: sods pattern new");
+        return 0;
+    }
+    println!();
+    for preset in &library {
+        println!("{:<20} {}", preset.name.green().bold(), preset.pattern);
+    }
+    println!();
+    0
+}
+
+/// Line-based prompts over any reader/writer, so the wizard can be scripted.
+struct Prompt<R, W> {
+    input: R,
+    out: W,
+}
+
+impl<R: BufRead, W: Write> Prompt<R, W> {
+    fn new(input: R, out: W) -> Self {
+        Self { input, out }
+    }
+
+    /// Ask a question; an empty answer returns `default`. Fails at end of input.
+    fn ask(&mut self, question: &str, default: &str) -> io::Result<String> {
+        if default.is_empty() {
+            write!(self.out, "{}: ", question)?;
+        } else {
+            write!(self.out, "{} [{}]: ", question, default)?;
+        }
+        self.out.flush()?;
+
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "input closed"));
+        }
+        let answer = line.trim();
+        Ok(if answer.is_empty() { default } else { answer }.to_string())
+    }
+
+    fn confirm(&mut self, question: &str, default: bool) -> io::Result<bool> {
+        let answer = self.ask(question, if default { "Y/n" } else { "y/N" })?;
+        Ok(match answer.to_lowercase().as_str() {
+            "y" | "yes" => true,
+            "n" | "no" => false,
+            _ => default,
+        })
+    }
+
+    fn say(&mut self, message: &str) -> io::Result<()> {
+        writeln!(self.out, "{}", message)
+    }
+}
+
+/// Symbols usable as pattern steps, with descriptions.
+///
+/// Presets expand to whole patterns and cannot be combined with other
+/// steps, so they are left out. Loaded plugins are included.
+fn step_symbols(aliases: &SymbolAliases) -> Vec<(String, String)> {
+    let mut symbols: Vec<(String, String)> = SYMBOLS
+        .iter()
+        .filter(|(symbol, _)| !pattern::PRESETS.contains(symbol))
+        .map(|(symbol, meaning)| {
+            let meaning = aliases.description(symbol).unwrap_or(meaning);
+            (symbol.to_string(), meaning.to_string())
+        })
+        .collect();
+    if let Ok(entries) = sods_core::SymbolDictionary::load_local_entries() {
+        for entry in entries {
+            if !symbols.iter().any(|(s, _)| *s == entry.symbol) {
+                symbols.push((
+                    entry.symbol,
+                    format!("{} (dictionary file)", entry.signature),
+                ));
+            }
+        }
+    }
+    if let Ok(plugins) = crate::commands::symbols::load_local_plugins() {
+        for plugin in plugins {
+            if !symbols.iter().any(|(s, _)| *s == plugin.symbol) {
+                symbols.push((plugin.symbol, format!("{} (plugin)", plugin.name)));
+            }
+        }
+    }
+    symbols
+}
+
+/// Turn `1`, `3`, `2-5` or `2+` into pattern quantifier syntax.
+fn parse_quantifier(input: &str) -> Result<String, String> {
+    let count = |s: &str| -> Result<usize, String> {
+        match s.trim().parse::<usize>() {
+            Ok(n) if n >= 1 => Ok(n),
+            _ => Err(format!("'{}' is not a count of at least 1", s.trim())),
+        }
+    };
+
+    let input = input.trim();
+    if let Some(min) = input.strip_suffix('+') {
+        return Ok(format!("{{{},}}", count(min)?));
+    }
+    if let Some((min, max)) = input.split_once('-') {
+        let (min, max) = (count(min)?, count(max)?);
+        if max < min {
+            return Err(format!("{}-{} is an empty range", min, max));
+        }
+        return Ok(format!("{{{},{}}}", min, max));
+    }
+    match count(input)? {
+        1 => Ok(String::new()),
+        n => Ok(format!("{{{}}}", n)),
+    }
+}
+
+/// Ask for steps until the user finishes, returning the pattern string.
+fn compose<R: BufRead, W: Write>(
+    prompt: &mut Prompt<R, W>,
+    symbols: &[(String, String)],
+    aliases: &SymbolAliases,
+) -> io::Result<String> {
+    let mut segments: Vec<String> = Vec::new();
+
+    while segments.len() < MAX_STEPS {
+        let step = segments.len() + 1;
+        let answer = prompt.ask(
+            &format!("Step {} symbol (name or number, empty to finish)", step),
+            "",
+        )?;
+        if answer.is_empty() {
+            if segments.is_empty() {
+                prompt.say("A pattern needs at least one step.")?;
+                continue;
+            }
+            break;
+        }
+
+        let symbol = match answer.parse::<usize>() {
+            Ok(i) if (1..=symbols.len()).contains(&i) => symbols[i - 1].0.clone(),
+            _ => aliases.resolve(&answer).to_string(),
+        };
+        if !symbols.iter().any(|(s, _)| *s == symbol) {
+            prompt.say(&format!(
+                "Unknown symbol '{}'. Pick one from the list.",
+                answer
+            ))?;
+            continue;
+        }
+
+        let quantifier = loop {
+            let answer = prompt.ask("How many times? (1, 3, 2-5 or 2+)", "1")?;
+            match parse_quantifier(&answer) {
+                Ok(q) => break q,
+                Err(e) => prompt.say(&e)?,
+            }
+        };
+
+        let condition = loop {
+            match prompt
+                .ask("Condition (none, deployer, value, range, to, from)", "none")?
+                .as_str()
+            {
+                "none" => break String::new(),
+                "deployer" => break " where from == deployer".to_string(),
+                "value" => {
+                    break loop {
+                        let amount = prompt.ask("Minimum value (e.g. 10 ether, 5000 gwei)", "")?;
+                        match pattern::parse_amount(&amount) {
+                            Ok(_) => break format!(" where value > {}", amount),
+                            Err(e) => prompt.say(&e.to_string())?,
+                        }
+                    }
+                }
+                "range" => {
+                    break loop {
+                        let low = prompt.ask("Lowest value (e.g. 1 ether, 500 tokens(6))", "")?;
+                        let high = prompt.ask("Highest value", "")?;
+                        let condition = format!(" where value between {} and {}", low, high);
+                        match BehavioralPattern::parse(&format!("Tf{}", condition)) {
+                            Ok(_) => break condition,
+                            Err(e) => prompt.say(&e.to_string())?,
+                        }
+                    }
+                }
+                "to" => {
+                    break loop {
+                        let address = prompt.ask("Recipient address (0x...)", "")?;
+                        match address.parse::<ethers_core::types::Address>() {
+                            Ok(_) => break format!(" where to == {}", address),
+                            Err(_) => prompt.say(&format!("Invalid address: {}", address))?,
+                        }
+                    }
+                }
+                "from" => {
+                    break loop {
+                        let address = prompt.ask("Sender address (0x...)", "")?;
+                        match address.parse::<ethers_core::types::Address>() {
+                            Ok(_) => break format!(" where from == {}", address),
+                            Err(_) => prompt.say(&format!("Invalid address: {}", address))?,
+                        }
+                    }
+                }
+                other => prompt.say(&format!("Unknown condition '{}'.", other))?,
+            }
+        };
+
+        let segment = format!("{}{}{}", symbol, quantifier, condition);
+        let candidate = segments
+            .iter()
+            .chain(std::iter::once(&segment))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        match BehavioralPattern::parse_with_aliases(&candidate, aliases) {
+            Ok(_) => {
+                segments.push(segment);
+                prompt.say(&format!("Pattern so far: {}", candidate))?;
+            }
+            Err(e) => prompt.say(&format!("Step rejected: {}", e))?,
+        }
+    }
+
+    Ok(segments.join(" -> "))
+}
+
+/// Library names follow the alias rules so they could be typed in a pattern.
+fn validate_name(name: &str) -> Result<(), String> {
+    if name
+        .chars()
+        .any(|c| !c.is_alphanumeric() && c != '-' && c != '_')
+    {
+        return Err("use only letters, digits, '-' and '_'".into());
+    }
+    if pattern::PRESETS.contains(&name) {
+        return Err(format!("'{}' is a built-in preset", name));
+    }
+    Ok(())
+}
+
+async fn backtest(
+    pattern: &str,
+    chain: &str,
+    rpc_url: Option<String>,
+    blocks: u64,
+    aliases: SymbolAliases,
+) -> Result<(), String> {
+    let chain_config = get_chain(chain).ok_or(format!("Chain '{}' not supported.", chain))?;
+    let rpc_urls: Vec<String> = match rpc_url
+        .or_else(|| crate::config::UserConfig::load().get_rpc_override(chain_config.name))
+    {
+        Some(url) => vec![url],
+        None => chain_config
+            .rpc_urls
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+    };
+    let verifier = sods_verifier::BlockVerifier::new(&rpc_urls)
+        .map_err(|e| format!("Failed to initialize RPCs: {}", e))?
+        .with_aliases(aliases);
+
+    let latest = verifier
+        .get_latest_block()
+        .await
+        .map_err(|e| format!("Failed to fetch latest block: {}", e))?;
+    let first = latest.saturating_sub(blocks.saturating_sub(1));
+    output::info(&format!(
+        "Backtesting on {} blocks #{}..#{} ({})",
+        latest - first + 1,
+        first,
+        latest,
+        chain_config.name
+    ));
+
+    let (mut matched, mut failed) = (0u64, 0u64);
+    for block in first..=latest {
+        match verifier.verify_patterns_in_block(&[pattern], block).await {
+            Ok(outcomes) if outcomes.iter().any(|o| !o.matched.is_empty()) => {
+                matched += 1;
+                let symbols: Vec<&str> = outcomes[0].matched.iter().map(|s| s.symbol()).collect();
+                println!("   #{:<10} {}", block, symbols.join(" -> ").green());
+            }
+            Ok(_) => {}
+            Err(e) => {
+                failed += 1;
+                println!("   #{:<10} {}", block, e.to_string().red());
+            }
+        }
+    }
+
+    let scanned = latest - first + 1 - failed;
+    output::kv("Matched", &format!("{} of {} blocks", matched, scanned));
+    if failed > 0 {
+        output::warning(&format!("{} blocks could not be fetched", failed));
+    }
+    Ok(())
+}
+
+async fn new_pattern(chain: &str, rpc_url: Option<String>) -> i32 {
+    let aliases = crate::config::load_symbol_aliases();
+    let symbols = step_symbols(&aliases);
+
+    output::header("Pattern Builder");
+    println!();
+    for (i, (symbol, meaning)) in symbols.iter().enumerate() {
+        println!("{:>3}. {:<10} {}", i + 1, symbol.green().bold(), meaning);
+    }
+    println!();
+
+    let stdin = io::stdin();
+    let mut prompt = Prompt::new(stdin.lock(), io::stdout());
+
+    let pattern = match compose(&mut prompt, &symbols, &aliases) {
+        Ok(p) => p,
+        Err(e) => {
+            println!();
+            output::error(&format!("Aborted: {}", e));
+            return 1;
+        }
+    };
+    println!();
+    output::success(&format!("Pattern: {}", pattern));
+
+    let result: io::Result<i32> = async {
+        if prompt.confirm("Backtest it against recent blocks?", false)? {
+            let blocks = prompt
+                .ask("How many blocks", &DEFAULT_BACKTEST_BLOCKS.to_string())?
+                .parse::<u64>()
+                .unwrap_or(DEFAULT_BACKTEST_BLOCKS)
+                .max(1);
+            if let Err(e) = backtest(&pattern, chain, rpc_url, blocks, aliases.clone()).await {
+                output::error(&e);
+            }
+        }
+
+        let library = load_library();
+        loop {
+            let name = prompt.ask("Save to the pattern library as (empty to skip)", "")?;
+            if name.is_empty() {
+                output::hint(&format!("Use it directly: sods verify \"{}\"", pattern));
+                return Ok(0);
+            }
+            if let Err(e) = validate_name(&name) {
+                output::error(&format!("Invalid name: {}", e));
+                continue;
+            }
+            if library.iter().any(|p| p.name == name)
+                && !prompt.confirm(&format!("'{}' exists. Replace it?", name), false)?
+            {
+                continue;
+            }
+
+            let preset = PatternPreset {
+                name: name.clone(),
+                pattern: pattern.clone(),
+            };
+            return Ok(match save_to_library(preset) {
+                Ok(()) => {
+                    output::success(&format!("Saved '{}' to {}", name, library_path().display()));
+                    output::hint("Share it in a rule pack: sods threats pack export");
+                    0
+                }
+                Err(e) => {
+                    output::error(&format!("Failed to save pattern: {}", e));
+                    1
+                }
+            });
+        }
+    }
+    .await;
+
+    result.unwrap_or_else(|e| {
+        println!();
+        output::error(&format!("Aborted: {}", e));
+        1
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols() -> Vec<(String, String)> {
+        vec![
+            ("Tf".to_string(), "Transfer".to_string()),
+            ("Sw".to_string(), "Swap".to_string()),
+        ]
+    }
+
+    #[test]
+    fn test_parse_quantifier() {
+        assert_eq!(parse_quantifier("1").unwrap(), "");
+        assert_eq!(parse_quantifier("3").unwrap(), "{3}");
+        assert_eq!(parse_quantifier("2-5").unwrap(), "{2,5}");
+        assert_eq!(parse_quantifier(" 2+ ").unwrap(), "{2,}");
+        assert!(parse_quantifier("0").is_err());
+        assert!(parse_quantifier("5-2").is_err());
+        assert!(parse_quantifier("many").is_err());
+    }
+
+    #[test]
+    fn test_compose_from_scripted_answers() {
+        let aliases = SymbolAliases::new().with_alias("Swap", "Sw");
+        let answers = [
+            "",      // no steps yet: re-asked
+            "1",     // Tf by number
+            "",      // once
+            "",      // no condition
+            "Bogus", // unknown symbol: re-asked
+            "Swap",  // alias resolves to Sw
+            "2-5", "value", "lots", // invalid amount: re-asked
+            "10 ether", "", // finish
+        ];
+        let input = answers.join("\n") + "\n";
+        let mut out = Vec::new();
+        let mut prompt = Prompt::new(input.as_bytes(), &mut out);
+
+        let pattern = compose(&mut prompt, &symbols(), &aliases).unwrap();
+        assert_eq!(pattern, "Tf -> Sw{2,5} where value > 10 ether");
+        assert!(BehavioralPattern::parse(&pattern).is_ok());
+
+        let transcript = String::from_utf8(out).unwrap();
+        assert!(transcript.contains("at least one step"));
+        assert!(transcript.contains("Unknown symbol 'Bogus'"));
+    }
+
+    #[test]
+    fn test_compose_fails_at_end_of_input() {
+        let mut out = Vec::new();
+        let mut prompt = Prompt::new("1\n".as_bytes(), &mut out);
+        assert!(compose(&mut prompt, &symbols(), &SymbolAliases::new()).is_err());
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("Drain_v2").is_ok());
+        assert!(validate_name("my pattern").is_err());
+        assert!(validate_name("Sandwich").is_err());
+    }
+}
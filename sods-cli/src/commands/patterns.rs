@@ -0,0 +1,116 @@
+//! Named pattern presets.
+//!
+//! Besides the built-in `Sandwich`, `Frontrun` and `Backrun`, presets can
+//! be defined in `~/.sods/patterns.toml`. Every command that takes a
+//! pattern accepts their names.
+
+use clap::{Args, Subcommand};
+use colored::Colorize;
+use sods_core::pattern::PRESETS;
+use sods_core::PatternPresets;
+
+use crate::config::{is_symbol_supported, SYMBOLS};
+use crate::output;
+
+#[derive(Args)]
+pub struct PatternsArgs {
+    #[command(subcommand)]
+    pub command: PatternsCommands,
+}
+
+#[derive(Subcommand)]
+pub enum PatternsCommands {
+    /// List built-in and user-defined presets
+    List,
+    /// Add or replace a user-defined preset
+    Add {
+        /// Preset name (letters, digits, '+', '-' and '_')
+        name: String,
+        /// Pattern the name stands for (e.g. "LP+ -> LP- within 10 blocks")
+        pattern: String,
+        /// What the pattern detects
+        #[arg(short, long)]
+        description: Option<String>,
+    },
+}
+
+pub fn run(args: PatternsArgs) -> i32 {
+    let mut presets = match PatternPresets::load_local() {
+        Ok(p) => p,
+        Err(e) => {
+            output::error(&format!("Failed to load presets: {}", e));
+            return 1;
+        }
+    };
+
+    match args.command {
+        PatternsCommands::List => {
+            output::header("Pattern Presets");
+            println!();
+            for (name, meaning) in SYMBOLS.iter().filter(|(s, _)| PRESETS.contains(s)) {
+                println!(
+                    "{:<20} {} {}",
+                    name.green().bold(),
+                    meaning,
+                    "(built-in)".dimmed()
+                );
+            }
+            for (name, preset) in presets.iter() {
+                println!("{:<20} {}", name.cyan().bold(), preset.pattern);
+                if let Some(ref description) = preset.description {
+                    println!("{:<20} {}", "", description.dimmed());
+                }
+            }
+            println!();
+            if presets.is_empty() {
+                output::hint("Add your own with: sods patterns add <NAME> \"<PATTERN>\"");
+            }
+            0
+        }
+        PatternsCommands::Add {
+            name,
+            pattern,
+            description,
+        } => {
+            if let Err(e) = add_preset(&mut presets, &name, &pattern, description) {
+                output::error(&e);
+                return 1;
+            }
+            if let Err(e) = presets.save_local() {
+                output::error(&format!("Failed to save presets: {}", e));
+                return 1;
+            }
+            output::success(&format!("Saved preset '{}' = {}", name, pattern));
+            0
+        }
+    }
+}
+
+/// Adds a preset unless its name is taken by a symbol.
+fn add_preset(
+    presets: &mut PatternPresets,
+    name: &str,
+    pattern: &str,
+    description: Option<String>,
+) -> Result<(), String> {
+    if is_symbol_supported(name) && !PRESETS.contains(&name) {
+        return Err(format!("'{}' is a behavioral symbol", name));
+    }
+    presets
+        .add(name, pattern, description)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preset_names_cannot_shadow_symbols() {
+        let mut presets = PatternPresets::new();
+        assert!(add_preset(&mut presets, "Tf", "Tf -> Tf", None).is_err());
+        assert!(add_preset(&mut presets, "Sandwich", "Tf -> Sw", None).is_err());
+        assert!(add_preset(&mut presets, "TfLoop", "Tf{3,}", None).is_ok());
+        assert_eq!(presets.get("TfLoop").unwrap().pattern, "Tf{3,}");
+    }
+}
@@ -0,0 +1,95 @@
+//! Publish epoch summaries and proof bundles to IPFS/Arweave.
+
+use crate::output;
+use crate::publisher::{PublicationStore, Publisher, DEFAULT_IPFS_API};
+use clap::{Args, ValueEnum};
+use sods_core::EpochSummary;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ArtifactKind {
+    /// Epoch summary JSON produced by the epoch summarizer
+    Epoch,
+    /// Proof bundle produced by `export-proof`
+    Proof,
+}
+
+#[derive(Args)]
+pub struct PublishArgs {
+    /// Path to the artifact to publish
+    pub file: PathBuf,
+
+    /// Artifact kind
+    #[arg(short, long, default_value = "proof")]
+    pub kind: ArtifactKind,
+
+    /// IPFS (Kubo) HTTP API endpoint
+    #[arg(long, default_value = DEFAULT_IPFS_API)]
+    pub ipfs_api: String,
+
+    /// Optional Arweave bundler gateway to mirror the upload to
+    #[arg(long)]
+    pub arweave_gateway: Option<String>,
+}
+
+pub async fn run(args: PublishArgs) -> i32 {
+    let bytes = match std::fs::read(&args.file) {
+        Ok(b) => b,
+        Err(e) => {
+            output::error(&format!("Failed to read {}: {}", args.file.display(), e));
+            return 1;
+        }
+    };
+
+    let mut publisher = Publisher::new(&args.ipfs_api);
+    if let Some(gateway) = &args.arweave_gateway {
+        publisher = publisher.with_arweave(gateway);
+    }
+
+    output::info(&format!("Publishing {}...", args.file.display()));
+    let result = match args.kind {
+        ArtifactKind::Epoch => match serde_json::from_slice::<EpochSummary>(&bytes) {
+            Ok(summary) => publisher.publish_epoch(&summary).await,
+            Err(e) => {
+                output::error(&format!("Invalid epoch summary: {}", e));
+                return 1;
+            }
+        },
+        ArtifactKind::Proof => {
+            let name = args
+                .file
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "proof".to_string());
+            publisher.publish_proof(&name, bytes).await
+        }
+    };
+
+    let record = match result {
+        Ok(r) => r,
+        Err(e) => {
+            output::error(&format!("Publication failed: {}", e));
+            return 1;
+        }
+    };
+
+    output::success("Published");
+    output::kv("CID", &record.cid);
+    if let Some(tx) = &record.arweave_tx {
+        output::kv("Arweave TX", tx);
+    }
+
+    match PublicationStore::load(PublicationStore::default_path()) {
+        Ok(mut store) => {
+            if let Err(e) = store.record(record) {
+                output::warning(&format!("Failed to record publication locally: {}", e));
+            }
+        }
+        Err(e) => {
+            output::warning(&format!("Publication not recorded locally: {}", e));
+            output::hint("Fix or move the store; the CID above is still valid.");
+        }
+    }
+
+    0
+}
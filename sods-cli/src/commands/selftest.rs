@@ -0,0 +1,98 @@
+//! Selftest command implementation.
+//!
+//! Runs the protocol conformance vectors from `sods_core::conformance`
+//! against the code in this binary and reports which ones still hold.
+
+use clap::Args;
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::output;
+use sods_core::conformance::{self, ConformanceCheck, SPEC_VERSION};
+
+#[derive(Args)]
+pub struct SelftestArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ConformanceReport {
+    spec_version: &'static str,
+    binary_version: &'static str,
+    passed: usize,
+    failed: usize,
+    checks: Vec<ConformanceCheck>,
+}
+
+impl ConformanceReport {
+    fn new(checks: Vec<ConformanceCheck>) -> Self {
+        let passed = checks.iter().filter(|c| c.passed).count();
+        Self {
+            spec_version: SPEC_VERSION,
+            binary_version: env!("CARGO_PKG_VERSION"),
+            passed,
+            failed: checks.len() - passed,
+            checks,
+        }
+    }
+}
+
+/// Run the selftest command.
+pub fn run(args: SelftestArgs) -> i32 {
+    let report = ConformanceReport::new(conformance::run_all());
+
+    if args.json {
+        match serde_json::to_string_pretty(&report) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                output::error(&format!("Failed to encode report: {}", e));
+                return 1;
+            }
+        }
+    } else {
+        print_report(&report);
+    }
+
+    if report.failed == 0 {
+        0
+    } else {
+        1
+    }
+}
+
+fn print_report(report: &ConformanceReport) {
+    output::header("SODS Protocol Conformance");
+    output::kv("Spec version", report.spec_version);
+    output::kv("Binary version", report.binary_version);
+    println!();
+
+    for check in &report.checks {
+        let status = if check.passed {
+            "PASS".green().bold()
+        } else {
+            "FAIL".red().bold()
+        };
+        println!("  {} {:<9} {}", status, check.id, check.description);
+        if let Some(detail) = &check.detail {
+            println!("       {}", detail.dimmed());
+        }
+    }
+
+    println!();
+    if report.failed == 0 {
+        output::success(&format!(
+            "All {} checks conform to spec v{}",
+            report.passed, report.spec_version
+        ));
+    } else {
+        output::error(&format!(
+            "{} of {} checks diverge from spec v{}",
+            report.failed,
+            report.passed + report.failed,
+            report.spec_version
+        ));
+        output::hint("This build computes different roots or calldata than other SODS nodes.");
+    }
+}
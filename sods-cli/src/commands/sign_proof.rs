@@ -0,0 +1,111 @@
+//! Sign proof bundles prepared by `export-proof --unsigned`.
+//!
+//! Meant to run on an offline machine: it needs the bundle file and a key
+//! from the local keystore, never an RPC endpoint. Before signing, the
+//! commitment is shown and the bundle digest printed by `export-proof` must
+//! be confirmed, so a bundle swapped in transit is not signed.
+
+use clap::Args;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+use crate::commands::export_proof::{print_proof, Format};
+use crate::keys;
+use crate::output;
+use crate::unsigned_bundle::{UnsignedBundle, CONFIRM_PREFIX_LEN};
+
+#[derive(Args)]
+pub struct SignProofArgs {
+    /// Unsigned bundle written by `export-proof --unsigned`
+    pub bundle: PathBuf,
+
+    /// Name of the signing key (see `sods keys`)
+    #[arg(long)]
+    pub key: String,
+
+    /// Output format of the signed proof (`vc` is issued by the signing key)
+    #[arg(short, long, default_value = "calldata")]
+    pub format: Format,
+
+    /// Bundle digest printed by `export-proof`, or its first 8 hex characters.
+    /// Required when not running interactively.
+    #[arg(long, value_name = "DIGEST")]
+    pub expect_digest: Option<String>,
+}
+
+pub async fn run(args: SignProofArgs) -> i32 {
+    let bundle = match UnsignedBundle::load(&args.bundle) {
+        Ok(b) => b,
+        Err(e) => {
+            output::error(&e);
+            return 1;
+        }
+    };
+
+    let commitment = bundle.commitment();
+    output::header("Proof Bundle");
+    output::kv("Pattern", &bundle.pattern);
+    output::kv("Chain ID", &commitment.chain_id.to_string());
+    output::kv("Block", &commitment.block_number.to_string());
+    output::kv(
+        "BMT root",
+        &format!("0x{}", hex::encode(commitment.bmt_root)),
+    );
+    output::kv(
+        "Receipts root",
+        &format!("0x{}", hex::encode(commitment.receipts_root)),
+    );
+    if let Some(cid) = &commitment.availability_cid {
+        output::kv("Availability CID", cid);
+    }
+    output::kv("Bundle digest", &bundle.digest_hex());
+
+    let confirmation = match args.expect_digest {
+        Some(digest) => digest,
+        None if std::io::stdin().is_terminal() => {
+            print!(
+                "Type the first {} characters of the digest printed by export-proof: ",
+                CONFIRM_PREFIX_LEN
+            );
+            let _ = std::io::stdout().flush();
+            let mut line = String::new();
+            if std::io::stdin().lock().read_line(&mut line).is_err() {
+                output::error("Failed to read confirmation");
+                return 1;
+            }
+            line
+        }
+        None => {
+            output::error("Pass --expect-digest when not running interactively.");
+            return 1;
+        }
+    };
+    if !bundle.confirms(&confirmation) {
+        output::error("Digest does not match this bundle; refusing to sign.");
+        output::hint("The bundle may have been altered in transit. Export it again.");
+        return 1;
+    }
+
+    let wallet = match keys::load_wallet(&args.key) {
+        Ok(w) => w,
+        Err(e) => {
+            output::error(&e);
+            return 1;
+        }
+    };
+
+    let pattern = bundle.pattern.clone();
+    let proof = match bundle.sign(&wallet).await {
+        Ok(p) => p,
+        Err(e) => {
+            output::error(&format!("Failed to sign commitment: {}", e));
+            return 1;
+        }
+    };
+    output::success(&format!(
+        "Commitment signed by {}",
+        ethers_signers::Signer::address(&wallet)
+    ));
+
+    print_proof(args.format, &pattern, proof, Some(&wallet))
+}
@@ -2,12 +2,15 @@
 
 use clap::{Args, Subcommand};
 use colored::Colorize;
+use ethers_core::types::H256;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
 use crate::config::SYMBOLS;
 use crate::output;
 use sods_core::plugins::SymbolPlugin;
+use sods_core::SymbolDictionary;
 
 #[derive(Args)]
 pub struct SymbolsArgs {
@@ -21,6 +24,8 @@ pub enum SymbolsCmd {
     List,
     /// Load a symbol plugin from a URL (JSON)
     Load { url: String },
+    /// Show plugins that collide on an event topic, and the resulting mapping
+    Conflicts,
 }
 
 /// Run the symbols command.
@@ -28,9 +33,104 @@ pub async fn run(args: SymbolsArgs) -> i32 {
     match args.command.unwrap_or(SymbolsCmd::List) {
         SymbolsCmd::List => list_symbols(),
         SymbolsCmd::Load { url } => load_symbol_plugin(&url).await,
+        SymbolsCmd::Conflicts => show_conflicts(),
     }
 }
 
+/// A plugin whose topic was already mapped when it was registered.
+struct Conflict {
+    topic: H256,
+    /// What the topic was mapped to, and by whom
+    existing: String,
+    plugin: String,
+    symbol: String,
+    /// Whether the plugin replaced the mapping rather than being skipped
+    overridden: bool,
+}
+
+/// Registers `plugins` in order on the core vocabulary, as `monitor` does,
+/// recording every collision. Also returns which plugin each topic comes
+/// from.
+fn audit_plugins(
+    plugins: Vec<SymbolPlugin>,
+) -> (SymbolDictionary, HashMap<H256, String>, Vec<Conflict>) {
+    let mut dictionary = SymbolDictionary::default();
+    let mut origins: HashMap<H256, String> = HashMap::new();
+    let mut conflicts = Vec::new();
+    for plugin in plugins {
+        let topic = plugin.event_topic;
+        if let Some(existing) = dictionary.conflict(topic, &plugin.symbol, Some(&plugin.parser)) {
+            let existing = match origins.get(&topic) {
+                Some(name) => format!("{} (plugin '{}')", existing, name),
+                None => format!("{} (core)", existing),
+            };
+            conflicts.push(Conflict {
+                topic,
+                existing,
+                plugin: plugin.name.clone(),
+                symbol: plugin.symbol.clone(),
+                overridden: plugin.overrides,
+            });
+        }
+        let name = plugin.name.clone();
+        if dictionary.register_plugin(plugin).is_ok() {
+            origins.insert(topic, name);
+        }
+    }
+    (dictionary, origins, conflicts)
+}
+
+fn show_conflicts() -> i32 {
+    let plugins = match load_local_plugins() {
+        Ok(p) => p,
+        Err(e) => {
+            output::error(&format!("Failed to load plugins: {}", e));
+            return 1;
+        }
+    };
+    let count = plugins.len();
+    let (dictionary, origins, conflicts) = audit_plugins(plugins);
+
+    output::header("Symbol Topic Conflicts");
+    if conflicts.is_empty() {
+        output::success(&format!("No conflicts among {} plugin(s)", count));
+    }
+    for c in &conflicts {
+        if c.overridden {
+            output::warning(&format!(
+                "{:?}: '{}' overrides {} with {}",
+                c.topic, c.plugin, c.existing, c.symbol
+            ));
+        } else {
+            output::error(&format!(
+                "{:?}: '{}' ({}) is skipped, the topic stays {}",
+                c.topic, c.plugin, c.symbol, c.existing
+            ));
+        }
+    }
+
+    output::header("Effective Mapping");
+    for (topic, symbol) in dictionary.mappings() {
+        let origin = match origins.get(&topic) {
+            Some(name) => format!("plugin '{}'", name),
+            None => "core".to_string(),
+        };
+        println!(
+            "{:<10} {:?} {}",
+            symbol.green().bold(),
+            topic,
+            origin.dimmed()
+        );
+    }
+
+    if conflicts.iter().any(|c| !c.overridden) {
+        println!();
+        output::hint("Set \"override\": true in a plugin's JSON to let it replace the mapping");
+        return 1;
+    }
+    0
+}
+
 fn list_symbols() -> i32 {
     output::header("Supported Behavioral Symbols");
 
@@ -38,9 +138,45 @@ fn list_symbols() -> i32 {
     println!("{:<8} {}", "Symbol".bold(), "Meaning".bold());
     println!("{}", "─".repeat(40).dimmed());
 
-    // Built-ins
+    // Built-ins, with labels and aliases from ~/.sods/config.toml
+    let aliases = crate::config::load_symbol_aliases();
     for (symbol, meaning) in SYMBOLS {
-        println!("{:<8} {}", symbol.green().bold(), meaning);
+        let meaning = aliases.description(symbol).unwrap_or(meaning);
+        let mut extra = Vec::new();
+        let display = aliases.display_name(symbol);
+        if display != *symbol {
+            extra.push(display.to_string());
+        }
+        let names = aliases.aliases_for(symbol);
+        if !names.is_empty() {
+            extra.push(format!("aliases: {}", names.join(", ")));
+        }
+        if extra.is_empty() {
+            println!("{:<8} {}", symbol.green().bold(), meaning);
+        } else {
+            println!(
+                "{:<8} {} {}",
+                symbol.green().bold(),
+                meaning,
+                format!("({})", extra.join("; ")).dimmed()
+            );
+        }
+    }
+
+    // Entries from ~/.sods/dictionary.toml
+    match SymbolDictionary::load_local_entries() {
+        Ok(entries) if !entries.is_empty() => {
+            println!("{}", "─".repeat(40).dimmed());
+            for entry in entries {
+                println!(
+                    "{:<8} {} (Dictionary file)",
+                    entry.symbol.green().bold(),
+                    entry.signature
+                );
+            }
+        }
+        Ok(_) => {}
+        Err(e) => output::warning(&format!("Ignoring dictionary file: {}", e)),
     }
 
     // Loaded Plugins
@@ -67,14 +203,47 @@ fn list_symbols() -> i32 {
         "  {:<20} Use '{{n}}' (e.g., 'Sw{{3}}' for 3 Swaps)",
         "Exact Count"
     );
+    println!(
+        "  {:<20} Use '!' or 'not' (e.g., 'Tf -> !Sw -> Tf')",
+        "Absence"
+    );
+    println!(
+        "  {:<20} Use '*' or 'any' (e.g., 'Dep -> * -> Wdw')",
+        "Wildcard"
+    );
+    println!("  {:<20} Use '( ... )' (e.g., '(Tf -> Sw){{3,}}')", "Group");
+    println!(
+        "  {:<20} Use '=>' (e.g., 'Tf => Sw' in one transaction)",
+        "Same Transaction"
+    );
+    println!(
+        "  {:<20} Use '($from -> $to)' (e.g., 'Tf($a -> $b) -> Tf($b -> $a)')",
+        "Address Capture"
+    );
+    println!(
+        "  {:<20} Use 'within 5 blocks' or 'within 60s' after the pattern",
+        "Time Window"
+    );
     println!("  {:<20} Use 'where from == deployer'", "Context Filter");
+    println!("  {:<20} Use 'where to == 0x...'", "Recipient Filter");
+    println!("  {:<20} Use 'where from == 0x...'", "Sender Filter");
     println!(
-        "  {:<20} Use 'where value > 10 ether' (or gwei)",
+        "  {:<20} Use 'where value > 10 ether' (or gwei, mwei, tokens(6), ...)",
         "Value Filter"
     );
+    println!(
+        "  {:<20} Use 'where value between 1 ether and 10 ether'",
+        "Value Range"
+    );
 
     println!();
     output::hint("Load new symbols: sods symbols load <URL>");
+    output::hint("Or map event signatures under [[symbols]] in ~/.sods/dictionary.toml");
+    if aliases.is_empty() {
+        output::hint(
+            "Define aliases like Swap = \"Sw\" under [symbols.aliases] in ~/.sods/config.toml",
+        );
+    }
 
     0
 }
@@ -129,7 +298,7 @@ fn get_plugins_dir() -> PathBuf {
     dir
 }
 
-fn save_plugin(plugin: &SymbolPlugin, json_content: &str) -> std::io::Result<()> {
+pub(crate) fn save_plugin(plugin: &SymbolPlugin, json_content: &str) -> std::io::Result<()> {
     let dir = get_plugins_dir();
     let filename = format!("{}.json", plugin.symbol);
     fs::write(dir.join(filename), json_content)
@@ -140,9 +309,12 @@ pub fn load_local_plugins() -> std::io::Result<Vec<SymbolPlugin>> {
     let mut plugins = Vec::new();
 
     if dir.exists() {
-        for entry in fs::read_dir(dir)? {
-            let entry = entry?;
-            let path = entry.path();
+        // Plugins register in file name order, so collisions resolve the same way every run
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.sort();
+        for path in paths {
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
                 let content = fs::read_to_string(&path)?;
                 if let Ok(p) = SymbolPlugin::load_from_json(&content) {
@@ -153,3 +325,37 @@ pub fn load_local_plugins() -> std::io::Result<Vec<SymbolPlugin>> {
     }
     Ok(plugins)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sods_core::plugins::ParserType;
+
+    #[test]
+    fn test_audit_reports_topic_collisions() {
+        let transfer = SymbolDictionary::default().topics_for_symbol("Tf")[0];
+        let plugin = |name: &str, symbol: &str, topic, overrides| SymbolPlugin {
+            name: name.into(),
+            symbol: symbol.into(),
+            chain: None,
+            event_topic: topic,
+            parser: ParserType::Generic,
+            overrides,
+        };
+        let custom = H256::repeat_byte(0x42);
+        let (dictionary, origins, conflicts) = audit_plugins(vec![
+            plugin("Custom", "Cx", custom, false),
+            plugin("Other", "Cy", custom, false),
+            plugin("My Transfer", "MyTf", transfer, true),
+        ]);
+
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].existing, "Cx (plugin 'Custom')");
+        assert!(!conflicts[0].overridden);
+        assert_eq!(conflicts[1].existing, "Tf (core)");
+        assert!(conflicts[1].overridden);
+        assert_eq!(dictionary.symbol_for_topic(custom), Some("Cx"));
+        assert_eq!(dictionary.symbol_for_topic(transfer), Some("MyTf"));
+        assert_eq!(origins[&transfer], "My Transfer");
+    }
+}
@@ -2,9 +2,15 @@
 
 use clap::{Args, Subcommand};
 use colored::Colorize;
-use sods_p2p::ThreatRule;
+use serde::de::DeserializeOwned;
+use sods_p2p::{PackDependency, RulePack, ThreatRule};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::commands::symbols::{load_local_plugins, save_plugin};
+use crate::keys;
+use crate::output;
+use crate::rule_pack::{self, ConflictPolicy, InstalledPack, LocalState};
 
 #[derive(Args)]
 pub struct ThreatsArgs {
@@ -21,17 +27,82 @@ pub enum ThreatsCommands {
         /// Compressed public key (33 bytes) in hex
         key: String,
     },
+    /// Import, export and list rule packs
+    Pack {
+        #[command(subcommand)]
+        command: PackCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum PackCommands {
+    /// Import a signed rule pack from a file or URL
+    Import {
+        /// Path or http(s) URL of the pack JSON
+        source: String,
+
+        /// How to resolve items that already exist locally
+        #[arg(long, value_enum, default_value = "newer")]
+        on_conflict: ConflictPolicy,
+
+        /// Allow installing an older version of an installed pack
+        #[arg(long)]
+        force: bool,
+    },
+    /// Bundle the local rules, watchlist, plugins and presets into a signed pack
+    Export {
+        /// Pack name (e.g. "DeFi-Drainer-Pack-2025")
+        #[arg(long)]
+        name: String,
+
+        /// Pack version (dotted numeric)
+        #[arg(long, default_value = "1.0.0")]
+        pack_version: String,
+
+        #[arg(long, default_value = "")]
+        description: String,
+
+        /// Required pack as NAME@MIN_VERSION (repeatable)
+        #[arg(long = "depends")]
+        depends: Vec<String>,
+
+        /// Name of the author's signing key (see `sods keys`)
+        #[arg(long)]
+        key: String,
+
+        /// Output file
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// List installed packs
+    List,
 }
 
 pub async fn run(args: ThreatsArgs) -> i32 {
     match args.command {
         ThreatsCommands::List => list_rules(),
         ThreatsCommands::AddKey { key } => add_key(key),
+        ThreatsCommands::Pack { command } => match command {
+            PackCommands::Import {
+                source,
+                on_conflict,
+                force,
+            } => import_pack(&source, on_conflict, force).await,
+            PackCommands::Export {
+                name,
+                pack_version,
+                description,
+                depends,
+                key,
+                output,
+            } => export_pack(&name, &pack_version, &description, &depends, &key, &output),
+            PackCommands::List => list_packs(),
+        },
     }
 }
 
 fn get_sods_dir() -> PathBuf {
-    let mut path = dirs::home_dir().expect("Home directory not found");
+    let mut path = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
     path.push(".sods");
     fs::create_dir_all(&path).ok();
     path
@@ -111,3 +182,230 @@ fn add_key(key_hex: String) -> i32 {
 
     0
 }
+
+fn read_json<T: DeserializeOwned + Default>(file: &str) -> T {
+    let path = get_sods_dir().join(file);
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+fn write_json<T: serde::Serialize>(file: &str, value: &T) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+    fs::write(get_sods_dir().join(file), json)
+}
+
+fn load_state() -> LocalState {
+    LocalState {
+        rules: read_json("threat_rules.json"),
+        watchlist: read_json("watchlist.json"),
+        plugins: load_local_plugins().unwrap_or_default(),
+        presets: read_json("presets.json"),
+        packs: read_json("packs.json"),
+    }
+}
+
+/// Read a pack from a file or an `https://` URL. Plain `http://` is
+/// refused, since the pack is executed as rules and plugins.
+async fn fetch_pack(source: &str) -> Result<String, String> {
+    if source.starts_with("http://") {
+        return Err(format!(
+            "Refusing to fetch {} over plain HTTP; use an https:// URL",
+            source
+        ));
+    }
+    if source.starts_with("https://") {
+        let resp = reqwest::get(source)
+            .await
+            .map_err(|e| format!("Failed to fetch pack: {}", e))?;
+        resp.text()
+            .await
+            .map_err(|e| format!("Failed to read body: {}", e))
+    } else {
+        fs::read_to_string(source).map_err(|e| format!("Failed to read {}: {}", source, e))
+    }
+}
+
+async fn import_pack(source: &str, policy: ConflictPolicy, force: bool) -> i32 {
+    output::header("Importing Rule Pack...");
+    println!("   Source: {}", source);
+
+    let body = match fetch_pack(source).await {
+        Ok(b) => b,
+        Err(e) => {
+            output::error(&e);
+            return 1;
+        }
+    };
+
+    let pack: RulePack = match serde_json::from_str(&body) {
+        Ok(p) => p,
+        Err(e) => {
+            output::error(&format!("Invalid pack JSON: {}", e));
+            return 1;
+        }
+    };
+
+    if !pack.verify() {
+        output::error("Pack signature or contents are invalid");
+        return 1;
+    }
+
+    let author = hex::encode(&pack.author_pubkey);
+    let trusted: Vec<String> = read_json("trusted_keys.json");
+    if trusted.is_empty() {
+        output::error("No trusted keys, so no pack author can be trusted");
+        output::hint("Trust the author first: sods threats add-key <KEY>");
+        return 1;
+    }
+    if !trusted.contains(&author) {
+        output::error(&format!("Pack author {} is not a trusted key", author));
+        output::hint("Trust the author first: sods threats add-key <KEY>");
+        return 1;
+    }
+
+    println!(
+        "   Pack:   {} v{} ({} rules, {} watchlist, {} plugins, {} presets)",
+        pack.name.cyan(),
+        pack.version,
+        pack.rules.len(),
+        pack.watchlist.len(),
+        pack.plugins.len(),
+        pack.presets.len()
+    );
+
+    // Held until the rules are rewritten, so `sods gc` cannot compact in between
+    let _rules_lock = match crate::retention::lock_store(&get_sods_dir().join("threat_rules.json"))
+    {
+        Ok(lock) => lock,
+        Err(e) => {
+            output::error(&format!("Failed to lock the threat rules: {}", e));
+            return 1;
+        }
+    };
+    let mut state = load_state();
+    if let Err(e) = rule_pack::check_dependencies(&pack, &state.packs, force) {
+        output::error(&e.to_string());
+        return 1;
+    }
+
+    let report = rule_pack::merge(&mut state, &pack, policy);
+
+    let saved = write_json("threat_rules.json", &state.rules)
+        .and_then(|_| write_json("watchlist.json", &state.watchlist))
+        .and_then(|_| write_json("presets.json", &state.presets))
+        .and_then(|_| write_json("packs.json", &state.packs))
+        .and_then(|_| {
+            state
+                .plugins
+                .iter()
+                .filter(|p| report.changed_plugins.contains(&p.symbol))
+                .try_for_each(|p| {
+                    let json = serde_json::to_string_pretty(p)
+                        .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+                    save_plugin(p, &json)
+                })
+        });
+    if let Err(e) = saved {
+        output::error(&format!("Failed to save pack contents: {}", e));
+        return 1;
+    }
+
+    output::success(&format!(
+        "Imported {} v{}: {} added, {} replaced",
+        pack.name, pack.version, report.added, report.replaced
+    ));
+    if !report.kept.is_empty() {
+        output::warning(&format!(
+            "Kept {} local item(s) on conflict: {}",
+            report.kept.len(),
+            report.kept.join(", ")
+        ));
+    }
+
+    0
+}
+
+fn export_pack(
+    name: &str,
+    version: &str,
+    description: &str,
+    depends: &[String],
+    key: &str,
+    output_path: &Path,
+) -> i32 {
+    let signing_key = match keys::load_signing_key(key) {
+        Ok(k) => k,
+        Err(e) => {
+            output::error(&e);
+            return 1;
+        }
+    };
+
+    let mut dependencies = Vec::new();
+    for dep in depends {
+        match dep.split_once('@') {
+            Some((dep_name, min_version)) => dependencies.push(PackDependency {
+                name: dep_name.to_string(),
+                min_version: min_version.to_string(),
+            }),
+            None => {
+                output::error(&format!(
+                    "Invalid dependency '{}' (expected NAME@VERSION)",
+                    dep
+                ));
+                return 1;
+            }
+        }
+    }
+
+    let state = load_state();
+    let pack = RulePack::new(
+        name,
+        version,
+        description,
+        dependencies,
+        state.rules,
+        state.watchlist,
+        state.plugins,
+        state.presets,
+        &signing_key,
+    );
+
+    if !pack.verify() {
+        output::error("Local state does not form a valid pack (check version and patterns)");
+        return 1;
+    }
+
+    let json = serde_json::to_string_pretty(&pack).unwrap();
+    if let Err(e) = fs::write(output_path, json) {
+        output::error(&format!("Failed to write pack: {}", e));
+        return 1;
+    }
+
+    output::success(&format!(
+        "Exported {} v{} to {}",
+        name,
+        version,
+        output_path.display()
+    ));
+    output::kv("Author", &hex::encode(&pack.author_pubkey));
+    0
+}
+
+fn list_packs() -> i32 {
+    let packs: Vec<InstalledPack> = read_json("packs.json");
+    if packs.is_empty() {
+        println!("No rule packs installed.");
+        return 0;
+    }
+
+    println!("{}", "Installed Rule Packs:".green().bold());
+    for pack in packs {
+        println!("- {} v{}", pack.name.cyan(), pack.version);
+        println!("  Author: {}", pack.author);
+    }
+    0
+}
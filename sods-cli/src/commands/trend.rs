@@ -3,11 +3,14 @@
 use clap::Args;
 use colored::Colorize;
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::time::Duration;
 use tokio::time::sleep;
 
 use crate::config::get_chain;
 use crate::output;
+use crate::sketch::{LeaderboardSummary, SymbolLeaderboard};
+use crate::trend_store::{self, SymbolDelta, TrendRun, TrendStore};
 use sods_core::pattern::BehavioralPattern;
 use sods_verifier::BlockVerifier;
 
@@ -37,9 +40,27 @@ pub struct TrendArgs {
     /// Output as JSON
     #[arg(long)]
     pub json: bool,
+
+    /// Compare with a previous run (`latest`, a run id, or a path)
+    #[arg(long)]
+    pub compare_with: Option<String>,
+
+    /// Do not persist this run under ~/.sods/trends
+    #[arg(long)]
+    pub no_save: bool,
+
+    /// Show the top N actors and contracts per symbol (0 to disable)
+    #[arg(long, default_value = "3")]
+    pub top: usize,
 }
 
-#[derive(Serialize)]
+/// Share of a symbol above which a single actor is called out.
+const DOMINANCE_THRESHOLD: f64 = 0.5;
+
+/// Candidates tracked per sketch, relative to the requested top N.
+const SKETCH_OVERSAMPLING: usize = 8;
+
+#[derive(Serialize, Default)]
 struct TrendJsonOutput {
     pattern: String,
     chain: String,
@@ -47,6 +68,16 @@ struct TrendJsonOutput {
     frequency_percent: f64,
     matches: usize,
     hotspots: Vec<u64>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    symbol_counts: BTreeMap<String, u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    run_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compared_with: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deltas: Option<Vec<SymbolDelta>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    leaderboards: BTreeMap<String, LeaderboardSummary>,
     error: Option<String>,
 }
 
@@ -63,7 +94,8 @@ pub async fn run(args: TrendArgs) -> i32 {
     };
 
     // 2. Parse Pattern
-    let pattern = match BehavioralPattern::parse(&args.pattern) {
+    let aliases = crate::config::load_symbol_aliases();
+    let pattern = match BehavioralPattern::parse_with_aliases(&args.pattern, &aliases) {
         Ok(p) => p,
         Err(e) => {
             if args.json {
@@ -75,6 +107,7 @@ pub async fn run(args: TrendArgs) -> i32 {
                     matches: 0,
                     hotspots: vec![],
                     error: Some(format!("Invalid pattern: {}", e)),
+                    ..Default::default()
                 };
                 println!("{}", serde_json::to_string_pretty(&output).unwrap());
             } else {
@@ -97,6 +130,7 @@ pub async fn run(args: TrendArgs) -> i32 {
                     matches: 0,
                     hotspots: vec![],
                     error: Some(format!("Unknown chain: '{}'", args.chain)),
+                    ..Default::default()
                 };
                 println!("{}", serde_json::to_string_pretty(&output).unwrap());
             } else {
@@ -125,7 +159,7 @@ pub async fn run(args: TrendArgs) -> i32 {
     };
 
     let verifier = match BlockVerifier::new(&rpc_urls) {
-        Ok(v) => v.with_backoff_profile(profile),
+        Ok(v) => v.with_backoff_profile(profile).with_aliases(aliases),
         Err(e) => {
             if args.json {
                 println!("{{ \"error\": \"Failed to initialize RPCs: {}\" }}", e);
@@ -166,6 +200,9 @@ pub async fn run(args: TrendArgs) -> i32 {
 
     // 6. Scan Loop
     let mut hotspots = Vec::new();
+    let mut symbol_counts: BTreeMap<String, u64> = BTreeMap::new();
+    let mut boards: BTreeMap<String, SymbolLeaderboard> = BTreeMap::new();
+    let sketch_capacity = (args.top * SKETCH_OVERSAMPLING).max(16);
     let start_block = head_block.saturating_sub(window).saturating_add(1); // e.g. head=100, win=10 -> 91..=100
 
     // Reverse order scan (newest first)
@@ -174,7 +211,13 @@ pub async fn run(args: TrendArgs) -> i32 {
         sleep(Duration::from_millis(500)).await;
 
         // Fetch symbols
-        let symbols = match verifier.fetch_block_symbols(block_num).await {
+        let fetched = match verifier.fetch_block_symbols_for(block_num, &pattern).await {
+            Ok(mut s) if pattern.has_gas_condition() => {
+                verifier.fill_gas_used(&mut s).await.map(|_| s)
+            }
+            other => other,
+        };
+        let symbols = match fetched {
             Ok(s) => s,
             Err(e) => {
                 if !args.json {
@@ -184,6 +227,16 @@ pub async fn run(args: TrendArgs) -> i32 {
             }
         };
 
+        for s in &symbols {
+            *symbol_counts.entry(s.symbol.clone()).or_default() += 1;
+            if args.top > 0 {
+                boards
+                    .entry(s.symbol.clone())
+                    .or_insert_with(|| SymbolLeaderboard::new(sketch_capacity))
+                    .observe(s);
+            }
+        }
+
         // Match pattern
         if pattern.matches(&symbols, None).is_some() {
             hotspots.push(block_num);
@@ -212,6 +265,55 @@ pub async fn run(args: TrendArgs) -> i32 {
         0.0
     };
 
+    let leaderboards: BTreeMap<String, LeaderboardSummary> = boards
+        .iter()
+        .map(|(symbol, board)| (symbol.clone(), board.summary(args.top)))
+        .collect();
+
+    // 8. Persist and compare
+    let created_at = chrono::Utc::now().timestamp() as u64;
+    let run = TrendRun {
+        id: format!("{}-{}", chain_config.name, created_at),
+        chain: chain_config.name.to_string(),
+        pattern: args.pattern.clone(),
+        head_block,
+        window,
+        created_at,
+        matches: matches_count,
+        symbol_counts: symbol_counts.clone(),
+    };
+
+    let store = TrendStore::new(TrendStore::default_dir());
+    let history = store.runs(&run.chain);
+    let previous = match &args.compare_with {
+        Some(reference) => match store.resolve(reference, &run.chain) {
+            Some(prev) => Some(prev),
+            None => {
+                output::warning(&format!(
+                    "No stored trend run '{}' to compare with.",
+                    reference
+                ));
+                None
+            }
+        },
+        None => None,
+    };
+    let deltas = previous
+        .as_ref()
+        .map(|prev| trend_store::compare(prev, &run));
+
+    let run_id = if args.no_save {
+        None
+    } else {
+        match store.save(&run) {
+            Ok(_) => Some(run.id.clone()),
+            Err(e) => {
+                output::warning(&format!("Failed to save trend run: {}", e));
+                None
+            }
+        }
+    };
+
     if args.json {
         let output = TrendJsonOutput {
             pattern: args.pattern.clone(),
@@ -220,6 +322,11 @@ pub async fn run(args: TrendArgs) -> i32 {
             frequency_percent: frequency,
             matches: matches_count,
             hotspots,
+            symbol_counts,
+            run_id,
+            compared_with: previous.map(|p| p.id),
+            deltas,
+            leaderboards,
             error: None,
         };
         println!("{}", serde_json::to_string_pretty(&output).unwrap());
@@ -254,8 +361,115 @@ pub async fn run(args: TrendArgs) -> i32 {
         } else {
             println!("   Hotspots:  None found");
         }
+        if let Some(id) = &run_id {
+            println!("   Saved as:  {}", id);
+        }
         println!();
+
+        if !leaderboards.is_empty() {
+            print_leaderboards(&leaderboards);
+        }
+
+        if let (Some(prev), Some(deltas)) = (&previous, &deltas) {
+            print_comparison(prev, &run, &history, deltas);
+        }
     }
 
     0
 }
+
+/// Prints per-symbol deltas against `previous`, with a sparkline of each
+/// symbol's per-block rate across all stored runs of the chain.
+fn print_comparison(
+    previous: &TrendRun,
+    current: &TrendRun,
+    history: &[TrendRun],
+    deltas: &[SymbolDelta],
+) {
+    println!(
+        "{} vs {} (block #{}, {} blocks):",
+        "Changes".bold(),
+        previous.id,
+        previous.head_block,
+        previous.window
+    );
+    if deltas.is_empty() {
+        println!("   No symbols observed in either run");
+        return;
+    }
+
+    println!(
+        "   {:<8} {:>8} {:>8} {:>10}  History",
+        "Symbol", "Before", "Now", "Change"
+    );
+    for delta in deltas {
+        let change = match delta.percent_change {
+            Some(pct) => format!("{:+.1}%", pct),
+            None => "new".to_string(),
+        };
+        let change = if delta.rate_delta > 0.0 {
+            change.green()
+        } else if delta.rate_delta < 0.0 {
+            change.red()
+        } else {
+            change.normal()
+        };
+        let rates: Vec<f64> = history
+            .iter()
+            .chain(std::iter::once(current))
+            .map(|run| run.rate(&delta.symbol))
+            .collect();
+        println!(
+            "   {:<8} {:>8} {:>8} {:>10}  {}",
+            delta.symbol,
+            delta.previous,
+            delta.current,
+            change,
+            trend_store::sparkline(&rates)
+        );
+    }
+    println!();
+}
+
+/// Prints the top actors and contracts per symbol, calling out any single
+/// actor that produced most of a symbol's occurrences.
+fn print_leaderboards(leaderboards: &BTreeMap<String, LeaderboardSummary>) {
+    println!("{}", "Leaderboards:".bold());
+    for (symbol, board) in leaderboards {
+        println!(
+            "   {} ({} events, ~{} distinct actors)",
+            symbol.bold(),
+            board.total,
+            board.distinct_actors
+        );
+
+        if let Some(top) = board.top_actors.first() {
+            let share = top.count as f64 / board.total as f64;
+            if share >= DOMINANCE_THRESHOLD {
+                println!(
+                    "     {} one address produced {:.0}% of {} events: {:?}",
+                    "⚠".yellow(),
+                    share * 100.0,
+                    symbol,
+                    top.address
+                );
+            }
+        }
+
+        for (label, hitters) in [
+            ("actor", &board.top_actors),
+            ("contract", &board.top_contracts),
+        ] {
+            for hitter in hitters {
+                println!(
+                    "     {:<8} {:?}  {:>6} ({:.1}%)",
+                    label,
+                    hitter.address,
+                    hitter.count,
+                    hitter.count as f64 / board.total as f64 * 100.0
+                );
+            }
+        }
+    }
+    println!();
+}
@@ -2,6 +2,11 @@
 
 use clap::{Args, ValueEnum};
 use serde::Serialize;
+use sods_core::pattern::{MatchExplanation, StepOutcome};
+use sods_core::{root_cause, ErrorClass, ErrorCode};
+use sods_p2p::{NetworkConfig, SodsClient};
+use sods_verifier::{FeeContext, ReorgContext};
+use std::path::PathBuf;
 
 use crate::config::{get_chain, is_symbol_supported, SYMBOLS};
 use crate::output;
@@ -36,7 +41,7 @@ pub struct VerifyArgs {
     #[arg(short, long, default_value = "sepolia")]
     pub chain: String,
 
-    /// Verification mode: 'rpc' (default), 'p2p', 'trustless', or 'storage-proof' (Zero-RPC trustless)
+    /// Verification mode: 'rpc' (default), 'p2p' (consensus among --bootstrap peers), 'trustless', or 'storage-proof' (Zero-RPC trustless)
     #[arg(short, long, default_value = "auto")]
     pub mode: Mode,
 
@@ -59,6 +64,32 @@ pub struct VerifyArgs {
     /// Skip header-anchored verification (not recommended for production)
     #[arg(long)]
     pub no_header_proof: bool,
+
+    /// Omit block explorer links from the output
+    #[arg(long)]
+    pub no_links: bool,
+
+    /// Compare the block across all RPC endpoints and report reorgs
+    #[arg(long)]
+    pub reorg_check: bool,
+
+    /// Explain step by step why the pattern did or did not match
+    #[arg(long)]
+    pub explain: bool,
+
+    /// Attribute logs from EIP-1967 proxies to their implementation too,
+    /// for `where from == deployer` (one storage read per contract)
+    #[arg(long)]
+    pub resolve_proxies: bool,
+
+    /// With --mode p2p, ask this peer (repeatable)
+    #[arg(long = "bootstrap", value_name = "MULTIADDR")]
+    pub bootstrap: Vec<String>,
+
+    /// With --mode p2p, write the consensus transcript (queried peers,
+    /// their signed answers and the decision) into this directory
+    #[arg(long, value_name = "DIR")]
+    pub transcript_dir: Option<PathBuf>,
 }
 
 /// JSON output structure.
@@ -78,6 +109,44 @@ struct JsonOutput {
     error: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     matched_sequence: Option<Vec<MatchedSymbol>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    block_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fees: Option<FeeContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reorg: Option<ReorgContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation: Option<MatchExplanation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transcript_path: Option<String>,
+    #[serde(flatten)]
+    error_details: Option<ErrorDetails>,
+}
+
+/// Machine-readable classification of a failure.
+#[derive(Serialize)]
+struct ErrorDetails {
+    error_code: &'static str,
+    error_class: ErrorClass,
+    retryable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_after_secs: Option<u64>,
+    /// Innermost error, when `error` wraps one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    root_cause: Option<String>,
+}
+
+impl ErrorDetails {
+    fn from_error<E: ErrorCode + 'static>(e: &E) -> Self {
+        let root = root_cause(e);
+        Self {
+            error_code: e.code(),
+            error_class: e.class(),
+            retryable: e.is_retryable(),
+            retry_after_secs: e.retry_after().map(|d| d.as_secs().max(1)),
+            root_cause: e.source().map(|_| root.to_string()),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -89,12 +158,18 @@ struct MatchedSymbol {
 /// Run the verify command.
 pub async fn run(args: VerifyArgs) -> i32 {
     // 0. Check for Pattern
-    if args.symbol.contains("->") || args.symbol.contains('→') || args.symbol.contains('{') {
+    if args.explain
+        || args.symbol.contains("->")
+        || args.symbol.contains('→')
+        || args.symbol.contains('{')
+    {
         return run_pattern_verification(args).await;
     }
 
-    // Validate symbol
-    if !is_symbol_supported(&args.symbol) {
+    // Validate symbol, accepting configured aliases
+    let aliases = crate::config::load_symbol_aliases();
+    let canonical = aliases.resolve(&args.symbol).to_string();
+    if !is_symbol_supported(&canonical) {
         if args.json {
             let output = JsonOutput {
                 success: false,
@@ -109,6 +184,12 @@ pub async fn run(args: VerifyArgs) -> i32 {
                 verification_mode: "n/a".into(),
                 error: Some(format!("Unsupported symbol: '{}'", args.symbol)),
                 matched_sequence: None,
+                block_url: None,
+                fees: None,
+                reorg: None,
+                explanation: None,
+                transcript_path: None,
+                error_details: None,
             };
             println!("{}", serde_json::to_string_pretty(&output).unwrap());
         } else {
@@ -144,6 +225,12 @@ pub async fn run(args: VerifyArgs) -> i32 {
                     verification_mode: "n/a".into(),
                     error: Some(format!("Unknown chain: '{}'", args.chain)),
                     matched_sequence: None,
+                    block_url: None,
+                    fees: None,
+                    reorg: None,
+                    explanation: None,
+                    transcript_path: None,
+                    error_details: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&output).unwrap());
             } else {
@@ -154,6 +241,10 @@ pub async fn run(args: VerifyArgs) -> i32 {
         }
     };
 
+    if let Mode::P2p = args.mode {
+        return run_p2p_verification(&args, &canonical).await;
+    }
+
     // Determine RPC URLs
     let rpc_urls: Vec<String> = if let Some(url) = args.rpc_url {
         vec![url]
@@ -179,9 +270,14 @@ pub async fn run(args: VerifyArgs) -> i32 {
     };
 
     if !args.json {
+        let shown = if canonical == args.symbol {
+            format!("'{}'", args.symbol)
+        } else {
+            format!("'{}' ({})", args.symbol, canonical)
+        };
         output::info(&format!(
-            "Verifying '{}' in block {} ({})...",
-            args.symbol, args.block, chain_config.description
+            "Verifying {} in block {} ({})...",
+            shown, args.block, chain_config.description
         ));
     }
 
@@ -195,7 +291,7 @@ pub async fn run(args: VerifyArgs) -> i32 {
     };
 
     // Choose verifier based on requirements
-    let verifier: sods_verifier::BlockVerifier = match args.mode {
+    let verifier = match args.mode {
         Mode::Rpc => sods_verifier::BlockVerifier::new_rpc_only(&rpc_urls),
         Mode::StorageProof => sods_verifier::BlockVerifier::new_zero_rpc(&rpc_urls),
         Mode::Trustless => sods_verifier::BlockVerifier::new(&rpc_urls),
@@ -222,6 +318,12 @@ pub async fn run(args: VerifyArgs) -> i32 {
                 verification_mode: format!("{:?}", args.mode).to_lowercase(),
                 error: Some(format!("Failed to create verifier: {}", e)),
                 matched_sequence: None,
+                block_url: None,
+                fees: None,
+                reorg: None,
+                explanation: None,
+                transcript_path: None,
+                error_details: Some(ErrorDetails::from_error(&e)),
             };
             println!("{}", serde_json::to_string_pretty(&output).unwrap());
         } else {
@@ -229,10 +331,19 @@ pub async fn run(args: VerifyArgs) -> i32 {
             output::hint("Check your network connection or RPC endpoints.");
         }
         e
-    })
-    .unwrap();
+    });
+    let verifier = match verifier {
+        Ok(verifier) => verifier,
+        Err(e) => return e.class().exit_code(),
+    };
 
-    let verifier = verifier.with_backoff_profile(profile);
+    let mut verifier = verifier.with_backoff_profile(profile).with_aliases(aliases);
+    if args.reorg_check {
+        verifier = verifier.with_reorg_tracking();
+    }
+    if args.resolve_proxies {
+        verifier = verifier.with_proxy_resolution();
+    }
 
     // Pre-flight health check
     if !verifier.health_check().await {
@@ -242,7 +353,7 @@ pub async fn run(args: VerifyArgs) -> i32 {
         // But we try to proceed anyway or fail early?
         // For verify, we might want to try once more or fail.
         // Let's fail if all are dead.
-        return 1;
+        return ErrorClass::Unavailable.exit_code();
     }
 
     // Beacon Support Warning
@@ -261,6 +372,11 @@ pub async fn run(args: VerifyArgs) -> i32 {
     {
         Ok(result) => {
             let elapsed = start.elapsed().as_millis() as u64;
+            let block_url = if args.no_links {
+                None
+            } else {
+                chain_config.block_url(args.block)
+            };
 
             if args.json {
                 let output = JsonOutput {
@@ -276,6 +392,12 @@ pub async fn run(args: VerifyArgs) -> i32 {
                     verification_mode: result.verification_mode.to_string(),
                     error: result.error,
                     matched_sequence: None,
+                    block_url,
+                    fees: result.fees.clone(),
+                    reorg: result.reorg.clone(),
+                    explanation: None,
+                    transcript_path: None,
+                    error_details: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&output).unwrap());
             } else {
@@ -286,6 +408,15 @@ pub async fn run(args: VerifyArgs) -> i32 {
                     elapsed,
                     result.occurrences,
                 );
+                if let Some(fees) = &result.fees {
+                    output::kv("Fees", &fees.summary());
+                }
+                if let Some(reorg) = &result.reorg {
+                    output::kv("Reorg", &reorg.summary());
+                }
+                if let Some(url) = &block_url {
+                    output::kv("Explorer", url);
+                }
 
                 if !result.is_verified {
                     output::hint(&format!(
@@ -321,15 +452,115 @@ pub async fn run(args: VerifyArgs) -> i32 {
                     },
                     error: Some(error_string),
                     matched_sequence: None,
+                    block_url: None,
+                    fees: None,
+                    reorg: None,
+                    explanation: None,
+                    transcript_path: None,
+                    error_details: Some(ErrorDetails::from_error(&e)),
                 };
                 println!("{}", serde_json::to_string_pretty(&output).unwrap());
             } else {
                 output::error(&format!("Verification failed: {}", e));
-                output::hint("Try a different block number or check the chain.");
+                if e.is_retryable() {
+                    output::retry_hint(e.retry_after());
+                } else {
+                    output::hint("Try a different block number or check the chain.");
+                }
+            }
+            e.class().exit_code()
+        }
+    }
+}
+
+/// Verifies `symbol` by consensus among the `--bootstrap` peers.
+async fn run_p2p_verification(args: &VerifyArgs, symbol: &str) -> i32 {
+    let start = std::time::Instant::now();
+    let mut config = NetworkConfig::new();
+    for addr in &args.bootstrap {
+        match addr.parse() {
+            Ok(addr) => config = config.with_bootstrap_peer(addr),
+            Err(_) => {
+                output::error(&format!("Invalid --bootstrap address: {}", addr));
+                return ErrorClass::InvalidInput.exit_code();
+            }
+        }
+    }
+    let mut client = match SodsClient::with_network_config(&config) {
+        Ok(client) => client,
+        Err(e) => {
+            output::error(&format!("Failed to initialize P2P client: {}", e));
+            return e.class().exit_code();
+        }
+    };
+    if let Some(dir) = &args.transcript_dir {
+        client = client.with_transcript_dir(dir);
+    }
+    if !args.json {
+        output::info(&format!(
+            "Verifying '{}' in block {} via P2P...",
+            symbol, args.block
+        ));
+    }
+
+    let outcome = match client.connect_bootstrap(&config.bootstrap).await {
+        Ok(()) => client.verify_via_p2p(symbol, args.block).await,
+        Err(e) => Err(e),
+    };
+    // Written for failed verifications too, which are the ones to audit
+    let transcript_path = client
+        .last_transcript_path()
+        .map(|path| path.display().to_string());
+    let elapsed = start.elapsed().as_millis() as u64;
+
+    let (verified, error) = match &outcome {
+        Ok(result) => (result.is_verified, None),
+        Err(e) => (false, Some(e)),
+    };
+    if args.json {
+        let output = JsonOutput {
+            success: error.is_none(),
+            symbol: args.symbol.clone(),
+            block: args.block,
+            chain: args.chain.clone(),
+            verified,
+            occurrences: 0,
+            proof_size_bytes: 0,
+            time_ms: elapsed,
+            method: "p2p".into(),
+            verification_mode: "p2p".into(),
+            error: error.map(|e| e.to_string()),
+            matched_sequence: None,
+            block_url: None,
+            fees: None,
+            reorg: None,
+            explanation: None,
+            transcript_path,
+            error_details: error.map(ErrorDetails::from_error),
+        };
+        println!("{}", serde_json::to_string_pretty(&output).unwrap());
+    } else {
+        match &outcome {
+            Ok(result) if result.is_verified => output::success(&format!(
+                "Verified via P2P ({}/{} peers agree)",
+                result.agreeing_peers, result.total_peers
+            )),
+            Ok(_) => output::error("Verification failed"),
+            Err(e) => {
+                output::error(&format!("P2P verification failed: {}", e));
+                output::p2p_hint(e);
             }
-            1
+        }
+        if let Some(path) = &transcript_path {
+            output::kv("Transcript", path);
         }
     }
+
+    match outcome {
+        Ok(result) if result.is_verified => 0,
+        Ok(_) => 1,
+        Err(e) => e.class().exit_code(),
+    }
 }
 
 use sods_core::pattern::BehavioralPattern;
@@ -338,7 +569,8 @@ async fn run_pattern_verification(args: VerifyArgs) -> i32 {
     let start = std::time::Instant::now();
 
     // 1. Parse Pattern
-    let _pattern = match BehavioralPattern::parse(&args.symbol) {
+    let aliases = crate::config::load_symbol_aliases();
+    let _pattern = match BehavioralPattern::parse_with_aliases(&args.symbol, &aliases) {
         Ok(p) => p,
         Err(e) => {
             if args.json {
@@ -357,6 +589,12 @@ async fn run_pattern_verification(args: VerifyArgs) -> i32 {
                     verification_mode: "n/a".into(),
                     error: Some(format!("Invalid pattern: {}", e)),
                     matched_sequence: None,
+                    block_url: None,
+                    fees: None,
+                    reorg: None,
+                    explanation: None,
+                    transcript_path: None,
+                    error_details: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&output).unwrap());
             } else {
@@ -407,7 +645,11 @@ async fn run_pattern_verification(args: VerifyArgs) -> i32 {
     };
 
     let verifier = match sods_verifier::BlockVerifier::new(&rpc_urls) {
-        Ok(v) => v.with_backoff_profile(profile),
+        Ok(v) if args.reorg_check => v
+            .with_backoff_profile(profile)
+            .with_aliases(aliases)
+            .with_reorg_tracking(),
+        Ok(v) => v.with_backoff_profile(profile).with_aliases(aliases),
         Err(e) => {
             if !args.json {
                 output::error(&format!("Failed to connect to RPC: {}", e));
@@ -415,6 +657,11 @@ async fn run_pattern_verification(args: VerifyArgs) -> i32 {
             return 1;
         }
     };
+    let verifier = if args.resolve_proxies {
+        verifier.with_proxy_resolution()
+    } else {
+        verifier
+    };
 
     // Beacon Support Warning
     if !args.json {
@@ -433,6 +680,27 @@ async fn run_pattern_verification(args: VerifyArgs) -> i32 {
     {
         Ok(result) => {
             let elapsed = start.elapsed().as_millis() as u64;
+            let block_url = if args.no_links {
+                None
+            } else {
+                chain_config.block_url(args.block)
+            };
+            let explanation = if args.explain {
+                match verifier
+                    .explain_pattern_in_block(&args.symbol, args.block)
+                    .await
+                {
+                    Ok(explanation) => Some(explanation),
+                    Err(e) => {
+                        if !args.json {
+                            output::warning(&format!("Could not explain the match: {}", e));
+                        }
+                        None
+                    }
+                }
+            } else {
+                None
+            };
 
             if args.json {
                 let output = JsonOutput {
@@ -448,6 +716,12 @@ async fn run_pattern_verification(args: VerifyArgs) -> i32 {
                     verification_mode: result.verification_mode.to_string(),
                     error: result.error,
                     matched_sequence: None, // Simplified for optimized path
+                    block_url,
+                    fees: result.fees.clone(),
+                    reorg: result.reorg.clone(),
+                    explanation: explanation.clone(),
+                    transcript_path: None,
+                    error_details: None,
                 };
                 println!("{}", serde_json::to_string_pretty(&output).unwrap());
             } else if result.is_verified {
@@ -459,9 +733,21 @@ async fn run_pattern_verification(args: VerifyArgs) -> i32 {
                 );
                 println!("   Time:        {} ms", elapsed);
                 println!("   Mode:        Incremental / Filtered");
+                if let Some(fees) = &result.fees {
+                    println!("   Fees:        {}", fees.summary());
+                }
+                if let Some(reorg) = &result.reorg {
+                    println!("   Reorg:       {}", reorg.summary());
+                }
+                if let Some(url) = &block_url {
+                    println!("   Explorer:    {}", url);
+                }
             } else {
                 output::error("Pattern not found in block.");
             }
+            if let (false, Some(explanation)) = (args.json, explanation) {
+                print_explanation(&explanation);
+            }
             if result.is_verified {
                 0
             } else {
@@ -470,11 +756,69 @@ async fn run_pattern_verification(args: VerifyArgs) -> i32 {
         }
         Err(e) => {
             if args.json {
-                // ... json error ...
+                let output = JsonOutput {
+                    success: false,
+                    symbol: args.symbol.clone(),
+                    block: args.block,
+                    chain: args.chain.clone(),
+                    verified: false,
+                    occurrences: 0,
+                    proof_size_bytes: 0,
+                    time_ms: start.elapsed().as_millis() as u64,
+                    method: "incremental_pattern".into(),
+                    verification_mode: "n/a".into(),
+                    error: Some(e.to_string()),
+                    matched_sequence: None,
+                    block_url: None,
+                    fees: None,
+                    reorg: None,
+                    explanation: None,
+                    transcript_path: None,
+                    error_details: Some(ErrorDetails::from_error(&e)),
+                };
+                println!("{}", serde_json::to_string_pretty(&output).unwrap());
             } else {
                 output::error(&format!("Pattern verification failed: {}", e));
+                if e.is_retryable() {
+                    output::retry_hint(e.retry_after());
+                }
+            }
+            e.class().exit_code()
+        }
+    }
+}
+
+/// Print each step of a pattern and how far matching got with it.
+fn print_explanation(explanation: &MatchExplanation) {
+    output::header("Match Explanation");
+    for trace in &explanation.steps {
+        let step = format!("Step {} ({})", trace.step + 1, trace.text);
+        match &trace.outcome {
+            StepOutcome::Matched { positions } if positions.is_empty() => {
+                output::success(&format!("{}: satisfied", step));
+            }
+            StepOutcome::Matched { positions } => {
+                output::success(&format!("{}: matched symbols {:?}", step, positions));
+            }
+            StepOutcome::Failed {
+                from,
+                candidates,
+                reason,
+            } => {
+                output::error(&format!("{}: {}", step, reason));
+                output::kv("Searched from", &format!("position {}", from));
+                for candidate in candidates {
+                    let verdict = match &candidate.failed_condition {
+                        Some(condition) => format!("fails '{}'", condition),
+                        None => "fits the step alone".to_string(),
+                    };
+                    output::kv(
+                        &format!("#{} {}", candidate.position, candidate.symbol),
+                        &format!("log {}, {}", candidate.log_index, verdict),
+                    );
+                }
             }
-            1
+            StepOutcome::NotReached => output::info(&format!("{}: not reached", step)),
         }
     }
 }
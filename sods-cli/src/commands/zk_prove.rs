@@ -5,6 +5,7 @@ use std::io::Write;
 
 use crate::config::get_chain;
 use crate::output;
+use sods_core::pattern::BehavioralPattern;
 use sods_verifier::BlockVerifier;
 
 #[derive(Args)]
@@ -33,6 +34,8 @@ pub struct ZkProveArgs {
 struct ZkProofOutput {
     success: bool,
     pattern: String,
+    /// Canonical hash of the pattern, as committed in the journal
+    pattern_hash: String,
     block: u64,
     chain: String,
     valid: bool,
@@ -50,6 +53,15 @@ pub async fn run(args: ZkProveArgs) -> i32 {
         }
     };
 
+    // Parsed as the guest parses it, to check the hash it commits to
+    let pattern = match BehavioralPattern::parse(&args.pattern) {
+        Ok(p) => p,
+        Err(e) => {
+            output::error(&format!("Invalid pattern: {}", e));
+            return 1;
+        }
+    };
+
     let rpc_urls: Vec<String> = if let Some(url) = args.rpc_url {
         vec![url]
     } else {
@@ -98,8 +110,8 @@ pub async fn run(args: ZkProveArgs) -> i32 {
     };
 
     // 3. Extract metadata from receipt journal
-    // Tuple: (blockNumber, chainId, pattern, result)
-    let journal_data: (u64, u64, String, bool) = match receipt.journal.decode() {
+    // Tuple: (blockNumber, chainId, patternHash, result)
+    let journal_data: (u64, u64, [u8; 32], bool) = match receipt.journal.decode() {
         Ok(v) => v,
         Err(e) => {
             output::error(&format!("Failed to decode receipt journal: {}", e));
@@ -107,6 +119,10 @@ pub async fn run(args: ZkProveArgs) -> i32 {
         }
     };
     let valid = journal_data.3;
+    if journal_data.2 != pattern.canonical_hash() {
+        output::error("Receipt journal commits to a different pattern");
+        return 1;
+    }
 
     // 4. Save artifacts
     let receipt_path = "proof.bin";
@@ -124,6 +140,7 @@ pub async fn run(args: ZkProveArgs) -> i32 {
     let result = ZkProofOutput {
         success: true,
         pattern: args.pattern.clone(),
+        pattern_hash: format!("0x{}", hex::encode(journal_data.2)),
         block: args.block,
         chain: args.chain.clone(),
         valid,
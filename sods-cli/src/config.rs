@@ -8,6 +8,28 @@ pub struct ChainConfig {
     pub rpc_urls: &'static [&'static str],
     pub default_ws: Option<&'static str>,
     pub description: &'static str,
+    /// Block explorer base URL (no trailing slash)
+    pub explorer_url: Option<&'static str>,
+}
+
+impl ChainConfig {
+    /// Explorer URL for a block, if the chain has an explorer.
+    pub fn block_url(&self, block_number: u64) -> Option<String> {
+        self.explorer_url
+            .map(|base| format!("{}/block/{}", base, block_number))
+    }
+
+    /// Explorer URL for a transaction hash.
+    pub fn tx_url(&self, tx_hash: &str) -> Option<String> {
+        self.explorer_url
+            .map(|base| format!("{}/tx/{}", base, tx_hash))
+    }
+
+    /// Explorer URL for an account or contract address.
+    pub fn address_url(&self, address: &str) -> Option<String> {
+        self.explorer_url
+            .map(|base| format!("{}/address/{}", base, address))
+    }
 }
 
 /// All supported chains.
@@ -22,6 +44,7 @@ pub const CHAINS: &[ChainConfig] = &[
         ],
         default_ws: Some("wss://ethereum-sepolia-rpc.publicnode.com"),
         description: "Ethereum Sepolia Testnet",
+        explorer_url: Some("https://sepolia.etherscan.io"),
     },
     ChainConfig {
         name: "ethereum",
@@ -33,6 +56,7 @@ pub const CHAINS: &[ChainConfig] = &[
         ],
         default_ws: Some("wss://ethereum-rpc.publicnode.com"),
         description: "Ethereum Mainnet",
+        explorer_url: Some("https://etherscan.io"),
     },
     ChainConfig {
         name: "base",
@@ -44,6 +68,7 @@ pub const CHAINS: &[ChainConfig] = &[
         ],
         default_ws: Some("wss://base-rpc.publicnode.com"),
         description: "Base Mainnet (Coinbase L2)",
+        explorer_url: Some("https://basescan.org"),
     },
     ChainConfig {
         name: "arbitrum",
@@ -55,6 +80,7 @@ pub const CHAINS: &[ChainConfig] = &[
         ],
         default_ws: Some("wss://arbitrum-one-rpc.publicnode.com"),
         description: "Arbitrum One",
+        explorer_url: Some("https://arbiscan.io"),
     },
     ChainConfig {
         name: "optimism",
@@ -66,6 +92,7 @@ pub const CHAINS: &[ChainConfig] = &[
         ],
         default_ws: None,
         description: "Optimism Mainnet",
+        explorer_url: Some("https://optimistic.etherscan.io"),
     },
     ChainConfig {
         name: "polygon-zkevm",
@@ -77,6 +104,7 @@ pub const CHAINS: &[ChainConfig] = &[
         ],
         default_ws: None,
         description: "Polygon zkEVM",
+        explorer_url: Some("https://zkevm.polygonscan.com"),
     },
     ChainConfig {
         name: "scroll",
@@ -88,6 +116,7 @@ pub const CHAINS: &[ChainConfig] = &[
         ],
         default_ws: None,
         description: "Scroll zkEVM",
+        explorer_url: Some("https://scrollscan.com"),
     },
 ];
 
@@ -98,10 +127,10 @@ pub fn get_chain(name: &str) -> Option<&'static ChainConfig> {
 
 /// Supported behavioral symbols.
 pub const SYMBOLS: &[(&str, &str)] = &[
-    ("Tf", "ERC20 Transfer"),
+    ("Tf", "ERC20/ERC721/ERC1155 Transfer"),
     ("Dep", "WETH Deposit"),
     ("Wdw", "WETH Withdrawal"),
-    ("Sw", "Uniswap V2/V3 Swap"),
+    ("Sw", "Uniswap V2/V3, Curve or Balancer Swap"),
     ("LP+", "Uniswap V2 Mint (Add Liquidity)"),
     ("LP-", "Uniswap V2 Burn (Remove Liquidity)"),
     ("MintNFT", "ERC721/ERC1155 Mint (Transfer from 0x0)"),
@@ -112,12 +141,22 @@ pub const SYMBOLS: &[(&str, &str)] = &[
         "L1→L2 Bridge Deposit (Optimism DepositFinalized)",
     ),
     ("BridgeOut", "L2→L1 Bridge Withdrawal (Arbitrum/Scroll)"),
+    (
+        "Stake",
+        "ETH Staking Deposit (Lido Submitted, Rocket Pool DepositReceived)",
+    ),
+    (
+        "Unstake",
+        "ETH Staking Exit (Lido WithdrawalClaimed, Rocket Pool rETH burn)",
+    ),
     ("Frontrun", "MEV Frontrun Pattern (Tf → Sw)"),
     ("Backrun", "MEV Backrun Pattern (Sw → Tf)"),
     ("Sandwich", "MEV Sandwich Pattern (Tf → Sw → Tf)"),
     ("AAOp", "ERC-4337 UserOperation execution"),
     ("Permit2", "Permit2 gasless approval"),
     ("CoWTrade", "CoW Swap intent fulfillment"),
+    ("BlobTx", "EIP-4844 blob transaction (rollup batch posting)"),
+    ("ClWdw", "Consensus-layer validator withdrawal (EIP-4895)"),
 ];
 
 /// Check if a symbol is supported.
@@ -125,13 +164,26 @@ pub fn is_symbol_supported(symbol: &str) -> bool {
     SYMBOLS.iter().any(|(s, _)| *s == symbol)
 }
 
+use crate::auth::{ApiAuth, ApiKeyConfig};
+use crate::retention::PolicyOverride;
 use serde::Deserialize;
-use std::collections::HashMap;
+use sods_core::aliases::SymbolAliases;
+use sods_core::PatternPresets;
+use sods_p2p::{ConnectionCaps, NetworkConfig, P2pMetrics};
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::path::PathBuf;
 
 /// User-defined configuration structure mapped from ~/.sods/config.toml
 #[derive(Debug, Deserialize, Default)]
 pub struct UserConfig {
     pub rpc_overrides: Option<HashMap<String, String>>,
+    /// `[symbols.aliases]` and `[symbols.labels.<SYMBOL>]` tables
+    #[serde(default)]
+    pub symbols: SymbolAliases,
+    /// `[retention.<store>]` tables overriding how long stores are kept
+    #[serde(default)]
+    pub retention: BTreeMap<String, PolicyOverride>,
 }
 
 impl UserConfig {
@@ -157,4 +209,185 @@ impl UserConfig {
             .get(&chain_name.to_lowercase())
             .cloned()
     }
+
+    /// Symbol aliases and labels, or an empty table if they are invalid.
+    pub fn symbol_aliases(&self) -> SymbolAliases {
+        let canonical = SYMBOLS.iter().map(|(symbol, _)| *symbol);
+        match self.symbols.validate_with_symbols(canonical) {
+            Ok(()) => self.symbols.clone(),
+            Err(e) => {
+                // stderr, so --json output stays parseable
+                eprintln!("Ignoring [symbols] in config.toml: {}", e);
+                SymbolAliases::default()
+            }
+        }
+    }
+}
+
+/// Load the user's symbol aliases from ~/.sods/config.toml.
+pub fn load_symbol_aliases() -> SymbolAliases {
+    UserConfig::load().symbol_aliases()
+}
+
+/// Load the user's pattern presets from ~/.sods/patterns.toml, or none if
+/// the file is invalid.
+pub fn load_pattern_presets() -> PatternPresets {
+    PatternPresets::load_local().unwrap_or_else(|e| {
+        // stderr, so --json output stays parseable
+        eprintln!("Ignoring patterns.toml: {}", e);
+        PatternPresets::default()
+    })
+}
+
+/// Daemon server settings from ~/.sods/daemon.toml
+///
+/// Unlike `UserConfig`, a file that fails to parse is an error rather than
+/// ignored: falling back to defaults would drop the API keys.
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DaemonConfig {
+    /// Address the HTTP and WebSocket servers bind to
+    pub bind: Option<IpAddr>,
+    /// `[[api_keys]]` entries
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    /// `[p2p]` connection limits and metrics
+    #[serde(default)]
+    pub p2p: P2pSettings,
+}
+
+/// The `[p2p]` table of ~/.sods/daemon.toml.
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct P2pSettings {
+    /// Most established connections in total
+    pub max_peers: Option<u32>,
+    /// Most outbound dials in progress at once
+    pub max_pending_dials: Option<u32>,
+    /// Most inbound connections from one IP address
+    pub max_connections_per_ip: Option<u32>,
+    /// Record libp2p metrics and serve them on the metrics port
+    #[serde(default)]
+    pub metrics: bool,
+}
+
+impl P2pSettings {
+    /// Applies the limits and metrics to `config`.
+    pub fn apply(&self, mut config: NetworkConfig) -> NetworkConfig {
+        config = config.with_connection_caps(ConnectionCaps {
+            max_peers: self.max_peers,
+            max_pending_dials: self.max_pending_dials,
+            max_per_ip: self.max_connections_per_ip,
+        });
+        if self.metrics {
+            config = config.with_metrics(P2pMetrics::new());
+        }
+        config
+    }
+}
+
+impl DaemonConfig {
+    pub fn path() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".sods").join("daemon.toml"))
+    }
+
+    /// Load ~/.sods/daemon.toml; a missing file means the defaults.
+    pub fn load() -> Result<Self, String> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(c) => c,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+        };
+        toml::from_str(&contents).map_err(|e| format!("Invalid {}: {}", path.display(), e))
+    }
+
+    /// The API keys and bind address, validated.
+    pub fn api_auth(&self) -> Result<ApiAuth, String> {
+        ApiAuth::new(self.api_keys.clone(), self.bind)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explorer_links() {
+        let base = get_chain("base").unwrap();
+        assert_eq!(
+            base.block_url(100).as_deref(),
+            Some("https://basescan.org/block/100")
+        );
+        assert_eq!(
+            base.tx_url("0xabc").as_deref(),
+            Some("https://basescan.org/tx/0xabc")
+        );
+        assert_eq!(
+            base.address_url("0xdef").as_deref(),
+            Some("https://basescan.org/address/0xdef")
+        );
+        assert!(CHAINS.iter().all(|c| c.explorer_url.is_some()));
+    }
+
+    #[test]
+    fn test_symbol_tables_from_toml() {
+        let config: UserConfig = toml::from_str(
+            r#"
+            [symbols.aliases]
+            Swap = "Sw"
+            Deposit = "Dep"
+
+            [symbols.labels.Sw]
+            display_name = "Échange"
+            description = "Échange Uniswap"
+            "#,
+        )
+        .unwrap();
+        let aliases = config.symbol_aliases();
+        assert_eq!(aliases.resolve("Deposit"), "Dep");
+        assert_eq!(aliases.display_name("Sw"), "Échange");
+        assert!(is_symbol_supported(aliases.resolve("Swap")));
+
+        let bad: UserConfig = toml::from_str("[symbols.aliases]\n\"Add LP\" = \"LP+\"").unwrap();
+        assert!(bad.symbol_aliases().is_empty());
+
+        let empty: UserConfig = toml::from_str("").unwrap();
+        assert!(empty.symbols.is_empty());
+    }
+
+    #[test]
+    fn test_daemon_config_from_toml() {
+        let config: DaemonConfig = toml::from_str(
+            r#"
+            bind = "10.0.0.5"
+
+            [[api_keys]]
+            name = "grafana"
+            key = "0123456789abcdef0123"
+            scopes = ["read-metrics", "read-alerts"]
+            "#,
+        )
+        .unwrap();
+        let auth = config.api_auth().unwrap();
+        assert!(auth.is_enabled());
+        assert_eq!(auth.socket_addr(9090).to_string(), "10.0.0.5:9090");
+
+        assert!(toml::from_str::<DaemonConfig>(
+            "[[api_keys]]\nname = \"a\"\nkey = \"0123456789abcdef\"\nscopes = [\"write\"]"
+        )
+        .is_err());
+        assert!(toml::from_str::<DaemonConfig>("port = 1").is_err());
+
+        let config: DaemonConfig =
+            toml::from_str("[p2p]\nmax_peers = 200\nmax_connections_per_ip = 4\nmetrics = true")
+                .unwrap();
+        let network = config.p2p.apply(NetworkConfig::new());
+        assert_eq!(network.connection_caps.max_peers, Some(200));
+        assert_eq!(network.connection_caps.max_per_ip, Some(4));
+        assert!(network.metrics.is_some());
+        assert!(toml::from_str::<DaemonConfig>("[p2p]\nmax_conns = 1").is_err());
+    }
 }
@@ -0,0 +1,248 @@
+//! Control socket for a running daemon.
+//!
+//! `sods daemon start` listens on a Unix domain socket
+//! (`~/.sods/sods.sock`, readable by the owner only). A client writes one
+//! JSON request per connection on a single line and reads one JSON
+//! response back:
+//!
+//! ```text
+//! {"command":"remove-target","name":"Manual Pattern"}
+//! {"result":"done","data":{"message":"Removed 1 target(s) named 'Manual Pattern'"}}
+//! ```
+//!
+//! Requests are answered by the daemon loop itself, between block scans,
+//! so a response always reflects a consistent view of its targets.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::sync::{mpsc, oneshot};
+
+/// Longest request line the daemon reads.
+const MAX_REQUEST_BYTES: u64 = 64 * 1024;
+
+/// How long either side waits for the other.
+const IO_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A command sent to the daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum ControlRequest {
+    /// Targets, chain progress and peers
+    Status,
+    /// Start monitoring a pattern
+    AddTarget {
+        name: String,
+        pattern: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        severity: Option<String>,
+    },
+    /// Stop monitoring every target with this name
+    RemoveTarget { name: String },
+    /// Re-read pattern presets and re-check the targets against them
+    ReloadConfig,
+    /// Everything the daemon loop tracks, for debugging
+    DumpState,
+    /// The P2P node's signed reputation snapshot
+    ExportReputation,
+}
+
+/// The daemon's answer to a `ControlRequest`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "result", content = "data", rename_all = "snake_case")]
+pub enum ControlResponse {
+    Status(DaemonStatus),
+    State(serde_json::Value),
+    Done { message: String },
+    Error { message: String },
+}
+
+impl ControlResponse {
+    pub fn done(message: impl Into<String>) -> Self {
+        ControlResponse::Done {
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        ControlResponse::Error {
+            message: message.into(),
+        }
+    }
+}
+
+/// Runtime summary reported by `status`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub pid: u32,
+    pub chain: String,
+    pub uptime_secs: u64,
+    /// Latest chain head seen, if any yet
+    pub head: Option<u64>,
+    pub last_scanned_block: u64,
+    /// Blocks between the head and the last one scanned
+    pub lag: Option<u64>,
+    /// Connected P2P peers, if the daemon joined the network
+    pub peers: Option<usize>,
+    pub targets: Vec<TargetStatus>,
+}
+
+/// One monitored pattern.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TargetStatus {
+    pub name: String,
+    pub severity: String,
+    pub pattern: String,
+    /// Seconds until the target is dropped
+    pub expires_in_secs: u64,
+}
+
+/// A request waiting for the daemon loop, with the channel for its answer.
+#[derive(Debug)]
+pub struct ControlCall {
+    pub request: ControlRequest,
+    pub reply: oneshot::Sender<ControlResponse>,
+}
+
+/// Default socket location (`~/.sods/sods.sock`).
+pub fn default_path() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".sods").join("sods.sock")
+}
+
+/// Listens on `path` and forwards each request to the returned receiver.
+///
+/// A socket file left behind by an earlier run is replaced. Must be called
+/// from within a tokio runtime.
+#[cfg(unix)]
+pub fn serve(path: &std::path::Path) -> std::io::Result<mpsc::Receiver<ControlCall>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let listener = tokio::net::UnixListener::bind(path)?;
+    // Control requests change what is monitored, so only the owner may send them
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+
+    let (tx, rx) = mpsc::channel(16);
+    tokio::spawn(async move {
+        loop {
+            let stream = tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        eprintln!("Control socket error: {}", e);
+                        continue;
+                    }
+                },
+                _ = tx.closed() => return,
+            };
+            tokio::spawn(handle_connection(stream, tx.clone()));
+        }
+    });
+    Ok(rx)
+}
+
+#[cfg(unix)]
+async fn handle_connection(stream: tokio::net::UnixStream, calls: mpsc::Sender<ControlCall>) {
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    let (read, mut write) = stream.into_split();
+    let mut line = String::new();
+    let mut reader = BufReader::new(read.take(MAX_REQUEST_BYTES));
+    match tokio::time::timeout(IO_TIMEOUT, reader.read_line(&mut line)).await {
+        Ok(Ok(n)) if n > 0 => {}
+        _ => return,
+    }
+    let response = match serde_json::from_str::<ControlRequest>(&line) {
+        Ok(request) => {
+            let (reply, answer) = oneshot::channel();
+            if calls.send(ControlCall { request, reply }).await.is_err() {
+                ControlResponse::error("Daemon is shutting down")
+            } else {
+                answer
+                    .await
+                    .unwrap_or_else(|_| ControlResponse::error("Daemon dropped the request"))
+            }
+        }
+        Err(e) => ControlResponse::error(format!("Invalid request: {}", e)),
+    };
+    if let Ok(mut body) = serde_json::to_vec(&response) {
+        body.push(b'\n');
+        let _ = tokio::time::timeout(IO_TIMEOUT, write.write_all(&body)).await;
+    }
+}
+
+/// Sends `request` to the daemon listening on `path` and waits for its
+/// answer.
+#[cfg(unix)]
+pub fn send(path: &std::path::Path, request: &ControlRequest) -> std::io::Result<ControlResponse> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+    let mut body = serde_json::to_vec(request)?;
+    body.push(b'\n');
+    stream.write_all(&body)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    Ok(serde_json::from_str(&line)?)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_requests_reach_the_daemon_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sods.sock");
+        // A stale socket file does not stop the daemon from listening
+        std::fs::write(&path, b"").unwrap();
+        let mut calls = serve(&path).unwrap();
+        tokio::spawn(async move {
+            while let Some(call) = calls.recv().await {
+                let response = match call.request {
+                    ControlRequest::RemoveTarget { name } => {
+                        ControlResponse::done(format!("Removed {}", name))
+                    }
+                    _ => ControlResponse::error("unsupported"),
+                };
+                let _ = call.reply.send(response);
+            }
+        });
+
+        let send_blocking = |path: PathBuf, request: ControlRequest| {
+            tokio::task::spawn_blocking(move || send(&path, &request))
+        };
+        let response = send_blocking(
+            path.clone(),
+            ControlRequest::RemoveTarget {
+                name: "Manual Pattern".into(),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(response, ControlResponse::done("Removed Manual Pattern"));
+
+        let raw = tokio::task::spawn_blocking(move || {
+            use std::io::{Read, Write};
+            let mut stream = std::os::unix::net::UnixStream::connect(&path).unwrap();
+            stream.write_all(b"{\"command\":\"explode\"}\n").unwrap();
+            let mut out = String::new();
+            stream.read_to_string(&mut out).unwrap();
+            out
+        })
+        .await
+        .unwrap();
+        assert!(matches!(
+            serde_json::from_str(&raw).unwrap(),
+            ControlResponse::Error { .. }
+        ));
+    }
+}
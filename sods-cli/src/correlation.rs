@@ -0,0 +1,28 @@
+//! Per-alert correlation IDs.
+//!
+//! A detection gets one ID when it is raised, and the daemon and
+//! `sods monitor` carry it into every sink the detection reaches: logs,
+//! WebSocket and webhook alerts, SIEM events, digests, desktop
+//! notifications, exported proof bundles and the alert metric's exemplar.
+
+use uuid::Uuid;
+
+/// Correlation ID of one detection, e.g. `alert_19000000_1f3a9c0e`, where
+/// `kind` names the detector and `block` is where it fired.
+pub fn new_alert_id(kind: &str, block: u64) -> String {
+    let nonce = Uuid::new_v4().simple().to_string();
+    format!("{}_{}_{}", kind, block, nonce.get(..8).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alert_ids_are_unique_per_detection() {
+        let id = new_alert_id("drift", 19_000_000);
+        let nonce = id.strip_prefix("drift_19000000_").unwrap();
+        assert_eq!(nonce.len(), 8);
+        assert_ne!(id, new_alert_id("drift", 19_000_000));
+    }
+}
@@ -0,0 +1,445 @@
+//! W3C Verifiable Credential wrapper for exported proofs.
+//!
+//! `export-proof --format vc` wraps an `OnChainBehavioralProof` in a
+//! Verifiable Credential (VC Data Model 1.1) so wallets and agent
+//! marketplaces that already consume VCs can store and check SODS
+//! attestations:
+//!
+//! - `issuer` is the prover's key as a `did:pkh:eip155:<chain>:<address>`
+//! - `credentialSubject` is the behavioral claim, with the on-chain proof
+//!   embedded unchanged so it can still be submitted to `SODSVerifier`
+//! - `proof` is an `EthereumEip712Signature2021` by that key over the
+//!   credential
+//!
+//! The signed message is the credential without its `proof` and without
+//! `null` fields, as EIP-712 typed data whose types are generated from the
+//! document itself. The proof carries the domain and types, so any verifier
+//! of the suite can rebuild the typed data without knowing SODS.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use ethers_core::types::transaction::eip712::{
+    EIP712Domain, Eip712, Eip712DomainType, TypedData, Types,
+};
+use ethers_core::types::{Address, Signature, H256};
+use ethers_core::utils::to_checksum;
+use ethers_signers::LocalWallet;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sods_core::proof::OnChainBehavioralProof;
+use sods_core::SymbolProvenance;
+
+/// Base context of every Verifiable Credential.
+pub const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+
+/// Context of the `EthereumEip712Signature2021` proof suite.
+pub const EIP712_CONTEXT: &str = "https://w3id.org/security/suites/eip712sig-2021/v1";
+
+/// Credential type, next to `VerifiableCredential`.
+pub const CREDENTIAL_TYPE: &str = "BehavioralProofCredential";
+
+/// `proof.type`: EIP-712 typed data signature over the credential.
+pub const PROOF_TYPE: &str = "EthereumEip712Signature2021";
+
+/// EIP-712 primary type of the signed credential.
+pub const PRIMARY_TYPE: &str = "VerifiableCredential";
+
+/// EIP-712 domain name of SODS credentials.
+pub const DOMAIN_NAME: &str = "SODS Behavioral Credential";
+
+/// A behavioral proof wrapped as a Verifiable Credential.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BehavioralCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<Value>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub issuer: String,
+    pub issuance_date: String,
+    pub credential_subject: BehavioralClaim,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<CredentialProof>,
+}
+
+/// The claim being attested: `pattern` occurred in `block_number`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BehavioralClaim {
+    #[serde(rename = "type")]
+    pub claim_type: String,
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub pattern: String,
+    pub symbols: Vec<String>,
+    /// BMT root as 0x-prefixed hex
+    pub bmt_root: String,
+    /// Weakest provenance among the matched symbols, if recorded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<SymbolProvenance>,
+    pub behavioral_proof: OnChainBehavioralProof,
+}
+
+/// Signature binding the credential to its issuer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialProof {
+    #[serde(rename = "type")]
+    pub proof_type: String,
+    pub created: String,
+    pub proof_purpose: String,
+    pub verification_method: String,
+    /// 65-byte `r || s || v` signature as 0x-prefixed hex
+    pub proof_value: String,
+    pub eip712: Eip712Params,
+}
+
+/// The typed data parameters the credential was signed with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Eip712Params {
+    pub domain: EIP712Domain,
+    pub types: Types,
+    pub primary_type: String,
+}
+
+/// `did:pkh` identifier of an EVM account (CAIP-10).
+pub fn did_pkh(chain_id: u64, address: Address) -> String {
+    format!(
+        "did:pkh:eip155:{}:{}",
+        chain_id,
+        to_checksum(&address, None)
+    )
+}
+
+impl BehavioralCredential {
+    /// Wrap `proof` as an unsigned credential issued by `issuer`.
+    pub fn new(
+        pattern: impl Into<String>,
+        proof: OnChainBehavioralProof,
+        issuer: Address,
+        issued_at: DateTime<Utc>,
+    ) -> Self {
+        let claim = BehavioralClaim {
+            claim_type: "BehavioralClaim".to_string(),
+            chain_id: proof.chain_id,
+            block_number: proof.block_number,
+            pattern: pattern.into(),
+            symbols: proof.symbols.clone(),
+            bmt_root: format!("0x{}", hex::encode(proof.bmt_root)),
+            provenance: proof.weakest_provenance(),
+            behavioral_proof: proof,
+        };
+        Self {
+            context: vec![
+                Value::String(CREDENTIALS_CONTEXT.to_string()),
+                Value::String(EIP712_CONTEXT.to_string()),
+            ],
+            id: format!("urn:uuid:{}", uuid::Uuid::new_v4()),
+            types: vec![
+                "VerifiableCredential".to_string(),
+                CREDENTIAL_TYPE.to_string(),
+            ],
+            issuer: did_pkh(claim.chain_id, issuer),
+            issuance_date: issued_at.to_rfc3339_opts(SecondsFormat::Secs, true),
+            credential_subject: claim,
+            proof: None,
+        }
+    }
+
+    /// EIP-712 parameters for signing this credential.
+    pub fn eip712_params(&self) -> Result<Eip712Params, String> {
+        let (_, types) = self.typed_message()?;
+        Ok(Eip712Params {
+            domain: EIP712Domain {
+                name: Some(DOMAIN_NAME.to_string()),
+                version: Some("1".to_string()),
+                chain_id: Some(self.credential_subject.chain_id.into()),
+                ..Default::default()
+            },
+            types,
+            primary_type: PRIMARY_TYPE.to_string(),
+        })
+    }
+
+    /// Digest covered by the signature: the credential without `proof`,
+    /// hashed as EIP-712 typed data under `params`.
+    pub fn signing_digest(&self, params: &Eip712Params) -> Result<[u8; 32], String> {
+        let (message, _) = self.typed_message()?;
+        let typed = TypedData {
+            domain: params.domain.clone(),
+            types: params.types.clone(),
+            primary_type: params.primary_type.clone(),
+            message: message.into_iter().collect(),
+        };
+        typed.encode_eip712().map_err(|e| e.to_string())
+    }
+
+    /// The signed message and its generated types.
+    fn typed_message(&self) -> Result<(Map<String, Value>, Types), String> {
+        let mut unsigned = self.clone();
+        unsigned.proof = None;
+        let message =
+            match without_nulls(serde_json::to_value(&unsigned).map_err(|e| e.to_string())?) {
+                Value::Object(message) => message,
+                _ => return Err("credential is not a JSON object".to_string()),
+            };
+        let mut types = Types::new();
+        types.insert(
+            "EIP712Domain".to_string(),
+            [
+                ("name", "string"),
+                ("version", "string"),
+                ("chainId", "uint256"),
+            ]
+            .into_iter()
+            .map(|(name, ty)| field(name, ty))
+            .collect(),
+        );
+        struct_types(PRIMARY_TYPE, &message, &mut types)?;
+        Ok((message, types))
+    }
+
+    /// Sign the credential with the issuer's key (Builder pattern).
+    ///
+    /// Fails if `wallet` is not the key named in `issuer`.
+    pub fn sign(mut self, wallet: &LocalWallet, created: DateTime<Utc>) -> Result<Self, String> {
+        use ethers_signers::Signer;
+
+        if did_pkh(self.credential_subject.chain_id, wallet.address()) != self.issuer {
+            return Err(format!(
+                "signing key {:?} is not the issuer {}",
+                wallet.address(),
+                self.issuer
+            ));
+        }
+        let eip712 = self.eip712_params()?;
+        let signature = wallet
+            .sign_hash(H256::from(self.signing_digest(&eip712)?))
+            .map_err(|e| e.to_string())?;
+        self.proof = Some(CredentialProof {
+            proof_type: PROOF_TYPE.to_string(),
+            created: created.to_rfc3339_opts(SecondsFormat::Secs, true),
+            proof_purpose: "assertionMethod".to_string(),
+            verification_method: format!("{}#blockchainAccountId", self.issuer),
+            proof_value: format!("0x{}", hex::encode(signature.to_vec())),
+            eip712,
+        });
+        Ok(self)
+    }
+
+    /// Check the signature and return the issuer's address.
+    ///
+    /// This only authenticates the credential; the embedded proof still
+    /// has to be checked against the BMT root or on-chain.
+    pub fn verify(&self) -> Result<Address, String> {
+        let proof = self.proof.as_ref().ok_or("credential is not signed")?;
+        if proof.proof_type != PROOF_TYPE {
+            return Err(format!("unsupported proof type '{}'", proof.proof_type));
+        }
+        // Types that leave a field out would leave it unsigned
+        if proof.eip712 != self.eip712_params()? {
+            return Err("proof.eip712 does not describe this credential".to_string());
+        }
+        let bytes = hex::decode(proof.proof_value.trim_start_matches("0x"))
+            .map_err(|e| format!("invalid proofValue: {}", e))?;
+        let signature = Signature::try_from(bytes.as_slice())
+            .map_err(|e| format!("invalid proofValue: {}", e))?;
+        let signer = signature
+            .recover(H256::from(self.signing_digest(&proof.eip712)?))
+            .map_err(|e| e.to_string())?;
+        if did_pkh(self.credential_subject.chain_id, signer) != self.issuer {
+            return Err(format!(
+                "signed by {:?}, not the issuer {}",
+                signer, self.issuer
+            ));
+        }
+        Ok(signer)
+    }
+}
+
+/// `value` without `null` object fields, which EIP-712 cannot encode.
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, value)| !value.is_null())
+                .map(|(key, value)| (key, without_nulls(value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(without_nulls).collect()),
+        other => other,
+    }
+}
+
+fn field(name: &str, ty: &str) -> Eip712DomainType {
+    Eip712DomainType {
+        name: name.to_string(),
+        r#type: ty.to_string(),
+    }
+}
+
+/// Add the EIP-712 struct type `name` of `object` to `types`, with its
+/// fields sorted by name, as `EthereumEip712Signature2021` generates them.
+fn struct_types(name: &str, object: &Map<String, Value>, types: &mut Types) -> Result<(), String> {
+    let mut fields = object
+        .iter()
+        .map(|(key, value)| Ok(field(key, &value_type(key, value, types)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    fields.sort_by(|a, b| a.name.cmp(&b.name));
+    match types.get(name) {
+        Some(existing) if *existing != fields => {
+            Err(format!("'{}' objects differ in their fields", name))
+        }
+        _ => {
+            types.insert(name.to_string(), fields);
+            Ok(())
+        }
+    }
+}
+
+/// EIP-712 type of the field `key`: objects become structs named after
+/// the key (`credentialSubject` as `CredentialSubject`), and arrays take
+/// the type of their elements.
+fn value_type(key: &str, value: &Value, types: &mut Types) -> Result<String, String> {
+    Ok(match value {
+        Value::String(_) => "string".to_string(),
+        Value::Bool(_) => "bool".to_string(),
+        Value::Number(n) if n.is_u64() => "uint256".to_string(),
+        Value::Array(items) => {
+            let mut element: Option<String> = None;
+            for item in items {
+                let ty = value_type(key, item, types)?;
+                if *element.get_or_insert_with(|| ty.clone()) != ty {
+                    return Err(format!("'{}' mixes element types", key));
+                }
+            }
+            format!("{}[]", element.unwrap_or_else(|| "string".to_string()))
+        }
+        Value::Object(object) => {
+            let mut chars = key.trim_start_matches('@').chars();
+            let name: String = chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+                .collect();
+            struct_types(&name, object, types)?;
+            name
+        }
+        _ => return Err(format!("'{}' has no EIP-712 type", key)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sods_core::{BehavioralMerkleTree, BehavioralSymbol};
+
+    fn sample_proof() -> OnChainBehavioralProof {
+        let symbols = vec![
+            BehavioralSymbol::new("Tf", 0).with_provenance(SymbolProvenance::ReceiptLog),
+            BehavioralSymbol::new("Sw", 1),
+        ];
+        let bmt = BehavioralMerkleTree::new(symbols.clone());
+        bmt.generate_onchain_proof(&[&symbols[0], &symbols[1]], 1, 100, None, 0)
+            .unwrap()
+    }
+
+    fn wallet() -> LocalWallet {
+        "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sign_and_verify_roundtrip() {
+        use ethers_signers::Signer;
+
+        let wallet = wallet();
+        let now = Utc::now();
+        let credential =
+            BehavioralCredential::new("Tf -> Sw", sample_proof(), wallet.address(), now)
+                .sign(&wallet, now)
+                .unwrap();
+
+        assert_eq!(credential.issuer, did_pkh(1, wallet.address()));
+        assert_eq!(
+            credential.credential_subject.provenance,
+            Some(SymbolProvenance::RpcLog)
+        );
+        assert_eq!(credential.verify().unwrap(), wallet.address());
+
+        // Survives a JSON roundtrip, whatever the key order
+        let json = serde_json::to_string(&credential).unwrap();
+        let parsed: BehavioralCredential = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.verify().unwrap(), wallet.address());
+        assert!(json.contains("\"@context\""));
+        assert!(json.contains("\"credentialSubject\""));
+
+        // Any change to the claim breaks the signature
+        let mut tampered = parsed.clone();
+        tampered.credential_subject.block_number += 1;
+        assert!(tampered.verify().is_err());
+
+        // So do types that leave a field unsigned
+        let mut narrowed = parsed;
+        let proof = narrowed.proof.as_mut().unwrap();
+        proof
+            .eip712
+            .types
+            .get_mut("CredentialSubject")
+            .unwrap()
+            .retain(|f| f.name != "blockNumber");
+        assert!(narrowed.verify().is_err());
+    }
+
+    #[test]
+    fn test_proof_is_standard_typed_data() {
+        use ethers_signers::Signer;
+
+        let wallet = wallet();
+        let now = Utc::now();
+        let credential =
+            BehavioralCredential::new("Tf -> Sw", sample_proof(), wallet.address(), now)
+                .sign(&wallet, now)
+                .unwrap();
+        let document = serde_json::to_value(&credential).unwrap();
+        let proof = &document["proof"];
+        assert_eq!(proof["type"], PROOF_TYPE);
+
+        // A generic verifier rebuilds the typed data from the proof and
+        // the document without its proof
+        let mut message = without_nulls(document.clone());
+        message.as_object_mut().unwrap().remove("proof");
+        let typed: TypedData = serde_json::from_value(serde_json::json!({
+            "domain": proof["eip712"]["domain"],
+            "types": proof["eip712"]["types"],
+            "primaryType": proof["eip712"]["primaryType"],
+            "message": message,
+        }))
+        .unwrap();
+        let bytes = hex::decode(
+            proof["proofValue"]
+                .as_str()
+                .unwrap()
+                .trim_start_matches("0x"),
+        )
+        .unwrap();
+        let signature = Signature::try_from(bytes.as_slice()).unwrap();
+        let digest = H256::from(typed.encode_eip712().unwrap());
+        assert_eq!(
+            signature.recover(digest).unwrap(),
+            credential.verify().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sign_rejects_foreign_key() {
+        let now = Utc::now();
+        let credential =
+            BehavioralCredential::new("Tf -> Sw", sample_proof(), Address::repeat_byte(0x11), now);
+        assert!(credential.clone().sign(&wallet(), now).is_err());
+        assert!(credential.verify().is_err());
+    }
+}
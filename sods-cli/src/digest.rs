@@ -0,0 +1,249 @@
+//! Periodic alert digests for the daemon.
+//!
+//! On a busy chain a low-severity rule can fire every block. With a digest
+//! configured, alerts below the pass-through severity are counted per chain
+//! and threat instead of delivered one by one, and a single summary goes out
+//! when the window closes. Every window that had to summarize something
+//! doubles the next one (up to a cap); a quiet window resets it.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Most alert IDs a digest entry lists; `count` still counts every alert.
+pub const MAX_DIGEST_ALERT_IDS: usize = 20;
+
+/// Orders the free-form severity labels used by threat rules and feeds.
+///
+/// Unknown labels rank as `medium`.
+pub fn severity_rank(severity: &str) -> u8 {
+    match severity.to_ascii_lowercase().as_str() {
+        "info" => 0,
+        "low" => 1,
+        "high" => 3,
+        "critical" => 4,
+        _ => 2,
+    }
+}
+
+/// The label one rank above `severity`; `critical` stays `critical`.
+pub fn raise_severity(severity: &str) -> &'static str {
+    match severity_rank(severity) {
+        0 => "low",
+        1 => "medium",
+        2 => "high",
+        _ => "critical",
+    }
+}
+
+/// Alerts of one threat on one chain within a digest window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DigestEntry {
+    pub chain: String,
+    pub threat_name: String,
+    pub severity: String,
+    pub count: u64,
+    pub first_block: u64,
+    pub last_block: u64,
+    /// IDs of the first alerts summarized, to trace them in other sinks
+    pub alert_ids: Vec<String>,
+}
+
+/// A closed digest window.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DigestSummary {
+    /// Unix timestamp the window opened
+    pub window_start: u64,
+    /// Unix timestamp the window closed
+    pub window_end: u64,
+    /// Alerts summarized
+    pub total: u64,
+    /// Length of the next window in seconds
+    pub next_interval_secs: u64,
+    pub entries: Vec<DigestEntry>,
+}
+
+impl DigestSummary {
+    /// Webhook payload for this digest.
+    pub fn to_payload(&self) -> serde_json::Value {
+        serde_json::json!({
+            "alert": "Behavioral alert digest",
+            "type": "alert_digest",
+            "window_start": self.window_start,
+            "window_end": self.window_end,
+            "total": self.total,
+            "next_interval_secs": self.next_interval_secs,
+            "counts": self.entries,
+            "source": "daemon"
+        })
+    }
+
+    /// One-line text for desktop notifications.
+    pub fn headline(&self) -> String {
+        format!(
+            "{} alerts from {} threats in the last {} min",
+            self.total,
+            self.entries.len(),
+            (self.window_end - self.window_start) / 60
+        )
+    }
+}
+
+/// Batches low-severity alerts into exponentially spaced summaries.
+#[derive(Debug, Clone)]
+pub struct AlertDigest {
+    base_interval: Duration,
+    max_interval: Duration,
+    interval: Duration,
+    passthrough: u8,
+    window_start: u64,
+    entries: BTreeMap<(String, String), DigestEntry>,
+}
+
+impl AlertDigest {
+    /// Creates a digest flushing every `interval`; alerts at or above
+    /// `passthrough` severity are never batched.
+    pub fn new(interval: Duration, passthrough: &str, now: u64) -> Self {
+        Self {
+            base_interval: interval,
+            max_interval: interval,
+            interval,
+            passthrough: severity_rank(passthrough),
+            window_start: now,
+            entries: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the cap for the doubling window length (Builder pattern).
+    pub fn with_max_interval(mut self, max: Duration) -> Self {
+        self.max_interval = max.max(self.base_interval);
+        self
+    }
+
+    /// Whether an alert of `severity` bypasses the digest.
+    pub fn is_immediate(&self, severity: &str) -> bool {
+        severity_rank(severity) >= self.passthrough
+    }
+
+    /// Length of the current window.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Counts one alert towards the current window.
+    pub fn add(
+        &mut self,
+        chain: &str,
+        threat_name: &str,
+        severity: &str,
+        block: u64,
+        alert_id: &str,
+    ) {
+        let entry = self
+            .entries
+            .entry((chain.to_string(), threat_name.to_string()))
+            .and_modify(|e| {
+                e.count += 1;
+                e.first_block = e.first_block.min(block);
+                e.last_block = e.last_block.max(block);
+            })
+            .or_insert_with(|| DigestEntry {
+                chain: chain.to_string(),
+                threat_name: threat_name.to_string(),
+                severity: severity.to_string(),
+                count: 1,
+                first_block: block,
+                last_block: block,
+                alert_ids: Vec::new(),
+            });
+        if entry.alert_ids.len() < MAX_DIGEST_ALERT_IDS {
+            entry.alert_ids.push(alert_id.to_string());
+        }
+    }
+
+    /// Closes the window if it has elapsed at `now`.
+    ///
+    /// Returns the summary when the window held alerts; an empty window
+    /// yields `None` and resets the interval to its base length.
+    pub fn flush(&mut self, now: u64) -> Option<DigestSummary> {
+        if now < self.window_start + self.interval.as_secs() {
+            return None;
+        }
+
+        let window_start = std::mem::replace(&mut self.window_start, now);
+        if self.entries.is_empty() {
+            self.interval = self.base_interval;
+            return None;
+        }
+
+        self.interval = (self.interval * 2).min(self.max_interval);
+        let mut entries: Vec<DigestEntry> =
+            std::mem::take(&mut self.entries).into_values().collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.count));
+
+        Some(DigestSummary {
+            window_start,
+            window_end: now,
+            total: entries.iter().map(|e| e.count).sum(),
+            next_interval_secs: self.interval.as_secs(),
+            entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[test]
+    fn test_passthrough_threshold() {
+        let digest = AlertDigest::new(HOUR, "high", 0);
+        assert!(digest.is_immediate("critical"));
+        assert!(digest.is_immediate("HIGH"));
+        assert!(!digest.is_immediate("low"));
+        assert!(!digest.is_immediate("manual"));
+
+        assert_eq!(raise_severity("info"), "low");
+        assert_eq!(raise_severity("manual"), "high");
+        assert_eq!(raise_severity("Critical"), "critical");
+    }
+
+    #[test]
+    fn test_flush_groups_and_backs_off() {
+        let mut digest = AlertDigest::new(HOUR, "critical", 1000).with_max_interval(3 * HOUR);
+        digest.add("base", "Drainer", "low", 12, "alert_12_a");
+        digest.add("base", "Drainer", "low", 10, "alert_10_b");
+        digest.add("base", "Spam", "info", 11, "alert_11_c");
+
+        assert!(digest.flush(1000 + 3599).is_none());
+
+        let summary = digest.flush(1000 + 3600).unwrap();
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.entries[0].threat_name, "Drainer");
+        assert_eq!(summary.entries[0].count, 2);
+        assert_eq!(
+            (
+                summary.entries[0].first_block,
+                summary.entries[0].last_block
+            ),
+            (10, 12)
+        );
+        assert_eq!(summary.entries[0].alert_ids, ["alert_12_a", "alert_10_b"]);
+        assert_eq!(summary.next_interval_secs, 7200);
+
+        // Sustained noise keeps doubling up to the cap
+        for i in 0..MAX_DIGEST_ALERT_IDS + 5 {
+            digest.add("base", "Spam", "info", 20, &format!("alert_20_{}", i));
+        }
+        let summary = digest.flush(4600 + 7200).unwrap();
+        assert_eq!(summary.next_interval_secs, 3 * 3600);
+        assert_eq!(summary.total, MAX_DIGEST_ALERT_IDS as u64 + 5);
+        assert_eq!(summary.entries[0].alert_ids.len(), MAX_DIGEST_ALERT_IDS);
+
+        // A quiet window resets to the base interval
+        assert!(digest.flush(11800 + 3 * 3600).is_none());
+        assert_eq!(digest.interval(), HOUR);
+    }
+}
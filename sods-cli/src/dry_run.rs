@@ -0,0 +1,145 @@
+//! Dry-run sink for alert delivery.
+//!
+//! With `--dry-run`, `daemon` and `monitor` run their full detection
+//! pipeline but hand every outgoing alert to a `DryRunSink` instead of a
+//! webhook, desktop notification or WebSocket client. Each alert is printed
+//! and appended as one JSON line to a local log, so a configuration change
+//! can be checked against production traffic without paging anyone.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Delivery channel an alert would have used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertChannel {
+    Webhook,
+    Notification,
+    Websocket,
+    ProtectiveAction,
+    Siem,
+}
+
+impl std::fmt::Display for AlertChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            AlertChannel::Webhook => "webhook",
+            AlertChannel::Notification => "notification",
+            AlertChannel::Websocket => "websocket",
+            AlertChannel::ProtectiveAction => "protective_action",
+            AlertChannel::Siem => "siem",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One suppressed delivery.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DryRunRecord {
+    pub timestamp: String,
+    pub channel: AlertChannel,
+    /// Destination (e.g. webhook URL), if the channel has one
+    pub target: Option<String>,
+    pub payload: serde_json::Value,
+}
+
+/// Append-only JSON-lines log of alerts that were not sent.
+#[derive(Debug, Clone)]
+pub struct DryRunSink {
+    path: PathBuf,
+}
+
+impl DryRunSink {
+    /// Default log location (`~/.sods/dry-run.jsonl`).
+    pub fn default_path() -> PathBuf {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        home.join(".sods").join("dry-run.jsonl")
+    }
+
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Prints and logs what would have been delivered on `channel`.
+    pub fn record(
+        &self,
+        channel: AlertChannel,
+        target: Option<&str>,
+        payload: serde_json::Value,
+    ) -> std::io::Result<()> {
+        println!(
+            "[dry-run] would send {} to {}: {}",
+            channel,
+            target.unwrap_or("-"),
+            payload
+        );
+
+        let record = DryRunRecord {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            channel,
+            target: target.map(str::to_string),
+            payload,
+        };
+        let mut line = serde_json::to_string(&record)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        line.push('\n');
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // A single write per record keeps lines intact across concurrent
+        // tasks, and the lock keeps `sods gc` from compacting meanwhile
+        let _lock = crate::retention::lock_store(&self.path)?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+
+    /// All records in the log, oldest first. Malformed lines are skipped.
+    pub fn records(&self) -> Vec<DryRunRecord> {
+        fs::read_to_string(&self.path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = DryRunSink::new(dir.path().join("nested").join("dry-run.jsonl"));
+
+        sink.record(
+            AlertChannel::Webhook,
+            Some("https://ntfy.sh/sods"),
+            serde_json::json!({ "block_number": 7 }),
+        )
+        .unwrap();
+        sink.record(
+            AlertChannel::Notification,
+            None,
+            serde_json::json!({ "body": "alert" }),
+        )
+        .unwrap();
+
+        let records = sink.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].channel, AlertChannel::Webhook);
+        assert_eq!(records[0].target.as_deref(), Some("https://ntfy.sh/sods"));
+        assert_eq!(records[0].payload["block_number"], 7);
+        assert_eq!(records[1].target, None);
+    }
+}
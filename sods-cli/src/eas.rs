@@ -0,0 +1,249 @@
+//! Ethereum Attestation Service export for verified proofs.
+//!
+//! `export-proof --format eas` turns a verified `OnChainBehavioralProof`
+//! into an EAS `AttestationRequest` under the [`SCHEMA`] below, so SODS
+//! findings can be indexed and consumed like any other EAS attestation.
+//! `--submit-eas` sends it to the chain's EAS contract with the signing
+//! key as attester.
+//!
+//! The schema is registered without a resolver and as revocable; its UID
+//! is `keccak256(abi.encodePacked(schema, resolver, revocable))`, exactly
+//! as `SchemaRegistry` derives it, so it can be computed offline.
+
+use ethers::middleware::SignerMiddleware;
+use ethers::providers::{Http, Middleware, Provider};
+use ethers::types::TransactionRequest;
+use ethers_core::abi::{self, Token};
+use ethers_core::types::{Address, H256, U256};
+use ethers_core::utils::{keccak256, to_checksum};
+use ethers_signers::{LocalWallet, Signer};
+use serde_json::{json, Value};
+use sods_core::proof::OnChainBehavioralProof;
+
+/// EAS schema of a SODS behavioral attestation.
+pub const SCHEMA: &str = "uint256 chainId,uint64 blockNumber,bytes32 patternHash,string pattern,string[] symbols,bytes32 bmtRoot,bytes32 receiptsRoot,bytes32 beaconRoot";
+
+/// `EAS.attest(AttestationRequest)`.
+const ATTEST_SIGNATURE: &str = "attest((bytes32,(address,uint64,bool,bytes32,bytes,uint256)))";
+
+/// `Attested(recipient, attester, uid, schemaUID)`, emitted by `attest`.
+const ATTESTED_EVENT: &str = "Attested(address,address,bytes32,bytes32)";
+
+/// EAS contract deployed on `chain_id`, for the chains SODS ships with.
+pub fn contract_address(chain_id: u64) -> Option<Address> {
+    let address = match chain_id {
+        1 => "0xA1207F3BBa224E2c9c3c6D5aF63D0eb1582Ce587",
+        11155111 => "0xC2679fBD37d54388Ce493F1DB75320D236e1815e",
+        10 | 8453 => "0x4200000000000000000000000000000000000021",
+        42161 => "0xbD75f629A22Dc1ceD33dDA0b68c546A1c035c458",
+        _ => return None,
+    };
+    address.parse().ok()
+}
+
+/// UID `SchemaRegistry` assigns to `schema` registered with `resolver`.
+pub fn schema_uid(schema: &str, resolver: Address, revocable: bool) -> [u8; 32] {
+    let mut packed = schema.as_bytes().to_vec();
+    packed.extend_from_slice(resolver.as_bytes());
+    packed.push(revocable as u8);
+    keccak256(packed)
+}
+
+/// UID of [`SCHEMA`] as SODS registers it: no resolver, revocable.
+pub fn sods_schema_uid() -> [u8; 32] {
+    schema_uid(SCHEMA, Address::zero(), true)
+}
+
+/// An EAS `AttestationRequest` carrying one behavioral proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attestation {
+    pub schema: [u8; 32],
+    /// Subject of the attestation; zero when the finding has none
+    pub recipient: Address,
+    /// Unix time the attestation expires, zero for never
+    pub expiration_time: u64,
+    pub revocable: bool,
+    pub ref_uid: [u8; 32],
+    /// `proof` ABI-encoded under [`SCHEMA`]
+    pub data: Vec<u8>,
+}
+
+impl Attestation {
+    /// Attest that the pattern with `pattern_hash` occurred as `proof` shows.
+    pub fn new(pattern: &str, pattern_hash: [u8; 32], proof: &OnChainBehavioralProof) -> Self {
+        let data = abi::encode(&[
+            Token::Uint(proof.chain_id.into()),
+            Token::Uint(proof.block_number.into()),
+            Token::FixedBytes(pattern_hash.to_vec()),
+            Token::String(pattern.to_string()),
+            Token::Array(proof.symbols.iter().cloned().map(Token::String).collect()),
+            Token::FixedBytes(proof.bmt_root.to_vec()),
+            Token::FixedBytes(proof.receipts_root.unwrap_or_default().to_vec()),
+            Token::FixedBytes(proof.beacon_root.unwrap_or_default().to_vec()),
+        ]);
+        Self {
+            schema: sods_schema_uid(),
+            recipient: Address::zero(),
+            expiration_time: 0,
+            revocable: true,
+            ref_uid: [0u8; 32],
+            data,
+        }
+    }
+
+    /// Attest about `recipient`, e.g. the contract the pattern matched on
+    /// (Builder pattern).
+    pub fn with_recipient(mut self, recipient: Address) -> Self {
+        self.recipient = recipient;
+        self
+    }
+
+    /// Calldata of `EAS.attest` for this request.
+    pub fn to_calldata(&self) -> Vec<u8> {
+        let request = Token::Tuple(vec![
+            Token::FixedBytes(self.schema.to_vec()),
+            Token::Tuple(vec![
+                Token::Address(self.recipient),
+                Token::Uint(self.expiration_time.into()),
+                Token::Bool(self.revocable),
+                Token::FixedBytes(self.ref_uid.to_vec()),
+                Token::Bytes(self.data.clone()),
+                Token::Uint(U256::zero()),
+            ]),
+        ]);
+        let mut calldata = keccak256(ATTEST_SIGNATURE)[..4].to_vec();
+        calldata.extend(abi::encode(&[request]));
+        calldata
+    }
+
+    /// The request as JSON, with the schema so it can be registered.
+    pub fn to_json(&self) -> Value {
+        json!({
+            "schema": SCHEMA,
+            "schemaUid": format!("0x{}", hex::encode(self.schema)),
+            "request": {
+                "schema": format!("0x{}", hex::encode(self.schema)),
+                "data": {
+                    "recipient": to_checksum(&self.recipient, None),
+                    "expirationTime": self.expiration_time,
+                    "revocable": self.revocable,
+                    "refUID": format!("0x{}", hex::encode(self.ref_uid)),
+                    "data": format!("0x{}", hex::encode(&self.data)),
+                    "value": 0,
+                },
+            },
+            "calldata": format!("0x{}", hex::encode(self.to_calldata())),
+        })
+    }
+}
+
+/// Result of a submitted attestation.
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub tx_hash: H256,
+    /// UID from the `Attested` event, if the receipt carried one
+    pub uid: Option<H256>,
+}
+
+/// Send `attestation` to the EAS contract at `eas`, signed by `wallet`,
+/// and wait for it to be mined.
+pub async fn submit(
+    rpc_url: &str,
+    wallet: LocalWallet,
+    eas: Address,
+    attestation: &Attestation,
+) -> Result<Submission, String> {
+    let provider =
+        Provider::<Http>::try_from(rpc_url).map_err(|e| format!("invalid RPC URL: {}", e))?;
+    let chain_id = provider
+        .get_chainid()
+        .await
+        .map_err(|e| format!("failed to fetch chain id: {}", e))?;
+    let client = SignerMiddleware::new(provider, wallet.with_chain_id(chain_id.as_u64()));
+
+    let tx = TransactionRequest::new()
+        .to(eas)
+        .data(attestation.to_calldata());
+    let pending = client
+        .send_transaction(tx, None)
+        .await
+        .map_err(|e| format!("failed to send attestation: {}", e))?;
+    let tx_hash = pending.tx_hash();
+    let receipt = pending
+        .await
+        .map_err(|e| format!("failed to confirm {:?}: {}", tx_hash, e))?
+        .ok_or_else(|| format!("attestation {:?} was dropped", tx_hash))?;
+    if receipt.status.is_some_and(|s| s.is_zero()) {
+        return Err(format!("attestation {:?} reverted", tx_hash));
+    }
+
+    let attested = H256::from(keccak256(ATTESTED_EVENT));
+    let uid = receipt
+        .logs
+        .iter()
+        .find(|log| log.address == eas && log.topics.first() == Some(&attested))
+        .filter(|log| log.data.len() >= 32)
+        .map(|log| H256::from_slice(&log.data[..32]));
+    Ok(Submission { tx_hash, uid })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use abi::ParamType;
+
+    fn proof() -> OnChainBehavioralProof {
+        OnChainBehavioralProof {
+            block_number: 20_000_000,
+            chain_id: 1,
+            symbols: vec!["Tf".to_string(), "Sw".to_string()],
+            log_indices: vec![0, 3],
+            leaf_hashes: vec![[1u8; 32], [2u8; 32]],
+            merkle_path: vec![],
+            is_left_path: vec![],
+            bmt_root: [7u8; 32],
+            beacon_root: None,
+            timestamp: 0,
+            receipts_root: Some([9u8; 32]),
+            signature: None,
+            dictionary_fingerprint: None,
+            provenance: vec![],
+            availability_cid: None,
+        }
+    }
+
+    #[test]
+    fn test_data_decodes_under_schema() {
+        let attestation = Attestation::new("Tf -> Sw", [5u8; 32], &proof());
+        let types: Vec<ParamType> = SCHEMA
+            .split(',')
+            .map(|field| match field.split(' ').next().unwrap() {
+                "uint256" | "uint64" => ParamType::Uint(256),
+                "bytes32" => ParamType::FixedBytes(32),
+                "string" => ParamType::String,
+                "string[]" => ParamType::Array(Box::new(ParamType::String)),
+                other => panic!("unexpected schema type {}", other),
+            })
+            .collect();
+        let fields = abi::decode(&types, &attestation.data).unwrap();
+        assert_eq!(fields[1], Token::Uint(20_000_000u64.into()));
+        assert_eq!(fields[2], Token::FixedBytes(vec![5u8; 32]));
+        assert_eq!(fields[3], Token::String("Tf -> Sw".to_string()));
+        assert_eq!(fields[6], Token::FixedBytes(vec![9u8; 32]));
+        assert_eq!(fields[7], Token::FixedBytes(vec![0u8; 32]));
+    }
+
+    #[test]
+    fn test_attest_calldata() {
+        let attestation = Attestation::new("Tf -> Sw", [5u8; 32], &proof());
+        let calldata = attestation.to_calldata();
+        // EAS.attest selector
+        assert_eq!(hex::encode(&calldata[..4]), "f17325e7");
+        // The schema UID is the first word of the request tuple
+        assert_eq!(&calldata[4 + 32..4 + 64], &sods_schema_uid());
+        assert_ne!(
+            sods_schema_uid(),
+            schema_uid(SCHEMA, Address::zero(), false)
+        );
+    }
+}
@@ -0,0 +1,480 @@
+//! Encrypted keystore for signing keys.
+//!
+//! Every command that signs something (P2P proof responses, threat packs,
+//! exported commitments and credentials) takes its secp256k1 key from here
+//! instead of a hex string on the command line, where it would end up in
+//! shell history and process listings.
+//!
+//! Each key is a JSON file in `~/.sods/keys/<name>.json`. The private key is
+//! sealed with AES-256-GCM under a key derived from a password with scrypt;
+//! the key's address is the associated data, so a file whose address was
+//! edited no longer decrypts. The password is read from `SODS_KEY_PASSWORD`
+//! if set, otherwise prompted for on the terminal.
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use chrono::Utc;
+use ethers_core::k256::ecdsa::SigningKey;
+use ethers_core::types::Address;
+use ethers_core::utils::{secret_key_to_address, to_checksum};
+use ethers_signers::LocalWallet;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+use crate::output;
+
+/// Keystore file format version.
+pub const KEYSTORE_VERSION: u32 = 1;
+
+/// Environment variable holding the keystore password for non-interactive use.
+pub const PASSWORD_ENV: &str = "SODS_KEY_PASSWORD";
+
+/// scrypt cost for new keys: N = 2^17, r = 8, p = 1 (128 MiB).
+pub const DEFAULT_SCRYPT_LOG_N: u8 = 17;
+
+/// Highest scrypt cost a keystore file may ask for (N = 2^20, 1 GiB at
+/// r = 8); files demanding more are rejected before deriving.
+pub const MAX_SCRYPT_LOG_N: u8 = 20;
+
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// scrypt parameters and salt of one keystore file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    /// Hex-encoded salt
+    pub salt: String,
+}
+
+/// How the private key is sealed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CryptoSection {
+    /// Always `scrypt`
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    /// Always `aes-256-gcm`
+    pub cipher: String,
+    /// Hex-encoded 96-bit nonce
+    pub nonce: String,
+    /// Hex-encoded ciphertext followed by the GCM tag
+    pub ciphertext: String,
+}
+
+/// One encrypted key as stored on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeystoreFile {
+    pub version: u32,
+    pub name: String,
+    pub address: Address,
+    /// Compressed public key (hex), as trusted with `sods threats add-key`
+    pub public_key: String,
+    /// Unix timestamp of creation or import
+    pub created_at: u64,
+    pub crypto: CryptoSection,
+}
+
+impl KeystoreFile {
+    /// Encrypt `key` under `password` with the default scrypt cost.
+    pub fn encrypt(name: &str, key: &SigningKey, password: &str) -> Result<Self, String> {
+        Self::encrypt_with_cost(name, key, password, DEFAULT_SCRYPT_LOG_N)
+    }
+
+    /// Encrypt `key` under `password` with scrypt N = 2^`log_n`.
+    pub fn encrypt_with_cost(
+        name: &str,
+        key: &SigningKey,
+        password: &str,
+        log_n: u8,
+    ) -> Result<Self, String> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        rand::thread_rng().fill_bytes(&mut nonce);
+
+        let kdfparams = KdfParams {
+            log_n,
+            r: SCRYPT_R,
+            p: SCRYPT_P,
+            salt: hex::encode(salt),
+        };
+        let address = secret_key_to_address(key);
+        let cipher = Aes256Gcm::new_from_slice(&derive_key(password, &kdfparams)?)
+            .map_err(|e| e.to_string())?;
+        let ciphertext = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: key.to_bytes().as_slice(),
+                    aad: address.as_bytes(),
+                },
+            )
+            .map_err(|_| "Encryption failed".to_string())?;
+
+        Ok(Self {
+            version: KEYSTORE_VERSION,
+            name: name.to_string(),
+            address,
+            public_key: public_key_hex(key),
+            created_at: Utc::now().timestamp() as u64,
+            crypto: CryptoSection {
+                kdf: "scrypt".to_string(),
+                kdfparams,
+                cipher: "aes-256-gcm".to_string(),
+                nonce: hex::encode(nonce),
+                ciphertext: hex::encode(ciphertext),
+            },
+        })
+    }
+
+    /// Decrypt the private key. Fails on a wrong password or a tampered file.
+    pub fn decrypt(&self, password: &str) -> Result<SigningKey, String> {
+        if self.version != KEYSTORE_VERSION {
+            return Err(format!("Unsupported keystore version {}", self.version));
+        }
+        if self.crypto.kdf != "scrypt" || self.crypto.cipher != "aes-256-gcm" {
+            return Err(format!(
+                "Unsupported keystore crypto {}/{}",
+                self.crypto.kdf, self.crypto.cipher
+            ));
+        }
+        let nonce = hex::decode(&self.crypto.nonce).map_err(|e| format!("Bad nonce: {}", e))?;
+        if nonce.len() != NONCE_LEN {
+            return Err("Bad nonce length".to_string());
+        }
+        let ciphertext =
+            hex::decode(&self.crypto.ciphertext).map_err(|e| format!("Bad ciphertext: {}", e))?;
+
+        let cipher = Aes256Gcm::new_from_slice(&derive_key(password, &self.crypto.kdfparams)?)
+            .map_err(|e| e.to_string())?;
+        let plaintext = cipher
+            .decrypt(
+                Nonce::from_slice(&nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: self.address.as_bytes(),
+                },
+            )
+            .map_err(|_| format!("Wrong password for key '{}'", self.name))?;
+        let key = SigningKey::from_slice(&plaintext).map_err(|e| e.to_string())?;
+        if secret_key_to_address(&key) != self.address {
+            return Err(format!("Key '{}' does not match its address", self.name));
+        }
+        Ok(key)
+    }
+
+    /// Checksummed address of the key.
+    pub fn address_checksum(&self) -> String {
+        to_checksum(&self.address, None)
+    }
+}
+
+fn derive_key(password: &str, params: &KdfParams) -> Result<[u8; 32], String> {
+    if params.log_n > MAX_SCRYPT_LOG_N || params.r > SCRYPT_R || params.p > SCRYPT_P {
+        return Err(format!(
+            "scrypt cost (log_n {}, r {}, p {}) exceeds the limit (log_n {}, r {}, p {})",
+            params.log_n, params.r, params.p, MAX_SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P
+        ));
+    }
+    let salt = hex::decode(&params.salt).map_err(|e| format!("Bad salt: {}", e))?;
+    let scrypt_params = scrypt::Params::new(params.log_n, params.r, params.p)
+        .map_err(|e| format!("Bad scrypt parameters: {}", e))?;
+    let mut out = [0u8; 32];
+    scrypt::scrypt(password.as_bytes(), &salt, &scrypt_params, &mut out)
+        .map_err(|e| format!("scrypt failed: {}", e))?;
+    Ok(out)
+}
+
+/// Compressed SEC1 public key of `key`, hex-encoded.
+pub fn public_key_hex(key: &SigningKey) -> String {
+    hex::encode(key.verifying_key().to_encoded_point(true).as_bytes())
+}
+
+/// A fresh random signing key.
+pub fn generate_key() -> SigningKey {
+    SigningKey::random(&mut rand::thread_rng())
+}
+
+/// Parse a hex private key, with or without `0x`.
+pub fn parse_private_key(hex_key: &str) -> Result<SigningKey, String> {
+    let bytes = hex::decode(hex_key.trim().trim_start_matches("0x"))
+        .map_err(|e| format!("Invalid private key: {}", e))?;
+    SigningKey::from_slice(&bytes).map_err(|_| "Invalid private key".to_string())
+}
+
+/// Whether `name` can be used as a key name (and file name).
+pub fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid key name '{}' (use letters, digits, '-' and '_')",
+            name
+        ))
+    }
+}
+
+/// Directory of keystore files.
+pub struct Keystore {
+    dir: PathBuf,
+}
+
+impl Keystore {
+    /// The keystore in `~/.sods/keys`.
+    pub fn open_default() -> Self {
+        let dir = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".sods")
+            .join("keys");
+        Self { dir }
+    }
+
+    /// A keystore in `dir`.
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        validate_name(name).is_ok() && self.path(name).is_file()
+    }
+
+    /// Write `file` under its name, readable only by the owner. Refuses to
+    /// replace an existing key.
+    pub fn save(&self, file: &KeystoreFile) -> Result<PathBuf, String> {
+        validate_name(&file.name)?;
+        let path = self.path(&file.name);
+        if path.exists() {
+            return Err(format!("Key '{}' already exists", file.name));
+        }
+        fs::create_dir_all(&self.dir).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string_pretty(file).map_err(|e| e.to_string())?;
+        write_private_file(&path, json.as_bytes()).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+
+    pub fn load(&self, name: &str) -> Result<KeystoreFile, String> {
+        validate_name(name)?;
+        let json = fs::read_to_string(self.path(name))
+            .map_err(|_| format!("No key named '{}' in {}", name, self.dir.display()))?;
+        serde_json::from_str(&json).map_err(|e| format!("Corrupt keystore file: {}", e))
+    }
+
+    /// All readable keys, by name.
+    pub fn list(&self) -> Vec<KeystoreFile> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut keys: Vec<KeystoreFile> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| fs::read_to_string(path).ok())
+            .filter_map(|json| serde_json::from_str(&json).ok())
+            .collect();
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        keys
+    }
+}
+
+/// Create `path` readable only by the owner and write `contents` to it.
+/// Refuses to replace an existing file.
+pub fn write_private_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents)
+}
+
+/// Read a password: from `file` if given, else `SODS_KEY_PASSWORD`, else
+/// prompted for (without echo on Unix terminals).
+pub fn read_password(prompt: &str, file: Option<&Path>) -> Result<String, String> {
+    if let Some(path) = file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        return Ok(content.trim_end_matches(['\r', '\n']).to_string());
+    }
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Ok(password);
+    }
+
+    let stdin = std::io::stdin();
+    let interactive = stdin.is_terminal();
+    if interactive {
+        eprint!("{}", prompt);
+        let _ = std::io::stderr().flush();
+        set_echo(false);
+    }
+    let mut line = String::new();
+    let read = stdin.lock().read_line(&mut line);
+    if interactive {
+        set_echo(true);
+        eprintln!();
+    }
+    read.map_err(|e| format!("Failed to read password: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Read a new password, asking twice when prompting.
+pub fn read_new_password(file: Option<&Path>) -> Result<String, String> {
+    let password = read_password("New password: ", file)?;
+    let prompted =
+        file.is_none() && std::env::var(PASSWORD_ENV).is_err() && std::io::stdin().is_terminal();
+    if prompted && read_password("Repeat password: ", None)? != password {
+        return Err("Passwords do not match".to_string());
+    }
+    if password.is_empty() {
+        return Err("Password must not be empty".to_string());
+    }
+    Ok(password)
+}
+
+#[cfg(unix)]
+fn set_echo(on: bool) {
+    let _ = std::process::Command::new("stty")
+        .arg(if on { "echo" } else { "-echo" })
+        .stdin(std::process::Stdio::inherit())
+        .status();
+}
+
+#[cfg(not(unix))]
+fn set_echo(_on: bool) {}
+
+/// Resolve a `--key`-style argument to a signing key.
+///
+/// `spec` names a key in the default keystore, which is decrypted after
+/// reading its password. A raw hex private key is still accepted for now,
+/// with a deprecation warning.
+pub fn load_signing_key(spec: &str) -> Result<SigningKey, String> {
+    let store = Keystore::open_default();
+    if store.contains(spec) {
+        let file = store.load(spec)?;
+        let password = read_password(&format!("Password for key '{}': ", spec), None)?;
+        return file.decrypt(&password);
+    }
+    if let Ok(key) = parse_private_key(spec) {
+        output::warning("Passing a raw private key is deprecated and leaks it to shell history.");
+        output::hint(
+            "Import it once with `sods keys import <NAME> --from <FILE>` and pass the name.",
+        );
+        return Ok(key);
+    }
+    Err(format!(
+        "No key named '{}' in {} (see `sods keys list`)",
+        spec,
+        store.dir().display()
+    ))
+}
+
+/// Like `load_signing_key`, as an ethers wallet.
+pub fn load_wallet(spec: &str) -> Result<LocalWallet, String> {
+    load_signing_key(spec).map(LocalWallet::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Cheap scrypt cost so the tests stay fast
+    const TEST_LOG_N: u8 = 4;
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let key = generate_key();
+        let file = KeystoreFile::encrypt_with_cost("ops", &key, "hunter2", TEST_LOG_N).unwrap();
+        assert_eq!(file.address, secret_key_to_address(&key));
+        assert_eq!(file.public_key.len(), 66);
+        assert!(!file
+            .crypto
+            .ciphertext
+            .contains(&hex::encode(key.to_bytes())));
+
+        let json = serde_json::to_string(&file).unwrap();
+        let parsed: KeystoreFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            parsed.decrypt("hunter2").unwrap().to_bytes(),
+            key.to_bytes()
+        );
+        assert!(parsed.decrypt("hunter3").is_err());
+
+        // The address is authenticated
+        let mut tampered = parsed.clone();
+        tampered.address = Address::repeat_byte(1);
+        assert!(tampered.decrypt("hunter2").is_err());
+
+        // A file cannot make decryption allocate without bound
+        let mut costly = parsed.clone();
+        costly.crypto.kdfparams.log_n = MAX_SCRYPT_LOG_N + 1;
+        assert!(costly
+            .decrypt("hunter2")
+            .unwrap_err()
+            .contains("exceeds the limit"));
+    }
+
+    #[test]
+    fn test_keystore_save_load_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Keystore::at(dir.path());
+        assert!(store.list().is_empty());
+
+        let key =
+            parse_private_key("0x4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318")
+                .unwrap();
+        let file = KeystoreFile::encrypt_with_cost("b-key", &key, "pw", TEST_LOG_N).unwrap();
+        store.save(&file).unwrap();
+        store
+            .save(
+                &KeystoreFile::encrypt_with_cost("a-key", &generate_key(), "pw", TEST_LOG_N)
+                    .unwrap(),
+            )
+            .unwrap();
+        assert!(store.save(&file).is_err());
+
+        assert!(store.contains("b-key"));
+        assert!(!store.contains("../b-key"));
+        assert_eq!(
+            store.load("b-key").unwrap().address_checksum(),
+            "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+        let names: Vec<String> = store.list().into_iter().map(|k| k.name).collect();
+        assert_eq!(names, vec!["a-key", "b-key"]);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(dir.path().join("b-key.json"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert!(validate_name("daemon_1").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name("..").is_err());
+    }
+}
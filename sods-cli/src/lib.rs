@@ -1,8 +1,27 @@
 #[cfg(feature = "api")]
 pub mod api;
+pub mod auth;
 pub mod commands;
 pub mod config;
+pub mod control;
+pub mod correlation;
+pub mod credential;
+pub mod digest;
+pub mod dry_run;
+pub mod eas;
+pub mod keys;
 pub mod logging;
 #[cfg(feature = "metrics")]
 pub mod monitoring;
 pub mod output;
+pub mod proof_export;
+#[cfg(feature = "ipfs")]
+pub mod publisher;
+pub mod retention;
+pub mod rule_pack;
+pub mod sampling;
+pub mod siem;
+pub mod sketch;
+pub mod trend_store;
+pub mod unsigned_bundle;
+pub mod webhook_queue;
@@ -1,4 +1,4 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use sods_cli::{commands, logging};
 
 #[derive(Parser)]
@@ -13,8 +13,9 @@ struct Cli {
 }
 
 #[derive(Subcommand)]
+#[allow(clippy::large_enum_variant)]
 enum Commands {
-    /// Verify behavioral patterns using Behavioral Merkle Trees (BMT). Causal Merkle Trees (CMT) coming in v8.0.
+    /// Verify behavioral patterns using Behavioral Merkle Trees (BMT). See `causal` for agent histories.
     Verify(commands::verify::VerifyArgs),
 
     /// List supported blockchain chains
@@ -29,6 +30,12 @@ enum Commands {
     /// Detect behavioral trends
     Trend(commands::trend::TrendArgs),
 
+    /// Scan a historical block range for a pattern, with checkpoints
+    Backfill(commands::backfill::BackfillArgs),
+
+    /// Audit a block range by verifying a seeded random sample of blocks
+    Audit(commands::audit::AuditArgs),
+
     /// Autonomous behavioral monitoring
     Monitor(commands::monitor::MonitorArgs),
 
@@ -38,9 +45,21 @@ enum Commands {
     /// Manage decentralized threat intelligence
     Threats(commands::threats::ThreatsArgs),
 
+    /// Sign a proof bundle exported with `export-proof --unsigned`
+    SignProof(commands::sign_proof::SignProofArgs),
+
+    /// Manage encrypted signing keys
+    Keys(commands::keys::KeysArgs),
+
     /// Export an on-chain verifiable behavioral proof
     ExportProof(commands::export_proof::ExportProofArgs),
 
+    /// Build, list and save behavioral patterns
+    Pattern(commands::pattern::PatternArgs),
+
+    /// List and add named pattern presets (~/.sods/patterns.toml)
+    Patterns(commands::patterns::PatternsArgs),
+
     /// Compute the privacy-safe hash of a behavioral pattern
     HashPattern(commands::hash_pattern::HashPatternArgs),
 
@@ -56,11 +75,32 @@ enum Commands {
 
     /// Manage ERC-8004 agent identity and services
     Agent(commands::agent::AgentArgs),
+
+    /// Record causal agent histories and generate/verify behavioral proofs
+    Causal(commands::causal::CausalArgs),
+
+    /// Benchmark RPC providers and verification stages
+    Bench(commands::bench::BenchArgs),
+
+    /// Check this build against the protocol conformance vectors
+    Selftest(commands::selftest::SelftestArgs),
+
+    /// Apply retention policies to the files under ~/.sods
+    Gc(commands::gc::GcArgs),
+
+    /// Print shell completions or write man pages
+    Completions(commands::completions::CompletionsArgs),
+
+    /// Pin epoch summaries and proof bundles to IPFS/Arweave
+    #[cfg(feature = "ipfs")]
+    Publish(commands::publish::PublishArgs),
 }
 
 fn main() {
     logging::init_logging();
     let cli = Cli::parse();
+    // User presets expand wherever a pattern is parsed
+    sods_cli::config::load_pattern_presets().install();
 
     // Special handling for Daemon to avoid fork issues with tokio threads
     if let Commands::Daemon(args) = cli.command {
@@ -81,15 +121,28 @@ fn main() {
             Commands::Symbols(args) => commands::symbols::run(args).await,
             Commands::Discover(args) => commands::discover::run(args).await,
             Commands::Trend(args) => commands::trend::run(args).await,
+            Commands::Backfill(args) => commands::backfill::run(args).await,
+            Commands::Audit(args) => commands::audit::run(args).await,
             Commands::Monitor(args) => commands::monitor::run(args).await,
             Commands::Threats(args) => commands::threats::run(args).await,
+            Commands::SignProof(args) => commands::sign_proof::run(args).await,
+            Commands::Keys(args) => commands::keys::run(args),
             Commands::ExportProof(args) => commands::export_proof::run(args).await,
+            Commands::Pattern(args) => commands::pattern::run(args).await,
+            Commands::Patterns(args) => commands::patterns::run(args),
             Commands::HashPattern(args) => commands::hash_pattern::run(args).await,
             #[cfg(feature = "zk")]
             Commands::ZkProve(args) => commands::zk_prove::run(args).await,
             Commands::Registry(args) => commands::registry::run(args),
             Commands::Listen(args) => commands::listen::run(args).await,
             Commands::Agent(args) => commands::agent::run(args).await,
+            Commands::Causal(args) => commands::causal::run(args),
+            Commands::Bench(args) => commands::bench::run(args).await,
+            Commands::Selftest(args) => commands::selftest::run(args),
+            Commands::Gc(args) => commands::gc::run(args),
+            Commands::Completions(args) => commands::completions::run(args, Cli::command()),
+            #[cfg(feature = "ipfs")]
+            Commands::Publish(args) => commands::publish::run(args).await,
             Commands::Daemon(_) => unreachable!(), // Handled above
         }
     });
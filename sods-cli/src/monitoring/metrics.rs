@@ -3,9 +3,13 @@ use http_body_util::Full;
 use prometheus::{
     Counter, Encoder, Gauge, Histogram, HistogramOpts, IntGauge, Registry, TextEncoder,
 };
+use prometheus_client::metrics::exemplar::CounterWithExemplar;
+use sods_p2p::P2pMetrics;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 
+use crate::auth::{ApiAuth, Scope};
+
 #[cfg(feature = "metrics")]
 #[derive(Clone)]
 pub struct AgentMetrics {
@@ -18,9 +22,18 @@ pub struct AgentMetrics {
     pub rpc_calls_total: Counter,
     pub p2p_messages_total: Counter,
     pub behavioral_alerts_total: Counter,
+    /// Behavioral alerts again, with the latest alert ID as exemplar so a
+    /// dashboard spike links to the detection; see `record_alert`
+    pub correlated_alerts_total: CounterWithExemplar<Vec<(String, String)>>,
     pub verification_failures_total: Counter,
     pub verification_duration_seconds: Histogram,
 
+    // Webhook delivery
+    pub webhook_deliveries_total: Counter,
+    pub webhook_retries_total: Counter,
+    pub webhook_dead_letters_total: Counter,
+    pub webhook_queue_depth: IntGauge,
+
     // ERC-8004 Specific Metrics
     pub registry_registrations_total: Counter,
     pub registry_updates_total: Counter,
@@ -37,6 +50,13 @@ pub struct AgentMetrics {
 
     pub agent_uptime_seconds: Gauge,
     pub last_validation_timestamp: Gauge,
+
+    /// libp2p connection and gossip metrics, served after the others
+    pub p2p: Option<P2pMetrics>,
+
+    /// OpenMetrics registry of the metrics with exemplars, which the
+    /// `prometheus` crate cannot encode
+    exemplars: Arc<prometheus_client::registry::Registry>,
 }
 
 #[cfg(feature = "metrics")]
@@ -65,6 +85,24 @@ impl AgentMetrics {
             "Time spent verifying blocks",
         ))?;
 
+        // Webhook delivery
+        let webhook_deliveries_total = Counter::new(
+            "sods_webhook_deliveries_total",
+            "Webhook payloads accepted by their endpoint",
+        )?;
+        let webhook_retries_total = Counter::new(
+            "sods_webhook_retries_total",
+            "Webhook delivery attempts scheduled for retry",
+        )?;
+        let webhook_dead_letters_total = Counter::new(
+            "sods_webhook_dead_letters_total",
+            "Webhook payloads moved to the dead-letter file",
+        )?;
+        let webhook_queue_depth = IntGauge::new(
+            "sods_webhook_queue_depth",
+            "Webhook payloads awaiting delivery",
+        )?;
+
         // ERC-8004
         let registry_registrations_total = Counter::new(
             "sods_registry_registrations_total",
@@ -117,6 +155,11 @@ impl AgentMetrics {
         registry.register(Box::new(verification_failures_total.clone()))?;
         registry.register(Box::new(verification_duration_seconds.clone()))?;
 
+        registry.register(Box::new(webhook_deliveries_total.clone()))?;
+        registry.register(Box::new(webhook_retries_total.clone()))?;
+        registry.register(Box::new(webhook_dead_letters_total.clone()))?;
+        registry.register(Box::new(webhook_queue_depth.clone()))?;
+
         registry.register(Box::new(registry_registrations_total.clone()))?;
         registry.register(Box::new(registry_updates_total.clone()))?;
         registry.register(Box::new(validation_requests_received_total.clone()))?;
@@ -129,6 +172,14 @@ impl AgentMetrics {
         registry.register(Box::new(agent_uptime_seconds.clone()))?;
         registry.register(Box::new(last_validation_timestamp.clone()))?;
 
+        let correlated_alerts_total = CounterWithExemplar::default();
+        let mut exemplars = prometheus_client::registry::Registry::default();
+        exemplars.register(
+            "sods_correlated_alerts",
+            "Total behavioral alerts, with the latest alert ID as exemplar",
+            correlated_alerts_total.clone(),
+        );
+
         let metrics = Self {
             registry,
             active_rules,
@@ -137,8 +188,13 @@ impl AgentMetrics {
             rpc_calls_total,
             p2p_messages_total,
             behavioral_alerts_total,
+            correlated_alerts_total,
             verification_failures_total,
             verification_duration_seconds,
+            webhook_deliveries_total,
+            webhook_retries_total,
+            webhook_dead_letters_total,
+            webhook_queue_depth,
             registry_registrations_total,
             registry_updates_total,
             validation_requests_received_total,
@@ -150,6 +206,8 @@ impl AgentMetrics {
             payment_success_rate,
             agent_uptime_seconds,
             last_validation_timestamp,
+            p2p: None,
+            exemplars: Arc::new(exemplars),
         };
 
         // Start uptime tracking
@@ -172,7 +230,39 @@ impl AgentMetrics {
         Ok(metrics)
     }
 
-    pub async fn start_http_server(self: Arc<Self>, port: u16) {
+    /// Count a behavioral alert, keeping `alert_id` as the exemplar.
+    pub fn record_alert(&self, alert_id: &str) {
+        self.behavioral_alerts_total.inc();
+        self.correlated_alerts_total.inc_by(
+            1,
+            Some(vec![("alert_id".to_string(), alert_id.to_string())]),
+        );
+    }
+
+    /// Text exposition of every metric, the exemplar-carrying and libp2p
+    /// ones after the rest.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let _ = TextEncoder::new().encode(&self.registry.gather(), &mut buffer);
+        let mut exemplars = String::new();
+        // Writing to a String cannot fail
+        let _ = prometheus_client::encoding::text::encode_registry(&mut exemplars, &self.exemplars);
+        buffer.extend_from_slice(exemplars.as_bytes());
+        if let Some(p2p) = &self.p2p {
+            buffer.extend_from_slice(p2p.encode().as_bytes());
+        }
+        buffer
+    }
+
+    /// Serve the libp2p metrics of `p2p` too, if any (Builder pattern).
+    pub fn with_p2p_metrics(mut self, p2p: Option<P2pMetrics>) -> Self {
+        self.p2p = p2p;
+        self
+    }
+
+    /// Serve `/_metrics` and the health endpoints, requiring a key with the
+    /// `read-metrics` scope when `auth` has keys.
+    pub async fn serve(self: Arc<Self>, port: u16, auth: Arc<ApiAuth>) {
         let mut app = Router::new();
 
         #[cfg(feature = "api")]
@@ -180,24 +270,18 @@ impl AgentMetrics {
             app = app.merge(crate::api::health::router::<Arc<AgentMetrics>>());
         }
 
-        let app = app
-            .route(
-                "/_metrics",
-                get(|State(m): State<Arc<AgentMetrics>>| async move {
-                    let encoder = TextEncoder::new();
-                    let metric_families = m.registry.gather();
-                    let mut buffer = Vec::new();
-                    encoder.encode(&metric_families, &mut buffer).unwrap();
-
-                    Response::builder()
-                        .header("Content-Type", encoder.format_type())
-                        .body(Full::from(buffer))
-                        .unwrap()
-                }),
-            )
-            .with_state(self);
-
-        let addr = format!("0.0.0.0:{}", port);
+        let app = app.route(
+            "/_metrics",
+            get(|State(m): State<Arc<AgentMetrics>>| async move {
+                Response::builder()
+                    .header("Content-Type", TextEncoder::new().format_type())
+                    .body(Full::from(m.encode()))
+                    .unwrap()
+            }),
+        );
+        let app = auth.require(app, Scope::ReadMetrics).with_state(self);
+
+        let addr = auth.socket_addr(port);
         let listener = match TcpListener::bind(&addr).await {
             Ok(l) => l,
             Err(e) => {
@@ -212,3 +296,20 @@ impl AgentMetrics {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_alert_exemplar_carries_alert_id() {
+        let metrics = AgentMetrics::new().unwrap();
+        metrics.record_alert("alert_19000000_1f3a9c0e");
+        metrics.record_alert("drift_19000001_0badf00d");
+
+        let text = String::from_utf8(metrics.encode()).unwrap();
+        assert!(text.contains("sods_behavioral_alerts_total 2"));
+        assert!(text
+            .contains("sods_correlated_alerts_total 2 # {alert_id=\"drift_19000001_0badf00d\"} 1"));
+    }
+}
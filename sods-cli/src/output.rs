@@ -1,6 +1,8 @@
 //! Terminal output formatting.
 
 use colored::Colorize;
+use sods_core::ErrorCode;
+use sods_p2p::SodsP2pError;
 
 /// Print a success message.
 pub fn success(msg: &str) {
@@ -56,7 +58,80 @@ pub fn verification_result(
     println!();
 }
 
+/// Print a hint for a transient failure, with the provider's suggested wait if known.
+pub fn retry_hint(retry_after: Option<std::time::Duration>) {
+    match retry_after {
+        Some(wait) => hint(&format!(
+            "The RPC provider is busy; retry in {}s.",
+            wait.as_secs().max(1)
+        )),
+        None => hint("This looks transient; retrying shortly may succeed."),
+    }
+}
+
 /// Print a helpful hint.
 pub fn hint(msg: &str) {
     println!("{} {}", "💡".dimmed(), msg.dimmed());
 }
+
+/// Print what to do about a P2P failure.
+pub fn p2p_hint(err: &SodsP2pError) {
+    match p2p_remediation(err) {
+        Some(msg) => hint(&msg),
+        None if err.is_retryable() => retry_hint(err.retry_after()),
+        None => {}
+    }
+}
+
+/// Next step for a P2P failure, if there is one beyond retrying.
+fn p2p_remediation(err: &SodsP2pError) -> Option<String> {
+    Some(match err {
+        SodsP2pError::NoAvailablePeers => {
+            "No peers are known; add a bootstrap node with --bootstrap <MULTIADDR>.".into()
+        }
+        SodsP2pError::NoReliablePeers { .. } => "Known peers have not earned enough reputation yet; let them answer a few puzzle challenges or add a trusted --bootstrap node.".into(),
+        SodsP2pError::AllPeersTimedOut { .. } => "Peers did not answer; check that outbound TCP is not blocked by a firewall, or add a closer --bootstrap node.".into(),
+        SodsP2pError::SignatureBindingMissing { .. } => "Peers did not advertise a signer binding; they may run an older release. Bootstrap from up-to-date nodes.".into(),
+        SodsP2pError::NotFound { .. } => "Peers agree the block has no match; check the symbol or pattern, or the block number.".into(),
+        SodsP2pError::ConsensusSplit { .. } => "Peers disagree on this block; check it against your own RPC with --mode rpc, or rerun with --transcript-dir <DIR> to record each peer's answer.".into(),
+        SodsP2pError::DialFailed { addr, .. } => format!(
+            "Check that {} is a valid address (e.g. /ip4/1.2.3.4/tcp/4001/p2p/<PEER_ID>) and that the node is not denied.",
+            addr
+        ),
+        SodsP2pError::ListenFailed { addr, .. } => {
+            let addr = addr.to_string();
+            let port = addr.split('/').skip_while(|p| *p != "tcp").nth(1);
+            match port.filter(|p| *p != "0") {
+                Some(port) => format!(
+                    "Open TCP port {} in the firewall, or free it if another process uses it.",
+                    port
+                ),
+                None => "Allow inbound TCP connections for sods in the firewall.".into(),
+            }
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_p2p_remediation() {
+        let listen = |addr: &str| SodsP2pError::ListenFailed {
+            addr: addr.parse().unwrap(),
+            reason: "Address in use".into(),
+        };
+        assert!(p2p_remediation(&listen("/ip4/0.0.0.0/tcp/4001"))
+            .unwrap()
+            .contains("port 4001"));
+        assert!(!p2p_remediation(&listen("/ip4/0.0.0.0/tcp/0"))
+            .unwrap()
+            .contains("port"));
+        assert!(p2p_remediation(&SodsP2pError::NoAvailablePeers)
+            .unwrap()
+            .contains("--bootstrap"));
+        assert!(p2p_remediation(&SodsP2pError::Timeout).is_none());
+    }
+}
@@ -0,0 +1,287 @@
+//! Record-and-prove: proof bundles written when a monitored pattern matches.
+//!
+//! Proving a match later means fetching its blocks' logs again, which many
+//! providers only serve for recent blocks. With `--export-proofs DIR`,
+//! `sods monitor` and the daemon write a [`MatchBundle`] the moment a match
+//! is detected, while the logs are still at hand: one
+//! `OnChainBehavioralProof` per block the match spans, anchored to the
+//! block header when it could be fetched, plus the matched symbols and the
+//! alert's correlation ID. With
+//! `--export-calldata` the proofs are also written as `verifyBehavior`
+//! calldata, one line per block, next to the bundle.
+
+use futures_util::future::{join_all, try_join_all};
+use serde::{Deserialize, Serialize};
+use sods_core::pattern::BehavioralPattern;
+use sods_core::proof::OnChainBehavioralProof;
+use sods_core::{BehavioralMerkleTree, BehavioralSymbol};
+use sods_verifier::BlockVerifier;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Value of the `type` field, versioning the bundle format.
+pub const BUNDLE_TYPE: &str = "sods-match-bundle/v1";
+
+/// Everything needed to show a match happened, without an RPC provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MatchBundle {
+    #[serde(rename = "type")]
+    pub bundle_type: String,
+    /// Pattern as typed
+    pub pattern: String,
+    /// Canonical pattern hash, hex
+    pub pattern_hash: String,
+    pub chain_id: u64,
+    /// Unix timestamp of detection
+    pub detected_at: u64,
+    /// ID of the alert the match raised, shared by every channel it went to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_id: Option<String>,
+    /// One proof per block the match spans, oldest first
+    pub proofs: Vec<OnChainBehavioralProof>,
+    /// Matched symbols in order
+    pub matched: Vec<BehavioralSymbol>,
+}
+
+impl MatchBundle {
+    /// An empty bundle for a match of `pattern` detected at `detected_at`.
+    pub fn new(
+        pattern: &BehavioralPattern,
+        pattern_str: &str,
+        chain_id: u64,
+        detected_at: u64,
+    ) -> Self {
+        Self {
+            bundle_type: BUNDLE_TYPE.to_string(),
+            pattern: pattern_str.to_string(),
+            pattern_hash: format!("0x{}", hex::encode(pattern.canonical_hash())),
+            chain_id,
+            detected_at,
+            alert_id: None,
+            proofs: Vec::new(),
+            matched: Vec::new(),
+        }
+    }
+
+    /// Tag the bundle with the alert the match raised (Builder pattern).
+    pub fn with_alert_id(mut self, alert_id: impl Into<String>) -> Self {
+        self.alert_id = Some(alert_id.into());
+        self
+    }
+
+    /// Prove `matched` against `blocks`, every symbol of each block the
+    /// match touches, by block number. Matched symbols without a block
+    /// number are taken to be in `block`.
+    pub fn prove(
+        mut self,
+        block: u64,
+        matched: &[BehavioralSymbol],
+        blocks: &BTreeMap<u64, Vec<BehavioralSymbol>>,
+        fingerprint: [u8; 32],
+    ) -> Result<Self, String> {
+        for number in blocks_of(matched, block) {
+            let symbols = blocks
+                .get(&number)
+                .ok_or_else(|| format!("symbols of block {} are missing", number))?;
+            let in_block: Vec<&BehavioralSymbol> = matched
+                .iter()
+                .filter(|s| s.block_number.unwrap_or(block) == number)
+                .collect();
+            let proof = BehavioralMerkleTree::new(symbols.clone())
+                .generate_onchain_proof(&in_block, self.chain_id, number, None, 0)
+                .ok_or_else(|| format!("matched symbols are not in block {}", number))?;
+            self.proofs
+                .push(proof.with_dictionary_fingerprint(fingerprint));
+        }
+        self.matched = matched.to_vec();
+        Ok(self)
+    }
+
+    /// File name of the bundle, without extension; unique per match.
+    pub fn file_stem(&self) -> String {
+        if let Some(alert_id) = &self.alert_id {
+            return format!("{}-{}", self.chain_id, alert_id);
+        }
+        let first_block = self.proofs.first().map_or(0, |p| p.block_number);
+        let log_index = self.matched.first().map_or(0, |s| s.log_index);
+        format!(
+            "{}-{}-{}-{}",
+            self.chain_id,
+            first_block,
+            log_index,
+            self.pattern_hash.get(2..10).unwrap_or_default()
+        )
+    }
+
+    /// `verifyBehavior` calldata of each proof, one per line.
+    pub fn calldata(&self) -> String {
+        self.proofs
+            .iter()
+            .map(|p| format!("0x{}\n", hex::encode(p.to_calldata())))
+            .collect()
+    }
+}
+
+/// Distinct blocks of `matched`, in order.
+fn blocks_of(matched: &[BehavioralSymbol], block: u64) -> Vec<u64> {
+    let mut numbers: Vec<u64> = matched
+        .iter()
+        .map(|s| s.block_number.unwrap_or(block))
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+/// Writes a bundle for each match it is given.
+#[derive(Debug, Clone)]
+pub struct ProofExporter {
+    dir: PathBuf,
+    calldata: bool,
+}
+
+impl ProofExporter {
+    /// Export into `dir`, creating it. A relative `dir` is resolved now,
+    /// so a daemon that changes directory keeps writing to the same place.
+    pub fn open(dir: &Path) -> Result<Self, String> {
+        let dir = if dir.is_absolute() {
+            dir.to_path_buf()
+        } else {
+            std::env::current_dir()
+                .map_err(|e| format!("Cannot resolve {}: {}", dir.display(), e))?
+                .join(dir)
+        };
+        fs::create_dir_all(&dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+        Ok(Self {
+            dir,
+            calldata: false,
+        })
+    }
+
+    /// Also write each bundle's proofs as calldata (Builder pattern).
+    pub fn with_calldata(mut self, calldata: bool) -> Self {
+        self.calldata = calldata;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Prove `matched`, a match of `pattern` found while scanning `block`,
+    /// into `bundle` and write it.
+    ///
+    /// `cached` returns the symbols of a block the caller still holds;
+    /// other blocks are fetched as the leaf set `pattern` is verified
+    /// against, so bundle roots equal the roots `sods verify` reports.
+    /// Headers are fetched concurrently to anchor the proofs, and a proof
+    /// whose header is unavailable is written unanchored.
+    pub async fn export(
+        &self,
+        verifier: &BlockVerifier,
+        pattern: &BehavioralPattern,
+        bundle: MatchBundle,
+        block: u64,
+        matched: &[BehavioralSymbol],
+        cached: impl Fn(u64) -> Option<Vec<BehavioralSymbol>>,
+    ) -> Result<PathBuf, String> {
+        let numbers = blocks_of(matched, block);
+        let fetched = try_join_all(numbers.iter().map(|&number| {
+            let cached = cached(number);
+            async move {
+                let symbols = match cached {
+                    Some(symbols) => symbols,
+                    None => verifier
+                        .fetch_block_symbols_for(number, pattern)
+                        .await
+                        .map_err(|e| format!("Failed to fetch block {}: {}", number, e))?,
+                };
+                Ok::<_, String>((number, symbols))
+            }
+        }))
+        .await?;
+        let blocks: BTreeMap<u64, Vec<BehavioralSymbol>> = fetched.into_iter().collect();
+
+        let mut bundle =
+            bundle.prove(block, matched, &blocks, verifier.dictionary().fingerprint())?;
+        let headers = join_all(
+            bundle
+                .proofs
+                .iter()
+                .map(|proof| verifier.fetch_block_header(proof.block_number)),
+        )
+        .await;
+        for (proof, header) in bundle.proofs.iter_mut().zip(headers) {
+            if let Ok(header) = header {
+                proof.beacon_root = header.parent_beacon_block_root.map(|h| h.0);
+                proof.timestamp = header.timestamp;
+                proof.receipts_root = Some(header.receipts_root.0);
+            }
+        }
+        self.write(&bundle)
+    }
+
+    /// Write `bundle`, and its calldata if enabled; returns the bundle path.
+    pub fn write(&self, bundle: &MatchBundle) -> Result<PathBuf, String> {
+        let path = self.dir.join(format!("{}.json", bundle.file_stem()));
+        let json = serde_json::to_string_pretty(bundle)
+            .map_err(|e| format!("Failed to encode bundle: {}", e))?;
+        fs::write(&path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        if self.calldata {
+            let calldata = path.with_extension("calldata");
+            fs::write(&calldata, bundle.calldata())
+                .map_err(|e| format!("Failed to write {}: {}", calldata.display(), e))?;
+        }
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, symbols: &[&str]) -> Vec<BehavioralSymbol> {
+        symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| BehavioralSymbol::new(*s, i as u32).with_block(number, number * 12))
+            .collect()
+    }
+
+    #[test]
+    fn test_cross_block_bundle() {
+        let pattern = BehavioralPattern::parse("Tf -> Sw within 2 blocks").unwrap();
+        let blocks = BTreeMap::from([(10, block(10, &["Dep", "Tf"])), (11, block(11, &["Sw"]))]);
+        let matched = vec![blocks[&10][1].clone(), blocks[&11][0].clone()];
+
+        let new = || MatchBundle::new(&pattern, "Tf -> Sw within 2 blocks", 1, 1_700_000_000);
+        let bundle = new().prove(11, &matched, &blocks, [3u8; 32]).unwrap();
+        assert_eq!(bundle.proofs.len(), 2);
+        assert_eq!(bundle.proofs[0].block_number, 10);
+        assert_eq!(bundle.proofs[0].symbols, vec!["Tf".to_string()]);
+        assert_eq!(bundle.proofs[1].log_indices, vec![0]);
+        assert_eq!(bundle.proofs[1].dictionary_fingerprint, Some([3u8; 32]));
+
+        let dir = tempfile::tempdir().unwrap();
+        let exporter = ProofExporter::open(&dir.path().join("proofs"))
+            .unwrap()
+            .with_calldata(true);
+        let path = exporter.write(&bundle).unwrap();
+        let read: MatchBundle = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(read.bundle_type, BUNDLE_TYPE);
+        assert_eq!(read.matched, matched);
+        assert_eq!(read.alert_id, None);
+        assert!(path.ends_with(format!("1-10-1-{}.json", &bundle.pattern_hash[2..10])));
+
+        let tagged = bundle.with_alert_id("alert_11_0badf00d");
+        let path = exporter.write(&tagged).unwrap();
+        assert!(path.ends_with("1-alert_11_0badf00d.json"));
+        let calldata = fs::read_to_string(path.with_extension("calldata")).unwrap();
+        assert_eq!(calldata.lines().count(), 2);
+
+        // A block the match touches must be supplied
+        let missing = BTreeMap::from([(11, block(11, &["Sw"]))]);
+        assert!(new().prove(11, &matched, &missing, [0u8; 32]).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+//! Durable publication of epoch summaries and proof bundles.
+//!
+//! Pins artifacts to IPFS through a Kubo-compatible HTTP API and optionally
+//! mirrors them to Arweave through a bundler gateway. Every successful
+//! publication is recorded in `~/.sods/publications.json` so the CID can be
+//! looked up later or bound into a `BehavioralCommitment`.
+
+use serde::{Deserialize, Serialize};
+use sods_core::EpochSummary;
+use std::fs;
+use std::path::PathBuf;
+
+/// Default Kubo RPC endpoint.
+pub const DEFAULT_IPFS_API: &str = "http://127.0.0.1:5001";
+
+/// Kind of artifact being published.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PublicationKind {
+    /// An `EpochSummary` (JSON)
+    Epoch,
+    /// An exported proof bundle (calldata or JSON)
+    Proof,
+}
+
+/// A record of one successful publication.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicationRecord {
+    pub kind: PublicationKind,
+    /// Artifact key (epoch id or proof file name)
+    pub key: String,
+    /// IPFS content identifier
+    pub cid: String,
+    /// Arweave transaction id, if mirrored
+    pub arweave_tx: Option<String>,
+    /// Unix timestamp of publication
+    pub published_at: u64,
+}
+
+/// Local index of published artifacts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PublicationStore {
+    pub records: Vec<PublicationRecord>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl PublicationStore {
+    /// Default store location (`~/.sods/publications.json`).
+    pub fn default_path() -> PathBuf {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        home.join(".sods").join("publications.json")
+    }
+
+    /// Load the store from disk, starting empty if the file is missing.
+    /// A store that cannot be read or parsed is an error, so a later
+    /// `record` does not overwrite it.
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let mut store: Self = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| format!("Corrupt publication store {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                return Err(format!(
+                    "Failed to read publication store {}: {}",
+                    path.display(),
+                    e
+                ))
+            }
+        };
+        store.path = path;
+        Ok(store)
+    }
+
+    /// Append a record and persist the store.
+    ///
+    /// The store is re-read under its lock first, so records compacted away
+    /// by `sods gc` since `load` stay dropped.
+    pub fn record(&mut self, record: PublicationRecord) -> std::io::Result<()> {
+        let _lock = crate::retention::lock_store(&self.path)?;
+        if let Ok(content) = fs::read_to_string(&self.path) {
+            let current: Self = serde_json::from_str(&content)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+            self.records = current.records;
+        }
+        self.records.push(record);
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        fs::write(&self.path, json)
+    }
+
+    /// Most recent CID published for the given artifact.
+    pub fn latest_cid(&self, kind: PublicationKind, key: &str) -> Option<&str> {
+        self.records
+            .iter()
+            .rev()
+            .find(|r| r.kind == kind && r.key == key)
+            .map(|r| r.cid.as_str())
+    }
+}
+
+#[derive(Deserialize)]
+struct KuboAddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct ArweaveUploadResponse {
+    id: String,
+}
+
+/// Publishes artifacts to IPFS (and optionally Arweave).
+pub struct Publisher {
+    client: reqwest::Client,
+    ipfs_api: String,
+    arweave_gateway: Option<String>,
+}
+
+impl Publisher {
+    /// Create a publisher for the given IPFS API endpoint.
+    pub fn new(ipfs_api: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            ipfs_api: ipfs_api.into().trim_end_matches('/').to_string(),
+            arweave_gateway: None,
+        }
+    }
+
+    /// Also mirror uploads to an Arweave bundler gateway (Builder pattern).
+    pub fn with_arweave(mut self, gateway: impl Into<String>) -> Self {
+        self.arweave_gateway = Some(gateway.into().trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Pin an epoch summary and return its publication record.
+    pub async fn publish_epoch(&self, summary: &EpochSummary) -> Result<PublicationRecord, String> {
+        let bytes = serde_json::to_vec(summary).map_err(|e| e.to_string())?;
+        self.publish(
+            PublicationKind::Epoch,
+            summary.epoch_id.to_string(),
+            format!("epoch-{}.json", summary.epoch_id),
+            bytes,
+        )
+        .await
+    }
+
+    /// Pin an exported proof bundle and return its publication record.
+    pub async fn publish_proof(
+        &self,
+        name: &str,
+        bytes: Vec<u8>,
+    ) -> Result<PublicationRecord, String> {
+        self.publish(
+            PublicationKind::Proof,
+            name.to_string(),
+            name.to_string(),
+            bytes,
+        )
+        .await
+    }
+
+    async fn publish(
+        &self,
+        kind: PublicationKind,
+        key: String,
+        file_name: String,
+        bytes: Vec<u8>,
+    ) -> Result<PublicationRecord, String> {
+        let cid = self.pin_ipfs(file_name, bytes.clone()).await?;
+        let arweave_tx = match &self.arweave_gateway {
+            Some(gateway) => Some(self.upload_arweave(gateway, bytes).await?),
+            None => None,
+        };
+
+        Ok(PublicationRecord {
+            kind,
+            key,
+            cid,
+            arweave_tx,
+            published_at: chrono::Utc::now().timestamp() as u64,
+        })
+    }
+
+    async fn pin_ipfs(&self, file_name: String, bytes: Vec<u8>) -> Result<String, String> {
+        let part = reqwest::multipart::Part::bytes(bytes).file_name(file_name);
+        let form = reqwest::multipart::Form::new().part("file", part);
+
+        let resp = self
+            .client
+            .post(format!(
+                "{}/api/v0/add?pin=true&cid-version=1",
+                self.ipfs_api
+            ))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|e| format!("IPFS request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("IPFS node returned {}", resp.status()));
+        }
+
+        resp.json::<KuboAddResponse>()
+            .await
+            .map(|r| r.hash)
+            .map_err(|e| format!("Invalid IPFS response: {}", e))
+    }
+
+    async fn upload_arweave(&self, gateway: &str, bytes: Vec<u8>) -> Result<String, String> {
+        let resp = self
+            .client
+            .post(format!("{}/tx", gateway))
+            .header("Content-Type", "application/octet-stream")
+            .body(bytes)
+            .send()
+            .await
+            .map_err(|e| format!("Arweave request failed: {}", e))?;
+
+        if !resp.status().is_success() {
+            return Err(format!("Arweave gateway returned {}", resp.status()));
+        }
+
+        resp.json::<ArweaveUploadResponse>()
+            .await
+            .map(|r| r.id)
+            .map_err(|e| format!("Invalid Arweave response: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(kind: PublicationKind, key: &str, cid: &str) -> PublicationRecord {
+        PublicationRecord {
+            kind,
+            key: key.to_string(),
+            cid: cid.to_string(),
+            arweave_tx: None,
+            published_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_store_roundtrip_and_latest_cid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("publications.json");
+
+        let mut store = PublicationStore::load(path.clone()).unwrap();
+        store
+            .record(record(PublicationKind::Epoch, "1", "bafyA"))
+            .unwrap();
+        store
+            .record(record(PublicationKind::Epoch, "1", "bafyB"))
+            .unwrap();
+        store
+            .record(record(PublicationKind::Proof, "1", "bafyC"))
+            .unwrap();
+
+        let reloaded = PublicationStore::load(path).unwrap();
+        assert_eq!(reloaded.records.len(), 3);
+        assert_eq!(
+            reloaded.latest_cid(PublicationKind::Epoch, "1"),
+            Some("bafyB")
+        );
+        assert_eq!(
+            reloaded.latest_cid(PublicationKind::Proof, "1"),
+            Some("bafyC")
+        );
+        assert_eq!(reloaded.latest_cid(PublicationKind::Epoch, "2"), None);
+    }
+
+    #[test]
+    fn test_record_keeps_compacted_records_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("publications.json");
+
+        let mut store = PublicationStore::load(path.clone()).unwrap();
+        store
+            .record(record(PublicationKind::Epoch, "1", "bafyA"))
+            .unwrap();
+        // `sods gc` compacts the store while it is loaded
+        fs::write(&path, r#"{"records":[]}"#).unwrap();
+        store
+            .record(record(PublicationKind::Epoch, "2", "bafyB"))
+            .unwrap();
+
+        let reloaded = PublicationStore::load(path).unwrap();
+        assert_eq!(reloaded.records.len(), 1);
+        assert_eq!(reloaded.latest_cid(PublicationKind::Epoch, "1"), None);
+    }
+
+    #[test]
+    fn test_corrupt_store_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("publications.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        assert!(PublicationStore::load(path.clone()).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json");
+    }
+}
@@ -0,0 +1,683 @@
+//! Retention policies for the files SODS keeps under `~/.sods`.
+//!
+//! Every store a long-running node appends to has a policy: records older
+//! than `max_age` are dropped, then the oldest remaining ones until the
+//! store fits in `max_size`. Defaults are listed by `sods gc`, and each
+//! store's policy can be changed in `~/.sods/config.toml`:
+//!
+//! ```toml
+//! [retention.dead-letters]
+//! max_age = "7d"
+//! max_size = "16MB"
+//!
+//! [retention.causal-events]
+//! max_age = "off"   # the default: agent histories are evidence
+//! ```
+//!
+//! `sods gc` compacts the stores on demand; `sods daemon start` does so
+//! before it starts monitoring, keeping threat rules for its
+//! `--expire-after` unless `[retention.threat-rules]` says otherwise.
+//!
+//! Compaction holds the store's lock (`<file>.lock`, see `lock_store`).
+//! The writers of the record stores take it too (the daemon, `sods threats
+//! import`, `sods publish`, `sods causal record`, the webhook dead-letter
+//! queue and the dry-run log), so records written while a store is
+//! compacted are not lost.
+
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const DAY: Duration = Duration::from_secs(24 * 3600);
+const MB: u64 = 1024 * 1024;
+
+/// How much of a store to keep; `None` limits nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub max_age: Option<Duration>,
+    pub max_bytes: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// Whether the policy keeps everything.
+    pub fn is_unlimited(&self) -> bool {
+        self.max_age.is_none() && self.max_bytes.is_none()
+    }
+}
+
+impl std::fmt::Display for RetentionPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.max_age, self.max_bytes) {
+            (None, None) => write!(f, "keep everything"),
+            (Some(age), None) => write!(f, "{}", format_age(age)),
+            (None, Some(size)) => write!(f, "{}", format_size(size)),
+            (Some(age), Some(size)) => write!(f, "{}, {}", format_age(age), format_size(size)),
+        }
+    }
+}
+
+/// A `[retention.<store>]` table in `config.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PolicyOverride {
+    /// e.g. `"30d"`, `"12h"`, or `"off"`
+    pub max_age: Option<String>,
+    /// e.g. `"64MB"`, `"512KB"`, or `"off"`
+    pub max_size: Option<String>,
+}
+
+/// How a store keeps its records, which decides how it is compacted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// One JSON record per line, oldest first, timestamped in this field.
+    /// Rewritten in place, as a running process may be appending to it.
+    JsonLines(&'static str),
+    /// Text lines without timestamps, oldest first; only size applies
+    Text,
+    /// A JSON array of records, top-level or under a key, each timestamped
+    /// in a field
+    JsonRecords {
+        key: Option<&'static str>,
+        timestamp: &'static str,
+    },
+    /// One file per record, aged by modification time
+    Directory,
+    /// A report rewritten as a whole, aged by modification time
+    File,
+}
+
+/// One place SODS persists data.
+#[derive(Debug, Clone)]
+pub struct Store {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub path: PathBuf,
+    pub layout: Layout,
+    pub policy: RetentionPolicy,
+}
+
+/// What compacting one store did.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Compaction {
+    pub store: &'static str,
+    pub path: PathBuf,
+    /// Records, lines or files dropped
+    pub removed: usize,
+    pub bytes_before: u64,
+    pub bytes_after: u64,
+}
+
+impl Compaction {
+    pub fn freed(&self) -> u64 {
+        self.bytes_before.saturating_sub(self.bytes_after)
+    }
+}
+
+/// The stores under `dir` (usually `~/.sods`) with their default policies.
+///
+/// Backfill checkpoints are not listed: they live next to the backfill's
+/// output and are removed when it completes.
+pub fn stores(dir: &Path) -> Vec<Store> {
+    let policy = |days: Option<u64>, mb: Option<u64>| RetentionPolicy {
+        max_age: days.map(|d| DAY * d as u32),
+        max_bytes: mb.map(|m| m * MB),
+    };
+    vec![
+        Store {
+            name: "threat-rules",
+            description: "Threat rules received from peers",
+            path: dir.join("threat_rules.json"),
+            layout: Layout::JsonRecords {
+                key: None,
+                timestamp: "timestamp",
+            },
+            policy: policy(Some(7), None),
+        },
+        Store {
+            name: "dry-run-alerts",
+            description: "Alerts logged by --dry-run",
+            path: dir.join("dry-run.jsonl"),
+            layout: Layout::JsonLines("timestamp"),
+            policy: policy(Some(30), Some(64)),
+        },
+        Store {
+            name: "dead-letters",
+            description: "Webhook deliveries that gave up",
+            path: dir.join("webhook-dlq.jsonl"),
+            layout: Layout::JsonLines("failed_at"),
+            policy: policy(Some(30), Some(64)),
+        },
+        Store {
+            name: "daemon-log",
+            description: "Daemon output",
+            path: dir.join("sods.log"),
+            layout: Layout::Text,
+            policy: policy(None, Some(32)),
+        },
+        Store {
+            name: "trends",
+            description: "Saved `sods trend` runs",
+            path: dir.join("trends"),
+            layout: Layout::Directory,
+            policy: policy(Some(180), Some(64)),
+        },
+        Store {
+            name: "forensics",
+            description: "Mempool forensics report",
+            path: dir.join("mempool-forensics.json"),
+            layout: Layout::File,
+            policy: policy(Some(30), None),
+        },
+        Store {
+            name: "publications",
+            description: "Index of published artifacts",
+            path: dir.join("publications.json"),
+            layout: Layout::JsonRecords {
+                key: Some("records"),
+                timestamp: "published_at",
+            },
+            policy: policy(None, None),
+        },
+        Store {
+            name: "causal-events",
+            description: "Recorded agent histories (evidence)",
+            path: dir.join("causal").join("events.json"),
+            layout: Layout::JsonRecords {
+                key: Some("events"),
+                timestamp: "timestamp",
+            },
+            policy: policy(None, None),
+        },
+    ]
+}
+
+/// The stores under `~/.sods`, with the policies set in `config.toml`.
+///
+/// `rule_expiry`, the daemon's `--expire-after`, replaces the default age
+/// of the threat rules, which are useless once expired.
+pub fn configured_stores(rule_expiry: Option<Duration>) -> Result<Vec<Store>, String> {
+    let dir = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".sods");
+    let mut stores = stores(&dir);
+    if let Some(expiry) = rule_expiry {
+        if let Some(rules) = stores.iter_mut().find(|s| s.name == "threat-rules") {
+            rules.policy.max_age = Some(expiry);
+        }
+    }
+    apply_overrides(&mut stores, &crate::config::UserConfig::load().retention)?;
+    Ok(stores)
+}
+
+/// Exclusive lock on the store at `path`, held until the returned file is
+/// dropped. The lock lives in a `.lock` file next to the store, so it
+/// survives the store being replaced by a rename.
+pub fn lock_store(path: &Path) -> io::Result<fs::File> {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".lock");
+    let lock_path = PathBuf::from(name);
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let lock = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)?;
+    lock.lock_exclusive()?;
+    Ok(lock)
+}
+
+/// Replace default policies with configured ones.
+pub fn apply_overrides(
+    stores: &mut [Store],
+    overrides: &BTreeMap<String, PolicyOverride>,
+) -> Result<(), String> {
+    for (name, policy) in overrides {
+        let store = stores
+            .iter_mut()
+            .find(|s| s.name == name)
+            .ok_or_else(|| format!("Unknown store [retention.{}]", name))?;
+        let invalid = |e: String| format!("[retention.{}]: {}", name, e);
+        if let Some(age) = &policy.max_age {
+            store.policy.max_age = parse_limit(age, parse_age).map_err(invalid)?;
+        }
+        if let Some(size) = &policy.max_size {
+            store.policy.max_bytes = parse_limit(size, parse_size).map_err(invalid)?;
+        }
+    }
+    Ok(())
+}
+
+fn parse_limit<T>(
+    value: &str,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    match value.trim() {
+        "off" => Ok(None),
+        value => parse(value).map(Some),
+    }
+}
+
+/// Parse an age such as `45m`, `12h` or `30d`.
+pub fn parse_age(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid age '{}'", input))?;
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => DAY.as_secs(),
+        _ => return Err(format!("Invalid age '{}': use s, m, h or d", input)),
+    };
+    Ok(Duration::from_secs(number.saturating_mul(unit)))
+}
+
+/// Parse a size such as `512KB`, `64MB` or `1GB` (binary units).
+pub fn parse_size(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size '{}'", input))?;
+    let unit = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "KB" => 1024,
+        "MB" => MB,
+        "GB" => 1024 * MB,
+        _ => return Err(format!("Invalid size '{}': use B, KB, MB or GB", input)),
+    };
+    Ok(number.saturating_mul(unit))
+}
+
+/// An age in the largest unit that divides it, as `parse_age` reads it.
+pub fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match [(DAY.as_secs(), "d"), (3600, "h"), (60, "m")]
+        .into_iter()
+        .find(|(unit, _)| secs > 0 && secs % unit == 0)
+    {
+        Some((unit, suffix)) => format!("{}{}", secs / unit, suffix),
+        None => format!("{}s", secs),
+    }
+}
+
+/// A size in the largest binary unit that holds it at least once.
+pub fn format_size(bytes: u64) -> String {
+    match [(1024 * MB, "GB"), (MB, "MB"), (1024, "KB")]
+        .into_iter()
+        .find(|(unit, _)| bytes >= *unit)
+    {
+        Some((unit, suffix)) if bytes % unit == 0 => format!("{}{}", bytes / unit, suffix),
+        Some((unit, suffix)) => format!("{:.1}{}", bytes as f64 / unit as f64, suffix),
+        None => format!("{}B", bytes),
+    }
+}
+
+impl Store {
+    /// Bytes the store takes on disk; zero if it does not exist.
+    pub fn size(&self) -> u64 {
+        match self.layout {
+            Layout::Directory => files_by_age(&self.path)
+                .map(|files| files.iter().map(|(_, _, len)| len).sum())
+                .unwrap_or(0),
+            _ => fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0),
+        }
+    }
+
+    /// Apply the store's policy as of `now`. With `dry_run`, only report
+    /// what would be removed.
+    pub fn compact(&self, now: SystemTime, dry_run: bool) -> io::Result<Compaction> {
+        let bytes_before = self.size();
+        let mut compaction = Compaction {
+            store: self.name,
+            path: self.path.clone(),
+            removed: 0,
+            bytes_before,
+            bytes_after: bytes_before,
+        };
+        if self.policy.is_unlimited() || bytes_before == 0 {
+            return Ok(compaction);
+        }
+        let cutoff = self
+            .policy
+            .max_age
+            .and_then(|age| now.checked_sub(age))
+            .map(unix_secs);
+        let _lock = match self.layout {
+            Layout::Directory => None,
+            _ => Some(lock_store(&self.path)?),
+        };
+        let (removed, bytes_after) = match self.layout {
+            Layout::JsonLines(field) => self.compact_lines(Some(field), cutoff, dry_run)?,
+            Layout::Text => self.compact_lines(None, cutoff, dry_run)?,
+            Layout::JsonRecords { key, timestamp } => {
+                self.compact_records(key, timestamp, cutoff, dry_run)?
+            }
+            Layout::Directory => self.compact_directory(cutoff, dry_run)?,
+            Layout::File => self.compact_file(cutoff, dry_run)?,
+        };
+        compaction.removed = removed;
+        compaction.bytes_after = bytes_after;
+        Ok(compaction)
+    }
+
+    fn compact_lines(
+        &self,
+        field: Option<&str>,
+        cutoff: Option<u64>,
+        dry_run: bool,
+    ) -> io::Result<(usize, u64)> {
+        let mut file = OpenOptions::new().read(true).write(true).open(&self.path)?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        let lines: Vec<&str> = content.lines().filter(|l| !l.is_empty()).collect();
+        let expired = |line: &str| match (field, cutoff) {
+            (Some(field), Some(cutoff)) => serde_json::from_str::<serde_json::Value>(line)
+                .ok()
+                .and_then(|record| record_time(&record, field))
+                .is_some_and(|t| t < cutoff),
+            _ => false,
+        };
+        let fresh: Vec<&str> = lines.iter().copied().filter(|l| !expired(l)).collect();
+        let kept = self.newest_within_size(&fresh, |line| line.len() as u64 + 1);
+        let removed = lines.len() - kept.len();
+        let mut body: String = kept.iter().map(|l| format!("{}\n", l)).collect();
+        if removed > 0 && !dry_run {
+            // Writers that hold an open file, like the daemon's log, do not
+            // take the lock; keep what they appended since the read
+            file.seek(SeekFrom::Start(content.len() as u64))?;
+            file.read_to_string(&mut body)?;
+            // In place rather than renamed, so appenders keep their file
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+            file.write_all(body.as_bytes())?;
+        }
+        Ok((
+            removed,
+            if removed > 0 {
+                body.len() as u64
+            } else {
+                content.len() as u64
+            },
+        ))
+    }
+
+    fn compact_records(
+        &self,
+        key: Option<&str>,
+        field: &str,
+        cutoff: Option<u64>,
+        dry_run: bool,
+    ) -> io::Result<(usize, u64)> {
+        let content = fs::read_to_string(&self.path)?;
+        let mut document: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let records = match key {
+            Some(key) => document.get_mut(key),
+            None => Some(&mut document),
+        }
+        .and_then(serde_json::Value::as_array_mut)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "expected a list of records"))?;
+
+        let before = records.len();
+        records.retain(|record| {
+            !cutoff.is_some_and(|cutoff| record_time(record, field).is_some_and(|t| t < cutoff))
+        });
+        let kept = self.newest_within_size(records, |record| record.to_string().len() as u64 + 1);
+        let removed = before - kept.len();
+        *records = kept.to_vec();
+        if removed == 0 {
+            return Ok((0, content.len() as u64));
+        }
+        let body = serde_json::to_string_pretty(&document)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        if !dry_run {
+            let tmp = self.path.with_extension("json.tmp");
+            fs::write(&tmp, &body)?;
+            fs::rename(&tmp, &self.path)?;
+        }
+        Ok((removed, body.len() as u64))
+    }
+
+    fn compact_directory(&self, cutoff: Option<u64>, dry_run: bool) -> io::Result<(usize, u64)> {
+        let files = files_by_age(&self.path)?;
+        let fresh: Vec<&(PathBuf, u64, u64)> = files
+            .iter()
+            .filter(|(_, modified, _)| !cutoff.is_some_and(|cutoff| *modified < cutoff))
+            .collect();
+        let kept = self.newest_within_size(&fresh, |(_, _, len)| *len);
+        let mut removed = 0;
+        for (path, _, _) in &files {
+            if kept.iter().any(|(kept, _, _)| kept == path) {
+                continue;
+            }
+            if !dry_run {
+                fs::remove_file(path)?;
+            }
+            removed += 1;
+        }
+        Ok((removed, kept.iter().map(|(_, _, len)| len).sum()))
+    }
+
+    fn compact_file(&self, cutoff: Option<u64>, dry_run: bool) -> io::Result<(usize, u64)> {
+        let metadata = fs::metadata(&self.path)?;
+        let modified = metadata.modified().map(unix_secs).unwrap_or(u64::MAX);
+        let too_old = cutoff.is_some_and(|cutoff| modified < cutoff);
+        let too_big = self
+            .policy
+            .max_bytes
+            .is_some_and(|max| metadata.len() > max);
+        if !(too_old || too_big) {
+            return Ok((0, metadata.len()));
+        }
+        if !dry_run {
+            fs::remove_file(&self.path)?;
+        }
+        Ok((1, 0))
+    }
+
+    /// The newest of `items` (oldest first) that fit in `max_bytes`
+    /// together, still oldest first.
+    fn newest_within_size<'a, T>(&self, items: &'a [T], size: impl Fn(&T) -> u64) -> &'a [T] {
+        let Some(max) = self.policy.max_bytes else {
+            return items;
+        };
+        let mut total = 0;
+        let mut start = items.len();
+        while start > 0 && total + size(&items[start - 1]) <= max {
+            total += size(&items[start - 1]);
+            start -= 1;
+        }
+        &items[start..]
+    }
+}
+
+/// Compact every store, reporting failures per store.
+pub fn compact_all(
+    stores: &[Store],
+    now: SystemTime,
+    dry_run: bool,
+) -> Vec<Result<Compaction, String>> {
+    stores
+        .iter()
+        .map(|store| {
+            store
+                .compact(now, dry_run)
+                .map_err(|e| format!("{} ({}): {}", store.name, store.path.display(), e))
+        })
+        .collect()
+}
+
+/// Files in `dir` with their modification time and size, oldest first.
+fn files_by_age(dir: &Path) -> io::Result<Vec<(PathBuf, u64, u64)>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            let modified = metadata.modified().map(unix_secs).unwrap_or(u64::MAX);
+            files.push((entry.path(), modified, metadata.len()));
+        }
+    }
+    files.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+    Ok(files)
+}
+
+/// A record's time in unix seconds, from a number or an RFC 3339 string.
+fn record_time(record: &serde_json::Value, field: &str) -> Option<u64> {
+    match record.get(field)? {
+        serde_json::Value::Number(n) => n.as_u64(),
+        serde_json::Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .ok()
+            .and_then(|t| u64::try_from(t.timestamp()).ok()),
+        _ => None,
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store(dir: &Path, name: &'static str, layout: Layout, policy: RetentionPolicy) -> Store {
+        Store {
+            name,
+            description: "",
+            path: dir.join(name),
+            layout,
+            policy,
+        }
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        assert_eq!(parse_age("30d"), Ok(DAY * 30));
+        assert_eq!(parse_age("45m"), Ok(Duration::from_secs(2700)));
+        assert!(parse_age("30").is_err());
+        assert_eq!(parse_size("64MB"), Ok(64 * MB));
+        assert_eq!(parse_size("512 kb"), Ok(512 * 1024));
+        assert!(parse_size("lots").is_err());
+        assert_eq!(format_age(DAY * 7), "7d");
+        assert_eq!(format_size(1536), "1.5KB");
+
+        let mut stores = stores(Path::new("/tmp"));
+        let mut overrides = BTreeMap::new();
+        overrides.insert(
+            "dead-letters".to_string(),
+            PolicyOverride {
+                max_age: Some("off".into()),
+                max_size: Some("1MB".into()),
+            },
+        );
+        apply_overrides(&mut stores, &overrides).unwrap();
+        let dlq = stores.iter().find(|s| s.name == "dead-letters").unwrap();
+        assert_eq!(dlq.policy.max_age, None);
+        assert_eq!(dlq.policy.max_bytes, Some(MB));
+        overrides.insert("nowhere".to_string(), PolicyOverride::default());
+        assert!(apply_overrides(&mut stores, &overrides).is_err());
+    }
+
+    #[test]
+    fn test_store_lock_is_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("threat_rules.json");
+        let held = lock_store(&path).unwrap();
+        let other = fs::File::open(dir.path().join("threat_rules.json.lock")).unwrap();
+        assert!(other.try_lock_exclusive().is_err());
+        drop(held);
+        assert!(other.try_lock_exclusive().is_ok());
+    }
+
+    #[test]
+    fn test_compaction_by_age_and_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = UNIX_EPOCH + Duration::from_secs(100 * DAY.as_secs());
+        let days_ago = |d: u64| 100 * DAY.as_secs() - d * DAY.as_secs();
+
+        // Expired lines go first; lines without a time are kept
+        let lines = store(
+            dir.path(),
+            "dlq.jsonl",
+            Layout::JsonLines("failed_at"),
+            RetentionPolicy {
+                max_age: Some(DAY * 30),
+                max_bytes: None,
+            },
+        );
+        let content = format!(
+            "{{\"failed_at\":{}}}\n{{\"failed_at\":{}}}\nnot json\n",
+            days_ago(40),
+            days_ago(1)
+        );
+        fs::write(&lines.path, &content).unwrap();
+        let report = lines.compact(now, true).unwrap();
+        assert_eq!(report.removed, 1);
+        assert_eq!(fs::read_to_string(&lines.path).unwrap(), content);
+        lines.compact(now, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&lines.path).unwrap(),
+            format!("{{\"failed_at\":{}}}\nnot json\n", days_ago(1))
+        );
+
+        // Then the oldest records until the store fits
+        let records = store(
+            dir.path(),
+            "events.json",
+            Layout::JsonRecords {
+                key: Some("events"),
+                timestamp: "timestamp",
+            },
+            RetentionPolicy {
+                max_age: None,
+                max_bytes: Some(50),
+            },
+        );
+        let events: Vec<_> = (0..5)
+            .map(|i| serde_json::json!({ "timestamp": i, "n": i }))
+            .collect();
+        fs::write(
+            &records.path,
+            serde_json::json!({ "events": events }).to_string(),
+        )
+        .unwrap();
+        let report = records.compact(now, false).unwrap();
+        assert_eq!(report.removed, 3);
+        let kept: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&records.path).unwrap()).unwrap();
+        assert_eq!(kept["events"][0]["n"], 3);
+
+        // Unlimited stores are left alone
+        let evidence = store(
+            dir.path(),
+            "evidence.json",
+            Layout::File,
+            RetentionPolicy::default(),
+        );
+        fs::write(&evidence.path, "{}").unwrap();
+        assert_eq!(evidence.compact(now, false).unwrap().removed, 0);
+        assert!(evidence.path.exists());
+    }
+}
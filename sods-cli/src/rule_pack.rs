@@ -0,0 +1,326 @@
+//! Merging of signed rule packs into local monitoring state.
+//!
+//! A `RulePack` bundles threat rules, watchlist entries, symbol plugins and
+//! pattern presets. Importing one checks its dependencies against the packs
+//! already installed, then merges each item by key (rule id, address,
+//! plugin symbol, preset name) under a `ConflictPolicy`.
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use sods_core::plugins::SymbolPlugin;
+use sods_p2p::threats::parse_version;
+use sods_p2p::{PatternPreset, RulePack, ThreatRule, WatchlistEntry};
+use std::fmt;
+
+/// How to resolve an incoming item whose key already exists locally.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ConflictPolicy {
+    /// Keep the local item
+    Keep,
+    /// Always take the pack's item
+    Replace,
+    /// Take the pack's rule only if its timestamp is newer; other items
+    /// carry no timestamp and are replaced
+    Newer,
+}
+
+/// Record of an installed pack (`~/.sods/packs.json`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledPack {
+    pub name: String,
+    pub version: String,
+    /// Hex-encoded author public key
+    pub author: String,
+    /// Pack creation timestamp
+    pub timestamp: u64,
+}
+
+/// Local monitoring state a pack is merged into.
+#[derive(Debug, Clone, Default)]
+pub struct LocalState {
+    pub rules: Vec<ThreatRule>,
+    pub watchlist: Vec<WatchlistEntry>,
+    pub plugins: Vec<SymbolPlugin>,
+    pub presets: Vec<PatternPreset>,
+    pub packs: Vec<InstalledPack>,
+}
+
+/// Why a pack cannot be imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    /// A declared dependency is missing or too old
+    MissingDependency {
+        name: String,
+        min_version: String,
+        installed: Option<String>,
+    },
+    /// The same pack is already installed at a newer version
+    Downgrade { installed: String, offered: String },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackError::MissingDependency {
+                name,
+                min_version,
+                installed: Some(v),
+            } => write!(f, "Requires {} >= {} (installed: {})", name, min_version, v),
+            PackError::MissingDependency {
+                name, min_version, ..
+            } => write!(f, "Requires {} >= {} (not installed)", name, min_version),
+            PackError::Downgrade { installed, offered } => write!(
+                f,
+                "Version {} is older than installed version {}",
+                offered, installed
+            ),
+        }
+    }
+}
+
+/// Outcome of merging one pack.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Items that did not exist locally
+    pub added: usize,
+    /// Existing items overwritten by the pack
+    pub replaced: usize,
+    /// Keys of conflicting items where the local version was kept
+    pub kept: Vec<String>,
+    /// Symbols of plugins that were added or replaced
+    pub changed_plugins: Vec<String>,
+}
+
+/// Checks `pack` against the installed packs.
+///
+/// Every dependency must be installed at `min_version` or later, and a pack
+/// already installed under the same name may not be downgraded unless
+/// `allow_downgrade` is set.
+pub fn check_dependencies(
+    pack: &RulePack,
+    installed: &[InstalledPack],
+    allow_downgrade: bool,
+) -> Result<(), PackError> {
+    for dep in &pack.dependencies {
+        let current = installed.iter().find(|p| p.name == dep.name);
+        let satisfied = current.is_some_and(|p| {
+            parse_version(&p.version).is_some()
+                && parse_version(&p.version) >= parse_version(&dep.min_version)
+        });
+        if !satisfied {
+            return Err(PackError::MissingDependency {
+                name: dep.name.clone(),
+                min_version: dep.min_version.clone(),
+                installed: current.map(|p| p.version.clone()),
+            });
+        }
+    }
+
+    if !allow_downgrade {
+        if let Some(current) = installed.iter().find(|p| p.name == pack.name) {
+            if parse_version(&pack.version) < parse_version(&current.version) {
+                return Err(PackError::Downgrade {
+                    installed: current.version.clone(),
+                    offered: pack.version.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Merges the contents of `pack` into `state` and records it as installed.
+///
+/// The pack must already be verified and its dependencies checked.
+pub fn merge(state: &mut LocalState, pack: &RulePack, policy: ConflictPolicy) -> ImportReport {
+    let mut report = ImportReport::default();
+
+    for rule in &pack.rules {
+        match state.rules.iter_mut().find(|r| r.id == rule.id) {
+            None => {
+                state.rules.push(rule.clone());
+                report.added += 1;
+            }
+            Some(existing) => {
+                let take = match policy {
+                    ConflictPolicy::Keep => false,
+                    ConflictPolicy::Replace => true,
+                    ConflictPolicy::Newer => rule.timestamp > existing.timestamp,
+                };
+                if take {
+                    *existing = rule.clone();
+                    report.replaced += 1;
+                } else {
+                    report.kept.push(format!("rule:{}", rule.id));
+                }
+            }
+        }
+    }
+
+    for entry in &pack.watchlist {
+        let key = format!("watchlist:{:?}", entry.address);
+        merge_item(&mut state.watchlist, entry, policy, key, &mut report, |e| {
+            e.address == entry.address
+        });
+    }
+
+    for preset in &pack.presets {
+        let key = format!("preset:{}", preset.name);
+        merge_item(&mut state.presets, preset, policy, key, &mut report, |p| {
+            p.name == preset.name
+        });
+    }
+
+    for plugin in &pack.plugins {
+        let key = format!("plugin:{}", plugin.symbol);
+        let before = report.added + report.replaced;
+        merge_item(&mut state.plugins, plugin, policy, key, &mut report, |p| {
+            p.symbol == plugin.symbol
+        });
+        if report.added + report.replaced > before {
+            report.changed_plugins.push(plugin.symbol.clone());
+        }
+    }
+
+    state.packs.retain(|p| p.name != pack.name);
+    state.packs.push(InstalledPack {
+        name: pack.name.clone(),
+        version: pack.version.clone(),
+        author: hex::encode(&pack.author_pubkey),
+        timestamp: pack.timestamp,
+    });
+
+    report
+}
+
+/// Merges an item without its own timestamp; identical items are no-ops.
+fn merge_item<T: Clone + Serialize>(
+    items: &mut Vec<T>,
+    incoming: &T,
+    policy: ConflictPolicy,
+    key: String,
+    report: &mut ImportReport,
+    same_key: impl Fn(&T) -> bool,
+) {
+    let Some(existing) = items.iter_mut().find(|item| same_key(item)) else {
+        items.push(incoming.clone());
+        report.added += 1;
+        return;
+    };
+    if serde_json::to_value(&*existing).ok() == serde_json::to_value(incoming).ok() {
+        return;
+    }
+    if policy == ConflictPolicy::Keep {
+        report.kept.push(key);
+    } else {
+        *existing = incoming.clone();
+        report.replaced += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::core::k256::ecdsa::SigningKey;
+    use ethers::types::Address;
+    use sods_p2p::PackDependency;
+
+    fn key() -> SigningKey {
+        SigningKey::from_slice(&[3u8; 32]).unwrap()
+    }
+
+    fn pack(name: &str, version: &str, rules: Vec<ThreatRule>) -> RulePack {
+        RulePack::new(
+            name,
+            version,
+            "test",
+            vec![],
+            rules,
+            vec![WatchlistEntry {
+                address: Address::repeat_byte(0xAB),
+                label: "drainer".to_string(),
+                chain: None,
+            }],
+            vec![],
+            vec![PatternPreset {
+                name: "Drain".to_string(),
+                pattern: "Tf{3,}".to_string(),
+            }],
+            &key(),
+        )
+    }
+
+    fn rule(id: &str, timestamp: u64) -> ThreatRule {
+        let mut rule = ThreatRule::new(id, id, "Tf", "base", "high", &key());
+        rule.timestamp = timestamp;
+        rule.sign(&key());
+        rule
+    }
+
+    #[test]
+    fn test_merge_conflict_policies() {
+        let mut state = LocalState {
+            rules: vec![rule("a", 100), rule("b", u64::MAX)],
+            presets: vec![PatternPreset {
+                name: "Drain".to_string(),
+                pattern: "Tf".to_string(),
+            }],
+            ..Default::default()
+        };
+        let incoming = pack("P", "1.0.0", vec![rule("a", 200), rule("b", 200)]);
+
+        let mut kept = state.clone();
+        let report = merge(&mut kept, &incoming, ConflictPolicy::Keep);
+        assert_eq!(report.added, 1);
+        assert_eq!(report.replaced, 0);
+        assert_eq!(report.kept.len(), 3);
+
+        let report = merge(&mut state, &incoming, ConflictPolicy::Newer);
+        assert_eq!(report.added, 1);
+        assert_eq!(report.replaced, 2);
+        assert_eq!(report.kept, vec!["rule:b".to_string()]);
+        assert_eq!(state.rules[0].timestamp, 200);
+        assert_eq!(state.presets[0].pattern, "Tf{3,}");
+        assert_eq!(state.packs[0].name, "P");
+
+        // Re-importing the same pack is a no-op
+        let report = merge(&mut state, &incoming, ConflictPolicy::Newer);
+        assert_eq!(report.added + report.replaced, 0);
+        assert_eq!(state.packs.len(), 1);
+    }
+
+    #[test]
+    fn test_dependency_and_downgrade_checks() {
+        let mut dependent = pack("Child", "1.0.0", vec![]);
+        dependent.dependencies.push(PackDependency {
+            name: "Base".to_string(),
+            min_version: "1.2.0".to_string(),
+        });
+
+        let installed = |name: &str, version: &str| InstalledPack {
+            name: name.to_string(),
+            version: version.to_string(),
+            author: String::new(),
+            timestamp: 0,
+        };
+
+        assert!(matches!(
+            check_dependencies(&dependent, &[], false),
+            Err(PackError::MissingDependency {
+                installed: None,
+                ..
+            })
+        ));
+        assert!(check_dependencies(&dependent, &[installed("Base", "1.1.9")], false).is_err());
+        assert!(check_dependencies(&dependent, &[installed("Base", "1.10.0")], false).is_ok());
+
+        let older = pack("Child", "0.9.0", vec![]);
+        let current = [installed("Child", "1.0.0")];
+        assert!(matches!(
+            check_dependencies(&older, &current, false),
+            Err(PackError::Downgrade { .. })
+        ));
+        assert!(check_dependencies(&older, &current, true).is_ok());
+    }
+}
@@ -0,0 +1,149 @@
+//! Block sampling strategies for quota-bounded discovery.
+//!
+//! Scanning every block of a large range burns through free-tier RPC limits.
+//! A `SamplingStrategy` picks which blocks to fetch, and `extrapolate` turns
+//! the per-block counts of a sample into an estimated total with a 95%
+//! confidence interval.
+
+use clap::ValueEnum;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::Serialize;
+use std::ops::RangeInclusive;
+
+/// z-score for a two-sided 95% confidence interval.
+const Z_95: f64 = 1.96;
+
+/// How `discover` chooses blocks from the requested range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SamplingStrategy {
+    /// Scan every block
+    Full,
+    /// Scan every Nth block
+    EveryNth,
+    /// Scan a seeded uniform random sample
+    Random,
+    /// Skip blocks whose logsBloom rules the symbol out
+    Bloom,
+}
+
+/// Picks every `step`-th block of `range`, newest first.
+pub fn every_nth(range: RangeInclusive<u64>, step: u64) -> Vec<u64> {
+    range.rev().step_by(step.max(1) as usize).collect()
+}
+
+/// Picks `size` distinct blocks of `range` uniformly at random, newest first.
+///
+/// The same seed always yields the same sample.
+pub fn random_sample(range: RangeInclusive<u64>, size: usize, seed: u64) -> Vec<u64> {
+    let start = *range.start();
+    let population = range_len(&range) as usize;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut blocks: Vec<u64> = rand::seq::index::sample(&mut rng, population, size.min(population))
+        .into_iter()
+        .map(|i| start + i as u64)
+        .collect();
+    blocks.sort_unstable_by(|a, b| b.cmp(a));
+    blocks
+}
+
+/// Number of blocks in an inclusive range.
+pub fn range_len(range: &RangeInclusive<u64>) -> u64 {
+    (range.end() + 1).saturating_sub(*range.start())
+}
+
+/// Estimated totals over a block population from a sample of it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Estimate {
+    /// Blocks in the population
+    pub population: u64,
+    /// Blocks actually scanned
+    pub sampled: u64,
+    /// Events observed in the sample
+    pub observed: u64,
+    /// Extrapolated event total
+    pub estimated_total: f64,
+    /// Lower bound of the 95% confidence interval
+    pub ci_low: f64,
+    /// Upper bound of the 95% confidence interval
+    pub ci_high: f64,
+}
+
+/// Extrapolates per-block `counts` of a simple random sample to `population`.
+///
+/// Uses the sample mean with a finite population correction, so a sample
+/// covering the whole population yields an exact interval. The lower bound
+/// never drops below what was actually observed.
+pub fn extrapolate(counts: &[usize], population: u64) -> Estimate {
+    let n = counts.len() as f64;
+    let observed: u64 = counts.iter().map(|&c| c as u64).sum();
+    if counts.is_empty() || population == 0 {
+        return Estimate {
+            population,
+            sampled: counts.len() as u64,
+            observed,
+            estimated_total: 0.0,
+            ci_low: 0.0,
+            ci_high: 0.0,
+        };
+    }
+
+    let big_n = population as f64;
+    let mean = observed as f64 / n;
+    let variance = if counts.len() > 1 {
+        counts
+            .iter()
+            .map(|&c| (c as f64 - mean).powi(2))
+            .sum::<f64>()
+            / (n - 1.0)
+    } else {
+        0.0
+    };
+    let fpc = (1.0 - n / big_n).max(0.0);
+    let std_err = big_n * (variance / n * fpc).sqrt();
+    let estimated_total = big_n * mean;
+
+    Estimate {
+        population,
+        sampled: counts.len() as u64,
+        observed,
+        estimated_total,
+        ci_low: (estimated_total - Z_95 * std_err).max(observed as f64),
+        ci_high: estimated_total + Z_95 * std_err,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_nth_and_random_sample() {
+        assert_eq!(every_nth(100..=110, 5), vec![110, 105, 100]);
+
+        let a = random_sample(1..=100_000, 50, 42);
+        let b = random_sample(1..=100_000, 50, 42);
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 50);
+        assert!(a.windows(2).all(|w| w[0] > w[1]));
+        assert!(a.iter().all(|x| (1..=100_000).contains(x)));
+
+        // Oversized samples are clamped to the population
+        assert_eq!(random_sample(1..=3, 10, 0), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_extrapolate_scales_and_bounds() {
+        let est = extrapolate(&[2, 4, 0, 2], 400);
+        assert_eq!(est.observed, 8);
+        assert!((est.estimated_total - 800.0).abs() < 1e-9);
+        assert!(est.ci_low < 800.0 && est.ci_high > 800.0);
+        assert!(est.ci_low >= 8.0);
+
+        // A census has no sampling error
+        let census = extrapolate(&[1, 2, 3], 3);
+        assert_eq!(census.ci_low, 6.0);
+        assert_eq!(census.ci_high, 6.0);
+    }
+}
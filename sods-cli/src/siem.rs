@@ -0,0 +1,504 @@
+//! Syslog/CEF alert sink for SIEM pipelines.
+//!
+//! With `daemon start --siem udp://collector:514`, every behavioral alert is
+//! also sent to a syslog collector, either as an ArcSight CEF event (the
+//! default, understood by Splunk, QRadar and most SIEMs without a custom
+//! parser) or as a plain RFC 5424 message with structured data. Unlike
+//! notifications and webhooks, the SIEM sees alerts that a digest holds
+//! back, since correlation is its job. Field mapping is documented in
+//! `docs/SIEM.md`.
+
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+
+/// Syslog facility used for all messages (`local0`).
+const FACILITY_LOCAL0: u8 = 16;
+
+/// SD-ID of the structured-data element in `syslog` messages. 32473 is the
+/// example enterprise number reserved by RFC 5612.
+pub const SD_ID: &str = "sods@32473";
+
+/// APP-NAME and MSGID of every message.
+const APP_NAME: &str = "sods";
+const MSG_ID: &str = "behavioral_alert";
+
+/// Send timeout for TCP collectors.
+const SEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Message body written to the collector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiemFormat {
+    /// ArcSight Common Event Format inside an RFC 5424 envelope
+    Cef,
+    /// RFC 5424 with the alert as structured data
+    Syslog,
+}
+
+impl FromStr for SiemFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "cef" => Ok(Self::Cef),
+            "syslog" | "rfc5424" => Ok(Self::Syslog),
+            other => Err(format!(
+                "unknown SIEM format '{}' (expected cef or syslog)",
+                other
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for SiemFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Cef => "cef",
+            Self::Syslog => "syslog",
+        })
+    }
+}
+
+/// Syslog transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SiemTransport {
+    /// One datagram per message (RFC 5426)
+    Udp,
+    /// Newline-terminated messages over one connection per alert (RFC 6587)
+    Tcp,
+}
+
+/// Collector address, e.g. `udp://10.0.0.5:514` or `tcp://siem.local:601`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiemTarget {
+    pub transport: SiemTransport,
+    /// `host:port`, resolved on every send
+    pub address: String,
+}
+
+impl FromStr for SiemTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (transport, address) = if let Some(rest) = s.strip_prefix("udp://") {
+            (SiemTransport::Udp, rest)
+        } else if let Some(rest) = s.strip_prefix("tcp://") {
+            (SiemTransport::Tcp, rest)
+        } else {
+            return Err(format!(
+                "SIEM target '{}' must start with udp:// or tcp://",
+                s
+            ));
+        };
+        let address = address.trim_end_matches('/');
+        match address.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && port.parse::<u16>().is_ok() => Ok(Self {
+                transport,
+                address: address.to_string(),
+            }),
+            _ => Err(format!("SIEM target '{}' needs a host and port", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for SiemTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scheme = match self.transport {
+            SiemTransport::Udp => "udp",
+            SiemTransport::Tcp => "tcp",
+        };
+        write!(f, "{}://{}", scheme, self.address)
+    }
+}
+
+/// One behavioral alert, flattened for SIEM fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SiemAlert {
+    pub timestamp: DateTime<Utc>,
+    pub alert_id: String,
+    pub chain: String,
+    pub chain_id: u64,
+    pub block_number: u64,
+    pub threat_name: String,
+    pub severity: String,
+    pub pattern: String,
+    /// Matched symbols in order
+    pub symbols: Vec<String>,
+    /// Sender of the first matched symbol
+    pub source: Option<String>,
+    /// Recipient of the first matched symbol
+    pub destination: Option<String>,
+    /// Transaction of the first matched symbol
+    pub tx_hash: Option<String>,
+    pub block_url: Option<String>,
+}
+
+impl SiemAlert {
+    /// Stable per-rule identifier: the first 8 bytes of the pattern's
+    /// canonical hash, or of `Keccak256(pattern)` if it does not parse.
+    pub fn signature_id(&self) -> String {
+        let hash = match sods_core::pattern::BehavioralPattern::parse(&self.pattern) {
+            Ok(pattern) => pattern.canonical_hash(),
+            Err(_) => ethers_core::utils::keccak256(self.pattern.as_bytes()),
+        };
+        format!("sods-{}", hex::encode(&hash[..8]))
+    }
+}
+
+/// CEF severity (0-10) for a rule severity label.
+pub fn cef_severity(severity: &str) -> u8 {
+    match crate::digest::severity_rank(severity) {
+        0 => 1,
+        1 => 3,
+        3 => 8,
+        4 => 10,
+        _ => 5,
+    }
+}
+
+/// Syslog severity (RFC 5424 §6.2.1) for a rule severity label.
+pub fn syslog_severity(severity: &str) -> u8 {
+    match crate::digest::severity_rank(severity) {
+        0 => 6, // informational
+        1 => 5, // notice
+        3 => 3, // error
+        4 => 2, // critical
+        _ => 4, // warning
+    }
+}
+
+/// Delivers alerts to a syslog collector.
+#[derive(Debug, Clone)]
+pub struct SiemSink {
+    target: SiemTarget,
+    format: SiemFormat,
+    hostname: String,
+}
+
+impl SiemSink {
+    pub fn new(target: SiemTarget, format: SiemFormat) -> Self {
+        let hostname = sysinfo::System::host_name()
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(|| "-".to_string());
+        Self {
+            target,
+            format,
+            hostname,
+        }
+    }
+
+    /// Override the HOSTNAME header field (Builder pattern).
+    pub fn with_hostname(mut self, hostname: impl Into<String>) -> Self {
+        self.hostname = hostname.into();
+        self
+    }
+
+    pub fn target(&self) -> &SiemTarget {
+        &self.target
+    }
+
+    pub fn format(&self) -> SiemFormat {
+        self.format
+    }
+
+    /// The complete syslog line for an alert, without a trailing newline.
+    pub fn render(&self, alert: &SiemAlert) -> String {
+        let pri = FACILITY_LOCAL0 * 8 + syslog_severity(&alert.severity);
+        let header = format!(
+            "<{}>1 {} {} {} {} {}",
+            pri,
+            alert.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            self.hostname,
+            APP_NAME,
+            std::process::id(),
+            MSG_ID
+        );
+        match self.format {
+            SiemFormat::Cef => format!("{} - {}", header, to_cef(alert)),
+            SiemFormat::Syslog => format!(
+                "{} {} {} ({}) detected on {} block #{}",
+                header,
+                structured_data(alert),
+                strip_controls(&alert.threat_name),
+                strip_controls(&alert.severity),
+                strip_controls(&alert.chain),
+                alert.block_number
+            ),
+        }
+    }
+
+    /// Send one alert. Blocking; the daemon calls it off the async runtime.
+    pub fn send(&self, alert: &SiemAlert) -> io::Result<()> {
+        let line = self.render(alert);
+        let addr = self
+            .target
+            .address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} did not resolve", self.target.address),
+                )
+            })?;
+        match self.target.transport {
+            SiemTransport::Udp => {
+                let bind = if addr.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = UdpSocket::bind(bind)?;
+                socket.send_to(line.as_bytes(), addr)?;
+            }
+            SiemTransport::Tcp => {
+                let mut stream = TcpStream::connect_timeout(&addr, SEND_TIMEOUT)?;
+                stream.set_write_timeout(Some(SEND_TIMEOUT))?;
+                stream.write_all(format!("{}\n", line).as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `CEF:0|SODS|sods-daemon|<version>|<signature>|<name>|<severity>|<extension>`
+pub fn to_cef(alert: &SiemAlert) -> String {
+    let mut ext = String::new();
+    let mut field = |key: &str, value: &str| {
+        if !ext.is_empty() {
+            ext.push(' ');
+        }
+        let _ = write!(ext, "{}={}", key, escape_cef_extension(value));
+    };
+
+    field("rt", &alert.timestamp.timestamp_millis().to_string());
+    field("externalId", &alert.alert_id);
+    field("cat", MSG_ID);
+    field("cs1Label", "chain");
+    field("cs1", &alert.chain);
+    field("cn1Label", "chainId");
+    field("cn1", &alert.chain_id.to_string());
+    field("cn2Label", "blockNumber");
+    field("cn2", &alert.block_number.to_string());
+    field("cs2Label", "pattern");
+    field("cs2", &alert.pattern);
+    field("cs3Label", "matchedSymbols");
+    field("cs3", &alert.symbols.join(","));
+    field("cnt", &alert.symbols.len().to_string());
+    if let Some(tx) = &alert.tx_hash {
+        field("cs4Label", "txHash");
+        field("cs4", tx);
+    }
+    if let Some(src) = &alert.source {
+        field("suser", src);
+    }
+    if let Some(dst) = &alert.destination {
+        field("duser", dst);
+    }
+    if let Some(url) = &alert.block_url {
+        field("request", url);
+    }
+
+    format!(
+        "CEF:0|SODS|sods-daemon|{}|{}|{}|{}|{}",
+        escape_cef_header(env!("CARGO_PKG_VERSION")),
+        escape_cef_header(&alert.signature_id()),
+        escape_cef_header(&alert.threat_name),
+        cef_severity(&alert.severity),
+        ext
+    )
+}
+
+/// `[sods@32473 chain="..." ...]`
+fn structured_data(alert: &SiemAlert) -> String {
+    let mut sd = format!("[{}", SD_ID);
+    let mut param = |name: &str, value: &str| {
+        let _ = write!(sd, " {}=\"{}\"", name, escape_sd_value(value));
+    };
+    param("alertId", &alert.alert_id);
+    param("chain", &alert.chain);
+    param("chainId", &alert.chain_id.to_string());
+    param("block", &alert.block_number.to_string());
+    param("threat", &alert.threat_name);
+    param("severity", &alert.severity);
+    param("signature", &alert.signature_id());
+    param("pattern", &alert.pattern);
+    param("symbols", &alert.symbols.join(","));
+    if let Some(tx) = &alert.tx_hash {
+        param("tx", tx);
+    }
+    if let Some(src) = &alert.source {
+        param("from", src);
+    }
+    if let Some(dst) = &alert.destination {
+        param("to", dst);
+    }
+    if let Some(url) = &alert.block_url {
+        param("blockUrl", url);
+    }
+    sd.push(']');
+    sd
+}
+
+/// CEF header fields escape `\` and `|`; line breaks are flattened.
+fn escape_cef_header(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace(['\r', '\n'], " ")
+}
+
+/// CEF extension values escape `\` and `=`, and encode line breaks.
+fn escape_cef_extension(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// RFC 5424 PARAM-VALUEs escape `"`, `\` and `]`; control characters
+/// are dropped so a value cannot start a forged line.
+fn escape_sd_value(value: &str) -> String {
+    strip_controls(value)
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
+
+/// Replaces control characters (CR, LF, NUL, ...) with spaces, so
+/// attacker-chosen fields such as token names stay on one syslog line.
+fn strip_controls(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alert() -> SiemAlert {
+        SiemAlert {
+            timestamp: DateTime::parse_from_rfc3339("2024-01-02T03:04:05.678Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            alert_id: "alert_1_ab".into(),
+            chain: "sepolia".into(),
+            chain_id: 11155111,
+            block_number: 42,
+            threat_name: "Sandwich|Bot".into(),
+            severity: "critical".into(),
+            pattern: "Tf -> Sw where value > 1 ether".into(),
+            symbols: vec!["Tf".into(), "Sw".into()],
+            source: Some("0xaa".into()),
+            destination: None,
+            tx_hash: Some("0xbb".into()),
+            block_url: None,
+        }
+    }
+
+    #[test]
+    fn test_signature_id_follows_the_canonical_pattern() {
+        let spaced = alert();
+        let compact = SiemAlert {
+            pattern: "Tf->Sw where value > 1 ether".into(),
+            ..alert()
+        };
+        assert_eq!(spaced.signature_id(), compact.signature_id());
+
+        let other = SiemAlert {
+            pattern: "Tf -> Sw".into(),
+            ..alert()
+        };
+        assert_ne!(spaced.signature_id(), other.signature_id());
+
+        // Unparseable patterns still get a stable ID
+        let raw = SiemAlert {
+            pattern: "Tf ->".into(),
+            ..alert()
+        };
+        assert_eq!(raw.signature_id(), raw.signature_id());
+        assert!(raw.signature_id().starts_with("sods-"));
+    }
+
+    #[test]
+    fn test_cef_layout_and_escaping() {
+        let cef = to_cef(&alert());
+        let prefix = format!(
+            "CEF:0|SODS|sods-daemon|{}|{}|Sandwich\\|Bot|10|",
+            env!("CARGO_PKG_VERSION"),
+            alert().signature_id()
+        );
+        assert!(cef.starts_with(&prefix), "{}", cef);
+        assert!(cef.contains("rt=1704164645678 "));
+        assert!(cef.contains("cs2=Tf -> Sw where value > 1 ether "));
+        assert!(cef.contains("cn2=42 "));
+        assert!(cef.contains("cs4=0xbb suser=0xaa"));
+        assert!(!cef.contains("duser="));
+        assert_eq!(escape_cef_extension("a=b\\c\n"), "a\\=b\\\\c\\n");
+    }
+
+    #[test]
+    fn test_render_envelopes() {
+        let sink = SiemSink::new("udp://127.0.0.1:514".parse().unwrap(), SiemFormat::Cef)
+            .with_hostname("node1");
+        let line = sink.render(&alert());
+        // local0 * 8 + critical
+        assert!(line.starts_with("<130>1 2024-01-02T03:04:05.678Z node1 sods "));
+        assert!(line.contains(" behavioral_alert - CEF:0|"));
+
+        let sink = SiemSink::new(sink.target().clone(), SiemFormat::Syslog).with_hostname("node1");
+        let line = sink.render(&alert());
+        assert!(line.contains("[sods@32473 alertId=\"alert_1_ab\" chain=\"sepolia\""));
+        assert!(line.ends_with("] Sandwich|Bot (critical) detected on sepolia block #42"));
+        assert_eq!(escape_sd_value("a\"]"), "a\\\"\\]");
+    }
+
+    #[test]
+    fn test_syslog_strips_line_breaks() {
+        let mut forged = alert();
+        forged.threat_name = "Drain\n<130>1 2024-01-02T03:04:05Z evil sods - - fake".into();
+        forged.pattern = "Tf\r\n<130>1 injected\"]".into();
+        let sink = SiemSink::new("udp://127.0.0.1:514".parse().unwrap(), SiemFormat::Syslog);
+        let line = sink.render(&forged);
+        assert!(!line.contains(['\r', '\n']), "{}", line);
+        assert!(line.contains("pattern=\"Tf  <130>1 injected\\\"\\]\""));
+        assert_eq!(escape_sd_value("a\u{0}b"), "a b");
+    }
+
+    #[test]
+    fn test_target_parsing() {
+        let t: SiemTarget = "tcp://siem.local:601".parse().unwrap();
+        assert_eq!(t.transport, SiemTransport::Tcp);
+        assert_eq!(t.to_string(), "tcp://siem.local:601");
+        assert!("siem.local:514".parse::<SiemTarget>().is_err());
+        assert!("udp://siem.local".parse::<SiemTarget>().is_err());
+        assert!("udp://:514".parse::<SiemTarget>().is_err());
+        assert_eq!("CEF".parse::<SiemFormat>(), Ok(SiemFormat::Cef));
+        assert!("json".parse::<SiemFormat>().is_err());
+    }
+
+    #[test]
+    fn test_udp_delivery() {
+        let collector = UdpSocket::bind("127.0.0.1:0").unwrap();
+        collector
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let target = SiemTarget {
+            transport: SiemTransport::Udp,
+            address: collector.local_addr().unwrap().to_string(),
+        };
+        let sink = SiemSink::new(target, SiemFormat::Syslog);
+        sink.send(&alert()).unwrap();
+
+        let mut buf = [0u8; 2048];
+        let n = collector.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], sink.render(&alert()).as_bytes());
+    }
+}
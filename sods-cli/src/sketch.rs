@@ -0,0 +1,237 @@
+//! Bounded-memory sketches for trend leaderboards.
+//!
+//! `TopK` (Space-Saving) tracks the heaviest hitters of a stream in fixed
+//! space; `HyperLogLog` estimates how many distinct keys were seen. Together
+//! they let `sods trend` report "one address produced 80% of Sw events"
+//! without keeping every address of the window in memory.
+
+use ethers::types::Address;
+use ethers::utils::keccak256;
+use serde::Serialize;
+use sods_core::symbol::BehavioralSymbol;
+use std::collections::HashMap;
+
+/// Space-Saving heavy-hitter sketch over addresses.
+///
+/// Counts are overestimates by at most the reported `error`; any key whose
+/// true count exceeds `total / capacity` is guaranteed to be tracked.
+#[derive(Debug, Clone)]
+pub struct TopK {
+    capacity: usize,
+    counters: HashMap<Address, (u64, u64)>,
+}
+
+/// One tracked key of a `TopK` sketch.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HeavyHitter {
+    pub address: Address,
+    /// Estimated occurrences (upper bound)
+    pub count: u64,
+    /// Maximum overestimation of `count`
+    pub error: u64,
+}
+
+impl TopK {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            counters: HashMap::new(),
+        }
+    }
+
+    pub fn offer(&mut self, key: Address) {
+        if let Some(entry) = self.counters.get_mut(&key) {
+            entry.0 += 1;
+            return;
+        }
+        if self.counters.len() < self.capacity {
+            self.counters.insert(key, (1, 0));
+            return;
+        }
+
+        // Replace the smallest counter, inheriting its count as error
+        let (&victim, &(min, _)) = self
+            .counters
+            .iter()
+            .min_by_key(|(addr, (count, _))| (*count, **addr))
+            .expect("capacity is non-zero");
+        self.counters.remove(&victim);
+        self.counters.insert(key, (min + 1, min));
+    }
+
+    /// The `n` largest counters, highest first.
+    pub fn top(&self, n: usize) -> Vec<HeavyHitter> {
+        let mut hitters: Vec<HeavyHitter> = self
+            .counters
+            .iter()
+            .map(|(&address, &(count, error))| HeavyHitter {
+                address,
+                count,
+                error,
+            })
+            .collect();
+        hitters.sort_by(|a, b| b.count.cmp(&a.count).then(a.address.cmp(&b.address)));
+        hitters.truncate(n);
+        hitters
+    }
+}
+
+/// HyperLogLog distinct-count estimator with 2^`PRECISION` registers.
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    const PRECISION: u32 = 10;
+
+    pub fn new() -> Self {
+        Self {
+            registers: vec![0; 1 << Self::PRECISION],
+        }
+    }
+
+    pub fn insert(&mut self, key: &[u8]) {
+        let digest = keccak256(key);
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&digest[..8]);
+        let hash = u64::from_be_bytes(word);
+
+        let index = (hash >> (64 - Self::PRECISION)) as usize;
+        let rest = hash << Self::PRECISION;
+        let rank = (rest.leading_zeros() + 1).min(64 - Self::PRECISION + 1) as u8;
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Estimated number of distinct keys inserted.
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        // Small-range correction (linear counting)
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Who produced a symbol over a trend window.
+#[derive(Debug, Clone)]
+pub struct SymbolLeaderboard {
+    /// Occurrences of the symbol
+    pub total: u64,
+    actors: TopK,
+    contracts: TopK,
+    distinct_actors: HyperLogLog,
+}
+
+/// Serializable snapshot of a `SymbolLeaderboard`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LeaderboardSummary {
+    pub total: u64,
+    pub distinct_actors: u64,
+    pub top_actors: Vec<HeavyHitter>,
+    pub top_contracts: Vec<HeavyHitter>,
+}
+
+impl SymbolLeaderboard {
+    /// Creates a leaderboard tracking up to `capacity` candidates per sketch.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            total: 0,
+            actors: TopK::new(capacity),
+            contracts: TopK::new(capacity),
+            distinct_actors: HyperLogLog::new(),
+        }
+    }
+
+    /// Records one occurrence. The zero address (mints, burns) is not
+    /// counted as an actor.
+    pub fn observe(&mut self, symbol: &BehavioralSymbol) {
+        self.total += 1;
+        if !symbol.from.is_zero() {
+            self.actors.offer(symbol.from);
+            self.distinct_actors.insert(symbol.from.as_bytes());
+        }
+        if !symbol.contract_address.is_zero() {
+            self.contracts.offer(symbol.contract_address);
+        }
+    }
+
+    /// The top `n` actors and contracts.
+    pub fn summary(&self, n: usize) -> LeaderboardSummary {
+        LeaderboardSummary {
+            total: self.total,
+            distinct_actors: self.distinct_actors.estimate().round() as u64,
+            top_actors: self.actors.top(n),
+            top_contracts: self.contracts.top(n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_k_finds_heavy_hitter() {
+        let mut sketch = TopK::new(4);
+        let whale = Address::repeat_byte(0xEE);
+        for i in 0..1000u64 {
+            if i % 5 != 0 {
+                sketch.offer(whale);
+            } else {
+                sketch.offer(Address::from_low_u64_be(i));
+            }
+        }
+
+        let top = sketch.top(1);
+        assert_eq!(top[0].address, whale);
+        assert!(top[0].count >= 800);
+        assert!(top[0].count - top[0].error <= 800);
+    }
+
+    #[test]
+    fn test_hyperloglog_estimate() {
+        let mut hll = HyperLogLog::new();
+        for i in 0..5000u64 {
+            hll.insert(Address::from_low_u64_be(i).as_bytes());
+            // Duplicates do not inflate the estimate
+            hll.insert(Address::from_low_u64_be(i).as_bytes());
+        }
+        let estimate = hll.estimate();
+        assert!((estimate - 5000.0).abs() / 5000.0 < 0.1, "{}", estimate);
+
+        let mut small = HyperLogLog::new();
+        for i in 0..10u64 {
+            small.insert(&i.to_be_bytes());
+        }
+        assert_eq!(small.estimate().round(), 10.0);
+    }
+
+    #[test]
+    fn test_leaderboard_skips_zero_actor() {
+        let mut board = SymbolLeaderboard::new(8);
+        let mut mint = BehavioralSymbol::new("Tf", 0);
+        mint.contract_address = Address::repeat_byte(1);
+        board.observe(&mint);
+
+        let summary = board.summary(3);
+        assert_eq!(summary.total, 1);
+        assert!(summary.top_actors.is_empty());
+        assert_eq!(summary.top_contracts[0].count, 1);
+    }
+}
@@ -0,0 +1,212 @@
+//! Persistence and comparison of `sods trend` runs.
+//!
+//! Every run is stored as `~/.sods/trends/<chain>-<unix_ts>.json` with its
+//! per-symbol counts, so later runs can report what changed without an
+//! external database.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Characters used to draw sparklines, lowest to highest.
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One persisted trend run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrendRun {
+    /// Run identifier (`<chain>-<unix_ts>`)
+    pub id: String,
+    pub chain: String,
+    pub pattern: String,
+    /// Newest block of the window
+    pub head_block: u64,
+    /// Number of blocks in the window
+    pub window: u64,
+    /// Unix timestamp of the run
+    pub created_at: u64,
+    /// Blocks matching the pattern
+    pub matches: usize,
+    /// Occurrences of each symbol over the window
+    pub symbol_counts: BTreeMap<String, u64>,
+}
+
+impl TrendRun {
+    /// Occurrences of `symbol` per scanned block.
+    pub fn rate(&self, symbol: &str) -> f64 {
+        let count = self.symbol_counts.get(symbol).copied().unwrap_or(0);
+        if self.window == 0 {
+            0.0
+        } else {
+            count as f64 / self.window as f64
+        }
+    }
+}
+
+/// Change of one symbol between two runs.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolDelta {
+    pub symbol: String,
+    pub previous: u64,
+    pub current: u64,
+    /// Change in occurrences per block
+    pub rate_delta: f64,
+    /// Relative change of the per-block rate, if the symbol was seen before
+    pub percent_change: Option<f64>,
+}
+
+/// Per-symbol deltas from `previous` to `current`, normalized by window size
+/// and sorted by magnitude of change.
+pub fn compare(previous: &TrendRun, current: &TrendRun) -> Vec<SymbolDelta> {
+    let symbols: BTreeSet<&String> = previous
+        .symbol_counts
+        .keys()
+        .chain(current.symbol_counts.keys())
+        .collect();
+
+    let mut deltas: Vec<SymbolDelta> = symbols
+        .into_iter()
+        .map(|symbol| {
+            let prev_rate = previous.rate(symbol);
+            let cur_rate = current.rate(symbol);
+            SymbolDelta {
+                symbol: symbol.clone(),
+                previous: previous.symbol_counts.get(symbol).copied().unwrap_or(0),
+                current: current.symbol_counts.get(symbol).copied().unwrap_or(0),
+                rate_delta: cur_rate - prev_rate,
+                percent_change: (prev_rate > 0.0)
+                    .then(|| (cur_rate - prev_rate) / prev_rate * 100.0),
+            }
+        })
+        .collect();
+
+    deltas.sort_by(|a, b| b.rate_delta.abs().total_cmp(&a.rate_delta.abs()));
+    deltas
+}
+
+/// Renders values as a unicode sparkline scaled to their maximum.
+pub fn sparkline(values: &[f64]) -> String {
+    let max = values.iter().copied().fold(0.0_f64, f64::max);
+    values
+        .iter()
+        .map(|&v| {
+            if max <= 0.0 {
+                SPARK_CHARS[0]
+            } else {
+                let idx = ((v / max) * (SPARK_CHARS.len() - 1) as f64).round() as usize;
+                SPARK_CHARS[idx.min(SPARK_CHARS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+/// Directory of persisted trend runs.
+pub struct TrendStore {
+    dir: PathBuf,
+}
+
+impl TrendStore {
+    /// Default store location (`~/.sods/trends`).
+    pub fn default_dir() -> PathBuf {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        home.join(".sods").join("trends")
+    }
+
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Persist a run, returning the file it was written to.
+    pub fn save(&self, run: &TrendRun) -> std::io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("{}.json", run.id));
+        let json = serde_json::to_string_pretty(run)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// All stored runs for `chain`, oldest first. Unreadable files are skipped.
+    pub fn runs(&self, chain: &str) -> Vec<TrendRun> {
+        let mut runs: Vec<TrendRun> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| Self::read(&entry.path()))
+            .filter(|run| run.chain == chain)
+            .collect();
+        runs.sort_by_key(|r| (r.created_at, r.id.clone()));
+        runs
+    }
+
+    /// Resolve `--compare-with`: `latest`, a run id, or a path to a run file.
+    pub fn resolve(&self, reference: &str, chain: &str) -> Option<TrendRun> {
+        if reference == "latest" {
+            return self.runs(chain).pop();
+        }
+        let by_id = self.dir.join(format!("{}.json", reference));
+        Self::read(&by_id).or_else(|| Self::read(Path::new(reference)))
+    }
+
+    fn read(path: &Path) -> Option<TrendRun> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: &str, created_at: u64, window: u64, counts: &[(&str, u64)]) -> TrendRun {
+        TrendRun {
+            id: id.to_string(),
+            chain: "base".to_string(),
+            pattern: "Sw".to_string(),
+            head_block: 100,
+            window,
+            created_at,
+            matches: 0,
+            symbol_counts: counts.iter().map(|(s, c)| (s.to_string(), *c)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_compare_normalizes_by_window() {
+        let prev = run("a", 1, 10, &[("Sw", 10), ("Tf", 5)]);
+        let cur = run("b", 2, 20, &[("Sw", 40), ("Dep", 2)]);
+
+        let deltas = compare(&prev, &cur);
+        assert_eq!(deltas[0].symbol, "Sw");
+        assert!((deltas[0].rate_delta - 1.0).abs() < 1e-9);
+        assert_eq!(deltas[0].percent_change, Some(100.0));
+
+        let dep = deltas.iter().find(|d| d.symbol == "Dep").unwrap();
+        assert_eq!(dep.percent_change, None);
+        let tf = deltas.iter().find(|d| d.symbol == "Tf").unwrap();
+        assert_eq!(tf.current, 0);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 0.5, 1.0]), "▁▅█");
+        assert_eq!(sparkline(&[0.0, 0.0]), "▁▁");
+    }
+
+    #[test]
+    fn test_store_resolves_latest_per_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TrendStore::new(dir.path().to_path_buf());
+        store.save(&run("base-1", 1, 10, &[])).unwrap();
+        store.save(&run("base-2", 2, 10, &[])).unwrap();
+        let mut other = run("ethereum-3", 3, 10, &[]);
+        other.chain = "ethereum".to_string();
+        store.save(&other).unwrap();
+
+        assert_eq!(store.runs("base").len(), 2);
+        assert_eq!(store.resolve("latest", "base").unwrap().id, "base-2");
+        assert_eq!(store.resolve("base-1", "base").unwrap().id, "base-1");
+        assert!(store.resolve("missing", "base").is_none());
+    }
+}
@@ -0,0 +1,183 @@
+//! Unsigned proof bundles for air-gapped signing.
+//!
+//! `export-proof --unsigned FILE` does everything that needs the network
+//! (fetching symbols, building the proof) and writes the proof without a
+//! signature. The file is carried to an offline machine, where
+//! `sods sign-proof` signs the commitment with a key that never touches a
+//! networked host.
+//!
+//! A tampered bundle would get a valid signature over the wrong block or
+//! root, so both sides print the bundle digest (`keccak256` of the bundle's
+//! JSON) and the signer has to confirm it before anything is signed. The
+//! commitment is rebuilt from the proof at signing time rather than stored,
+//! so what is signed is always what the proof claims.
+
+use ethers_core::utils::keccak256;
+use ethers_signers::{LocalWallet, Signer};
+use serde::{Deserialize, Serialize};
+use sods_core::proof::OnChainBehavioralProof;
+use sods_core::BehavioralCommitment;
+use std::fs;
+use std::path::Path;
+
+/// Value of the `type` field, versioning the bundle format.
+pub const BUNDLE_TYPE: &str = "sods-unsigned-proof/v1";
+
+/// Hex characters of the digest the signer must confirm.
+pub const CONFIRM_PREFIX_LEN: usize = 8;
+
+/// A proof waiting for its commitment signature.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedBundle {
+    #[serde(rename = "type")]
+    pub bundle_type: String,
+    /// Pattern the proof was generated for, as typed
+    pub pattern: String,
+    pub proof: OnChainBehavioralProof,
+    /// CID to bind into the commitment, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability_cid: Option<String>,
+    /// Unix timestamp of export
+    pub created_at: u64,
+}
+
+impl UnsignedBundle {
+    pub fn new(
+        pattern: impl Into<String>,
+        mut proof: OnChainBehavioralProof,
+        availability_cid: Option<String>,
+        created_at: u64,
+    ) -> Self {
+        proof.signature = None;
+        Self {
+            bundle_type: BUNDLE_TYPE.to_string(),
+            pattern: pattern.into(),
+            proof,
+            availability_cid,
+            created_at,
+        }
+    }
+
+    /// Read and check a bundle file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let bundle: Self =
+            serde_json::from_str(&json).map_err(|e| format!("Not a proof bundle: {}", e))?;
+        if bundle.bundle_type != BUNDLE_TYPE {
+            return Err(format!("Unsupported bundle type '{}'", bundle.bundle_type));
+        }
+        if bundle.proof.signature.is_some() {
+            return Err("Bundle is already signed".to_string());
+        }
+        Ok(bundle)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json + "\n")
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    /// `keccak256` of the bundle's compact JSON.
+    ///
+    /// Computed over the parsed bundle, so reformatting the file does not
+    /// change it but any change to its content does.
+    pub fn digest(&self) -> [u8; 32] {
+        keccak256(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// The digest as `0x`-prefixed hex.
+    pub fn digest_hex(&self) -> String {
+        format!("0x{}", hex::encode(self.digest()))
+    }
+
+    /// Whether `confirmation` is the digest or at least its first
+    /// `CONFIRM_PREFIX_LEN` hex characters (case-insensitive, `0x` optional).
+    pub fn confirms(&self, confirmation: &str) -> bool {
+        let typed = confirmation.trim().trim_start_matches("0x").to_lowercase();
+        typed.len() >= CONFIRM_PREFIX_LEN && hex::encode(self.digest()).starts_with(&typed)
+    }
+
+    /// The commitment a signature covers, rebuilt from the proof.
+    pub fn commitment(&self) -> BehavioralCommitment {
+        let commitment = BehavioralCommitment::new(
+            self.proof.chain_id,
+            self.proof.block_number,
+            self.proof.receipts_root.unwrap_or([0u8; 32]),
+            self.proof.bmt_root,
+        );
+        match &self.availability_cid {
+            Some(cid) => commitment.with_availability_cid(cid.clone()),
+            None => commitment,
+        }
+    }
+
+    /// Sign the commitment, returning the signed proof.
+    pub async fn sign(self, wallet: &LocalWallet) -> Result<OnChainBehavioralProof, String> {
+        let hash = self.commitment().hash();
+        let mut proof = self.proof;
+        proof.availability_cid = self.availability_cid;
+        let signature = wallet.sign_message(hash).await.map_err(|e| e.to_string())?;
+        proof.signature = Some(signature.to_vec());
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sods_core::{BehavioralMerkleTree, BehavioralSymbol};
+
+    fn bundle() -> UnsignedBundle {
+        let symbols = vec![
+            BehavioralSymbol::new("Tf", 0),
+            BehavioralSymbol::new("Sw", 1),
+        ];
+        let bmt = BehavioralMerkleTree::new(symbols.clone());
+        let matched: Vec<&BehavioralSymbol> = symbols.iter().collect();
+        let mut proof = bmt
+            .generate_onchain_proof(&matched, 1, 100, None, 0)
+            .unwrap();
+        proof.receipts_root = Some([7u8; 32]);
+        UnsignedBundle::new("Tf -> Sw", proof, None, 1_700_000_000)
+    }
+
+    #[test]
+    fn test_digest_survives_roundtrip_and_detects_changes() {
+        let original = bundle();
+        let pretty = serde_json::to_string_pretty(&original).unwrap();
+        let parsed: UnsignedBundle = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(parsed.digest(), original.digest());
+
+        let hex = original.digest_hex();
+        assert!(original.confirms(&hex));
+        assert!(original.confirms(&hex[2..2 + CONFIRM_PREFIX_LEN].to_uppercase()));
+        assert!(!original.confirms(&hex[2..2 + CONFIRM_PREFIX_LEN - 1]));
+
+        // Substituting the block changes both digest and commitment
+        let mut substituted = original.clone();
+        substituted.proof.block_number = 101;
+        assert!(!substituted.confirms(&hex));
+        assert_ne!(
+            substituted.commitment().hash(),
+            original.commitment().hash()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sign_covers_rebuilt_commitment() {
+        let bundle = bundle();
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+        let hash = bundle.commitment().hash();
+        let proof = bundle.sign(&wallet).await.unwrap();
+
+        let signature =
+            ethers_core::types::Signature::try_from(proof.signature.unwrap().as_slice()).unwrap();
+        // An EIP-191 signature over the commitment hash
+        assert_eq!(signature.recover(hash.to_vec()).unwrap(), wallet.address());
+    }
+}
@@ -0,0 +1,632 @@
+//! Persistent outbound webhook queue.
+//!
+//! Alerts used to be posted once with a 5s timeout, so an endpoint outage
+//! lost every alert raised while it lasted. The daemon now enqueues each
+//! webhook payload in a `WebhookQueue` saved to `~/.sods/webhook-queue.json`
+//! and a background dispatcher delivers it, retrying with exponential
+//! backoff. Deliveries that exhaust their attempts, or that the endpoint
+//! rejects outright (a 4xx other than 408/429), are appended to the
+//! dead-letter file `~/.sods/webhook-dlq.jsonl` for inspection or replay.
+//! Pending deliveries survive a restart.
+//!
+//! Each change is appended to the journal `~/.sods/webhook-queue.jsonl`
+//! instead of rewriting the queue; once the journal grows past
+//! `COMPACT_AFTER` entries it is folded into the snapshot, which is
+//! replaced atomically. The queue holds webhook URLs and payloads, so all
+//! three files are created readable by the owner only.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[cfg(feature = "metrics")]
+use crate::monitoring::metrics::AgentMetrics;
+#[cfg(not(feature = "metrics"))]
+type AgentMetrics = ();
+
+/// Default attempts per delivery; with the default backoff the last one is
+/// made about 37 minutes (2222s) after the first.
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 12;
+
+/// Deliveries kept before the oldest is dead-lettered.
+pub const MAX_PENDING: usize = 10_000;
+
+/// Timeout of a single POST.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Journal entries appended before the queue is compacted into its
+/// snapshot.
+const COMPACT_AFTER: usize = 1_000;
+
+/// How often the dispatcher looks for due retries when nothing is enqueued.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Retry schedule for failed deliveries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    /// Delay after the first failure, in seconds; doubled after each one
+    pub base_delay_secs: u64,
+    pub max_delay_secs: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay_secs: 2,
+            max_delay_secs: 600,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the next attempt, after `attempts` failed ones.
+    pub fn delay_after(&self, attempts: u32) -> u64 {
+        let exponent = attempts.saturating_sub(1).min(32);
+        self.base_delay_secs
+            .saturating_mul(1u64 << exponent)
+            .min(self.max_delay_secs)
+    }
+}
+
+/// One webhook payload awaiting delivery.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedDelivery {
+    pub id: String,
+    pub url: String,
+    pub payload: serde_json::Value,
+    /// Unix seconds
+    pub enqueued_at: u64,
+    /// Failed attempts so far
+    pub attempts: u32,
+    /// Unix seconds of the next attempt
+    pub next_attempt_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// A delivery that will not be retried, as written to the dead-letter file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    /// Unix seconds
+    pub failed_at: u64,
+    pub reason: String,
+    pub delivery: QueuedDelivery,
+}
+
+/// One change to the queue, as appended to the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum JournalEntry {
+    /// A new delivery, or a rescheduled one replacing its earlier state
+    Upsert(QueuedDelivery),
+    /// Delivered or dead-lettered
+    Remove { id: String },
+}
+
+/// Result of one delivery attempt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    Delivered,
+    /// Worth retrying: network errors, timeouts, 5xx, 408 and 429
+    Transient(String),
+    /// The endpoint will not accept this payload
+    Permanent(String),
+}
+
+/// Counters since the queue was opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct DeliveryStats {
+    pub delivered: u64,
+    pub retried: u64,
+    pub dead_lettered: u64,
+}
+
+/// Webhook deliveries persisted to disk, oldest first.
+#[derive(Debug)]
+pub struct WebhookQueue {
+    path: PathBuf,
+    journal_path: PathBuf,
+    /// Entries appended since the last compaction
+    journaled: usize,
+    dlq_path: PathBuf,
+    policy: RetryPolicy,
+    pending: VecDeque<QueuedDelivery>,
+    stats: DeliveryStats,
+}
+
+impl WebhookQueue {
+    /// Default queue location (`~/.sods/webhook-queue.json`).
+    pub fn default_path() -> PathBuf {
+        sods_dir().join("webhook-queue.json")
+    }
+
+    /// Default dead-letter location (`~/.sods/webhook-dlq.jsonl`).
+    pub fn default_dlq_path() -> PathBuf {
+        sods_dir().join("webhook-dlq.jsonl")
+    }
+
+    /// Open the queue at `path`, resuming deliveries left by an earlier run
+    /// from its snapshot and journal. A malformed snapshot is reported and
+    /// started over; a malformed journal line (e.g. torn by a crash) is
+    /// skipped.
+    pub fn open(path: PathBuf, dlq_path: PathBuf) -> Self {
+        let mut pending = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("⚠️ Ignoring malformed {}: {}", path.display(), e);
+                VecDeque::new()
+            }),
+            Err(_) => VecDeque::new(),
+        };
+        let journal_path = path.with_extension("jsonl");
+        let journal = fs::read_to_string(&journal_path).unwrap_or_default();
+        for entry in journal
+            .lines()
+            .filter_map(|line| serde_json::from_str::<JournalEntry>(line).ok())
+        {
+            apply(&mut pending, entry);
+        }
+        let mut queue = Self {
+            path,
+            journal_path,
+            journaled: 0,
+            dlq_path,
+            policy: RetryPolicy::default(),
+            pending,
+            stats: DeliveryStats::default(),
+        };
+        if !journal.is_empty() || !queue.pending.is_empty() {
+            queue.compact();
+        }
+        queue
+    }
+
+    /// Use a different retry schedule (Builder pattern).
+    pub fn with_policy(mut self, policy: RetryPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn stats(&self) -> DeliveryStats {
+        self.stats
+    }
+
+    pub fn dlq_path(&self) -> &Path {
+        &self.dlq_path
+    }
+
+    /// Queue a payload for immediate delivery. Returns the delivery ID.
+    pub fn enqueue(&mut self, url: &str, payload: serde_json::Value, now: u64) -> String {
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let delivery = QueuedDelivery {
+            id: id.clone(),
+            url: url.to_string(),
+            payload,
+            enqueued_at: now,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+        };
+        self.pending.push_back(delivery.clone());
+        self.journal(JournalEntry::Upsert(delivery));
+        while self.pending.len() > MAX_PENDING {
+            if let Some(oldest) = self.pending.pop_front() {
+                self.journal(JournalEntry::Remove {
+                    id: oldest.id.clone(),
+                });
+                self.dead_letter(oldest, "queue full".into(), now);
+            }
+        }
+        id
+    }
+
+    /// Deliveries whose next attempt is due, oldest first.
+    pub fn due(&self, now: u64) -> Vec<QueuedDelivery> {
+        self.pending
+            .iter()
+            .filter(|d| d.next_attempt_at <= now)
+            .cloned()
+            .collect()
+    }
+
+    /// Seconds until the earliest scheduled attempt, if any.
+    pub fn next_due_in(&self, now: u64) -> Option<u64> {
+        self.pending
+            .iter()
+            .map(|d| d.next_attempt_at.saturating_sub(now))
+            .min()
+    }
+
+    /// Apply the outcome of an attempt on delivery `id`.
+    pub fn record(&mut self, id: &str, outcome: AttemptOutcome, now: u64) {
+        let Some(index) = self.pending.iter().position(|d| d.id == id) else {
+            return;
+        };
+        let entry = match outcome {
+            AttemptOutcome::Delivered => {
+                self.pending.remove(index);
+                self.stats.delivered += 1;
+                JournalEntry::Remove { id: id.to_string() }
+            }
+            AttemptOutcome::Permanent(reason) => {
+                if let Some(mut delivery) = self.pending.remove(index) {
+                    delivery.attempts += 1;
+                    delivery.last_error = Some(reason.clone());
+                    self.dead_letter(delivery, reason, now);
+                }
+                JournalEntry::Remove { id: id.to_string() }
+            }
+            AttemptOutcome::Transient(error) => {
+                let delivery = &mut self.pending[index];
+                delivery.attempts += 1;
+                delivery.last_error = Some(error);
+                if delivery.attempts >= self.policy.max_attempts {
+                    if let Some(delivery) = self.pending.remove(index) {
+                        let reason = format!("gave up after {} attempts", delivery.attempts);
+                        self.dead_letter(delivery, reason, now);
+                    }
+                    JournalEntry::Remove { id: id.to_string() }
+                } else {
+                    delivery.next_attempt_at = now + self.policy.delay_after(delivery.attempts);
+                    self.stats.retried += 1;
+                    JournalEntry::Upsert(delivery.clone())
+                }
+            }
+        };
+        self.journal(entry);
+    }
+
+    /// All dead letters, oldest first. Malformed lines are skipped.
+    pub fn dead_letters(&self) -> Vec<DeadLetter> {
+        fs::read_to_string(&self.dlq_path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    fn dead_letter(&mut self, delivery: QueuedDelivery, reason: String, now: u64) {
+        eprintln!(
+            "Webhook delivery {} to {} dead-lettered: {}",
+            delivery.id, delivery.url, reason
+        );
+        self.stats.dead_lettered += 1;
+        let letter = DeadLetter {
+            failed_at: now,
+            reason,
+            delivery,
+        };
+        // `sods gc` compacts the dead letters under the same lock
+        let appended = crate::retention::lock_store(&self.dlq_path)
+            .and_then(|_lock| append_line(&self.dlq_path, &letter));
+        if let Err(e) = appended {
+            eprintln!(
+                "Failed to write dead letter to {}: {}",
+                self.dlq_path.display(),
+                e
+            );
+        }
+    }
+
+    /// Append `entry` to the journal, compacting once it has grown long.
+    /// If the append fails the queue is compacted right away so the change
+    /// still reaches disk.
+    fn journal(&mut self, entry: JournalEntry) {
+        self.journaled += 1;
+        if self.journaled >= COMPACT_AFTER {
+            self.compact();
+            return;
+        }
+        if let Err(e) = append_line(&self.journal_path, &entry) {
+            eprintln!("Failed to append to {}: {}", self.journal_path.display(), e);
+            self.compact();
+        }
+    }
+
+    /// Replace the snapshot with the current queue and start an empty
+    /// journal. A rename keeps the snapshot whole if we crash mid-write;
+    /// replaying a journal that outlived the rename gives the same queue.
+    fn compact(&mut self) {
+        let result = (|| {
+            let json = serde_json::to_vec(&self.pending)
+                .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+            let tmp = self.path.with_extension("json.tmp");
+            let _ = fs::remove_file(&tmp);
+            open_private(&tmp, false)?.write_all(&json)?;
+            fs::rename(&tmp, &self.path)?;
+            match fs::remove_file(&self.journal_path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        })();
+        match result {
+            Ok(()) => self.journaled = 0,
+            Err(e) => eprintln!("Failed to save {}: {}", self.path.display(), e),
+        }
+    }
+}
+
+/// Queue shared between the daemon loop and the dispatcher.
+#[derive(Clone)]
+pub struct SharedQueue {
+    queue: Arc<Mutex<WebhookQueue>>,
+    wake: Arc<tokio::sync::Notify>,
+}
+
+impl SharedQueue {
+    pub fn new(queue: WebhookQueue) -> Self {
+        Self {
+            queue: Arc::new(Mutex::new(queue)),
+            wake: Arc::new(tokio::sync::Notify::new()),
+        }
+    }
+
+    /// Queue a payload and wake the dispatcher.
+    pub fn enqueue(&self, url: &str, payload: serde_json::Value) {
+        self.lock().enqueue(url, payload, unix_now());
+        self.wake.notify_one();
+    }
+
+    pub fn lock(&self) -> std::sync::MutexGuard<'_, WebhookQueue> {
+        // A panicked holder cannot leave the queue half-updated
+        self.queue.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Deliver queued payloads until the process exits.
+    pub async fn run_dispatcher(self, metrics: Option<Arc<AgentMetrics>>) {
+        let client = reqwest::Client::new();
+        let mut reported = DeliveryStats::default();
+        loop {
+            let due = self.lock().due(unix_now());
+            for delivery in due {
+                let outcome = attempt(&client, &delivery).await;
+                let mut queue = self.lock();
+                queue.record(&delivery.id, outcome, unix_now());
+                report_metrics(metrics.as_deref(), &mut reported, &queue);
+            }
+
+            let wait = self
+                .lock()
+                .next_due_in(unix_now())
+                .map(|secs| Duration::from_secs(secs.max(1)).min(POLL_INTERVAL * 30))
+                .unwrap_or(POLL_INTERVAL * 30);
+            tokio::select! {
+                _ = self.wake.notified() => {}
+                _ = tokio::time::sleep(wait) => {}
+            }
+        }
+    }
+}
+
+/// Publish counter increments since the last report, and the queue depth.
+#[cfg(feature = "metrics")]
+fn report_metrics(
+    metrics: Option<&AgentMetrics>,
+    reported: &mut DeliveryStats,
+    queue: &WebhookQueue,
+) {
+    let Some(m) = metrics else {
+        return;
+    };
+    let now = queue.stats();
+    m.webhook_deliveries_total
+        .inc_by((now.delivered - reported.delivered) as f64);
+    m.webhook_retries_total
+        .inc_by((now.retried - reported.retried) as f64);
+    m.webhook_dead_letters_total
+        .inc_by((now.dead_lettered - reported.dead_lettered) as f64);
+    m.webhook_queue_depth.set(queue.len() as i64);
+    *reported = now;
+}
+
+#[cfg(not(feature = "metrics"))]
+fn report_metrics(
+    _metrics: Option<&AgentMetrics>,
+    _reported: &mut DeliveryStats,
+    _queue: &WebhookQueue,
+) {
+}
+
+/// POST one delivery and classify the response.
+async fn attempt(client: &reqwest::Client, delivery: &QueuedDelivery) -> AttemptOutcome {
+    if !delivery.url.starts_with("https://") {
+        return AttemptOutcome::Permanent("webhook must use https://".into());
+    }
+    match client
+        .post(&delivery.url)
+        .json(&delivery.payload)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(resp) => classify_status(resp.status().as_u16()),
+        Err(e) => AttemptOutcome::Transient(e.to_string()),
+    }
+}
+
+/// Whether an HTTP status means delivered, retry later, or give up.
+pub fn classify_status(status: u16) -> AttemptOutcome {
+    match status {
+        200..=299 => AttemptOutcome::Delivered,
+        408 | 429 => AttemptOutcome::Transient(format!("HTTP {}", status)),
+        400..=499 => AttemptOutcome::Permanent(format!("HTTP {}", status)),
+        _ => AttemptOutcome::Transient(format!("HTTP {}", status)),
+    }
+}
+
+/// Apply a journal entry to `pending`.
+fn apply(pending: &mut VecDeque<QueuedDelivery>, entry: JournalEntry) {
+    match entry {
+        JournalEntry::Upsert(delivery) => match pending.iter_mut().find(|d| d.id == delivery.id) {
+            Some(existing) => *existing = delivery,
+            None => pending.push_back(delivery),
+        },
+        JournalEntry::Remove { id } => pending.retain(|d| d.id != id),
+    }
+}
+
+fn append_line<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let mut line =
+        serde_json::to_string(value).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    line.push('\n');
+    open_private(path, true)?.write_all(line.as_bytes())
+}
+
+/// Open `path` for writing, creating it (and its directory) readable by
+/// the owner only; appends if `append`, else the file must not exist.
+fn open_private(path: &Path, append: bool) -> io::Result<fs::File> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = OpenOptions::new();
+    if append {
+        options.create(true).append(true);
+    } else {
+        options.write(true).create_new(true);
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+fn sods_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".sods")
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn open(dir: &Path) -> WebhookQueue {
+        WebhookQueue::open(dir.join("queue.json"), dir.join("dlq.jsonl")).with_policy(RetryPolicy {
+            max_attempts: 3,
+            base_delay_secs: 2,
+            max_delay_secs: 600,
+        })
+    }
+
+    #[test]
+    fn test_retry_backoff_then_dead_letter() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = open(dir.path());
+        let id = queue.enqueue("https://hooks.example/a", json!({"n": 1}), 100);
+        assert_eq!(queue.due(100).len(), 1);
+
+        queue.record(&id, AttemptOutcome::Transient("HTTP 503".into()), 100);
+        assert!(queue.due(101).is_empty());
+        assert_eq!(queue.next_due_in(100), Some(2));
+
+        queue.record(&id, AttemptOutcome::Transient("timeout".into()), 102);
+        assert_eq!(queue.due(105).len(), 0);
+        assert_eq!(queue.due(106).len(), 1);
+
+        queue.record(&id, AttemptOutcome::Transient("timeout".into()), 106);
+        assert!(queue.is_empty());
+        let letters = queue.dead_letters();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].reason, "gave up after 3 attempts");
+        assert_eq!(letters[0].delivery.last_error.as_deref(), Some("timeout"));
+        assert_eq!(
+            queue.stats(),
+            DeliveryStats {
+                delivered: 0,
+                retried: 2,
+                dead_lettered: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_queue_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = open(dir.path());
+        let delivered = queue.enqueue("https://hooks.example/a", json!(1), 10);
+        let kept = queue.enqueue("https://hooks.example/b", json!(2), 10);
+        queue.record(&delivered, AttemptOutcome::Delivered, 11);
+        queue.record(&kept, AttemptOutcome::Transient("HTTP 500".into()), 11);
+
+        let reopened = open(dir.path());
+        assert_eq!(reopened.len(), 1);
+        let pending = &reopened.pending[0];
+        assert_eq!(pending.id, kept);
+        assert_eq!(pending.attempts, 1);
+        assert_eq!(pending.next_attempt_at, 13);
+    }
+
+    #[test]
+    fn test_journal_appends_and_compacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = open(dir.path());
+        let journal = dir.path().join("queue.jsonl");
+        let first = queue.enqueue("https://hooks.example/a", json!(1), 0);
+        queue.enqueue("https://hooks.example/b", json!(2), 0);
+        queue.record(&first, AttemptOutcome::Delivered, 1);
+        // Changes are appended, the snapshot is not rewritten
+        assert!(!dir.path().join("queue.json").exists());
+        assert_eq!(fs::read_to_string(&journal).unwrap().lines().count(), 3);
+
+        // A line torn by a crash is skipped and reopening compacts
+        let mut file = OpenOptions::new().append(true).open(&journal).unwrap();
+        file.write_all(b"{\"op\":\"upsert\",\"id\"").unwrap();
+        let reopened = open(dir.path());
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(reopened.pending[0].url, "https://hooks.example/b");
+        assert!(!journal.exists());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            queue.dead_letter(queue.pending[0].clone(), "test".into(), 2);
+            for name in ["queue.json", "dlq.jsonl"] {
+                let mode = fs::metadata(dir.path().join(name))
+                    .unwrap()
+                    .permissions()
+                    .mode();
+                assert_eq!(mode & 0o777, 0o600, "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_status_classification() {
+        assert_eq!(classify_status(204), AttemptOutcome::Delivered);
+        assert!(matches!(classify_status(429), AttemptOutcome::Transient(_)));
+        assert!(matches!(classify_status(502), AttemptOutcome::Transient(_)));
+        assert!(matches!(classify_status(404), AttemptOutcome::Permanent(_)));
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut queue = open(dir.path());
+        let id = queue.enqueue("https://hooks.example/a", json!(1), 0);
+        queue.record(&id, classify_status(410), 0);
+        assert!(queue.is_empty());
+        assert_eq!(queue.dead_letters()[0].reason, "HTTP 410");
+
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.delay_after(1), 2);
+        assert_eq!(policy.delay_after(4), 16);
+        assert_eq!(policy.delay_after(40), 600);
+    }
+}
@@ -1,5 +1,6 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use sods_core::pattern::BehavioralPattern;
+use sods_core::pattern::{BehavioralPattern, CompiledPattern};
+use sods_core::symbol::BehavioralSymbol;
 
 fn bench_simple_pattern(c: &mut Criterion) {
     c.bench_function("parse_simple", |b| {
@@ -34,11 +35,79 @@ fn bench_long_pattern_rejected(c: &mut Criterion) {
     });
 }
 
+/// Threat rules of the kind a daemon watches, most of which do not match.
+fn rules() -> Vec<String> {
+    let names = ["Tf", "Sw", "Dep", "Wdw", "LP+", "LP-", "MintNFT", "BuyNFT"];
+    let mut rules = Vec::new();
+    for (i, a) in names.iter().enumerate() {
+        let b = names[(i + 1) % names.len()];
+        let c = names[(i + 3) % names.len()];
+        rules.push(format!("{} -> {} -> {}", a, b, c));
+        rules.push(format!("{} -> {}{{3,}}", a, b));
+        rules.push(format!("{} | {} -> * -> {}", a, c, b));
+        rules.push(format!("{}{{2,5}} -> {}", a, c));
+        rules.push(format!("{} -> {} where value > 10 ether", b, a));
+    }
+    rules
+}
+
+/// A busy block: mostly transfers and swaps.
+fn block() -> Vec<BehavioralSymbol> {
+    let mix = ["Tf", "Tf", "Sw", "Tf", "Sw", "Dep", "Tf", "Wdw"];
+    (0..500)
+        .map(|i| BehavioralSymbol::new(mix[i % mix.len()], i as u32))
+        .collect()
+}
+
+fn bench_rules_per_block(c: &mut Criterion) {
+    let rules = rules();
+    let symbols = block();
+    let parsed: Vec<BehavioralPattern> = rules
+        .iter()
+        .map(|r| BehavioralPattern::parse(r).unwrap())
+        .collect();
+    let compiled: Vec<CompiledPattern> = parsed.iter().map(BehavioralPattern::compile).collect();
+
+    let mut group = c.benchmark_group(format!("{}_rules_per_block", rules.len()));
+    // What the daemon did per block: parse every rule, then match it
+    group.bench_function("parse_and_match", |b| {
+        b.iter(|| {
+            rules
+                .iter()
+                .filter(|r| {
+                    BehavioralPattern::parse(r)
+                        .unwrap()
+                        .matches(black_box(&symbols), None)
+                        .is_some()
+                })
+                .count()
+        })
+    });
+    group.bench_function("match", |b| {
+        b.iter(|| {
+            parsed
+                .iter()
+                .filter(|p| p.matches(black_box(&symbols), None).is_some())
+                .count()
+        })
+    });
+    group.bench_function("compiled_match", |b| {
+        b.iter(|| {
+            compiled
+                .iter()
+                .filter(|p| p.matches(black_box(&symbols), None).is_some())
+                .count()
+        })
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_simple_pattern,
     bench_complex_pattern,
     bench_malicious_pattern_rejected,
-    bench_long_pattern_rejected
+    bench_long_pattern_rejected,
+    bench_rules_per_block
 );
 criterion_main!(benches);
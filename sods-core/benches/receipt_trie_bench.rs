@@ -0,0 +1,54 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use ethers_core::types::{Address, Bytes, Log, TransactionReceipt, H256, U256, U64};
+use sods_core::header_anchor::{compute_receipts_root, rlp_encode_receipt, KeccakHasher};
+use sods_core::ordered_trie::ordered_trie_root;
+
+/// Receipts shaped like a busy L2 block: typed, a few logs each.
+fn receipts(count: u64) -> Vec<TransactionReceipt> {
+    (0..count)
+        .map(|i| TransactionReceipt {
+            status: Some(U64::from(1)),
+            cumulative_gas_used: U256::from(50_000 * (i + 1)),
+            transaction_type: Some(U64::from(2)),
+            logs: (0..3)
+                .map(|j| Log {
+                    address: Address::from_low_u64_be(i * 7 + j),
+                    topics: vec![H256::from_low_u64_be(j), H256::from_low_u64_be(i)],
+                    data: Bytes::from(vec![i as u8; 64]),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        })
+        .collect()
+}
+
+fn bench_trie_root(c: &mut Criterion) {
+    let mut group = c.benchmark_group("receipt_trie_root");
+    for count in [50u64, 300, 1000] {
+        let encoded: Vec<Vec<u8>> = receipts(count).iter().map(rlp_encode_receipt).collect();
+        group.bench_with_input(BenchmarkId::new("triehash", count), &encoded, |b, enc| {
+            b.iter(|| triehash::ordered_trie_root::<KeccakHasher, _>(black_box(enc)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("ordered_trie", count),
+            &encoded,
+            |b, enc| b.iter(|| ordered_trie_root(black_box(enc))),
+        );
+    }
+    group.finish();
+}
+
+fn bench_compute_receipts_root(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compute_receipts_root");
+    for count in [50u64, 300, 1000] {
+        let receipts = receipts(count);
+        group.bench_with_input(BenchmarkId::from_parameter(count), &receipts, |b, r| {
+            b.iter(|| compute_receipts_root(black_box(r)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_trie_root, bench_compute_receipts_root);
+criterion_main!(benches);
@@ -0,0 +1,227 @@
+//! User-defined symbol aliases and display labels.
+//!
+//! Canonical symbols are short on purpose (`Sw`, `Dep`, `LP+`) because they
+//! are hashed into every leaf. Aliases let people write `Swap` or `Deposit`
+//! in queries and patterns instead; they are resolved to the canonical
+//! symbol before anything is hashed, so BMT roots and proofs are unchanged.
+//! Labels only affect how symbols are shown.
+//!
+//! Both are usually read from a TOML table:
+//!
+//! ```toml
+//! [symbols.aliases]
+//! Swap = "Sw"
+//! Deposit = "Dep"
+//!
+//! [symbols.labels.Sw]
+//! display_name = "Échange"
+//! description = "Échange sur Uniswap V2/V3"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::error::{Result, SodsError};
+
+/// Display name and optional description for one canonical symbol.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolLabel {
+    pub display_name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// Alias table and display labels for behavioral symbols.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolAliases {
+    /// Alias -> canonical symbol
+    #[serde(default)]
+    aliases: HashMap<String, String>,
+    /// Canonical symbol -> label
+    #[serde(default)]
+    labels: HashMap<String, SymbolLabel>,
+}
+
+impl SymbolAliases {
+    /// Create an empty table; every name resolves to itself.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an alias for a canonical symbol (Builder pattern).
+    pub fn with_alias(mut self, alias: impl Into<String>, symbol: impl Into<String>) -> Self {
+        self.aliases.insert(alias.into(), symbol.into());
+        self
+    }
+
+    /// Set the label shown for a canonical symbol (Builder pattern).
+    pub fn with_label(
+        mut self,
+        symbol: impl Into<String>,
+        display_name: impl Into<String>,
+        description: Option<String>,
+    ) -> Self {
+        self.labels.insert(
+            symbol.into(),
+            SymbolLabel {
+                display_name: display_name.into(),
+                description,
+            },
+        );
+        self
+    }
+
+    /// Whether no aliases or labels are defined.
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty() && self.labels.is_empty()
+    }
+
+    /// Resolve a name to its canonical symbol.
+    ///
+    /// Matching is exact, like symbols themselves (`swap` is not `Swap`).
+    /// Aliases are not chained, and names without an alias are returned
+    /// unchanged.
+    pub fn resolve<'a>(&'a self, name: &'a str) -> &'a str {
+        self.aliases.get(name).map(String::as_str).unwrap_or(name)
+    }
+
+    /// Aliases defined for a canonical symbol, sorted.
+    pub fn aliases_for(&self, symbol: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .aliases
+            .iter()
+            .filter(|(_, target)| target.as_str() == symbol)
+            .map(|(alias, _)| alias.as_str())
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// Name to show for a canonical symbol, falling back to the symbol.
+    pub fn display_name<'a>(&'a self, symbol: &'a str) -> &'a str {
+        self.labels
+            .get(symbol)
+            .map(|l| l.display_name.as_str())
+            .unwrap_or(symbol)
+    }
+
+    /// Configured description of a canonical symbol, if any.
+    pub fn description(&self, symbol: &str) -> Option<&str> {
+        self.labels.get(symbol)?.description.as_deref()
+    }
+
+    /// Check that every alias can appear in a pattern and does not shadow
+    /// a pattern preset or a symbol another alias resolves to.
+    ///
+    /// Aliases must use the same characters as symbol names (alphanumerics,
+    /// `+`, `-`, `_`) so the pattern parser can tokenize them.
+    pub fn validate(&self) -> Result<()> {
+        let mut names: Vec<&String> = self.aliases.keys().collect();
+        names.sort_unstable();
+        for alias in names {
+            if alias.is_empty()
+                || alias
+                    .chars()
+                    .any(|c| !c.is_alphanumeric() && c != '+' && c != '-' && c != '_')
+            {
+                return Err(SodsError::PatternError(format!(
+                    "Invalid symbol alias: {:?}",
+                    alias
+                )));
+            }
+            if crate::pattern::PRESETS.contains(&alias.as_str()) {
+                return Err(SodsError::PatternError(format!(
+                    "Alias '{}' shadows a pattern preset",
+                    alias
+                )));
+            }
+            if self.aliases[alias].is_empty() {
+                return Err(SodsError::PatternError(format!(
+                    "Alias '{}' has an empty target",
+                    alias
+                )));
+            }
+            if self.aliases.values().any(|target| target == alias) {
+                return Err(SodsError::PatternError(format!(
+                    "Alias '{}' shadows a symbol another alias resolves to",
+                    alias
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// `validate`, and also check that no alias shadows one of the
+    /// `canonical` symbols, which would silently redirect it.
+    pub fn validate_with_symbols<'a>(
+        &self,
+        canonical: impl IntoIterator<Item = &'a str>,
+    ) -> Result<()> {
+        self.validate()?;
+        let mut shadowed: Vec<&str> = canonical
+            .into_iter()
+            .filter(|symbol| self.aliases.contains_key(*symbol))
+            .collect();
+        shadowed.sort_unstable();
+        match shadowed.first() {
+            Some(symbol) => Err(SodsError::PatternError(format!(
+                "Alias '{}' shadows a canonical symbol",
+                symbol
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_and_labels() {
+        let aliases = SymbolAliases::new()
+            .with_alias("Swap", "Sw")
+            .with_alias("Trade", "Sw")
+            .with_label("Sw", "Échange", Some("Échange Uniswap".into()));
+
+        assert_eq!(aliases.resolve("Swap"), "Sw");
+        assert_eq!(aliases.resolve("swap"), "swap");
+        assert_eq!(aliases.resolve("Tf"), "Tf");
+        assert_eq!(aliases.aliases_for("Sw"), vec!["Swap", "Trade"]);
+        assert_eq!(aliases.display_name("Sw"), "Échange");
+        assert_eq!(aliases.display_name("Tf"), "Tf");
+        assert_eq!(aliases.description("Sw"), Some("Échange Uniswap"));
+        assert!(aliases.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_untokenizable_aliases() {
+        assert!(SymbolAliases::new()
+            .with_alias("Add Liquidity", "LP+")
+            .validate()
+            .is_err());
+        assert!(SymbolAliases::new()
+            .with_alias("Sandwich", "Sw")
+            .validate()
+            .is_err());
+        assert!(SymbolAliases::new().with_alias("X", "").validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_aliases_shadowing_symbols() {
+        // `Sw` is the target of `Swap`, so it cannot also be an alias
+        assert!(SymbolAliases::new()
+            .with_alias("Swap", "Sw")
+            .with_alias("Sw", "Tf")
+            .validate()
+            .is_err());
+
+        let aliases = SymbolAliases::new().with_alias("Tf", "Sw");
+        assert!(aliases.validate().is_ok());
+        assert!(aliases.validate_with_symbols(["Sw", "Tf"]).is_err());
+        assert!(SymbolAliases::new()
+            .with_alias("Swap", "Sw")
+            .validate_with_symbols(["Sw", "Tf"])
+            .is_ok());
+    }
+}
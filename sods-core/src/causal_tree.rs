@@ -77,13 +77,7 @@ impl CausalMerkleTree {
 
         let mut layers = vec![leaves];
 
-        loop {
-            let current = layers.last().unwrap();
-
-            if current.len() == 1 {
-                break;
-            }
-
+        while let Some(current) = layers.last().filter(|layer| layer.len() > 1) {
             let mut next_layer = Vec::with_capacity((current.len() + 1) / 2);
 
             for i in (0..current.len()).step_by(2) {
@@ -105,7 +99,7 @@ impl CausalMerkleTree {
             layers.push(next_layer);
         }
 
-        let root = layers.last().unwrap()[0];
+        let root = layers[layers.len() - 1][0];
         (layers, root)
     }
 
@@ -195,11 +189,10 @@ impl CausalMerkleTree {
                     // MUST match target symbol
                     if next_sym.symbol() == target_symbols[match_ptr] {
                         // MUST be (nonce + 1) OR (same nonce && sequence + 1)
-                        let is_next_nonce =
-                            next_sym.nonce == potential_match.last().unwrap().nonce + 1;
-                        let is_next_seq = next_sym.nonce == potential_match.last().unwrap().nonce
-                            && next_sym.call_sequence
-                                > potential_match.last().unwrap().call_sequence;
+                        let prev = potential_match[potential_match.len() - 1];
+                        let is_next_nonce = next_sym.nonce == prev.nonce + 1;
+                        let is_next_seq = next_sym.nonce == prev.nonce
+                            && next_sym.call_sequence > prev.call_sequence;
 
                         if is_next_nonce || is_next_seq {
                             potential_match.push(next_sym);
@@ -224,7 +217,7 @@ impl CausalMerkleTree {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ethers_core::types::{Address, H256};
+    use ethereum_types::{Address, H256};
 
     fn mock_sym(symbol: &str, from: u64, nonce: u64, log_index: u32) -> BehavioralSymbol {
         BehavioralSymbol::new(symbol, log_index)
@@ -0,0 +1,187 @@
+//! Time sources for expiry logic.
+//!
+//! Puzzles, rate limits, threat rule lifetimes and causal windows all
+//! depend on the current time. Code that reads it through a [`Clock`]
+//! instead of `SystemTime::now` can be driven by a [`MockClock`], so tests
+//! step time forward explicitly instead of sleeping, or by a
+//! [`ScaledClock`], so a simulation runs hours of expiry in minutes.
+
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync + Debug {
+    /// Current wall-clock time.
+    fn now(&self) -> SystemTime;
+
+    /// Current monotonic time, for intervals and deadlines.
+    fn instant(&self) -> Instant;
+
+    /// Current wall-clock time in seconds since the Unix epoch; zero if
+    /// the clock is before it.
+    fn unix_secs(&self) -> u64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// A clock shared by the components of one node.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The operating system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// The system clock as a [`SharedClock`].
+    pub fn shared() -> SharedClock {
+        Arc::new(SystemClock)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn instant(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when told to.
+///
+/// Clones share the same time, so a test can keep one handle and advance
+/// the clock it injected elsewhere.
+#[derive(Debug, Clone)]
+pub struct MockClock {
+    start: SystemTime,
+    start_instant: Instant,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl MockClock {
+    /// A clock standing at `start`.
+    pub fn new(start: SystemTime) -> Self {
+        Self {
+            start,
+            start_instant: Instant::now(),
+            elapsed: Arc::new(Mutex::new(Duration::ZERO)),
+        }
+    }
+
+    /// A clock standing at `secs` seconds after the Unix epoch.
+    pub fn at_unix(secs: u64) -> Self {
+        Self::new(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    /// Move the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.lock() += by;
+    }
+
+    /// Time the clock has been advanced by since it was created.
+    pub fn elapsed(&self) -> Duration {
+        *self.lock()
+    }
+
+    /// This clock as a [`SharedClock`], still advanced through `self`.
+    pub fn shared(&self) -> SharedClock {
+        Arc::new(self.clone())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Duration> {
+        // A panic elsewhere cannot leave a Duration half-written
+        self.elapsed.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new(SystemTime::now())
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        self.start + self.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.start_instant + self.elapsed()
+    }
+}
+
+/// A clock that starts at a given time and runs `speed` times faster than
+/// real time.
+#[derive(Debug, Clone, Copy)]
+pub struct ScaledClock {
+    start: SystemTime,
+    origin: Instant,
+    speed: u32,
+}
+
+impl ScaledClock {
+    /// A clock standing at `start` now and running `speed` times faster
+    /// than real time; a speed of 0 is taken as 1.
+    pub fn new(start: SystemTime, speed: u32) -> Self {
+        Self {
+            start,
+            origin: Instant::now(),
+            speed: speed.max(1),
+        }
+    }
+
+    /// This clock as a [`SharedClock`].
+    pub fn shared(self) -> SharedClock {
+        Arc::new(self)
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.origin.elapsed().saturating_mul(self.speed)
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> SystemTime {
+        self.start + self.elapsed()
+    }
+
+    fn instant(&self) -> Instant {
+        self.origin + self.elapsed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_moves_only_when_advanced() {
+        let clock = MockClock::at_unix(1_700_000_000);
+        let shared = clock.shared();
+        let (then, then_instant) = (shared.now(), shared.instant());
+        assert_eq!(shared.unix_secs(), 1_700_000_000);
+        assert_eq!(shared.now(), then);
+
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(shared.unix_secs(), 1_700_000_090);
+        assert_eq!(shared.instant() - then_instant, Duration::from_secs(90));
+        assert_eq!(clock.elapsed(), Duration::from_secs(90));
+    }
+
+    #[test]
+    fn test_scaled_clock_runs_fast_from_its_start() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let clock = ScaledClock::new(start, 1000);
+        let then = clock.instant();
+        assert!(clock.unix_secs() >= 1_700_000_000);
+
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(clock.instant() - then >= Duration::from_secs(20));
+        assert!(clock.unix_secs() >= 1_700_000_020);
+    }
+}
@@ -11,6 +11,9 @@ pub struct BehavioralCommitment {
     pub block_number: u64,
     pub receipts_root: [u8; 32],
     pub bmt_root: [u8; 32],
+    /// Content identifier of the published proof bundle (IPFS/Arweave).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability_cid: Option<String>,
 }
 
 impl BehavioralCommitment {
@@ -26,21 +29,43 @@ impl BehavioralCommitment {
             block_number,
             receipts_root,
             bmt_root,
+            availability_cid: None,
         }
     }
 
+    /// Bind a published content identifier into the commitment (Builder pattern).
+    pub fn with_availability_cid(mut self, cid: impl Into<String>) -> Self {
+        self.availability_cid = Some(cid.into());
+        self
+    }
+
     /// Encode the commitment to bytes for signing (matching Solidity abi.encodePacked).
     ///
     /// Format: (uint64, uint64, bytes32, bytes32)
+    ///
+    /// When an availability CID is bound, `keccak256(cid)` is appended as a
+    /// trailing bytes32 so commitments without a CID keep their original encoding.
     pub fn to_signing_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::with_capacity(8 + 8 + 32 + 32);
+        let mut bytes = Vec::with_capacity(8 + 8 + 32 + 32 + 32);
         bytes.extend_from_slice(&self.chain_id.to_be_bytes());
         bytes.extend_from_slice(&self.block_number.to_be_bytes());
         bytes.extend_from_slice(&self.receipts_root);
         bytes.extend_from_slice(&self.bmt_root);
+        if let Some(cid) = &self.availability_cid {
+            bytes.extend_from_slice(&Self::availability_hash(cid));
+        }
         bytes
     }
 
+    /// The `availabilityHash` a CID is committed as: `keccak256(cid)`.
+    pub fn availability_hash(cid: &str) -> [u8; 32] {
+        let mut hasher = Keccak::v256();
+        hasher.update(cid.as_bytes());
+        let mut cid_hash = [0u8; 32];
+        hasher.finalize(&mut cid_hash);
+        cid_hash
+    }
+
     /// Compute the Keccak256 hash of the commitment.
     pub fn hash(&self) -> [u8; 32] {
         let mut hasher = Keccak::v256();
@@ -72,6 +97,16 @@ mod tests {
         assert_eq!(bytes[48..80], [0x22; 32]);
     }
 
+    #[test]
+    fn test_commitment_availability_cid() {
+        let plain = BehavioralCommitment::new(1, 1, [0; 32], [0; 32]);
+        let bound = plain.clone().with_availability_cid("bafybeigdyrzt");
+
+        assert_eq!(plain.to_signing_bytes().len(), 80);
+        assert_eq!(bound.to_signing_bytes().len(), 112);
+        assert_ne!(plain.hash(), bound.hash());
+    }
+
     #[test]
     fn test_commitment_hash() {
         let commitment = BehavioralCommitment::new(1, 1, [0; 32], [0; 32]);
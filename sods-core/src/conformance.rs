@@ -0,0 +1,309 @@
+//! Protocol conformance vectors.
+//!
+//! Known-answer checks for the parts of the SODS specification that other
+//! implementations must reproduce bit for bit: the BMT hashing rules (§5.1),
+//! proof verification and serialization (§5.4, RFC §9) and the calldata
+//! layout of `SODSVerifier.verifyBehavior`. The expected values are fixed
+//! here rather than recomputed, so running the checks shows whether the
+//! code compiled into a binary still matches the published spec.
+
+use serde::Serialize;
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::proof::Proof;
+use crate::symbol::BehavioralSymbol;
+use crate::tree::BehavioralMerkleTree;
+
+/// Specification version these vectors are taken from.
+pub const SPEC_VERSION: &str = "1.0.0";
+
+/// `Keccak256(b"")`, the root of a tree without symbols.
+const EMPTY_ROOT: &str = "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470";
+
+/// Leaf hashes of single symbols: (symbol, log index, hash).
+const LEAF_VECTORS: &[(&str, u32, &str)] = &[
+    (
+        "Tf",
+        0,
+        "0f41a35220699c7fc94923b25d80e94cb9becee0ec8c69e47024882688149a46",
+    ),
+    (
+        "Sw",
+        1,
+        "d5dbd5dc21108cffb2e7171ac3d71624c3140b64cbbb2896bdd8e3470dab3f67",
+    ),
+    (
+        "Dep",
+        2,
+        "474a75b3a38566c64598241aea770dee25eab29993d008d6cbf62b9134f7e5af",
+    ),
+    (
+        "LP+",
+        7,
+        "0bca02b6a1492066cfa815f95c95f57d32d0ae26f9a31af51deedd779d2992ce",
+    ),
+    (
+        "lp+",
+        7,
+        "503c3e7c3ab3baef3228a2e88875668016cb9c6e1edc18a91ef3af4a46220aa5",
+    ),
+];
+
+/// Root over `Tf@0, Sw@1, Dep@2`; the odd last node is duplicated.
+const THREE_LEAF_ROOT: &str = "812c3834f5dfa12af033bddadbf2c69b815577d782ada52f85a4a18cd9d7a975";
+
+/// Solidity signature the calldata is laid out for.
+const VERIFY_BEHAVIOR_SIGNATURE: &str = "verifyBehavior(uint256,uint256,string[],uint32[],bytes32[],bytes32[],bool[],bytes32,bytes32,uint256,bytes32,bytes,address)";
+
+/// First four bytes of `Keccak256(VERIFY_BEHAVIOR_SIGNATURE)`.
+const VERIFY_BEHAVIOR_SELECTOR: &str = "58093393";
+
+/// `Keccak256` of the calldata for `reference_onchain_proof`.
+#[cfg(feature = "evm")]
+const REFERENCE_CALLDATA_HASH: &str =
+    "b89b73f1bb8d9f7c3c3e8758c502f9d08562eaecd7596f029e70b2cde1662062";
+
+/// Outcome of one conformance check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConformanceCheck {
+    /// Identifier, matching the RFC's conformance table where one exists
+    pub id: &'static str,
+    pub description: &'static str,
+    pub passed: bool,
+    /// What differed, for failed checks
+    pub detail: Option<String>,
+}
+
+impl ConformanceCheck {
+    fn compare(id: &'static str, description: &'static str, expected: &str, actual: &str) -> Self {
+        let passed = expected == actual;
+        Self {
+            id,
+            description,
+            passed,
+            detail: (!passed).then(|| format!("expected {}, got {}", expected, actual)),
+        }
+    }
+
+    fn holds(id: &'static str, description: &'static str, failure: Option<String>) -> Self {
+        Self {
+            id,
+            description,
+            passed: failure.is_none(),
+            detail: failure,
+        }
+    }
+}
+
+/// Runs every conformance check, in a stable order.
+pub fn run_all() -> Vec<ConformanceCheck> {
+    vec![
+        check_empty_root(),
+        check_leaf_hashes(),
+        check_case_sensitivity(),
+        check_tree_root(),
+        check_proof_round_trip(),
+        check_wrong_direction(),
+        check_tampered_sibling(),
+        check_calldata_selector(),
+        #[cfg(feature = "evm")]
+        check_calldata_layout(),
+    ]
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut out = [0u8; 32];
+    hasher.finalize(&mut out);
+    out
+}
+
+fn three_leaf_tree() -> BehavioralMerkleTree {
+    BehavioralMerkleTree::new(vec![
+        BehavioralSymbol::new("Dep", 2),
+        BehavioralSymbol::new("Tf", 0),
+        BehavioralSymbol::new("Sw", 1),
+    ])
+}
+
+fn check_empty_root() -> ConformanceCheck {
+    let root = BehavioralMerkleTree::new(Vec::new()).root();
+    ConformanceCheck::compare(
+        "BMT-001",
+        "Empty block root is Keccak256(\"\")",
+        EMPTY_ROOT,
+        &hex::encode(root),
+    )
+}
+
+fn check_leaf_hashes() -> ConformanceCheck {
+    let failure = LEAF_VECTORS.iter().find_map(|(symbol, index, expected)| {
+        let actual = hex::encode(BehavioralSymbol::new(*symbol, *index).leaf_hash());
+        (actual != *expected).then(|| {
+            format!(
+                "{}@{}: expected {}, got {}",
+                symbol, index, expected, actual
+            )
+        })
+    });
+    ConformanceCheck::holds(
+        "LEAF-001",
+        "Leaf hash is Keccak256(symbol || u32be(log_index))",
+        failure,
+    )
+}
+
+fn check_case_sensitivity() -> ConformanceCheck {
+    let upper = BehavioralSymbol::new("LP+", 7).leaf_hash();
+    let lower = BehavioralSymbol::new("lp+", 7).leaf_hash();
+    ConformanceCheck::holds(
+        "SYM-007",
+        "Symbols are case-sensitive (LP+ != lp+)",
+        (upper == lower).then(|| "LP+ and lp+ hash to the same leaf".to_string()),
+    )
+}
+
+fn check_tree_root() -> ConformanceCheck {
+    ConformanceCheck::compare(
+        "BMT-002",
+        "Root over unsorted Dep@2, Tf@0, Sw@1 (sorted, odd node duplicated)",
+        THREE_LEAF_ROOT,
+        &hex::encode(three_leaf_tree().root()),
+    )
+}
+
+fn check_proof_round_trip() -> ConformanceCheck {
+    let tree = three_leaf_tree();
+    let root = tree.root();
+    let failure = (0..tree.len()).find_map(|index| {
+        let Some(proof) = tree.generate_proof_by_index(index) else {
+            return Some(format!("no proof for leaf {}", index));
+        };
+        if !proof.verify(&root) {
+            return Some(format!("proof for leaf {} does not verify", index));
+        }
+        match Proof::deserialize(&proof.serialize()) {
+            Ok(decoded) if decoded == proof && decoded.verify(&root) => None,
+            Ok(_) => Some(format!("leaf {} changed after serialization", index)),
+            Err(e) => Some(format!("leaf {} failed to deserialize: {}", index, e)),
+        }
+    });
+    ConformanceCheck::holds(
+        "VER-004",
+        "Proofs verify and survive serialization round-trips",
+        failure,
+    )
+}
+
+fn check_wrong_direction() -> ConformanceCheck {
+    let tree = three_leaf_tree();
+    let failure = match tree.generate_proof_by_index(0) {
+        Some(mut proof) => {
+            proof.directions[0] = !proof.directions[0];
+            proof
+                .verify(&tree.root())
+                .then(|| "proof with a flipped direction verified".to_string())
+        }
+        None => Some("no proof for leaf 0".to_string()),
+    };
+    ConformanceCheck::holds(
+        "VER-010",
+        "Proof for the wrong position is rejected",
+        failure,
+    )
+}
+
+fn check_tampered_sibling() -> ConformanceCheck {
+    let tree = three_leaf_tree();
+    let failure = match tree.generate_proof_by_index(1) {
+        Some(mut proof) => {
+            proof.path[0][0] ^= 0x01;
+            proof
+                .verify(&tree.root())
+                .then(|| "proof with a tampered sibling verified".to_string())
+        }
+        None => Some("no proof for leaf 1".to_string()),
+    };
+    ConformanceCheck::holds(
+        "VER-011",
+        "Proof with a tampered sibling hash is rejected",
+        failure,
+    )
+}
+
+fn check_calldata_selector() -> ConformanceCheck {
+    ConformanceCheck::compare(
+        "ABI-001",
+        "verifyBehavior selector",
+        VERIFY_BEHAVIOR_SELECTOR,
+        &hex::encode(&keccak(VERIFY_BEHAVIOR_SIGNATURE.as_bytes())[..4]),
+    )
+}
+
+/// A fixed proof exercising every calldata field.
+#[cfg(feature = "evm")]
+fn reference_onchain_proof() -> crate::proof::OnChainBehavioralProof {
+    crate::proof::OnChainBehavioralProof {
+        block_number: 10_002_322,
+        chain_id: 11_155_111,
+        symbols: vec!["Tf".to_string(), "Sw".to_string()],
+        log_indices: vec![0, 1],
+        leaf_hashes: vec![
+            BehavioralSymbol::new("Tf", 0).leaf_hash(),
+            BehavioralSymbol::new("Sw", 1).leaf_hash(),
+        ],
+        merkle_path: vec![[0x11; 32], [0x22; 32]],
+        is_left_path: vec![true, false],
+        bmt_root: [0x33; 32],
+        beacon_root: Some([0x44; 32]),
+        timestamp: 1_700_000_000,
+        receipts_root: None,
+        signature: Some(vec![0x55; 65]),
+        dictionary_fingerprint: None,
+        provenance: Vec::new(),
+        availability_cid: None,
+    }
+}
+
+#[cfg(feature = "evm")]
+fn check_calldata_layout() -> ConformanceCheck {
+    let calldata = reference_onchain_proof().to_calldata();
+    ConformanceCheck::compare(
+        "ABI-002",
+        "verifyBehavior calldata encoding of a reference proof",
+        REFERENCE_CALLDATA_HASH,
+        &hex::encode(keccak(&calldata)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_conforms_to_spec() {
+        for check in run_all() {
+            assert!(check.passed, "{} failed: {:?}", check.id, check.detail);
+        }
+    }
+
+    #[test]
+    fn test_tree_vector_follows_from_leaf_vectors() {
+        let leaf = |i: usize| hex::decode(LEAF_VECTORS[i].2).unwrap();
+        let left = keccak(&[leaf(0), leaf(1)].concat());
+        let right = keccak(&[leaf(2), leaf(2)].concat());
+        assert_eq!(
+            hex::encode(keccak(&[left, right].concat())),
+            THREE_LEAF_ROOT
+        );
+        assert_eq!(hex::encode(keccak(b"")), EMPTY_ROOT);
+    }
+
+    #[test]
+    fn test_failed_check_reports_difference() {
+        let check = ConformanceCheck::compare("X-1", "demo", "aa", "bb");
+        assert!(!check.passed);
+        assert_eq!(check.detail.as_deref(), Some("expected aa, got bb"));
+    }
+}
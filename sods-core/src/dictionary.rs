@@ -3,11 +3,24 @@
 //! This module provides the `SymbolDictionary` which maps known event topic
 //! hashes (keccak256 of event signatures) to their corresponding behavioral
 //! symbol codes.
+//!
+//! Beyond the built-in vocabulary, symbols can be added without
+//! recompiling from a dictionary file (`~/.sods/dictionary.toml`):
+//!
+//! ```toml
+//! [[symbols]]
+//! signature = "Staked(address,uint256)"
+//! symbol = "Stk"
+//! parser = "generic"  # transfer, swap or generic (the default)
+//! ```
 
 use ethers_core::types::{Log, H256};
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
-use crate::symbol::BehavioralSymbol;
+use crate::error::{Result, SodsError};
+use crate::symbol::{BehavioralSymbol, SymbolProvenance};
 
 // ============================================================================
 // Core Event Topic Hashes (keccak256 of event signatures)
@@ -81,8 +94,51 @@ const PERMIT2_SIG: &str = "Permit(address,address,uint256,uint256,address,uint25
 /// CoW Swap Trade(...)
 const COW_TRADE_SIG: &str = "Trade(address,address,address,uint256,uint256,uint256,bytes)";
 
+/// Curve StableSwap TokenExchange(address indexed buyer, int128 sold_id, uint256 tokens_sold, int128 bought_id, uint256 tokens_bought)
+const CURVE_TOKEN_EXCHANGE_SIG: &str = "TokenExchange(address,int128,uint256,int128,uint256)";
+
+/// Curve CryptoSwap/NG TokenExchange with uint256 coin indices
+const CURVE_TOKEN_EXCHANGE_V2_SIG: &str = "TokenExchange(address,uint256,uint256,uint256,uint256)";
+
+/// Curve TokenExchangeUnderlying, for metapool and lending pool underlying coins
+const CURVE_TOKEN_EXCHANGE_UNDERLYING_SIG: &str =
+    "TokenExchangeUnderlying(address,int128,uint256,int128,uint256)";
+
+/// Balancer V2 Vault Swap(bytes32 indexed poolId, address indexed tokenIn, address indexed tokenOut, uint256 amountIn, uint256 amountOut)
+const BALANCER_SWAP_SIG: &str = "Swap(bytes32,address,address,uint256,uint256)";
+
+/// ERC1155 TransferSingle(address indexed operator, address indexed from, address indexed to, uint256 id, uint256 value)
+const TRANSFER_SINGLE_SIG: &str = "TransferSingle(address,address,address,uint256,uint256)";
+
+/// ERC1155 TransferBatch(address indexed operator, address indexed from, address indexed to, uint256[] ids, uint256[] values)
+const TRANSFER_BATCH_SIG: &str = "TransferBatch(address,address,address,uint256[],uint256[])";
+
+/// Most items of an ERC-1155 `TransferBatch` expanded into symbols.
+///
+/// Every item shares the log's index, and with it the leaf hash, so a
+/// proof cannot tell them apart; the cap keeps one cheap log from adding
+/// thousands of indistinguishable leaves. A larger batch yields a single
+/// symbol without token id or amount.
+pub const MAX_ERC1155_BATCH_ITEMS: usize = 32;
+
+/// Lido stETH Submitted(address indexed sender, uint256 amount, address referral)
+const LIDO_SUBMITTED_SIG: &str = "Submitted(address,uint256,address)";
+
+/// Lido WithdrawalQueue WithdrawalClaimed(uint256 indexed requestId, address indexed owner, address indexed receiver, uint256 amountOfETH)
+const LIDO_WITHDRAWAL_CLAIMED_SIG: &str = "WithdrawalClaimed(uint256,address,address,uint256)";
+
+/// Rocket Pool RocketDepositPool DepositReceived(address indexed from, uint256 amount, uint256 time)
+const ROCKET_DEPOSIT_RECEIVED_SIG: &str = "DepositReceived(address,uint256,uint256)";
+
+/// Rocket Pool rETH TokensBurned(address indexed from, uint256 amount, uint256 ethAmount, uint256 time)
+const ROCKET_TOKENS_BURNED_SIG: &str = "TokensBurned(address,uint256,uint256,uint256)";
+
 use ethers_core::types::{Address, U256};
 
+/// Domain tag of `SymbolDictionary::fingerprint`; bump the version when the
+/// encoding changes.
+const DICTIONARY_FINGERPRINT_DOMAIN: &[u8] = b"sods-dictionary/v1";
+
 // ============================================================================
 // Symbol Dictionary
 // ============================================================================
@@ -98,6 +154,83 @@ pub struct SymbolDictionary {
     plugin_parsers: HashMap<H256, crate::plugins::ParserType>,
 }
 
+/// One symbol defined in a dictionary file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DictionaryEntry {
+    /// Event signature, e.g. `Staked(address,uint256)`
+    pub signature: String,
+    /// Behavioral symbol code the event maps to
+    pub symbol: String,
+    /// How the event's fields are read
+    #[serde(default = "generic_parser")]
+    pub parser: crate::plugins::ParserType,
+}
+
+fn generic_parser() -> crate::plugins::ParserType {
+    crate::plugins::ParserType::Generic
+}
+
+impl DictionaryEntry {
+    /// Topic0 of the entry's event.
+    pub fn topic(&self) -> H256 {
+        event_signature_to_topic0(&self.signature)
+    }
+
+    /// The entry as a plugin, for registering it wherever plugins are.
+    pub fn to_plugin(&self) -> crate::plugins::SymbolPlugin {
+        crate::plugins::SymbolPlugin {
+            name: self.signature.clone(),
+            symbol: self.symbol.clone(),
+            chain: None,
+            event_topic: self.topic(),
+            parser: self.parser.clone(),
+            overrides: false,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let (name, params) = self
+            .signature
+            .strip_suffix(')')
+            .and_then(|s| s.split_once('('))
+            .unwrap_or(("", ""));
+        let ident = |s: &str| {
+            s.chars().next().is_some_and(|c| !c.is_ascii_digit())
+                && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        };
+        let type_chars = |s: &str| {
+            s.chars()
+                .all(|c| c.is_ascii_alphanumeric() || "[](),".contains(c))
+        };
+        if !ident(name) || !type_chars(params) {
+            return Err(SodsError::ConfigError(format!(
+                "Invalid event signature '{}' (expected e.g. Staked(address,uint256))",
+                self.signature
+            )));
+        }
+        if self.symbol.is_empty() || !is_symbol_text(&self.symbol) {
+            return Err(SodsError::ConfigError(format!(
+                "Invalid symbol '{}' for {}",
+                self.symbol, self.signature
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Contents of a dictionary file.
+#[derive(Debug, Default, Deserialize)]
+struct DictionaryFile {
+    #[serde(default)]
+    symbols: Vec<DictionaryEntry>,
+}
+
+/// Whether `s` only holds characters a symbol may contain.
+fn is_symbol_text(s: &str) -> bool {
+    s.chars()
+        .all(|c| c.is_alphanumeric() || c == '+' || c == '-' || c == '_')
+}
+
 impl Default for SymbolDictionary {
     /// Create a dictionary with all core immutable symbols.
     fn default() -> Self {
@@ -122,6 +255,16 @@ impl Default for SymbolDictionary {
             (AA_OP_SIG, "AAOp"),
             (PERMIT2_SIG, "Permit2"),
             (COW_TRADE_SIG, "CoWTrade"),
+            (TRANSFER_SINGLE_SIG, "Tf"),
+            (TRANSFER_BATCH_SIG, "Tf"),
+            (CURVE_TOKEN_EXCHANGE_SIG, "Sw"),
+            (CURVE_TOKEN_EXCHANGE_V2_SIG, "Sw"),
+            (CURVE_TOKEN_EXCHANGE_UNDERLYING_SIG, "Sw"),
+            (BALANCER_SWAP_SIG, "Sw"),
+            (LIDO_SUBMITTED_SIG, "Stake"),
+            (ROCKET_DEPOSIT_RECEIVED_SIG, "Stake"),
+            (LIDO_WITHDRAWAL_CLAIMED_SIG, "Unstake"),
+            (ROCKET_TOKENS_BURNED_SIG, "Unstake"),
         ];
 
         for (sig, symbol) in core_signatures {
@@ -147,6 +290,105 @@ impl SymbolDictionary {
         }
     }
 
+    /// Create a dictionary holding one symbol per event in a contract ABI.
+    ///
+    /// See `register_abi`.
+    pub fn from_abi(abi_json: &str, symbol_prefix: &str) -> Result<Self> {
+        let mut dictionary = Self::empty();
+        dictionary.register_abi(abi_json, symbol_prefix)?;
+        Ok(dictionary)
+    }
+
+    /// Register every event in a contract ABI (JSON, as emitted by solc)
+    /// as `<symbol_prefix><EventName>`, parsed by `ParserType::Abi`.
+    /// Anonymous events have no topic to look up and are skipped.
+    ///
+    /// Returns the number of events registered. Fails without registering
+    /// any if the ABI does not parse, the prefix is not a valid symbol, or
+    /// an event's topic is already mapped to something else.
+    pub fn register_abi(&mut self, abi_json: &str, symbol_prefix: &str) -> Result<usize> {
+        use crate::plugins::{AbiFields, ParserType};
+
+        if !is_symbol_text(symbol_prefix) {
+            return Err(SodsError::ConfigError(format!(
+                "Invalid symbol prefix '{}'",
+                symbol_prefix
+            )));
+        }
+        let contract = ethabi::Contract::load(abi_json.as_bytes())
+            .map_err(|e| SodsError::Serialization(format!("Invalid ABI JSON: {}", e)))?;
+
+        let mut events = Vec::new();
+        for event in contract.events().filter(|event| !event.anonymous) {
+            let topic = H256::from(event.signature().0);
+            let symbol = format!("{}{}", symbol_prefix, event.name);
+            let parser = ParserType::Abi(AbiFields::for_event(event));
+            if let Some(existing) = self.conflict(topic, &symbol, Some(&parser)) {
+                return Err(symbol_conflict(topic, existing, &symbol));
+            }
+            events.push((topic, symbol, parser));
+        }
+        for (topic, symbol, parser) in &events {
+            self.registry.remove(topic);
+            self.dynamic_registry.insert(*topic, symbol.clone());
+            self.plugin_parsers.insert(*topic, parser.clone());
+        }
+        Ok(events.len())
+    }
+
+    /// Create the built-in dictionary extended with the entries of a
+    /// dictionary file. A leading `~/` in `path` is the home directory.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let entries = Self::load_entries(path)?;
+        let mut dictionary = Self::default();
+        dictionary.register_entries(&entries)?;
+        Ok(dictionary)
+    }
+
+    /// Default path of the dictionary file.
+    pub fn get_default_path() -> Result<PathBuf> {
+        home::home_dir()
+            .map(|h| h.join(".sods").join("dictionary.toml"))
+            .ok_or_else(|| SodsError::ConfigError("Could not determine home directory".into()))
+    }
+
+    /// Read and validate the entries of a dictionary file.
+    pub fn load_entries(path: impl AsRef<Path>) -> Result<Vec<DictionaryEntry>> {
+        let path = expand_home(path.as_ref())?;
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            SodsError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let file: DictionaryFile = toml::from_str(&content)
+            .map_err(|e| SodsError::ConfigError(format!("Invalid {}: {}", path.display(), e)))?;
+        file.symbols
+            .iter()
+            .try_for_each(DictionaryEntry::validate)?;
+        Ok(file.symbols)
+    }
+
+    /// Entries of the default dictionary file; a missing file means none.
+    pub fn load_local_entries() -> Result<Vec<DictionaryEntry>> {
+        let path = Self::get_default_path()?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Self::load_entries(path)
+    }
+
+    /// Register dictionary file entries.
+    ///
+    /// Returns the number of entries registered. Fails without registering
+    /// any if an entry's topic is already mapped to something else,
+    /// including by an earlier entry.
+    pub fn register_entries(&mut self, entries: &[DictionaryEntry]) -> Result<usize> {
+        let mut extended = self.clone();
+        for entry in entries {
+            extended.register_plugin(entry.to_plugin())?;
+        }
+        *self = extended;
+        Ok(entries.len())
+    }
+
     /// Look up the symbol for a given event topic.
     #[inline]
     pub fn symbol_for_topic(&self, topic: H256) -> Option<&str> {
@@ -178,20 +420,20 @@ impl SymbolDictionary {
     }
 
     /// Map a behavioral pattern to the set of required Ethereum topic hashes.
+    ///
+    /// Empty, meaning every log is needed, if the pattern has a wildcard
+    /// or a gap limit, which counts symbols of every kind.
     pub fn pattern_to_required_topics(
         &self,
         pattern: &crate::pattern::BehavioralPattern,
     ) -> Vec<H256> {
-        use crate::pattern::PatternStep;
+        if pattern.has_wildcard() || pattern.has_gap_limit() {
+            return Vec::new();
+        }
         let mut required_topics = HashSet::new(); // Use HashSet to avoid duplicates
 
-        for step in pattern.steps() {
-            let symbol_code = match step {
-                PatternStep::Exact(s, _) => s,
-                PatternStep::AtLeast(s, _, _) => s,
-                PatternStep::Range(s, _, _, _) => s,
-            };
-
+        // Every alternative of a step can match, so all their topics are needed
+        for symbol_code in pattern.steps().iter().flat_map(|step| step.symbols()) {
             for topic in self.topics_for_symbol(symbol_code) {
                 required_topics.insert(topic);
             }
@@ -200,21 +442,138 @@ impl SymbolDictionary {
         required_topics.into_iter().collect()
     }
 
+    /// Fingerprint of the symbol vocabulary.
+    ///
+    /// Keccak256 over every topic in ascending order, each followed by the
+    /// symbol it resolves to and its plugin parser, under a versioned domain
+    /// tag. Two dictionaries share a fingerprint exactly when they turn the
+    /// same logs into the same symbols.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        use sha3::{Digest, Keccak256};
+
+        let mut topics: Vec<&H256> = self
+            .registry
+            .keys()
+            .chain(self.dynamic_registry.keys())
+            .collect();
+        topics.sort_unstable();
+        topics.dedup();
+
+        let mut hasher = Keccak256::new();
+        hasher.update(DICTIONARY_FINGERPRINT_DOMAIN);
+        for topic in topics {
+            let symbol = self.symbol_for_topic(*topic).unwrap_or_default();
+            let parser = match self.plugin_parsers.get(topic) {
+                None => 0u8,
+                Some(crate::plugins::ParserType::Transfer) => 1,
+                Some(crate::plugins::ParserType::Swap) => 2,
+                Some(crate::plugins::ParserType::Generic) => 3,
+                Some(crate::plugins::ParserType::Abi(_)) => 4,
+            };
+            hasher.update(topic.as_bytes());
+            hasher.update((symbol.len() as u32).to_be_bytes());
+            hasher.update(symbol.as_bytes());
+            hasher.update([parser]);
+            if let Some(crate::plugins::ParserType::Abi(fields)) = self.plugin_parsers.get(topic) {
+                for source in [fields.from, fields.to, fields.value] {
+                    let (tag, index) = match source {
+                        None => (0u8, 0),
+                        Some(crate::plugins::FieldSource::Topic(i)) => (1, i),
+                        Some(crate::plugins::FieldSource::Word(i)) => (2, i),
+                    };
+                    hasher.update([tag]);
+                    hasher.update((index as u64).to_be_bytes());
+                }
+            }
+        }
+        hasher.finalize().into()
+    }
+
+    /// The symbol `topic` is already mapped to, if mapping it to `symbol`
+    /// with `parser` would change how its logs parse.
+    ///
+    /// Registering the same symbol and parser again is not a conflict.
+    pub fn conflict(
+        &self,
+        topic: H256,
+        symbol: &str,
+        parser: Option<&crate::plugins::ParserType>,
+    ) -> Option<&str> {
+        let existing = self.symbol_for_topic(topic)?;
+        (existing != symbol || self.plugin_parsers.get(&topic) != parser).then_some(existing)
+    }
+
     /// Register a custom symbol for a topic.
-    pub fn register_custom(&mut self, topic: H256, symbol: &'static str) {
+    ///
+    /// Fails with `SodsError::SymbolConflict` if the topic is already
+    /// mapped to something else; `replace_custom` overrides instead.
+    pub fn register_custom(&mut self, topic: H256, symbol: &'static str) -> Result<()> {
+        if let Some(existing) = self.conflict(topic, symbol, None) {
+            return Err(symbol_conflict(topic, existing, symbol));
+        }
+        self.replace_custom(topic, symbol);
+        Ok(())
+    }
+
+    /// Map a topic to a custom symbol, replacing any existing mapping.
+    pub fn replace_custom(&mut self, topic: H256, symbol: &'static str) {
+        self.dynamic_registry.remove(&topic);
+        self.plugin_parsers.remove(&topic);
         self.registry.insert(topic, symbol);
     }
 
     /// Register a dynamic plugin based symbol.
-    pub fn register_plugin(&mut self, plugin: crate::plugins::SymbolPlugin) {
-        self.dynamic_registry
-            .insert(plugin.event_topic, plugin.symbol);
-        self.plugin_parsers
-            .insert(plugin.event_topic, plugin.parser);
+    ///
+    /// Fails with `SodsError::SymbolConflict` if the plugin's topic is
+    /// already mapped to something else, unless the plugin is marked as
+    /// an override.
+    pub fn register_plugin(&mut self, plugin: crate::plugins::SymbolPlugin) -> Result<()> {
+        let topic = plugin.event_topic;
+        if let Some(existing) = self.conflict(topic, &plugin.symbol, Some(&plugin.parser)) {
+            if !plugin.overrides {
+                return Err(symbol_conflict(topic, existing, &plugin.symbol));
+            }
+        }
+        // Core and custom mappings are looked up first, so an override must drop them
+        self.registry.remove(&topic);
+        self.dynamic_registry.insert(topic, plugin.symbol);
+        self.plugin_parsers.insert(topic, plugin.parser);
+        Ok(())
+    }
+
+    /// Every topic with the symbol it resolves to, sorted by symbol.
+    pub fn mappings(&self) -> Vec<(H256, &str)> {
+        let mut mappings: Vec<(H256, &str)> = self
+            .registry
+            .iter()
+            .map(|(topic, symbol)| (*topic, *symbol))
+            .chain(self.dynamic_registry.iter().map(|(t, s)| (*t, s.as_str())))
+            .collect();
+        mappings.sort_by(|a, b| a.1.cmp(b.1).then(a.0.cmp(&b.0)));
+        mappings
+    }
+
+    /// Parse an EVM log into every behavioral symbol it carries.
+    ///
+    /// An ERC-1155 `TransferBatch` yields one symbol per token id, each
+    /// with its own `value`, up to `MAX_ERC1155_BATCH_ITEMS`; every other
+    /// log yields what `parse_log` does.
+    pub fn parse_log_all(&self, log: &Log) -> Vec<BehavioralSymbol> {
+        if let Some(symbols) = self.erc1155_symbols(log) {
+            return symbols;
+        }
+        self.parse_log(log).into_iter().collect()
     }
 
     /// Parse an EVM log into a behavioral symbol.
+    ///
+    /// For an ERC-1155 `TransferBatch` this is the first token id only;
+    /// `parse_log_all` returns them all.
     pub fn parse_log(&self, log: &Log) -> Option<BehavioralSymbol> {
+        if let Some(symbols) = self.erc1155_symbols(log) {
+            return symbols.into_iter().next();
+        }
+
         // Get topic[0] (event signature hash)
         let topic = log.topics.first()?;
 
@@ -229,9 +588,13 @@ impl SymbolDictionary {
         let mut to = Address::zero();
         let mut value = U256::zero();
         let mut token_id = None;
+        let mut collection = None;
+        let mut amount_in = None;
+        let mut provenance = SymbolProvenance::default();
 
         // Check for specific parser override
         if let Some(parser) = self.plugin_parsers.get(topic) {
+            provenance = SymbolProvenance::PluginParsed;
             match parser {
                 crate::plugins::ParserType::Generic => {
                     // Just basic symbol, no context extracted yet
@@ -249,6 +612,20 @@ impl SymbolDictionary {
                         from = Address::from(log.topics[1]);
                     }
                 }
+                crate::plugins::ParserType::Abi(fields) => {
+                    use crate::plugins::AbiFields;
+
+                    let read = |source: Option<_>| source.and_then(|s| AbiFields::read(s, log));
+                    if let Some(word) = read(fields.from) {
+                        from = Address::from(word);
+                    }
+                    if let Some(word) = read(fields.to) {
+                        to = Address::from(word);
+                    }
+                    if let Some(word) = read(fields.value) {
+                        value = U256::from_big_endian(word.as_bytes());
+                    }
+                }
             }
         }
         // Fallback to legacy hardcoded heuristic if no plugin or plugin is generic
@@ -261,19 +638,70 @@ impl SymbolDictionary {
                 // If Topic3 exists, it's likely ERC721 TokenID
                 if log.topics.len() == 4 {
                     token_id = Some(U256::from_big_endian(log.topics[3].as_bytes()));
+                    collection = Some(log.address);
 
                     // Helper: Check if Mint (from 0x0)
                     if from == Address::zero() && symbol_code == "Tf" {
                         // Only override if it's the base code
-                        return Some(
-                            BehavioralSymbol::new("MintNFT", log_index)
-                                .with_context(from, to, value, token_id),
-                        );
+                        let mut sym = BehavioralSymbol::new("MintNFT", log_index)
+                            .with_context(from, to, value, token_id);
+                        sym.contract_address = log.address;
+                        sym.collection = collection;
+                        return Some(sym);
                     }
                 } else if log.data.len() >= 32 {
                     value = U256::from_big_endian(&log.data[0..32]);
                 }
             }
+        } else if [
+            CURVE_TOKEN_EXCHANGE_SIG,
+            CURVE_TOKEN_EXCHANGE_V2_SIG,
+            CURVE_TOKEN_EXCHANGE_UNDERLYING_SIG,
+        ]
+        .iter()
+        .any(|sig| *topic == event_signature_to_topic0(sig))
+        {
+            // Curve: buyer indexed; data is (sold_id, tokens_sold, bought_id, tokens_bought)
+            if log.topics.len() >= 2 && log.data.len() >= 128 {
+                from = Address::from(log.topics[1]);
+                amount_in = Some(U256::from_big_endian(&log.data[32..64]));
+                value = U256::from_big_endian(&log.data[96..128]);
+            }
+        } else if *topic == event_signature_to_topic0(BALANCER_SWAP_SIG) {
+            // Balancer: the Vault does not log the trader, so `from` is left
+            // for the verifier to fill with the transaction sender
+            if log.topics.len() >= 4 && log.data.len() >= 64 {
+                to = Address::from(log.topics[3]); // tokenOut
+                amount_in = Some(U256::from_big_endian(&log.data[0..32]));
+                value = U256::from_big_endian(&log.data[32..64]);
+            }
+        } else if [LIDO_SUBMITTED_SIG, ROCKET_DEPOSIT_RECEIVED_SIG]
+            .iter()
+            .any(|sig| *topic == event_signature_to_topic0(sig))
+        {
+            // Staker indexed; ETH staked is the first data word
+            if log.topics.len() >= 2 && log.data.len() >= 32 {
+                from = Address::from(log.topics[1]);
+                value = U256::from_big_endian(&log.data[0..32]);
+            }
+        } else if *topic == event_signature_to_topic0(LIDO_WITHDRAWAL_CLAIMED_SIG) {
+            if log.topics.len() >= 4 && log.data.len() >= 32 {
+                from = Address::from(log.topics[2]); // owner
+                to = Address::from(log.topics[3]); // receiver
+                value = U256::from_big_endian(&log.data[0..32]);
+            }
+        } else if *topic == event_signature_to_topic0(ROCKET_TOKENS_BURNED_SIG) {
+            // rETH burned for ETH: amount_in is the rETH, value the ETH out
+            if log.topics.len() >= 2 && log.data.len() >= 64 {
+                from = Address::from(log.topics[1]);
+                amount_in = Some(U256::from_big_endian(&log.data[0..32]));
+                value = U256::from_big_endian(&log.data[32..64]);
+            }
+        } else if *topic == event_signature_to_topic0(SEAPORT_ORDER_FULFILLED_SIG) {
+            if let Some((nft, id)) = seaport_nft(&log.data) {
+                collection = Some(nft);
+                token_id = Some(id);
+            }
         } else if *topic == event_signature_to_topic0(AA_OP_SIG) {
             // ERC-4337
             if log.topics.len() >= 3 {
@@ -326,12 +754,68 @@ impl SymbolDictionary {
         }
 
         // Construct Symbol
-        let mut sym =
-            BehavioralSymbol::new(symbol_code, log_index).with_context(from, to, value, token_id);
+        let mut sym = BehavioralSymbol::new(symbol_code, log_index)
+            .with_context(from, to, value, token_id)
+            .with_provenance(provenance);
         sym.contract_address = log.address;
+        sym.collection = collection;
+        sym.amount_in = amount_in;
         Some(sym)
     }
 
+    /// Symbols of an ERC-1155 transfer log, one per token id, or `None`
+    /// for any other log or a transfer a plugin parses instead.
+    ///
+    /// Transfers from the zero address are mints, as for ERC-721. A batch
+    /// whose id and value arrays do not decode keeps its addresses only.
+    fn erc1155_symbols(&self, log: &Log) -> Option<Vec<BehavioralSymbol>> {
+        let topic = *log.topics.first()?;
+        let single = topic == event_signature_to_topic0(TRANSFER_SINGLE_SIG);
+        if !single && topic != event_signature_to_topic0(TRANSFER_BATCH_SIG) {
+            return None;
+        }
+        let symbol_code = self.symbol_for_topic(topic)?;
+        if self.plugin_parsers.contains_key(&topic) || log.topics.len() < 4 {
+            return None;
+        }
+
+        let log_index = log.log_index.map(|i| i.as_u32()).unwrap_or(0);
+        let from = Address::from(log.topics[2]);
+        let to = Address::from(log.topics[3]);
+        let code = if from.is_zero() && symbol_code == "Tf" {
+            "MintNFT"
+        } else {
+            symbol_code
+        };
+        let items = if single {
+            let word = |i: usize| {
+                log.data
+                    .get(i * 32..(i + 1) * 32)
+                    .map(U256::from_big_endian)
+            };
+            word(0).zip(word(1)).map(|item| vec![item])
+        } else {
+            erc1155_batch(&log.data).filter(|items| items.len() <= MAX_ERC1155_BATCH_ITEMS)
+        };
+
+        let symbol = |item: Option<(U256, U256)>| {
+            let (token_id, value) = item.unzip();
+            let mut sym = BehavioralSymbol::new(code, log_index).with_context(
+                from,
+                to,
+                value.unwrap_or_default(),
+                token_id,
+            );
+            sym.contract_address = log.address;
+            sym.collection = token_id.map(|_| log.address);
+            sym
+        };
+        Some(match items {
+            Some(items) => items.into_iter().map(|item| symbol(Some(item))).collect(),
+            None => vec![symbol(None)],
+        })
+    }
+
     /// Returns the number of registered symbols.
     #[inline]
     pub fn len(&self) -> usize {
@@ -345,6 +829,79 @@ impl SymbolDictionary {
     }
 }
 
+/// `path` with a leading `~` replaced by the home directory.
+fn expand_home(path: &Path) -> Result<PathBuf> {
+    match path.strip_prefix("~") {
+        Ok(rest) => home::home_dir()
+            .map(|home| home.join(rest))
+            .ok_or_else(|| SodsError::ConfigError("Could not determine home directory".into())),
+        Err(_) => Ok(path.to_path_buf()),
+    }
+}
+
+fn symbol_conflict(topic: H256, existing: &str, symbol: &str) -> SodsError {
+    SodsError::SymbolConflict {
+        topic: format!("{:?}", topic),
+        existing: existing.to_string(),
+        symbol: symbol.to_string(),
+    }
+}
+
+/// Most items read from one side of a Seaport order.
+const MAX_SEAPORT_ITEMS: usize = 64;
+
+/// The first ERC-721 or ERC-1155 item in a Seaport `OrderFulfilled` log's
+/// data, from the offer or else the consideration: the NFT sold in a
+/// listing or bought with an accepted offer.
+fn seaport_nft(data: &[u8]) -> Option<(Address, U256)> {
+    let word = |i: usize| data.get(i * 32..(i + 1) * 32).map(U256::from_big_endian);
+    // Head: orderHash, recipient, offer offset, consideration offset. Offer
+    // items are 4 words (itemType, token, identifier, amount), consideration
+    // items add a recipient.
+    for (offset_word, item_words) in [(2, 4), (3, 5)] {
+        let Some(base) = word(offset_word)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .filter(|offset| offset % 32 == 0)
+            .map(|offset| offset / 32)
+        else {
+            continue;
+        };
+        let Some(len) = word(base).and_then(|len| usize::try_from(len).ok()) else {
+            continue;
+        };
+        for item in 0..len.min(MAX_SEAPORT_ITEMS) {
+            let first = base + 1 + item * item_words;
+            // ItemType 2 is ERC721, 3 is ERC1155
+            if matches!(word(first).map(|t| t.low_u64()), Some(2 | 3)) {
+                let token = data.get((first + 1) * 32 + 12..(first + 2) * 32)?;
+                return Some((Address::from_slice(token), word(first + 2)?));
+            }
+        }
+    }
+    None
+}
+
+/// The `(id, value)` pairs of an ERC-1155 `TransferBatch` log's data.
+fn erc1155_batch(data: &[u8]) -> Option<Vec<(U256, U256)>> {
+    let words = data.len() / 32;
+    let word = |i: usize| data.get(i * 32..(i + 1) * 32).map(U256::from_big_endian);
+    // Head: ids offset, values offset; each array is a length then words
+    let array = |head: usize| -> Option<Vec<U256>> {
+        let offset = usize::try_from(word(head)?).ok()?;
+        if offset % 32 != 0 {
+            return None;
+        }
+        let base = offset / 32;
+        let len = usize::try_from(word(base)?).ok()?;
+        if len > words.saturating_sub(base + 1) {
+            return None;
+        }
+        (0..len).map(|i| word(base + 1 + i)).collect()
+    };
+    let (ids, values) = (array(0)?, array(1)?);
+    (ids.len() == values.len()).then(|| ids.into_iter().zip(values).collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -356,6 +913,22 @@ mod tests {
         assert_eq!(hex::encode(topic), expected);
     }
 
+    #[test]
+    fn test_required_topics_with_wildcard() {
+        use crate::pattern::BehavioralPattern;
+
+        let dict = SymbolDictionary::default();
+        let named = BehavioralPattern::parse("Dep -> Wdw").unwrap();
+        assert!(!dict.pattern_to_required_topics(&named).is_empty());
+
+        // A wildcard can match any log, so nothing may be filtered out
+        let wild = BehavioralPattern::parse("Dep -> * -> Wdw").unwrap();
+        assert!(dict.pattern_to_required_topics(&wild).is_empty());
+        // So can the symbols a gap limit counts
+        let gap = BehavioralPattern::parse("Dep ->{<=2} Wdw").unwrap();
+        assert!(dict.pattern_to_required_topics(&gap).is_empty());
+    }
+
     #[test]
     fn test_mint_nft_detection() {
         // Test that Transfer from 0x0 is detected as MintNFT
@@ -382,10 +955,58 @@ mod tests {
         assert_eq!(dict.symbol_for_topic(topic), Some("BuyNFT"));
     }
 
+    #[test]
+    fn test_nft_symbols_carry_their_collection() {
+        let dict = SymbolDictionary::default();
+        let collection = Address::repeat_byte(0xbc);
+        let mint = Log {
+            address: collection,
+            topics: vec![
+                event_signature_to_topic0(TRANSFER_SIG),
+                H256::zero(),
+                H256::repeat_byte(0x1),
+                H256::from_low_u64_be(7),
+            ],
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&mint).unwrap();
+        assert_eq!(sym.collection, Some(collection));
+        assert_eq!(sym.contract_address, collection);
+
+        // A listing sale: the offer holds one ERC721, paid for in ETH
+        let words: Vec<U256> = vec![
+            U256::zero(),    // orderHash
+            U256::one(),     // recipient
+            U256::from(128), // offer offset
+            U256::from(288), // consideration offset
+            U256::one(),     // offer length
+            U256::from(2),   // itemType ERC721
+            U256::from_big_endian(collection.as_bytes()),
+            U256::from(4242), // identifier
+            U256::one(),      // amount
+            U256::zero(),     // consideration length
+        ];
+        let mut data = vec![0u8; words.len() * 32];
+        for (i, w) in words.iter().enumerate() {
+            w.to_big_endian(&mut data[i * 32..(i + 1) * 32]);
+        }
+        let sale = Log {
+            address: Address::repeat_byte(0x5e),
+            topics: vec![event_signature_to_topic0(SEAPORT_ORDER_FULFILLED_SIG)],
+            data: data.into(),
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&sale).unwrap();
+        assert_eq!(sym.symbol(), "BuyNFT");
+        assert_eq!(sym.collection, Some(collection));
+        assert_eq!(sym.token_id, Some(U256::from(4242)));
+        assert!(seaport_nft(&[0xff; 40]).is_none());
+    }
+
     #[test]
     fn test_default_has_core_symbols() {
         let dict = SymbolDictionary::default();
-        assert_eq!(dict.len(), 17);
+        assert_eq!(dict.len(), 27);
     }
 
     #[test]
@@ -426,7 +1047,7 @@ mod tests {
     #[test]
     fn test_all_new_symbols_registered() {
         let dict = SymbolDictionary::default();
-        assert_eq!(dict.len(), 17);
+        assert_eq!(dict.len(), 27);
     }
 
     #[test]
@@ -514,4 +1135,370 @@ mod tests {
         assert_eq!(sym.to, buy_token);
         assert_eq!(sym.value, buy_amount);
     }
+
+    #[test]
+    fn test_fingerprint_tracks_vocabulary() {
+        let base = SymbolDictionary::default().fingerprint();
+        assert_eq!(base, SymbolDictionary::default().fingerprint());
+        assert_ne!(base, SymbolDictionary::empty().fingerprint());
+
+        let plugin = |parser| crate::plugins::SymbolPlugin {
+            name: "Custom".into(),
+            symbol: "Cx".into(),
+            chain: None,
+            event_topic: H256::repeat_byte(0x42),
+            parser,
+            overrides: false,
+        };
+        let mut generic = SymbolDictionary::default();
+        generic
+            .register_plugin(plugin(crate::plugins::ParserType::Generic))
+            .unwrap();
+        let mut transfer = SymbolDictionary::default();
+        transfer
+            .register_plugin(plugin(crate::plugins::ParserType::Transfer))
+            .unwrap();
+
+        assert_ne!(generic.fingerprint(), base);
+        assert_ne!(generic.fingerprint(), transfer.fingerprint());
+    }
+
+    #[test]
+    fn test_plugin_parsed_provenance() {
+        let topic = H256::repeat_byte(0x42);
+        let mut dict = SymbolDictionary::default();
+        dict.register_plugin(crate::plugins::SymbolPlugin {
+            name: "Custom".into(),
+            symbol: "Cx".into(),
+            chain: None,
+            event_topic: topic,
+            parser: crate::plugins::ParserType::Swap,
+            overrides: false,
+        })
+        .unwrap();
+
+        let log = Log {
+            topics: vec![topic, H256::repeat_byte(0x11)],
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&log).unwrap();
+        assert_eq!(sym.provenance, SymbolProvenance::PluginParsed);
+
+        let transfer = Log {
+            topics: vec![event_signature_to_topic0(TRANSFER_SIG)],
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&transfer).unwrap();
+        assert_eq!(sym.provenance, SymbolProvenance::RpcLog);
+    }
+
+    #[test]
+    fn test_topic_conflicts() {
+        use crate::plugins::{ParserType, SymbolPlugin};
+
+        let transfer = event_signature_to_topic0(TRANSFER_SIG);
+        let plugin = |symbol: &str, topic, overrides| SymbolPlugin {
+            name: symbol.into(),
+            symbol: symbol.into(),
+            chain: None,
+            event_topic: topic,
+            parser: ParserType::Generic,
+            overrides,
+        };
+        let mut dict = SymbolDictionary::default();
+
+        // A core topic is only taken over on request
+        let err = dict
+            .register_plugin(plugin("MyTf", transfer, false))
+            .unwrap_err();
+        assert!(matches!(err, SodsError::SymbolConflict { ref existing, .. } if existing == "Tf"));
+        assert_eq!(dict.symbol_for_topic(transfer), Some("Tf"));
+        dict.register_plugin(plugin("MyTf", transfer, true))
+            .unwrap();
+        assert_eq!(dict.symbol_for_topic(transfer), Some("MyTf"));
+        assert!(!dict.topics_for_symbol("Tf").contains(&transfer));
+
+        // Two plugins on one topic collide; the same plugin twice does not
+        let topic = H256::repeat_byte(0x42);
+        dict.register_plugin(plugin("Cx", topic, false)).unwrap();
+        dict.register_plugin(plugin("Cx", topic, false)).unwrap();
+        assert!(dict.register_plugin(plugin("Cy", topic, false)).is_err());
+        assert!(dict.register_custom(topic, "Cz").is_err());
+        dict.replace_custom(topic, "Cz");
+        assert_eq!(dict.symbol_for_topic(topic), Some("Cz"));
+        assert_eq!(dict.mappings().len(), SymbolDictionary::default().len() + 1);
+    }
+
+    #[test]
+    fn test_symbols_from_abi() {
+        let abi = r#"[
+            {"type": "event", "name": "Supply", "anonymous": false, "inputs": [
+                {"name": "reserve", "type": "address", "indexed": true},
+                {"name": "user", "type": "address", "indexed": false},
+                {"name": "onBehalfOf", "type": "address", "indexed": true},
+                {"name": "amount", "type": "uint256", "indexed": false},
+                {"name": "referralCode", "type": "uint16", "indexed": true}
+            ]},
+            {"type": "event", "name": "Paused", "anonymous": false, "inputs": []},
+            {"type": "event", "name": "Hidden", "anonymous": true, "inputs": []},
+            {"type": "function", "name": "supply", "inputs": [], "outputs": [],
+             "stateMutability": "nonpayable"}
+        ]"#;
+        let dict = SymbolDictionary::from_abi(abi, "Aave").unwrap();
+        let supply = event_signature_to_topic0("Supply(address,address,address,uint256,uint16)");
+        assert_eq!(dict.symbol_for_topic(supply), Some("AaveSupply"));
+        assert_eq!(dict.mappings().len(), 2);
+
+        let mut data = vec![0u8; 64];
+        data[12..32].copy_from_slice(&[0x22; 20]);
+        data[63] = 100;
+        let log = Log {
+            topics: vec![
+                supply,
+                H256::from(Address::repeat_byte(0x11)),
+                H256::from(Address::repeat_byte(0x33)),
+                H256::from_low_u64_be(7),
+            ],
+            data: data.into(),
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&log).unwrap();
+        assert_eq!(sym.symbol(), "AaveSupply");
+        assert_eq!(sym.from, Address::repeat_byte(0x11));
+        assert_eq!(sym.to, Address::repeat_byte(0x22));
+        assert_eq!(sym.value, U256::from(100));
+        assert_eq!(sym.provenance, SymbolProvenance::PluginParsed);
+
+        // Nothing is registered when one event collides with a core symbol
+        let erc20 = r#"[
+            {"type": "event", "name": "Approval", "anonymous": false, "inputs": []},
+            {"type": "event", "name": "Transfer", "anonymous": false, "inputs": [
+                {"name": "from", "type": "address", "indexed": true},
+                {"name": "to", "type": "address", "indexed": true},
+                {"name": "value", "type": "uint256", "indexed": false}
+            ]}
+        ]"#;
+        let mut core = SymbolDictionary::default();
+        let err = core.register_abi(erc20, "Tok").unwrap_err();
+        assert!(matches!(err, SodsError::SymbolConflict { ref existing, .. } if existing == "Tf"));
+        assert_eq!(core.mappings().len(), SymbolDictionary::default().len());
+        assert!(SymbolDictionary::from_abi(abi, "Aave.").is_err());
+        assert!(SymbolDictionary::from_abi("{", "Aave").is_err());
+        assert_ne!(
+            SymbolDictionary::from_abi(erc20, "Tok")
+                .unwrap()
+                .fingerprint(),
+            SymbolDictionary::from_abi(erc20, "Erc")
+                .unwrap()
+                .fingerprint()
+        );
+    }
+
+    #[test]
+    fn test_dictionary_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dictionary.toml");
+        std::fs::write(
+            &path,
+            r#"
+                [[symbols]]
+                signature = "Staked(address,uint256)"
+                symbol = "Stk"
+
+                [[symbols]]
+                signature = "Sweep(address,address,uint256)"
+                symbol = "Swp"
+                parser = "transfer"
+            "#,
+        )
+        .unwrap();
+        let dictionary = SymbolDictionary::from_file(&path).unwrap();
+        let staked = event_signature_to_topic0("Staked(address,uint256)");
+        assert_eq!(dictionary.symbol_for_topic(staked), Some("Stk"));
+        assert_eq!(dictionary.topics_for_symbol("Swp").len(), 1);
+        let transfer = event_signature_to_topic0(TRANSFER_SIG);
+        assert_eq!(dictionary.symbol_for_topic(transfer), Some("Tf"));
+
+        let entry = |signature: &str, symbol: &str| DictionaryEntry {
+            signature: signature.into(),
+            symbol: symbol.into(),
+            parser: crate::plugins::ParserType::Generic,
+        };
+        // Remapping a built-in topic registers nothing
+        let mut dictionary = SymbolDictionary::default();
+        let entries = [
+            entry("Staked(address,uint256)", "Stk"),
+            entry(TRANSFER_SIG, "Xf"),
+        ];
+        assert!(matches!(
+            dictionary.register_entries(&entries),
+            Err(SodsError::SymbolConflict { .. })
+        ));
+        assert_eq!(dictionary.symbol_for_topic(staked), None);
+
+        assert!(entry("Staked(address, uint256)", "Stk").validate().is_err());
+        assert!(entry("Staked", "Stk").validate().is_err());
+        assert!(entry("Staked(address,uint256)", "").validate().is_err());
+        assert!(SymbolDictionary::from_file(dir.path().join("missing.toml")).is_err());
+    }
+
+    #[test]
+    fn test_erc1155_transfers() {
+        let dict = SymbolDictionary::default();
+        let collection = Address::repeat_byte(0x55);
+        let (from, to) = (Address::repeat_byte(0x1), Address::repeat_byte(0x2));
+        let encode = |words: &[u64]| -> Vec<u8> {
+            words
+                .iter()
+                .flat_map(|w| H256::from_low_u64_be(*w).0)
+                .collect()
+        };
+        let log = |sig: &str, from: Address, data: Vec<u8>| Log {
+            address: collection,
+            topics: vec![
+                event_signature_to_topic0(sig),
+                H256::repeat_byte(0x9), // operator
+                H256::from(from),
+                H256::from(to),
+            ],
+            data: data.into(),
+            log_index: Some(3.into()),
+            ..Default::default()
+        };
+
+        let single = log(TRANSFER_SINGLE_SIG, from, encode(&[42, 5]));
+        let sym = dict.parse_log(&single).unwrap();
+        assert_eq!(sym.symbol, "Tf");
+        assert_eq!((sym.from, sym.to), (from, to));
+        assert_eq!((sym.token_id, sym.value), (Some(42.into()), 5.into()));
+        assert_eq!(sym.collection, Some(collection));
+
+        // ids [7, 8, 9] at 0x40, values [1, 10, 100] at 0xc0
+        let data = encode(&[0x40, 0xc0, 3, 7, 8, 9, 3, 1, 10, 100]);
+        let batch = log(TRANSFER_BATCH_SIG, Address::zero(), data);
+        let symbols = dict.parse_log_all(&batch);
+        assert_eq!(symbols.len(), 3);
+        assert!(symbols.iter().all(|s| s.symbol == "MintNFT" && s.to == to));
+        assert_eq!(symbols[1].token_id, Some(8.into()));
+        assert_eq!(symbols[2].value, 100.into());
+        assert_eq!(dict.parse_log(&batch), Some(symbols[0].clone()));
+        assert_eq!(dict.parse_log_all(&single).len(), 1);
+
+        // Mismatched arrays keep the addresses and drop the items
+        let data = encode(&[0x40, 0xa0, 2, 7, 8, 1, 1]);
+        let symbols = dict.parse_log_all(&log(TRANSFER_BATCH_SIG, from, data));
+        assert_eq!(symbols.len(), 1);
+        assert_eq!((symbols[0].from, symbols[0].token_id), (from, None));
+
+        // So does a batch too large to expand
+        let n = MAX_ERC1155_BATCH_ITEMS as u64 + 1;
+        let mut words = vec![0x40, 0x40 + 32 * (n + 1), n];
+        words.extend(1..=n);
+        words.push(n);
+        words.extend(std::iter::repeat(1).take(n as usize));
+        let symbols = dict.parse_log_all(&log(TRANSFER_BATCH_SIG, from, encode(&words)));
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].token_id, None);
+    }
+
+    #[test]
+    fn test_curve_and_balancer_swaps() {
+        let dict = SymbolDictionary::default();
+        let buyer = Address::repeat_byte(0x1);
+        let token_out = Address::repeat_byte(0x3);
+        let encode = |words: &[u64]| -> Vec<u8> {
+            words
+                .iter()
+                .flat_map(|w| H256::from_low_u64_be(*w).0)
+                .collect()
+        };
+
+        // sold coin 0, 1000 in; bought coin 1, 998 out
+        let curve = Log {
+            topics: vec![
+                event_signature_to_topic0(CURVE_TOKEN_EXCHANGE_SIG),
+                H256::from(buyer),
+            ],
+            data: encode(&[0, 1000, 1, 998]).into(),
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&curve).unwrap();
+        assert_eq!(sym.symbol, "Sw");
+        assert_eq!(sym.from, buyer);
+        assert_eq!((sym.amount_in, sym.value), (Some(1000.into()), 998.into()));
+
+        let balancer = Log {
+            topics: vec![
+                event_signature_to_topic0(BALANCER_SWAP_SIG),
+                H256::repeat_byte(0x7), // poolId
+                H256::from(Address::repeat_byte(0x2)),
+                H256::from(token_out),
+            ],
+            data: encode(&[50, 49]).into(),
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&balancer).unwrap();
+        assert_eq!(sym.symbol, "Sw");
+        assert_eq!((sym.from, sym.to), (Address::zero(), token_out));
+        assert_eq!((sym.amount_in, sym.value), (Some(50.into()), 49.into()));
+
+        let underlying = event_signature_to_topic0(CURVE_TOKEN_EXCHANGE_UNDERLYING_SIG);
+        assert_eq!(dict.symbol_for_topic(underlying), Some("Sw"));
+    }
+
+    #[test]
+    fn test_staking_events() {
+        let dict = SymbolDictionary::default();
+        let staker = Address::repeat_byte(0x1);
+        let receiver = Address::repeat_byte(0x2);
+        let encode = |words: &[u64]| -> Vec<u8> {
+            words
+                .iter()
+                .flat_map(|w| H256::from_low_u64_be(*w).0)
+                .collect()
+        };
+
+        // 32 ETH submitted with no referral
+        let submitted = Log {
+            topics: vec![
+                event_signature_to_topic0(LIDO_SUBMITTED_SIG),
+                H256::from(staker),
+            ],
+            data: encode(&[32, 0]).into(),
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&submitted).unwrap();
+        assert_eq!(sym.symbol, "Stake");
+        assert_eq!((sym.from, sym.value), (staker, 32.into()));
+
+        let claimed = Log {
+            topics: vec![
+                event_signature_to_topic0(LIDO_WITHDRAWAL_CLAIMED_SIG),
+                H256::from_low_u64_be(7), // requestId
+                H256::from(staker),
+                H256::from(receiver),
+            ],
+            data: encode(&[31]).into(),
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&claimed).unwrap();
+        assert_eq!(sym.symbol, "Unstake");
+        assert_eq!((sym.from, sym.to, sym.value), (staker, receiver, 31.into()));
+
+        // 10 rETH burned for 11 ETH
+        let burned = Log {
+            topics: vec![
+                event_signature_to_topic0(ROCKET_TOKENS_BURNED_SIG),
+                H256::from(staker),
+            ],
+            data: encode(&[10, 11, 1_700_000_000]).into(),
+            ..Default::default()
+        };
+        let sym = dict.parse_log(&burned).unwrap();
+        assert_eq!(sym.symbol, "Unstake");
+        assert_eq!((sym.amount_in, sym.value), (Some(10.into()), 11.into()));
+
+        let deposit = event_signature_to_topic0(ROCKET_DEPOSIT_RECEIVED_SIG);
+        assert_eq!(dict.symbol_for_topic(deposit), Some("Stake"));
+    }
 }
@@ -0,0 +1,334 @@
+//! Epoch-level behavioral summaries.
+//!
+//! This module provides the `EpochSummarizer` which builds a second-level
+//! Merkle tree over per-block BMT roots for a day (or any fixed epoch),
+//! producing a single epoch root plus per-block inclusion proofs.
+//!
+//! Long-horizon attestations can then reference one compact commitment
+//! instead of thousands of individual block roots.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Keccak};
+
+use crate::error::{Result, SodsError};
+use crate::tree::BehavioralMerkleTree;
+
+/// Length of a daily epoch in seconds.
+pub const DAILY_EPOCH_SECS: u64 = 86_400;
+
+/// Compute the epoch identifier for a block timestamp.
+///
+/// Epochs are aligned to the Unix epoch, so `epoch_id * epoch_secs` is the
+/// first second of the epoch. Returns 0 if `epoch_secs` is 0.
+pub fn epoch_id_for_timestamp(timestamp: u64, epoch_secs: u64) -> u64 {
+    timestamp.checked_div(epoch_secs).unwrap_or(0)
+}
+
+/// Compute the epoch leaf hash for a block.
+///
+/// Formula: `keccak256(abi.encodePacked(uint64(block_number), bytes32(bmt_root)))`
+pub fn epoch_leaf_hash(block_number: u64, bmt_root: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(&block_number.to_be_bytes());
+    hasher.update(bmt_root);
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    output
+}
+
+/// A single block entry committed into an epoch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochBlock {
+    /// Block number
+    pub block_number: u64,
+    /// The block's BMT root (Keccak256)
+    pub bmt_root: [u8; 32],
+}
+
+/// Accumulates per-block BMT roots for one epoch.
+///
+/// # Example
+///
+/// ```rust
+/// use sods_core::{BehavioralMerkleTree, BehavioralSymbol, EpochSummarizer};
+///
+/// let bmt = BehavioralMerkleTree::new(vec![BehavioralSymbol::new("Tf", 0)]);
+///
+/// let mut summarizer = EpochSummarizer::new(19_700);
+/// summarizer.add_block(100, bmt.root()).unwrap();
+/// summarizer.add_block(101, [0u8; 32]).unwrap();
+///
+/// let summary = summarizer.finalize();
+/// let proof = summary.generate_proof(100).unwrap();
+/// assert!(proof.verify(&summary.root));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct EpochSummarizer {
+    epoch_id: u64,
+    blocks: BTreeMap<u64, [u8; 32]>,
+}
+
+impl EpochSummarizer {
+    /// Create an empty summarizer for the given epoch.
+    pub fn new(epoch_id: u64) -> Self {
+        Self {
+            epoch_id,
+            blocks: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the epoch identifier.
+    #[inline]
+    pub fn epoch_id(&self) -> u64 {
+        self.epoch_id
+    }
+
+    /// Returns the number of blocks added so far.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Returns true if no blocks have been added.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Add a block's BMT root to the epoch.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SodsError::InternalError` if the block was already added with
+    /// a different root. Re-adding the same root is a no-op.
+    pub fn add_block(&mut self, block_number: u64, bmt_root: [u8; 32]) -> Result<()> {
+        match self.blocks.get(&block_number) {
+            Some(existing) if *existing != bmt_root => Err(SodsError::InternalError(format!(
+                "Conflicting BMT root for block {} in epoch {}",
+                block_number, self.epoch_id
+            ))),
+            _ => {
+                self.blocks.insert(block_number, bmt_root);
+                Ok(())
+            }
+        }
+    }
+
+    /// Add a block from its Behavioral Merkle Tree.
+    pub fn add_tree(&mut self, block_number: u64, bmt: &BehavioralMerkleTree) -> Result<()> {
+        self.add_block(block_number, bmt.root())
+    }
+
+    /// Build the epoch tree and return the resulting summary.
+    ///
+    /// Blocks are committed in ascending block-number order.
+    pub fn finalize(self) -> EpochSummary {
+        let blocks: Vec<EpochBlock> = self
+            .blocks
+            .into_iter()
+            .map(|(block_number, bmt_root)| EpochBlock {
+                block_number,
+                bmt_root,
+            })
+            .collect();
+
+        let (_, root) = EpochSummary::build_layers(&blocks);
+
+        EpochSummary {
+            epoch_id: self.epoch_id,
+            root,
+            blocks,
+        }
+    }
+}
+
+/// A finalized epoch commitment over per-block BMT roots.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochSummary {
+    /// Epoch identifier
+    pub epoch_id: u64,
+    /// The epoch root (Keccak256 Merkle root over block leaves)
+    pub root: [u8; 32],
+    /// Committed blocks, sorted by block number
+    pub blocks: Vec<EpochBlock>,
+}
+
+impl EpochSummary {
+    /// First block in the epoch, if any.
+    pub fn start_block(&self) -> Option<u64> {
+        self.blocks.first().map(|b| b.block_number)
+    }
+
+    /// Last block in the epoch, if any.
+    pub fn end_block(&self) -> Option<u64> {
+        self.blocks.last().map(|b| b.block_number)
+    }
+
+    /// Generate an inclusion proof for a block's BMT root.
+    ///
+    /// Returns `None` if the block is not part of this epoch.
+    pub fn generate_proof(&self, block_number: u64) -> Option<EpochProof> {
+        let leaf_index = self
+            .blocks
+            .binary_search_by_key(&block_number, |b| b.block_number)
+            .ok()?;
+
+        let (layers, _) = Self::build_layers(&self.blocks);
+
+        let mut path = Vec::new();
+        let mut directions = Vec::new();
+        let mut idx = leaf_index;
+
+        for layer in layers.iter().take(layers.len().saturating_sub(1)) {
+            if idx % 2 == 0 {
+                // Sibling is on the right (or self if odd layer)
+                path.push(*layer.get(idx + 1).unwrap_or(&layer[idx]));
+                directions.push(true);
+            } else {
+                path.push(layer[idx - 1]);
+                directions.push(false);
+            }
+            idx /= 2;
+        }
+
+        let block = self.blocks[leaf_index];
+        Some(EpochProof {
+            epoch_id: self.epoch_id,
+            block_number: block.block_number,
+            bmt_root: block.bmt_root,
+            path,
+            directions,
+        })
+    }
+
+    /// Build tree layers using the same rules as the BMT.
+    fn build_layers(blocks: &[EpochBlock]) -> (Vec<Vec<[u8; 32]>>, [u8; 32]) {
+        let leaves: Vec<[u8; 32]> = blocks
+            .iter()
+            .map(|b| epoch_leaf_hash(b.block_number, &b.bmt_root))
+            .collect();
+        BehavioralMerkleTree::build_tree(leaves)
+    }
+}
+
+/// Proof that a block's BMT root is committed in an epoch root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EpochProof {
+    /// Epoch identifier
+    pub epoch_id: u64,
+    /// Block number being proved
+    pub block_number: u64,
+    /// The block's BMT root
+    pub bmt_root: [u8; 32],
+    /// Sibling hashes from leaf to epoch root
+    pub path: Vec<[u8; 32]>,
+    /// Direction for each sibling: true = sibling is on right, false = on left
+    pub directions: Vec<bool>,
+}
+
+impl EpochProof {
+    /// Verify this proof against an expected epoch root.
+    pub fn verify(&self, epoch_root: &[u8; 32]) -> bool {
+        if self.path.len() != self.directions.len() {
+            return false;
+        }
+
+        let mut current = epoch_leaf_hash(self.block_number, &self.bmt_root);
+
+        for (sibling, is_right) in self.path.iter().zip(self.directions.iter()) {
+            let mut hasher = Keccak::v256();
+            if *is_right {
+                hasher.update(&current);
+                hasher.update(sibling);
+            } else {
+                hasher.update(sibling);
+                hasher.update(&current);
+            }
+            hasher.finalize(&mut current);
+        }
+
+        current == *epoch_root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn root(byte: u8) -> [u8; 32] {
+        [byte; 32]
+    }
+
+    #[test]
+    fn test_epoch_id_for_timestamp() {
+        assert_eq!(epoch_id_for_timestamp(0, DAILY_EPOCH_SECS), 0);
+        assert_eq!(epoch_id_for_timestamp(86_399, DAILY_EPOCH_SECS), 0);
+        assert_eq!(epoch_id_for_timestamp(86_400, DAILY_EPOCH_SECS), 1);
+        assert_eq!(epoch_id_for_timestamp(123, 0), 0);
+    }
+
+    #[test]
+    fn test_empty_epoch() {
+        let summary = EpochSummarizer::new(1).finalize();
+        assert!(summary.blocks.is_empty());
+        assert!(summary.generate_proof(0).is_none());
+        assert_eq!(summary.start_block(), None);
+    }
+
+    #[test]
+    fn test_single_block_root_is_leaf() {
+        let mut s = EpochSummarizer::new(1);
+        s.add_block(10, root(0xAA)).unwrap();
+        let summary = s.finalize();
+
+        assert_eq!(summary.root, epoch_leaf_hash(10, &root(0xAA)));
+        let proof = summary.generate_proof(10).unwrap();
+        assert!(proof.path.is_empty());
+        assert!(proof.verify(&summary.root));
+    }
+
+    #[test]
+    fn test_all_proofs_verify_and_order_independent() {
+        let mut a = EpochSummarizer::new(7);
+        let mut b = EpochSummarizer::new(7);
+        for n in 0..5u64 {
+            a.add_block(100 + n, root(n as u8)).unwrap();
+        }
+        for n in (0..5u64).rev() {
+            b.add_block(100 + n, root(n as u8)).unwrap();
+        }
+
+        let summary = a.finalize();
+        assert_eq!(summary.root, b.finalize().root);
+        assert_eq!(summary.start_block(), Some(100));
+        assert_eq!(summary.end_block(), Some(104));
+
+        for n in 0..5u64 {
+            let proof = summary.generate_proof(100 + n).unwrap();
+            assert!(proof.verify(&summary.root), "block {} failed", 100 + n);
+        }
+    }
+
+    #[test]
+    fn test_tampered_proof_fails() {
+        let mut s = EpochSummarizer::new(1);
+        s.add_block(1, root(1)).unwrap();
+        s.add_block(2, root(2)).unwrap();
+        let summary = s.finalize();
+
+        let mut proof = summary.generate_proof(1).unwrap();
+        proof.bmt_root = root(9);
+        assert!(!proof.verify(&summary.root));
+    }
+
+    #[test]
+    fn test_conflicting_root_rejected() {
+        let mut s = EpochSummarizer::new(1);
+        s.add_block(1, root(1)).unwrap();
+        assert!(s.add_block(1, root(1)).is_ok());
+        assert!(s.add_block(1, root(2)).is_err());
+        assert_eq!(s.len(), 1);
+    }
+}
@@ -1,10 +1,86 @@
 //! Error types for the SODS core library.
 //!
 //! This module defines all error types that can occur during
-//! symbol parsing, tree construction, and proof verification.
+//! symbol parsing, tree construction, and proof verification, along with
+//! the [`ErrorCode`] trait every SODS crate's error type implements, so
+//! callers can classify an error from any layer the same way.
 
+use std::error::Error as StdError;
+use std::time::Duration;
 use thiserror::Error;
 
+/// Broad kind of a failure, for mapping errors from any SODS crate to exit
+/// and HTTP status codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    /// The request is malformed or asks for something unsupported
+    InvalidInput,
+    /// What was asked for does not exist
+    NotFound,
+    /// Data was checked and did not verify
+    VerificationFailed,
+    /// A dependency (RPC provider, peers) is failing or unreachable
+    Unavailable,
+    /// A bug or an unexpected local failure
+    Internal,
+}
+
+impl ErrorClass {
+    /// Process exit code of a command that failed this way.
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorClass::Internal => 1,
+            ErrorClass::InvalidInput => 2,
+            ErrorClass::NotFound => 3,
+            ErrorClass::VerificationFailed => 4,
+            ErrorClass::Unavailable => 5,
+        }
+    }
+
+    /// HTTP status of a request that failed this way.
+    pub fn http_status(self) -> u16 {
+        match self {
+            ErrorClass::InvalidInput => 400,
+            ErrorClass::NotFound => 404,
+            ErrorClass::VerificationFailed => 422,
+            ErrorClass::Internal => 500,
+            ErrorClass::Unavailable => 503,
+        }
+    }
+}
+
+/// Machine-readable classification shared by the SODS error types.
+///
+/// Errors that wrap another crate's error report the wrapped error's code
+/// and class, so the root cause survives crate boundaries.
+pub trait ErrorCode: StdError {
+    /// Stable machine-readable error code, e.g. `rate_limited`.
+    fn code(&self) -> &'static str;
+
+    /// Broad kind of the failure.
+    fn class(&self) -> ErrorClass;
+
+    /// Whether the same request may succeed if retried later.
+    fn is_retryable(&self) -> bool {
+        self.class() == ErrorClass::Unavailable
+    }
+
+    /// How long to wait before retrying, if the failing side said.
+    fn retry_after(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// The innermost error in `err`'s `source()` chain, or `err` itself.
+pub fn root_cause<'a>(err: &'a (dyn StdError + 'static)) -> &'a (dyn StdError + 'static) {
+    let mut cause = err;
+    while let Some(source) = cause.source() {
+        cause = source;
+    }
+    cause
+}
+
 /// Errors that can occur in SODS core operations.
 #[derive(Debug, Error)]
 pub enum SodsError {
@@ -41,10 +117,72 @@ pub enum SodsError {
     #[error("Internal error: {0}")]
     InternalError(String),
 
+    /// An event topic is already mapped to a different symbol.
+    #[error("Topic {topic} is already mapped to '{existing}', not '{symbol}'")]
+    SymbolConflict {
+        /// The contested topic
+        topic: String,
+        /// The symbol the topic keeps
+        existing: String,
+        /// The symbol that was rejected
+        symbol: String,
+    },
+
     /// Configuration or persistence error.
     #[error("Configuration error: {0}")]
     ConfigError(String),
 }
 
+impl ErrorCode for SodsError {
+    fn code(&self) -> &'static str {
+        match self {
+            SodsError::SymbolNotFound { .. } => "symbol_not_found",
+            SodsError::VerificationFailed => "verification_failed",
+            SodsError::InvalidProof(_) => "invalid_proof",
+            SodsError::Serialization(_) => "serialization_error",
+            SodsError::UnknownTopic(_) => "unknown_topic",
+            SodsError::PatternError(_) => "pattern_error",
+            SodsError::InternalError(_) => "internal_error",
+            SodsError::SymbolConflict { .. } => "symbol_conflict",
+            SodsError::ConfigError(_) => "config_error",
+        }
+    }
+
+    fn class(&self) -> ErrorClass {
+        match self {
+            SodsError::SymbolNotFound { .. } => ErrorClass::NotFound,
+            SodsError::VerificationFailed => ErrorClass::VerificationFailed,
+            SodsError::InternalError(_) => ErrorClass::Internal,
+            SodsError::InvalidProof(_)
+            | SodsError::Serialization(_)
+            | SodsError::UnknownTopic(_)
+            | SodsError::PatternError(_)
+            | SodsError::SymbolConflict { .. }
+            | SodsError::ConfigError(_) => ErrorClass::InvalidInput,
+        }
+    }
+}
+
 /// Result type alias for SODS operations.
 pub type Result<T> = std::result::Result<T, SodsError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Error)]
+    #[error("outer: {0}")]
+    struct Outer(#[source] SodsError);
+
+    #[test]
+    fn test_root_cause_and_classes() {
+        let err = Outer(SodsError::PatternError("unbalanced".into()));
+        let root = root_cause(&err);
+        assert_eq!(root.to_string(), "Pattern error: unbalanced");
+        let root = root.downcast_ref::<SodsError>().unwrap();
+        assert_eq!(root.code(), "pattern_error");
+        assert_eq!(root.class().exit_code(), 2);
+        assert!(!root.is_retryable());
+        assert_eq!(ErrorClass::Unavailable.http_status(), 503);
+    }
+}
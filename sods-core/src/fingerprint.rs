@@ -0,0 +1,472 @@
+//! Behavioral fingerprints of contracts.
+//!
+//! A fingerprint condenses the symbols a contract emitted over a window of
+//! blocks into per-symbol counts, read as a distribution over symbol codes,
+//! with a 64-bit simhash for quick comparison. `FingerprintTracker` builds
+//! one per watched contract and window, keeps a short history of them, and
+//! reports drift when a window looks unlike the ones before it, e.g. a
+//! token contract that suddenly starts emitting `BridgeOut`.
+
+use ethereum_types::Address;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, SodsError};
+use crate::symbol::BehavioralSymbol;
+
+/// Blocks per fingerprint, unless configured otherwise.
+pub const DEFAULT_WINDOW_BLOCKS: u64 = 100;
+
+/// Distance from the baseline at which a window counts as drift.
+pub const DEFAULT_DRIFT_THRESHOLD: f64 = 0.3;
+
+/// Symbols a window and its baseline each need before they are compared.
+pub const DEFAULT_MIN_SYMBOLS: u64 = 10;
+
+/// Fingerprints kept per contract; together they form the baseline.
+const MAX_HISTORY: usize = 8;
+
+/// What one contract emitted over a range of blocks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub contract: Address,
+    pub first_block: u64,
+    pub last_block: u64,
+    /// Occurrences of each symbol
+    pub counts: BTreeMap<String, u64>,
+}
+
+impl Fingerprint {
+    /// An empty fingerprint of `contract` covering `first_block` onwards.
+    pub fn new(contract: Address, first_block: u64) -> Self {
+        Self {
+            contract,
+            first_block,
+            last_block: first_block,
+            counts: BTreeMap::new(),
+        }
+    }
+
+    /// Count `symbol` towards the fingerprint.
+    pub fn add(&mut self, symbol: &str) {
+        *self.counts.entry(symbol.to_string()).or_default() += 1;
+    }
+
+    /// Symbols counted in total.
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    /// Share of each symbol, summing to 1 unless the fingerprint is empty.
+    pub fn distribution(&self) -> BTreeMap<String, f64> {
+        let total = self.total().max(1) as f64;
+        self.counts
+            .iter()
+            .map(|(symbol, count)| (symbol.clone(), *count as f64 / total))
+            .collect()
+    }
+
+    /// Total variation distance between the two distributions: 0 for the
+    /// same mix of symbols, 1 for no symbol in common.
+    pub fn distance(&self, other: &Fingerprint) -> f64 {
+        let (ours, theirs) = (self.distribution(), other.distribution());
+        let symbols: HashSet<&String> = ours.keys().chain(theirs.keys()).collect();
+        let sum: f64 = symbols
+            .into_iter()
+            .map(|s| (ours.get(s).unwrap_or(&0.0) - theirs.get(s).unwrap_or(&0.0)).abs())
+            .sum();
+        sum / 2.0
+    }
+
+    /// Simhash over the symbol codes, each weighted by its count. Similar
+    /// distributions differ in few bits; the hash is stable across runs
+    /// and machines.
+    pub fn simhash(&self) -> u64 {
+        let mut weights = [0i64; 64];
+        for (symbol, count) in &self.counts {
+            let digest = Keccak256::digest(symbol.as_bytes());
+            let mut bits = [0u8; 8];
+            bits.copy_from_slice(&digest[..8]);
+            let bits = u64::from_be_bytes(bits);
+            for (i, weight) in weights.iter_mut().enumerate() {
+                let count = i64::try_from(*count).unwrap_or(i64::MAX);
+                if bits >> i & 1 == 1 {
+                    *weight = weight.saturating_add(count);
+                } else {
+                    *weight = weight.saturating_sub(count);
+                }
+            }
+        }
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, w)| **w > 0)
+            .fold(0, |hash, (i, _)| hash | 1 << i)
+    }
+
+    /// Several fingerprints of one contract combined into one.
+    fn merged<'a>(contract: Address, parts: impl IntoIterator<Item = &'a Fingerprint>) -> Self {
+        let mut merged = Fingerprint::new(contract, u64::MAX);
+        for part in parts {
+            merged.first_block = merged.first_block.min(part.first_block);
+            merged.last_block = merged.last_block.max(part.last_block);
+            for (symbol, count) in &part.counts {
+                *merged.counts.entry(symbol.clone()).or_default() += count;
+            }
+        }
+        if merged.first_block == u64::MAX {
+            merged.first_block = merged.last_block;
+        }
+        merged
+    }
+}
+
+/// A window that differs from the contract's earlier behavior.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FingerprintDrift {
+    pub contract: Address,
+    /// The earlier windows combined
+    pub baseline: Fingerprint,
+    pub current: Fingerprint,
+    /// Total variation distance from the baseline
+    pub distance: f64,
+    /// Symbols the baseline never had
+    pub new_symbols: Vec<String>,
+    /// Bits in which the two simhashes differ
+    pub simhash_distance: u32,
+}
+
+impl FingerprintDrift {
+    /// One-line description, e.g. for notifications.
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{:?} drifted {:.0}% from its baseline over blocks {}-{}",
+            self.contract,
+            self.distance * 100.0,
+            self.current.first_block,
+            self.current.last_block
+        );
+        if !self.new_symbols.is_empty() {
+            summary.push_str(&format!(
+                "; started emitting {}",
+                self.new_symbols.join(", ")
+            ));
+        }
+        summary
+    }
+}
+
+/// Recent fingerprints per contract, as stored on disk.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FingerprintHistory {
+    #[serde(default)]
+    contracts: BTreeMap<Address, Vec<Fingerprint>>,
+}
+
+impl FingerprintHistory {
+    /// Fingerprints of `contract`, oldest first.
+    pub fn get(&self, contract: &Address) -> &[Fingerprint] {
+        self.contracts.get(contract).map_or(&[], Vec::as_slice)
+    }
+
+    /// Record a fingerprint, dropping the oldest beyond the history limit.
+    pub fn push(&mut self, fingerprint: Fingerprint) {
+        let history = self.contracts.entry(fingerprint.contract).or_default();
+        history.push(fingerprint);
+        if history.len() > MAX_HISTORY {
+            history.remove(0);
+        }
+    }
+
+    /// Get the default path for the history file.
+    pub fn get_default_path() -> Result<PathBuf> {
+        home::home_dir()
+            .map(|h| h.join(".sods").join("fingerprints.json"))
+            .ok_or_else(|| SodsError::ConfigError("Could not determine home directory".into()))
+    }
+
+    /// Load the history from `path`; a missing file means none.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| SodsError::ConfigError(format!("Failed to read fingerprints: {}", e)))?;
+        serde_json::from_str(&content)
+            .map_err(|e| SodsError::ConfigError(format!("Invalid {}: {}", path.display(), e)))
+    }
+
+    /// Save the history to `path`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                SodsError::ConfigError(format!("Failed to create config dir: {}", e))
+            })?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| SodsError::Serialization(e.to_string()))?;
+        fs::write(path, content)
+            .map_err(|e| SodsError::ConfigError(format!("Failed to write fingerprints: {}", e)))
+    }
+}
+
+/// Fingerprints watched contracts window by window and reports drift.
+///
+/// Windows are aligned to multiples of the window size, so every tracker
+/// with the same size cuts the chain into the same windows. A window in
+/// which a contract emitted nothing is not recorded, so quiet periods do
+/// not wear down its baseline.
+#[derive(Debug, Clone)]
+pub struct FingerprintTracker {
+    contracts: Vec<Address>,
+    window: u64,
+    threshold: f64,
+    min_symbols: u64,
+    history: FingerprintHistory,
+    /// Index of the window being filled, and a fingerprint per contract
+    open: Option<(u64, Vec<Fingerprint>)>,
+    /// Whether a window was recorded since the history was last saved
+    unsaved: bool,
+}
+
+impl FingerprintTracker {
+    /// Track `contracts` with the default window and threshold.
+    pub fn new(contracts: impl IntoIterator<Item = Address>) -> Self {
+        let mut contracts: Vec<Address> = contracts.into_iter().collect();
+        contracts.sort_unstable();
+        contracts.dedup();
+        Self {
+            contracts,
+            window: DEFAULT_WINDOW_BLOCKS,
+            threshold: DEFAULT_DRIFT_THRESHOLD,
+            min_symbols: DEFAULT_MIN_SYMBOLS,
+            history: FingerprintHistory::default(),
+            open: None,
+            unsaved: false,
+        }
+    }
+
+    /// Fingerprint every `blocks` blocks (Builder pattern).
+    pub fn with_window(mut self, blocks: u64) -> Self {
+        self.window = blocks.max(1);
+        self
+    }
+
+    /// Report drift at this distance from the baseline, between 0 and 1
+    /// (Builder pattern).
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Compare only windows and baselines with at least this many symbols
+    /// (Builder pattern).
+    pub fn with_min_symbols(mut self, min_symbols: u64) -> Self {
+        self.min_symbols = min_symbols;
+        self
+    }
+
+    /// Continue from fingerprints recorded earlier (Builder pattern).
+    pub fn with_history(mut self, history: FingerprintHistory) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// The watched contracts.
+    pub fn contracts(&self) -> &[Address] {
+        &self.contracts
+    }
+
+    /// Fingerprints recorded so far.
+    pub fn history(&self) -> &FingerprintHistory {
+        &self.history
+    }
+
+    /// Save the history to `path` if a window was recorded since the last
+    /// save, returning whether it was written.
+    pub fn save_history(&mut self, path: &Path) -> Result<bool> {
+        if !self.unsaved {
+            return Ok(false);
+        }
+        self.history.save(path)?;
+        self.unsaved = false;
+        Ok(true)
+    }
+
+    /// Count the watched contracts' symbols in block `block_number`.
+    ///
+    /// Blocks are expected in ascending order. Once a block belongs to a
+    /// later window, the open window is closed, and the drift of each
+    /// contract whose window differs from its baseline is returned.
+    pub fn observe(
+        &mut self,
+        block_number: u64,
+        symbols: &[BehavioralSymbol],
+    ) -> Vec<FingerprintDrift> {
+        let index = block_number / self.window;
+        let mut drifts = Vec::new();
+        if self.open.as_ref().is_some_and(|(open, _)| *open != index) {
+            drifts = self.close();
+        }
+        let (first_block, contracts) = (index * self.window, &self.contracts);
+        let (_, open) = self.open.get_or_insert_with(|| {
+            let window = contracts
+                .iter()
+                .map(|c| Fingerprint::new(*c, first_block))
+                .collect();
+            (index, window)
+        });
+        for fingerprint in open.iter_mut() {
+            fingerprint.last_block = fingerprint.last_block.max(block_number);
+        }
+        for symbol in symbols {
+            if let Ok(i) = self.contracts.binary_search(&symbol.contract_address) {
+                open[i].add(&symbol.symbol);
+            }
+        }
+        drifts
+    }
+
+    /// Record the open window and compare it with the baselines.
+    fn close(&mut self) -> Vec<FingerprintDrift> {
+        let Some((_, window)) = self.open.take() else {
+            return Vec::new();
+        };
+        let mut drifts = Vec::new();
+        for current in window.into_iter().filter(|f| f.total() > 0) {
+            let baseline =
+                Fingerprint::merged(current.contract, self.history.get(&current.contract));
+            if let Some(drift) = self.compare(&baseline, &current) {
+                drifts.push(drift);
+            }
+            self.history.push(current);
+            self.unsaved = true;
+        }
+        drifts
+    }
+
+    fn compare(&self, baseline: &Fingerprint, current: &Fingerprint) -> Option<FingerprintDrift> {
+        if baseline.total() < self.min_symbols || current.total() < self.min_symbols {
+            return None;
+        }
+        let distance = current.distance(baseline);
+        let new_symbols: Vec<String> = current
+            .counts
+            .keys()
+            .filter(|s| !baseline.counts.contains_key(*s))
+            .cloned()
+            .collect();
+        (distance >= self.threshold || !new_symbols.is_empty()).then(|| FingerprintDrift {
+            contract: current.contract,
+            baseline: baseline.clone(),
+            current: current.clone(),
+            distance,
+            new_symbols,
+            simhash_distance: (baseline.simhash() ^ current.simhash()).count_ones(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emitted(contract: Address, symbols: &[(&str, usize)]) -> Vec<BehavioralSymbol> {
+        symbols
+            .iter()
+            .flat_map(|(symbol, count)| std::iter::repeat(*symbol).take(*count))
+            .enumerate()
+            .map(|(i, symbol)| {
+                let mut sym = BehavioralSymbol::new(symbol, i as u32);
+                sym.contract_address = contract;
+                sym
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_distance_and_simhash() {
+        let token = Address::repeat_byte(1);
+        let mut a = Fingerprint::new(token, 0);
+        let mut b = Fingerprint::new(token, 0);
+        for _ in 0..9 {
+            a.add("Tf");
+            b.add("Tf");
+        }
+        a.add("Sw");
+        b.add("BridgeOut");
+        assert!((a.distance(&b) - 0.1).abs() < 1e-9);
+        assert_eq!(a.distance(&a), 0.0);
+        assert_eq!(a.simhash(), a.clone().simhash());
+        assert!((a.simhash() ^ b.simhash()).count_ones() < 16);
+    }
+
+    #[test]
+    fn test_tracker_flags_new_behavior() {
+        let token = Address::repeat_byte(1);
+        let other = Address::repeat_byte(2);
+        let mut tracker = FingerprintTracker::new([token]).with_window(10);
+
+        // Two windows of plain transfers build the baseline
+        for block in 0..20 {
+            let mut symbols = emitted(token, &[("Tf", 2)]);
+            symbols.extend(emitted(other, &[("BridgeOut", 1)]));
+            assert!(tracker.observe(block, &symbols).is_empty());
+        }
+        // Then the token starts bridging out
+        for block in 20..30 {
+            assert!(tracker
+                .observe(block, &emitted(token, &[("Tf", 2), ("BridgeOut", 1)]))
+                .is_empty());
+        }
+        let drifts = tracker.observe(30, &[]);
+        assert_eq!(drifts.len(), 1);
+        assert_eq!(drifts[0].new_symbols, vec!["BridgeOut".to_string()]);
+        assert_eq!(drifts[0].baseline.total(), 40);
+        assert_eq!(
+            (drifts[0].current.first_block, drifts[0].current.last_block),
+            (20, 29)
+        );
+        assert_eq!(tracker.history().get(&token).len(), 3);
+        assert!(tracker.history().get(&other).is_empty());
+    }
+
+    #[test]
+    fn test_history_saved_only_when_a_window_closes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fingerprints.json");
+        let token = Address::repeat_byte(1);
+        let mut tracker = FingerprintTracker::new([token]).with_window(10);
+
+        tracker.observe(0, &emitted(token, &[("Tf", 1)]));
+        assert!(!tracker.save_history(&path).unwrap());
+        assert!(!path.exists());
+
+        tracker.observe(10, &[]);
+        assert!(tracker.save_history(&path).unwrap());
+        assert!(!tracker.save_history(&path).unwrap());
+        assert_eq!(FingerprintHistory::load(&path).unwrap(), *tracker.history());
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fingerprints.json");
+        let mut history = FingerprintHistory::default();
+        for block in 0..(MAX_HISTORY as u64 + 2) {
+            let mut fingerprint = Fingerprint::new(Address::repeat_byte(1), block);
+            fingerprint.add("Tf");
+            history.push(fingerprint);
+        }
+        assert_eq!(history.get(&Address::repeat_byte(1)).len(), MAX_HISTORY);
+        history.save(&path).unwrap();
+        assert_eq!(FingerprintHistory::load(&path).unwrap(), history);
+        assert_eq!(
+            FingerprintHistory::load(&dir.path().join("missing.json")).unwrap(),
+            FingerprintHistory::default()
+        );
+    }
+}
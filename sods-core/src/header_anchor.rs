@@ -1,3 +1,4 @@
+use bytes::BytesMut;
 use ethers_core::types::{TransactionReceipt, H256};
 use ethers_core::utils::hex;
 use ethers_core::utils::rlp::RlpStream;
@@ -15,6 +16,18 @@ impl Hasher for KeccakHasher {
     }
 }
 
+/// Upper bound on the encoded size of `receipt`, used to size its buffer.
+fn receipt_size_hint(receipt: &TransactionReceipt) -> usize {
+    // Type byte, list header, status, gas and the 256-byte bloom
+    let fixed = 1 + 9 + 9 + 33 + 259;
+    let logs: usize = receipt
+        .logs
+        .iter()
+        .map(|log| 9 + 21 + 9 + 33 * log.topics.len() + 9 + log.data.len())
+        .sum();
+    fixed + 9 + logs + 64
+}
+
 pub fn rlp_encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
     // Determine the list size. Standard is 4.
     // L2s might append additional fields.
@@ -30,7 +43,16 @@ pub fn rlp_encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
 
     list_size += l2_fields.len();
 
-    let mut stream = RlpStream::new_list(list_size);
+    // EIP-2718 typed receipts (Type 1, 2, 3, etc.) are prefixed by their
+    // type byte; writing it first avoids copying the encoding afterwards
+    let mut buffer = BytesMut::with_capacity(receipt_size_hint(receipt));
+    if let Some(t) = receipt.transaction_type {
+        let t_u64 = t.as_u64();
+        if t_u64 > 0 {
+            buffer.extend_from_slice(&[t_u64 as u8]);
+        }
+    }
+    let mut stream = RlpStream::new_list_with_buffer(buffer, list_size);
 
     // Status (1 = success, 0 = failure)
     let status = receipt.status.map(|s| s.as_u64()).unwrap_or(1);
@@ -40,22 +62,22 @@ pub fn rlp_encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
     stream.append(&receipt.cumulative_gas_used);
 
     // Logs bloom (256 bytes)
-    stream.append(&receipt.logs_bloom.as_bytes().to_vec());
+    stream.append(&receipt.logs_bloom.as_bytes());
 
     // Logs
     stream.begin_list(receipt.logs.len());
     for log in &receipt.logs {
         stream.begin_list(3);
-        stream.append(&log.address.as_bytes().to_vec());
+        stream.append(&log.address.as_bytes());
 
         // Topics
         stream.begin_list(log.topics.len());
         for topic in &log.topics {
-            stream.append(&topic.as_bytes().to_vec());
+            stream.append(&topic.as_bytes());
         }
 
         // Data
-        stream.append(&log.data.to_vec());
+        stream.append(&log.data.as_ref());
     }
 
     // Append L2 fields if present
@@ -108,19 +130,7 @@ pub fn rlp_encode_receipt(receipt: &TransactionReceipt) -> Vec<u8> {
         }
     }
 
-    let rlp_bytes = stream.out().to_vec();
-
-    // Handle EIP-2718 typed transactions (Type 1, 2, 3, etc.)
-    if let Some(t) = receipt.transaction_type {
-        let t_u64 = t.as_u64();
-        if t_u64 > 0 {
-            let mut typed = vec![t_u64 as u8];
-            typed.extend(rlp_bytes);
-            return typed;
-        }
-    }
-
-    rlp_bytes
+    stream.out().into()
 }
 
 /// Compute the Merkle-Patricia trie root from a list of receipts.
@@ -135,8 +145,8 @@ pub fn compute_receipts_root(receipts: &[TransactionReceipt]) -> H256 {
 
     let encoded_receipts: Vec<Vec<u8>> = receipts.iter().map(rlp_encode_receipt).collect();
 
-    // triehash::ordered_trie_root computes the root of a trie where keys are RLP-encoded indices
-    triehash::ordered_trie_root::<KeccakHasher, _>(encoded_receipts)
+    // Keys are RLP-encoded indices; see `ordered_trie` for the fast path
+    crate::ordered_trie::ordered_trie_root(&encoded_receipts)
 }
 
 #[cfg(test)]
@@ -164,4 +174,31 @@ mod tests {
         let root = compute_receipts_root(&[receipt]);
         assert_ne!(root, H256::zero());
     }
+
+    #[test]
+    fn test_typed_receipts_match_generic_trie() {
+        use ethers_core::types::{Address, Bytes, Log};
+
+        let receipts: Vec<TransactionReceipt> = (0..150u64)
+            .map(|i| TransactionReceipt {
+                status: Some(U64::from(i % 2)),
+                cumulative_gas_used: U256::from(21_000 * (i + 1)),
+                transaction_type: Some(U64::from(i % 3)),
+                logs: vec![Log {
+                    address: Address::repeat_byte(i as u8),
+                    topics: vec![H256::repeat_byte(0xAA)],
+                    data: Bytes::from(vec![i as u8; 40]),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })
+            .collect();
+
+        let encoded: Vec<Vec<u8>> = receipts.iter().map(rlp_encode_receipt).collect();
+        assert_eq!(encoded[2][0], 2);
+        assert_eq!(
+            compute_receipts_root(&receipts),
+            triehash::ordered_trie_root::<KeccakHasher, _>(encoded)
+        );
+    }
 }
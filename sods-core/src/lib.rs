@@ -37,32 +37,59 @@
 //! }
 //! ```
 
+#![warn(clippy::unwrap_used)]
+
+pub mod aliases;
 pub mod causal_tree;
+pub mod clock;
 pub mod deployer;
+#[cfg(feature = "evm")]
 pub mod dictionary;
+pub mod epoch;
 pub mod error;
+pub mod fingerprint;
 pub mod pattern;
+pub mod presets;
 pub mod proof;
 pub mod registry;
 pub mod symbol;
 pub mod tree;
 
 // Re-export main types for convenience
+pub use aliases::{SymbolAliases, SymbolLabel};
 pub use causal_tree::CausalMerkleTree;
-pub use dictionary::SymbolDictionary;
-pub use error::SodsError;
+pub use clock::{Clock, MockClock, ScaledClock, SharedClock, SystemClock};
+#[cfg(feature = "evm")]
+pub use dictionary::{DictionaryEntry, SymbolDictionary};
+pub use epoch::{EpochProof, EpochSummarizer, EpochSummary};
+pub use error::{root_cause, ErrorClass, ErrorCode, SodsError};
+pub use fingerprint::{Fingerprint, FingerprintDrift, FingerprintHistory, FingerprintTracker};
+pub use presets::{PatternPresets, PresetEntry};
 pub use proof::Proof;
 pub use registry::ContractRegistry;
-pub use symbol::BehavioralSymbol;
+pub use symbol::{
+    BehavioralSymbol, SymbolProvenance, BLOB_TX_SYMBOL, CL_WITHDRAWAL_SYMBOL, GAS_PER_BLOB,
+};
 pub use tree::BehavioralMerkleTree;
 pub mod shadow;
 pub use shadow::BehavioralShadow;
 pub mod commitment;
+pub mod conformance;
+#[cfg(feature = "evm")]
 pub mod header_anchor;
+#[cfg(feature = "evm")]
 pub mod plugins;
 pub mod ssz;
 pub use commitment::BehavioralCommitment;
+#[cfg(feature = "evm")]
 pub use plugins::SymbolPlugin;
+#[cfg(feature = "evm")]
 pub mod mpt;
+#[cfg(feature = "evm")]
+pub mod ordered_trie;
+#[cfg(feature = "evm")]
 pub use mpt::MptVerifier;
+#[cfg(feature = "evm")]
 pub mod storage_proof;
+pub mod stream;
+pub use stream::{PatternStreamMatcher, StreamMatch};
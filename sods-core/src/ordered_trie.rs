@@ -0,0 +1,268 @@
+//! Fast root computation for index-keyed Merkle-Patricia tries.
+//!
+//! Receipt and transaction tries are keyed by `RLP(index)`. Those keys sort
+//! in a fixed order (1..=127, then 0, then 128.. ascending), so the entries
+//! can be laid out sorted without collecting them into a map, the shared
+//! prefix of any contiguous run is the shared prefix of its first and last
+//! key, and each branch can be split with a binary search. Nodes are
+//! RLP-encoded by hand into buffers sized exactly up front.
+
+use ethers_core::types::H256;
+use sha3::{Digest, Keccak256};
+
+/// Root of the trie mapping `RLP(i)` to `values[i]`.
+///
+/// Produces the same root as `triehash::ordered_trie_root`.
+pub fn ordered_trie_root<V: AsRef<[u8]>>(values: &[V]) -> H256 {
+    if values.is_empty() {
+        // keccak256(RLP(""))
+        return H256::from_slice(&Keccak256::digest([0x80]));
+    }
+
+    // All key nibbles in one buffer, in sorted key order
+    let n = values.len();
+    let mut nibbles = Vec::with_capacity(n * 2 * key_len(n - 1));
+    let mut bounds = Vec::with_capacity(n);
+    for index in sorted_indices(n) {
+        let start = nibbles.len();
+        push_key_nibbles(index, &mut nibbles);
+        bounds.push((start, nibbles.len(), index));
+    }
+    let entries: Vec<Entry> = bounds
+        .into_iter()
+        .map(|(start, end, index)| Entry {
+            key: &nibbles[start..end],
+            value: values[index].as_ref(),
+        })
+        .collect();
+
+    H256::from_slice(&Keccak256::digest(encode_node(&entries, 0)))
+}
+
+struct Entry<'a> {
+    /// Key as nibbles
+    key: &'a [u8],
+    value: &'a [u8],
+}
+
+/// Indices in ascending order of their RLP encoding.
+fn sorted_indices(n: usize) -> impl Iterator<Item = usize> {
+    (1..n.min(128)).chain((n > 0).then_some(0)).chain(128..n)
+}
+
+/// Length of `RLP(index)`.
+fn key_len(index: usize) -> usize {
+    if index < 128 && index != 0 {
+        1
+    } else {
+        1 + be_len(index)
+    }
+}
+
+/// Bytes needed for `value` in big-endian without leading zeros.
+fn be_len(value: usize) -> usize {
+    let bits = (usize::BITS - value.leading_zeros()) as usize;
+    (bits + 7) / 8
+}
+
+fn push_key_nibbles(index: usize, out: &mut Vec<u8>) {
+    let mut push_byte = |b: u8| {
+        out.push(b >> 4);
+        out.push(b & 0x0f);
+    };
+    match index {
+        0 => push_byte(0x80),
+        1..=127 => push_byte(index as u8),
+        _ => {
+            let len = be_len(index);
+            push_byte(0x80 + len as u8);
+            for b in &index.to_be_bytes()[std::mem::size_of::<usize>() - len..] {
+                push_byte(*b);
+            }
+        }
+    }
+}
+
+/// RLP encoding of the node holding `entries`, which share their first
+/// `depth` nibbles.
+fn encode_node(entries: &[Entry], depth: usize) -> Vec<u8> {
+    let first = &entries[0];
+    if entries.len() == 1 {
+        let path = hex_prefix(&first.key[depth..], true);
+        return encode_list(&[Item::Str(&path), Item::Str(first.value)]);
+    }
+
+    let last = &entries[entries.len() - 1];
+    let shared = first.key[depth..]
+        .iter()
+        .zip(&last.key[depth..])
+        .take_while(|(a, b)| a == b)
+        .count();
+    if shared > 0 {
+        let path = hex_prefix(&first.key[depth..depth + shared], false);
+        let child = child_ref(encode_node(entries, depth + shared));
+        return encode_list(&[Item::Str(&path), child.item()]);
+    }
+
+    // A key ending here sorts first and becomes the branch value
+    let (value, rest) = if first.key.len() == depth {
+        (first.value, &entries[1..])
+    } else {
+        (&[][..], entries)
+    };
+    let mut children: Vec<ChildRef> = Vec::with_capacity(16);
+    let mut rest = rest;
+    for nibble in 0..16u8 {
+        let split = rest.partition_point(|e| e.key[depth] <= nibble);
+        let (group, tail) = rest.split_at(split);
+        children.push(if group.is_empty() {
+            ChildRef::Empty
+        } else {
+            child_ref(encode_node(group, depth + 1))
+        });
+        rest = tail;
+    }
+
+    let mut items: Vec<Item> = children.iter().map(ChildRef::item).collect();
+    items.push(Item::Str(value));
+    encode_list(&items)
+}
+
+/// Reference from a parent to a child node.
+enum ChildRef {
+    Empty,
+    /// Nodes shorter than 32 bytes are inlined
+    Inline(Vec<u8>),
+    Hash([u8; 32]),
+}
+
+impl ChildRef {
+    fn item(&self) -> Item<'_> {
+        match self {
+            ChildRef::Empty => Item::Str(&[]),
+            ChildRef::Inline(node) => Item::Raw(node),
+            ChildRef::Hash(hash) => Item::Str(hash),
+        }
+    }
+}
+
+fn child_ref(node: Vec<u8>) -> ChildRef {
+    if node.len() < 32 {
+        ChildRef::Inline(node)
+    } else {
+        ChildRef::Hash(Keccak256::digest(&node).into())
+    }
+}
+
+/// Compact (hex-prefix) encoding of a nibble path.
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let odd = nibbles.len() % 2;
+    let flag = (u8::from(leaf) * 2 + odd as u8) << 4;
+    let mut out = Vec::with_capacity(nibbles.len() / 2 + 1);
+    out.push(if odd == 1 { flag | nibbles[0] } else { flag });
+    out.extend(nibbles[odd..].chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    out
+}
+
+/// An element of an RLP list.
+enum Item<'a> {
+    /// Byte string, encoded with its length prefix
+    Str(&'a [u8]),
+    /// Already encoded item
+    Raw(&'a [u8]),
+}
+
+impl Item<'_> {
+    fn encoded_len(&self) -> usize {
+        match self {
+            Item::Str([b]) if *b < 0x80 => 1,
+            Item::Str(s) => prefix_len(s.len()) + s.len(),
+            Item::Raw(raw) => raw.len(),
+        }
+    }
+}
+
+fn prefix_len(len: usize) -> usize {
+    if len < 56 {
+        1
+    } else {
+        1 + be_len(len)
+    }
+}
+
+fn push_prefix(out: &mut Vec<u8>, offset: u8, len: usize) {
+    if len < 56 {
+        out.push(offset + len as u8);
+    } else {
+        let size = be_len(len);
+        out.push(offset + 55 + size as u8);
+        out.extend_from_slice(&len.to_be_bytes()[std::mem::size_of::<usize>() - size..]);
+    }
+}
+
+fn encode_list(items: &[Item]) -> Vec<u8> {
+    let payload: usize = items.iter().map(Item::encoded_len).sum();
+    let mut out = Vec::with_capacity(prefix_len(payload) + payload);
+    push_prefix(&mut out, 0xc0, payload);
+    for item in items {
+        match item {
+            Item::Str([b]) if *b < 0x80 => out.push(*b),
+            Item::Str(s) => {
+                push_prefix(&mut out, 0x80, s.len());
+                out.extend_from_slice(s);
+            }
+            Item::Raw(raw) => out.extend_from_slice(raw),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header_anchor::KeccakHasher;
+    use ethers_core::utils::rlp;
+
+    fn reference_root(values: &[Vec<u8>]) -> H256 {
+        triehash::ordered_trie_root::<KeccakHasher, _>(values)
+    }
+
+    #[test]
+    fn test_keys_are_emitted_in_rlp_order() {
+        let keys: Vec<Vec<u8>> = sorted_indices(300)
+            .map(|i| rlp::encode(&i).to_vec())
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+
+        for index in [0usize, 1, 127, 128, 255, 256, 65_535, 65_536] {
+            let mut nibbles = Vec::new();
+            push_key_nibbles(index, &mut nibbles);
+            let bytes: Vec<u8> = nibbles.chunks(2).map(|p| p[0] << 4 | p[1]).collect();
+            assert_eq!(bytes, rlp::encode(&index).to_vec(), "index {}", index);
+            assert_eq!(bytes.len(), key_len(index));
+        }
+    }
+
+    #[test]
+    fn test_matches_reference_implementation() {
+        for n in [
+            0usize, 1, 2, 3, 15, 16, 17, 127, 128, 129, 130, 255, 256, 257, 300, 1000,
+        ] {
+            // Tiny values exercise inlined nodes, large ones hashed nodes
+            for size in [1usize, 3, 40, 600] {
+                let values: Vec<Vec<u8>> = (0..n)
+                    .map(|i| (0..size).map(|j| (i * 31 + j) as u8).collect())
+                    .collect();
+                assert_eq!(
+                    ordered_trie_root(&values),
+                    reference_root(&values),
+                    "n={} size={}",
+                    n,
+                    size
+                );
+            }
+        }
+    }
+}
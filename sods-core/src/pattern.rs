@@ -1,20 +1,372 @@
+use crate::aliases::SymbolAliases;
 use crate::deployer::ContractRegistry;
 use crate::error::{Result, SodsError};
+use crate::presets::PatternPresets;
 use crate::symbol::BehavioralSymbol;
-use ethers_core::types::U256;
+use ethereum_types::{Address, H256, U256};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::time::{Duration, Instant};
 
-// const MAX_PATTERN_DEPTH: usize = 5;
+const MAX_PATTERN_DEPTH: usize = 5;
 const MAX_SYMBOLS_PER_PATTERN: usize = 10;
 const PARSING_TIMEOUT_MS: u64 = 10;
 const MAX_QUANTIFIER_VALUE: usize = 1000;
 const MAX_PATTERN_LENGTH: usize = 500;
 
+/// Most states the backtracking search visits in one block before giving
+/// up with an error.
+pub const MAX_SEARCH_STEPS: usize = 100_000;
+
+/// Most steps in a pattern from another node (threat feeds, peers).
+pub const MAX_UNTRUSTED_STEPS: usize = 6;
+
+/// Most capture variables and actor roles in a pattern from another node.
+pub const MAX_UNTRUSTED_CAPTURES: usize = 3;
+
+/// Names that expand to a fixed pattern instead of a symbol.
+pub const PRESETS: &[&str] = &["Sandwich", "Frontrun", "Backrun"];
+
+/// The pattern each built-in preset stands for.
+const PRESET_PATTERNS: &[(&str, &str)] = &[
+    ("Sandwich", "Tf -> Sw -> Tf"),
+    ("Frontrun", "Tf -> Sw"),
+    ("Backrun", "Sw -> Tf"),
+];
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum PatternCondition {
     None,
     FromDeployer,
     ValueGreaterThan(U256),
+    /// `value between <low> and <high>`, both bounds inclusive
+    ValueInRange(U256, U256),
+    ToAddress(Address),
+    FromAddress(Address),
+    /// `token_id == <N>`: an NFT event for this token
+    TokenId(U256),
+    /// `token_id in collection <address>`: an NFT event for any token of
+    /// this contract
+    InCollection(Address),
+    /// `gas_used > <N>`: the symbol's transaction used more than `N` gas.
+    /// Symbols without receipt data, e.g. pending transactions, never match.
+    GasGreaterThan(u64),
+    /// `on <address>`: the symbol was emitted by this contract, e.g. one
+    /// pool or protocol
+    OnContract(Address),
+    /// Address captures (`Tf($a -> $b)`): the sender and recipient bind to
+    /// the named variables on first use and must equal them afterwards.
+    /// `None` is the `_` placeholder.
+    Capture(Option<String>, Option<String>),
+    /// Actor role (`A:Tf`): the sender binds to the role on first use and
+    /// must equal it afterwards, and no other role may bind the same
+    /// address. Roles are separate from capture variables.
+    Actor(String),
+    /// Several conditions that must all hold, e.g. captures with a `where`.
+    All(Vec<PatternCondition>),
+}
+
+impl PatternCondition {
+    /// Whether this condition binds address variables.
+    pub fn has_capture(&self) -> bool {
+        match self {
+            PatternCondition::Capture(..) | PatternCondition::Actor(_) => true,
+            PatternCondition::All(conditions) => {
+                conditions.iter().any(PatternCondition::has_capture)
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether this condition needs the gas used by transactions.
+    pub fn has_gas_condition(&self) -> bool {
+        match self {
+            PatternCondition::GasGreaterThan(_) => true,
+            PatternCondition::All(conditions) => {
+                conditions.iter().any(PatternCondition::has_gas_condition)
+            }
+            _ => false,
+        }
+    }
+
+    /// The actor role this condition assigns, if any.
+    pub fn actor(&self) -> Option<&str> {
+        match self {
+            PatternCondition::Actor(name) => Some(name),
+            PatternCondition::All(conditions) => conditions.iter().find_map(|c| c.actor()),
+            _ => None,
+        }
+    }
+
+    /// Capture variables and actor roles (as `A:`) this condition binds.
+    fn variables(&self, out: &mut HashSet<String>) {
+        match self {
+            PatternCondition::Capture(from, to) => {
+                out.extend([from, to].into_iter().flatten().cloned())
+            }
+            PatternCondition::Actor(name) => {
+                out.insert(format!("{}:", name));
+            }
+            PatternCondition::All(conditions) => conditions.iter().for_each(|c| c.variables(out)),
+            _ => {}
+        }
+    }
+
+    /// This condition and `other`, both of which must hold.
+    fn and(self, other: PatternCondition) -> PatternCondition {
+        match (self, other) {
+            (PatternCondition::None, cond) | (cond, PatternCondition::None) => cond,
+            (PatternCondition::All(mut all), cond) => {
+                all.push(cond);
+                PatternCondition::All(all)
+            }
+            (cond, PatternCondition::All(mut all)) => {
+                all.insert(0, cond);
+                PatternCondition::All(all)
+            }
+            (first, second) => PatternCondition::All(vec![first, second]),
+        }
+    }
+}
+
+/// Addresses bound to capture variables so far.
+type Bindings = BTreeMap<String, Address>;
+
+/// Symbols matched by one step: the ranges it covers, in order, and the
+/// position matching continues from.
+#[derive(Debug, Clone)]
+struct Matched {
+    ranges: Vec<(usize, usize)>,
+    end: usize,
+}
+
+impl Matched {
+    fn range(first: usize, end: usize) -> Self {
+        Self {
+            ranges: vec![(first, end)],
+            end,
+        }
+    }
+
+    /// Repetitions one after another; nothing at `start` for none.
+    fn concat(repetitions: &[Matched], start: usize) -> Self {
+        Self {
+            ranges: repetitions
+                .iter()
+                .flat_map(|rep| rep.ranges.iter().copied())
+                .collect(),
+            end: repetitions.last().map_or(start, |rep| rep.end),
+        }
+    }
+
+    /// Positions of the matched symbols, in order.
+    fn positions(&self) -> Vec<usize> {
+        self.ranges
+            .iter()
+            .flat_map(|&(first, end)| first..end)
+            .collect()
+    }
+
+    /// Position of the first matched symbol, or the end if none was.
+    fn first(&self) -> usize {
+        self.ranges.first().map_or(self.end, |&(first, _)| first)
+    }
+
+    fn symbols<'m, 'a: 'm>(
+        &'m self,
+        symbols: &'a [BehavioralSymbol],
+    ) -> impl Iterator<Item = &'a BehavioralSymbol> + 'm {
+        self.ranges
+            .iter()
+            .flat_map(move |&(first, end)| &symbols[first..end])
+    }
+}
+
+/// Search state known not to lead to a match: next step, position, window
+/// anchor and bindings.
+type SearchState = (usize, usize, Option<usize>, Bindings);
+
+/// What the search has left: the states it gave up on, and how many more
+/// it may visit.
+struct Search {
+    dead: HashSet<SearchState>,
+    budget: usize,
+}
+
+/// Span a whole match must fit in (`within 3 blocks`, `within 60s`).
+///
+/// Measured from the first matched symbol. Symbols without a block number
+/// or timestamp are taken to be in the same block as the first one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchWindow {
+    Blocks(u64),
+    Seconds(u64),
+}
+
+impl MatchWindow {
+    /// Whether `symbol` lies within the window starting at `anchor`.
+    fn contains(&self, anchor: &BehavioralSymbol, symbol: &BehavioralSymbol) -> bool {
+        let (start, at, limit) = match self {
+            MatchWindow::Blocks(n) => (anchor.block_number, symbol.block_number, *n),
+            MatchWindow::Seconds(n) => (anchor.timestamp, symbol.timestamp, *n),
+        };
+        match (start, at) {
+            (Some(start), Some(at)) => start.abs_diff(at) <= limit,
+            _ => true,
+        }
+    }
+
+    /// Parse the text after `within`: `N blocks`, or a duration such as
+    /// `60s`, `5m` or `1h`.
+    fn parse(clause: &str) -> Result<Self> {
+        let invalid = || {
+            SodsError::PatternError(format!(
+                "Invalid window '{}': expected e.g. 'within 3 blocks' or 'within 60s'",
+                clause
+            ))
+        };
+        let digits = clause
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(clause.len());
+        let count: u64 = clause[..digits].parse().map_err(|_| invalid())?;
+        let secs = |factor: u64| count.checked_mul(factor).map(MatchWindow::Seconds);
+        match clause[digits..].trim() {
+            "block" | "blocks" => Some(MatchWindow::Blocks(count)),
+            "s" => secs(1),
+            "m" => secs(60),
+            "h" => secs(3600),
+            _ => None,
+        }
+        .ok_or_else(invalid)
+    }
+}
+
+/// How one step of a match landed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepMatch {
+    /// Positions of the step's symbols in the matched slice; empty for a
+    /// negated step
+    pub positions: Vec<usize>,
+    /// Log indexes between the previous step's last symbol and this step's
+    /// first, if both are in the same block
+    pub log_gap: Option<u32>,
+    /// Blocks between the previous step's last symbol and this step's
+    /// first; symbols without a block number count as the same block
+    pub block_gap: Option<u64>,
+    /// Sum of the values of the step's symbols
+    pub value: U256,
+}
+
+/// Where each step of a match landed, in pattern order, so thresholds
+/// such as "the sandwich moved more than 0.1 ETH" need no second pass
+/// over the symbols.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchReport {
+    pub steps: Vec<StepMatch>,
+}
+
+impl MatchReport {
+    fn new(steps: &[Matched], symbols: &[BehavioralSymbol]) -> Self {
+        let mut previous: Option<&BehavioralSymbol> = None;
+        let steps = steps
+            .iter()
+            .map(|matched| {
+                let positions = matched.positions();
+                let first = positions.first().map(|&i| &symbols[i]);
+                let (log_gap, block_gap) = match (previous, first) {
+                    (Some(prev), Some(first)) => {
+                        let blocks = match (prev.block_number, first.block_number) {
+                            (Some(a), Some(b)) => b.saturating_sub(a),
+                            _ => 0,
+                        };
+                        let logs =
+                            (blocks == 0).then(|| first.log_index.saturating_sub(prev.log_index));
+                        (logs, Some(blocks))
+                    }
+                    _ => (None, None),
+                };
+                if let Some(&last) = positions.last() {
+                    previous = Some(&symbols[last]);
+                }
+                StepMatch {
+                    value: positions
+                        .iter()
+                        .fold(U256::zero(), |sum, &i| sum.saturating_add(symbols[i].value)),
+                    positions,
+                    log_gap,
+                    block_gap,
+                }
+            })
+            .collect();
+        Self { steps }
+    }
+
+    /// Sum of the values of all matched symbols.
+    pub fn total_value(&self) -> U256 {
+        self.steps
+            .iter()
+            .fold(U256::zero(), |sum, step| sum.saturating_add(step.value))
+    }
+
+    /// Blocks from the first matched symbol to the last.
+    pub fn block_span(&self) -> u64 {
+        self.steps.iter().filter_map(|step| step.block_gap).sum()
+    }
+}
+
+/// Most candidates listed for a failed step.
+const MAX_EXPLAINED_CANDIDATES: usize = 20;
+
+/// Why a pattern did or did not match, from `BehavioralPattern::match_explain`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchExplanation {
+    pub matched: bool,
+    /// One entry per step, in pattern order
+    pub steps: Vec<StepTrace>,
+}
+
+impl MatchExplanation {
+    /// The step that failed, if any.
+    pub fn failed_step(&self) -> Option<&StepTrace> {
+        self.steps
+            .iter()
+            .find(|s| matches!(s.outcome, StepOutcome::Failed { .. }))
+    }
+}
+
+/// How far matching got with one step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepTrace {
+    pub step: usize,
+    /// The step in pattern syntax
+    pub text: String,
+    pub outcome: StepOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum StepOutcome {
+    /// Matched the symbols at these positions; none for a negated step
+    Matched { positions: Vec<usize> },
+    /// No match from position `from` on, given the steps before it
+    Failed {
+        from: usize,
+        /// Symbols the step could have matched, up to a limit
+        candidates: Vec<Candidate>,
+        reason: String,
+    },
+    /// Not tried, since an earlier step failed
+    NotReached,
+}
+
+/// A symbol a failed step considered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Candidate {
+    pub position: usize,
+    pub symbol: String,
+    pub log_index: u32,
+    /// The condition it failed, if it failed one
+    pub failed_condition: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -22,11 +374,411 @@ pub enum PatternStep {
     Exact(String, PatternCondition),
     AtLeast(String, usize, PatternCondition),
     Range(String, usize, usize, PatternCondition),
+    /// Alternatives (`A | B{2,}`); the leftmost match wins.
+    AnyOf(Vec<PatternStep>),
+    /// Absence (`!Sw`, `not Sw`): no such symbol between the neighbouring
+    /// steps, or between a step and the start or end of the block.
+    Absent(String, PatternCondition),
+    /// Wildcard (`*`, `any`): one symbol of any kind.
+    Any(PatternCondition),
+    /// Group (`(Tf -> Sw){3,}`): the steps matched `min` to `max` times in
+    /// a row, without limit if `max` is `None`.
+    Group(Vec<PatternStep>, usize, Option<usize>),
+    /// Same transaction (`Tf => Sw`): the steps matched in order within one
+    /// transaction.
+    SameTx(Vec<PatternStep>),
+    /// Gap limit (`Tf ->{<=3} Sw`): the step, starting at most `n` symbols
+    /// after the previous step's last one.
+    MaxGap(Box<PatternStep>, usize),
+}
+
+impl PatternStep {
+    /// Symbols this step refers to, one per alternative. For an absence
+    /// this is the symbol that must not occur.
+    pub fn symbols(&self) -> Vec<&str> {
+        match self {
+            PatternStep::Exact(s, _)
+            | PatternStep::AtLeast(s, _, _)
+            | PatternStep::Range(s, _, _, _)
+            | PatternStep::Absent(s, _) => vec![s.as_str()],
+            PatternStep::AnyOf(steps)
+            | PatternStep::Group(steps, _, _)
+            | PatternStep::SameTx(steps) => steps.iter().flat_map(PatternStep::symbols).collect(),
+            PatternStep::MaxGap(step, _) => step.symbols(),
+            PatternStep::Any(_) => Vec::new(),
+        }
+    }
+
+    /// Whether this step, or one of its alternatives or grouped steps, is a
+    /// wildcard.
+    pub fn is_wildcard(&self) -> bool {
+        match self {
+            PatternStep::Any(_) => true,
+            PatternStep::AnyOf(steps)
+            | PatternStep::Group(steps, _, _)
+            | PatternStep::SameTx(steps) => steps.iter().any(PatternStep::is_wildcard),
+            PatternStep::MaxGap(step, _) => step.is_wildcard(),
+            _ => false,
+        }
+    }
+
+    /// Whether this step, or one of its alternatives, captures addresses.
+    pub fn has_capture(&self) -> bool {
+        match self {
+            PatternStep::AnyOf(steps)
+            | PatternStep::Group(steps, _, _)
+            | PatternStep::SameTx(steps) => steps.iter().any(PatternStep::has_capture),
+            PatternStep::MaxGap(step, _) => step.has_capture(),
+            _ => self.condition().is_some_and(PatternCondition::has_capture),
+        }
+    }
+
+    /// Whether this step, or one within it, has a `gas_used` condition.
+    pub fn has_gas_condition(&self) -> bool {
+        match self {
+            PatternStep::AnyOf(steps)
+            | PatternStep::Group(steps, ..)
+            | PatternStep::SameTx(steps) => steps.iter().any(PatternStep::has_gas_condition),
+            PatternStep::MaxGap(step, _) => step.has_gas_condition(),
+            _ => self
+                .condition()
+                .is_some_and(PatternCondition::has_gas_condition),
+        }
+    }
+
+    /// Capture variables and actor roles this step binds.
+    fn variables(&self, out: &mut HashSet<String>) {
+        match self {
+            PatternStep::AnyOf(steps)
+            | PatternStep::Group(steps, ..)
+            | PatternStep::SameTx(steps) => steps.iter().for_each(|s| s.variables(out)),
+            PatternStep::MaxGap(step, _) => step.variables(out),
+            _ => {
+                if let Some(cond) = self.condition() {
+                    cond.variables(out);
+                }
+            }
+        }
+    }
+
+    fn condition(&self) -> Option<&PatternCondition> {
+        match self {
+            PatternStep::Exact(_, cond)
+            | PatternStep::AtLeast(_, _, cond)
+            | PatternStep::Range(_, _, _, cond)
+            | PatternStep::Absent(_, cond)
+            | PatternStep::Any(cond) => Some(cond),
+            PatternStep::AnyOf(_)
+            | PatternStep::Group(..)
+            | PatternStep::SameTx(_)
+            | PatternStep::MaxGap(..) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for PatternCondition {
+    /// The condition as written after `where` (captures: inside the
+    /// parentheses; actors: before the symbol).
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatternCondition::None => Ok(()),
+            PatternCondition::FromDeployer => write!(f, "from == deployer"),
+            PatternCondition::ValueGreaterThan(v) => write!(f, "value > {}", v),
+            PatternCondition::ValueInRange(low, high) => {
+                write!(f, "value between {} and {}", low, high)
+            }
+            PatternCondition::ToAddress(a) => write!(f, "to == {:?}", a),
+            PatternCondition::FromAddress(a) => write!(f, "from == {:?}", a),
+            PatternCondition::TokenId(id) => write!(f, "token_id == {}", id),
+            PatternCondition::InCollection(a) => write!(f, "token_id in collection {:?}", a),
+            PatternCondition::GasGreaterThan(gas) => write!(f, "gas_used > {}", gas),
+            PatternCondition::OnContract(a) => write!(f, "on {:?}", a),
+            PatternCondition::Capture(from, to) => {
+                let var =
+                    |v: &Option<String>| v.as_ref().map_or("_".to_string(), |n| format!("${}", n));
+                write!(f, "{} -> {}", var(from), var(to))
+            }
+            PatternCondition::Actor(name) => write!(f, "{}:", name),
+            PatternCondition::All(conditions) => {
+                let text: Vec<String> = conditions.iter().map(|c| c.to_string()).collect();
+                write!(f, "{}", text.join(" and "))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for PatternStep {
+    /// The step in pattern syntax, with amounts in wei.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |steps: &[PatternStep], sep: &str| {
+            steps
+                .iter()
+                .map(|s| s.to_string())
+                .collect::<Vec<_>>()
+                .join(sep)
+        };
+        let actor = match self.condition().and_then(PatternCondition::actor) {
+            Some(actor) => format!("{}:", actor),
+            None => String::new(),
+        };
+        match self {
+            PatternStep::Exact(s, _) => write!(f, "{}{}", actor, s)?,
+            PatternStep::AtLeast(s, min, _) => write!(f, "{}{{{},}}", s, min)?,
+            PatternStep::Range(s, min, max, _) if min == max => write!(f, "{}{{{}}}", s, min)?,
+            PatternStep::Range(s, min, max, _) => write!(f, "{}{{{},{}}}", s, min, max)?,
+            PatternStep::AnyOf(steps) => write!(f, "{}", join(steps, " | "))?,
+            PatternStep::Absent(s, _) => write!(f, "!{}{}", actor, s)?,
+            PatternStep::Any(_) => write!(f, "{}*", actor)?,
+            PatternStep::Group(steps, min, max) => {
+                write!(f, "({})", join(steps, " -> "))?;
+                match max {
+                    Some(max) if max == min => write!(f, "{{{}}}", min)?,
+                    Some(max) => write!(f, "{{{},{}}}", min, max)?,
+                    None => write!(f, "{{{},}}", min)?,
+                }
+            }
+            PatternStep::SameTx(steps) => write!(f, "{}", join(steps, " => "))?,
+            PatternStep::MaxGap(step, n) => write!(f, "{{<={}}} {}", n, step)?,
+        }
+        let Some(condition) = self.condition() else {
+            return Ok(());
+        };
+        let conditions = match condition {
+            PatternCondition::All(all) => all.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+        for capture in conditions
+            .iter()
+            .filter(|c| c.has_capture() && c.actor().is_none())
+        {
+            write!(f, "({})", capture)?;
+        }
+        for contract in conditions
+            .iter()
+            .filter(|c| matches!(c, PatternCondition::OnContract(_)))
+        {
+            write!(f, " {}", contract)?;
+        }
+        let filters: Vec<String> = conditions
+            .iter()
+            .filter(|c| {
+                !c.has_capture()
+                    && !matches!(c, PatternCondition::OnContract(_))
+                    && **c != PatternCondition::None
+            })
+            .map(|c| c.to_string())
+            .collect();
+        if !filters.is_empty() {
+            write!(f, " where {}", filters.join(" and "))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for BehavioralPattern {
+    /// The pattern in pattern syntax, with presets expanded; it parses back
+    /// to the same steps.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let steps: Vec<String> = self.steps.iter().map(|s| s.to_string()).collect();
+        write!(f, "{}", steps.join(" -> "))?;
+        match self.window {
+            Some(MatchWindow::Blocks(n)) => write!(f, " within {} blocks", n),
+            Some(MatchWindow::Seconds(n)) => write!(f, " within {}s", n),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Version of the encoding behind `BehavioralPattern::canonical_hash`. It
+/// is the first encoded byte, so a new encoding never reproduces an old
+/// hash; bump it whenever the encoding changes.
+pub const PATTERN_HASH_VERSION: u8 = 1;
+
+fn encode_u64(out: &mut Vec<u8>, n: u64) {
+    out.extend_from_slice(&n.to_be_bytes());
+}
+
+fn encode_str(out: &mut Vec<u8>, s: &str) {
+    encode_u64(out, s.len() as u64);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn encode_u256(out: &mut Vec<u8>, v: U256) {
+    let mut bytes = [0u8; 32];
+    v.to_big_endian(&mut bytes);
+    out.extend_from_slice(&bytes);
+}
+
+/// Encodings of the conditions that must all hold, nested `All`s
+/// flattened and `None` dropped.
+fn condition_parts(condition: &PatternCondition, parts: &mut Vec<Vec<u8>>) {
+    let mut out = Vec::new();
+    match condition {
+        PatternCondition::None => return,
+        PatternCondition::All(all) => {
+            for c in all {
+                condition_parts(c, parts);
+            }
+            return;
+        }
+        PatternCondition::FromDeployer => out.push(1),
+        PatternCondition::ValueGreaterThan(v) => {
+            out.push(2);
+            encode_u256(&mut out, *v);
+        }
+        PatternCondition::ValueInRange(low, high) => {
+            out.push(3);
+            encode_u256(&mut out, *low);
+            encode_u256(&mut out, *high);
+        }
+        PatternCondition::ToAddress(a) => {
+            out.push(4);
+            out.extend_from_slice(a.as_bytes());
+        }
+        PatternCondition::FromAddress(a) => {
+            out.push(5);
+            out.extend_from_slice(a.as_bytes());
+        }
+        PatternCondition::TokenId(id) => {
+            out.push(6);
+            encode_u256(&mut out, *id);
+        }
+        PatternCondition::InCollection(a) => {
+            out.push(7);
+            out.extend_from_slice(a.as_bytes());
+        }
+        PatternCondition::GasGreaterThan(gas) => {
+            out.push(8);
+            encode_u64(&mut out, *gas);
+        }
+        PatternCondition::OnContract(a) => {
+            out.push(9);
+            out.extend_from_slice(a.as_bytes());
+        }
+        PatternCondition::Capture(from, to) => {
+            out.push(10);
+            for var in [from, to] {
+                match var {
+                    Some(name) => {
+                        out.push(1);
+                        encode_str(&mut out, name);
+                    }
+                    None => out.push(0),
+                }
+            }
+        }
+        PatternCondition::Actor(name) => {
+            out.push(11);
+            encode_str(&mut out, name);
+        }
+    }
+    parts.push(out);
+}
+
+/// Conditions in a fixed order, so `where a and b` encodes as `where b and a`.
+fn encode_condition(out: &mut Vec<u8>, condition: &PatternCondition) {
+    let mut parts = Vec::new();
+    condition_parts(condition, &mut parts);
+    parts.sort();
+    parts.dedup();
+    match parts.as_slice() {
+        [] => out.push(0),
+        [part] => out.extend_from_slice(part),
+        parts => {
+            out.push(12);
+            encode_u64(out, parts.len() as u64);
+            for part in parts {
+                out.extend_from_slice(part);
+            }
+        }
+    }
+}
+
+fn encode_steps(out: &mut Vec<u8>, steps: &[PatternStep]) {
+    encode_u64(out, steps.len() as u64);
+    for step in steps {
+        encode_step(out, step);
+    }
+}
+
+fn encode_step(out: &mut Vec<u8>, step: &PatternStep) {
+    match step {
+        PatternStep::Exact(symbol, condition) => {
+            out.push(0);
+            encode_str(out, symbol);
+            encode_condition(out, condition);
+        }
+        PatternStep::AtLeast(symbol, min, condition) => {
+            out.push(1);
+            encode_str(out, symbol);
+            encode_u64(out, *min as u64);
+            encode_condition(out, condition);
+        }
+        PatternStep::Range(symbol, min, max, condition) => {
+            out.push(2);
+            encode_str(out, symbol);
+            encode_u64(out, *min as u64);
+            encode_u64(out, *max as u64);
+            encode_condition(out, condition);
+        }
+        PatternStep::AnyOf(alternatives) => {
+            // Alternatives in a fixed order, as for conditions
+            let mut parts: Vec<Vec<u8>> = alternatives
+                .iter()
+                .map(|alternative| {
+                    let mut part = Vec::new();
+                    encode_step(&mut part, alternative);
+                    part
+                })
+                .collect();
+            parts.sort();
+            parts.dedup();
+            out.push(3);
+            encode_u64(out, parts.len() as u64);
+            for part in parts {
+                out.extend_from_slice(&part);
+            }
+        }
+        PatternStep::Absent(symbol, condition) => {
+            out.push(4);
+            encode_str(out, symbol);
+            encode_condition(out, condition);
+        }
+        PatternStep::Any(condition) => {
+            out.push(5);
+            encode_condition(out, condition);
+        }
+        PatternStep::Group(steps, min, max) => {
+            out.push(6);
+            encode_steps(out, steps);
+            encode_u64(out, *min as u64);
+            match max {
+                Some(max) => {
+                    out.push(1);
+                    encode_u64(out, *max as u64);
+                }
+                None => out.push(0),
+            }
+        }
+        PatternStep::SameTx(steps) => {
+            out.push(7);
+            encode_steps(out, steps);
+        }
+        PatternStep::MaxGap(step, gap) => {
+            out.push(8);
+            encode_step(out, step);
+            encode_u64(out, *gap as u64);
+        }
+    }
 }
 
+/// Spellings of the wildcard step.
+const WILDCARDS: &[&str] = &["*", "any"];
+
 #[derive(Debug, Clone)]
 pub struct BehavioralPattern {
     steps: Vec<PatternStep>,
+    window: Option<MatchWindow>,
 }
 
 impl BehavioralPattern {
@@ -34,14 +786,173 @@ impl BehavioralPattern {
         &self.steps
     }
 
+    /// The `within` clause, if any.
+    pub fn window(&self) -> Option<MatchWindow> {
+        self.window
+    }
+
+    /// Whether any step matches `symbol`; always true with a wildcard.
+    pub fn mentions(&self, symbol: &str) -> bool {
+        self.has_wildcard()
+            || self
+                .steps
+                .iter()
+                .any(|step| step.symbols().contains(&symbol))
+    }
+
+    /// Whether any step is a wildcard, i.e. the pattern can match symbols
+    /// it does not name.
+    pub fn has_wildcard(&self) -> bool {
+        self.steps.iter().any(PatternStep::is_wildcard)
+    }
+
+    /// Whether any step binds address variables.
+    pub fn has_capture(&self) -> bool {
+        self.steps.iter().any(PatternStep::has_capture)
+    }
+
+    /// Whether any step has a `gas_used` condition, i.e. matching needs
+    /// the symbols' transaction receipts.
+    pub fn has_gas_condition(&self) -> bool {
+        self.steps.iter().any(PatternStep::has_gas_condition)
+    }
+
+    /// Check the limits on patterns from another node, such as threat
+    /// feeds and peer queries: at most `MAX_UNTRUSTED_STEPS` steps and
+    /// `MAX_UNTRUSTED_CAPTURES` capture variables and actor roles.
+    pub fn check_untrusted(&self) -> Result<()> {
+        if self.steps.len() > MAX_UNTRUSTED_STEPS {
+            return Err(SodsError::PatternError(format!(
+                "Too many steps in an untrusted pattern (max {})",
+                MAX_UNTRUSTED_STEPS
+            )));
+        }
+        let mut variables = HashSet::new();
+        self.steps.iter().for_each(|s| s.variables(&mut variables));
+        if variables.len() > MAX_UNTRUSTED_CAPTURES {
+            return Err(SodsError::PatternError(format!(
+                "Too many captures in an untrusted pattern (max {})",
+                MAX_UNTRUSTED_CAPTURES
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether any step limits its gap to the previous one, i.e. matching
+    /// depends on symbols the pattern does not name.
+    pub fn has_gap_limit(&self) -> bool {
+        self.steps
+            .iter()
+            .any(|s| matches!(s, PatternStep::MaxGap(..)))
+    }
+
+    /// The parsed pattern as versioned bytes: spacing, presets, aliases,
+    /// amount units and the order of `where` conditions or `|`
+    /// alternatives do not change it, so every spelling of a pattern
+    /// encodes the same.
+    pub fn canonical_encoding(&self) -> Vec<u8> {
+        let mut out = vec![PATTERN_HASH_VERSION];
+        match self.window {
+            None => out.push(0),
+            Some(MatchWindow::Blocks(n)) => {
+                out.push(1);
+                encode_u64(&mut out, n);
+            }
+            Some(MatchWindow::Seconds(n)) => {
+                out.push(2);
+                encode_u64(&mut out, n);
+            }
+        }
+        encode_steps(&mut out, &self.steps);
+        out
+    }
+
+    /// Keccak256 of `canonical_encoding`: the identifier `hash-pattern`
+    /// prints and ZK proofs commit to.
+    pub fn canonical_hash(&self) -> [u8; 32] {
+        use sha3::{Digest, Keccak256};
+        Keccak256::digest(self.canonical_encoding()).into()
+    }
+
+    /// Compile for matching against many blocks; see `CompiledPattern`.
+    pub fn compile(&self) -> CompiledPattern {
+        CompiledPattern::new(self.clone())
+    }
+
     /// Parse a pattern string into a BehavioralPattern.
     ///
     /// Syntax:
     /// - "A -> B": Sequence of A then B
     /// - "A{n,}": At least n occurrences of A
+    /// - "A | B{2,} -> C": A or at least two Bs, then C
+    /// - "A -> !B -> C": A then C, with no B in between (also "not B")
+    /// - "A -> * -> C": A, any one symbol, then C (also "any")
+    /// - "A ->{<=3} B": A, then B with at most 3 symbols in between
+    /// - "(A -> B){3,}": A then B, at least three times in a row
+    /// - "A => B": A then B in the same transaction
+    /// - "Tf($a -> $b) -> Tf($b -> $a)": Transfers back and forth between
+    ///   the same two addresses
+    /// - "LP+ -> Sw within 3 blocks": Whole match within 3 blocks (or a
+    ///   duration: "within 60s", "5m", "1h")
     /// - "LP+ where from == deployer": Context filter
-    /// - "Sandwich": Preset for "Tf -> Sw -> Tf"
+    /// - "ClWdw{8,} where to == 0x...": Recipient filter
+    /// - "Tf where from == 0x...": Sender filter
+    /// - "BuyNFT where token_id == 42": NFT filter (decimal or 0x hex)
+    /// - "MintNFT where token_id in collection 0x...": Collection filter
+    /// - "Sandwich": Preset for "Tf -> Sw -> Tf"; installed
+    ///   `PatternPresets` expand the same way
+    /// - "@Sandwich -> Wdw": A preset's steps, then Wdw
     pub fn parse(input: &str) -> Result<Self> {
+        Self::parse_with_aliases(input, &SymbolAliases::default())
+    }
+
+    /// Parse a pattern string, resolving symbol aliases.
+    ///
+    /// Each step's symbol is replaced by its canonical name, so
+    /// "Deposit -> Swap" with the matching aliases parses to the same steps
+    /// as "Dep -> Sw". Presets, built-in and installed (see
+    /// `PatternPresets`), are checked before aliases.
+    pub fn parse_with_aliases(input: &str, aliases: &SymbolAliases) -> Result<Self> {
+        Self::parse_with_presets(input, aliases, &PatternPresets::installed(), true)
+    }
+
+    /// Parse a pattern from another node, such as a threat rule or a peer's
+    /// query, within the limits of `check_untrusted`.
+    ///
+    /// Installed presets differ from node to node, so `@Name` references
+    /// are rejected, as is a pattern an installed preset would change.
+    /// Built-in presets are the same everywhere and still expand.
+    pub fn parse_untrusted(input: &str) -> Result<Self> {
+        if input.contains('@') {
+            return Err(SodsError::PatternError(
+                "Preset references (@Name) are local; expand them before sharing a pattern".into(),
+            ));
+        }
+        let pattern = Self::parse_with_presets(
+            input,
+            &SymbolAliases::default(),
+            &PatternPresets::default(),
+            true,
+        )?;
+        if Self::parse(input)?.canonical_encoding() != pattern.canonical_encoding() {
+            return Err(SodsError::PatternError(format!(
+                "'{}' names a local preset; expand it before sharing a pattern",
+                input.trim()
+            )));
+        }
+        pattern.check_untrusted()?;
+        Ok(pattern)
+    }
+
+    /// Parse a pattern whose `@Name` references resolve against `presets`
+    /// and the built-in presets. With `whole_name`, a pattern that is just
+    /// a user preset's name expands to that preset.
+    pub(crate) fn parse_with_presets(
+        input: &str,
+        aliases: &SymbolAliases,
+        presets: &PatternPresets,
+        whole_name: bool,
+    ) -> Result<Self> {
         let start_time = Instant::now();
 
         if input.len() > MAX_PATTERN_LENGTH {
@@ -51,176 +962,161 @@ impl BehavioralPattern {
             )));
         }
 
-        // 1. Check Presets
-        match input {
-            "Sandwich" => {
-                return Ok(Self {
-                    steps: vec![
-                        PatternStep::Exact("Tf".into(), PatternCondition::None),
-                        PatternStep::Exact("Sw".into(), PatternCondition::None),
-                        PatternStep::Exact("Tf".into(), PatternCondition::None),
-                    ],
-                })
-            }
-            "Frontrun" => {
-                return Ok(Self {
-                    steps: vec![
-                        PatternStep::Exact("Tf".into(), PatternCondition::None),
-                        PatternStep::Exact("Sw".into(), PatternCondition::None),
-                    ],
-                })
+        // A trailing "within ..." clause applies to the whole pattern
+        if let Some(idx) = input.rfind(" within ") {
+            let window = MatchWindow::parse(input[idx + 8..].trim())?;
+            let mut pattern =
+                Self::parse_with_presets(&input[..idx], aliases, presets, whole_name)?;
+            if pattern.window.is_some() {
+                return Err(SodsError::PatternError(
+                    "Only one 'within' clause is allowed".into(),
+                ));
             }
-            "Backrun" => {
-                return Ok(Self {
-                    steps: vec![
-                        PatternStep::Exact("Sw".into(), PatternCondition::None),
-                        PatternStep::Exact("Tf".into(), PatternCondition::None),
-                    ],
-                })
+            pattern.window = Some(window);
+            return Ok(pattern);
+        }
+
+        // 1. Check Presets
+        let mut symbol_count = 0;
+        if let Some(pattern) = builtin_preset(input.trim()) {
+            return Ok(Self {
+                steps: Self::parse_sequence(
+                    pattern,
+                    &SymbolAliases::default(),
+                    presets,
+                    0,
+                    &mut symbol_count,
+                    start_time,
+                )?,
+                window: None,
+            });
+        }
+        if whole_name {
+            if let Some(preset) = presets.get(input.trim()) {
+                return Self::parse_with_presets(&preset.pattern, aliases, presets, false);
             }
-            _ => {}
         }
 
-        let parts: Vec<&str> = input.split("->").collect();
+        let steps =
+            Self::parse_sequence(input, aliases, presets, 0, &mut symbol_count, start_time)?;
+
+        if steps.is_empty() {
+            return Err(SodsError::PatternError("Empty pattern".to_string()));
+        }
+        if steps.iter().all(|s| matches!(s, PatternStep::Absent(..))) {
+            return Err(SodsError::PatternError(
+                "Pattern needs at least one step that is not negated".into(),
+            ));
+        }
+
+        Ok(Self {
+            steps,
+            window: None,
+        })
+    }
+
+    /// Parse steps separated by `->`, counting their symbols into
+    /// `symbol_count`. `depth` is the number of enclosing groups and
+    /// references. A step `@Name` is replaced by the steps of the preset
+    /// it names.
+    fn parse_sequence(
+        input: &str,
+        aliases: &SymbolAliases,
+        presets: &PatternPresets,
+        depth: usize,
+        symbol_count: &mut usize,
+        start_time: Instant,
+    ) -> Result<Vec<PatternStep>> {
         let mut steps = Vec::new();
 
-        for part in parts {
-            let part = part.trim();
+        for (i, part) in split_top_level(input, "->").into_iter().enumerate() {
+            let (gap, part) = Self::parse_gap(part.trim())?;
             if part.is_empty() {
                 return Err(SodsError::PatternError("Empty pattern segment".into()));
             }
+            if gap.is_some() && i == 0 {
+                return Err(SodsError::PatternError(format!(
+                    "A gap limit needs a step before it: '{}'",
+                    input.trim()
+                )));
+            }
+            if gap.is_some() && (part.starts_with('!') || part.starts_with("not ")) {
+                return Err(SodsError::PatternError(format!(
+                    "Negated steps cannot have a gap limit: '{}'",
+                    part
+                )));
+            }
+            let first = steps.len();
+
+            if let Some(name) = part.strip_prefix('@') {
+                steps.extend(Self::parse_reference(
+                    name,
+                    aliases,
+                    presets,
+                    depth,
+                    symbol_count,
+                    start_time,
+                )?);
+                // The gap is to the first of the preset's steps
+                if let (Some(gap), Some(step)) = (gap, steps.get_mut(first)) {
+                    Self::limit_gap(step, gap)?;
+                }
+                continue;
+            }
 
-            // Parse condition if present ("... where ...")
-            let (part_base, condition) = if let Some(idx) = part.find("where") {
-                let cond_str = part[idx + 5..].trim();
-                let base = part[..idx].trim();
-
-                let cond = if cond_str == "from == deployer" {
-                    PatternCondition::FromDeployer
-                } else if let Some(stripped) = cond_str.strip_prefix("value >") {
-                    let amount_str = stripped.trim();
-                    let amount = parse_amount(amount_str)?;
-                    PatternCondition::ValueGreaterThan(amount)
-                } else {
+            // '=>' binds tighter than '->' and looser than '|'
+            let links = split_top_level(part, "=>");
+            if links.len() == 1 {
+                steps.push(Self::parse_segment(
+                    part,
+                    aliases,
+                    presets,
+                    depth,
+                    symbol_count,
+                    start_time,
+                )?);
+            } else {
+                let chain = links
+                    .into_iter()
+                    .map(|link| match link.trim() {
+                        "" => Err(SodsError::PatternError(format!(
+                            "Empty step in same-transaction chain '{}'",
+                            part
+                        ))),
+                        link if link.starts_with('{') => Err(SodsError::PatternError(format!(
+                            "Gap limits are not supported in same-transaction chains: '{}'",
+                            part
+                        ))),
+                        link => Self::parse_segment(
+                            link,
+                            aliases,
+                            presets,
+                            depth,
+                            symbol_count,
+                            start_time,
+                        ),
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                if chain.iter().any(|s| matches!(s, PatternStep::Absent(..))) {
                     return Err(SodsError::PatternError(format!(
-                        "Unsupported condition: {}",
-                        cond_str
+                        "Negated steps are not supported in same-transaction chains: '{}'",
+                        part
                     )));
-                };
-                (base, cond)
-            } else {
-                (part, PatternCondition::None)
-            };
-
-            // Check for quantifier { ... }
-            if let Some(start_idx) = part_base.find('{') {
-                if let Some(end_idx) = part_base.find('}') {
-                    // ReDoS Protection: Ensure only one quantifier block per segment
-                    if part_base[end_idx + 1..].contains('{')
-                        || part_base[..start_idx].contains('{')
-                    {
-                        return Err(SodsError::PatternError(
-                            "Nested or multiple quantifiers not supported".into(),
-                        ));
-                    }
-
-                    // Ensure no trailing text after '}' in the base part
-                    if !part_base[end_idx + 1..].trim().is_empty() {
-                        return Err(SodsError::PatternError(format!(
-                            "Unexpected text after quantifier: '{}'",
-                            &part_base[end_idx + 1..]
-                        )));
-                    }
-
-                    let symbol = part_base[..start_idx].trim().to_string();
-                    let quantifier = &part_base[start_idx + 1..end_idx]; // inside {}
-
-                    if let Some(comma_idx) = quantifier.find(',') {
-                        let min_str = quantifier[..comma_idx].trim();
-                        let max_str = quantifier[comma_idx + 1..].trim();
-
-                        let min = min_str.parse::<usize>().map_err(|_| {
-                            SodsError::PatternError(format!("Invalid min quantifier: {}", min_str))
-                        })?;
-                        if min > MAX_QUANTIFIER_VALUE {
-                            return Err(SodsError::PatternError(format!(
-                                "Quantifier too large (max {})",
-                                MAX_QUANTIFIER_VALUE
-                            )));
-                        }
-
-                        if max_str.is_empty() {
-                            // {n,}
-                            steps.push(PatternStep::AtLeast(symbol, min, condition));
-                        } else {
-                            // {n,m}
-                            let max = max_str.parse::<usize>().map_err(|_| {
-                                SodsError::PatternError(format!(
-                                    "Invalid max quantifier: {}",
-                                    max_str
-                                ))
-                            })?;
-                            if max > MAX_QUANTIFIER_VALUE {
-                                return Err(SodsError::PatternError(format!(
-                                    "Quantifier too large (max {})",
-                                    MAX_QUANTIFIER_VALUE
-                                )));
-                            }
-                            if max < min {
-                                return Err(SodsError::PatternError(format!(
-                                    "Max quantifier {} must be >= min {}",
-                                    max, min
-                                )));
-                            }
-                            steps.push(PatternStep::Range(symbol, min, max, condition));
-                        }
-                    } else {
-                        // {n} exact count shorthand -> treat as Range(n, n)
-                        let count = quantifier.trim().parse::<usize>().map_err(|_| {
-                            SodsError::PatternError(format!(
-                                "Invalid exact quantifier: {}",
-                                quantifier
-                            ))
-                        })?;
-                        if count > MAX_QUANTIFIER_VALUE {
-                            return Err(SodsError::PatternError(format!(
-                                "Quantifier too large (max {})",
-                                MAX_QUANTIFIER_VALUE
-                            )));
-                        }
-                        steps.push(PatternStep::Range(symbol, count, count, condition));
-                    }
-                } else {
-                    return Err(SodsError::PatternError(format!(
-                        "Unclosed quantifier: expected '}}' in segment '{}'",
-                        part_base
-                    )));
-                }
-            } else {
-                // Check if we have an unmatched '}'
-                if part_base.contains('}') {
-                    return Err(SodsError::PatternError("Unmatched '}' in pattern".into()));
                 }
-                // Single symbol
-                // Validation: Symbol name must be alphanumeric + simple chars (+, -, _)
-                // and MUST NOT contain control characters or null bytes.
-                if part_base.is_empty()
-                    || part_base.chars().any(|c| {
-                        c.is_control()
-                            || c == '\0'
-                            || (!c.is_alphanumeric() && c != '+' && c != '-' && c != '_')
-                    })
-                {
+                if chain.iter().any(PatternStep::has_capture) {
                     return Err(SodsError::PatternError(format!(
-                        "Invalid symbol name: {:?}",
-                        part_base
+                        "Captures are not supported in same-transaction chains: '{}'",
+                        part
                     )));
                 }
-                steps.push(PatternStep::Exact(part_base.to_string(), condition));
+                steps.push(PatternStep::SameTx(chain));
+            }
+            if let (Some(gap), Some(step)) = (gap, steps.last_mut()) {
+                Self::limit_gap(step, gap)?;
             }
 
             // Check Limits
-            if steps.len() > MAX_SYMBOLS_PER_PATTERN {
+            if *symbol_count > MAX_SYMBOLS_PER_PATTERN {
                 return Err(SodsError::PatternError(format!(
                     "Pattern too complex (max {} symbols)",
                     MAX_SYMBOLS_PER_PATTERN
@@ -233,114 +1129,1374 @@ impl BehavioralPattern {
                 ));
             }
         }
+        Ok(steps)
+    }
 
-        if steps.is_empty() {
-            return Err(SodsError::PatternError("Empty pattern".to_string()));
+    /// Split a `{<=n}` gap limit (written right after the arrow) off the
+    /// front of a step.
+    fn parse_gap(part: &str) -> Result<(Option<usize>, &str)> {
+        let Some(rest) = part.strip_prefix("{<=") else {
+            return Ok((None, part));
+        };
+        let (limit, step) = rest.split_once('}').ok_or_else(|| {
+            SodsError::PatternError(format!("Unclosed gap limit: expected '}}' in '{}'", part))
+        })?;
+        let limit: usize = limit
+            .trim()
+            .parse()
+            .map_err(|_| SodsError::PatternError(format!("Invalid gap limit '{{<={}}}'", limit)))?;
+        if limit > MAX_QUANTIFIER_VALUE {
+            return Err(SodsError::PatternError(format!(
+                "Gap limit too large (max {})",
+                MAX_QUANTIFIER_VALUE
+            )));
+        }
+        Ok((Some(limit), step.trim()))
+    }
+
+    /// Make `step` start at most `gap` symbols after the previous one.
+    fn limit_gap(step: &mut PatternStep, gap: usize) -> Result<()> {
+        match step {
+            PatternStep::Absent(..) => Err(SodsError::PatternError(format!(
+                "Negated steps cannot have a gap limit: '{}'",
+                step
+            ))),
+            // Two limits on one gap: the tighter one holds
+            PatternStep::MaxGap(_, limit) => {
+                *limit = (*limit).min(gap);
+                Ok(())
+            }
+            _ => {
+                let inner = std::mem::replace(step, PatternStep::Any(PatternCondition::None));
+                *step = PatternStep::MaxGap(Box::new(inner), gap);
+                Ok(())
+            }
         }
+    }
 
-        Ok(Self { steps })
+    /// Steps of the preset `@name` refers to.
+    fn parse_reference(
+        name: &str,
+        aliases: &SymbolAliases,
+        presets: &PatternPresets,
+        depth: usize,
+        symbol_count: &mut usize,
+        start_time: Instant,
+    ) -> Result<Vec<PatternStep>> {
+        if depth >= MAX_PATTERN_DEPTH {
+            return Err(SodsError::PatternError(format!(
+                "Pattern references nested too deeply (max {} levels)",
+                MAX_PATTERN_DEPTH
+            )));
+        }
+        let pattern = builtin_preset(name)
+            .or_else(|| presets.get(name).map(|p| p.pattern.as_str()))
+            .ok_or_else(|| {
+                SodsError::PatternError(format!("Unknown pattern reference '@{}'", name))
+            })?
+            .trim();
+        if pattern.contains(" within ") {
+            return Err(SodsError::PatternError(format!(
+                "'@{}' has a time window and can only be used on its own",
+                name
+            )));
+        }
+        // A preset may consist of just another preset's name
+        let pattern = if builtin_preset(pattern).is_some() || presets.get(pattern).is_some() {
+            format!("@{}", pattern)
+        } else {
+            pattern.to_string()
+        };
+        Self::parse_sequence(
+            &pattern,
+            aliases,
+            presets,
+            depth + 1,
+            symbol_count,
+            start_time,
+        )
+    }
+
+    /// Parse one step between arrows: a negated step, or one or more
+    /// alternatives separated by `|`.
+    fn parse_segment(
+        part: &str,
+        aliases: &SymbolAliases,
+        presets: &PatternPresets,
+        depth: usize,
+        symbol_count: &mut usize,
+        start_time: Instant,
+    ) -> Result<PatternStep> {
+        // A reference expands to several steps, so it cannot be negated,
+        // quantified or an alternative unless wrapped in a group
+        let misplaced_reference = || {
+            SodsError::PatternError(format!(
+                "Pattern references must be whole steps or groups: '{}'",
+                part
+            ))
+        };
+        if let Some(negated) = part.strip_prefix('!').or_else(|| part.strip_prefix("not ")) {
+            if negated.contains('@') {
+                return Err(misplaced_reference());
+            }
+            *symbol_count += 1;
+            return Self::parse_absent(negated.trim(), aliases);
+        }
+
+        // '|' binds looser than quantifiers and conditions, so each
+        // alternative is a complete step of its own
+        let alternatives: Vec<&str> = split_top_level(part, "|")
+            .into_iter()
+            .map(str::trim)
+            .collect();
+        if alternatives
+            .iter()
+            .any(|alt| !alt.starts_with('(') && alt.contains('@'))
+        {
+            return Err(misplaced_reference());
+        }
+        if alternatives.iter().any(|alt| alt.is_empty()) {
+            return Err(SodsError::PatternError(format!(
+                "Empty alternative in segment '{}'",
+                part
+            )));
+        }
+        // A group's symbols are counted as it is parsed
+        *symbol_count += alternatives
+            .iter()
+            .filter(|alt| !alt.starts_with('('))
+            .count();
+        let mut parsed = alternatives
+            .into_iter()
+            .map(|alt| {
+                if alt.starts_with('(') {
+                    Self::parse_group(alt, aliases, presets, depth, symbol_count, start_time)
+                } else {
+                    Self::parse_step(alt, aliases)
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(if parsed.len() == 1 {
+            parsed.remove(0)
+        } else {
+            PatternStep::AnyOf(parsed)
+        })
+    }
+
+    /// Parse `( ... )` with an optional quantifier, e.g. `(Tf -> Sw){3,}`.
+    /// Without a quantifier the group matches once.
+    fn parse_group(
+        part: &str,
+        aliases: &SymbolAliases,
+        presets: &PatternPresets,
+        depth: usize,
+        symbol_count: &mut usize,
+        start_time: Instant,
+    ) -> Result<PatternStep> {
+        if depth >= MAX_PATTERN_DEPTH {
+            return Err(SodsError::PatternError(format!(
+                "Groups nested too deeply (max {} levels)",
+                MAX_PATTERN_DEPTH
+            )));
+        }
+
+        let mut open = 0usize;
+        let close = part
+            .char_indices()
+            .find(|&(_, c)| {
+                match c {
+                    '(' => open += 1,
+                    ')' => open -= 1,
+                    _ => {}
+                }
+                open == 0
+            })
+            .map(|(i, _)| i)
+            .ok_or_else(|| {
+                SodsError::PatternError(format!("Unclosed group: expected ')' in '{}'", part))
+            })?;
+
+        let rest = part[close + 1..].trim();
+        let (min, max) = if rest.is_empty() {
+            (1, Some(1))
+        } else {
+            let quantifier = rest
+                .strip_prefix('{')
+                .and_then(|q| q.strip_suffix('}'))
+                .ok_or_else(|| {
+                    SodsError::PatternError(format!("Unexpected text after group: '{}'", rest))
+                })?;
+            Self::parse_quantifier(quantifier)?
+        };
+
+        let steps = Self::parse_sequence(
+            &part[1..close],
+            aliases,
+            presets,
+            depth + 1,
+            symbol_count,
+            start_time,
+        )?;
+        if steps.iter().any(|s| matches!(s, PatternStep::Absent(..))) {
+            return Err(SodsError::PatternError(format!(
+                "Negated steps are not supported inside groups: '{}'",
+                part
+            )));
+        }
+        if steps.iter().any(PatternStep::has_capture) {
+            return Err(SodsError::PatternError(format!(
+                "Captures are not supported inside groups: '{}'",
+                part
+            )));
+        }
+        if steps.iter().any(|s| matches!(s, PatternStep::MaxGap(..))) {
+            return Err(SodsError::PatternError(format!(
+                "Gap limits are not supported inside groups: '{}'",
+                part
+            )));
+        }
+        Ok(PatternStep::Group(steps, min, max))
+    }
+
+    /// Parse the step after `!` / `not`: a single symbol, with an optional
+    /// condition.
+    fn parse_absent(part: &str, aliases: &SymbolAliases) -> Result<PatternStep> {
+        if part.starts_with('(') {
+            return Err(SodsError::PatternError(format!(
+                "Groups cannot be negated: '{}'",
+                part
+            )));
+        }
+        if part.contains('|') {
+            return Err(SodsError::PatternError(format!(
+                "Negated steps cannot have alternatives: '{}'",
+                part
+            )));
+        }
+        match Self::parse_step(part, aliases)? {
+            PatternStep::Exact(symbol, condition) => Ok(PatternStep::Absent(symbol, condition)),
+            PatternStep::Any(_) => Err(SodsError::PatternError(format!(
+                "Wildcards cannot be negated: '{}'",
+                part
+            ))),
+            _ => Err(SodsError::PatternError(format!(
+                "Quantifiers are not supported on negated steps: '{}'",
+                part
+            ))),
+        }
+    }
+
+    /// Parse one step: an optional actor role, then a symbol with an
+    /// optional quantifier, captures and condition.
+    fn parse_step(part: &str, aliases: &SymbolAliases) -> Result<PatternStep> {
+        let Some((actor, rest)) = Self::split_actor(part) else {
+            return Self::parse_captured_step(part, aliases);
+        };
+        let with_actor =
+            |cond: PatternCondition| PatternCondition::Actor(actor.to_string()).and(cond);
+        match Self::parse_captured_step(rest, aliases)? {
+            PatternStep::Exact(symbol, cond) => Ok(PatternStep::Exact(symbol, with_actor(cond))),
+            PatternStep::Any(cond) => Ok(PatternStep::Any(with_actor(cond))),
+            _ => Err(SodsError::PatternError(format!(
+                "Actors are not supported on quantified steps: '{}'",
+                part
+            ))),
+        }
+    }
+
+    /// Split an `A:` actor role off the front of a step.
+    fn split_actor(part: &str) -> Option<(&str, &str)> {
+        let (actor, rest) = part.split_once(':')?;
+        let valid =
+            !actor.is_empty() && actor.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        valid.then(|| (actor, rest.trim()))
+    }
+
+    /// Parse a step after its actor role.
+    fn parse_captured_step(part: &str, aliases: &SymbolAliases) -> Result<PatternStep> {
+        let Some(open) = part.find('(') else {
+            return Self::parse_uncaptured_step(part, aliases);
+        };
+        let close = part[open..].find(')').map(|i| open + i).ok_or_else(|| {
+            SodsError::PatternError(format!("Unclosed capture: expected ')' in '{}'", part))
+        })?;
+        let rest = part[close + 1..].trim();
+        if !rest.is_empty() && !rest.starts_with("where") && !rest.starts_with("on ") {
+            return Err(SodsError::PatternError(format!(
+                "Unexpected text after capture: '{}'",
+                rest
+            )));
+        }
+        let capture = Self::parse_capture(&part[open + 1..close])?;
+
+        let step = Self::parse_uncaptured_step(&format!("{} {}", &part[..open], rest), aliases)?;
+        let with_capture = |cond: PatternCondition| match cond {
+            PatternCondition::None => capture.clone(),
+            cond => PatternCondition::All(vec![capture.clone(), cond]),
+        };
+        match step {
+            PatternStep::Exact(symbol, cond) => Ok(PatternStep::Exact(symbol, with_capture(cond))),
+            PatternStep::Any(cond) => Ok(PatternStep::Any(with_capture(cond))),
+            _ => Err(SodsError::PatternError(format!(
+                "Captures are not supported on quantified steps: '{}'",
+                part
+            ))),
+        }
+    }
+
+    /// Parse the inside of `( ... )`: `$from -> $to`, with `_` for either
+    /// address that is not captured.
+    fn parse_capture(inner: &str) -> Result<PatternCondition> {
+        let variable = |side: &str| -> Result<Option<String>> {
+            let side = side.trim();
+            if side == "_" {
+                return Ok(None);
+            }
+            match side.strip_prefix('$') {
+                Some(name)
+                    if !name.is_empty()
+                        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+                {
+                    Ok(Some(name.to_string()))
+                }
+                _ => Err(SodsError::PatternError(format!(
+                    "Invalid capture variable: {:?}",
+                    side
+                ))),
+            }
+        };
+        match inner.split_once("->") {
+            Some((from, to)) => Ok(PatternCondition::Capture(variable(from)?, variable(to)?)),
+            None => Err(SodsError::PatternError(format!(
+                "Capture must be '$from -> $to', got '{}'",
+                inner.trim()
+            ))),
+        }
+    }
+
+    /// Parse a step without captures.
+    fn parse_uncaptured_step(part: &str, aliases: &SymbolAliases) -> Result<PatternStep> {
+        let part = part.trim();
+        // Parse condition if present ("... where ...")
+        let (part_base, condition) = if let Some(idx) = part.find("where") {
+            let cond_str = part[idx + 5..].trim();
+            let base = part[..idx].trim();
+
+            let cond = if cond_str == "from == deployer" {
+                PatternCondition::FromDeployer
+            } else if let Some(stripped) = cond_str.strip_prefix("value >") {
+                let amount_str = stripped.trim();
+                let amount = parse_amount(amount_str)?;
+                PatternCondition::ValueGreaterThan(amount)
+            } else if let Some(stripped) = cond_str.strip_prefix("value between") {
+                let Some((low, high)) = stripped.split_once(" and ") else {
+                    return Err(SodsError::PatternError(format!(
+                        "Value range must be 'value between <low> and <high>', got '{}'",
+                        cond_str
+                    )));
+                };
+                let (low, high) = (parse_amount(low.trim())?, parse_amount(high.trim())?);
+                if low > high {
+                    return Err(SodsError::PatternError(format!(
+                        "Empty value range: {} is above {}",
+                        low, high
+                    )));
+                }
+                PatternCondition::ValueInRange(low, high)
+            } else if let Some(stripped) = cond_str.strip_prefix("to ==") {
+                let address = stripped.trim().parse::<Address>().map_err(|_| {
+                    SodsError::PatternError(format!("Invalid address: {}", stripped.trim()))
+                })?;
+                PatternCondition::ToAddress(address)
+            } else if let Some(stripped) = cond_str.strip_prefix("from ==") {
+                let address = stripped.trim().parse::<Address>().map_err(|_| {
+                    SodsError::PatternError(format!("Invalid address: {}", stripped.trim()))
+                })?;
+                PatternCondition::FromAddress(address)
+            } else if let Some(stripped) = cond_str.strip_prefix("token_id in collection") {
+                let address = stripped.trim().parse::<Address>().map_err(|_| {
+                    SodsError::PatternError(format!("Invalid address: {}", stripped.trim()))
+                })?;
+                PatternCondition::InCollection(address)
+            } else if let Some(stripped) = cond_str.strip_prefix("token_id ==") {
+                PatternCondition::TokenId(parse_token_id(stripped.trim())?)
+            } else if let Some(stripped) = cond_str.strip_prefix("gas_used >") {
+                let gas = stripped.trim().replace('_', "").parse().map_err(|_| {
+                    SodsError::PatternError(format!("Invalid gas amount: {}", stripped.trim()))
+                })?;
+                PatternCondition::GasGreaterThan(gas)
+            } else {
+                return Err(SodsError::PatternError(format!(
+                    "Unsupported condition: {}",
+                    cond_str
+                )));
+            };
+            (base, cond)
+        } else {
+            (part, PatternCondition::None)
+        };
+        // "... on <address>" scopes the step to one contract
+        let (part_base, condition) = match part_base.split_once(" on ") {
+            Some((base, address)) => {
+                let address = address.trim().parse::<Address>().map_err(|_| {
+                    SodsError::PatternError(format!("Invalid contract address: {}", address.trim()))
+                })?;
+                (
+                    base.trim(),
+                    PatternCondition::OnContract(address).and(condition),
+                )
+            }
+            None => (part_base, condition),
+        };
+
+        // Check for quantifier { ... }
+        if let Some(start_idx) = part_base.find('{') {
+            if let Some(end_idx) = part_base.find('}') {
+                // ReDoS Protection: Ensure only one quantifier block per segment
+                if part_base[end_idx + 1..].contains('{') || part_base[..start_idx].contains('{') {
+                    return Err(SodsError::PatternError(
+                        "Nested or multiple quantifiers not supported".into(),
+                    ));
+                }
+
+                // Ensure no trailing text after '}' in the base part
+                if !part_base[end_idx + 1..].trim().is_empty() {
+                    return Err(SodsError::PatternError(format!(
+                        "Unexpected text after quantifier: '{}'",
+                        &part_base[end_idx + 1..]
+                    )));
+                }
+
+                let symbol = part_base[..start_idx].trim();
+                if WILDCARDS.contains(&symbol) {
+                    return Err(SodsError::PatternError(
+                        "Quantifiers are not supported on wildcards".into(),
+                    ));
+                }
+                let symbol = aliases.resolve(symbol).to_string();
+                let quantifier = &part_base[start_idx + 1..end_idx]; // inside {}
+                Ok(match Self::parse_quantifier(quantifier)? {
+                    (min, None) => PatternStep::AtLeast(symbol, min, condition),
+                    (min, Some(max)) => PatternStep::Range(symbol, min, max, condition),
+                })
+            } else {
+                Err(SodsError::PatternError(format!(
+                    "Unclosed quantifier: expected '}}' in segment '{}'",
+                    part_base
+                )))
+            }
+        } else {
+            // Check if we have an unmatched '}'
+            if part_base.contains('}') {
+                return Err(SodsError::PatternError("Unmatched '}' in pattern".into()));
+            }
+            if WILDCARDS.contains(&part_base) {
+                return Ok(PatternStep::Any(condition));
+            }
+            // Single symbol
+            // Validation: Symbol name must be alphanumeric + simple chars (+, -, _)
+            // and MUST NOT contain control characters or null bytes.
+            if part_base.is_empty()
+                || part_base.chars().any(|c| {
+                    c.is_control()
+                        || c == '\0'
+                        || (!c.is_alphanumeric() && c != '+' && c != '-' && c != '_')
+                })
+            {
+                return Err(SodsError::PatternError(format!(
+                    "Invalid symbol name: {:?}",
+                    part_base
+                )));
+            }
+            Ok(PatternStep::Exact(
+                aliases.resolve(part_base).to_string(),
+                condition,
+            ))
+        }
+    }
+
+    /// Parse the inside of `{ ... }`: `n`, `n,` or `n,m`, as the minimum
+    /// and, unless unbounded, the maximum count.
+    fn parse_quantifier(quantifier: &str) -> Result<(usize, Option<usize>)> {
+        let too_large = || {
+            SodsError::PatternError(format!(
+                "Quantifier too large (max {})",
+                MAX_QUANTIFIER_VALUE
+            ))
+        };
+        if let Some(comma_idx) = quantifier.find(',') {
+            let min_str = quantifier[..comma_idx].trim();
+            let max_str = quantifier[comma_idx + 1..].trim();
+
+            let min = min_str.parse::<usize>().map_err(|_| {
+                SodsError::PatternError(format!("Invalid min quantifier: {}", min_str))
+            })?;
+            if min > MAX_QUANTIFIER_VALUE {
+                return Err(too_large());
+            }
+
+            if max_str.is_empty() {
+                // {n,}
+                return Ok((min, None));
+            }
+            // {n,m}
+            let max = max_str.parse::<usize>().map_err(|_| {
+                SodsError::PatternError(format!("Invalid max quantifier: {}", max_str))
+            })?;
+            if max > MAX_QUANTIFIER_VALUE {
+                return Err(too_large());
+            }
+            if max < min {
+                return Err(SodsError::PatternError(format!(
+                    "Max quantifier {} must be >= min {}",
+                    max, min
+                )));
+            }
+            Ok((min, Some(max)))
+        } else {
+            // {n} exact count shorthand -> treat as {n,n}
+            let count = quantifier.trim().parse::<usize>().map_err(|_| {
+                SodsError::PatternError(format!("Invalid exact quantifier: {}", quantifier))
+            })?;
+            if count > MAX_QUANTIFIER_VALUE {
+                return Err(too_large());
+            }
+            Ok((count, Some(count)))
+        }
     }
 
     /// Check if the pattern matches the given sorted symbols.
     /// Returns the sequence of matched symbols if found, or None.
+    ///
+    /// A search that runs out of `MAX_SEARCH_STEPS` counts as no match;
+    /// `try_matches` tells the two apart.
     pub fn matches<'a>(
         &self,
         symbols: &'a [BehavioralSymbol],
         registry: Option<&ContractRegistry>,
     ) -> Option<Vec<&'a BehavioralSymbol>> {
-        let mut matched_sequence = Vec::new();
-        let mut current_sym_idx = 0;
+        self.try_matches(symbols, registry).ok().flatten()
+    }
+
+    /// Like `matches`, but fails if the search runs out of
+    /// `MAX_SEARCH_STEPS`.
+    pub fn try_matches<'a>(
+        &self,
+        symbols: &'a [BehavioralSymbol],
+        registry: Option<&ContractRegistry>,
+    ) -> Result<Option<Vec<&'a BehavioralSymbol>>> {
+        let steps = self.match_steps(symbols, registry)?;
+        Ok(steps.map(|steps| steps.iter().flat_map(|m| m.symbols(symbols)).collect()))
+    }
+
+    /// Like `matches`, with a report of where each step matched and the
+    /// values it moved.
+    pub fn matches_with_report<'a>(
+        &self,
+        symbols: &'a [BehavioralSymbol],
+        registry: Option<&ContractRegistry>,
+    ) -> Option<(Vec<&'a BehavioralSymbol>, MatchReport)> {
+        let steps = self.match_steps(symbols, registry).ok().flatten()?;
+        let report = MatchReport::new(&steps, symbols);
+        Some((
+            steps.iter().flat_map(|m| m.symbols(symbols)).collect(),
+            report,
+        ))
+    }
+
+    /// Like `matches`, but explains the outcome step by step: what each
+    /// step matched, or for the first step that could not be matched after
+    /// the ones before it, the symbols it considered and why they failed.
+    pub fn match_explain(
+        &self,
+        symbols: &[BehavioralSymbol],
+        registry: Option<&ContractRegistry>,
+    ) -> MatchExplanation {
+        let trace = |step: usize, outcome| StepTrace {
+            step,
+            text: self.steps[step].to_string(),
+            outcome,
+        };
+        if let Ok(Some(steps)) = self.match_steps(symbols, registry) {
+            return MatchExplanation {
+                matched: true,
+                steps: steps
+                    .iter()
+                    .enumerate()
+                    .map(|(i, m)| {
+                        trace(
+                            i,
+                            StepOutcome::Matched {
+                                positions: m.positions(),
+                            },
+                        )
+                    })
+                    .collect(),
+            };
+        }
+
+        // The longest prefix that still matches ends before the failing step
+        let mut matched = Vec::new();
+        let mut failed = self.steps.len().saturating_sub(1);
+        for len in 1..self.steps.len() {
+            let prefix = BehavioralPattern {
+                steps: self.steps[..len].to_vec(),
+                window: self.window,
+            };
+            match prefix.match_steps(symbols, registry) {
+                Ok(Some(steps)) => matched = steps,
+                _ => {
+                    failed = len - 1;
+                    break;
+                }
+            }
+        }
+        let from = matched
+            .iter()
+            .rev()
+            .find(|m| !m.ranges.is_empty())
+            .map_or(0, |m| m.end);
+
+        let mut steps: Vec<StepTrace> = matched
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                trace(
+                    i,
+                    StepOutcome::Matched {
+                        positions: m.positions(),
+                    },
+                )
+            })
+            .collect();
+        if let Some(step) = self.steps.get(failed) {
+            let (candidates, reason) = self.explain_failure(step, symbols, from, registry);
+            steps.push(trace(
+                failed,
+                StepOutcome::Failed {
+                    from,
+                    candidates,
+                    reason,
+                },
+            ));
+        }
+        steps.extend((failed + 1..self.steps.len()).map(|i| trace(i, StepOutcome::NotReached)));
+        MatchExplanation {
+            matched: false,
+            steps,
+        }
+    }
+
+    /// Symbols from `from` that `step` could have matched, and why it
+    /// matched none of them.
+    fn explain_failure(
+        &self,
+        step: &PatternStep,
+        symbols: &[BehavioralSymbol],
+        from: usize,
+        registry: Option<&ContractRegistry>,
+    ) -> (Vec<Candidate>, String) {
+        // (symbol, condition) of each simple step within this one
+        fn leaves(step: &PatternStep) -> Vec<(Option<&str>, &PatternCondition)> {
+            match step {
+                PatternStep::Exact(s, c)
+                | PatternStep::AtLeast(s, _, c)
+                | PatternStep::Range(s, _, _, c)
+                | PatternStep::Absent(s, c) => vec![(Some(s.as_str()), c)],
+                PatternStep::Any(c) => vec![(None, c)],
+                PatternStep::AnyOf(steps)
+                | PatternStep::Group(steps, _, _)
+                | PatternStep::SameTx(steps) => steps.iter().flat_map(leaves).collect(),
+                PatternStep::MaxGap(step, _) => leaves(step),
+            }
+        }
+        let leaves = leaves(step);
+        let candidates: Vec<Candidate> = symbols
+            .iter()
+            .enumerate()
+            .skip(from)
+            .filter_map(|(position, sym)| {
+                let conditions: Vec<&PatternCondition> = leaves
+                    .iter()
+                    .filter(|(name, _)| name.map_or(true, |n| n == sym.symbol))
+                    .map(|(_, c)| *c)
+                    .collect();
+                if conditions.is_empty() {
+                    return None;
+                }
+                let failed_condition = conditions
+                    .iter()
+                    .map(|c| Self::failed_condition(sym, c, registry))
+                    .collect::<Option<Vec<_>>>()
+                    .and_then(|failed| failed.first().map(|c| c.to_string()));
+                Some(Candidate {
+                    position,
+                    symbol: sym.symbol.clone(),
+                    log_index: sym.log_index,
+                    failed_condition,
+                })
+            })
+            .take(MAX_EXPLAINED_CANDIDATES)
+            .collect();
+
+        let reason = if let PatternStep::Absent(s, _) = step {
+            format!("A {} occurs where it is not allowed", s)
+        } else if candidates.is_empty() {
+            match step.is_wildcard() {
+                true => format!("No symbols at or after position {}", from),
+                false => format!(
+                    "No {} at or after position {}",
+                    step.symbols().join(" or "),
+                    from
+                ),
+            }
+        } else if candidates.iter().all(|c| c.failed_condition.is_some()) {
+            "Every candidate failed a condition".to_string()
+        } else {
+            match step {
+                PatternStep::AtLeast(_, min, _) | PatternStep::Range(_, min, _, _) => {
+                    format!("Fewer than {} in a row at position {}", min, from)
+                }
+                PatternStep::Group(_, min, _) => {
+                    format!("Fewer than {} repetitions in a row", min)
+                }
+                PatternStep::SameTx(_) => "No transaction contains all the steps".to_string(),
+                PatternStep::MaxGap(_, gap) => {
+                    format!("No candidate within {} symbol(s) of the previous step", gap)
+                }
+                _ if self.window.is_some() => {
+                    "No candidate fits the window with the earlier steps".to_string()
+                }
+                _ if self.has_capture() => {
+                    "No candidate fits the addresses captured or actors bound earlier".to_string()
+                }
+                _ => "No candidate fits with the earlier steps".to_string(),
+            }
+        };
+        (candidates, reason)
+    }
+
+    /// The simple condition `symbol` fails, if any; captures are not checked.
+    fn failed_condition<'c>(
+        symbol: &BehavioralSymbol,
+        condition: &'c PatternCondition,
+        registry: Option<&ContractRegistry>,
+    ) -> Option<&'c PatternCondition> {
+        match condition {
+            PatternCondition::All(conditions) => conditions
+                .iter()
+                .find_map(|c| Self::failed_condition(symbol, c, registry)),
+            other => (!Self::check_condition(symbol, other, registry)).then_some(other),
+        }
+    }
+
+    /// What each step matched; negated steps match nothing.
+    fn match_steps(
+        &self,
+        symbols: &[BehavioralSymbol],
+        registry: Option<&ContractRegistry>,
+    ) -> Result<Option<Vec<Matched>>> {
+        if self.has_capture()
+            || self.window.is_some()
+            || self
+                .steps
+                .iter()
+                .any(|s| matches!(s, PatternStep::Absent(..) | PatternStep::MaxGap(..)))
+        {
+            return self.matches_by_search(symbols, registry);
+        }
 
+        let mut steps = Vec::with_capacity(self.steps.len());
+        let mut end = 0;
         for step in &self.steps {
-            if current_sym_idx >= symbols.len() {
+            if end >= symbols.len() {
+                return Ok(None); // Ran out of symbols
+            }
+            let Some(matched) = Self::match_step(step, symbols, end, registry) else {
+                return Ok(None);
+            };
+            end = matched.end;
+            steps.push(matched);
+        }
+        Ok(Some(steps))
+    }
+
+    /// Match `steps` one after another from `start`, each at its first
+    /// occurrence.
+    fn match_sequence(
+        steps: &[PatternStep],
+        symbols: &[BehavioralSymbol],
+        start: usize,
+        registry: Option<&ContractRegistry>,
+    ) -> Option<Matched> {
+        let mut matched = Matched {
+            ranges: Vec::new(),
+            end: start,
+        };
+        for step in steps {
+            if matched.end >= symbols.len() {
                 return None; // Ran out of symbols
             }
 
-            match step {
-                PatternStep::Exact(target, cond) => {
-                    // Find first occurrence of target starting from current_sym_idx that satisfies condition
-                    let found_idx = symbols[current_sym_idx..].iter().position(|s| {
-                        s.symbol == *target && Self::check_condition(s, cond, registry)
-                    })?;
-                    let absolute_idx = current_sym_idx + found_idx;
-
-                    matched_sequence.push(&symbols[absolute_idx]);
-                    current_sym_idx = absolute_idx + 1;
+            let next = Self::match_step(step, symbols, matched.end, registry)?;
+            matched.ranges.extend(next.ranges);
+            matched.end = next.end;
+        }
+        Some(matched)
+    }
+
+    /// Match one step at `start`.
+    fn match_step(
+        step: &PatternStep,
+        symbols: &[BehavioralSymbol],
+        start: usize,
+        registry: Option<&ContractRegistry>,
+    ) -> Option<Matched> {
+        let matches = |sym: &BehavioralSymbol, target: &str, cond: &PatternCondition| {
+            sym.symbol == target && Self::check_condition(sym, cond, registry)
+        };
+
+        match step {
+            PatternStep::Exact(target, cond) => {
+                // Find first occurrence of target starting from start that satisfies condition
+                let found_idx = symbols[start..]
+                    .iter()
+                    .position(|s| matches(s, target, cond))?;
+                Some(Matched::range(start + found_idx, start + found_idx + 1))
+            }
+            PatternStep::AtLeast(target, min, cond) => {
+                // Greedy consumption for "At Least n"
+                let count = symbols[start..]
+                    .iter()
+                    .take_while(|s| matches(s, target, cond))
+                    .count();
+                (count >= *min).then(|| Matched::range(start, start + count))
+            }
+            PatternStep::Range(target, min, max, cond) => {
+                // Greedy consumption for Range {min, max}
+                let count = symbols[start..]
+                    .iter()
+                    .take(*max)
+                    .take_while(|s| matches(s, target, cond))
+                    .count();
+                (count >= *min).then(|| Matched::range(start, start + count))
+            }
+            PatternStep::AnyOf(alternatives) => {
+                // Leftmost match; ties go to the alternative written first
+                alternatives
+                    .iter()
+                    .filter_map(|alt| Self::match_step(alt, symbols, start, registry))
+                    .min_by_key(Matched::first)
+            }
+            PatternStep::Any(cond) => {
+                let found_idx = symbols[start..]
+                    .iter()
+                    .position(|s| Self::check_condition(s, cond, registry))?;
+                Some(Matched::range(start + found_idx, start + found_idx + 1))
+            }
+            PatternStep::Group(steps, min, max) => {
+                // Greedy, like the other quantifiers
+                let repetitions = Self::repetitions(steps, *max, symbols, start, registry);
+                (repetitions.len() >= *min).then(|| Matched::concat(&repetitions, start))
+            }
+            // The first transaction in which all the steps match
+            PatternStep::SameTx(steps) => tx_runs(symbols, start).find_map(|(first, end)| {
+                Self::match_sequence(steps, &symbols[..end], first, registry)
+            }),
+            PatternStep::MaxGap(step, gap) => Self::match_step(step, symbols, start, registry)
+                .filter(|matched| matched.first() - start <= *gap),
+            // Absences are checked by `matches_by_search`
+            PatternStep::Absent(..) => None,
+        }
+    }
+
+    /// Consecutive matches of `steps` from `start`, as many as there are up
+    /// to `max`.
+    fn repetitions(
+        steps: &[PatternStep],
+        max: Option<usize>,
+        symbols: &[BehavioralSymbol],
+        start: usize,
+        registry: Option<&ContractRegistry>,
+    ) -> Vec<Matched> {
+        let mut repetitions: Vec<Matched> = Vec::new();
+        let mut pos = start;
+        while repetitions.len() < max.unwrap_or(usize::MAX) {
+            let Some(repetition) = Self::match_sequence(steps, symbols, pos, registry) else {
+                break;
+            };
+            // One that matched nothing would repeat forever
+            if repetition.end == pos {
+                break;
+            }
+            pos = repetition.end;
+            repetitions.push(repetition);
+        }
+        repetitions
+    }
+
+    /// Matching for patterns with negated steps, captures, a window or gap
+    /// limits.
+    ///
+    /// The greedy matcher commits to the first occurrence of every step,
+    /// but avoiding a forbidden symbol may require placing an earlier step
+    /// later (in `Tf Sw Tf Tf`, `Tf -> !Sw -> Tf` only matches the last two
+    /// transfers), and so may binding a capture to addresses a later step
+    /// agrees with, starting late enough for the rest to fit the window, or
+    /// placing a step close enough to the next one.
+    /// So placements are searched instead, leftmost first, remembering the
+    /// states from which the rest cannot match. Bindings make the number of
+    /// states grow with every capture, so the search stops with an error
+    /// after `MAX_SEARCH_STEPS` of them.
+    fn matches_by_search(
+        &self,
+        symbols: &[BehavioralSymbol],
+        registry: Option<&ContractRegistry>,
+    ) -> Result<Option<Vec<Matched>>> {
+        let mut search = Search {
+            dead: HashSet::new(),
+            budget: MAX_SEARCH_STEPS,
+        };
+        let mut found = Vec::new();
+        let bindings = Bindings::new();
+        if !self.search(0, 0, &bindings, symbols, registry, &mut search, &mut found)? {
+            return Ok(None);
+        }
+        let mut steps: Vec<Matched> = (0..self.steps.len())
+            .map(|_| Matched::concat(&[], 0))
+            .collect();
+        for (step, matched) in found {
+            steps[step] = matched;
+        }
+        Ok(Some(steps))
+    }
+
+    /// Match `steps[step..]` from `pos` under `bindings`, pushing what each
+    /// positive step matched.
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        step: usize,
+        pos: usize,
+        bindings: &Bindings,
+        symbols: &[BehavioralSymbol],
+        registry: Option<&ContractRegistry>,
+        search: &mut Search,
+        found: &mut Vec<(usize, Matched)>,
+    ) -> Result<bool> {
+        if search.budget == 0 {
+            return Err(SodsError::PatternError(format!(
+                "Pattern search gave up after {} steps",
+                MAX_SEARCH_STEPS
+            )));
+        }
+        search.budget -= 1;
+
+        // The absences before the next positive step forbid symbols in the gap
+        let next = self.steps[step..]
+            .iter()
+            .position(|s| !matches!(s, PatternStep::Absent(..)))
+            .map(|i| step + i);
+        let absences = &self.steps[step..next.unwrap_or(self.steps.len())];
+        let gap_limit = symbols[pos.min(symbols.len())..]
+            .iter()
+            .position(|sym| {
+                absences.iter().any(|absent| match absent {
+                    // Unbound variables match any address here
+                    PatternStep::Absent(target, cond) => {
+                        sym.symbol == *target
+                            && Self::check_condition(sym, cond, registry)
+                            && Self::bind(sym, cond, &mut bindings.clone())
+                    }
+                    _ => false,
+                })
+            })
+            .map_or(symbols.len(), |i| pos + i);
+
+        let Some(next) = next else {
+            // Trailing absences hold up to the end of the block
+            return Ok(gap_limit == symbols.len());
+        };
+        let anchor = self.window.and(
+            found
+                .iter()
+                .find_map(|(_, m)| m.ranges.first())
+                .map(|&(first, _)| first),
+        );
+        if pos >= symbols.len() || !search.dead.insert((next, pos, anchor, bindings.clone())) {
+            return Ok(false);
+        }
+
+        for (matched, placed) in Self::placements(&self.steps[next], symbols, pos, registry) {
+            let first = matched.first();
+            if first > gap_limit {
+                break;
+            }
+            if let Some(window) = &self.window {
+                let start = &symbols[anchor.unwrap_or(first)];
+                if !matched
+                    .symbols(symbols)
+                    .all(|sym| window.contains(start, sym))
+                {
+                    continue;
                 }
-                PatternStep::AtLeast(target, min, cond) => {
-                    let mut count = 0;
-                    let mut temp_matched = Vec::new();
-                    let mut idx = current_sym_idx;
-
-                    // Greedy consumption for "At Least n"
-                    while idx < symbols.len() {
-                        let sym = &symbols[idx];
-                        if sym.symbol == *target && Self::check_condition(sym, cond, registry) {
-                            temp_matched.push(sym);
-                            idx += 1;
-                            count += 1;
-                        } else {
-                            break;
-                        }
+            }
+            let mut bound = bindings.clone();
+            if let Some(cond) = placed.condition() {
+                if !matched
+                    .symbols(symbols)
+                    .all(|sym| Self::bind(sym, cond, &mut bound))
+                {
+                    continue;
+                }
+            }
+            let end = matched.end;
+            found.push((next, matched));
+            if self.search(next + 1, end, &bound, symbols, registry, search, found)? {
+                return Ok(true);
+            }
+            found.pop();
+        }
+        Ok(false)
+    }
+
+    /// Every way `step` can match from `start`, by first matched symbol,
+    /// with the alternative that matched.
+    fn placements<'p>(
+        step: &'p PatternStep,
+        symbols: &[BehavioralSymbol],
+        start: usize,
+        registry: Option<&ContractRegistry>,
+    ) -> Vec<(Matched, &'p PatternStep)> {
+        match step {
+            PatternStep::Exact(target, cond) => (start..symbols.len())
+                .filter(|&i| {
+                    symbols[i].symbol == *target
+                        && Self::check_condition(&symbols[i], cond, registry)
+                })
+                .map(|i| (Matched::range(i, i + 1), step))
+                .collect(),
+            PatternStep::Any(cond) => (start..symbols.len())
+                .filter(|&i| Self::check_condition(&symbols[i], cond, registry))
+                .map(|i| (Matched::range(i, i + 1), step))
+                .collect(),
+            PatternStep::AnyOf(alternatives) => {
+                let mut all: Vec<(Matched, &PatternStep)> = alternatives
+                    .iter()
+                    .flat_map(|alt| Self::placements(alt, symbols, start, registry))
+                    .collect();
+                // Stable, so ties keep the order the alternatives were written in
+                all.sort_by_key(|(matched, _)| matched.first());
+                all
+            }
+            // Most repetitions first, so fewer can still fit a window
+            PatternStep::Group(steps, min, max) => {
+                let repetitions = Self::repetitions(steps, *max, symbols, start, registry);
+                (*min..=repetitions.len())
+                    .rev()
+                    .map(|count| (Matched::concat(&repetitions[..count], start), step))
+                    .collect()
+            }
+            // One placement per transaction
+            PatternStep::SameTx(steps) => tx_runs(symbols, start)
+                .filter_map(|(first, end)| {
+                    Self::match_sequence(steps, &symbols[..end], first, registry)
+                })
+                .map(|matched| (matched, step))
+                .collect(),
+            PatternStep::MaxGap(step, gap) => Self::placements(step, symbols, start, registry)
+                .into_iter()
+                .filter(|(matched, _)| matched.first() - start <= *gap)
+                .collect(),
+            // Quantified steps are anchored at `start`
+            _ => Self::match_step(step, symbols, start, registry)
+                .map(|matched| (matched, step))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    fn check_condition(
+        symbol: &BehavioralSymbol,
+        condition: &PatternCondition,
+        registry: Option<&ContractRegistry>,
+    ) -> bool {
+        match condition {
+            PatternCondition::None => true,
+            PatternCondition::FromDeployer => {
+                // Primary: Check Dynamic Registry
+                if let Some(reg) = registry {
+                    if let Some(deployer) = reg.get_deployer(&symbol.contract_address) {
+                        return symbol.from == deployer;
                     }
+                }
+                // Fallback: Check heuristic flag on symbol
+                symbol.is_from_deployer
+            }
+            PatternCondition::ValueGreaterThan(threshold) => symbol.value > *threshold,
+            PatternCondition::ValueInRange(low, high) => (*low..=*high).contains(&symbol.value),
+            PatternCondition::ToAddress(address) => symbol.to == *address,
+            PatternCondition::FromAddress(address) => symbol.from == *address,
+            PatternCondition::TokenId(id) => symbol.token_id == Some(*id),
+            PatternCondition::InCollection(address) => symbol.collection == Some(*address),
+            PatternCondition::GasGreaterThan(gas) => symbol.gas_used.is_some_and(|g| g > *gas),
+            PatternCondition::OnContract(address) => symbol.contract_address == *address,
+            // Checked against the bindings by `bind`
+            PatternCondition::Capture(..) | PatternCondition::Actor(_) => true,
+            PatternCondition::All(conditions) => conditions
+                .iter()
+                .all(|cond| Self::check_condition(symbol, cond, registry)),
+        }
+    }
 
-                    if count < *min {
-                        return None; // Not enough symbols
+    /// Bind the captures in `condition` to `symbol`'s addresses, failing if
+    /// a variable is already bound to a different address.
+    fn bind(
+        symbol: &BehavioralSymbol,
+        condition: &PatternCondition,
+        bindings: &mut Bindings,
+    ) -> bool {
+        match condition {
+            PatternCondition::Capture(from, to) => [(from, symbol.from), (to, symbol.to)]
+                .into_iter()
+                .all(|(variable, address)| match variable {
+                    Some(name) => *bindings.entry(name.clone()).or_insert(address) == address,
+                    None => true,
+                }),
+            // Stored as `A:`, apart from capture variables
+            PatternCondition::Actor(name) => {
+                let role = format!("{}:", name);
+                match bindings.get(&role) {
+                    Some(address) => *address == symbol.from,
+                    None if bindings.iter().any(|(other, address)| {
+                        other.ends_with(':') && *address == symbol.from
+                    }) =>
+                    {
+                        false
                     }
+                    None => {
+                        bindings.insert(role, symbol.from);
+                        true
+                    }
+                }
+            }
+            PatternCondition::All(conditions) => conditions
+                .iter()
+                .all(|cond| Self::bind(symbol, cond, bindings)),
+            _ => true,
+        }
+    }
+}
 
-                    matched_sequence.extend(temp_matched);
-                    current_sym_idx = idx;
+/// A pattern compiled once for matching against many blocks.
+///
+/// Sequences of plain, wildcard, quantified and single-symbol alternative
+/// steps become a deterministic automaton with one state per step, run in
+/// a single pass over the symbols without backtracking or allocating per
+/// step. Its matches are exactly those of `BehavioralPattern::matches`.
+/// Patterns the automaton cannot express (groups, same-transaction chains,
+/// negation, captures and windows) are matched by the pattern itself.
+#[derive(Debug, Clone)]
+pub struct CompiledPattern {
+    pattern: BehavioralPattern,
+    states: Option<Vec<State>>,
+}
+
+/// What a symbol must be to advance the automaton: a given symbol, or any
+/// for a wildcard, satisfying a condition.
+#[derive(Debug, Clone)]
+struct Guard {
+    symbol: Option<String>,
+    condition: PatternCondition,
+}
+
+impl Guard {
+    fn new(symbol: Option<&String>, condition: &PatternCondition) -> Self {
+        Self {
+            symbol: symbol.cloned(),
+            condition: condition.clone(),
+        }
+    }
+
+    #[inline]
+    fn accepts(&self, sym: &BehavioralSymbol, registry: Option<&ContractRegistry>) -> bool {
+        self.symbol.as_ref().map_or(true, |s| *s == sym.symbol)
+            && BehavioralPattern::check_condition(sym, &self.condition, registry)
+    }
+}
+
+/// Automaton state for one step.
+#[derive(Debug, Clone)]
+enum State {
+    /// Skip symbols up to the first one passing any guard, and take it
+    Seek(Vec<Guard>),
+    /// Take `min` to `max` symbols passing the guard, as many as follow
+    /// directly
+    Run(Guard, usize, usize),
+}
+
+impl State {
+    fn compile(step: &PatternStep) -> Option<Self> {
+        match step {
+            PatternStep::Exact(symbol, cond) => {
+                Some(State::Seek(vec![Guard::new(Some(symbol), cond)]))
+            }
+            PatternStep::Any(cond) => Some(State::Seek(vec![Guard::new(None, cond)])),
+            PatternStep::AtLeast(symbol, min, cond) => {
+                Some(State::Run(Guard::new(Some(symbol), cond), *min, usize::MAX))
+            }
+            PatternStep::Range(symbol, min, max, cond) => {
+                Some(State::Run(Guard::new(Some(symbol), cond), *min, *max))
+            }
+            // The leftmost symbol of any alternative
+            PatternStep::AnyOf(alternatives) => alternatives
+                .iter()
+                .map(|alt| match alt {
+                    PatternStep::Exact(symbol, cond) => Some(Guard::new(Some(symbol), cond)),
+                    PatternStep::Any(cond) => Some(Guard::new(None, cond)),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(State::Seek),
+            _ => None,
+        }
+    }
+}
+
+impl CompiledPattern {
+    pub fn new(pattern: BehavioralPattern) -> Self {
+        let states = if pattern.has_capture() || pattern.window.is_some() {
+            None
+        } else {
+            pattern.steps.iter().map(State::compile).collect()
+        };
+        Self { pattern, states }
+    }
+
+    pub fn pattern(&self) -> &BehavioralPattern {
+        &self.pattern
+    }
+
+    /// Whether matching runs on the automaton rather than the pattern.
+    pub fn is_automaton(&self) -> bool {
+        self.states.is_some()
+    }
+
+    /// Same as `BehavioralPattern::try_matches`; only the pattern's own
+    /// search can run out of steps.
+    pub fn try_matches<'a>(
+        &self,
+        symbols: &'a [BehavioralSymbol],
+        registry: Option<&ContractRegistry>,
+    ) -> Result<Option<Vec<&'a BehavioralSymbol>>> {
+        if self.states.is_none() {
+            return self.pattern.try_matches(symbols, registry);
+        }
+        Ok(self.matches(symbols, registry))
+    }
+
+    /// Same as `BehavioralPattern::matches`.
+    pub fn matches<'a>(
+        &self,
+        symbols: &'a [BehavioralSymbol],
+        registry: Option<&ContractRegistry>,
+    ) -> Option<Vec<&'a BehavioralSymbol>> {
+        let Some(states) = &self.states else {
+            return self.pattern.matches(symbols, registry);
+        };
+
+        // Each state scans on from where the previous one stopped
+        let mut matched = Vec::new();
+        let mut pos = 0;
+        for state in states {
+            let rest = symbols.get(pos..).filter(|rest| !rest.is_empty())?;
+            match state {
+                State::Seek(guards) => {
+                    let found = match guards.as_slice() {
+                        [guard] => rest.iter().position(|sym| guard.accepts(sym, registry)),
+                        _ => rest.iter().position(|sym| {
+                            guards.iter().any(|guard| guard.accepts(sym, registry))
+                        }),
+                    }?;
+                    matched.push(&rest[found]);
+                    pos += found + 1;
                 }
-                PatternStep::Range(target, min, max, cond) => {
-                    let mut count = 0;
-                    let mut temp_matched = Vec::new();
-                    let mut idx = current_sym_idx;
-
-                    // Greedy consumption for Range {min, max}
-                    while count < *max && idx < symbols.len() {
-                        let sym = &symbols[idx];
-                        if sym.symbol == *target && Self::check_condition(sym, cond, registry) {
-                            temp_matched.push(sym);
-                            idx += 1;
-                            count += 1;
-                        } else {
-                            break;
-                        }
-                    }
-
+                State::Run(guard, min, max) => {
+                    let count = rest
+                        .iter()
+                        .take(*max)
+                        .take_while(|sym| guard.accepts(sym, registry))
+                        .count();
                     if count < *min {
                         return None;
                     }
-
-                    matched_sequence.extend(temp_matched);
-                    current_sym_idx = idx;
+                    matched.extend(&rest[..count]);
+                    pos += count;
                 }
             }
         }
-
-        Some(matched_sequence)
+        Some(matched)
     }
+}
 
-    fn check_condition(
-        symbol: &BehavioralSymbol,
-        condition: &PatternCondition,
-        registry: Option<&ContractRegistry>,
-    ) -> bool {
-        match condition {
-            PatternCondition::None => true,
-            PatternCondition::FromDeployer => {
-                // Primary: Check Dynamic Registry
-                if let Some(reg) = registry {
-                    if let Some(deployer) = reg.get_deployer(&symbol.contract_address) {
-                        return symbol.from == deployer;
-                    }
-                }
-                // Fallback: Check heuristic flag on symbol
-                symbol.is_from_deployer
-            }
-            PatternCondition::ValueGreaterThan(threshold) => symbol.value > *threshold,
+/// The pattern a built-in preset stands for.
+fn builtin_preset(name: &str) -> Option<&'static str> {
+    PRESET_PATTERNS
+        .iter()
+        .find(|(preset, _)| *preset == name)
+        .map(|(_, pattern)| *pattern)
+}
+
+/// Split `input` at `separator`, except inside parentheses, so the arrow
+/// in a capture does not end the step.
+fn split_top_level<'a>(input: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < input.len() {
+        let rest = &input[i..];
+        if rest.starts_with('(') {
+            depth += 1;
+        } else if rest.starts_with(')') {
+            depth = depth.saturating_sub(1);
+        } else if depth == 0 && rest.starts_with(separator) {
+            parts.push(&input[start..i]);
+            i += separator.len();
+            start = i;
+            continue;
         }
+        i += rest.chars().next().map_or(1, char::len_utf8);
     }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Ranges of consecutive symbols from `start` that share a transaction.
+///
+/// A transaction's logs are consecutive within a block, so each range is
+/// one transaction (or the rest of it, for the first). Symbols without a
+/// transaction hash, such as withdrawals, are never in the same
+/// transaction as another symbol.
+fn tx_runs(
+    symbols: &[BehavioralSymbol],
+    start: usize,
+) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let mut first = start;
+    std::iter::from_fn(move || {
+        let tx_hash = symbols.get(first)?.tx_hash;
+        let len = if tx_hash == H256::zero() {
+            1
+        } else {
+            symbols[first..]
+                .iter()
+                .take_while(|sym| sym.tx_hash == tx_hash)
+                .count()
+        };
+        let run = (first, first + len);
+        first += len;
+        Some(run)
+    })
 }
 
 /// Helper function for ZK guest or simple matching
@@ -392,14 +2548,27 @@ pub fn parse_amount(input: &str) -> Result<U256> {
     // Determine target decimals based on unit
     let target_decimals = match unit.as_str() {
         "ether" => 18,
+        "finney" => 15,
+        "szabo" => 12,
         "gwei" => 9,
+        "mwei" => 6,
+        "kwei" => 3,
         "wei" => 0,
-        _ => {
-            return Err(SodsError::PatternError(format!(
-                "Unsupported unit: {}",
-                unit
-            )))
-        }
+        // A token with its own decimals, e.g. "tokens(6)" for USDC
+        _ => match unit
+            .strip_prefix("tokens(")
+            .and_then(|rest| rest.strip_suffix(')'))
+            .and_then(|decimals| decimals.parse::<u32>().ok())
+        {
+            // 10^77 is the largest power of ten a U256 holds
+            Some(decimals) if decimals <= 77 => decimals,
+            _ => {
+                return Err(SodsError::PatternError(format!(
+                    "Unsupported unit: {}",
+                    unit
+                )))
+            }
+        },
     };
 
     // Parse integer part
@@ -428,16 +2597,26 @@ pub fn parse_amount(input: &str) -> Result<U256> {
             result = result.saturating_add(decimal_part.saturating_mul(decimal_scale));
         }
     } else if !decimals_str.is_empty() {
-        return Err(SodsError::PatternError(
-            "Decimals not allowed for 'wei' unit".into(),
-        ));
+        return Err(SodsError::PatternError(format!(
+            "Decimals not allowed for '{}' unit",
+            unit
+        )));
     } else {
-        // Non-ether/gwei path (wei or raw), already matches U256 format or handled above
+        // Zero-decimal path (wei, raw or tokens(0)), already matches U256 format
     }
 
     Ok(result)
 }
 
+/// Parse an NFT token ID, in decimal or `0x` hex.
+fn parse_token_id(input: &str) -> Result<U256> {
+    let parsed = match input.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(input).ok(),
+    };
+    parsed.ok_or_else(|| SodsError::PatternError(format!("Invalid token id: {}", input)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -521,6 +2700,131 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_pattern_references() {
+        let p = BehavioralPattern::parse("@Sandwich -> Wdw").unwrap();
+        assert_eq!(
+            format!("{:?}", p),
+            format!(
+                "{:?}",
+                BehavioralPattern::parse("Tf -> Sw -> Tf -> Wdw").unwrap()
+            )
+        );
+        let p = BehavioralPattern::parse("(@Frontrun){2,} -> @Backrun within 3 blocks").unwrap();
+        assert_eq!(p.steps.len(), 3);
+        assert!(p.window.is_some());
+
+        let presets = PatternPresets::new()
+            .with_preset("Drain", "LP- -> @Backrun")
+            .with_preset("Timed", "Tf -> Sw within 2 blocks");
+        let parse = |input| {
+            BehavioralPattern::parse_with_presets(input, &SymbolAliases::default(), &presets, true)
+        };
+        assert_eq!(parse("Dep -> @Drain").unwrap().steps.len(), 4);
+        assert!(parse("@Nowhere -> Sw").is_err());
+        assert!(parse("@Timed -> Sw").is_err());
+        assert!(parse("@Drain{2,}").is_err());
+        assert!(parse("Dep | @Drain").is_err());
+        assert_eq!(parse("Dep | (@Drain){2,}").unwrap().steps.len(), 1);
+        assert!(parse("!@Drain -> Sw").is_err());
+    }
+
+    #[test]
+    fn test_match_report_per_step() {
+        let sym = |name: &str, log: u32, block: u64, value: u64| {
+            let mut s = mock_sym(name, log);
+            s.block_number = Some(block);
+            s.value = U256::from(value);
+            s
+        };
+        let symbols = vec![
+            sym("Tf", 0, 10, 5),
+            sym("Sw", 1, 10, 100),
+            sym("Sw", 2, 10, 50),
+            sym("Dep", 3, 10, 0),
+            sym("Tf", 0, 12, 7),
+        ];
+
+        let p = BehavioralPattern::parse("Tf -> Sw{2,} -> Tf").unwrap();
+        let (matched, report) = p.matches_with_report(&symbols, None).unwrap();
+        assert_eq!(matched, p.matches(&symbols, None).unwrap());
+        let positions: Vec<_> = report.steps.iter().map(|s| s.positions.clone()).collect();
+        assert_eq!(positions, vec![vec![0], vec![1, 2], vec![4]]);
+        assert_eq!(report.steps[1].value, U256::from(150));
+        assert_eq!(report.steps[1].log_gap, Some(1));
+        assert_eq!(report.steps[2].log_gap, None);
+        assert_eq!(report.steps[2].block_gap, Some(2));
+        assert_eq!(report.total_value(), U256::from(162));
+        assert_eq!(report.block_span(), 2);
+
+        // Negated steps keep their place with nothing matched
+        let p = BehavioralPattern::parse("Tf -> !Wdw -> Dep").unwrap();
+        let (_, report) = p.matches_with_report(&symbols, None).unwrap();
+        assert_eq!(report.steps.len(), 3);
+        assert!(report.steps[1].positions.is_empty());
+        assert_eq!(report.steps[2].positions, vec![3]);
+        assert_eq!(report.steps[2].log_gap, Some(3));
+    }
+
+    #[test]
+    fn test_match_explain() {
+        let sym = |name: &str, log: u32, value: u64| {
+            let mut s = mock_sym(name, log);
+            s.value = U256::from(value);
+            s
+        };
+        let symbols = vec![sym("Tf", 0, 5), sym("Sw", 1, 100), sym("Sw", 2, 50)];
+
+        let p = BehavioralPattern::parse("Tf -> Sw{2,}").unwrap();
+        let explained = p.match_explain(&symbols, None);
+        assert!(explained.matched);
+        assert!(explained.failed_step().is_none());
+        assert_eq!(
+            explained.steps[1].outcome,
+            StepOutcome::Matched {
+                positions: vec![1, 2]
+            }
+        );
+
+        let p =
+            BehavioralPattern::parse("Tf -> Sw where value > 60 wei -> Sw where value > 60 wei")
+                .unwrap();
+        let explained = p.match_explain(&symbols, None);
+        assert!(!explained.matched);
+        let failed = explained.failed_step().unwrap();
+        assert_eq!(failed.step, 2);
+        assert_eq!(failed.text, "Sw where value > 60");
+        let StepOutcome::Failed {
+            from, candidates, ..
+        } = &failed.outcome
+        else {
+            panic!("step 1 should fail");
+        };
+        assert_eq!(*from, 2);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(
+            candidates[0].failed_condition.as_deref(),
+            Some("value > 60")
+        );
+        assert_eq!(
+            explained.steps[1].outcome,
+            StepOutcome::Matched { positions: vec![1] }
+        );
+
+        let explained = BehavioralPattern::parse("Tf -> Dep -> Sw")
+            .unwrap()
+            .match_explain(&symbols, None);
+        assert_eq!(explained.steps[2].outcome, StepOutcome::NotReached);
+
+        let explained = BehavioralPattern::parse("Sw -> Wdw")
+            .unwrap()
+            .match_explain(&symbols, None);
+        let StepOutcome::Failed { reason, .. } = &explained.failed_step().unwrap().outcome else {
+            panic!("step 1 should fail");
+        };
+        assert_eq!(reason, "No Wdw at or after position 2");
+    }
+
     #[test]
     fn test_frontrun_match() {
         let symbols = vec![mock_sym("Tf", 0), mock_sym("Sw", 1)];
@@ -549,7 +2853,7 @@ mod tests {
 
     #[test]
     fn test_match_value_condition() {
-        use ethers_core::types::Address;
+        use ethereum_types::Address;
         let sym_high = BehavioralSymbol::new("Tf", 0).with_context(
             Address::zero(),
             Address::zero(),
@@ -571,6 +2875,113 @@ mod tests {
         assert!(p.matches(&vec![sym_low], None).is_none());
     }
 
+    #[test]
+    fn test_match_value_range_condition() {
+        use ethereum_types::Address;
+        let sym = |idx: u32, value: u128| {
+            BehavioralSymbol::new("Tf", idx).with_context(
+                Address::zero(),
+                Address::zero(),
+                U256::from(value),
+                None,
+            )
+        };
+        let ether = 1_000_000_000_000_000_000u128;
+
+        let p = BehavioralPattern::parse("Tf where value between 1 ether and 10 ether").unwrap();
+        assert_eq!(
+            p.steps()[0],
+            PatternStep::Exact(
+                "Tf".into(),
+                PatternCondition::ValueInRange(U256::from(ether), U256::from(10 * ether))
+            )
+        );
+        // Both bounds are inclusive
+        assert!(p.matches(&[sym(0, ether)], None).is_some());
+        assert!(p.matches(&[sym(0, 10 * ether)], None).is_some());
+        assert!(p.matches(&[sym(0, ether / 2)], None).is_none());
+        assert!(p.matches(&[sym(0, 11 * ether)], None).is_none());
+
+        assert!(BehavioralPattern::parse("Tf where value between 10 ether and 1 ether").is_err());
+        assert!(BehavioralPattern::parse("Tf where value between 1 ether").is_err());
+    }
+
+    #[test]
+    fn test_compiled_pattern_matches_like_pattern() {
+        use ethereum_types::Address;
+        let big = |sym: BehavioralSymbol| {
+            sym.with_context(
+                Address::zero(),
+                Address::zero(),
+                U256::from(2_000_000_000_000_000_000u128),
+                None,
+            )
+        };
+        let blocks = [
+            vec![],
+            vec![mock_sym("Tf", 0)],
+            vec![mock_sym("Tf", 0), mock_sym("Sw", 1), mock_sym("Tf", 2)],
+            vec![
+                mock_sym("Tf", 0),
+                mock_sym("Sw", 1),
+                mock_sym("Sw", 2),
+                mock_sym("Sw", 3),
+                mock_sym("Tf", 4),
+            ],
+            vec![
+                mock_sym("Sw", 0),
+                big(mock_sym("Tf", 1)),
+                mock_sym("Dep", 2),
+                mock_sym("Sw", 3),
+                mock_sym("Sw", 4),
+            ],
+        ];
+        let patterns = [
+            "Tf",
+            "Tf -> Sw -> Tf",
+            "Sandwich",
+            "Tf -> Sw{2,} -> Tf",
+            "Tf -> Sw{2,3}",
+            "Sw{0} -> Tf",
+            "Tf -> Sw{0,}",
+            "Dep | Sw -> Tf",
+            "Sw -> * -> Sw",
+            "Tf where value > 1 ether -> Sw{2}",
+            "* where value > 1 ether",
+        ];
+        for pattern in patterns {
+            let parsed = BehavioralPattern::parse(pattern).unwrap();
+            let compiled = parsed.compile();
+            assert!(compiled.is_automaton(), "{}", pattern);
+            for symbols in &blocks {
+                assert_eq!(
+                    compiled.matches(symbols, None),
+                    parsed.matches(symbols, None),
+                    "{} on {:?}",
+                    pattern,
+                    symbols.iter().map(|s| s.symbol()).collect::<Vec<_>>()
+                );
+            }
+        }
+
+        // Anything else is matched by the pattern itself
+        for pattern in [
+            "(Tf -> Sw){2}",
+            "Tf => Sw",
+            "Tf -> !Sw -> Tf",
+            "Tf($a -> $b) -> Tf($b -> $a)",
+            "Tf -> Sw within 2 blocks",
+            "Tf | Sw{2,}",
+        ] {
+            let compiled = BehavioralPattern::parse(pattern).unwrap().compile();
+            assert!(!compiled.is_automaton(), "{}", pattern);
+            assert_eq!(
+                compiled.matches(&blocks[2], None),
+                compiled.pattern().matches(&blocks[2], None)
+            );
+        }
+    }
+
     #[test]
     fn test_parse_amount_units() {
         assert_eq!(
@@ -582,6 +2993,21 @@ mod tests {
             U256::from(500_000_000_000u128)
         );
         assert_eq!(parse_amount("1000000").unwrap(), U256::from(1_000_000));
+        assert_eq!(parse_amount("2 kwei").unwrap(), U256::from(2_000));
+        assert_eq!(parse_amount("1.5 mwei").unwrap(), U256::from(1_500_000));
+        assert_eq!(
+            parse_amount("3 finney").unwrap(),
+            U256::from(3_000_000_000_000_000u128)
+        );
+        // Token amounts in the token's own decimals
+        assert_eq!(
+            parse_amount("2.5 tokens(6)").unwrap(),
+            U256::from(2_500_000)
+        );
+        assert_eq!(parse_amount("7 tokens(0)").unwrap(), U256::from(7));
+        assert!(parse_amount("1.5 tokens(0)").is_err());
+        assert!(parse_amount("1 tokens(78)").is_err());
+        assert!(parse_amount("1 tokens").is_err());
     }
 
     #[test]
@@ -651,4 +3077,832 @@ mod tests {
         assert!(!matches_str(&vec![mock_sym("Tf", 0)], "tf", None)); // Case sensitive
         assert!(!matches_str(&vec![mock_sym("Tf", 0)], "TF", None));
     }
+
+    #[test]
+    fn test_parse_with_aliases() {
+        let aliases = SymbolAliases::new()
+            .with_alias("Deposit", "Dep")
+            .with_alias("Swap", "Sw")
+            .with_alias("Withdraw", "Wdw");
+        let pattern =
+            BehavioralPattern::parse_with_aliases("Deposit -> Swap{2,} -> Withdraw", &aliases)
+                .unwrap();
+        assert_eq!(
+            pattern.steps(),
+            BehavioralPattern::parse("Dep -> Sw{2,} -> Wdw")
+                .unwrap()
+                .steps()
+        );
+        // Without the table the long names stay as written
+        match &BehavioralPattern::parse("Swap").unwrap().steps()[0] {
+            PatternStep::Exact(s, _) => assert_eq!(s, "Swap"),
+            _ => panic!("Expected Exact step"),
+        }
+    }
+
+    #[test]
+    fn test_mentions() {
+        let pattern = BehavioralPattern::parse("BlobTx{2,} -> BridgeIn").unwrap();
+        assert!(pattern.mentions("BlobTx"));
+        assert!(pattern.mentions("BridgeIn"));
+        assert!(!pattern.mentions("Tf"));
+        // Presets mention the symbols they expand to
+        assert!(BehavioralPattern::parse("Sandwich").unwrap().mentions("Sw"));
+    }
+
+    #[test]
+    fn test_parse_alternation_precedence() {
+        // '|' groups within a segment, '->' separates segments
+        let p = BehavioralPattern::parse("Dep | Wdw -> Sw").unwrap();
+        assert_eq!(
+            p.steps(),
+            &[
+                PatternStep::AnyOf(vec![
+                    PatternStep::Exact("Dep".into(), PatternCondition::None),
+                    PatternStep::Exact("Wdw".into(), PatternCondition::None),
+                ]),
+                PatternStep::Exact("Sw".into(), PatternCondition::None),
+            ]
+        );
+
+        // Quantifiers and conditions bind to a single alternative
+        let p = BehavioralPattern::parse("Dep | Wdw{2,} where value > 1 ether").unwrap();
+        match &p.steps()[0] {
+            PatternStep::AnyOf(alternatives) => {
+                assert_eq!(
+                    alternatives[0],
+                    PatternStep::Exact("Dep".into(), PatternCondition::None)
+                );
+                assert!(matches!(
+                    alternatives[1],
+                    PatternStep::AtLeast(_, 2, PatternCondition::ValueGreaterThan(_))
+                ));
+            }
+            other => panic!("Expected AnyOf, got {:?}", other),
+        }
+        assert!(p.mentions("Dep") && p.mentions("Wdw"));
+
+        assert!(BehavioralPattern::parse("Dep | -> Sw").is_err());
+        assert!(BehavioralPattern::parse("| Dep").is_err());
+        // Quantifying an alternation takes a group
+        assert!(matches!(
+            BehavioralPattern::parse("(Dep | Wdw){2}").unwrap().steps()[0],
+            PatternStep::Group(_, 2, Some(2))
+        ));
+        // Every alternative counts toward the symbol limit
+        assert!(BehavioralPattern::parse("A|B|C|D|E|F -> G|H|I|J|K").is_err());
+    }
+
+    #[test]
+    fn test_alternation_matching() {
+        let p = BehavioralPattern::parse("Dep | Wdw -> Sw").unwrap();
+        assert!(p
+            .matches(&[mock_sym("Dep", 0), mock_sym("Sw", 1)], None)
+            .is_some());
+        assert!(p
+            .matches(&[mock_sym("Wdw", 0), mock_sym("Sw", 1)], None)
+            .is_some());
+        assert!(p
+            .matches(&[mock_sym("Tf", 0), mock_sym("Sw", 1)], None)
+            .is_none());
+
+        // The leftmost alternative match is taken
+        let symbols = [
+            mock_sym("Tf", 0),
+            mock_sym("Wdw", 1),
+            mock_sym("Dep", 2),
+            mock_sym("Sw", 3),
+        ];
+        let matched = p.matches(&symbols, None).unwrap();
+        assert_eq!(matched[0].symbol(), "Wdw");
+        assert_eq!(matched.len(), 2);
+
+        // "Dep | Wdw{2}" is one Dep or two Wdw, not two of either
+        let p = BehavioralPattern::parse("Dep | Wdw{2} -> Sw").unwrap();
+        let two_wdw = [mock_sym("Wdw", 0), mock_sym("Wdw", 1), mock_sym("Sw", 2)];
+        assert_eq!(p.matches(&two_wdw, None).unwrap().len(), 3);
+        let one_wdw = [mock_sym("Wdw", 0), mock_sym("Sw", 1)];
+        assert!(p.matches(&one_wdw, None).is_none());
+        let two_dep = [mock_sym("Dep", 0), mock_sym("Dep", 1), mock_sym("Sw", 2)];
+        assert_eq!(p.matches(&two_dep, None).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_parse_negation() {
+        let p =
+            BehavioralPattern::parse("Tf -> !Sw -> not LP+ where from == deployer -> Tf").unwrap();
+        assert_eq!(
+            p.steps()[1],
+            PatternStep::Absent("Sw".into(), PatternCondition::None)
+        );
+        assert_eq!(
+            p.steps()[2],
+            PatternStep::Absent("LP+".into(), PatternCondition::FromDeployer)
+        );
+        assert!(p.mentions("Sw"));
+
+        assert!(BehavioralPattern::parse("!Sw").is_err());
+        assert!(BehavioralPattern::parse("Tf -> !Sw{2,}").is_err());
+        assert!(BehavioralPattern::parse("Tf -> !Sw | Dep").is_err());
+        assert!(BehavioralPattern::parse("Tf -> Dep | !Sw").is_err());
+    }
+
+    #[test]
+    fn test_negation_matching() {
+        let p = BehavioralPattern::parse("Tf -> !Sw -> Tf").unwrap();
+        let syms = |names: &[&str]| -> Vec<BehavioralSymbol> {
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| mock_sym(n, i as u32))
+                .collect()
+        };
+
+        assert!(p.matches(&syms(&["Tf", "Tf"]), None).is_some());
+        assert!(p.matches(&syms(&["Tf", "Dep", "Tf"]), None).is_some());
+        assert!(p.matches(&syms(&["Tf", "Sw", "Tf"]), None).is_none());
+
+        // The first transfer pair is broken by a swap, the second is not
+        let symbols = syms(&["Tf", "Sw", "Tf", "Tf"]);
+        let matched = p.matches(&symbols, None).unwrap();
+        assert_eq!(
+            matched.iter().map(|s| s.log_index()).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+
+        // An earlier step moves later to close the gap
+        let p = BehavioralPattern::parse("Dep -> Tf -> !Sw -> Tf").unwrap();
+        let symbols = syms(&["Dep", "Tf", "Sw", "Tf", "Tf"]);
+        let matched = p.matches(&symbols, None).unwrap();
+        assert_eq!(
+            matched.iter().map(|s| s.log_index()).collect::<Vec<_>>(),
+            vec![0, 3, 4]
+        );
+
+        // At the edges, the gap runs to the start or end of the block
+        let leading = BehavioralPattern::parse("!Sw -> Tf").unwrap();
+        assert!(leading.matches(&syms(&["Dep", "Tf"]), None).is_some());
+        assert!(leading.matches(&syms(&["Sw", "Tf"]), None).is_none());
+        let trailing = BehavioralPattern::parse("Tf -> !Sw").unwrap();
+        assert!(trailing.matches(&syms(&["Sw", "Tf"]), None).is_some());
+        assert!(trailing.matches(&syms(&["Tf", "Sw"]), None).is_none());
+    }
+
+    #[test]
+    fn test_wildcard() {
+        let p = BehavioralPattern::parse("Dep -> * -> Wdw").unwrap();
+        assert_eq!(p.steps()[1], PatternStep::Any(PatternCondition::None));
+        assert_eq!(
+            BehavioralPattern::parse("Dep -> any -> Wdw")
+                .unwrap()
+                .steps(),
+            p.steps()
+        );
+        assert!(p.has_wildcard() && p.mentions("BlobTx"));
+        assert!(BehavioralPattern::parse("*{2}").is_err());
+        assert!(BehavioralPattern::parse("Tf -> !*").is_err());
+
+        let syms = |names: &[&str]| -> Vec<BehavioralSymbol> {
+            names
+                .iter()
+                .enumerate()
+                .map(|(i, n)| mock_sym(n, i as u32))
+                .collect()
+        };
+        assert!(p.matches(&syms(&["Dep", "Sw", "Wdw"]), None).is_some());
+        assert!(p.matches(&syms(&["Dep", "LP+", "Wdw"]), None).is_some());
+        // The wildcard takes one symbol, so Wdw must come after it
+        assert!(p.matches(&syms(&["Dep", "Wdw"]), None).is_none());
+
+        // Conditions apply to whatever symbol the wildcard takes
+        let big = BehavioralPattern::parse("Dep -> * where value > 1 ether").unwrap();
+        let mut symbols = syms(&["Dep", "Sw", "Tf"]);
+        symbols[2].value = U256::exp10(19);
+        let matched = big.matches(&symbols, None).unwrap();
+        assert_eq!(matched[1].symbol(), "Tf");
+    }
+
+    #[test]
+    fn test_parse_captures() {
+        let p = BehavioralPattern::parse("Tf($a -> $b) -> Tf($b -> _) where value > 1").unwrap();
+        assert_eq!(
+            p.steps()[0],
+            PatternStep::Exact(
+                "Tf".into(),
+                PatternCondition::Capture(Some("a".into()), Some("b".into()))
+            )
+        );
+        assert_eq!(
+            p.steps()[1],
+            PatternStep::Exact(
+                "Tf".into(),
+                PatternCondition::All(vec![
+                    PatternCondition::Capture(Some("b".into()), None),
+                    PatternCondition::ValueGreaterThan(U256::one()),
+                ])
+            )
+        );
+        assert!(p.has_capture());
+        assert!(BehavioralPattern::parse("Tf($a -> $b) | Sw -> *(_ -> $a)").is_ok());
+        assert!(BehavioralPattern::parse("Tf{2,}($a -> _)").is_err());
+        assert!(BehavioralPattern::parse("Tf($a)").is_err());
+        assert!(BehavioralPattern::parse("Tf(a -> $b)").is_err());
+        assert!(BehavioralPattern::parse("Tf($a -> $b").is_err());
+        assert!(BehavioralPattern::parse("Tf($a -> $b) Sw").is_err());
+    }
+
+    #[test]
+    fn test_capture_matching() {
+        let [alice, bob, carol] = [1u8, 2, 3].map(Address::repeat_byte);
+        let tf =
+            |i, from, to| BehavioralSymbol::new("Tf", i).with_context(from, to, U256::zero(), None);
+        let p = BehavioralPattern::parse("Tf($a -> $b) -> Tf($b -> $a)").unwrap();
+
+        assert!(p
+            .matches(&[tf(0, alice, bob), tf(1, bob, alice)], None)
+            .is_some());
+        assert!(p
+            .matches(&[tf(0, alice, bob), tf(1, bob, carol)], None)
+            .is_none());
+
+        // The first transfer does not bind for good: the match starts at the
+        // one the return transfer agrees with
+        let symbols = [tf(0, alice, carol), tf(1, alice, bob), tf(2, bob, alice)];
+        let matched = p.matches(&symbols, None).unwrap();
+        assert_eq!(
+            matched.iter().map(|s| s.log_index()).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        // Bound variables constrain negated steps too
+        let quiet =
+            BehavioralPattern::parse("Tf($a -> $b) -> !Tf($a -> _) -> Tf($b -> $a)").unwrap();
+        assert!(quiet
+            .matches(
+                &[tf(0, alice, bob), tf(1, carol, bob), tf(2, bob, alice)],
+                None
+            )
+            .is_some());
+        assert!(quiet
+            .matches(
+                &[tf(0, alice, bob), tf(1, alice, carol), tf(2, bob, alice)],
+                None
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_search_budget() {
+        // Every transfer binds new addresses, so no state repeats
+        let symbols: Vec<_> = (0..100u32)
+            .map(|i| {
+                let from = Address::from_low_u64_be(i as u64 + 1);
+                BehavioralSymbol::new("Tf", i).with_context(from, from, U256::zero(), None)
+            })
+            .collect();
+        let p =
+            BehavioralPattern::parse("Tf($a -> _) -> Tf($b -> _) -> Tf($c -> _) -> Sw").unwrap();
+        assert!(p.try_matches(&symbols, None).is_err());
+        assert!(p.matches(&symbols, None).is_none());
+        assert!(p.compile().try_matches(&symbols, None).is_err());
+
+        // Small blocks stay within the budget
+        assert_eq!(p.try_matches(&symbols[..10], None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_untrusted_limits() {
+        let ok = BehavioralPattern::parse("Tf($a -> $b) -> Tf($b -> $a)").unwrap();
+        assert!(ok.check_untrusted().is_ok());
+
+        let captures = BehavioralPattern::parse("Tf($a -> $b) -> Tf($c -> $d)").unwrap();
+        assert!(captures.check_untrusted().is_err());
+        let roles = BehavioralPattern::parse("A:Tf -> B:Sw -> C:Tf -> D:Sw").unwrap();
+        assert!(roles.check_untrusted().is_err());
+
+        let long = BehavioralPattern::parse("Tf -> Sw -> Tf -> Sw -> Tf -> Sw -> Tf").unwrap();
+        assert!(long.check_untrusted().is_err());
+    }
+
+    #[test]
+    fn test_actor_roles() {
+        let p = BehavioralPattern::parse("A:Tf -> B:Sw -> A:Tf($a -> _)").unwrap();
+        assert_eq!(
+            p.steps()[0],
+            PatternStep::Exact("Tf".into(), PatternCondition::Actor("A".into()))
+        );
+        assert_eq!(p.steps()[2].to_string(), "A:Tf($a -> _)");
+        assert!(p.has_capture());
+        assert!(BehavioralPattern::parse("A:Tf | B:Sw -> !A:Tf -> A:* where value > 1").is_ok());
+        assert!(BehavioralPattern::parse("A:Tf{2,}").is_err());
+        assert!(BehavioralPattern::parse("A B:Tf").is_err());
+
+        // The classic sandwich: the victim's swap between the attacker's
+        let [attacker, victim, other] = [1u8, 2, 3].map(Address::repeat_byte);
+        let sym = |name, i, from| {
+            BehavioralSymbol::new(name, i).with_context(from, Address::zero(), U256::zero(), None)
+        };
+        let sandwich = BehavioralPattern::parse("A:Tf -> B:Sw -> A:Tf").unwrap();
+        assert!(sandwich
+            .matches(
+                &[
+                    sym("Tf", 0, attacker),
+                    sym("Sw", 1, victim),
+                    sym("Tf", 2, attacker)
+                ],
+                None
+            )
+            .is_some());
+        // Both ends must come from the same actor...
+        assert!(sandwich
+            .matches(
+                &[
+                    sym("Tf", 0, attacker),
+                    sym("Sw", 1, victim),
+                    sym("Tf", 2, other)
+                ],
+                None
+            )
+            .is_none());
+        // ...and the middle from a different one
+        assert!(sandwich
+            .matches(
+                &[
+                    sym("Tf", 0, attacker),
+                    sym("Sw", 1, attacker),
+                    sym("Tf", 2, attacker)
+                ],
+                None
+            )
+            .is_none());
+        let symbols = [
+            sym("Tf", 0, attacker),
+            sym("Sw", 1, attacker),
+            sym("Sw", 2, victim),
+            sym("Tf", 3, attacker),
+        ];
+        let matched = sandwich.matches(&symbols, None).unwrap();
+        assert_eq!(
+            matched.iter().map(|s| s.log_index()).collect::<Vec<_>>(),
+            vec![0, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_gas_condition() {
+        let p = BehavioralPattern::parse("Tf -> Sw where gas_used > 500_000").unwrap();
+        assert_eq!(
+            p.steps()[1],
+            PatternStep::Exact("Sw".into(), PatternCondition::GasGreaterThan(500_000))
+        );
+        assert_eq!(p.steps()[1].to_string(), "Sw where gas_used > 500000");
+        assert!(p.has_gas_condition());
+        assert!(!BehavioralPattern::parse("Tf -> Sw")
+            .unwrap()
+            .has_gas_condition());
+        assert!(BehavioralPattern::parse("(Tf -> Sw where gas_used > 1){2}")
+            .unwrap()
+            .has_gas_condition());
+        assert!(BehavioralPattern::parse("Sw where gas_used > lots").is_err());
+
+        let sw = |i, gas: Option<u64>| {
+            let sym = BehavioralSymbol::new("Sw", i);
+            match gas {
+                Some(gas) => sym.with_gas_used(gas),
+                None => sym,
+            }
+        };
+        let heavy = BehavioralPattern::parse("Sw where gas_used > 500000").unwrap();
+        assert!(heavy.matches(&[sw(0, Some(600_000))], None).is_some());
+        assert!(heavy.matches(&[sw(0, Some(500_000))], None).is_none());
+        // Without a receipt the gas is unknown, so the condition fails
+        assert!(heavy.matches(&[sw(0, None)], None).is_none());
+    }
+
+    #[test]
+    fn test_on_contract_condition() {
+        let pool: Address = "0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640"
+            .parse()
+            .unwrap();
+        let p = BehavioralPattern::parse(&format!(
+            "Sw on {:?} where value > 1 -> Tf($a -> _) on {:?}",
+            pool, pool
+        ))
+        .unwrap();
+        assert_eq!(
+            p.steps()[0],
+            PatternStep::Exact(
+                "Sw".into(),
+                PatternCondition::All(vec![
+                    PatternCondition::OnContract(pool),
+                    PatternCondition::ValueGreaterThan(U256::one()),
+                ])
+            )
+        );
+        assert_eq!(
+            p.steps()[0].to_string(),
+            format!("Sw on {:?} where value > 1", pool)
+        );
+        assert_eq!(
+            p.steps()[1].to_string(),
+            format!("Tf($a -> _) on {:?}", pool)
+        );
+        assert!(BehavioralPattern::parse(&format!("Sw{{2,}} on {:?}", pool)).is_ok());
+        assert!(BehavioralPattern::parse("Sw on 0xnotanaddress").is_err());
+
+        let sw = |i, contract| {
+            let mut sym = BehavioralSymbol::new("Sw", i);
+            sym.contract_address = contract;
+            sym
+        };
+        let scoped = BehavioralPattern::parse(&format!("Sw on {:?}", pool)).unwrap();
+        let symbols = [sw(0, Address::repeat_byte(9)), sw(1, pool)];
+        let matched = scoped.matches(&symbols, None).unwrap();
+        assert_eq!(matched[0].log_index(), 1);
+        assert!(scoped
+            .matches(&[sw(0, Address::repeat_byte(9))], None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_within_window() {
+        let p = BehavioralPattern::parse("LP+ -> Sw within 3 blocks").unwrap();
+        assert_eq!(p.window(), Some(MatchWindow::Blocks(3)));
+        assert_eq!(p.steps().len(), 2);
+        assert_eq!(
+            BehavioralPattern::parse("Sandwich within 1m")
+                .unwrap()
+                .window(),
+            Some(MatchWindow::Seconds(60))
+        );
+        assert!(BehavioralPattern::parse("Sw within 3").is_err());
+        assert!(BehavioralPattern::parse("Sw within 3 days").is_err());
+        assert!(BehavioralPattern::parse("Sw within 3s within 5s").is_err());
+
+        let at = |name: &str, idx: u32, block: u64| {
+            mock_sym(name, idx).with_block(block, 1_700_000_000 + block * 12)
+        };
+        // The first LP+ is too far from the swap; the second is close enough
+        let symbols = [at("LP+", 0, 100), at("LP+", 1, 105), at("Sw", 2, 108)];
+        let matched = p.matches(&symbols, None).unwrap();
+        assert_eq!(matched[0].log_index(), 1);
+        assert!(p.matches(&symbols[..1], None).is_none());
+        assert!(p
+            .matches(&[at("LP+", 0, 100), at("Sw", 1, 104)], None)
+            .is_none());
+
+        let timed = BehavioralPattern::parse("LP+ -> Sw within 36s").unwrap();
+        assert!(timed
+            .matches(&[at("LP+", 0, 100), at("Sw", 1, 103)], None)
+            .is_some());
+        assert!(timed
+            .matches(&[at("LP+", 0, 100), at("Sw", 1, 104)], None)
+            .is_none());
+
+        // Without block numbers everything is taken to be in one block
+        let plain = [mock_sym("LP+", 0), mock_sym("Sw", 1)];
+        assert!(p.matches(&plain, None).is_some());
+    }
+
+    #[test]
+    fn test_max_gap() {
+        let exact = |s: &str| PatternStep::Exact(s.into(), PatternCondition::None);
+        let p = BehavioralPattern::parse("Tf ->{<=1} Sw").unwrap();
+        assert_eq!(
+            p.steps(),
+            &[exact("Tf"), PatternStep::MaxGap(Box::new(exact("Sw")), 1)]
+        );
+        assert!(p.has_gap_limit());
+        assert_eq!(p.steps()[1].to_string(), "{<=1} Sw");
+        assert!(BehavioralPattern::parse("Tf -> {<=1} Sw{2,} | Dep").is_ok());
+        assert!(BehavioralPattern::parse("{<=1} Tf -> Sw").is_err());
+        assert!(BehavioralPattern::parse("Tf ->{<=x} Sw").is_err());
+        assert!(BehavioralPattern::parse("Tf ->{<=1 Sw").is_err());
+        assert!(BehavioralPattern::parse("Tf ->{<=1} !Sw -> Dep").is_err());
+        assert!(BehavioralPattern::parse("(Tf ->{<=1} Sw){2}").is_err());
+        assert!(BehavioralPattern::parse("Tf => {<=1} Sw").is_err());
+
+        let symbols = [
+            mock_sym("Tf", 0),
+            mock_sym("Dep", 1),
+            mock_sym("Dep", 2),
+            mock_sym("Sw", 3),
+        ];
+        // Two symbols between them is one too many
+        assert!(p.matches(&symbols, None).is_none());
+        assert!(BehavioralPattern::parse("Tf ->{<=2} Sw")
+            .unwrap()
+            .matches(&symbols, None)
+            .is_some());
+        // A later Tf is close enough, although the first one is not
+        let symbols = [
+            mock_sym("Tf", 0),
+            mock_sym("Dep", 1),
+            mock_sym("Tf", 2),
+            mock_sym("Dep", 3),
+            mock_sym("Sw", 4),
+        ];
+        let matched = p.matches(&symbols, None).unwrap();
+        assert_eq!(matched[0].log_index(), 2);
+        assert_eq!(p.compile().matches(&symbols, None), Some(matched));
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        let exact = |s: &str| PatternStep::Exact(s.into(), PatternCondition::None);
+        let p = BehavioralPattern::parse("(Tf -> Sw){3,}").unwrap();
+        assert_eq!(
+            p.steps(),
+            &[PatternStep::Group(vec![exact("Tf"), exact("Sw")], 3, None)]
+        );
+        assert!(p.mentions("Sw"));
+
+        let nested = BehavioralPattern::parse("Dep -> (Tf -> (Sw | LP+){2}) | Wdw").unwrap();
+        match &nested.steps()[1] {
+            PatternStep::AnyOf(alternatives) => match &alternatives[0] {
+                PatternStep::Group(steps, 1, Some(1)) => assert_eq!(
+                    steps[1],
+                    PatternStep::Group(
+                        vec![PatternStep::AnyOf(vec![exact("Sw"), exact("LP+")])],
+                        2,
+                        Some(2)
+                    )
+                ),
+                other => panic!("Expected group, got {:?}", other),
+            },
+            other => panic!("Expected alternation, got {:?}", other),
+        }
+
+        for invalid in [
+            "(Tf -> Sw",
+            "(Tf -> Sw) Dep",
+            "(Tf -> Sw){3,1}",
+            "(Tf -> !Sw){2}",
+            "(Tf($a -> _)){2}",
+            "!(Tf -> Sw)",
+            "((((((Tf))))))",
+            "(Tf -> Sw -> Tf -> Sw -> Tf -> Sw){2} -> Tf -> Sw -> Tf -> Sw -> Tf",
+        ] {
+            assert!(BehavioralPattern::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_group_matching() {
+        let names = ["Tf", "Sw", "Tf", "Sw", "Tf", "Sw", "Dep"];
+        let symbols: Vec<BehavioralSymbol> = names
+            .iter()
+            .enumerate()
+            .map(|(i, s)| mock_sym(s, i as u32))
+            .collect();
+
+        let repeated = BehavioralPattern::parse("(Tf -> Sw){3,}").unwrap();
+        assert_eq!(repeated.matches(&symbols, None).unwrap().len(), 6);
+        assert!(repeated.matches(&symbols[..4], None).is_none());
+
+        // At most two repetitions, then the deposit
+        let bounded = BehavioralPattern::parse("(Tf -> Sw){2} -> Dep").unwrap();
+        let matched = bounded.matches(&symbols, None).unwrap();
+        assert_eq!(matched.len(), 5);
+        assert_eq!(matched[4].symbol, "Dep");
+
+        let either = BehavioralPattern::parse("(Tf -> Sw) | Dep").unwrap();
+        assert_eq!(either.matches(&symbols[4..], None).unwrap().len(), 2);
+
+        // With a window, fewer repetitions are tried when all do not fit
+        let at = |name: &str, idx: u32, block: u64| mock_sym(name, idx).with_block(block, 0);
+        let blocks = [
+            at("Tf", 0, 100),
+            at("Sw", 1, 100),
+            at("Tf", 2, 101),
+            at("Sw", 3, 101),
+            at("Tf", 4, 104),
+            at("Sw", 5, 104),
+        ];
+        let windowed = BehavioralPattern::parse("(Tf -> Sw){2,} within 2 blocks").unwrap();
+        assert_eq!(windowed.matches(&blocks, None).unwrap().len(), 4);
+        let strict = BehavioralPattern::parse("(Tf -> Sw){3,} within 2 blocks").unwrap();
+        assert!(strict.matches(&blocks, None).is_none());
+    }
+
+    #[test]
+    fn test_parse_same_tx() {
+        let exact = |s: &str| PatternStep::Exact(s.into(), PatternCondition::None);
+        let p = BehavioralPattern::parse("Dep -> Tf => Sw | LP+ -> Wdw").unwrap();
+        assert_eq!(
+            p.steps(),
+            &[
+                exact("Dep"),
+                PatternStep::SameTx(vec![
+                    exact("Tf"),
+                    PatternStep::AnyOf(vec![exact("Sw"), exact("LP+")]),
+                ]),
+                exact("Wdw"),
+            ]
+        );
+        assert!(p.mentions("LP+"));
+        assert!(BehavioralPattern::parse("(Tf => Sw){2}").is_ok());
+
+        for invalid in [
+            "Tf =>",
+            "=> Sw",
+            "Tf => !Sw",
+            "Tf($a -> _) => Sw",
+            "A => B => C => D => E => F -> G => H => I => J => K",
+        ] {
+            assert!(BehavioralPattern::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_same_tx_matching() {
+        let in_tx = |name: &str, idx: u32, tx: u8| {
+            mock_sym(name, idx).with_causality(H256::repeat_byte(tx), 0, idx)
+        };
+        let same = BehavioralPattern::parse("Tf => Sw").unwrap();
+        let any = BehavioralPattern::parse("Tf -> Sw").unwrap();
+
+        // Transfer in one transaction, swap in the next
+        let split = [in_tx("Tf", 0, 1), in_tx("Sw", 1, 2)];
+        assert!(any.matches(&split, None).is_some());
+        assert!(same.matches(&split, None).is_none());
+
+        // The first transfer's transaction has no swap; the second's does
+        let symbols = [
+            in_tx("Tf", 0, 1),
+            in_tx("Tf", 1, 2),
+            in_tx("Dep", 2, 2),
+            in_tx("Sw", 3, 2),
+        ];
+        let matched = same.matches(&symbols, None).unwrap();
+        assert_eq!(
+            matched.iter().map(|s| s.log_index()).collect::<Vec<_>>(),
+            vec![1, 3]
+        );
+
+        // Chains follow cross-transaction steps
+        let p = BehavioralPattern::parse("Tf -> Tf => Sw").unwrap();
+        assert!(p.matches(&symbols, None).is_some());
+        let p = BehavioralPattern::parse("Tf => Dep -> Tf").unwrap();
+        assert!(p.matches(&symbols, None).is_none());
+
+        // Without a transaction hash, symbols are not known to share one
+        assert!(same
+            .matches(&[mock_sym("Tf", 0), mock_sym("Sw", 1)], None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_to_address_condition() {
+        let recipient: Address = "0x00000000219ab540356cBB839Cbe05303d7705Fa"
+            .parse()
+            .unwrap();
+        let pattern = BehavioralPattern::parse(
+            "ClWdw{2,} where to == 0x00000000219ab540356cBB839Cbe05303d7705Fa",
+        )
+        .unwrap();
+        match &pattern.steps()[0] {
+            PatternStep::AtLeast(_, 2, PatternCondition::ToAddress(addr)) => {
+                assert_eq!(*addr, recipient)
+            }
+            other => panic!("Expected ToAddress condition, got {:?}", other),
+        }
+
+        let to = |addr| {
+            BehavioralSymbol::new("ClWdw", 0).with_context(
+                Address::zero(),
+                addr,
+                U256::zero(),
+                None,
+            )
+        };
+        assert!(pattern
+            .matches(&[to(recipient), to(recipient)], None)
+            .is_some());
+        assert!(pattern
+            .matches(&[to(recipient), to(Address::zero())], None)
+            .is_none());
+
+        assert!(BehavioralPattern::parse("ClWdw where to == 0xnope").is_err());
+    }
+
+    #[test]
+    fn test_from_address_condition() {
+        let treasury: Address = "0x00000000219ab540356cBB839Cbe05303d7705Fa"
+            .parse()
+            .unwrap();
+        let pattern = BehavioralPattern::parse(
+            "Tf where from == 0x00000000219ab540356cBB839Cbe05303d7705Fa -> Sw",
+        )
+        .unwrap();
+        assert_eq!(
+            pattern.steps()[0],
+            PatternStep::Exact("Tf".into(), PatternCondition::FromAddress(treasury))
+        );
+
+        let tf = |from| {
+            BehavioralSymbol::new("Tf", 0).with_context(from, Address::zero(), U256::zero(), None)
+        };
+        let sw = BehavioralSymbol::new("Sw", 1);
+        assert!(pattern.matches(&[tf(treasury), sw.clone()], None).is_some());
+        assert!(pattern.matches(&[tf(Address::zero()), sw], None).is_none());
+
+        // "from == deployer" keeps its meaning
+        assert_eq!(
+            BehavioralPattern::parse("LP+ where from == deployer")
+                .unwrap()
+                .steps()[0],
+            PatternStep::Exact("LP+".into(), PatternCondition::FromDeployer)
+        );
+        assert!(BehavioralPattern::parse("Tf where from == 0x12").is_err());
+    }
+
+    #[test]
+    fn test_parse_nft_conditions() {
+        let collection: Address = "0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D"
+            .parse()
+            .unwrap();
+        let pattern = BehavioralPattern::parse(
+            "BuyNFT where token_id in collection 0xBC4CA0EdA7647A8aB7C2061c2E118A18a936f13D",
+        )
+        .unwrap();
+        assert_eq!(
+            pattern.steps()[0],
+            PatternStep::Exact("BuyNFT".into(), PatternCondition::InCollection(collection))
+        );
+        assert_eq!(
+            BehavioralPattern::parse("MintNFT where token_id == 0x2a")
+                .unwrap()
+                .steps()[0],
+            PatternStep::Exact("MintNFT".into(), PatternCondition::TokenId(U256::from(42)))
+        );
+
+        let sale =
+            |token: u64| BehavioralSymbol::new("BuyNFT", 0).with_nft(collection, token.into());
+        let by_token = BehavioralPattern::parse("BuyNFT where token_id == 42").unwrap();
+        assert!(by_token.matches(&[sale(42)], None).is_some());
+        assert!(by_token.matches(&[sale(43)], None).is_none());
+        assert!(pattern.matches(&[sale(43)], None).is_some());
+        // Symbols without a token never match
+        assert!(by_token
+            .matches(&[BehavioralSymbol::new("BuyNFT", 0)], None)
+            .is_none());
+        assert!(pattern
+            .matches(&[BehavioralSymbol::new("BuyNFT", 0)], None)
+            .is_none());
+
+        assert!(BehavioralPattern::parse("BuyNFT where token_id == forty").is_err());
+        assert!(BehavioralPattern::parse("BuyNFT where token_id in collection 0x12").is_err());
+    }
+
+    #[test]
+    fn test_canonical_hash() {
+        let hash = |p: &str| BehavioralPattern::parse(p).unwrap().canonical_hash();
+        assert_eq!(hash("Tf -> Sw"), hash("Tf->Sw"));
+        assert_eq!(hash("Sandwich"), hash("Tf -> Sw -> Tf"));
+        assert_eq!(hash("Tf | Sw -> Dep"), hash("Sw|Tf -> Dep"));
+        assert_eq!(
+            hash("Tf where value > 1 ether"),
+            hash("Tf where value > 1000000000000000000")
+        );
+        assert_ne!(hash("Tf -> Sw"), hash("Sw -> Tf"));
+        assert_ne!(hash("Tf -> Sw"), hash("Tf -> Sw within 3 blocks"));
+        assert_ne!(hash("Tf{2,}"), hash("Tf{2,3}"));
+
+        let encoding = BehavioralPattern::parse("Tf").unwrap().canonical_encoding();
+        assert_eq!(encoding[0], PATTERN_HASH_VERSION);
+        // Pinned so a change to the encoding cannot go unnoticed
+        assert_eq!(
+            hex::encode(hash("Tf -> Sw")),
+            "0533cf19446fd9e84bb31b27918c0682306811562158da553c2de2221398c3d8"
+        );
+    }
+
+    #[test]
+    fn test_display_parses_back() {
+        for text in [
+            "Sandwich -> Wdw",
+            "Tf{2,} -> Sw{1,3} -> Dep{2} within 3 blocks",
+            "Tf | Sw{2,} -> !Dep -> * within 60s",
+            "(Tf -> Sw){2,} -> Tf => Sw",
+            "Tf ->{<=3} Sw where value > 1 ether",
+            "Sw{2,} on 0x88e6A0c2dDD26FEEb64F039a2c41296FcB3f5640 where from == deployer",
+            "Tf where value between 1 ether and 10 ether",
+            "A:Tf($a -> _) -> B:Sw -> A:Tf(_ -> $a)",
+            "Tf where to == 0x00000000000000000000000000000000000000aa",
+            "BuyNFT where token_id in collection 0x00000000000000000000000000000000000000bb",
+        ] {
+            let pattern = BehavioralPattern::parse(text).unwrap();
+            let shown = pattern.to_string();
+            assert_eq!(
+                BehavioralPattern::parse(&shown)
+                    .unwrap_or_else(|e| panic!("{:?} from {:?}: {}", shown, text, e))
+                    .canonical_encoding(),
+                pattern.canonical_encoding(),
+                "{:?} from {:?}",
+                shown,
+                text
+            );
+        }
+    }
 }
@@ -12,6 +12,80 @@ pub enum ParserType {
     Swap,
     /// Generic log (just checks topic presence)
     Generic,
+    /// Fields located from the event's ABI (`SymbolDictionary::from_abi`)
+    Abi(AbiFields),
+}
+
+/// Where a log keeps a 32-byte field.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldSource {
+    /// `topics[i]`: an indexed parameter
+    Topic(usize),
+    /// The `i`th 32-byte word of the data: a static parameter
+    Word(usize),
+}
+
+/// Where an ABI-described event keeps the fields of its symbol.
+///
+/// Parameters are taken in declaration order, indexed or not: the first
+/// address is `from`, the second `to`, and the first `uint256` is `value`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AbiFields {
+    pub from: Option<FieldSource>,
+    pub to: Option<FieldSource>,
+    pub value: Option<FieldSource>,
+}
+
+impl AbiFields {
+    /// Locate the fields of `event`.
+    pub fn for_event(event: &ethabi::Event) -> Self {
+        use ethabi::ParamType;
+
+        // Words a parameter takes in the head of the data
+        fn head_words(kind: &ParamType) -> usize {
+            match kind {
+                _ if kind.is_dynamic() => 1,
+                ParamType::Tuple(kinds) => kinds.iter().map(head_words).sum(),
+                ParamType::FixedArray(kind, len) => head_words(kind) * len,
+                _ => 1,
+            }
+        }
+
+        let mut fields = Self::default();
+        let mut addresses = Vec::new();
+        // topics[0] is the event signature
+        let mut topic = 1;
+        let mut word = 0;
+        for param in &event.inputs {
+            let source = if param.indexed {
+                topic += 1;
+                FieldSource::Topic(topic - 1)
+            } else {
+                word += head_words(&param.kind);
+                FieldSource::Word(word - head_words(&param.kind))
+            };
+            match param.kind {
+                ParamType::Address => addresses.push(source),
+                ParamType::Uint(256) if fields.value.is_none() => fields.value = Some(source),
+                _ => {}
+            }
+        }
+        fields.from = addresses.first().copied();
+        fields.to = addresses.get(1).copied();
+        fields
+    }
+
+    /// The field at `source` in `log`, if the log is long enough.
+    pub fn read(source: FieldSource, log: &ethers_core::types::Log) -> Option<H256> {
+        match source {
+            FieldSource::Topic(i) => log.topics.get(i).copied(),
+            FieldSource::Word(i) => log
+                .data
+                .get(i.checked_mul(32)?..i.checked_add(1)?.checked_mul(32)?)
+                .map(H256::from_slice),
+        }
+    }
 }
 
 /// A dynamic symbol plugin definition loaded from JSON.
@@ -27,6 +101,13 @@ pub struct SymbolPlugin {
     pub event_topic: H256,
     /// The parser logic to use
     pub parser: ParserType,
+    /// Replace whatever the topic is already mapped to, instead of failing
+    #[serde(
+        default,
+        rename = "override",
+        skip_serializing_if = "std::ops::Not::not"
+    )]
+    pub overrides: bool,
 }
 
 impl SymbolPlugin {
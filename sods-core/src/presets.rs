@@ -0,0 +1,248 @@
+//! User-defined pattern presets.
+//!
+//! `Sandwich`, `Frontrun` and `Backrun` are built into the parser. Anyone
+//! can name further patterns in `~/.sods/patterns.toml`:
+//!
+//! ```toml
+//! [patterns.RugPull]
+//! pattern = "LP+ -> LP- within 10 blocks"
+//! description = "Liquidity added and pulled shortly after"
+//! ```
+//!
+//! Once installed with `PatternPresets::install`, each name parses like a
+//! built-in preset wherever `BehavioralPattern::parse` is used, and any
+//! pattern can include a preset as steps with `@Name`, e.g.
+//! `@Sandwich -> Wdw`. Presets may reference each other the same way, up
+//! to a fixed depth.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::aliases::SymbolAliases;
+use crate::error::{Result, SodsError};
+use crate::pattern::{BehavioralPattern, PRESETS};
+
+/// Presets consulted by the pattern parser.
+static INSTALLED: RwLock<Option<Arc<PatternPresets>>> = RwLock::new(None);
+
+/// One named pattern.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetEntry {
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// Named patterns, as stored in `patterns.toml`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternPresets {
+    /// Name -> preset
+    #[serde(default)]
+    patterns: BTreeMap<String, PresetEntry>,
+}
+
+impl PatternPresets {
+    /// Create an empty set of presets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a preset without validating it (Builder pattern).
+    pub fn with_preset(mut self, name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.patterns.insert(
+            name.into(),
+            PresetEntry {
+                pattern: pattern.into(),
+                description: None,
+            },
+        );
+        self
+    }
+
+    /// Add or replace a preset after checking its name and pattern.
+    pub fn add(&mut self, name: &str, pattern: &str, description: Option<String>) -> Result<()> {
+        let preset = PresetEntry {
+            pattern: pattern.to_string(),
+            description,
+        };
+        // Checked with the preset in place, so references to itself fail
+        let mut updated = self.clone();
+        updated.patterns.insert(name.to_string(), preset);
+        validate_preset(name, &updated.patterns[name], &updated)?;
+        *self = updated;
+        Ok(())
+    }
+
+    /// The preset with this name, if any.
+    pub fn get(&self, name: &str) -> Option<&PresetEntry> {
+        self.patterns.get(name)
+    }
+
+    /// All presets, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PresetEntry)> {
+        self.patterns.iter().map(|(name, p)| (name.as_str(), p))
+    }
+
+    /// Whether no presets are defined.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check that every name can appear in a pattern, does not shadow a
+    /// built-in preset, and names a pattern that parses with these presets.
+    pub fn validate(&self) -> Result<()> {
+        self.patterns
+            .iter()
+            .try_for_each(|(name, preset)| validate_preset(name, preset, self))
+    }
+
+    /// Get the default path for the presets file.
+    pub fn get_default_path() -> Result<PathBuf> {
+        home::home_dir()
+            .map(|h| h.join(".sods").join("patterns.toml"))
+            .ok_or_else(|| SodsError::ConfigError("Could not determine home directory".into()))
+    }
+
+    /// Load and validate presets from `path`; a missing file means none.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| SodsError::ConfigError(format!("Failed to read presets: {}", e)))?;
+        let presets: Self = toml::from_str(&content)
+            .map_err(|e| SodsError::ConfigError(format!("Invalid {}: {}", path.display(), e)))?;
+        presets.validate()?;
+        Ok(presets)
+    }
+
+    /// Load presets from the default path.
+    pub fn load_local() -> Result<Self> {
+        Self::load(&Self::get_default_path()?)
+    }
+
+    /// Save the presets to `path`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| {
+                SodsError::ConfigError(format!("Failed to create config dir: {}", e))
+            })?;
+        }
+        let content = toml::to_string_pretty(self)
+            .map_err(|e| SodsError::ConfigError(format!("Failed to serialize presets: {}", e)))?;
+        fs::write(path, content)
+            .map_err(|e| SodsError::ConfigError(format!("Failed to write presets: {}", e)))
+    }
+
+    /// Save the presets to the default path.
+    pub fn save_local(&self) -> Result<()> {
+        self.save(&Self::get_default_path()?)
+    }
+
+    /// Make these presets the ones `BehavioralPattern::parse` expands,
+    /// replacing any installed before.
+    pub fn install(self) {
+        *INSTALLED.write().unwrap_or_else(|p| p.into_inner()) = Some(Arc::new(self));
+    }
+
+    /// The installed presets, or none.
+    pub(crate) fn installed() -> Arc<PatternPresets> {
+        INSTALLED
+            .read()
+            .unwrap_or_else(|p| p.into_inner())
+            .clone()
+            .unwrap_or_default()
+    }
+}
+
+fn validate_preset(name: &str, preset: &PresetEntry, presets: &PatternPresets) -> Result<()> {
+    if name.is_empty()
+        || name
+            .chars()
+            .any(|c| !c.is_alphanumeric() && c != '+' && c != '-' && c != '_')
+    {
+        return Err(SodsError::PatternError(format!(
+            "Invalid preset name: {:?}",
+            name
+        )));
+    }
+    if PRESETS.contains(&name) {
+        return Err(SodsError::PatternError(format!(
+            "Preset '{}' shadows a built-in preset",
+            name
+        )));
+    }
+    BehavioralPattern::parse_with_presets(
+        &preset.pattern,
+        &SymbolAliases::default(),
+        presets,
+        false,
+    )
+    .map_err(|e| {
+        SodsError::PatternError(format!("Preset '{}' has an invalid pattern: {}", name, e))
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_validates_presets() {
+        let mut presets = PatternPresets::new();
+        assert!(presets.add("RugPull", "LP+ -> LP-", None).is_ok());
+        assert!(presets.add("Sandwich", "Tf -> Sw", None).is_err());
+        assert!(presets.add("Rug Pull", "LP+ -> LP-", None).is_err());
+        assert!(presets.add("Broken", "LP+ ->", None).is_err());
+        assert!(presets.add("Double", "@RugPull -> @RugPull", None).is_ok());
+        assert!(presets.add("Missing", "@Nowhere -> Sw", None).is_err());
+        assert!(presets.add("Loop", "Tf -> @Loop", None).is_err());
+        assert_eq!(presets.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_installed_presets_parse_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("patterns.toml");
+        let mut presets = PatternPresets::new();
+        presets
+            .add(
+                "TestRugPull",
+                "LP+ -> LP- within 10 blocks",
+                Some("Liquidity pulled".into()),
+            )
+            .unwrap();
+        presets.save(&path).unwrap();
+
+        let loaded = PatternPresets::load(&path).unwrap();
+        assert_eq!(loaded, presets);
+        assert!(PatternPresets::load(&dir.path().join("missing.toml"))
+            .unwrap()
+            .is_empty());
+
+        let expanded = format!(
+            "{:?}",
+            BehavioralPattern::parse("LP+ -> LP- within 10 blocks").unwrap()
+        );
+        assert_ne!(
+            format!("{:?}", BehavioralPattern::parse("TestRugPull").unwrap()),
+            expanded
+        );
+        loaded.install();
+        assert_eq!(
+            format!("{:?}", BehavioralPattern::parse("TestRugPull").unwrap()),
+            expanded
+        );
+        // A window after the name applies to the expanded pattern
+        assert!(BehavioralPattern::parse("TestRugPull within 2 blocks").is_err());
+
+        // Patterns from other nodes cannot depend on local presets
+        assert!(BehavioralPattern::parse_untrusted("TestRugPull").is_err());
+        assert!(BehavioralPattern::parse_untrusted("@TestRugPull -> Sw").is_err());
+        assert!(BehavioralPattern::parse_untrusted("Sandwich").is_ok());
+    }
+}
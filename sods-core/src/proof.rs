@@ -169,16 +169,60 @@ pub struct OnChainBehavioralProof {
     pub receipts_root: Option<[u8; 32]>,
     /// ECDSA signature (optional)
     pub signature: Option<Vec<u8>>,
+    /// `SymbolDictionary::fingerprint` of the vocabulary the symbols were
+    /// parsed with. Not part of the calldata.
+    #[serde(default)]
+    pub dictionary_fingerprint: Option<[u8; 32]>,
+    /// Provenance of each symbol, parallel to `symbols`. Not part of the
+    /// calldata; empty for proofs exported before it was recorded.
+    #[serde(default)]
+    pub provenance: Vec<crate::symbol::SymbolProvenance>,
+    /// Content identifier bound into the signed commitment. Encoded as
+    /// `availabilityHash` for `verifyBehaviorWithAvailability`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub availability_cid: Option<String>,
 }
 
 impl OnChainBehavioralProof {
+    /// Record the fingerprint of the dictionary used to parse the symbols (Builder pattern).
+    pub fn with_dictionary_fingerprint(mut self, fingerprint: [u8; 32]) -> Self {
+        self.dictionary_fingerprint = Some(fingerprint);
+        self
+    }
+
+    /// Whether the proof was generated with the same vocabulary as
+    /// `dictionary`; `None` if the proof does not say.
+    #[cfg(feature = "evm")]
+    pub fn matches_dictionary(&self, dictionary: &crate::SymbolDictionary) -> Option<bool> {
+        self.dictionary_fingerprint
+            .map(|fingerprint| fingerprint == dictionary.fingerprint())
+    }
+
+    /// The least trustworthy provenance among the proved symbols; `None`
+    /// if the proof does not record provenance.
+    pub fn weakest_provenance(&self) -> Option<crate::symbol::SymbolProvenance> {
+        self.provenance.iter().copied().min()
+    }
+
+    /// Bind the published CID the commitment was signed over (Builder pattern).
+    pub fn with_availability_cid(mut self, cid: impl Into<String>) -> Self {
+        self.availability_cid = Some(cid.into());
+        self
+    }
+
     /// Export the proof as ABI-encoded calldata for `SODSVerifier.verifyBehavior`.
     /// v3 ABI: includes isLeftPath for explicit Merkle path ordering.
+    ///
+    /// A proof with an `availability_cid` is encoded for
+    /// `verifyBehaviorWithAvailability`, which takes `keccak256(cid)` as a
+    /// trailing bytes32 so the signature check covers the CID.
+    #[cfg(feature = "evm")]
     pub fn to_calldata(&self) -> Vec<u8> {
         // signature: verifyBehavior(uint256,uint256,string[],uint32[],bytes32[],bytes32[],bool[],bytes32,bytes32,uint256,bytes32,bytes,address)
+        // or verifyBehaviorWithAvailability(..., address, bytes32)
         use ethabi::{encode, Token};
 
-        let tokens = vec![
+        let mut tokens = vec![
             Token::Uint(self.block_number.into()),
             Token::Uint(self.chain_id.into()),
             Token::Array(
@@ -214,6 +258,11 @@ impl OnChainBehavioralProof {
             Token::Bytes(self.signature.clone().unwrap_or_default()),
             Token::Address(ethabi::Address::zero()), // trustedSigner (placeholder)
         ];
+        if let Some(cid) = &self.availability_cid {
+            tokens.push(Token::FixedBytes(
+                crate::commitment::BehavioralCommitment::availability_hash(cid).to_vec(),
+            ));
+        }
 
         encode(&tokens)
     }
@@ -289,6 +338,7 @@ mod tests {
     // use super::*;
     use crate::symbol::BehavioralSymbol;
     use crate::tree::BehavioralMerkleTree;
+    use crate::SymbolDictionary;
 
     #[test]
     fn test_onchain_proof_manual_abi_serialization() {
@@ -312,4 +362,83 @@ mod tests {
         println!("Calldata len: {}", calldata.len());
         println!("Calldata: 0x{}", hex::encode(&calldata));
     }
+
+    #[test]
+    fn test_dictionary_fingerprint_is_checked_not_encoded() {
+        let syms = vec![BehavioralSymbol::new("Tf", 0)];
+        let bmt = BehavioralMerkleTree::new(syms.clone());
+        let proof = bmt
+            .generate_onchain_proof(&[&syms[0]], 1, 100, None, 0)
+            .unwrap();
+        assert_eq!(proof.matches_dictionary(&SymbolDictionary::default()), None);
+
+        let stamped = proof
+            .clone()
+            .with_dictionary_fingerprint(SymbolDictionary::default().fingerprint());
+        assert_eq!(stamped.to_calldata(), proof.to_calldata());
+        assert_eq!(
+            stamped.matches_dictionary(&SymbolDictionary::default()),
+            Some(true)
+        );
+        assert_eq!(
+            stamped.matches_dictionary(&SymbolDictionary::empty()),
+            Some(false)
+        );
+
+        // Proofs exported before the field existed still load
+        let mut json = serde_json::to_value(&proof).unwrap();
+        json.as_object_mut()
+            .unwrap()
+            .remove("dictionary_fingerprint");
+        let legacy: crate::proof::OnChainBehavioralProof = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.dictionary_fingerprint, None);
+    }
+
+    #[test]
+    fn test_provenance_is_carried_not_encoded() {
+        use crate::symbol::SymbolProvenance;
+
+        let syms = vec![
+            BehavioralSymbol::new("Tf", 0).with_provenance(SymbolProvenance::ReceiptLog),
+            BehavioralSymbol::new("Sw", 1).with_provenance(SymbolProvenance::TraceDerived),
+        ];
+        let bmt = BehavioralMerkleTree::new(syms.clone());
+        let proof = bmt
+            .generate_onchain_proof(&[&syms[0], &syms[1]], 1, 100, None, 0)
+            .unwrap();
+        assert_eq!(
+            proof.provenance,
+            vec![SymbolProvenance::ReceiptLog, SymbolProvenance::TraceDerived]
+        );
+        assert_eq!(
+            proof.weakest_provenance(),
+            Some(SymbolProvenance::TraceDerived)
+        );
+
+        let mut bare = proof.clone();
+        bare.provenance.clear();
+        assert_eq!(bare.to_calldata(), proof.to_calldata());
+        assert_eq!(bare.weakest_provenance(), None);
+    }
+
+    #[test]
+    fn test_availability_cid_is_encoded() {
+        let syms = vec![BehavioralSymbol::new("Tf", 0)];
+        let bmt = BehavioralMerkleTree::new(syms.clone());
+        let proof = bmt
+            .generate_onchain_proof(&[&syms[0]], 1, 100, None, 0)
+            .unwrap();
+        let bound = proof.clone().with_availability_cid("bafybeigdyrzt");
+
+        let plain = proof.to_calldata();
+        let calldata = bound.to_calldata();
+        assert_eq!(calldata.len(), plain.len() + 32);
+        assert_eq!(
+            calldata[13 * 32..14 * 32],
+            crate::BehavioralCommitment::availability_hash("bafybeigdyrzt")
+        );
+        assert!(!serde_json::to_string(&proof)
+            .unwrap()
+            .contains("availability_cid"));
+    }
 }
@@ -46,11 +46,10 @@ fn migrate_v1_to_v2(data: &mut Value) -> Result<()> {
                 }
             } else if entry.is_object() {
                 // Already an object, ensure "name" exists for schema compliance
-                if entry.get("name").is_none() {
-                    entry
-                        .as_object_mut()
-                        .unwrap()
-                        .insert("name".to_string(), Value::String("Unknown".to_string()));
+                if let Some(object) = entry.as_object_mut() {
+                    object
+                        .entry("name")
+                        .or_insert_with(|| Value::String("Unknown".to_string()));
                 }
             }
         }
@@ -1,16 +1,14 @@
 pub mod migration;
+#[cfg(feature = "evm")]
 pub mod validator;
 
 use crate::error::{Result, SodsError};
-use ethers_core::types::Address;
-use migration::migrate_registry;
+use ethereum_types::Address;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
-use validator::RegistryValidator;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ContractEntry {
@@ -65,6 +63,7 @@ impl ContractRegistry {
     }
 
     /// Load the registry from the default local file path with validation and migration.
+    #[cfg(feature = "evm")]
     pub fn load_local() -> Result<Self> {
         let path = Self::get_default_path()?;
         if !path.exists() {
@@ -74,14 +73,14 @@ impl ContractRegistry {
         let content = fs::read_to_string(&path)
             .map_err(|e| SodsError::ConfigError(format!("Failed to read registry: {}", e)))?;
 
-        let mut json_data: Value = serde_json::from_str(&content)
+        let mut json_data: serde_json::Value = serde_json::from_str(&content)
             .map_err(|e| SodsError::ConfigError(format!("Failed to parse registry JSON: {}", e)))?;
 
         // 1. Migrate if necessary
-        migrate_registry(&mut json_data)?;
+        migration::migrate_registry(&mut json_data)?;
 
         // 2. Validate against schema
-        let validator = RegistryValidator::new()?;
+        let validator = validator::RegistryValidator::new()?;
         validator.validate(&json_data)?;
 
         // 3. Deserialize into typed structure
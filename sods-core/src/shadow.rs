@@ -1,6 +1,6 @@
 use crate::pattern::{BehavioralPattern, PatternCondition, PatternStep};
 use crate::symbol::BehavioralSymbol;
-use ethers_core::types::Address;
+use ethereum_types::Address;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ShadowStatus {
@@ -19,6 +19,8 @@ pub struct BehavioralShadow {
     pub current_step_index: usize,
     pub timeout_block: u64,
     pub created_at_block: u64,
+    /// Actor symbols passed over while waiting on a gap-limited step.
+    skipped: usize,
 }
 
 impl BehavioralShadow {
@@ -35,6 +37,7 @@ impl BehavioralShadow {
             current_step_index: 0, // 0 is usually the start trigger
             timeout_block: created_at_block + 10, // Default 10 block timeout
             created_at_block,
+            skipped: 0,
         }
     }
 
@@ -65,12 +68,42 @@ impl BehavioralShadow {
                 continue;
             }
 
+            // Any other symbol is a deviation anyway, so negated steps only
+            // name the forbidden symbol when it shows up
+            while let Some(PatternStep::Absent(target, cond)) =
+                self.steps.get(self.current_step_index)
+            {
+                if sym.symbol == *target && Self::check_condition(sym, cond) {
+                    return ShadowStatus::Deviation(format!(
+                        "Forbidden symbol {} at step {}",
+                        sym.symbol, self.current_step_index
+                    ));
+                }
+                self.current_step_index += 1;
+            }
+
             // Current expected step
             if self.current_step_index >= self.steps.len() {
                 return ShadowStatus::Resolved;
             }
 
-            let expected_step = &self.steps[self.current_step_index];
+            // A gap-limited step lets up to `max_gap` other actor symbols
+            // through before its own; past that the actor has deviated
+            let (expected_step, max_gap) = match &self.steps[self.current_step_index] {
+                PatternStep::MaxGap(step, gap) => (step.as_ref(), *gap),
+                step => (step, 0),
+            };
+            if max_gap > 0 && !Self::accepts(expected_step, sym) {
+                if self.skipped >= max_gap {
+                    return ShadowStatus::Deviation(format!(
+                        "Gap of {} exceeded before {} at step {}",
+                        max_gap, expected_step, self.current_step_index
+                    ));
+                }
+                self.skipped += 1;
+                continue;
+            }
+            self.skipped = 0;
 
             match expected_step {
                 PatternStep::Exact(target_res, cond) => {
@@ -100,6 +133,33 @@ impl BehavioralShadow {
                         ));
                     }
                 }
+                PatternStep::AnyOf(alternatives)
+                    if alternatives
+                        .iter()
+                        .all(|alt| matches!(alt, PatternStep::Exact(..))) =>
+                {
+                    let accepted = alternatives.iter().any(|alt| {
+                        matches!(alt, PatternStep::Exact(target, cond)
+                            if sym.symbol == *target && Self::check_condition(sym, cond))
+                    });
+                    if !accepted {
+                        return ShadowStatus::Deviation(format!(
+                            "Unexpected symbol: Expected {}, got {}",
+                            expected_step.symbols().join(" | "),
+                            sym.symbol
+                        ));
+                    }
+                    self.current_step_index += 1;
+                }
+                PatternStep::Any(cond) => {
+                    if !Self::check_condition(sym, cond) {
+                        return ShadowStatus::Deviation(format!(
+                            "Condition check failed for {} at step {}",
+                            sym.symbol, self.current_step_index
+                        ));
+                    }
+                    self.current_step_index += 1;
+                }
                 _ => {
                     // Logic for Range/AtLeast is complex for shadowing (stateful counting).
                     // For MVP V1.1, we assume expanded Exact steps or simple matching.
@@ -116,11 +176,37 @@ impl BehavioralShadow {
         }
     }
 
+    /// Whether `sym` satisfies a step the shadow can check symbol by symbol.
+    fn accepts(step: &PatternStep, sym: &BehavioralSymbol) -> bool {
+        match step {
+            PatternStep::Exact(target, cond) => {
+                sym.symbol == *target && Self::check_condition(sym, cond)
+            }
+            PatternStep::AnyOf(alternatives) => {
+                alternatives.iter().any(|alt| Self::accepts(alt, sym))
+            }
+            PatternStep::Any(cond) => Self::check_condition(sym, cond),
+            _ => true,
+        }
+    }
+
     fn check_condition(symbol: &BehavioralSymbol, condition: &PatternCondition) -> bool {
         match condition {
             PatternCondition::None => true,
             PatternCondition::FromDeployer => symbol.is_from_deployer,
             PatternCondition::ValueGreaterThan(threshold) => symbol.value > *threshold,
+            PatternCondition::ValueInRange(low, high) => (*low..=*high).contains(&symbol.value),
+            PatternCondition::ToAddress(address) => symbol.to == *address,
+            PatternCondition::FromAddress(address) => symbol.from == *address,
+            PatternCondition::TokenId(id) => symbol.token_id == Some(*id),
+            PatternCondition::InCollection(address) => symbol.collection == Some(*address),
+            PatternCondition::GasGreaterThan(gas) => symbol.gas_used.is_some_and(|g| g > *gas),
+            PatternCondition::OnContract(address) => symbol.contract_address == *address,
+            // A shadow follows one actor; captures are not tracked
+            PatternCondition::Capture(..) | PatternCondition::Actor(_) => true,
+            PatternCondition::All(conditions) => conditions
+                .iter()
+                .all(|cond| Self::check_condition(symbol, cond)),
         }
     }
 
@@ -134,3 +220,38 @@ impl BehavioralShadow {
         Self::new(actor, nonce, pattern.steps().to_vec(), block)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethereum_types::U256;
+
+    fn actor_symbol(actor: Address, symbol: &str, nonce: u64) -> BehavioralSymbol {
+        BehavioralSymbol::new(symbol, nonce as u32)
+            .with_context(actor, Address::zero(), U256::zero(), None)
+            .with_causality(Default::default(), nonce, 0)
+    }
+
+    #[test]
+    fn test_max_gap_is_enforced() {
+        let actor = Address::repeat_byte(1);
+        let pattern = BehavioralPattern::parse("Tf ->{<=1} Sw").unwrap();
+
+        let mut shadow = BehavioralShadow::from_pattern(&pattern, actor, 0, 100);
+        shadow.current_step_index = 1;
+        let within = [actor_symbol(actor, "Ap", 1), actor_symbol(actor, "Sw", 2)];
+        assert_eq!(shadow.check_block(101, &within), ShadowStatus::Resolved);
+
+        let mut shadow = BehavioralShadow::from_pattern(&pattern, actor, 0, 100);
+        shadow.current_step_index = 1;
+        let beyond = [
+            actor_symbol(actor, "Ap", 1),
+            actor_symbol(actor, "Ap", 2),
+            actor_symbol(actor, "Sw", 3),
+        ];
+        assert!(matches!(
+            shadow.check_block(101, &beyond),
+            ShadowStatus::Deviation(_)
+        ));
+    }
+}
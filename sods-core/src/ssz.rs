@@ -1,4 +1,4 @@
-use ethers_core::types::{Address, Bloom, H256, U256};
+use ethereum_types::{Address, Bloom, H256, U256};
 use serde::{Deserialize, Serialize};
 
 /// ExecutionPayloadHeader for Post-Dencun blocks.
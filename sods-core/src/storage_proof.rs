@@ -30,9 +30,8 @@ pub fn verify_storage_proof(proof: &EIP1186ProofResponse, state_root: H256) -> P
         ) {
             let rlp = Rlp::new(&account_rlp);
             // Account RLP is [nonce, balance, storageRoot, codeHash]
-            if rlp.item_count().unwrap_or(0) >= 3 {
-                let storage_root: H256 = rlp.at(2).unwrap().as_val().unwrap();
-
+            let storage_root = rlp.at(2).and_then(|item| item.as_val::<H256>());
+            if let Ok(storage_root) = storage_root {
                 for sp in &proof.storage_proof {
                     let mut key_bytes = [0u8; 32];
                     sp.key.to_big_endian(&mut key_bytes);
@@ -0,0 +1,290 @@
+//! Pattern matching over a stream of blocks.
+//!
+//! `BehavioralPattern::matches` looks at one symbol slice, so a monitor
+//! feeding it block by block never sees a sandwich whose legs land in
+//! consecutive blocks. `PatternStreamMatcher` keeps the last few blocks
+//! in one buffer and, each time a non-empty block arrives, matches over
+//! the part of it not yet passed over, reporting each match once,
+//! including those that span several blocks.
+//!
+//! How many blocks are kept follows the pattern's `within` clause: `N`
+//! blocks keeps `N + 1`, a duration keeps blocks until they fall out of
+//! it (up to `MAX_STREAM_BLOCKS`), and no clause keeps a single block, so
+//! such patterns match exactly as they do per block.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::pattern::{BehavioralPattern, MatchReport, MatchWindow};
+use crate::symbol::BehavioralSymbol;
+
+/// Most blocks a stream keeps, whatever the pattern's window.
+pub const MAX_STREAM_BLOCKS: u64 = 256;
+
+/// A match found by `PatternStreamMatcher`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamMatch {
+    /// Matched symbols in order
+    pub symbols: Vec<BehavioralSymbol>,
+    /// Where each step landed; positions index into `symbols`
+    pub report: MatchReport,
+    /// Block of the first matched symbol
+    pub first_block: u64,
+    /// Block of the last matched symbol
+    pub last_block: u64,
+}
+
+impl StreamMatch {
+    /// Whether the match spans more than one block.
+    pub fn is_cross_block(&self) -> bool {
+        self.first_block != self.last_block
+    }
+}
+
+/// One buffered block: its number, symbol count and timestamp.
+#[derive(Debug, Clone, Copy)]
+struct BufferedBlock {
+    number: u64,
+    len: usize,
+    timestamp: Option<u64>,
+}
+
+/// Matches a pattern against blocks as they arrive.
+#[derive(Debug, Clone)]
+pub struct PatternStreamMatcher {
+    pattern: BehavioralPattern,
+    max_blocks: u64,
+    /// Buffered blocks, oldest first
+    blocks: VecDeque<BufferedBlock>,
+    /// Symbols of the buffered blocks, in block order
+    symbols: Vec<BehavioralSymbol>,
+    /// Leading buffered symbols already part of a reported match, or
+    /// before one
+    consumed: usize,
+}
+
+impl PatternStreamMatcher {
+    pub fn new(pattern: BehavioralPattern) -> Self {
+        let max_blocks = match pattern.window() {
+            None => 1,
+            Some(MatchWindow::Blocks(n)) => n.saturating_add(1),
+            Some(MatchWindow::Seconds(_)) => MAX_STREAM_BLOCKS,
+        };
+        Self {
+            pattern,
+            max_blocks: max_blocks.min(MAX_STREAM_BLOCKS),
+            blocks: VecDeque::new(),
+            symbols: Vec::new(),
+            consumed: 0,
+        }
+    }
+
+    /// Keep up to `max_blocks` blocks instead of what the window implies,
+    /// e.g. to let a pattern without a `within` clause span blocks
+    /// (Builder pattern).
+    pub fn with_max_blocks(mut self, max_blocks: u64) -> Self {
+        self.max_blocks = max_blocks.clamp(1, MAX_STREAM_BLOCKS);
+        self
+    }
+
+    pub fn pattern(&self) -> &BehavioralPattern {
+        &self.pattern
+    }
+
+    /// Number of blocks currently buffered.
+    pub fn buffered_blocks(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Add block `number` and return the matches it completes.
+    ///
+    /// Blocks are expected in increasing order. A block at or below the
+    /// newest buffered one is taken as a reorg: it replaces that block and
+    /// drops the ones after it. Matches already returned are not taken
+    /// back, and symbols before the reorged block that were passed over
+    /// stay passed over. Symbols without a block number are given `number`.
+    ///
+    /// Any new match must use a symbol of the new block, so an empty block
+    /// is only buffered, not matched.
+    pub fn push_block(
+        &mut self,
+        number: u64,
+        mut symbols: Vec<BehavioralSymbol>,
+    ) -> Vec<StreamMatch> {
+        while let Some(block) = self.blocks.back().filter(|b| b.number >= number) {
+            let kept = self.symbols.len() - block.len;
+            self.symbols.truncate(kept);
+            self.blocks.pop_back();
+        }
+        self.consumed = self.consumed.min(self.symbols.len());
+
+        for sym in &mut symbols {
+            sym.block_number.get_or_insert(number);
+        }
+        let added = symbols.len();
+        self.blocks.push_back(BufferedBlock {
+            number,
+            len: added,
+            timestamp: symbols.first().and_then(|s| s.timestamp),
+        });
+        self.symbols.extend(symbols);
+        self.evict(number);
+
+        let mut found = Vec::new();
+        if added == 0 {
+            return found;
+        }
+        while let Some((matched, mut report)) = self
+            .pattern
+            .matches_with_report(&self.symbols[self.consumed..], None)
+        {
+            let Some(&last) = report.steps.iter().flat_map(|s| &s.positions).max() else {
+                break;
+            };
+            let symbols: Vec<BehavioralSymbol> = matched.into_iter().cloned().collect();
+            // Steps match in order, so their positions, in order, are the
+            // matched symbols
+            let positions = report.steps.iter_mut().flat_map(|s| &mut s.positions);
+            for (index, position) in positions.enumerate() {
+                *position = index;
+            }
+            let block_of =
+                |sym: Option<&BehavioralSymbol>| sym.and_then(|s| s.block_number).unwrap_or(number);
+            found.push(StreamMatch {
+                first_block: block_of(symbols.first()),
+                last_block: block_of(symbols.last()),
+                symbols,
+                report,
+            });
+            self.consumed += last + 1;
+        }
+        found
+    }
+
+    /// Symbols of buffered block `number`, e.g. to prove a match in it.
+    pub fn block_symbols(&self, number: u64) -> Option<&[BehavioralSymbol]> {
+        let mut start = 0;
+        for block in &self.blocks {
+            if block.number == number {
+                return Some(&self.symbols[start..start + block.len]);
+            }
+            start += block.len;
+        }
+        None
+    }
+
+    /// Drop blocks no match ending in `newest` could reach.
+    fn evict(&mut self, newest: u64) {
+        let cutoff = match self.pattern.window() {
+            Some(MatchWindow::Seconds(secs)) => self
+                .blocks
+                .back()
+                .and_then(|b| b.timestamp)
+                .map(|t| t.saturating_sub(secs)),
+            _ => None,
+        };
+        let mut dropped = 0;
+        while let Some(block) = self.blocks.front() {
+            // A leading empty block holds nothing a later match could use
+            let too_old = block.len == 0
+                || block.number.saturating_add(self.max_blocks) <= newest
+                || cutoff.is_some_and(|cutoff| block.timestamp.is_some_and(|t| t < cutoff));
+            if !too_old {
+                break;
+            }
+            dropped += block.len;
+            self.blocks.pop_front();
+        }
+        if dropped > 0 {
+            self.symbols.drain(..dropped);
+            self.consumed = self.consumed.saturating_sub(dropped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(number: u64, symbols: &[&str]) -> Vec<BehavioralSymbol> {
+        symbols
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                BehavioralSymbol::new(*s, i as u32).with_block(number, 1_700_000_000 + number * 12)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_matches_across_blocks() {
+        let pattern = BehavioralPattern::parse("Tf -> Sw -> Tf within 2 blocks").unwrap();
+        let mut stream = PatternStreamMatcher::new(pattern);
+
+        assert!(stream.push_block(100, block(100, &["Tf"])).is_empty());
+        assert!(stream.push_block(101, block(101, &["Sw"])).is_empty());
+        let found = stream.push_block(102, block(102, &["Tf", "Sw"]));
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].first_block, found[0].last_block), (100, 102));
+        assert!(found[0].is_cross_block());
+        assert_eq!(found[0].report.steps[2].positions, vec![2]);
+        assert_eq!(stream.block_symbols(101).unwrap()[0].symbol, "Sw");
+
+        // Reported once; the Sw left over needs a fresh Tf before it
+        assert!(stream.push_block(103, block(103, &["Tf"])).is_empty());
+        assert_eq!(stream.buffered_blocks(), 3);
+
+        // Blocks more than 2 behind the newest are dropped
+        assert!(stream.push_block(106, block(106, &["Sw", "Tf"])).is_empty());
+        assert_eq!(stream.buffered_blocks(), 1);
+    }
+
+    #[test]
+    fn test_seconds_window_evicts_by_timestamp() {
+        // Blocks are 12 seconds apart, so 30 seconds spans three of them
+        let pattern = BehavioralPattern::parse("Tf -> Sw within 30s").unwrap();
+        let mut stream = PatternStreamMatcher::new(pattern);
+        stream.push_block(1, block(1, &["Tf"]));
+        assert!(stream.push_block(2, Vec::new()).is_empty());
+        for n in 3..=10 {
+            stream.push_block(n, block(n, &["Dep"]));
+        }
+        assert_eq!(stream.buffered_blocks(), 3);
+        assert!(stream.block_symbols(1).is_none());
+        assert_eq!(stream.block_symbols(9).unwrap()[0].symbol, "Dep");
+
+        // The Tf fell out of the window
+        assert!(stream.push_block(11, block(11, &["Sw"])).is_empty());
+        stream.push_block(12, block(12, &["Tf"]));
+        assert_eq!(stream.push_block(13, block(13, &["Sw"])).len(), 1);
+    }
+
+    #[test]
+    fn test_without_window_matches_per_block() {
+        let pattern = BehavioralPattern::parse("Tf -> Sw").unwrap();
+        let mut stream = PatternStreamMatcher::new(pattern.clone());
+        assert!(stream.push_block(1, block(1, &["Tf"])).is_empty());
+        assert!(stream.push_block(2, block(2, &["Sw"])).is_empty());
+        let found = stream.push_block(3, block(3, &["Tf", "Sw", "Tf", "Sw"]));
+        assert_eq!(found.len(), 2);
+        assert!(!found[0].is_cross_block());
+
+        let mut spanning = PatternStreamMatcher::new(pattern).with_max_blocks(2);
+        spanning.push_block(1, block(1, &["Tf"]));
+        assert_eq!(spanning.push_block(2, block(2, &["Sw"])).len(), 1);
+    }
+
+    #[test]
+    fn test_reorged_block_is_replaced() {
+        let pattern = BehavioralPattern::parse("Tf -> Sw within 3 blocks").unwrap();
+        let mut stream = PatternStreamMatcher::new(pattern);
+        stream.push_block(10, block(10, &["Tf"]));
+        stream.push_block(11, block(11, &["Tf"]));
+        // 11 is reorged away, and its replacement completes the first Tf
+        let found = stream.push_block(11, block(11, &["Sw"]));
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].first_block, found[0].last_block), (10, 11));
+        assert_eq!(stream.buffered_blocks(), 2);
+    }
+}
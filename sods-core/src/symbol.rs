@@ -3,7 +3,7 @@
 //! This module defines the `BehavioralSymbol` struct which represents
 //! a parsed behavioral event extracted from EVM logs.
 
-use ethers_core::types::{Address, H256, U256};
+use ethereum_types::{Address, H256, U256};
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 
@@ -16,6 +16,78 @@ pub struct BehavioralProofInput {
     pub chain_id: u64,
 }
 
+/// Symbol for an EIP-4844 blob transaction, one per type-3 transaction.
+/// Unlike other symbols it comes from the transaction, not from a log.
+pub const BLOB_TX_SYMBOL: &str = "BlobTx";
+
+/// Blob gas consumed by each blob (EIP-4844 `GAS_PER_BLOB`).
+pub const GAS_PER_BLOB: u64 = 1 << 17;
+
+/// Symbol for a consensus-layer withdrawal (EIP-4895), one per withdrawal
+/// in the block body. Withdrawals never emit logs.
+pub const CL_WITHDRAWAL_SYMBOL: &str = "ClWdw";
+
+/// How a behavioral symbol was derived, from least to most trustworthy.
+///
+/// Provenance is informational: it is not part of the leaf hash, so two
+/// symbols with different provenance still produce the same BMT root.
+/// Risk logic can use it to discount symbols whose derivation path is
+/// unverified.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolProvenance {
+    /// Guessed from pending transaction calldata; may never be mined.
+    MempoolPredicted,
+    /// Extracted from a simulated or replayed execution trace.
+    TraceDerived,
+    /// Context extracted by a dynamically loaded plugin parser.
+    PluginParsed,
+    /// Log returned by an RPC endpoint without anchoring (unanchored).
+    #[default]
+    RpcLog,
+    /// Log taken from receipts validated against the block's `receiptsRoot`
+    /// (anchored).
+    ReceiptLog,
+}
+
+impl SymbolProvenance {
+    /// Whether the log is cryptographically bound to the block header.
+    pub fn is_anchored(self) -> bool {
+        self == Self::ReceiptLog
+    }
+
+    /// Suggested weight (0.0 - 1.0) for scoring a symbol of this provenance.
+    pub fn weight(self) -> f32 {
+        match self {
+            Self::MempoolPredicted => 0.2,
+            Self::TraceDerived => 0.5,
+            Self::PluginParsed => 0.6,
+            Self::RpcLog => 0.8,
+            Self::ReceiptLog => 1.0,
+        }
+    }
+
+    /// The least trustworthy provenance among `symbols`, if any.
+    pub fn weakest<'a>(symbols: impl IntoIterator<Item = &'a BehavioralSymbol>) -> Option<Self> {
+        symbols.into_iter().map(|s| s.provenance).min()
+    }
+}
+
+impl std::fmt::Display for SymbolProvenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::MempoolPredicted => "mempool_predicted",
+            Self::TraceDerived => "trace_derived",
+            Self::PluginParsed => "plugin_parsed",
+            Self::RpcLog => "rpc_log",
+            Self::ReceiptLog => "receipt_log",
+        };
+        f.write_str(name)
+    }
+}
+
 /// A behavioral symbol extracted from an EVM log event.
 ///
 /// Represents an atomic blockchain operation identified by its symbol code
@@ -34,7 +106,7 @@ pub struct BehavioralProofInput {
 ///
 /// ```rust
 /// use sods_core::BehavioralSymbol;
-/// use ethers_core::types::{Address, U256};
+/// use ethereum_types::{Address, U256};
 ///
 /// let symbol = BehavioralSymbol::new("Tf", 42);
 /// assert_eq!(symbol.symbol(), "Tf");
@@ -86,6 +158,53 @@ pub struct BehavioralSymbol {
 
     /// CoW Swap solver address
     pub solver: Option<Address>,
+
+    /// How this symbol was derived (not part of the leaf hash)
+    #[serde(default)]
+    pub provenance: SymbolProvenance,
+
+    /// Blob gas used by the symbol's transaction, if it is an EIP-4844
+    /// blob transaction (not part of the leaf hash)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blob_gas_used: Option<u64>,
+
+    /// Versioned hashes of the blobs the transaction carries
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blob_versioned_hashes: Vec<H256>,
+
+    /// Validator a consensus-layer withdrawal was paid from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validator_index: Option<u64>,
+
+    /// Block the symbol was emitted in, for patterns spanning several
+    /// blocks (not part of the leaf hash)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub block_number: Option<u64>,
+
+    /// Timestamp of that block (seconds since epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+
+    /// NFT contract the token belongs to, for NFT events (not part of the
+    /// leaf hash)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection: Option<Address>,
+
+    /// Gas used by the symbol's transaction, from its receipt; set when
+    /// receipts were fetched anyway or a pattern has a `gas_used`
+    /// condition (not part of the leaf hash)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_used: Option<u64>,
+
+    /// Amount given up in a swap, when the event reports it; `value` holds
+    /// the amount received (not part of the leaf hash)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub amount_in: Option<U256>,
+
+    /// Implementation behind `contract_address` when that is an EIP-1967
+    /// proxy and proxy resolution is on (not part of the leaf hash)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub implementation: Option<Address>,
 }
 
 impl BehavioralSymbol {
@@ -107,6 +226,16 @@ impl BehavioralSymbol {
             user_op_hash: None,
             permit_deadline: None,
             solver: None,
+            provenance: SymbolProvenance::default(),
+            blob_gas_used: None,
+            blob_versioned_hashes: vec![],
+            validator_index: None,
+            block_number: None,
+            timestamp: None,
+            collection: None,
+            gas_used: None,
+            amount_in: None,
+            implementation: None,
         }
     }
 
@@ -145,12 +274,60 @@ impl BehavioralSymbol {
         self
     }
 
+    /// Set the NFT collection and token (Builder pattern).
+    pub fn with_nft(mut self, collection: Address, token_id: U256) -> Self {
+        self.collection = Some(collection);
+        self.token_id = Some(token_id);
+        self
+    }
+
     /// Set CoW Swap context (Builder pattern).
     pub fn with_cow_context(mut self, solver: Address) -> Self {
         self.solver = Some(solver);
         self
     }
 
+    /// Set how this symbol was derived (Builder pattern).
+    pub fn with_provenance(mut self, provenance: SymbolProvenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    /// Set EIP-4844 blob context from the transaction's versioned hashes (Builder pattern).
+    ///
+    /// Each blob consumes exactly `GAS_PER_BLOB`, so the blob gas used follows
+    /// from the number of hashes.
+    pub fn with_blob_context(mut self, versioned_hashes: Vec<H256>) -> Self {
+        self.blob_gas_used = Some(versioned_hashes.len() as u64 * GAS_PER_BLOB);
+        self.blob_versioned_hashes = versioned_hashes;
+        self
+    }
+
+    /// Set the validator of a consensus-layer withdrawal (Builder pattern).
+    pub fn with_validator(mut self, validator_index: u64) -> Self {
+        self.validator_index = Some(validator_index);
+        self
+    }
+
+    /// Set the gas used by the symbol's transaction (Builder pattern).
+    pub fn with_gas_used(mut self, gas_used: u64) -> Self {
+        self.gas_used = Some(gas_used);
+        self
+    }
+
+    /// Set the amount given up in a swap (Builder pattern).
+    pub fn with_amount_in(mut self, amount_in: U256) -> Self {
+        self.amount_in = Some(amount_in);
+        self
+    }
+
+    /// Set the block number and timestamp, used by `within` windows (Builder pattern).
+    pub fn with_block(mut self, block_number: u64, timestamp: u64) -> Self {
+        self.block_number = Some(block_number);
+        self.timestamp = Some(timestamp);
+        self
+    }
+
     /// Returns the symbolic code.
     #[inline]
     pub fn symbol(&self) -> &str {
@@ -238,6 +415,24 @@ mod tests {
         assert!(sym_a < sym_b);
     }
 
+    #[test]
+    fn test_blob_context_is_not_hashed() {
+        let plain = BehavioralSymbol::new(BLOB_TX_SYMBOL, 3);
+        let blob = plain
+            .clone()
+            .with_blob_context(vec![H256::repeat_byte(1), H256::repeat_byte(2)]);
+
+        assert_eq!(blob.blob_gas_used, Some(2 * GAS_PER_BLOB));
+        assert_eq!(blob.blob_versioned_hashes.len(), 2);
+        assert_eq!(blob.leaf_hash(), plain.leaf_hash());
+
+        // Symbols without blobs serialize as before
+        let json = serde_json::to_string(&plain).unwrap();
+        assert!(!json.contains("blob"));
+        let parsed: BehavioralSymbol = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, plain);
+    }
+
     #[test]
     fn test_leaf_hash_minimal() {
         let sym = BehavioralSymbol::new("Tf", 0);
@@ -269,4 +464,27 @@ mod tests {
         assert_eq!(symbols[3].log_index(), 10);
         assert_eq!(symbols[3].symbol(), "Wdw");
     }
+
+    #[test]
+    fn test_provenance_does_not_affect_leaf_hash() {
+        let rpc = BehavioralSymbol::new("Tf", 3);
+        let anchored = rpc.clone().with_provenance(SymbolProvenance::ReceiptLog);
+        assert_eq!(rpc.provenance, SymbolProvenance::RpcLog);
+        assert_eq!(rpc.leaf_hash(), anchored.leaf_hash());
+        assert!(anchored.provenance.is_anchored());
+
+        let predicted =
+            BehavioralSymbol::new("Sw", 4).with_provenance(SymbolProvenance::MempoolPredicted);
+        assert_eq!(
+            SymbolProvenance::weakest([&anchored, &predicted]),
+            Some(SymbolProvenance::MempoolPredicted)
+        );
+        assert_eq!(SymbolProvenance::weakest(std::iter::empty()), None);
+
+        // Symbols serialized before the field existed still load
+        let mut json = serde_json::to_value(&rpc).unwrap();
+        json.as_object_mut().unwrap().remove("provenance");
+        let legacy: BehavioralSymbol = serde_json::from_value(json).unwrap();
+        assert_eq!(legacy.provenance, SymbolProvenance::RpcLog);
+    }
 }
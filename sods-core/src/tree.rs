@@ -106,7 +106,7 @@ impl BehavioralMerkleTree {
     }
 
     /// Build the Merkle tree layers from leaves to root using Keccak256.
-    fn build_tree(leaves: Vec<[u8; 32]>) -> (Vec<Vec<[u8; 32]>>, [u8; 32]) {
+    pub(crate) fn build_tree(leaves: Vec<[u8; 32]>) -> (Vec<Vec<[u8; 32]>>, [u8; 32]) {
         if leaves.is_empty() {
             return (vec![], keccak256_empty());
         }
@@ -117,13 +117,7 @@ impl BehavioralMerkleTree {
 
         let mut layers = vec![leaves];
 
-        loop {
-            let current = layers.last().unwrap();
-
-            if current.len() == 1 {
-                break;
-            }
-
+        while let Some(current) = layers.last().filter(|layer| layer.len() > 1) {
             let mut next_layer = Vec::with_capacity((current.len() + 1) / 2);
 
             for i in (0..current.len()).step_by(2) {
@@ -149,7 +143,7 @@ impl BehavioralMerkleTree {
             layers.push(next_layer);
         }
 
-        let root = layers.last().unwrap()[0];
+        let root = layers[layers.len() - 1][0];
         (layers, root)
     }
 
@@ -272,11 +266,13 @@ impl BehavioralMerkleTree {
         let mut symbols = Vec::new();
         let mut log_indices = Vec::new();
         let mut leaf_hashes = Vec::new();
+        let mut provenance = Vec::new();
 
         for s in matched_symbols {
             symbols.push(s.symbol().to_string());
             log_indices.push(s.log_index());
             leaf_hashes.push(s.leaf_hash());
+            provenance.push(s.provenance);
         }
 
         // For simplicity in the first version, we'll provide the proof for the FIRST symbol
@@ -300,6 +296,9 @@ impl BehavioralMerkleTree {
             timestamp,
             receipts_root: None,
             signature: None,
+            dictionary_fingerprint: None,
+            provenance,
+            availability_cid: None,
         })
     }
 }
@@ -1,4 +1,4 @@
-use ethers_core::types::U256;
+use ethereum_types::U256;
 use sods_core::pattern::parse_amount;
 
 #[test]
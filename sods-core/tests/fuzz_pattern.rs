@@ -1,5 +1,23 @@
 use proptest::prelude::*;
 use sods_core::pattern::BehavioralPattern;
+use sods_core::symbol::BehavioralSymbol;
+
+/// Steps the compiled automaton supports, over a three-symbol alphabet.
+fn step() -> impl Strategy<Value = String> {
+    let name = prop::sample::select(vec!["Tf", "Sw", "Dep"]);
+    prop_oneof![
+        name.clone().prop_map(str::to_string),
+        Just("*".to_string()),
+        (name.clone(), 0usize..3).prop_map(|(s, min)| format!("{}{{{},}}", s, min)),
+        (name.clone(), 0usize..3, 0usize..3).prop_map(|(s, min, extra)| format!(
+            "{}{{{},{}}}",
+            s,
+            min,
+            min + extra
+        )),
+        (name.clone(), name).prop_map(|(a, b)| format!("{} | {}", a, b)),
+    ]
+}
 
 proptest! {
     // This test feeds entirely random strings into the pattern parser.
@@ -48,4 +66,22 @@ proptest! {
 
         let _ = BehavioralPattern::parse(&pattern_str);
     }
+
+    // The compiled automaton must find exactly the matches of the pattern
+    #[test]
+    fn test_compiled_pattern_agrees_with_pattern(
+        steps in prop::collection::vec(step(), 1..5),
+        block in prop::collection::vec(prop::sample::select(vec!["Tf", "Sw", "Dep"]), 0..12),
+    ) {
+        let pattern = BehavioralPattern::parse(&steps.join(" -> ")).unwrap();
+        let compiled = pattern.compile();
+        prop_assert!(compiled.is_automaton());
+
+        let symbols: Vec<BehavioralSymbol> = block
+            .iter()
+            .enumerate()
+            .map(|(i, s)| BehavioralSymbol::new(*s, i as u32))
+            .collect();
+        prop_assert_eq!(compiled.matches(&symbols, None), pattern.matches(&symbols, None));
+    }
 }
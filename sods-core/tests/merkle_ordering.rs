@@ -74,6 +74,7 @@ fn test_single_leaf_tree_no_path() {
     assert!(proof.is_left_path.is_empty());
 }
 
+#[cfg(feature = "evm")]
 #[test]
 fn test_calldata_includes_is_left_path() {
     let syms = vec![
@@ -1,3 +1,5 @@
+#![cfg(feature = "evm")]
+
 use serde_json::json;
 use sods_core::registry::ContractRegistry;
 use std::fs;
@@ -0,0 +1,272 @@
+//! Batched proof requests.
+//!
+//! Verifying a block range one `ProofRequest` at a time costs a full
+//! round-trip per block. A `BatchProofRequest` carries many (symbol, block)
+//! pairs at once; the serving peer answers on the same stream with one
+//! length-prefixed CBOR frame per item, in request order, followed by an
+//! empty frame. The peer replies with a `BatchReply::Streaming` and writes
+//! each frame as soon as the item is proven, and frames are read one at a
+//! time with a per-frame size limit, so neither side has to hold a single
+//! oversized message.
+//!
+//! A batch is charged once, by its size, against its own rate limit
+//! bucket (`RateLimitConfig::batch_items`).
+
+use async_trait::async_trait;
+use futures::channel::mpsc;
+use futures::prelude::*;
+use libp2p::{request_response, StreamProtocol};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::io;
+
+use crate::protocol::{ProofRequest, ProofResponse};
+
+/// Maximum number of items in one batch.
+pub const MAX_BATCH_SIZE: usize = 256;
+
+/// Maximum encoded size of a batch request.
+const REQUEST_SIZE_MAXIMUM: u64 = 64 * 1024;
+
+/// Maximum encoded size of a single response frame.
+const FRAME_SIZE_MAXIMUM: u32 = 1024 * 1024;
+
+/// Request for proofs of several (symbol, block) pairs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchProofRequest {
+    pub items: Vec<ProofRequest>,
+}
+
+impl BatchProofRequest {
+    /// Builds a batch from (symbol, block) pairs.
+    pub fn new<S: Into<String>>(items: impl IntoIterator<Item = (S, u64)>) -> Self {
+        Self {
+            items: items
+                .into_iter()
+                .map(|(symbol, block_number)| ProofRequest {
+                    symbol: symbol.into(),
+                    block_number,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Responses to a `BatchProofRequest`, in request order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchProofResponse {
+    pub items: Vec<ProofResponse>,
+}
+
+/// What travels back for a `BatchProofRequest`.
+#[derive(Debug)]
+pub enum BatchReply {
+    /// All responses at once; what the requesting side reads
+    Complete(BatchProofResponse),
+    /// Responses written as they arrive, until the sender is dropped; what
+    /// the serving side sends
+    Streaming(mpsc::Receiver<ProofResponse>),
+}
+
+/// Codec for the batch protocol: a CBOR request, framed CBOR responses.
+#[derive(Debug, Clone, Default)]
+pub struct BatchCodec;
+
+fn encode<T: Serialize>(value: &T) -> io::Result<Vec<u8>> {
+    cbor4ii::serde::to_vec(Vec::new(), value)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> io::Result<T> {
+    cbor4ii::serde::from_slice(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+fn too_large(what: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{} exceeds protocol limit", what),
+    )
+}
+
+#[async_trait]
+impl request_response::Codec for BatchCodec {
+    type Protocol = StreamProtocol;
+    type Request = BatchProofRequest;
+    type Response = BatchReply;
+
+    async fn read_request<T>(&mut self, _: &StreamProtocol, io: &mut T) -> io::Result<Self::Request>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let mut buf = Vec::new();
+        io.take(REQUEST_SIZE_MAXIMUM).read_to_end(&mut buf).await?;
+        let request: BatchProofRequest = decode(&buf)?;
+        if request.items.len() > MAX_BATCH_SIZE {
+            return Err(too_large("Batch size"));
+        }
+        Ok(request)
+    }
+
+    async fn read_response<T>(
+        &mut self,
+        _: &StreamProtocol,
+        io: &mut T,
+    ) -> io::Result<Self::Response>
+    where
+        T: AsyncRead + Unpin + Send,
+    {
+        let mut items = Vec::new();
+        loop {
+            let mut len = [0u8; 4];
+            io.read_exact(&mut len).await?;
+            let len = u32::from_be_bytes(len);
+            if len == 0 {
+                return Ok(BatchReply::Complete(BatchProofResponse { items }));
+            }
+            if len > FRAME_SIZE_MAXIMUM {
+                return Err(too_large("Response frame"));
+            }
+            if items.len() == MAX_BATCH_SIZE {
+                return Err(too_large("Batch size"));
+            }
+            let mut frame = vec![0u8; len as usize];
+            io.read_exact(&mut frame).await?;
+            items.push(decode(&frame)?);
+        }
+    }
+
+    async fn write_request<T>(
+        &mut self,
+        _: &StreamProtocol,
+        io: &mut T,
+        req: Self::Request,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        if req.items.len() > MAX_BATCH_SIZE {
+            return Err(too_large("Batch size"));
+        }
+        io.write_all(&encode(&req)?).await
+    }
+
+    async fn write_response<T>(
+        &mut self,
+        _: &StreamProtocol,
+        io: &mut T,
+        res: Self::Response,
+    ) -> io::Result<()>
+    where
+        T: AsyncWrite + Unpin + Send,
+    {
+        match res {
+            BatchReply::Complete(batch) => {
+                for item in &batch.items {
+                    write_frame(io, item).await?;
+                }
+            }
+            BatchReply::Streaming(mut items) => {
+                let mut count = 0;
+                while let Some(item) = items.next().await {
+                    count += 1;
+                    if count > MAX_BATCH_SIZE {
+                        return Err(too_large("Batch size"));
+                    }
+                    write_frame(io, &item).await?;
+                    io.flush().await?;
+                }
+            }
+        }
+        io.write_all(&0u32.to_be_bytes()).await
+    }
+}
+
+/// Writes one length-prefixed response frame.
+async fn write_frame<T>(io: &mut T, item: &ProofResponse) -> io::Result<()>
+where
+    T: AsyncWrite + Unpin + Send,
+{
+    let frame = encode(item)?;
+    let len = u32::try_from(frame.len())
+        .ok()
+        .filter(|len| *len <= FRAME_SIZE_MAXIMUM)
+        .ok_or_else(|| too_large("Response frame"))?;
+    io.write_all(&len.to_be_bytes()).await?;
+    io.write_all(&frame).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::io::Cursor;
+    use libp2p::request_response::Codec;
+
+    #[tokio::test]
+    async fn test_codec_roundtrip() {
+        let protocol = StreamProtocol::new(crate::protocol::BATCH_PROTOCOL_NAME);
+        let mut codec = BatchCodec;
+
+        let request = BatchProofRequest::new([("Tf", 10u64), ("Dep", 11)]);
+        let mut wire = Cursor::new(Vec::new());
+        codec
+            .write_request(&protocol, &mut wire, request.clone())
+            .await
+            .unwrap();
+        wire.set_position(0);
+        assert_eq!(
+            codec.read_request(&protocol, &mut wire).await.unwrap(),
+            request
+        );
+
+        let response = BatchProofResponse {
+            items: vec![
+                ProofResponse::success(vec![1, 2], [0xAA; 32], 1),
+                ProofResponse::error("Symbol not found"),
+            ],
+        };
+        let mut wire = Cursor::new(Vec::new());
+        codec
+            .write_response(&protocol, &mut wire, BatchReply::Complete(response.clone()))
+            .await
+            .unwrap();
+        wire.set_position(0);
+        let BatchReply::Complete(read) = codec.read_response(&protocol, &mut wire).await.unwrap()
+        else {
+            panic!("expected a complete reply");
+        };
+        assert_eq!(read, response);
+
+        // A streamed reply reads back the same
+        let (mut tx, rx) = mpsc::channel(MAX_BATCH_SIZE);
+        for item in &response.items {
+            tx.try_send(item.clone()).unwrap();
+        }
+        drop(tx);
+        let mut streamed = Cursor::new(Vec::new());
+        codec
+            .write_response(&protocol, &mut streamed, BatchReply::Streaming(rx))
+            .await
+            .unwrap();
+        assert_eq!(streamed.into_inner(), wire.into_inner());
+    }
+
+    #[tokio::test]
+    async fn test_rejects_oversized_batches() {
+        let protocol = StreamProtocol::new(crate::protocol::BATCH_PROTOCOL_NAME);
+        let mut codec = BatchCodec;
+        let request = BatchProofRequest::new((0..=MAX_BATCH_SIZE as u64).map(|n| ("Tf", n)));
+
+        let mut wire = Cursor::new(Vec::new());
+        assert!(codec
+            .write_request(&protocol, &mut wire, request.clone())
+            .await
+            .is_err());
+
+        // A hostile client bypassing the check is refused on read
+        let mut wire = Cursor::new(encode(&request).unwrap());
+        assert!(codec.read_request(&protocol, &mut wire).await.is_err());
+
+        // A truncated response stream is an error, not a short batch
+        let mut wire = Cursor::new(vec![0, 0, 0, 9, 1, 2]);
+        assert!(codec.read_response(&protocol, &mut wire).await.is_err());
+    }
+}
@@ -1,8 +1,16 @@
 //! Libp2p network behavior combining identify and request-response.
 
+use libp2p::allow_block_list::{self, AllowedPeers, BlockedPeers};
+use libp2p::connection_limits::{self, ConnectionLimits};
+use libp2p::swarm::behaviour::toggle::Toggle;
 use libp2p::{gossipsub, identify, request_response, swarm::NetworkBehaviour, StreamProtocol};
+use sha2::{Digest, Sha256};
 
-use crate::protocol::{ProofRequest, ProofResponse};
+use crate::batch::{BatchCodec, BatchProofRequest, BatchReply};
+use crate::capabilities::PeerInfo;
+use crate::config::NetworkConfig;
+use crate::ip_limits;
+use crate::protocol::{ProofRequest, ProofResponse, BATCH_PROTOCOL_NAME};
 
 /// Protocol identifier for SODS.
 pub fn sods_protocol() -> StreamProtocol {
@@ -11,7 +19,12 @@ pub fn sods_protocol() -> StreamProtocol {
 
 /// Protocol identifier for SODS puzzles.
 pub fn puzzle_protocol() -> StreamProtocol {
-    StreamProtocol::new("/sods/puzzle/1.0.0")
+    StreamProtocol::new(crate::protocol::PUZZLE_PROTOCOL_NAME)
+}
+
+/// Protocol identifier for batched proof requests.
+pub fn batch_protocol() -> StreamProtocol {
+    StreamProtocol::new(BATCH_PROTOCOL_NAME)
 }
 
 /// Combined network behavior for SODS P2P.
@@ -27,8 +40,18 @@ pub struct SodsBehaviour {
         crate::protocol::PuzzleChallenge,
         crate::protocol::PuzzleSolution,
     >,
+    /// Request-response for batched proofs
+    pub batch: request_response::Behaviour<BatchCodec>,
     /// Gossipsub for threat intelligence
     pub gossipsub: gossipsub::Behaviour,
+    /// Static allowlist (only active when one is configured)
+    pub allowed_peers: Toggle<allow_block_list::Behaviour<AllowedPeers>>,
+    /// Static denylist
+    pub blocked_peers: allow_block_list::Behaviour<BlockedPeers>,
+    /// Connection caps for the node role
+    pub limits: connection_limits::Behaviour,
+    /// Per-IP cap on inbound connections (only active when configured)
+    pub ip_limits: Toggle<ip_limits::Behaviour>,
 }
 
 /// Events emitted by the SODS behavior.
@@ -42,6 +65,8 @@ pub enum SodsBehaviourEvent {
     Puzzle(
         request_response::Event<crate::protocol::PuzzleChallenge, crate::protocol::PuzzleSolution>,
     ),
+    /// Batched proof event
+    Batch(request_response::Event<BatchProofRequest, BatchReply>),
     /// Gossipsub event
     Gossipsub(gossipsub::Event),
 }
@@ -72,38 +97,53 @@ impl
     }
 }
 
+impl From<request_response::Event<BatchProofRequest, BatchReply>> for SodsBehaviourEvent {
+    fn from(event: request_response::Event<BatchProofRequest, BatchReply>) -> Self {
+        SodsBehaviourEvent::Batch(event)
+    }
+}
+
 impl From<gossipsub::Event> for SodsBehaviourEvent {
     fn from(event: gossipsub::Event) -> Self {
         SodsBehaviourEvent::Gossipsub(event)
     }
 }
 
+// Access lists never emit events
+impl From<void::Void> for SodsBehaviourEvent {
+    fn from(event: void::Void) -> Self {
+        void::unreachable(event)
+    }
+}
+
 impl SodsBehaviour {
     /// Create a new SODS behavior with the given keypair.
     pub fn new(keypair: &libp2p::identity::Keypair) -> Self {
+        Self::with_peer_info(keypair, &PeerInfo::local())
+    }
+
+    /// Create a new SODS behavior advertising `info` via identify.
+    pub fn with_peer_info(keypair: &libp2p::identity::Keypair, info: &PeerInfo) -> Self {
         // Identify config
         let identify = identify::Behaviour::new(
             identify::Config::new("/sods/1.0.0".to_string(), keypair.public())
-                .with_agent_version("sods/0.2.0".to_string()),
+                .with_agent_version(info.to_string()),
         );
 
-        // Request-response config using CBOR codec
-        let request_response = request_response::cbor::Behaviour::new(
-            [(sods_protocol(), request_response::ProtocolSupport::Full)],
-            request_response::Config::default(),
-        );
+        let support = request_response::ProtocolSupport::Full;
+        let (request_response, puzzle, batch) = request_protocols(support);
 
-        // Puzzle config
-        let puzzle = request_response::cbor::Behaviour::new(
-            [(puzzle_protocol(), request_response::ProtocolSupport::Full)],
-            request_response::Config::default(),
-        );
-
-        // Gossipsub config
+        // Gossipsub config. Message ids are content hashes so a republished
+        // rule is recognised regardless of who sends it, and messages are
+        // only forwarded once the application has validated them.
         let message_authenticity = gossipsub::MessageAuthenticity::Signed(keypair.clone());
         let gossipsub_config = gossipsub::ConfigBuilder::default()
             .heartbeat_interval(std::time::Duration::from_secs(1))
             .validation_mode(gossipsub::ValidationMode::Strict)
+            .message_id_fn(|message: &gossipsub::Message| {
+                gossipsub::MessageId::from(hex::encode(Sha256::digest(&message.data)))
+            })
+            .validate_messages()
             .build()
             .expect("Valid config");
 
@@ -114,7 +154,79 @@ impl SodsBehaviour {
             identify,
             request_response,
             puzzle,
+            batch,
             gossipsub,
+            allowed_peers: Toggle::from(None),
+            blocked_peers: allow_block_list::Behaviour::default(),
+            limits: connection_limits::Behaviour::new(ConnectionLimits::default()),
+            ip_limits: Toggle::from(None),
+        }
+    }
+
+    /// Applies the allow- and denylist, the role and the connection caps
+    /// of `config` (Builder pattern).
+    pub fn with_network_config(mut self, config: &NetworkConfig) -> Self {
+        self.limits = connection_limits::Behaviour::new(config.connection_limits());
+        self.ip_limits = Toggle::from(
+            config
+                .connection_caps
+                .max_per_ip
+                .map(ip_limits::Behaviour::new),
+        );
+        if !config.role.serves_proofs() {
+            let support = request_response::ProtocolSupport::Outbound;
+            (self.request_response, self.puzzle, self.batch) = request_protocols(support);
+        }
+        if let Some(allowed) = &config.allowlist {
+            let mut list = allow_block_list::Behaviour::<AllowedPeers>::default();
+            for peer in allowed {
+                list.allow_peer(*peer);
+            }
+            self.allowed_peers = Toggle::from(Some(list));
+        }
+        for peer in &config.denylist {
+            self.blocked_peers.block_peer(*peer);
         }
+        self
     }
 }
+
+type ProofExchange = request_response::cbor::Behaviour<ProofRequest, ProofResponse>;
+type PuzzleExchange = request_response::cbor::Behaviour<
+    crate::protocol::PuzzleChallenge,
+    crate::protocol::PuzzleSolution,
+>;
+
+/// Builds the proof, puzzle and batch protocols with the given support.
+///
+/// `Outbound` support means requests can be sent but inbound ones are not
+/// accepted, which keeps a query-only node from serving anything.
+fn request_protocols(
+    support: request_response::ProtocolSupport,
+) -> (
+    ProofExchange,
+    PuzzleExchange,
+    request_response::Behaviour<BatchCodec>,
+) {
+    // Request-response config using CBOR codec
+    let proofs = request_response::cbor::Behaviour::new(
+        [(sods_protocol(), support.clone())],
+        request_response::Config::default(),
+    );
+
+    // Puzzle config
+    let puzzle = request_response::cbor::Behaviour::new(
+        [(puzzle_protocol(), support.clone())],
+        request_response::Config::default(),
+    );
+
+    // Batch config; a batch takes longer to serve than a single proof
+    let batch = request_response::Behaviour::with_codec(
+        BatchCodec,
+        [(batch_protocol(), support)],
+        request_response::Config::default()
+            .with_request_timeout(std::time::Duration::from_secs(60)),
+    );
+
+    (proofs, puzzle, batch)
+}
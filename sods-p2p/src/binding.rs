@@ -0,0 +1,114 @@
+//! Binding between a peer's libp2p identity and its proof signing key.
+//!
+//! Proof responses are signed with a secp256k1 key so they can be checked
+//! on-chain, while connections are authenticated (via Noise) with the
+//! peer's libp2p key. A `SignerBinding` is the libp2p key's signature over
+//! the secp256k1 public key; a peer publishes it in its identify agent
+//! string, and a client only accepts responses signed by the key bound to
+//! the peer it is talking to. The binding carries the libp2p public key,
+//! so it can be re-verified offline, e.g. from a consensus transcript.
+
+use k256::ecdsa::SigningKey;
+use libp2p::identity::{Keypair, PublicKey};
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, SodsP2pError};
+
+/// Domain separator for the signed binding message.
+const BINDING_DOMAIN: &[u8] = b"sods-signer-binding/v1";
+
+/// A libp2p identity vouching for a secp256k1 proof signing key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerBinding {
+    /// Compressed secp256k1 public key (33 bytes)
+    pub signer: Vec<u8>,
+    /// Protobuf-encoded libp2p public key
+    pub identity: Vec<u8>,
+    /// Signature of the libp2p key over the domain-separated signer key
+    pub signature: Vec<u8>,
+}
+
+fn binding_message(signer: &[u8]) -> Vec<u8> {
+    [BINDING_DOMAIN, signer].concat()
+}
+
+impl SignerBinding {
+    /// Binds `signing_key` to the identity `keypair`.
+    pub fn new(keypair: &Keypair, signing_key: &SigningKey) -> Result<Self> {
+        let signer = signing_key
+            .verifying_key()
+            .to_encoded_point(true)
+            .as_bytes()
+            .to_vec();
+        let signature = keypair
+            .sign(&binding_message(&signer))
+            .map_err(|e| SodsP2pError::NetworkError(format!("Binding error: {}", e)))?;
+
+        Ok(Self {
+            signer,
+            identity: keypair.public().encode_protobuf(),
+            signature,
+        })
+    }
+
+    /// Whether this binding was issued by `peer` and its signature holds.
+    pub fn verify(&self, peer: &PeerId) -> bool {
+        let Ok(identity) = PublicKey::try_decode_protobuf(&self.identity) else {
+            return false;
+        };
+        identity.to_peer_id() == *peer
+            && identity.verify(&binding_message(&self.signer), &self.signature)
+    }
+
+    /// Compact form for the identify agent string.
+    pub fn encode(&self) -> String {
+        format!(
+            "{}:{}:{}",
+            hex::encode(&self.signer),
+            hex::encode(&self.identity),
+            hex::encode(&self.signature)
+        )
+    }
+
+    /// Parses the form produced by `encode`.
+    pub fn decode(s: &str) -> Option<Self> {
+        let mut parts = s.split(':').map(hex::decode);
+        let binding = Self {
+            signer: parts.next()?.ok()?,
+            identity: parts.next()?.ok()?,
+            signature: parts.next()?.ok()?,
+        };
+        parts.next().is_none().then_some(binding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binding_verifies_only_for_issuer() {
+        let keypair = Keypair::generate_ed25519();
+        let peer = PeerId::from(keypair.public());
+        let signing_key = SigningKey::from_slice(&[9u8; 32]).unwrap();
+
+        let binding = SignerBinding::new(&keypair, &signing_key).unwrap();
+        assert!(binding.verify(&peer));
+        assert_eq!(
+            SignerBinding::decode(&binding.encode()),
+            Some(binding.clone())
+        );
+
+        // Another peer cannot claim the binding
+        assert!(!binding.verify(&PeerId::random()));
+
+        // Nor can the binding be pointed at a different signer
+        let other = SigningKey::from_slice(&[8u8; 32]).unwrap();
+        let forged = SignerBinding {
+            signer: SignerBinding::new(&keypair, &other).unwrap().signer,
+            ..binding
+        };
+        assert!(!forged.verify(&peer));
+    }
+}
@@ -1,13 +1,31 @@
-//! LRU cache for verified blocks and BMT roots.
+//! LRU caches for verified blocks, BMT roots and signed proof responses.
 
 use lru::LruCache;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
 use sods_core::BehavioralSymbol;
+use sods_verifier::ReorgEvent;
+use std::collections::BTreeMap;
 use std::num::NonZeroUsize;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+use crate::protocol::ProofResponse;
 
 /// Default cache capacity (number of blocks).
 const DEFAULT_CAPACITY: usize = 1000;
 
+/// Default proof cache capacity (number of responses).
+const DEFAULT_PROOF_CAPACITY: usize = 10_000;
+
+/// Default lifetime of a cached proof response.
+const DEFAULT_PROOF_TTL: Duration = Duration::from_secs(600);
+
+/// Default lifetime of a cached error response.
+const DEFAULT_ERROR_TTL: Duration = Duration::from_secs(30);
+
+/// Reorgs remembered so a repeated report does not invalidate again.
+const SEEN_REORGS: usize = 64;
+
 /// A cached block with its BMT data.
 #[derive(Debug, Clone)]
 pub struct CachedBlock {
@@ -20,12 +38,22 @@ pub struct CachedBlock {
 }
 
 impl CachedBlock {
-    /// Create a new cached block entry.
+    /// Create a new cached block entry, cached now by the system clock.
     pub fn new(bmt_root: [u8; 32], symbols: Vec<BehavioralSymbol>) -> Self {
+        Self::cached_at(bmt_root, symbols, Instant::now())
+    }
+
+    /// Create a new cached block entry cached at `cached_at`, e.g. a
+    /// `Clock`'s instant.
+    pub fn cached_at(
+        bmt_root: [u8; 32],
+        symbols: Vec<BehavioralSymbol>,
+        cached_at: Instant,
+    ) -> Self {
         Self {
             bmt_root,
             symbols,
-            cached_at: Instant::now(),
+            cached_at,
         }
     }
 
@@ -53,7 +81,7 @@ impl BlockCache {
 
     /// Create a new block cache with specified capacity.
     pub fn with_capacity(capacity: usize) -> Self {
-        let cap = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::new(1).unwrap());
+        let cap = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
         Self {
             cache: LruCache::new(cap),
         }
@@ -83,6 +111,19 @@ impl BlockCache {
     pub fn is_empty(&self) -> bool {
         self.cache.is_empty()
     }
+
+    /// Drop all blocks at or above `block_number` (e.g. after a reorg).
+    pub fn invalidate_from(&mut self, block_number: u64) {
+        let stale: Vec<u64> = self
+            .cache
+            .iter()
+            .map(|(n, _)| *n)
+            .filter(|n| *n >= block_number)
+            .collect();
+        for n in stale {
+            self.cache.pop(&n);
+        }
+    }
 }
 
 impl Default for BlockCache {
@@ -91,6 +132,211 @@ impl Default for BlockCache {
     }
 }
 
+/// Cache key: block number and SHA-256 of the queried symbol or pattern.
+pub type ProofKey = (u64, [u8; 32]);
+
+/// Builds the cache key for a query in a block.
+pub fn proof_key(block_number: u64, query: &str) -> ProofKey {
+    (block_number, Sha256::digest(query.as_bytes()).into())
+}
+
+/// Counters exposed by `ProofCache::stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ProofCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries dropped because their TTL ran out
+    pub expired: u64,
+    /// Entries dropped to make room for newer ones
+    pub evicted: u64,
+    /// Entries dropped because their block was reorganized
+    pub invalidated: u64,
+    /// Reorgs detected from conflicting BMT roots
+    pub reorgs: u64,
+    /// Entries currently cached
+    pub entries: usize,
+}
+
+impl ProofCacheStats {
+    /// Fraction of lookups served from the cache.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CachedProof {
+    response: ProofResponse,
+    cached_at: Instant,
+}
+
+/// LRU cache of signed proof responses with a TTL.
+///
+/// A response for a block is only valid while that block stays canonical.
+/// The cache remembers the BMT root each block was served with; a fresh
+/// response carrying a different root means the chain reorganized, and
+/// every entry from that block onwards is dropped. Reorgs the verifier's
+/// `ReorgTracker` reports are applied through `observe_reorgs`.
+///
+/// Error responses are cached too, for a shorter `error_ttl`, so a peer
+/// repeating a failing query does not cost an RPC round trip each time.
+pub struct ProofCache {
+    cache: LruCache<ProofKey, CachedProof>,
+    roots: BTreeMap<u64, [u8; 32]>,
+    seen_reorgs: Vec<ReorgEvent>,
+    ttl: Duration,
+    error_ttl: Duration,
+    stats: ProofCacheStats,
+}
+
+impl ProofCache {
+    /// Create a proof cache with default capacity and TTL.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_PROOF_CAPACITY, DEFAULT_PROOF_TTL)
+    }
+
+    /// Create a proof cache holding up to `capacity` responses for `ttl`.
+    pub fn with_capacity(capacity: usize, ttl: Duration) -> Self {
+        let cap = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Self {
+            cache: LruCache::new(cap),
+            roots: BTreeMap::new(),
+            seen_reorgs: Vec::new(),
+            ttl,
+            error_ttl: DEFAULT_ERROR_TTL.min(ttl),
+            stats: ProofCacheStats::default(),
+        }
+    }
+
+    /// How long error responses are served from the cache (Builder pattern).
+    pub fn with_error_ttl(mut self, error_ttl: Duration) -> Self {
+        self.error_ttl = error_ttl;
+        self
+    }
+
+    /// Look up a response, dropping it if it has expired at `now`.
+    pub fn get(&mut self, key: &ProofKey, now: Instant) -> Option<ProofResponse> {
+        let fresh = self.cache.get(key).map(|entry| {
+            let ttl = if entry.response.success {
+                self.ttl
+            } else {
+                self.error_ttl
+            };
+            now.saturating_duration_since(entry.cached_at) < ttl
+        });
+
+        match fresh {
+            Some(true) => {
+                self.stats.hits += 1;
+                self.cache.get(key).map(|entry| entry.response.clone())
+            }
+            Some(false) => {
+                self.cache.pop(key);
+                self.stats.expired += 1;
+                self.stats.misses += 1;
+                None
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Cache a response. Only successful ones carry a BMT root to check
+    /// for reorgs.
+    pub fn insert(&mut self, key: ProofKey, response: ProofResponse, now: Instant) {
+        let (block_number, _) = key;
+        if !response.success {
+            self.push(key, response, now);
+            return;
+        }
+
+        if let Some(root) = self.roots.get(&block_number) {
+            if *root != response.bmt_root {
+                self.stats.reorgs += 1;
+                self.invalidate_from(block_number);
+            }
+        }
+        self.roots.insert(block_number, response.bmt_root);
+        // Keep the root index bounded; forgetting old roots only means a
+        // reorg that deep goes unnoticed until the entries expire.
+        while self.roots.len() > self.cache.cap().get() {
+            self.roots.pop_first();
+        }
+        self.push(key, response, now);
+    }
+
+    fn push(&mut self, key: ProofKey, response: ProofResponse, now: Instant) {
+        let entry = CachedProof {
+            response,
+            cached_at: now,
+        };
+        if let Some((evicted, _)) = self.cache.push(key, entry) {
+            if evicted != key {
+                self.stats.evicted += 1;
+            }
+        }
+    }
+
+    /// Drop every response for `block_number` and later blocks.
+    pub fn invalidate_from(&mut self, block_number: u64) {
+        let stale: Vec<ProofKey> = self
+            .cache
+            .iter()
+            .map(|(key, _)| *key)
+            .filter(|(n, _)| *n >= block_number)
+            .collect();
+        for key in &stale {
+            self.cache.pop(key);
+        }
+        self.stats.invalidated += stale.len() as u64;
+        self.roots.split_off(&block_number);
+    }
+
+    /// Apply the reorgs in a verifier's `ReorgContext::history`, dropping
+    /// everything from the lowest replaced height of each new one.
+    ///
+    /// Returns the lowest height invalidated, if any reorg was new.
+    pub fn observe_reorgs(&mut self, history: &[ReorgEvent]) -> Option<u64> {
+        let mut lowest: Option<u64> = None;
+        for event in history {
+            if self.seen_reorgs.contains(event) {
+                continue;
+            }
+            self.seen_reorgs.push(*event);
+            let from = (event.height + 1).saturating_sub(event.depth.max(1));
+            self.stats.reorgs += 1;
+            self.invalidate_from(from);
+            lowest = Some(lowest.map_or(from, |l| l.min(from)));
+        }
+        if self.seen_reorgs.len() > SEEN_REORGS {
+            let excess = self.seen_reorgs.len() - SEEN_REORGS;
+            self.seen_reorgs.drain(..excess);
+        }
+        lowest
+    }
+
+    /// Current counters.
+    pub fn stats(&self) -> ProofCacheStats {
+        ProofCacheStats {
+            entries: self.cache.len(),
+            ..self.stats
+        }
+    }
+}
+
+impl Default for ProofCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,6 +366,88 @@ mod tests {
         assert!(cache.contains(3));
     }
 
+    fn response(root: u8) -> ProofResponse {
+        ProofResponse::success(vec![1, 2, 3], [root; 32], 1)
+    }
+
+    #[test]
+    fn test_proof_cache_ttl_and_stats() {
+        let mut cache = ProofCache::with_capacity(2, Duration::from_secs(60));
+        let now = Instant::now();
+        let key = proof_key(100, "Tf");
+
+        assert!(cache.get(&key, now).is_none());
+        cache.insert(key, response(1), now);
+        cache.insert(proof_key(100, "Dep"), ProofResponse::error("RPC down"), now);
+        assert_eq!(cache.get(&key, now).map(|r| r.bmt_root), Some([1; 32]));
+
+        // Failed responses are cached briefly
+        assert!(cache
+            .get(&proof_key(100, "Dep"), now)
+            .is_some_and(|r| !r.success));
+        assert!(cache
+            .get(&proof_key(100, "Dep"), now + DEFAULT_ERROR_TTL)
+            .is_none());
+
+        let later = now + Duration::from_secs(60);
+        assert!(cache.get(&key, later).is_none());
+
+        cache.insert(proof_key(1, "Tf"), response(1), now);
+        cache.insert(proof_key(2, "Tf"), response(2), now);
+        cache.insert(proof_key(3, "Tf"), response(3), now);
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.expired), (2, 3, 2));
+        assert_eq!((stats.evicted, stats.entries), (1, 2));
+        assert!((stats.hit_rate() - 0.4).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_proof_cache_reorg_invalidation() {
+        let mut cache = ProofCache::new();
+        let now = Instant::now();
+
+        cache.insert(proof_key(10, "Tf"), response(1), now);
+        cache.insert(proof_key(11, "Tf"), response(1), now);
+        cache.insert(proof_key(9, "Tf"), response(1), now);
+
+        // Same block, different root: block 10 was reorganized
+        cache.insert(proof_key(10, "Dep"), response(2), now);
+
+        assert!(cache.get(&proof_key(10, "Tf"), now).is_none());
+        assert!(cache.get(&proof_key(11, "Tf"), now).is_none());
+        assert!(cache.get(&proof_key(9, "Tf"), now).is_some());
+        assert!(cache.get(&proof_key(10, "Dep"), now).is_some());
+
+        let stats = cache.stats();
+        assert_eq!((stats.reorgs, stats.invalidated), (1, 2));
+    }
+
+    #[test]
+    fn test_proof_cache_observes_tracked_reorgs() {
+        let mut cache = ProofCache::new();
+        let now = Instant::now();
+        for block in 8..=12 {
+            cache.insert(proof_key(block, "Tf"), response(1), now);
+        }
+
+        // Blocks 10 and 11 were replaced
+        let history = [ReorgEvent {
+            height: 11,
+            depth: 2,
+        }];
+        assert_eq!(cache.observe_reorgs(&history), Some(10));
+        assert!(cache.get(&proof_key(9, "Tf"), now).is_some());
+        assert!(cache.get(&proof_key(10, "Tf"), now).is_none());
+        assert!(cache.get(&proof_key(12, "Tf"), now).is_none());
+
+        // The same history again is not a new reorg
+        cache.insert(proof_key(10, "Tf"), response(2), now);
+        assert_eq!(cache.observe_reorgs(&history), None);
+        assert!(cache.get(&proof_key(10, "Tf"), now).is_some());
+        assert_eq!((cache.stats().reorgs, cache.stats().invalidated), (1, 3));
+    }
+
     #[test]
     fn test_cached_block_has_symbol() {
         let symbols = vec![
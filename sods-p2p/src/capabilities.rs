@@ -0,0 +1,217 @@
+//! Wire protocol versioning and capability negotiation.
+//!
+//! Every node advertises its wire version and optional features in the
+//! identify agent string, e.g. `sods/0.2.0-beta (wire=3; caps=ranges)`.
+//! Nodes from before this scheme send a bare `sods/<version>` and are
+//! treated as wire version 1 without optional features. A client only
+//! talks to peers within its supported wire range and only uses a feature
+//! (such as batched requests) when both sides advertise it, so codecs can
+//! evolve without splitting the network.
+//!
+//! Serving peers also append their `SignerBinding` (`signer=...`), which
+//! clients need to accept the peer's signed proof responses.
+
+use std::fmt;
+
+use crate::binding::SignerBinding;
+
+/// Wire version spoken by this build.
+///
+/// Version 3 added signer bindings; responses from older peers are not
+/// accepted because their signatures are not tied to their identity.
+pub const WIRE_VERSION: u32 = 3;
+
+/// Oldest wire version this build still talks to.
+///
+/// Peers below version 3 cannot bind their signing key, so every response
+/// they sent would be rejected; they are skipped at negotiation instead of
+/// being queried and penalized.
+pub const MIN_WIRE_VERSION: u32 = 3;
+
+const AGENT_PREFIX: &str = "sods/";
+
+/// Optional protocol features.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Serves proofs for behavioral patterns, not only single symbols, up
+    /// to the limits of `protocol::check_remote_pattern`
+    pub patterns: bool,
+    /// Serves batched multi-block requests (`/sods/proof-batch`)
+    pub ranges: bool,
+    /// Accepts compressed payloads
+    pub compression: bool,
+}
+
+impl Capabilities {
+    /// Features implemented by this build.
+    pub fn local() -> Self {
+        Self {
+            patterns: true,
+            ranges: true,
+            compression: false,
+        }
+    }
+
+    /// Features both sides support.
+    pub fn intersect(&self, other: &Self) -> Self {
+        Self {
+            patterns: self.patterns && other.patterns,
+            ranges: self.ranges && other.ranges,
+            compression: self.compression && other.compression,
+        }
+    }
+
+    fn names(&self) -> Vec<&'static str> {
+        [
+            (self.patterns, "patterns"),
+            (self.ranges, "ranges"),
+            (self.compression, "compression"),
+        ]
+        .into_iter()
+        .filter_map(|(on, name)| on.then_some(name))
+        .collect()
+    }
+}
+
+/// What a node advertises about itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerInfo {
+    /// Software version (e.g. `0.2.0-beta`)
+    pub software: String,
+    pub wire_version: u32,
+    pub capabilities: Capabilities,
+    /// Proof signing key binding (serving peers only)
+    pub signer: Option<SignerBinding>,
+}
+
+impl PeerInfo {
+    /// Info for this build.
+    pub fn local() -> Self {
+        Self {
+            software: env!("CARGO_PKG_VERSION").to_string(),
+            wire_version: WIRE_VERSION,
+            capabilities: Capabilities::local(),
+            signer: None,
+        }
+    }
+
+    /// Advertises the proof signing key binding (Builder pattern).
+    pub fn with_signer(mut self, binding: SignerBinding) -> Self {
+        self.signer = Some(binding);
+        self
+    }
+
+    /// Parses an identify agent string; `None` if it is not a SODS node.
+    pub fn parse(agent: &str) -> Option<Self> {
+        let rest = agent.strip_prefix(AGENT_PREFIX)?;
+        let (software, params) = match rest.split_once(" (") {
+            Some((software, params)) => (software, params.strip_suffix(')')?),
+            None => (rest, ""),
+        };
+
+        let mut info = Self {
+            software: software.trim().to_string(),
+            wire_version: 1,
+            capabilities: Capabilities::default(),
+            signer: None,
+        };
+        for param in params.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            match param.split_once('=') {
+                Some(("wire", v)) => info.wire_version = v.parse().ok()?,
+                Some(("caps", caps)) => {
+                    for cap in caps.split(',') {
+                        // Unknown capabilities come from newer nodes; ignore them
+                        match cap.trim() {
+                            "patterns" => info.capabilities.patterns = true,
+                            "ranges" => info.capabilities.ranges = true,
+                            "compression" => info.capabilities.compression = true,
+                            _ => {}
+                        }
+                    }
+                }
+                Some(("signer", binding)) => info.signer = SignerBinding::decode(binding),
+                _ => {}
+            }
+        }
+        Some(info)
+    }
+
+    /// Features usable with `remote`, or `None` if the wire versions are
+    /// incompatible.
+    ///
+    /// Newer peers are expected to keep serving older clients, so only the
+    /// lower bound is checked on each side.
+    pub fn negotiate(&self, remote: &PeerInfo) -> Option<Capabilities> {
+        if remote.wire_version < MIN_WIRE_VERSION {
+            return None;
+        }
+        Some(self.capabilities.intersect(&remote.capabilities))
+    }
+}
+
+/// Renders the identify agent string.
+impl fmt::Display for PeerInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{} (wire={}; caps={}",
+            AGENT_PREFIX,
+            self.software,
+            self.wire_version,
+            self.capabilities.names().join(",")
+        )?;
+        if let Some(binding) = &self.signer {
+            write!(f, "; signer={}", binding.encode())?;
+        }
+        f.write_str(")")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_string_roundtrip() {
+        let local = PeerInfo::local();
+        assert_eq!(PeerInfo::parse(&local.to_string()), Some(local.clone()));
+
+        let keypair = libp2p::identity::Keypair::generate_ed25519();
+        let key = k256::ecdsa::SigningKey::from_slice(&[4u8; 32]).unwrap();
+        let serving = local.with_signer(SignerBinding::new(&keypair, &key).unwrap());
+        assert_eq!(PeerInfo::parse(&serving.to_string()), Some(serving));
+
+        // Nodes predating capability negotiation
+        let legacy = PeerInfo::parse("sods/0.2.0").unwrap();
+        assert_eq!(legacy.wire_version, 1);
+        assert_eq!(legacy.capabilities, Capabilities::default());
+
+        // Features from the future are ignored, not rejected
+        let newer = PeerInfo::parse("sods/0.9.0 (wire=5; caps=ranges,quantum)").unwrap();
+        assert_eq!(newer.wire_version, 5);
+        assert!(newer.capabilities.ranges);
+
+        assert!(PeerInfo::parse("rust-libp2p/0.54").is_none());
+        assert!(PeerInfo::parse("sods/0.3.0 (wire=x)").is_none());
+    }
+
+    #[test]
+    fn test_negotiation() {
+        let local = PeerInfo::local();
+
+        let legacy = PeerInfo::parse("sods/0.2.0").unwrap();
+        assert_eq!(local.negotiate(&legacy), None);
+
+        let peer = PeerInfo::parse("sods/0.3.0 (wire=3; caps=ranges,compression)").unwrap();
+        let agreed = local.negotiate(&peer).unwrap();
+        assert!(agreed.ranges);
+        assert!(!agreed.compression);
+
+        // Wire version 2 predates signer bindings
+        let unbound = PeerInfo {
+            wire_version: 2,
+            ..peer
+        };
+        assert_eq!(local.negotiate(&unbound), None);
+    }
+}
@@ -10,17 +10,31 @@ use libp2p::{
     Multiaddr, PeerId,
 };
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::timeout;
 use tracing::{debug, info, warn};
 
-use sods_verifier::BlockVerifier;
+use sods_core::clock::SharedClock;
+use sods_core::ErrorCode;
+use sods_verifier::{BlockVerifier, SymbolVerifier};
 
+use crate::batch::{BatchProofRequest, BatchProofResponse, BatchReply, MAX_BATCH_SIZE};
 use crate::behavior::{SodsBehaviour, SodsBehaviourEvent};
-use crate::consensus::{evaluate_consensus, required_quorum, DEFAULT_THRESHOLD};
+use crate::binding::SignerBinding;
+use crate::capabilities::{Capabilities, PeerInfo};
+use crate::config::{build_swarm, NetworkConfig};
+use crate::consensus::{
+    describe_split, evaluate_consensus, required_quorum, ConsensusResult, DEFAULT_THRESHOLD,
+};
 use crate::error::{Result, SodsP2pError};
-use crate::protocol::{ProofRequest, ProofResponse};
+use crate::metrics::SwarmMetrics;
+use crate::protocol::{
+    is_pattern_query, verify_query, ProofRequest, ProofResponse, PuzzleChallenge, Stake,
+};
 use crate::reputation::ReputationTracker;
+use crate::transcript::{ConsensusTranscript, Decision, DecisionSource, PeerOutcome};
 
 /// Number of peers to query for consensus.
 const QUERY_PEER_COUNT: usize = 3;
@@ -28,6 +42,9 @@ const QUERY_PEER_COUNT: usize = 3;
 /// Timeout for P2P requests.
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Timeout for batched P2P requests.
+const BATCH_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Discovery timeout before querying.
 const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(5);
 
@@ -48,62 +65,143 @@ pub struct P2pVerificationResult {
     pub symbol: String,
     /// Block number that was queried.
     pub block_number: u64,
+    /// Consensus transcript written for this verification, if enabled.
+    pub transcript_path: Option<PathBuf>,
 }
 
 /// A SODS client that requests proofs via P2P.
 pub struct SodsClient {
     swarm: Swarm<SodsBehaviour>,
     reputation: ReputationTracker,
-    fallback_verifier: Option<BlockVerifier>,
+    fallback_verifier: Option<Arc<dyn SymbolVerifier>>,
     known_peers: HashSet<PeerId>,
     local_peer_id: PeerId,
     pending_requests: HashMap<OutboundRequestId, PeerId>,
+    /// Batch requests in flight; ids are only unique per protocol, so they
+    /// are kept apart from `pending_requests`
+    pending_batches: HashMap<OutboundRequestId, PeerId>,
     pending_challenges: HashMap<OutboundRequestId, (PeerId, crate::protocol::BehavioralPuzzle)>,
     slashed_peers: HashSet<PeerId>,
+    /// Features negotiated with each known peer
+    peer_capabilities: HashMap<PeerId, Capabilities>,
+    /// Verified proof signing key bindings of known peers
+    peer_signers: HashMap<PeerId, SignerBinding>,
+    transcript_dir: Option<PathBuf>,
+    last_transcript: Option<ConsensusTranscript>,
+    last_transcript_path: Option<PathBuf>,
+    /// Where to accept inbound connections (none for query-only)
+    listen_addr: Option<Multiaddr>,
+    /// Sizes the puzzles new peers must solve
+    stake: Stake,
+    metrics: Option<SwarmMetrics>,
+    /// Times puzzle expiry
+    clock: SharedClock,
 }
 
 impl SodsClient {
     /// Create a new SODS client (P2P only).
     pub fn new() -> Result<Self> {
-        Self::build(None)
+        Self::build(None, &NetworkConfig::default())
+    }
+
+    /// Create a new SODS client restricted by `config` (P2P only).
+    pub fn with_network_config(config: &NetworkConfig) -> Result<Self> {
+        Self::build(None, config)
     }
 
     /// Create a new SODS client with RPC fallback.
     pub fn with_fallback(rpc_url: &str) -> Result<Self> {
         let verifier = BlockVerifier::new(&[rpc_url.to_string()])?;
-        Self::build(Some(verifier))
+        Self::build(Some(Arc::new(verifier)), &NetworkConfig::default())
+    }
+
+    /// Create a new SODS client restricted by `config` that falls back to
+    /// `verifier`, e.g. a fake chain in tests.
+    pub fn with_fallback_verifier(
+        verifier: Arc<dyn SymbolVerifier>,
+        config: &NetworkConfig,
+    ) -> Result<Self> {
+        Self::build(Some(verifier), config)
     }
 
-    fn build(fallback_verifier: Option<BlockVerifier>) -> Result<Self> {
+    fn build(
+        fallback_verifier: Option<Arc<dyn SymbolVerifier>>,
+        config: &NetworkConfig,
+    ) -> Result<Self> {
         let keypair = Keypair::generate_ed25519();
         let local_peer_id = PeerId::from(keypair.public());
 
-        let swarm = libp2p::SwarmBuilder::with_existing_identity(keypair.clone())
-            .with_tokio()
-            .with_tcp(
-                libp2p::tcp::Config::default(),
-                libp2p::noise::Config::new,
-                libp2p::yamux::Config::default,
-            )
-            .map_err(|e| SodsP2pError::NetworkError(format!("TCP error: {}", e)))?
-            .with_behaviour(|_key| SodsBehaviour::new(&keypair))
-            .map_err(|e| SodsP2pError::NetworkError(format!("Behaviour error: {}", e)))?
-            .build();
+        let swarm = build_swarm(keypair, config, &PeerInfo::local())?;
 
         info!("Created SODS client with ID: {}", local_peer_id);
 
         Ok(Self {
             swarm,
-            reputation: ReputationTracker::new(),
+            reputation: config
+                .reputation
+                .clone()
+                .unwrap_or_default()
+                .with_clock(config.clock()),
             fallback_verifier,
             known_peers: HashSet::new(),
             local_peer_id,
             pending_requests: HashMap::new(),
+            pending_batches: HashMap::new(),
             pending_challenges: HashMap::new(),
             slashed_peers: HashSet::new(),
+            peer_capabilities: HashMap::new(),
+            peer_signers: HashMap::new(),
+            transcript_dir: None,
+            last_transcript: None,
+            last_transcript_path: None,
+            listen_addr: config.role.listens().then(|| config.any_listen_addr()),
+            stake: Stake::default(),
+            metrics: config.metrics.as_ref().map(|m| m.recorder(&local_peer_id)),
+            clock: config.clock(),
         })
     }
 
+    /// The next swarm event, recorded in the metrics.
+    async fn next_event(&mut self) -> SwarmEvent<SodsBehaviourEvent> {
+        let event = self.swarm.select_next_some().await;
+        if let Some(metrics) = &self.metrics {
+            metrics.record(&event);
+        }
+        event
+    }
+
+    /// Challenge new peers with puzzles sized for `stake`, so that passing
+    /// as reliable costs more where more is at risk (Builder pattern).
+    pub fn with_stake(mut self, stake: Stake) -> Self {
+        self.stake = stake;
+        self
+    }
+
+    /// Start from `reputation`, e.g. seeded with imported snapshots
+    /// (Builder pattern).
+    pub fn with_reputation(mut self, reputation: ReputationTracker) -> Self {
+        self.reputation = reputation.with_clock(self.clock.clone());
+        self
+    }
+
+    /// Write a consensus transcript for every verification into `dir`
+    /// (Builder pattern).
+    pub fn with_transcript_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.transcript_dir = Some(dir.into());
+        self
+    }
+
+    /// Transcript of the most recent `verify_via_p2p` call.
+    pub fn last_transcript(&self) -> Option<&ConsensusTranscript> {
+        self.last_transcript.as_ref()
+    }
+
+    /// Where the transcript of the most recent `verify_via_p2p` call was
+    /// written, including for a verification that failed.
+    pub fn last_transcript_path(&self) -> Option<&Path> {
+        self.last_transcript_path.as_deref()
+    }
+
     /// Get the local peer ID.
     pub fn peer_id(&self) -> &PeerId {
         &self.local_peer_id
@@ -111,18 +209,25 @@ impl SodsClient {
 
     /// Connect to bootstrap nodes and discover peers.
     pub async fn connect_bootstrap(&mut self, addrs: &[Multiaddr]) -> Result<()> {
-        // Start listening first
-        let listen_addr: Multiaddr = "/ip4/0.0.0.0/tcp/0".parse().unwrap();
-        self.swarm
-            .listen_on(listen_addr)
-            .map_err(|e| SodsP2pError::NetworkError(format!("Listen error: {}", e)))?;
+        // Start listening first, unless query-only
+        if let Some(listen_addr) = self.listen_addr.clone() {
+            self.swarm
+                .listen_on(listen_addr.clone())
+                .map_err(|e| SodsP2pError::ListenFailed {
+                    addr: listen_addr,
+                    reason: e.to_string(),
+                })?;
+        }
 
         // Dial bootstrap nodes
         for addr in addrs {
             info!("Dialing bootstrap node: {}", addr);
             self.swarm
                 .dial(addr.clone())
-                .map_err(|e| SodsP2pError::NetworkError(format!("Dial error: {}", e)))?;
+                .map_err(|e| SodsP2pError::DialFailed {
+                    addr: addr.clone(),
+                    reason: e.to_string(),
+                })?;
         }
 
         // Wait for peer discovery
@@ -135,7 +240,7 @@ impl SodsClient {
                 .count()
                 < QUERY_PEER_COUNT
             {
-                match self.swarm.select_next_some().await {
+                match self.next_event().await {
                     SwarmEvent::Behaviour(SodsBehaviourEvent::Identify(event)) => {
                         self.handle_identify_event(event);
                     }
@@ -171,6 +276,26 @@ impl SodsClient {
         if let identify::Event::Received { peer_id, info, .. } = event {
             if peer_id != self.local_peer_id {
                 debug!("Identified peer: {} ({})", peer_id, info.agent_version);
+                let Some(remote) = PeerInfo::parse(&info.agent_version) else {
+                    debug!("Ignoring non-SODS peer {}", peer_id);
+                    return;
+                };
+                let Some(capabilities) = PeerInfo::local().negotiate(&remote) else {
+                    debug!(
+                        "Ignoring peer {} with incompatible wire version {}",
+                        peer_id, remote.wire_version
+                    );
+                    return;
+                };
+                self.peer_capabilities.insert(peer_id, capabilities);
+                match remote.signer {
+                    Some(binding) if binding.verify(&peer_id) => {
+                        self.peer_signers.insert(peer_id, binding);
+                    }
+                    Some(_) => warn!("Peer {} advertised an invalid signer binding", peer_id),
+                    None => debug!("Peer {} has no signer binding", peer_id),
+                }
+
                 if !self.known_peers.contains(&peer_id) && !self.slashed_peers.contains(&peer_id) {
                     self.known_peers.insert(peer_id);
                     self.issue_challenge(&peer_id);
@@ -186,27 +311,9 @@ impl SodsClient {
     fn issue_challenge(&mut self, peer_id: &PeerId) {
         info!("Issuing PoB challenge to {}", peer_id);
 
-        // Randomize challenge block to prevent pre-computation attacks
-        let mut block_number = 10002800; // Fallback for PoC
-
-        if let Some(_verifier) = &self.fallback_verifier {
-            // In a production environment, we'd fetch the latest block and pick one from the last 100.
-            // For now, we use a simple pseudo-random offset if we have a verifier.
-            let seed = self.local_peer_id.to_bytes();
-            let mut sum: u64 = 0;
-            for b in seed {
-                sum += b as u64;
-            }
-
-            // Try to get a block within the last 1000 blocks relative to a base
-            block_number = 10002000 + (sum % 1000);
-        }
-
-        let challenge = crate::protocol::PuzzleChallenge {
-            chain_id: 11155111, // Sepolia
-            block_number,
-            symbol: "Tf".to_string(),
-        };
+        // Random blocks and a fresh nonce, so solutions cannot be computed
+        // ahead of time or reused
+        let challenge = PuzzleChallenge::for_stake(self.stake);
 
         let request_id = self
             .swarm
@@ -215,7 +322,10 @@ impl SodsClient {
             .send_request(peer_id, challenge.clone());
         self.pending_challenges.insert(
             request_id,
-            (*peer_id, crate::protocol::BehavioralPuzzle::new(challenge)),
+            (
+                *peer_id,
+                crate::protocol::BehavioralPuzzle::issued_at(challenge, self.clock.now()),
+            ),
         );
     }
 
@@ -251,64 +361,232 @@ impl SodsClient {
         puzzle: crate::protocol::BehavioralPuzzle,
         solution: crate::protocol::PuzzleSolution,
     ) {
-        if puzzle.is_expired() {
+        if puzzle.is_expired_at(self.clock.now()) {
             warn!("Puzzle solution received after expiration from {}", peer_id);
             self.reputation.penalize(&peer_id);
             return;
         }
 
         let challenge = puzzle.challenge;
+        // A solution only counts for the peer whose bound key signed it, so
+        // one solver's answer cannot be replayed by other peers
+        let bound = self
+            .peer_signers
+            .get(&peer_id)
+            .is_some_and(|binding| solution.verify(&challenge, &peer_id, &binding.signer));
+        if !bound {
+            warn!(
+                "Peer {} sent a PoB solution not signed for it and this challenge",
+                peer_id
+            );
+            self.reputation.penalize(&peer_id);
+            return;
+        }
+
         let Some(verifier) = &self.fallback_verifier else {
             warn!("No fallback verifier available to verify PoB solution. Assuming malicious.");
             return;
         };
 
-        match verifier
-            .verify_symbol_in_block(&challenge.symbol, challenge.block_number)
-            .await
-        {
-            Ok(result) => {
-                if result.occurrences as u32 == solution.occurrences {
-                    info!(
-                        "✅ Peer {} SOLVED Proof-of-Behavior puzzle. Granting reliability.",
-                        peer_id
-                    );
-                    // Initial Reward to hit MIN_RELIABLE_SCORE (0.4)
-                    // score = min(0.0 * 1.1 + 0.5 = 0.5, 1.0)
-                    let score = self.reputation.get_score(&peer_id); // 0.0
-                    if score < 0.4 {
-                        self.reputation.reward(&peer_id);
-                        self.reputation.reward(&peer_id); // Boost to be sure (0.0 -> 0.05 -> 0.1 ? No, rewarding starts from 0.0)
-                                                          // Actually, Reward logic in reputation.rs: *score = (*score * 1.1 + 0.05).min(1.0);
-                                                          // 0.0 -> 0.05 -> 0.105 -> 0.165 ...
-                                                          // I might need to adjust reputation.rs reward to handle PoB better or call it more times.
-                                                          // Let's call it enough times to hit 0.4.
-                        for _ in 0..10 {
-                            self.reputation.reward(&peer_id);
-                        }
-                    }
-                } else {
-                    warn!(
-                        "❌ Peer {} FAILED Proof-of-Behavior puzzle. Rejected.",
-                        peer_id
-                    );
-                    self.reputation.penalize(&peer_id);
+        let expected = match challenge.count_cells(verifier).await {
+            Ok(expected) => expected,
+            Err(e) => {
+                warn!("Failed to verify PoB solution due to RPC error: {}", e);
+                return;
+            }
+        };
+
+        if solution.success && solution.counts == expected {
+            info!(
+                "✅ Peer {} SOLVED Proof-of-Behavior puzzle. Granting reliability.",
+                peer_id
+            );
+            // A fresh peer starts at 0.0 and each reward computes
+            // `score * 1.1 + 0.05`, so a dozen lift it past
+            // MIN_RELIABLE_SCORE
+            if !self.reputation.is_reliable(&peer_id) {
+                for _ in 0..12 {
+                    self.reputation.reward(&peer_id);
                 }
             }
-            Err(e) => warn!("Failed to verify PoB solution due to RPC error: {}", e),
+        } else {
+            warn!(
+                "❌ Peer {} FAILED Proof-of-Behavior puzzle. Rejected.",
+                peer_id
+            );
+            self.reputation.penalize(&peer_id);
         }
     }
 
-    /// Verify a symbol via P2P network.
+    /// Verify a symbol or pattern via P2P network.
+    ///
+    /// Patterns are only sent to peers with the `patterns` capability.
+    ///
+    /// The decision is recorded in a `ConsensusTranscript`, available via
+    /// `last_transcript` and saved to disk if a transcript directory is set.
     pub async fn verify_via_p2p(
         &mut self,
         symbol: &str,
         block_number: u64,
     ) -> Result<P2pVerificationResult> {
+        let mut transcript = ConsensusTranscript::new(symbol, block_number, DEFAULT_THRESHOLD);
+        let outcome = self
+            .run_verification(symbol, block_number, &mut transcript)
+            .await;
+
+        self.finish(transcript, outcome)
+    }
+
+    /// Records the decision in `transcript`, saves it if enabled and
+    /// attaches its path to the result.
+    fn finish(
+        &mut self,
+        mut transcript: ConsensusTranscript,
+        outcome: Result<(P2pVerificationResult, DecisionSource)>,
+    ) -> Result<P2pVerificationResult> {
+        transcript.decide(match &outcome {
+            Ok((result, source)) => Decision {
+                source: *source,
+                is_verified: result.is_verified,
+                agreeing_peers: result.agreeing_peers,
+                total_peers: result.total_peers,
+                bmt_root: result.bmt_root.map(hex::encode),
+                error: None,
+                error_code: None,
+            },
+            Err(e) => Decision {
+                source: DecisionSource::Failed,
+                is_verified: false,
+                agreeing_peers: 0,
+                total_peers: transcript.entries.len(),
+                bmt_root: None,
+                error: Some(e.to_string()),
+                error_code: Some(e.code().to_string()),
+            },
+        });
+
+        let transcript_path = self.transcript_dir.as_ref().and_then(|dir| {
+            transcript
+                .save(dir)
+                .map_err(|e| warn!("Failed to write consensus transcript: {}", e))
+                .ok()
+        });
+        self.last_transcript = Some(transcript);
+        self.last_transcript_path = transcript_path.clone();
+
+        outcome.map(|(result, _)| P2pVerificationResult {
+            transcript_path,
+            ..result
+        })
+    }
+
+    /// Verify many (symbol, block) pairs via P2P in one round-trip per peer.
+    ///
+    /// Only peers advertising the `ranges` capability are queried. Each
+    /// selected peer receives a single `BatchProofRequest`; consensus
+    /// is then evaluated per item exactly as in `verify_via_p2p`, and each
+    /// item gets its own transcript. Unlike `verify_via_p2p`, local RPC
+    /// verification is only used as a fallback for items the peers could
+    /// not settle, so a range costs one round-trip instead of one RPC call
+    /// per block. Results are returned in query order.
+    pub async fn verify_batch_via_p2p(
+        &mut self,
+        queries: &[(String, u64)],
+    ) -> Vec<Result<P2pVerificationResult>> {
+        let mut results = Vec::with_capacity(queries.len());
+        for chunk in queries.chunks(MAX_BATCH_SIZE) {
+            results.extend(self.verify_chunk(chunk).await);
+        }
+        results
+    }
+
+    /// Features negotiated with `peer`, if it has been identified.
+    pub fn peer_capabilities(&self, peer: &PeerId) -> Option<Capabilities> {
+        self.peer_capabilities.get(peer).copied()
+    }
+
+    async fn verify_chunk(
+        &mut self,
+        queries: &[(String, u64)],
+    ) -> Vec<Result<P2pVerificationResult>> {
+        // Only peers that negotiated range support understand batches
+        let patterns = queries.iter().any(|(query, _)| is_pattern_query(query));
+        let peers_list: Vec<_> = self
+            .known_peers
+            .iter()
+            .filter(|p| {
+                self.peer_capabilities(p)
+                    .is_some_and(|c| c.ranges && (c.patterns || !patterns))
+            })
+            .cloned()
+            .collect();
+        let selected = self
+            .reputation
+            .select_best_peers(&peers_list, QUERY_PEER_COUNT);
+
+        let transcripts: Vec<ConsensusTranscript> = queries
+            .iter()
+            .map(|(symbol, block)| {
+                let mut transcript = ConsensusTranscript::new(symbol, *block, DEFAULT_THRESHOLD);
+                for peer_id in &selected {
+                    transcript.record_query(
+                        peer_id,
+                        self.reputation.get_score(peer_id),
+                        self.peer_signers.get(peer_id),
+                    );
+                }
+                transcript
+            })
+            .collect();
+
+        let request = BatchProofRequest::new(queries.iter().map(|(s, b)| (s.clone(), *b)));
+        for peer_id in &selected {
+            let request_id = self
+                .swarm
+                .behaviour_mut()
+                .batch
+                .send_request(peer_id, request.clone());
+            self.pending_batches.insert(request_id, *peer_id);
+        }
+        let batches = self.collect_batch_responses(selected.len()).await;
+
+        let mut results = Vec::with_capacity(queries.len());
+        for (index, ((symbol, block_number), mut transcript)) in
+            queries.iter().zip(transcripts).enumerate()
+        {
+            // A peer answering with the wrong number of items is ignored
+            let responses: Vec<(PeerId, ProofResponse)> = batches
+                .iter()
+                .filter(|(_, batch)| batch.items.len() == queries.len())
+                .map(|(peer, batch)| (*peer, batch.items[index].clone()))
+                .collect();
+
+            let consensus = if responses.is_empty() {
+                Err(unanswered(
+                    peers_list.len(),
+                    selected.len(),
+                    BATCH_REQUEST_TIMEOUT,
+                ))
+            } else {
+                self.settle(responses, &mut transcript)
+            };
+            let outcome = self.conclude(symbol, *block_number, consensus).await;
+            results.push(self.finish(transcript, outcome));
+        }
+        results
+    }
+
+    /// Runs the verification, recording peer responses in `transcript`.
+    async fn run_verification(
+        &mut self,
+        symbol: &str,
+        block_number: u64,
+        transcript: &mut ConsensusTranscript,
+    ) -> Result<(P2pVerificationResult, DecisionSource)> {
         // --- LOCAL TRUTH SUPREMACY ---
         // If local verification is available and succeeds, we ignore P2P.
         if let Some(verifier) = &self.fallback_verifier {
-            if let Ok(result) = verifier.verify_symbol_in_block(symbol, block_number).await {
+            if let Ok(result) = verify_query(verifier.as_ref(), symbol, block_number).await {
                 if result.is_verified {
                     info!(
                         "Local verification succeeded. Bypassing P2P consensus for symbol '{}'",
@@ -319,7 +597,7 @@ impl SodsClient {
                         arr.copy_from_slice(&v);
                         arr
                     });
-                    return Ok(P2pVerificationResult {
+                    let result = P2pVerificationResult {
                         is_verified: true,
                         agreeing_peers: self.known_peers.len(), // Use all for metrics
                         total_peers: self.known_peers.len(),
@@ -327,23 +605,36 @@ impl SodsClient {
                         used_fallback: true,
                         symbol: symbol.to_string(),
                         block_number,
-                    });
+                        transcript_path: None,
+                    };
+                    return Ok((result, DecisionSource::LocalTruth));
                 }
             }
         }
 
-        if self.known_peers.is_empty() {
-            return self.try_fallback(symbol, block_number).await;
-        }
-
-        // Select peers to query
-        let peers_list: Vec<_> = self.known_peers.iter().cloned().collect();
+        // Select peers to query; only some serve patterns
+        let pattern = is_pattern_query(symbol);
+        let peers_list: Vec<_> = self
+            .known_peers
+            .iter()
+            .filter(|p| !pattern || self.peer_capabilities(p).is_some_and(|c| c.patterns))
+            .cloned()
+            .collect();
         let selected = self
             .reputation
             .select_best_peers(&peers_list, QUERY_PEER_COUNT);
 
         if selected.is_empty() {
-            return self.try_fallback(symbol, block_number).await;
+            let reason = unanswered(peers_list.len(), 0, REQUEST_TIMEOUT);
+            return self.fallback_decision(symbol, block_number, reason).await;
+        }
+
+        for peer_id in &selected {
+            transcript.record_query(
+                peer_id,
+                self.reputation.get_score(peer_id),
+                self.peer_signers.get(peer_id),
+            );
         }
 
         // Send requests
@@ -363,27 +654,55 @@ impl SodsClient {
 
         // Collect responses
         let responses = self.collect_responses(selected.len()).await;
+        let consensus = if responses.is_empty() {
+            Err(unanswered(
+                peers_list.len(),
+                selected.len(),
+                REQUEST_TIMEOUT,
+            ))
+        } else {
+            self.settle(responses, transcript)
+        };
+        self.conclude(symbol, block_number, consensus).await
+    }
 
-        if responses.is_empty() {
-            return self.try_fallback(symbol, block_number).await;
-        }
-
-        // Verify signatures and filter invalid responses
+    /// Checks signatures, evaluates consensus and updates reputation.
+    ///
+    /// Fails if no peer sent a response signed by its bound key, or if
+    /// the valid responses did not reach consensus.
+    fn settle(
+        &mut self,
+        responses: Vec<(PeerId, ProofResponse)>,
+        transcript: &mut ConsensusTranscript,
+    ) -> Result<ConsensusResult> {
+        let received = responses.len();
+        // Verify signatures, and that each response was signed by the key
+        // the responding peer bound to its identity
         let valid_responses: Vec<_> = responses
             .into_iter()
             .filter(|(peer_id, resp)| {
-                if resp.is_signed() && resp.verify_signature() {
-                    true
-                } else {
+                if !(resp.is_signed() && resp.verify_signature()) {
                     warn!("Invalid signature from peer {}", peer_id);
+                    transcript.record_response(peer_id, resp, PeerOutcome::InvalidSignature);
+                    self.reputation.penalize(peer_id);
+                    false
+                } else if self
+                    .peer_signers
+                    .get(peer_id)
+                    .map_or(true, |binding| binding.signer != resp.public_key)
+                {
+                    warn!("Response from {} not signed by its bound key", peer_id);
+                    transcript.record_response(peer_id, resp, PeerOutcome::UnboundSigner);
                     self.reputation.penalize(peer_id);
                     false
+                } else {
+                    true
                 }
             })
             .collect();
 
         if valid_responses.is_empty() {
-            return self.try_fallback(symbol, block_number).await;
+            return Err(SodsP2pError::SignatureBindingMissing { rejected: received });
         }
 
         // Evaluate consensus using Adaptive Quorum
@@ -391,41 +710,82 @@ impl SodsClient {
         let consensus =
             evaluate_consensus(valid_responses.clone(), &self.reputation, DEFAULT_THRESHOLD);
 
+        if consensus.agreed_absent {
+            // Nothing was proven, so nobody is rewarded or penalized
+            for (peer_id, resp) in &valid_responses {
+                let outcome = if resp.success {
+                    PeerOutcome::Conflicting
+                } else {
+                    PeerOutcome::Agreed
+                };
+                transcript.record_response(peer_id, resp, outcome);
+            }
+            let reason = valid_responses
+                .iter()
+                .find_map(|(_, resp)| resp.error.clone())
+                .unwrap_or_else(|| "no proof".into());
+            return Err(SodsP2pError::NotFound {
+                agreeing: consensus.agreeing_peers,
+                total: consensus.total_peers,
+                reason,
+            });
+        }
+
         // Update reputation and perform slashing
         for (peer_id, resp) in &valid_responses {
             if consensus.conflicting_peers.contains(peer_id) {
                 // Check if this peer's response actually contradicts a successful consensus
                 if consensus.is_verified && resp.success {
                     warn!("❌ Peer {} provided conflicting root! SLASHING.", peer_id);
+                    transcript.record_response(peer_id, resp, PeerOutcome::Slashed);
                     self.slashed_peers.insert(*peer_id);
                     self.known_peers.remove(peer_id);
                     // Note: We don't disconnect immediately here to avoid blocking,
                     // but they are erased from the reliable set.
+                } else {
+                    transcript.record_response(peer_id, resp, PeerOutcome::Conflicting);
                 }
                 self.reputation.penalize(peer_id);
             } else {
+                transcript.record_response(peer_id, resp, PeerOutcome::Agreed);
                 self.reputation.reward(peer_id);
             }
         }
 
-        if consensus.is_verified {
-            Ok(P2pVerificationResult {
-                is_verified: true,
-                agreeing_peers: consensus.agreeing_peers,
-                total_peers: consensus.total_peers,
-                bmt_root: consensus.bmt_root,
-                used_fallback: false,
-                symbol: symbol.to_string(),
-                block_number,
-            })
-        } else if self.fallback_verifier.is_some() {
-            self.try_fallback(symbol, block_number).await
-        } else {
-            Err(SodsP2pError::ConsensusFailure {
+        if !consensus.is_verified {
+            return Err(SodsP2pError::ConsensusSplit {
                 agreeing: consensus.agreeing_peers,
                 total: consensus.total_peers,
-            })
+                details: describe_split(&valid_responses),
+            });
         }
+        Ok(consensus)
+    }
+
+    /// Turns a consensus outcome into a result, falling back to RPC when
+    /// the peers could not decide.
+    async fn conclude(
+        &mut self,
+        symbol: &str,
+        block_number: u64,
+        consensus: Result<ConsensusResult>,
+    ) -> Result<(P2pVerificationResult, DecisionSource)> {
+        let consensus = match consensus {
+            Ok(consensus) => consensus,
+            Err(reason) => return self.fallback_decision(symbol, block_number, reason).await,
+        };
+
+        let result = P2pVerificationResult {
+            is_verified: true,
+            agreeing_peers: consensus.agreeing_peers,
+            total_peers: consensus.total_peers,
+            bmt_root: consensus.bmt_root,
+            used_fallback: false,
+            symbol: symbol.to_string(),
+            block_number,
+            transcript_path: None,
+        };
+        Ok((result, DecisionSource::Consensus))
     }
 
     /// Collect responses from pending requests.
@@ -440,7 +800,7 @@ impl SodsClient {
                     _ = interval.tick() => {
                         self.cleanup_expired_challenges();
                     }
-                    event = self.swarm.select_next_some() => {
+                    event = self.next_event() => {
                         match event {
                             SwarmEvent::Behaviour(SodsBehaviourEvent::RequestResponse(event)) => {
                                 if let request_response::Event::Message {
@@ -478,22 +838,88 @@ impl SodsClient {
         responses
     }
 
+    /// RPC fallback as the final decision of `run_verification`; without a
+    /// fallback verifier, fails with `reason`, why the peers could not decide.
+    async fn fallback_decision(
+        &mut self,
+        symbol: &str,
+        block_number: u64,
+        reason: SodsP2pError,
+    ) -> Result<(P2pVerificationResult, DecisionSource)> {
+        let result = self.try_fallback(symbol, block_number, reason).await?;
+        Ok((result, DecisionSource::Fallback))
+    }
+
+    /// Collect batch responses from pending batch requests.
+    async fn collect_batch_responses(
+        &mut self,
+        expected_count: usize,
+    ) -> Vec<(PeerId, BatchProofResponse)> {
+        let mut responses = Vec::new();
+        let mut failed = 0;
+
+        let collection = timeout(BATCH_REQUEST_TIMEOUT, async {
+            while responses.len() + failed < expected_count {
+                match self.next_event().await {
+                    SwarmEvent::Behaviour(SodsBehaviourEvent::Batch(
+                        request_response::Event::Message {
+                            peer,
+                            message:
+                                request_response::Message::Response {
+                                    response: BatchReply::Complete(response),
+                                    request_id,
+                                },
+                        },
+                    )) => {
+                        // Late replies to an earlier batch are not counted
+                        if self.pending_batches.remove(&request_id).is_some() {
+                            responses.push((peer, response));
+                        }
+                    }
+                    SwarmEvent::Behaviour(SodsBehaviourEvent::Batch(
+                        request_response::Event::OutboundFailure {
+                            peer,
+                            request_id,
+                            error,
+                        },
+                    )) => {
+                        warn!("Batch request to {} failed: {:?}", peer, error);
+                        if self.pending_batches.remove(&request_id).is_some() {
+                            failed += 1;
+                        }
+                    }
+                    SwarmEvent::Behaviour(SodsBehaviourEvent::Puzzle(event)) => {
+                        self.handle_puzzle_event(event).await;
+                    }
+                    SwarmEvent::Behaviour(SodsBehaviourEvent::Identify(event)) => {
+                        self.handle_identify_event(event);
+                    }
+                    _ => {}
+                }
+            }
+        });
+
+        let _ = collection.await;
+        // Anything still pending timed out; its reply must not count later
+        self.pending_batches.clear();
+        responses
+    }
+
     /// Try fallback to RPC verification.
     async fn try_fallback(
         &mut self,
         symbol: &str,
         block_number: u64,
+        reason: SodsP2pError,
     ) -> Result<P2pVerificationResult> {
-        let verifier = self
-            .fallback_verifier
-            .as_ref()
-            .ok_or(SodsP2pError::NoAvailablePeers)?;
+        let Some(verifier) = self.fallback_verifier.as_ref() else {
+            return Err(reason);
+        };
+        debug!("Peers could not decide: {}", reason);
 
         info!("Using RPC fallback for verification");
 
-        let result = verifier
-            .verify_symbol_in_block(symbol, block_number)
-            .await?;
+        let result = verify_query(verifier.as_ref(), symbol, block_number).await?;
 
         let bmt_root = result.merkle_root.map(|v| {
             let mut arr = [0u8; 32];
@@ -509,6 +935,7 @@ impl SodsClient {
             used_fallback: true,
             symbol: symbol.to_string(),
             block_number,
+            transcript_path: None,
         })
     }
 
@@ -525,8 +952,9 @@ impl SodsClient {
     /// Cleanup expired behavioral puzzles to prevent memory leaks.
     pub fn cleanup_expired_challenges(&mut self) {
         let before = self.pending_challenges.len();
+        let now = self.clock.now();
         self.pending_challenges
-            .retain(|_, (_, puzzle)| !puzzle.is_expired());
+            .retain(|_, (_, puzzle)| !puzzle.is_expired_at(now));
         let saved = before - self.pending_challenges.len();
         if saved > 0 {
             debug!("Cleaned up {} expired behavioral puzzles", saved);
@@ -534,13 +962,200 @@ impl SodsClient {
     }
 }
 
+/// Why no response arrived from `queried` of `known` candidate peers.
+fn unanswered(known: usize, queried: usize, wait: Duration) -> SodsP2pError {
+    if known == 0 {
+        SodsP2pError::NoAvailablePeers
+    } else if queried == 0 {
+        SodsP2pError::NoReliablePeers { known }
+    } else {
+        SodsP2pError::AllPeersTimedOut {
+            queried,
+            timeout_secs: wait.as_secs(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::{BehavioralPuzzle, PuzzleChallenge, PuzzleSolution};
+    use k256::ecdsa::SigningKey;
+    use sods_core::clock::{Clock, MockClock};
+    use sods_core::BehavioralSymbol;
+    use sods_verifier::{VerificationMode, VerificationResult};
+
+    /// A chain where every block holds `count` of each symbol.
+    struct FakeChain {
+        count: usize,
+    }
+
+    #[async_trait::async_trait]
+    impl SymbolVerifier for FakeChain {
+        async fn verify_symbol_in_block(
+            &self,
+            symbol: &str,
+            block_number: u64,
+        ) -> sods_verifier::error::Result<VerificationResult> {
+            Ok(VerificationResult::success(
+                symbol.to_string(),
+                block_number,
+                0,
+                [0x11; 32],
+                self.count,
+                1.0,
+                VerificationMode::Trustless,
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::ZERO,
+            ))
+        }
+
+        async fn verify_pattern_trustless(
+            &self,
+            pattern: &str,
+            block_number: u64,
+        ) -> sods_verifier::error::Result<VerificationResult> {
+            self.verify_symbol_in_block(pattern, block_number).await
+        }
+
+        async fn fetch_block_symbols(
+            &self,
+            _block_number: u64,
+        ) -> sods_verifier::error::Result<Vec<BehavioralSymbol>> {
+            Ok(Vec::new())
+        }
+
+        async fn get_latest_block(&self) -> sods_verifier::error::Result<u64> {
+            Ok(0)
+        }
+    }
+
+    fn fake_client(count: usize) -> SodsClient {
+        SodsClient::with_fallback_verifier(Arc::new(FakeChain { count }), &NetworkConfig::new())
+            .unwrap()
+    }
 
     #[test]
     fn test_client_creation() {
         let client = SodsClient::new();
         assert!(client.is_ok());
     }
+
+    #[test]
+    fn test_unanswered_reasons() {
+        let wait = Duration::from_secs(10);
+        assert!(matches!(
+            unanswered(0, 0, wait),
+            SodsP2pError::NoAvailablePeers
+        ));
+        assert!(matches!(
+            unanswered(4, 0, wait),
+            SodsP2pError::NoReliablePeers { known: 4 }
+        ));
+        assert!(matches!(
+            unanswered(4, 3, wait),
+            SodsP2pError::AllPeersTimedOut {
+                queried: 3,
+                timeout_secs: 10
+            }
+        ));
+    }
+
+    #[test]
+    fn test_unbound_responses_are_rejected() {
+        let mut client = SodsClient::new().unwrap();
+        let mut transcript = ConsensusTranscript::new("Tf", 1, DEFAULT_THRESHOLD);
+        let responses = vec![
+            (
+                PeerId::random(),
+                ProofResponse::success(vec![1], [0xAA; 32], 1),
+            ),
+            (
+                PeerId::random(),
+                ProofResponse::success(vec![1], [0xAA; 32], 1),
+            ),
+        ];
+        assert!(matches!(
+            client.settle(responses, &mut transcript),
+            Err(SodsP2pError::SignatureBindingMissing { rejected: 2 })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_local_truth_from_injected_verifier() {
+        let mut client = fake_client(1);
+        let result = client.verify_via_p2p("Tf", 5).await.unwrap();
+        assert!(result.is_verified && result.used_fallback);
+        assert_eq!(result.bmt_root, Some([0x11; 32]));
+        assert_eq!(
+            client
+                .last_transcript()
+                .unwrap()
+                .decision
+                .as_ref()
+                .unwrap()
+                .source,
+            DecisionSource::LocalTruth
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_verification_keeps_transcript_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut client = SodsClient::new().unwrap().with_transcript_dir(dir.path());
+        assert!(client.verify_via_p2p("Tf", 5).await.is_err());
+        let path = client.last_transcript_path().unwrap();
+        assert!(path.starts_with(dir.path()) && path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_puzzle_solutions_checked_against_fallback() {
+        let mut client = fake_client(2).with_stake(Stake::High);
+        let keypair = Keypair::generate_ed25519();
+        let peer = PeerId::from(keypair.public());
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        client
+            .peer_signers
+            .insert(peer, SignerBinding::new(&keypair, &key).unwrap());
+
+        let challenge = PuzzleChallenge::for_stake(client.stake);
+        let cells = challenge.cells().count();
+        let wrong = PuzzleSolution::signed(&challenge, &peer, vec![1; cells], &key);
+        client
+            .verify_solution(peer, BehavioralPuzzle::new(challenge.clone()), wrong)
+            .await;
+        assert!(!client.reputation().is_reliable(&peer));
+
+        let right = PuzzleSolution::signed(&challenge, &peer, vec![2; cells], &key);
+        client
+            .verify_solution(peer, BehavioralPuzzle::new(challenge), right)
+            .await;
+        assert!(client.reputation().is_reliable(&peer));
+    }
+
+    #[tokio::test]
+    async fn test_late_puzzle_solution_is_rejected() {
+        let clock = MockClock::default();
+        let config = NetworkConfig::new().with_clock(clock.shared());
+        let mut client =
+            SodsClient::with_fallback_verifier(Arc::new(FakeChain { count: 2 }), &config).unwrap();
+        let keypair = Keypair::generate_ed25519();
+        let peer = PeerId::from(keypair.public());
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        client
+            .peer_signers
+            .insert(peer, SignerBinding::new(&keypair, &key).unwrap());
+
+        let challenge = PuzzleChallenge::random();
+        let cells = challenge.cells().count();
+        let puzzle = BehavioralPuzzle::issued_at(challenge.clone(), clock.now());
+        assert!(!puzzle.is_expired_at(clock.now()));
+
+        // A correct answer, but past the deadline
+        clock.advance(puzzle.expires_after + Duration::from_secs(1));
+        let right = PuzzleSolution::signed(&challenge, &peer, vec![2; cells], &key);
+        client.verify_solution(peer, puzzle, right).await;
+        assert!(!client.reputation().is_reliable(&peer));
+    }
 }
@@ -0,0 +1,420 @@
+//! Network access configuration for peers and clients.
+//!
+//! Enterprises can run an internal proof-exchange mesh by restricting
+//! connections to a static allowlist of `PeerId`s, refusing a denylist,
+//! and/or joining a private network keyed by a `PreSharedKey`. Serving
+//! peers additionally throttle each remote peer per `RateLimitConfig`.
+//!
+//! A `NodeRole` narrows what a node exposes: a serve-only peer answers
+//! proof requests but stays out of threat gossip, and a query-only node
+//! never listens or answers requests at all.
+//!
+//! A private node can also bridge threat rules to and from the public
+//! network (see `federation`). Tests can run whole networks in one process
+//! over the memory transport.
+
+use futures::future::Either;
+use k256::ecdsa::SigningKey;
+use libp2p::connection_limits::ConnectionLimits;
+use libp2p::core::transport::MemoryTransport;
+use libp2p::multiaddr::Protocol;
+use libp2p::{
+    core::upgrade::Version, identity::Keypair, swarm::Swarm, Multiaddr, PeerId, Transport,
+};
+use sods_core::clock::{SharedClock, SystemClock};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::behavior::SodsBehaviour;
+use crate::capabilities::PeerInfo;
+use crate::error::{Result, SodsP2pError};
+use crate::federation::FederationConfig;
+use crate::metrics::P2pMetrics;
+use crate::pnet::PreSharedKey;
+use crate::rate_limit::RateLimitConfig;
+use crate::reputation::ReputationTracker;
+
+/// What a node does on the network.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NodeRole {
+    /// Serves proofs and takes part in threat gossip
+    #[default]
+    Full,
+    /// Serves proofs; never subscribes to or publishes threat gossip
+    ServeOnly,
+    /// Only dials out; no listener and no inbound request handling
+    QueryOnly,
+}
+
+impl NodeRole {
+    /// Whether the node answers proof, batch and puzzle requests.
+    pub fn serves_proofs(&self) -> bool {
+        !matches!(self, NodeRole::QueryOnly)
+    }
+
+    /// Whether the node joins the threat gossip topic.
+    pub fn gossips_threats(&self) -> bool {
+        !matches!(self, NodeRole::ServeOnly)
+    }
+
+    /// Whether the node accepts inbound connections.
+    pub fn listens(&self) -> bool {
+        !matches!(self, NodeRole::QueryOnly)
+    }
+
+    /// Connection limits sized for the role.
+    pub fn connection_limits(&self) -> ConnectionLimits {
+        let limits = ConnectionLimits::default().with_max_pending_incoming(Some(32));
+        match self {
+            NodeRole::Full => limits
+                .with_max_established_incoming(Some(128))
+                .with_max_established_per_peer(Some(4)),
+            // Many clients, each with a single proof-exchange connection
+            NodeRole::ServeOnly => limits
+                .with_max_established_incoming(Some(512))
+                .with_max_established_per_peer(Some(2)),
+            NodeRole::QueryOnly => limits
+                .with_max_pending_incoming(Some(0))
+                .with_max_established_incoming(Some(0))
+                .with_max_established(Some(64))
+                .with_max_established_per_peer(Some(1)),
+        }
+    }
+}
+
+impl fmt::Display for NodeRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NodeRole::Full => "full",
+            NodeRole::ServeOnly => "serve-only",
+            NodeRole::QueryOnly => "query-only",
+        })
+    }
+}
+
+impl FromStr for NodeRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(NodeRole::Full),
+            "serve-only" => Ok(NodeRole::ServeOnly),
+            "query-only" => Ok(NodeRole::QueryOnly),
+            other => Err(format!(
+                "Unknown role '{}' (expected full, serve-only or query-only)",
+                other
+            )),
+        }
+    }
+}
+
+/// Operator caps on connections, applied over the limits of the node role.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionCaps {
+    /// Most established connections in total; a peer usually holds one
+    pub max_peers: Option<u32>,
+    /// Most outbound dials in progress at once
+    pub max_pending_dials: Option<u32>,
+    /// Most inbound connections from one IP address
+    pub max_per_ip: Option<u32>,
+}
+
+/// Who a node talks to, and over which network.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkConfig {
+    /// If set, only these peers may connect
+    pub allowlist: Option<HashSet<PeerId>>,
+    /// Peers that may never connect
+    pub denylist: HashSet<PeerId>,
+    /// Pre-shared key of a private network
+    pub psk: Option<PreSharedKey>,
+    /// Request limits a serving peer enforces on each remote peer
+    pub rate_limits: RateLimitConfig,
+    /// What the node exposes
+    pub role: NodeRole,
+    /// Peers to dial on startup; a query-only node can only reach the
+    /// network through these
+    pub bootstrap: Vec<Multiaddr>,
+    /// Key that signs proof responses; a fresh one is generated per peer
+    /// if unset
+    pub signing_key: Option<SigningKey>,
+    /// Bridge threat rules between this private network and the public one
+    pub federation: Option<FederationConfig>,
+    /// Connect over in-process `/memory/<port>` addresses instead of TCP
+    pub memory_transport: bool,
+    /// Caps over the role's connection limits
+    pub connection_caps: ConnectionCaps,
+    /// Registry the swarm records its libp2p metrics into
+    pub metrics: Option<P2pMetrics>,
+    /// Time source for puzzle expiry, rate limits and caches; the system
+    /// clock if unset
+    pub clock: Option<SharedClock>,
+    /// Starting peer reputation, e.g. seeded from imported snapshots
+    pub reputation: Option<ReputationTracker>,
+}
+
+impl NetworkConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a peer to the allowlist, enabling allowlist mode (Builder pattern).
+    pub fn with_allowed_peer(mut self, peer: PeerId) -> Self {
+        self.allowlist.get_or_insert_with(HashSet::new).insert(peer);
+        self
+    }
+
+    /// Adds a peer to the denylist (Builder pattern).
+    pub fn with_denied_peer(mut self, peer: PeerId) -> Self {
+        self.denylist.insert(peer);
+        self
+    }
+
+    /// Joins the private network identified by `psk` (Builder pattern).
+    pub fn with_private_network(mut self, psk: PreSharedKey) -> Self {
+        self.psk = Some(psk);
+        self
+    }
+
+    /// Sets the node role (Builder pattern).
+    pub fn with_role(mut self, role: NodeRole) -> Self {
+        self.role = role;
+        self
+    }
+
+    /// Adds a peer to dial on startup (Builder pattern).
+    pub fn with_bootstrap_peer(mut self, addr: Multiaddr) -> Self {
+        self.bootstrap.push(addr);
+        self
+    }
+
+    /// Signs proof responses with a persistent key instead of an ephemeral
+    /// one, so the signer address stays stable across restarts (Builder pattern).
+    pub fn with_signing_key(mut self, key: SigningKey) -> Self {
+        self.signing_key = Some(key);
+        self
+    }
+
+    /// Federates threat rules with the public network; only meaningful
+    /// together with a private network (Builder pattern).
+    pub fn with_federation(mut self, federation: FederationConfig) -> Self {
+        self.federation = Some(federation);
+        self
+    }
+
+    /// Uses the in-process memory transport instead of TCP, so a whole
+    /// network can run inside one test (Builder pattern).
+    pub fn with_memory_transport(mut self) -> Self {
+        self.memory_transport = true;
+        self
+    }
+
+    /// Address to listen on when no specific one is wanted: any interface
+    /// and a free port.
+    pub fn any_listen_addr(&self) -> Multiaddr {
+        if self.memory_transport {
+            Multiaddr::empty().with(Protocol::Memory(0))
+        } else {
+            Multiaddr::empty()
+                .with(Protocol::Ip4(std::net::Ipv4Addr::UNSPECIFIED))
+                .with(Protocol::Tcp(0))
+        }
+    }
+
+    /// Caps connections beyond what the role allows (Builder pattern).
+    pub fn with_connection_caps(mut self, caps: ConnectionCaps) -> Self {
+        self.connection_caps = caps;
+        self
+    }
+
+    /// Records libp2p metrics into `metrics` (Builder pattern).
+    pub fn with_metrics(mut self, metrics: P2pMetrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Reads the time from `clock` instead of the system clock, e.g. a
+    /// `MockClock` in tests and simulations (Builder pattern).
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Starts peers from `reputation` instead of an empty tracker
+    /// (Builder pattern).
+    pub fn with_reputation(mut self, reputation: ReputationTracker) -> Self {
+        self.reputation = Some(reputation);
+        self
+    }
+
+    /// The configured clock, or the system clock.
+    pub fn clock(&self) -> SharedClock {
+        self.clock.clone().unwrap_or_else(SystemClock::shared)
+    }
+
+    /// The role's connection limits with the configured caps applied.
+    pub fn connection_limits(&self) -> ConnectionLimits {
+        let mut limits = self.role.connection_limits();
+        let caps = self.connection_caps;
+        if caps.max_peers.is_some() {
+            limits = limits.with_max_established(caps.max_peers);
+        }
+        if caps.max_pending_dials.is_some() {
+            limits = limits.with_max_pending_outgoing(caps.max_pending_dials);
+        }
+        limits
+    }
+
+    /// Sets the per-peer request limits (Builder pattern).
+    pub fn with_rate_limits(mut self, limits: RateLimitConfig) -> Self {
+        self.rate_limits = limits;
+        self
+    }
+
+    /// Whether a connection to or from `peer` is permitted.
+    ///
+    /// The denylist takes precedence over the allowlist.
+    pub fn is_permitted(&self, peer: &PeerId) -> bool {
+        !self.denylist.contains(peer)
+            && self
+                .allowlist
+                .as_ref()
+                .map_or(true, |allowed| allowed.contains(peer))
+    }
+}
+
+/// Builds the TCP swarm shared by `SodsPeer` and `SodsClient`, advertising
+/// `info` via identify.
+pub(crate) fn build_swarm(
+    keypair: Keypair,
+    config: &NetworkConfig,
+    info: &PeerInfo,
+) -> Result<Swarm<SodsBehaviour>> {
+    let behaviour_key = keypair.clone();
+    let behaviour = move |_: &Keypair| {
+        SodsBehaviour::with_peer_info(&behaviour_key, info).with_network_config(config)
+    };
+
+    let builder = libp2p::SwarmBuilder::with_existing_identity(keypair).with_tokio();
+    if config.memory_transport {
+        let psk = config.psk;
+        return Ok(builder
+            .with_other_transport(|key| {
+                Ok::<_, Box<dyn std::error::Error + Send + Sync>>(
+                    MemoryTransport::default()
+                        .and_then(move |socket, _| async move {
+                            match psk {
+                                Some(psk) => psk.config().handshake(socket).await.map(Either::Left),
+                                None => Ok(Either::Right(socket)),
+                            }
+                        })
+                        .upgrade(Version::V1)
+                        .authenticate(libp2p::noise::Config::new(key)?)
+                        .multiplex(libp2p::yamux::Config::default()),
+                )
+            })
+            .map_err(|e| SodsP2pError::NetworkError(format!("Memory transport error: {}", e)))?
+            .with_behaviour(behaviour)
+            .map_err(|e| SodsP2pError::NetworkError(format!("Behaviour error: {}", e)))?
+            .build());
+    }
+    let swarm = match config.psk {
+        None => builder
+            .with_tcp(
+                libp2p::tcp::Config::default(),
+                libp2p::noise::Config::new,
+                libp2p::yamux::Config::default,
+            )
+            .map_err(|e| SodsP2pError::NetworkError(format!("TCP error: {}", e)))?
+            .with_behaviour(behaviour)
+            .map_err(|e| SodsP2pError::NetworkError(format!("Behaviour error: {}", e)))?
+            .build(),
+        Some(psk) => builder
+            .with_other_transport(|key| {
+                Ok::<_, Box<dyn std::error::Error + Send + Sync>>(
+                    libp2p::tcp::tokio::Transport::new(libp2p::tcp::Config::default())
+                        .and_then(move |socket, _| psk.config().handshake(socket))
+                        .upgrade(Version::V1)
+                        .authenticate(libp2p::noise::Config::new(key)?)
+                        .multiplex(libp2p::yamux::Config::default()),
+                )
+            })
+            .map_err(|e| SodsP2pError::NetworkError(format!("Private transport error: {}", e)))?
+            .with_behaviour(behaviour)
+            .map_err(|e| SodsP2pError::NetworkError(format!("Behaviour error: {}", e)))?
+            .build(),
+    };
+    Ok(swarm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_denylist_overrides_allowlist() {
+        let a = PeerId::random();
+        let b = PeerId::random();
+        let open = NetworkConfig::new().with_denied_peer(b);
+        assert!(open.is_permitted(&a));
+        assert!(!open.is_permitted(&b));
+
+        let closed = NetworkConfig::new()
+            .with_allowed_peer(a)
+            .with_allowed_peer(b)
+            .with_denied_peer(b);
+        assert!(closed.is_permitted(&a));
+        assert!(!closed.is_permitted(&b));
+        assert!(!closed.is_permitted(&PeerId::random()));
+    }
+
+    #[test]
+    fn test_role_parsing_and_surface() {
+        for role in [NodeRole::Full, NodeRole::ServeOnly, NodeRole::QueryOnly] {
+            assert_eq!(role.to_string().parse::<NodeRole>(), Ok(role));
+        }
+        assert!("observer".parse::<NodeRole>().is_err());
+
+        assert!(NodeRole::ServeOnly.serves_proofs());
+        assert!(!NodeRole::ServeOnly.gossips_threats());
+        assert!(!NodeRole::QueryOnly.listens());
+        assert!(!NodeRole::QueryOnly.serves_proofs());
+        assert!(NodeRole::QueryOnly.gossips_threats());
+    }
+
+    #[test]
+    fn test_connection_caps_override_role_limits() {
+        let config = NetworkConfig::new().with_connection_caps(ConnectionCaps {
+            max_peers: Some(50),
+            max_pending_dials: Some(8),
+            max_per_ip: Some(4),
+        });
+        // `ConnectionLimits` has no getters
+        let limits = format!("{:?}", config.connection_limits());
+        assert!(limits.contains("max_established_total: Some(50)"));
+        assert!(limits.contains("max_pending_outgoing: Some(8)"));
+        // Limits without a cap keep the role's value
+        assert!(limits.contains("max_established_incoming: Some(128)"));
+    }
+
+    #[tokio::test]
+    async fn test_private_swarm_builds() {
+        let config = NetworkConfig::new().with_private_network(PreSharedKey::generate());
+        assert!(build_swarm(Keypair::generate_ed25519(), &config, &PeerInfo::local()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_memory_swarm_listens_in_process() {
+        let config = NetworkConfig::new()
+            .with_memory_transport()
+            .with_private_network(PreSharedKey::generate());
+        let mut swarm =
+            build_swarm(Keypair::generate_ed25519(), &config, &PeerInfo::local()).unwrap();
+        assert_eq!(config.any_listen_addr().to_string(), "/memory/0");
+        assert!(swarm.listen_on(config.any_listen_addr()).is_ok());
+        assert!(swarm
+            .listen_on(NetworkConfig::new().any_listen_addr())
+            .is_err());
+    }
+}
@@ -33,6 +33,9 @@ pub struct ConsensusResult {
     pub proof_bytes: Option<Vec<u8>>,
     /// Peers that provided conflicting responses.
     pub conflicting_peers: Vec<PeerId>,
+    /// Whether enough peers agreed there is no proof to give, e.g. because
+    /// the symbol or pattern does not occur in the block.
+    pub agreed_absent: bool,
 }
 
 impl ConsensusResult {
@@ -45,6 +48,7 @@ impl ConsensusResult {
             bmt_root: None,
             proof_bytes: None,
             conflicting_peers: Vec::new(),
+            agreed_absent: false,
         }
     }
 }
@@ -75,6 +79,25 @@ pub fn evaluate_consensus(
     // Filter successful responses
     let successful: Vec<_> = responses.iter().filter(|(_, r)| r.success).collect();
 
+    // Peers answering without a proof agree with each other, not against
+    // the others
+    let absent_weight: f32 = responses
+        .iter()
+        .filter(|(_, r)| !r.success)
+        .map(|(p, _)| reputation.get_score(p))
+        .sum();
+    if (absent_weight / total_weight) as f64 >= threshold {
+        return ConsensusResult {
+            is_verified: false,
+            agreeing_peers: total_count - successful.len(),
+            total_peers: total_count,
+            bmt_root: None,
+            proof_bytes: None,
+            conflicting_peers: successful.iter().map(|(p, _)| *p).collect(),
+            agreed_absent: true,
+        };
+    }
+
     if successful.is_empty() {
         return ConsensusResult {
             is_verified: false,
@@ -83,6 +106,7 @@ pub fn evaluate_consensus(
             bmt_root: None,
             proof_bytes: None,
             conflicting_peers: responses.iter().map(|(p, _)| *p).collect(),
+            agreed_absent: false,
         };
     }
 
@@ -127,6 +151,7 @@ pub fn evaluate_consensus(
             bmt_root: Some(largest_root),
             proof_bytes,
             conflicting_peers,
+            agreed_absent: false,
         }
     } else {
         // No consensus
@@ -137,10 +162,37 @@ pub fn evaluate_consensus(
             bmt_root: None,
             proof_bytes: None,
             conflicting_peers,
+            agreed_absent: false,
         }
     }
 }
 
+/// Summarizes what each side answered, largest first, e.g.
+/// "2 on root 0xaaaaaaaa, 1 on root 0xbbbbbbbb, 1 failed".
+pub fn describe_split(responses: &[(PeerId, ProofResponse)]) -> String {
+    let mut roots: Vec<([u8; 32], usize)> = Vec::new();
+    let mut failed = 0;
+    for (_, resp) in responses {
+        if !resp.success {
+            failed += 1;
+        } else if let Some((_, count)) = roots.iter_mut().find(|(root, _)| *root == resp.bmt_root) {
+            *count += 1;
+        } else {
+            roots.push((resp.bmt_root, 1));
+        }
+    }
+    roots.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut parts: Vec<String> = roots
+        .iter()
+        .map(|(root, count)| format!("{} on root 0x{}", count, hex::encode(&root[..4])))
+        .collect();
+    if failed > 0 {
+        parts.push(format!("{} failed", failed));
+    }
+    parts.join(", ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,6 +205,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_describe_split() {
+        let peer = || PeerId::random();
+        let responses = vec![
+            (peer(), make_response([0xBB; 32], true)),
+            (peer(), make_response([0xAA; 32], true)),
+            (peer(), make_response([0xBB; 32], true)),
+            (peer(), make_response([0; 32], false)),
+        ];
+        assert_eq!(
+            describe_split(&responses),
+            "2 on root 0xbbbbbbbb, 1 on root 0xaaaaaaaa, 1 failed"
+        );
+    }
+
     #[test]
     fn test_weighted_consensus() {
         let mut tracker = ReputationTracker::new();
@@ -182,4 +249,33 @@ mod tests {
         assert_eq!(result.bmt_root, Some(good_root));
         assert!(result.conflicting_peers.contains(&peer3));
     }
+
+    #[test]
+    fn test_agreed_absence_is_not_a_split() {
+        let mut tracker = ReputationTracker::new();
+        let (a, b, c) = (PeerId::random(), PeerId::random(), PeerId::random());
+        for peer in [a, b, c] {
+            tracker.reward(&peer);
+        }
+
+        let responses = vec![
+            (a, make_response([0; 32], false)),
+            (b, make_response([0; 32], false)),
+        ];
+        let result = evaluate_consensus(responses, &tracker, DEFAULT_THRESHOLD);
+        assert!(result.agreed_absent);
+        assert!(!result.is_verified);
+        assert_eq!(result.agreeing_peers, 2);
+        assert!(result.conflicting_peers.is_empty());
+
+        // One absence is a side of a split like any root
+        let responses = vec![
+            (a, make_response([0; 32], false)),
+            (b, make_response([0xAA; 32], true)),
+            (c, make_response([0xBB; 32], true)),
+        ];
+        let result = evaluate_consensus(responses, &tracker, DEFAULT_THRESHOLD);
+        assert!(!result.agreed_absent);
+        assert!(!result.is_verified);
+    }
 }
@@ -1,5 +1,7 @@
 //! Error types for SODS P2P operations.
 
+use libp2p::Multiaddr;
+use sods_core::{ErrorClass, ErrorCode};
 use thiserror::Error;
 
 /// Errors that can occur during P2P operations.
@@ -21,13 +23,67 @@ pub enum SodsP2pError {
     #[error("No available peers for verification")]
     NoAvailablePeers,
 
-    /// Social consensus failed.
-    #[error("Consensus failure: only {agreeing}/{total} peers agreed")]
-    ConsensusFailure {
+    /// Peers are known, but none has enough reputation to be queried.
+    #[error("None of the {known} known peers is reliable enough to query")]
+    NoReliablePeers {
+        /// Number of known peers
+        known: usize,
+    },
+
+    /// No queried peer answered in time.
+    #[error("All {queried} queried peers timed out after {timeout_secs}s")]
+    AllPeersTimedOut {
+        /// Number of peers queried
+        queried: usize,
+        /// Request timeout in seconds
+        timeout_secs: u64,
+    },
+
+    /// No response was signed by the key its peer bound to its identity.
+    #[error("None of the {rejected} responses was signed by its peer's bound key")]
+    SignatureBindingMissing {
+        /// Number of responses rejected
+        rejected: usize,
+    },
+
+    /// The peers disagreed and no side reached the consensus threshold.
+    #[error("Consensus split: only {agreeing}/{total} peers agreed ({details})")]
+    ConsensusSplit {
         /// Number of agreeing peers
         agreeing: usize,
-        /// Total peers queried
+        /// Total peers that sent a valid response
+        total: usize,
+        /// What each side answered
+        details: String,
+    },
+
+    /// Enough peers agreed there is no proof for the query in the block.
+    #[error("Not found: {agreeing}/{total} peers had no proof ({reason})")]
+    NotFound {
+        /// Number of peers without a proof
+        agreeing: usize,
+        /// Total peers that sent a valid response
         total: usize,
+        /// Reason given by one of them
+        reason: String,
+    },
+
+    /// A bootstrap or peer address could not be dialed.
+    #[error("Failed to dial {addr}: {reason}")]
+    DialFailed {
+        /// Address dialed
+        addr: Multiaddr,
+        /// Transport error
+        reason: String,
+    },
+
+    /// The node could not open its listener.
+    #[error("Failed to listen on {addr}: {reason}")]
+    ListenFailed {
+        /// Address to listen on
+        addr: Multiaddr,
+        /// Transport error
+        reason: String,
     },
 
     /// Proof verification failed.
@@ -51,6 +107,71 @@ pub enum SodsP2pError {
     Io(#[from] std::io::Error),
 }
 
+impl ErrorCode for SodsP2pError {
+    /// Verifier errors keep their own code.
+    fn code(&self) -> &'static str {
+        match self {
+            SodsP2pError::NetworkError(_) => "network_error",
+            SodsP2pError::ProtocolError(_) => "protocol_error",
+            SodsP2pError::SerializationError(_) => "serialization_error",
+            SodsP2pError::NoAvailablePeers => "no_available_peers",
+            SodsP2pError::NoReliablePeers { .. } => "no_reliable_peers",
+            SodsP2pError::AllPeersTimedOut { .. } => "all_peers_timed_out",
+            SodsP2pError::SignatureBindingMissing { .. } => "signature_binding_missing",
+            SodsP2pError::ConsensusSplit { .. } => "consensus_split",
+            SodsP2pError::NotFound { .. } => "not_found",
+            SodsP2pError::DialFailed { .. } => "dial_failed",
+            SodsP2pError::ListenFailed { .. } => "listen_failed",
+            SodsP2pError::VerificationFailed(_) => "verification_failed",
+            SodsP2pError::Timeout => "timeout",
+            SodsP2pError::Verifier(e) => e.code(),
+            SodsP2pError::ChallengeExpired => "challenge_expired",
+            SodsP2pError::Io(_) => "io_error",
+        }
+    }
+
+    fn class(&self) -> ErrorClass {
+        match self {
+            SodsP2pError::NetworkError(_)
+            | SodsP2pError::NoAvailablePeers
+            | SodsP2pError::NoReliablePeers { .. }
+            | SodsP2pError::AllPeersTimedOut { .. }
+            | SodsP2pError::DialFailed { .. }
+            | SodsP2pError::ListenFailed { .. }
+            | SodsP2pError::Timeout => ErrorClass::Unavailable,
+            SodsP2pError::SignatureBindingMissing { .. }
+            | SodsP2pError::ConsensusSplit { .. }
+            | SodsP2pError::VerificationFailed(_)
+            | SodsP2pError::ChallengeExpired => ErrorClass::VerificationFailed,
+            SodsP2pError::ProtocolError(_)
+            | SodsP2pError::SerializationError(_)
+            | SodsP2pError::Io(_) => ErrorClass::Internal,
+            SodsP2pError::NotFound { .. } => ErrorClass::NotFound,
+            SodsP2pError::Verifier(e) => e.class(),
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        match self {
+            SodsP2pError::Timeout
+            | SodsP2pError::NoAvailablePeers
+            | SodsP2pError::NoReliablePeers { .. }
+            | SodsP2pError::AllPeersTimedOut { .. }
+            | SodsP2pError::DialFailed { .. } => true,
+            SodsP2pError::Verifier(e) => e.is_retryable(),
+            _ => false,
+        }
+    }
+
+    /// Wait suggested by an underlying RPC provider, if any.
+    fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            SodsP2pError::Verifier(e) => e.retry_after(),
+            _ => None,
+        }
+    }
+}
+
 /// Result type alias for P2P operations.
 pub type Result<T> = std::result::Result<T, SodsP2pError>;
 
@@ -60,11 +181,14 @@ mod tests {
 
     #[test]
     fn test_error_display() {
-        let err = SodsP2pError::ConsensusFailure {
+        let err = SodsP2pError::ConsensusSplit {
             agreeing: 1,
             total: 3,
+            details: "1 on root 0x1111, 2 on root 0x2222".into(),
         };
         assert!(err.to_string().contains("1/3"));
+        assert!(err.to_string().contains("0x2222"));
+        assert_eq!(err.code(), "consensus_split");
     }
 
     #[test]
@@ -72,4 +196,27 @@ mod tests {
         let err = SodsP2pError::NoAvailablePeers;
         assert!(err.to_string().contains("No available peers"));
     }
+
+    #[test]
+    fn test_verifier_errors_keep_retry_info() {
+        let err = SodsP2pError::from(sods_verifier::SodsVerifierError::RateLimited {
+            retry_after: Some(std::time::Duration::from_secs(5)),
+        });
+        assert!(err.is_retryable());
+        assert_eq!(err.code(), "rate_limited");
+        assert_eq!(err.retry_after(), Some(std::time::Duration::from_secs(5)));
+        assert_eq!(err.class(), ErrorClass::Unavailable);
+        let root = sods_core::root_cause(&err);
+        assert!(root
+            .downcast_ref::<sods_verifier::SodsVerifierError>()
+            .is_some());
+
+        let err = SodsP2pError::SignatureBindingMissing { rejected: 3 };
+        assert!(!err.is_retryable());
+        let err = SodsP2pError::AllPeersTimedOut {
+            queried: 3,
+            timeout_secs: 30,
+        };
+        assert!(err.is_retryable());
+    }
 }
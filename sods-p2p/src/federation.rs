@@ -0,0 +1,285 @@
+//! Threat intelligence federation between a private mesh and the public one.
+//!
+//! A bridge node runs two peers with unrelated identities: one on the public
+//! threat topic and one inside a private (pre-shared key) mesh. Rules
+//! gossiped publicly are re-published into the private mesh, and rules
+//! authored inside it are re-published publicly only when a
+//! `FederationPolicy` allows their author. The public network only ever
+//! sees the bridge's public peer, with its own libp2p identity and an
+//! ephemeral proof signing key, so the members and topology of the private
+//! mesh stay hidden.
+
+use libp2p::Multiaddr;
+use sods_core::clock::{SharedClock, SystemClock};
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, info, warn};
+
+use crate::config::{NetworkConfig, NodeRole};
+use crate::peer::SodsPeer;
+use crate::replay::{ReplayVerdict, SeenCache};
+use crate::threats::ThreatRule;
+
+/// One of the two networks a bridge connects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mesh {
+    Public,
+    Private,
+}
+
+impl Mesh {
+    /// The other side of the bridge.
+    pub fn other(self) -> Self {
+        match self {
+            Mesh::Public => Mesh::Private,
+            Mesh::Private => Mesh::Public,
+        }
+    }
+}
+
+/// Which rules may cross the bridge.
+///
+/// By default every valid public rule is imported and nothing is exported.
+#[derive(Debug, Clone, Default)]
+pub struct FederationPolicy {
+    /// If set, only public rules by these authors (compressed public keys)
+    /// are imported
+    pub import_authors: Option<HashSet<Vec<u8>>>,
+    /// Authors whose rules may leave the private mesh
+    pub export_authors: HashSet<Vec<u8>>,
+}
+
+impl FederationPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Imports public rules by `author`, enabling import filtering (Builder pattern).
+    pub fn with_import_author(mut self, author: Vec<u8>) -> Self {
+        self.import_authors
+            .get_or_insert_with(HashSet::new)
+            .insert(author);
+        self
+    }
+
+    /// Lets rules by `author` be re-published publicly (Builder pattern).
+    pub fn with_export_author(mut self, author: Vec<u8>) -> Self {
+        self.export_authors.insert(author);
+        self
+    }
+
+    /// Whether `rule`, received from `from`, may be re-published on the
+    /// other side.
+    pub fn permits(&self, rule: &ThreatRule, from: Mesh) -> bool {
+        match from {
+            Mesh::Public => self
+                .import_authors
+                .as_ref()
+                .map_or(true, |authors| authors.contains(&rule.author_pubkey)),
+            Mesh::Private => self.export_authors.contains(&rule.author_pubkey),
+        }
+    }
+}
+
+/// Bridge settings carried in a node's `NetworkConfig`.
+#[derive(Debug, Clone, Default)]
+pub struct FederationConfig {
+    pub policy: FederationPolicy,
+    /// Public peers the bridge's public side dials on startup
+    pub public_bootstrap: Vec<Multiaddr>,
+}
+
+impl FederationConfig {
+    /// Network config of the bridge's public peer: the public network with
+    /// the private side's rate limits, and none of its keys or peer lists.
+    pub fn public_network(&self, private: &NetworkConfig) -> NetworkConfig {
+        let mut config = NetworkConfig::new()
+            .with_role(NodeRole::Full)
+            .with_rate_limits(private.rate_limits.clone());
+        config.bootstrap = self.public_bootstrap.clone();
+        config
+    }
+}
+
+/// A running peer as seen by the bridge: the rules it receives and a
+/// handle to publish into its mesh.
+pub struct MeshLink {
+    incoming: broadcast::Receiver<ThreatRule>,
+    outgoing: mpsc::UnboundedSender<ThreatRule>,
+}
+
+impl MeshLink {
+    /// Link to `peer`; take it before moving the peer into `listen`.
+    pub fn of(peer: &SodsPeer) -> Self {
+        Self {
+            incoming: peer.subscribe_threats(),
+            outgoing: peer.threat_publisher(),
+        }
+    }
+}
+
+/// Forwards threat rules between the public network and a private mesh.
+pub struct ThreatBridge {
+    policy: FederationPolicy,
+    /// Rules already bridged, in either direction
+    bridged: SeenCache,
+    local_tx: broadcast::Sender<ThreatRule>,
+    /// Dates received rules for the replay check
+    clock: SharedClock,
+}
+
+impl ThreatBridge {
+    /// Bridge rules up to `max_age` old under `policy`.
+    pub fn new(policy: FederationPolicy, max_age: Duration) -> Self {
+        let (local_tx, _) = broadcast::channel(100);
+        Self {
+            policy,
+            bridged: SeenCache::new(max_age),
+            local_tx,
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// Reads the time from `clock` instead of the system clock, e.g. the
+    /// node's `NetworkConfig::clock` (Builder pattern).
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Rules the private side should act on: those gossiped in the private
+    /// mesh and those imported from the public network.
+    pub fn subscribe(&self) -> broadcast::Receiver<ThreatRule> {
+        self.local_tx.subscribe()
+    }
+
+    /// Where `rule`, received from `from` at unix time `now`, should be
+    /// re-published, if anywhere.
+    ///
+    /// Each rule crosses at most once, so a rule coming back from the side
+    /// it was published to is not bounced back.
+    pub fn route(&mut self, rule: &ThreatRule, from: Mesh, now: u64) -> Option<Mesh> {
+        if !self.policy.permits(rule, from) {
+            return None;
+        }
+        match self.bridged.check(rule, now) {
+            ReplayVerdict::Fresh => {
+                self.bridged.prune(now);
+                Some(from.other())
+            }
+            _ => None,
+        }
+    }
+
+    /// Bridge rules until either peer stops.
+    pub async fn run(mut self, mut public: MeshLink, mut private: MeshLink) {
+        loop {
+            let (rule, from) = tokio::select! {
+                rule = public.incoming.recv() => (rule, Mesh::Public),
+                rule = private.incoming.recv() => (rule, Mesh::Private),
+            };
+            let rule = match rule {
+                Ok(rule) => rule,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!("Bridge fell behind; skipped {} {:?} rules", skipped, from);
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+
+            let now = self.clock.unix_secs();
+            let route = self.route(&rule, from, now);
+            if from == Mesh::Private || route.is_some() {
+                let _ = self.local_tx.send(rule.clone());
+            }
+            let Some(to) = route else {
+                debug!("Not bridging {:?} rule '{}'", from, rule.id);
+                continue;
+            };
+            let link = match to {
+                Mesh::Public => &public,
+                Mesh::Private => &private,
+            };
+            info!("Bridging rule '{}' into the {:?} mesh", rule.id, to);
+            if link.outgoing.send(rule).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    fn rule(id: &str, key: &SigningKey) -> ThreatRule {
+        ThreatRule::new(id, "Rug", "LP+ -> Sw -> LP-", "base", "high", key)
+    }
+
+    fn author(key: &SigningKey) -> Vec<u8> {
+        key.verifying_key().to_sec1_bytes().to_vec()
+    }
+
+    #[test]
+    fn test_routing_follows_policy() {
+        let community = SigningKey::from_slice(&[1u8; 32]).unwrap();
+        let internal = SigningKey::from_slice(&[2u8; 32]).unwrap();
+        let analyst = SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let now = rule("probe", &community).timestamp;
+
+        let policy = FederationPolicy::new().with_export_author(author(&analyst));
+        let mut bridge = ThreatBridge::new(policy, Duration::from_secs(3600));
+
+        // Public intel flows in, once
+        let public = rule("public-1", &community);
+        assert_eq!(
+            bridge.route(&public, Mesh::Public, now),
+            Some(Mesh::Private)
+        );
+        assert_eq!(bridge.route(&public, Mesh::Private, now), None);
+
+        // Only allowed authors' rules flow out
+        let shared = rule("shared-1", &analyst);
+        let internal_only = rule("internal-1", &internal);
+        assert_eq!(
+            bridge.route(&shared, Mesh::Private, now),
+            Some(Mesh::Public)
+        );
+        assert_eq!(bridge.route(&internal_only, Mesh::Private, now), None);
+        assert_eq!(bridge.route(&shared, Mesh::Public, now), None);
+    }
+
+    #[test]
+    fn test_import_authors_filter() {
+        let trusted = SigningKey::from_slice(&[4u8; 32]).unwrap();
+        let unknown = SigningKey::from_slice(&[5u8; 32]).unwrap();
+        let policy = FederationPolicy::new().with_import_author(author(&trusted));
+
+        assert!(policy.permits(&rule("a", &trusted), Mesh::Public));
+        assert!(!policy.permits(&rule("b", &unknown), Mesh::Public));
+        // Nothing is exported unless allowed
+        assert!(!policy.permits(&rule("c", &trusted), Mesh::Private));
+    }
+
+    #[test]
+    fn test_public_side_hides_private_settings() {
+        let private = NetworkConfig::new()
+            .with_private_network(crate::pnet::PreSharedKey::generate())
+            .with_role(NodeRole::ServeOnly)
+            .with_signing_key(SigningKey::from_slice(&[6u8; 32]).unwrap())
+            .with_denied_peer(libp2p::PeerId::random());
+        let federation = FederationConfig {
+            policy: FederationPolicy::new(),
+            public_bootstrap: vec!["/ip4/10.0.0.1/tcp/4001".parse().unwrap()],
+        };
+
+        let public = federation.public_network(&private);
+        assert!(public.psk.is_none());
+        assert!(public.signing_key.is_none());
+        assert!(public.denylist.is_empty());
+        assert_eq!(public.role, NodeRole::Full);
+        assert_eq!(public.bootstrap.len(), 1);
+    }
+}
@@ -0,0 +1,162 @@
+//! Per-IP cap on inbound connections.
+//!
+//! `libp2p::connection_limits` bounds connections per peer and in total,
+//! but a host can mint any number of peer IDs. This behaviour refuses an
+//! inbound connection once the remote IP already holds `max_per_ip`
+//! pending or established ones. Addresses without an IP (e.g. the memory
+//! transport) are never limited.
+
+use libp2p::core::transport::PortUse;
+use libp2p::core::Endpoint;
+use libp2p::multiaddr::Protocol;
+use libp2p::swarm::{
+    dummy, ConnectionClosed, ConnectionDenied, ConnectionId, FromSwarm, ListenFailure,
+    NetworkBehaviour, THandler, THandlerInEvent, THandlerOutEvent, ToSwarm,
+};
+use libp2p::{Multiaddr, PeerId};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::task::{Context, Poll};
+use void::Void;
+
+/// Why an inbound connection was refused.
+#[derive(Debug, thiserror::Error)]
+#[error("{ip} already holds {limit} connection(s)")]
+pub struct IpLimitExceeded {
+    pub ip: IpAddr,
+    pub limit: u32,
+}
+
+/// Refuses inbound connections from an IP that holds too many already.
+pub struct Behaviour {
+    max_per_ip: u32,
+    /// Remote IP of each pending or established inbound connection
+    connections: HashMap<ConnectionId, IpAddr>,
+}
+
+impl Behaviour {
+    pub fn new(max_per_ip: u32) -> Self {
+        Self {
+            max_per_ip,
+            connections: HashMap::new(),
+        }
+    }
+
+    /// Inbound connections currently held by `ip`.
+    pub fn connections_from(&self, ip: IpAddr) -> usize {
+        self.connections.values().filter(|&&c| c == ip).count()
+    }
+}
+
+fn ip_of(addr: &Multiaddr) -> Option<IpAddr> {
+    addr.iter().find_map(|p| match p {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
+impl NetworkBehaviour for Behaviour {
+    type ConnectionHandler = dummy::ConnectionHandler;
+    type ToSwarm = Void;
+
+    fn handle_pending_inbound_connection(
+        &mut self,
+        connection_id: ConnectionId,
+        _: &Multiaddr,
+        send_back_addr: &Multiaddr,
+    ) -> Result<(), ConnectionDenied> {
+        let Some(ip) = ip_of(send_back_addr) else {
+            return Ok(());
+        };
+        if self.connections_from(ip) >= self.max_per_ip as usize {
+            return Err(ConnectionDenied::new(IpLimitExceeded {
+                ip,
+                limit: self.max_per_ip,
+            }));
+        }
+        self.connections.insert(connection_id, ip);
+        Ok(())
+    }
+
+    fn handle_established_inbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: &Multiaddr,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        // Counted since it was pending
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn handle_established_outbound_connection(
+        &mut self,
+        _: ConnectionId,
+        _: PeerId,
+        _: &Multiaddr,
+        _: Endpoint,
+        _: PortUse,
+    ) -> Result<THandler<Self>, ConnectionDenied> {
+        Ok(dummy::ConnectionHandler)
+    }
+
+    fn on_swarm_event(&mut self, event: FromSwarm) {
+        match event {
+            FromSwarm::ConnectionClosed(ConnectionClosed { connection_id, .. })
+            | FromSwarm::ListenFailure(ListenFailure { connection_id, .. }) => {
+                self.connections.remove(&connection_id);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_connection_handler_event(
+        &mut self,
+        _: PeerId,
+        _: ConnectionId,
+        event: THandlerOutEvent<Self>,
+    ) {
+        void::unreachable(event)
+    }
+
+    fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<Self::ToSwarm, THandlerInEvent<Self>>> {
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inbound_connections_capped_per_ip() {
+        let mut limits = Behaviour::new(2);
+        let local: Multiaddr = "/ip4/0.0.0.0/tcp/4001".parse().unwrap();
+        let a: Multiaddr = "/ip4/10.0.0.1/tcp/5000".parse().unwrap();
+        let b: Multiaddr = "/ip4/10.0.0.2/tcp/5000".parse().unwrap();
+        let memory: Multiaddr = "/memory/7".parse().unwrap();
+
+        let id = ConnectionId::new_unchecked;
+        assert!(limits
+            .handle_pending_inbound_connection(id(1), &local, &a)
+            .is_ok());
+        assert!(limits
+            .handle_pending_inbound_connection(id(2), &local, &a)
+            .is_ok());
+        let denied = limits
+            .handle_pending_inbound_connection(id(3), &local, &a)
+            .unwrap_err();
+        assert!(denied.downcast_ref::<IpLimitExceeded>().is_some());
+
+        assert!(limits
+            .handle_pending_inbound_connection(id(4), &local, &b)
+            .is_ok());
+        for n in 5..10 {
+            assert!(limits
+                .handle_pending_inbound_connection(id(n), &local, &memory)
+                .is_ok());
+        }
+        assert_eq!(limits.connections_from(ip_of(&a).unwrap()), 2);
+    }
+}
@@ -8,15 +8,23 @@
 //! ## Features
 //!
 //! - **Peer Discovery**: mDNS-based local network discovery
-//! - **Proof Exchange**: Request and serve behavioral proofs
+//! - **Proof Exchange**: Request and serve behavioral proofs, singly or in batches
 //! - **Social Consensus**: Cross-check proofs from multiple peers
-//! - **Reputation Tracking**: Track peer reliability
+//! - **Capability Negotiation**: Versioned wire protocol advertised via identify
+//! - **Reputation Tracking**: Track peer reliability; bootstrap from signed snapshots
+//! - **Audit Transcripts**: Record every consensus decision as a JSON artifact
 //! - **Fallback**: Falls back to RPC if P2P fails
+//! - **Rate Limiting**: Per-peer token buckets with temporary bans
+//! - **Private Networks**: Peer allow/deny lists and pre-shared key isolation
+//! - **Node Roles**: Serve-only and query-only nodes with a reduced attack surface
+//! - **Connection Caps**: Peer, pending-dial and per-IP limits, with libp2p metrics
+//! - **Replay Protection**: Gossiped threat rules are deduplicated and age-checked
+//! - **Federation**: Bridge threat rules between a private mesh and the public network
 //!
 //! ## Quick Start
 //!
 //! ```rust,no_run
-//! use sods_p2p::{SodsClient, SodsPeer};
+//! use sods_p2p::{SodsClient, SodsPeer, Stake};
 //!
 //! #[tokio::main]
 //! async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -24,8 +32,9 @@
 //!     let mut peer = SodsPeer::new("https://sepolia.infura.io/v3/YOUR_KEY")?;
 //!     tokio::spawn(async move { peer.listen("/ip4/0.0.0.0/tcp/0").await });
 //!
-//!     // Client requests proof via P2P
-//!     let mut client = SodsClient::new()?;
+//!     // Client requests proof via P2P, vetting new peers with the
+//!     // hardest Proof-of-Behavior puzzles
+//!     let mut client = SodsClient::new()?.with_stake(Stake::High);
 //!     let result = client
 //!         .verify_via_p2p("Dep", 10002322)
 //!         .await?;
@@ -37,22 +46,48 @@
 //! }
 //! ```
 
+#![warn(clippy::unwrap_used)]
+
+pub mod batch;
 pub mod behavior;
+pub mod binding;
 pub mod bootstrappers;
 pub mod cache;
+pub mod capabilities;
 pub mod client;
+pub mod config;
 pub mod consensus;
 pub mod error;
+pub mod federation;
+pub mod ip_limits;
+pub mod metrics;
 pub mod network;
 pub mod peer;
+pub mod pnet;
 pub mod protocol;
+pub mod rate_limit;
+pub mod replay;
 pub mod reputation;
 pub mod threats;
+pub mod transcript;
 
 // Re-export main types
+pub use batch::{BatchProofRequest, BatchProofResponse, BatchReply};
+pub use binding::SignerBinding;
+pub use cache::{ProofCache, ProofCacheStats};
+pub use capabilities::{Capabilities, PeerInfo};
 pub use client::{P2pVerificationResult, SodsClient};
+pub use config::{ConnectionCaps, NetworkConfig, NodeRole};
 pub use error::SodsP2pError;
+pub use federation::{FederationConfig, FederationPolicy, MeshLink, ThreatBridge};
+pub use metrics::P2pMetrics;
 pub use peer::SodsPeer;
-pub use protocol::{ProofRequest, ProofResponse};
-pub use reputation::ReputationTracker;
-pub use threats::{ThreatRegistry, ThreatRule};
+pub use pnet::PreSharedKey;
+pub use protocol::{ProofRequest, ProofResponse, Stake};
+pub use rate_limit::{BucketLimit, RateLimitConfig};
+pub use replay::SeenCache;
+pub use reputation::{ReputationSnapshot, ReputationTracker};
+pub use threats::{
+    PackDependency, PatternPreset, RulePack, ThreatRegistry, ThreatRule, WatchlistEntry,
+};
+pub use transcript::ConsensusTranscript;
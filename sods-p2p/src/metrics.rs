@@ -0,0 +1,87 @@
+//! Prometheus metrics for the swarm.
+//!
+//! With a `P2pMetrics` in its `NetworkConfig`, each `SodsPeer` and
+//! `SodsClient` records libp2p's connection, identify and gossipsub
+//! metrics into the shared registry (connections opened and closed,
+//! dial and listen errors, gossip traffic), labelled with its peer ID so
+//! the public side of a federation bridge stays apart from the private one.
+
+use libp2p::metrics::{Metrics, Recorder, Registry};
+use libp2p::swarm::SwarmEvent;
+use libp2p::PeerId;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use crate::behavior::SodsBehaviourEvent;
+
+/// Registry shared by the swarms built with it; clones share it too.
+#[derive(Clone, Default)]
+pub struct P2pMetrics {
+    registry: Arc<Mutex<Registry>>,
+}
+
+impl fmt::Debug for P2pMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("P2pMetrics").finish_non_exhaustive()
+    }
+}
+
+impl P2pMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Metrics for the swarm of `peer`, registered under its `peer_id` label.
+    pub(crate) fn recorder(&self, peer: &PeerId) -> SwarmMetrics {
+        let mut registry = self.registry.lock().unwrap_or_else(|e| e.into_inner());
+        let label = ("peer_id".into(), peer.to_string().into());
+        SwarmMetrics(Metrics::new(registry.sub_registry_with_label(label)))
+    }
+
+    /// The metrics in the Prometheus text format, without the OpenMetrics
+    /// `# EOF` line, so they can follow other metrics in one response.
+    pub fn encode(&self) -> String {
+        let registry = self.registry.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        // Writing to a String cannot fail
+        let _ = prometheus_client::encoding::text::encode_registry(&mut out, &registry);
+        out
+    }
+}
+
+/// Records the events of one swarm.
+pub(crate) struct SwarmMetrics(Metrics);
+
+impl SwarmMetrics {
+    pub(crate) fn record(&self, event: &SwarmEvent<SodsBehaviourEvent>) {
+        match event {
+            SwarmEvent::Behaviour(SodsBehaviourEvent::Identify(event)) => self.0.record(event),
+            SwarmEvent::Behaviour(SodsBehaviourEvent::Gossipsub(event)) => self.0.record(event),
+            _ => {}
+        }
+        self.0.record(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::swarm::ConnectionId;
+
+    #[test]
+    fn test_swarm_events_are_recorded_per_peer() {
+        let metrics = P2pMetrics::new();
+        let peer = PeerId::random();
+        let recorder = metrics.recorder(&peer);
+        recorder.record(&SwarmEvent::IncomingConnection {
+            connection_id: ConnectionId::new_unchecked(1),
+            local_addr: "/ip4/127.0.0.1/tcp/1".parse().unwrap(),
+            send_back_addr: "/ip4/127.0.0.1/tcp/2".parse().unwrap(),
+        });
+
+        let text = metrics.encode();
+        assert!(text.contains("libp2p_swarm_connections_incoming"));
+        assert!(text.contains(&peer.to_string()));
+        assert!(!text.contains("# EOF"));
+    }
+}
@@ -9,30 +9,66 @@ use libp2p::{
     swarm::{Swarm, SwarmEvent},
     Multiaddr, PeerId,
 };
-use tokio::sync::broadcast;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch};
 use tracing::{debug, info, warn};
 
+use sods_core::clock::SharedClock;
 use sods_core::BehavioralMerkleTree;
-use sods_verifier::BlockVerifier;
+use sods_verifier::{BlockVerifier, SymbolVerifier};
 
+use crate::batch::{BatchProofRequest, BatchReply, MAX_BATCH_SIZE};
 use crate::behavior::{SodsBehaviour, SodsBehaviourEvent};
-use crate::cache::{BlockCache, CachedBlock};
+use crate::binding::SignerBinding;
+use crate::cache::{proof_key, BlockCache, CachedBlock, ProofCache, ProofCacheStats};
+use crate::capabilities::PeerInfo;
+use crate::config::{build_swarm, NetworkConfig, NodeRole};
 use crate::error::{Result, SodsP2pError};
-use crate::protocol::{ProofRequest, ProofResponse};
-use crate::reputation::ReputationTracker;
+use crate::metrics::SwarmMetrics;
+use crate::protocol::{
+    check_remote_pattern, is_pattern_query, verify_query, ProofRequest, ProofResponse,
+};
+use crate::rate_limit::{PeerRateLimiter, RateDecision, RequestKind};
+use crate::replay::{ReplayVerdict, SeenCache};
+use crate::reputation::{ReputationSnapshot, ReputationTracker};
 use crate::threats::{ThreatRule, THREATS_TOPIC};
 
 /// A SODS peer that serves behavioral proofs to the network.
 pub struct SodsPeer {
     swarm: Swarm<SodsBehaviour>,
-    verifier: BlockVerifier,
+    verifier: Arc<dyn SymbolVerifier>,
     reputation: ReputationTracker,
     cache: BlockCache,
+    proof_cache: ProofCache,
     local_peer_id: PeerId,
     signing_key: SigningKey,
     threat_tx: broadcast::Sender<ThreatRule>,
+    rate_limiter: PeerRateLimiter,
+    /// Configured denylist; these peers stay blocked when a ban expires
+    denylist: HashSet<PeerId>,
+    role: NodeRole,
+    /// Threat rules already accepted, for replay protection
+    seen_rules: SeenCache,
+    /// Rules to publish from outside the event loop
+    publish_tx: mpsc::UnboundedSender<ThreatRule>,
+    publish_rx: mpsc::UnboundedReceiver<ThreatRule>,
+    /// Number of connected peers, for observers outside the event loop
+    connected_tx: watch::Sender<usize>,
+    /// Latest signed reputation snapshot, for observers outside the event loop
+    reputation_tx: watch::Sender<Option<ReputationSnapshot>>,
+    metrics: Option<SwarmMetrics>,
+    /// Times rate limits, bans, the proof cache and rule freshness
+    clock: SharedClock,
 }
 
+/// How often expired rate limit bans are lifted and cache stats logged.
+const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Oldest threat rule accepted from gossip unless configured otherwise.
+const DEFAULT_RULE_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+
 impl SodsPeer {
     /// Create a new SODS peer.
     ///
@@ -40,60 +76,135 @@ impl SodsPeer {
     ///
     /// * `rpc_url` - RPC endpoint for fetching block data
     pub fn new(rpc_url: &str) -> Result<Self> {
-        let verifier = BlockVerifier::new(&[rpc_url.to_string()])?;
+        Self::with_network_config(rpc_url, &NetworkConfig::default())
+    }
+
+    /// Create a new SODS peer restricted by `config` (allow/deny lists,
+    /// private network, per-peer rate limits, node role).
+    pub fn with_network_config(rpc_url: &str, config: &NetworkConfig) -> Result<Self> {
+        // Reorg tracking lets cached proofs be dropped when their block is replaced
+        let verifier = BlockVerifier::new(&[rpc_url.to_string()])?.with_reorg_tracking();
+        Self::with_verifier(Arc::new(verifier), config)
+    }
+
+    /// Create a new SODS peer that answers requests with `verifier`, e.g.
+    /// one shared with other components or a fake chain in tests.
+    pub fn with_verifier(
+        verifier: Arc<dyn SymbolVerifier>,
+        config: &NetworkConfig,
+    ) -> Result<Self> {
         let keypair = Keypair::generate_ed25519();
         let local_peer_id = PeerId::from(keypair.public());
-        let reputation = ReputationTracker::new();
-
-        // Generate secp256k1 signing key for message signing
-        let mut seed = [0u8; 32];
-        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut seed);
-        let signing_key = SigningKey::from_slice(&seed)
-            .map_err(|e| SodsP2pError::NetworkError(format!("Key gen error: {}", e)))?;
+        let reputation = config
+            .reputation
+            .clone()
+            .unwrap_or_default()
+            .with_clock(config.clock());
+
+        // secp256k1 key for message signing, ephemeral unless configured
+        let signing_key = match &config.signing_key {
+            Some(key) => key.clone(),
+            None => {
+                let mut seed = [0u8; 32];
+                rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut seed);
+                SigningKey::from_slice(&seed)
+                    .map_err(|e| SodsP2pError::NetworkError(format!("Key gen error: {}", e)))?
+            }
+        };
 
         // Threat broadcast channel (capacity 100)
         let (threat_tx, _) = broadcast::channel(100);
+        let (publish_tx, publish_rx) = mpsc::unbounded_channel();
 
-        let mut swarm = libp2p::SwarmBuilder::with_existing_identity(keypair.clone())
-            .with_tokio()
-            .with_tcp(
-                libp2p::tcp::Config::default(),
-                libp2p::noise::Config::new,
-                libp2p::yamux::Config::default,
-            )
-            .map_err(|e| SodsP2pError::NetworkError(format!("TCP error: {}", e)))?
-            .with_behaviour(|_key| SodsBehaviour::new(&keypair))
-            .map_err(|e| SodsP2pError::NetworkError(format!("Behaviour error: {}", e)))?
-            .build();
+        // Vouch for the proof signing key with the libp2p identity
+        let binding = SignerBinding::new(&keypair, &signing_key)?;
+        let info = PeerInfo::local().with_signer(binding);
+        let mut swarm = build_swarm(keypair, config, &info)?;
 
         // Subscribe to threats topic
-        let topic = gossipsub::IdentTopic::new(THREATS_TOPIC);
-        swarm
-            .behaviour_mut()
-            .gossipsub
-            .subscribe(&topic)
-            .map_err(|e| SodsP2pError::NetworkError(format!("Subscription error: {:?}", e)))?;
+        if config.role.gossips_threats() {
+            let topic = gossipsub::IdentTopic::new(THREATS_TOPIC);
+            swarm
+                .behaviour_mut()
+                .gossipsub
+                .subscribe(&topic)
+                .map_err(|e| SodsP2pError::NetworkError(format!("Subscription error: {:?}", e)))?;
+        }
 
-        info!("Created SODS peer with ID: {}", local_peer_id);
+        info!(
+            "Created SODS peer with ID: {} (role: {})",
+            local_peer_id, config.role
+        );
 
         Ok(Self {
             swarm,
             verifier,
             reputation,
             cache: BlockCache::new(),
+            proof_cache: ProofCache::new(),
             local_peer_id,
             signing_key,
             threat_tx,
+            rate_limiter: PeerRateLimiter::new(config.rate_limits.clone()),
+            denylist: config.denylist.clone(),
+            role: config.role,
+            seen_rules: SeenCache::new(DEFAULT_RULE_MAX_AGE),
+            publish_tx,
+            publish_rx,
+            connected_tx: watch::channel(0).0,
+            reputation_tx: watch::channel(None).0,
+            metrics: config.metrics.as_ref().map(|m| m.recorder(&local_peer_id)),
+            clock: config.clock(),
         })
     }
 
+    /// Replace the proof response cache, e.g. to change its size or TTL
+    /// (Builder pattern).
+    pub fn with_proof_cache(mut self, cache: ProofCache) -> Self {
+        self.proof_cache = cache;
+        self
+    }
+
+    /// Replace the threat rule replay cache, e.g. with a persistent one
+    /// or a different maximum rule age (Builder pattern).
+    pub fn with_seen_cache(mut self, cache: SeenCache) -> Self {
+        self.seen_rules = cache;
+        self
+    }
+
     /// Get the local peer ID.
     pub fn peer_id(&self) -> &PeerId {
         &self.local_peer_id
     }
 
+    /// Role this peer was created with.
+    pub fn role(&self) -> NodeRole {
+        self.role
+    }
+
+    /// Hit/miss counters of the proof response cache.
+    pub fn proof_cache_stats(&self) -> ProofCacheStats {
+        self.proof_cache.stats()
+    }
+
+    /// Forget cached data for `block_number` and later blocks.
+    ///
+    /// Call this when a reorg is observed out of band; reorgs that change
+    /// a block's BMT root are also detected automatically.
+    pub fn invalidate_from(&mut self, block_number: u64) {
+        self.proof_cache.invalidate_from(block_number);
+        self.cache.invalidate_from(block_number);
+    }
+
     /// Publish a threat rule to the network.
     pub fn publish_threat(&mut self, rule: &ThreatRule) -> Result<()> {
+        if !self.role.gossips_threats() {
+            return Err(SodsP2pError::NetworkError(format!(
+                "A {} peer does not publish threat rules",
+                self.role
+            )));
+        }
+
         let topic = gossipsub::IdentTopic::new(THREATS_TOPIC);
         let data = serde_json::to_vec(rule)
             .map_err(|e| SodsP2pError::SerializationError(e.to_string()))?;
@@ -137,19 +248,43 @@ impl SodsPeer {
         self.threat_tx.subscribe()
     }
 
+    /// Handle for publishing threat rules once the peer is running in
+    /// `listen`.
+    pub fn threat_publisher(&self) -> mpsc::UnboundedSender<ThreatRule> {
+        self.publish_tx.clone()
+    }
+
+    /// Number of connected peers, kept up to date once the peer is running
+    /// in `listen`.
+    pub fn connected_peers(&self) -> watch::Receiver<usize> {
+        self.connected_tx.subscribe()
+    }
+
+    /// Signed snapshot of this peer's reputation observations, refreshed
+    /// every maintenance tick once the peer is running in `listen`.
+    pub fn reputation_snapshots(&self) -> watch::Receiver<Option<ReputationSnapshot>> {
+        self.reputation_tx.subscribe()
+    }
+
     /// Connect to bootstrap nodes.
     pub async fn connect_bootstrap(&mut self, addrs: &[Multiaddr]) -> Result<()> {
         for addr in addrs {
             info!("Dialing bootstrap node: {}", addr);
             self.swarm
                 .dial(addr.clone())
-                .map_err(|e| SodsP2pError::NetworkError(format!("Dial error: {}", e)))?;
+                .map_err(|e| SodsP2pError::DialFailed {
+                    addr: addr.clone(),
+                    reason: e.to_string(),
+                })?;
         }
         Ok(())
     }
 
     /// Start listening on the specified address.
     ///
+    /// A query-only peer opens no listener and only runs the event loop
+    /// for its outbound connections.
+    ///
     /// # Arguments
     ///
     /// * `addr` - Multiaddr to listen on (e.g., "/ip4/0.0.0.0/tcp/0")
@@ -158,15 +293,44 @@ impl SodsPeer {
             .parse()
             .map_err(|e| SodsP2pError::NetworkError(format!("Invalid address: {}", e)))?;
 
-        self.swarm
-            .listen_on(addr)
-            .map_err(|e| SodsP2pError::NetworkError(format!("Listen error: {}", e)))?;
+        if self.role.listens() {
+            self.swarm
+                .listen_on(addr.clone())
+                .map_err(|e| SodsP2pError::ListenFailed {
+                    addr,
+                    reason: e.to_string(),
+                })?;
+            info!("Peer {} listening...", self.local_peer_id);
+        } else {
+            info!("Peer {} running without a listener", self.local_peer_id);
+        }
 
-        info!("Peer {} listening...", self.local_peer_id);
+        let mut maintenance = tokio::time::interval(MAINTENANCE_INTERVAL);
 
         // Event loop
         loop {
-            match self.swarm.select_next_some().await {
+            let event = tokio::select! {
+                event = self.swarm.select_next_some() => event,
+                _ = maintenance.tick() => {
+                    self.lift_expired_bans();
+                    if self.reputation_tx.receiver_count() > 0 {
+                        let snapshot = self.reputation.snapshot(&self.signing_key);
+                        self.reputation_tx.send_replace(Some(snapshot));
+                    }
+                    debug!("Proof cache: {:?}", self.proof_cache.stats());
+                    continue;
+                }
+                Some(rule) = self.publish_rx.recv() => {
+                    if let Err(e) = self.publish_threat(&rule) {
+                        warn!("Failed to publish threat rule '{}': {}", rule.id, e);
+                    }
+                    continue;
+                }
+            };
+            if let Some(metrics) = &self.metrics {
+                metrics.record(&event);
+            }
+            match event {
                 SwarmEvent::NewListenAddr { address, .. } => {
                     info!("Listening on {}", address);
                 }
@@ -179,14 +343,21 @@ impl SodsPeer {
                 SwarmEvent::Behaviour(SodsBehaviourEvent::Puzzle(event)) => {
                     self.handle_puzzle_event(event).await;
                 }
+                SwarmEvent::Behaviour(SodsBehaviourEvent::Batch(event)) => {
+                    self.handle_batch_event(event).await;
+                }
                 SwarmEvent::Behaviour(SodsBehaviourEvent::Gossipsub(event)) => {
                     self.handle_gossip_event(event);
                 }
                 SwarmEvent::ConnectionEstablished { peer_id, .. } => {
                     debug!("Connected to peer: {}", peer_id);
+                    self.connected_tx
+                        .send_replace(self.swarm.connected_peers().count());
                 }
                 SwarmEvent::ConnectionClosed { peer_id, .. } => {
                     debug!("Disconnected from peer: {}", peer_id);
+                    self.connected_tx
+                        .send_replace(self.swarm.connected_peers().count());
                 }
                 _ => {}
             }
@@ -194,28 +365,71 @@ impl SodsPeer {
     }
 
     /// Handle gossipsub events.
+    ///
+    /// Every message is reported back to gossipsub: only fresh, valid rules
+    /// are forwarded to other peers, and invalid ones count against the
+    /// sender's score.
     fn handle_gossip_event(&mut self, event: gossipsub::Event) {
         if let gossipsub::Event::Message {
             propagation_source,
-            message_id: _,
+            message_id,
             message,
         } = event
         {
-            if let Ok(rule) = serde_json::from_slice::<ThreatRule>(&message.data) {
-                info!(
-                    "Received threat rule '{}' from {}",
-                    rule.id, propagation_source
-                );
+            let acceptance = match serde_json::from_slice::<ThreatRule>(&message.data) {
+                Ok(rule) => self.accept_threat_rule(rule, &propagation_source),
+                Err(_) => {
+                    warn!("Received malformed gossip message");
+                    gossipsub::MessageAcceptance::Reject
+                }
+            };
+            if let Err(e) = self
+                .swarm
+                .behaviour_mut()
+                .gossipsub
+                .report_message_validation_result(&message_id, &propagation_source, acceptance)
+            {
+                debug!("Failed to forward gossip message {}: {:?}", message_id, e);
+            }
+        }
+    }
 
-                // Validate rule
-                if rule.verify() {
-                    // Forward to local listeners
-                    let _ = self.threat_tx.send(rule);
-                } else {
-                    warn!("Received INVALID threat rule from {}", propagation_source);
+    /// Validates a gossiped rule and forwards it to local listeners if it
+    /// is new.
+    fn accept_threat_rule(
+        &mut self,
+        rule: ThreatRule,
+        source: &PeerId,
+    ) -> gossipsub::MessageAcceptance {
+        info!("Received threat rule '{}' from {}", rule.id, source);
+
+        // Validate rule
+        if !rule.verify() {
+            warn!("Received INVALID threat rule from {}", source);
+            return gossipsub::MessageAcceptance::Reject;
+        }
+
+        let now = self.clock.unix_secs();
+        match self.seen_rules.check(&rule, now) {
+            ReplayVerdict::Fresh => {
+                self.seen_rules.prune(now);
+                if let Err(e) = self.seen_rules.save() {
+                    warn!("Failed to persist seen threat rules: {}", e);
                 }
-            } else {
-                warn!("Received malformed gossip message");
+                // Forward to local listeners
+                let _ = self.threat_tx.send(rule);
+                gossipsub::MessageAcceptance::Accept
+            }
+            ReplayVerdict::Duplicate => {
+                debug!("Ignoring already seen threat rule '{}'", rule.id);
+                gossipsub::MessageAcceptance::Ignore
+            }
+            verdict => {
+                warn!(
+                    "Rejecting replayed threat rule '{}' from {} ({:?})",
+                    rule.id, source, verdict
+                );
+                gossipsub::MessageAcceptance::Reject
             }
         }
     }
@@ -260,7 +474,11 @@ impl SodsPeer {
                     request, channel, ..
                 } => {
                     info!("Received request from {}: {:?}", peer, request);
-                    let response = self.handle_proof_request(request).await;
+                    let response = if self.admit(&peer, RequestKind::Proof) {
+                        self.handle_proof_request(request).await
+                    } else {
+                        ProofResponse::error_signed("Rate limit exceeded", &self.signing_key)
+                    };
 
                     if let Err(e) = self
                         .swarm
@@ -287,26 +505,143 @@ impl SodsPeer {
         }
     }
 
-    /// Handle an incoming proof request.
+    /// Handle batched proof request events.
+    async fn handle_batch_event(
+        &mut self,
+        event: request_response::Event<BatchProofRequest, BatchReply>,
+    ) {
+        use request_response::Event;
+
+        match event {
+            Event::Message {
+                peer,
+                message:
+                    request_response::Message::Request {
+                        request, channel, ..
+                    },
+            } => {
+                info!(
+                    "Received batch of {} proof requests from {}",
+                    request.items.len(),
+                    peer
+                );
+                // The whole batch is charged up front, one token per item
+                let admitted = self.admit_n(&peer, RequestKind::BatchItem, request.items.len());
+
+                // The connection writes each response as soon as it is sent
+                let (mut items, reply) = futures::channel::mpsc::channel(MAX_BATCH_SIZE);
+                if let Err(e) = self
+                    .swarm
+                    .behaviour_mut()
+                    .batch
+                    .send_response(channel, BatchReply::Streaming(reply))
+                {
+                    warn!("Failed to send batch response: {:?}", e);
+                    return;
+                }
+                for item in request.items {
+                    let response = if admitted {
+                        self.handle_proof_request(item).await
+                    } else {
+                        ProofResponse::error_signed("Rate limit exceeded", &self.signing_key)
+                    };
+                    if items.try_send(response).is_err() {
+                        debug!("Batch stream to {} closed early", peer);
+                        break;
+                    }
+                }
+            }
+            Event::InboundFailure { peer, error, .. } => {
+                warn!("Batch inbound failure from {}: {:?}", peer, error);
+            }
+            _ => {}
+        }
+    }
+
+    /// Charges a request against `peer`'s rate limit, banning it on abuse.
+    ///
+    /// A ban blocks the peer at the swarm level, which also closes its
+    /// open connections.
+    fn admit(&mut self, peer: &PeerId, kind: RequestKind) -> bool {
+        self.admit_n(peer, kind, 1)
+    }
+
+    /// Charges `cost` requests at once, e.g. the items of a batch.
+    fn admit_n(&mut self, peer: &PeerId, kind: RequestKind, cost: usize) -> bool {
+        let now = self.clock.instant();
+        let cost = u32::try_from(cost).unwrap_or(u32::MAX);
+        match self.rate_limiter.check_n(peer, kind, cost, now) {
+            RateDecision::Allowed => true,
+            RateDecision::Limited => {
+                debug!("Rate limited {:?} request from {}", kind, peer);
+                false
+            }
+            RateDecision::Banned(until) => {
+                warn!(
+                    "Banning peer {} for {}s after repeated rate limit violations",
+                    peer,
+                    until.saturating_duration_since(now).as_secs()
+                );
+                self.swarm.behaviour_mut().blocked_peers.block_peer(*peer);
+                false
+            }
+        }
+    }
+
+    /// Unblocks peers whose rate limit ban has run out.
+    fn lift_expired_bans(&mut self) {
+        for peer in self.rate_limiter.expire_bans(self.clock.instant()) {
+            if !self.denylist.contains(&peer) {
+                info!("Ban on peer {} expired", peer);
+                self.swarm.behaviour_mut().blocked_peers.unblock_peer(peer);
+            }
+        }
+    }
+
+    /// Handle an incoming proof request, serving repeated queries from the
+    /// proof cache.
     async fn handle_proof_request(&mut self, request: ProofRequest) -> ProofResponse {
+        let key = proof_key(request.block_number, &request.symbol);
+        if let Some(response) = self.proof_cache.get(&key, self.clock.instant()) {
+            debug!(
+                "Proof cache hit for {} in block {}",
+                request.symbol, request.block_number
+            );
+            return response;
+        }
+
+        let response = self.compute_proof_response(request).await;
+        self.proof_cache
+            .insert(key, response.clone(), self.clock.instant());
+        response
+    }
+
+    /// Build a signed response from the block cache or via Layer 1.
+    async fn compute_proof_response(&mut self, request: ProofRequest) -> ProofResponse {
         let ProofRequest {
             symbol,
             block_number,
         } = request;
 
-        // Check cache first (clone to avoid borrow issues)
-        if let Some(cached) = self.cache.get(block_number).cloned() {
-            debug!("Cache hit for block {}", block_number);
-            return self.generate_proof_from_cache(&cached, &symbol);
+        // Check cache first (clone to avoid borrow issues); it holds
+        // single symbols only
+        if is_pattern_query(&symbol) {
+            if let Err(reason) = check_remote_pattern(&symbol) {
+                return ProofResponse::error_signed(reason, &self.signing_key);
+            }
+        } else {
+            if let Some(cached) = self.cache.get(block_number).cloned() {
+                debug!("Cache hit for block {}", block_number);
+                return self.generate_proof_from_cache(&cached, &symbol);
+            }
         }
 
         // Fetch and verify via Layer 1
-        match self
-            .verifier
-            .verify_symbol_in_block(&symbol, block_number)
-            .await
-        {
+        match verify_query(self.verifier.as_ref(), &symbol, block_number).await {
             Ok(result) => {
+                if let Some(reorg) = &result.reorg {
+                    self.observe_reorgs(&reorg.history);
+                }
                 if result.is_verified {
                     let root = result
                         .merkle_root
@@ -334,6 +669,14 @@ impl SodsPeer {
         }
     }
 
+    /// Drop cached data invalidated by reorgs the verifier has observed.
+    fn observe_reorgs(&mut self, history: &[sods_verifier::ReorgEvent]) {
+        if let Some(from) = self.proof_cache.observe_reorgs(history) {
+            info!("Reorg down to block {}: dropping cached proofs", from);
+            self.cache.invalidate_from(from);
+        }
+    }
+
     /// Generate proof from cached block data.
     fn generate_proof_from_cache(&self, cached: &CachedBlock, symbol: &str) -> ProofResponse {
         if !cached.has_symbol(symbol) {
@@ -376,7 +719,22 @@ impl SodsPeer {
                     request, channel, ..
                 } => {
                     info!("Received PoB challenge from {}: {:?}", peer, request);
-                    let response = self.solve_puzzle(request).await;
+                    let response = if !request.is_within_limits() {
+                        warn!("Refusing oversized PoB challenge from {}", peer);
+                        crate::protocol::PuzzleSolution::failed(
+                            &request,
+                            &self.local_peer_id,
+                            &self.signing_key,
+                        )
+                    } else if self.admit(&peer, RequestKind::Puzzle) {
+                        self.solve_puzzle(request).await
+                    } else {
+                        crate::protocol::PuzzleSolution::failed(
+                            &request,
+                            &self.local_peer_id,
+                            &self.signing_key,
+                        )
+                    };
 
                     if let Err(e) = self
                         .swarm
@@ -397,24 +755,24 @@ impl SodsPeer {
         }
     }
 
-    /// Solve a Proof-of-Behavior puzzle.
+    /// Solve a Proof-of-Behavior puzzle, signing the solution with our
+    /// peer id and the challenge nonce.
     async fn solve_puzzle(
         &mut self,
         challenge: crate::protocol::PuzzleChallenge,
     ) -> crate::protocol::PuzzleSolution {
-        match self
-            .verifier
-            .verify_symbol_in_block(&challenge.symbol, challenge.block_number)
-            .await
-        {
-            Ok(result) => crate::protocol::PuzzleSolution {
-                occurrences: result.occurrences as u32,
-                success: true,
-            },
-            Err(_) => crate::protocol::PuzzleSolution {
-                occurrences: 0,
-                success: false,
-            },
+        match challenge.count_cells(&self.verifier).await {
+            Ok(counts) => crate::protocol::PuzzleSolution::signed(
+                &challenge,
+                &self.local_peer_id,
+                counts,
+                &self.signing_key,
+            ),
+            Err(_) => crate::protocol::PuzzleSolution::failed(
+                &challenge,
+                &self.local_peer_id,
+                &self.signing_key,
+            ),
         }
     }
 }
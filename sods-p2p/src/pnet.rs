@@ -0,0 +1,184 @@
+//! Private network transport (pre-shared key).
+//!
+//! Peers started with the same `PreSharedKey` wrap every connection in the
+//! libp2p `pnet` transport upgrade (XSalsa20 under the key) before the Noise
+//! handshake. Peers without the key cannot complete the handshake, so a
+//! private SODS mesh stays invisible to the public one. This wrapper keeps
+//! the key out of debug output and logs.
+//!
+//! Keys use the go-ipfs swarm key file format:
+//!
+//! ```text
+//! /key/swarm/psk/1.0.0/
+//! /base16/
+//! <64 hex characters>
+//! ```
+
+use libp2p::pnet::PnetConfig;
+use rand::RngCore;
+use std::fmt;
+use std::str::FromStr;
+
+const KEY_SIZE: usize = 32;
+const KEY_CODEC: &str = "/key/swarm/psk/1.0.0/";
+const KEY_ENCODING: &str = "/base16/";
+
+/// A 256-bit key shared by all members of a private network.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PreSharedKey([u8; KEY_SIZE]);
+
+impl PreSharedKey {
+    pub fn new(key: [u8; KEY_SIZE]) -> Self {
+        Self(key)
+    }
+
+    /// Generates a fresh random key.
+    pub fn generate() -> Self {
+        let mut key = [0u8; KEY_SIZE];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self(key)
+    }
+
+    /// Identifier that is safe to log; matches the go-libp2p fingerprint.
+    pub fn fingerprint(&self) -> String {
+        libp2p::pnet::PreSharedKey::new(self.0)
+            .fingerprint()
+            .to_string()
+    }
+
+    /// The libp2p `pnet` upgrade for this key.
+    pub(crate) fn config(&self) -> PnetConfig {
+        PnetConfig::new(libp2p::pnet::PreSharedKey::new(self.0))
+    }
+}
+
+// Never print the key itself in debug output
+impl fmt::Debug for PreSharedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PreSharedKey")
+            .field(&self.fingerprint())
+            .finish()
+    }
+}
+
+/// Renders the swarm key file format.
+impl fmt::Display for PreSharedKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", KEY_CODEC)?;
+        writeln!(f, "{}", KEY_ENCODING)?;
+        write!(f, "{}", hex::encode(self.0))
+    }
+}
+
+impl FromStr for PreSharedKey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next() != Some(KEY_CODEC) {
+            return Err(format!("Expected key codec '{}'", KEY_CODEC));
+        }
+        if lines.next() != Some(KEY_ENCODING) {
+            return Err(format!("Expected key encoding '{}'", KEY_ENCODING));
+        }
+        let bytes = lines
+            .next()
+            .and_then(|l| hex::decode(l).ok())
+            .ok_or_else(|| "Missing or invalid hex key".to_string())?;
+        let key: [u8; KEY_SIZE] = bytes
+            .try_into()
+            .map_err(|_| format!("Key must be {} bytes", KEY_SIZE))?;
+        Ok(Self(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+    use std::io;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    #[test]
+    fn test_key_file_roundtrip() {
+        let key = PreSharedKey::generate();
+        let parsed: PreSharedKey = key.to_string().parse().unwrap();
+        assert_eq!(parsed, key);
+        assert!(!format!("{:?}", key).contains(&hex::encode(key.0)));
+
+        assert!("/key/swarm/psk/1.0.0/\n/base16/\nabcd"
+            .parse::<PreSharedKey>()
+            .is_err());
+        assert!("garbage".parse::<PreSharedKey>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handshake_requires_same_key() {
+        async fn exchange(a: PreSharedKey, b: PreSharedKey) -> Vec<u8> {
+            let (left, right) = tokio::io::duplex(64);
+            let (l, r) = futures::join!(
+                a.config().handshake(Compat(left)),
+                b.config().handshake(Compat(right))
+            );
+            let (mut l, mut r) = (l.unwrap(), r.unwrap());
+
+            let message = vec![7u8; 200];
+            let (_, received) = futures::join!(
+                async {
+                    l.write_all(&message).await.unwrap();
+                    l.flush().await.unwrap();
+                },
+                async {
+                    let mut buf = vec![0u8; 200];
+                    r.read_exact(&mut buf).await.unwrap();
+                    buf
+                }
+            );
+            received
+        }
+
+        let key = PreSharedKey::new([1u8; 32]);
+        assert_eq!(exchange(key, key).await, vec![7u8; 200]);
+        assert_ne!(
+            exchange(key, PreSharedKey::new([2u8; 32])).await,
+            vec![7u8; 200]
+        );
+    }
+
+    /// Minimal tokio -> futures io adapter for the in-memory duplex pipe.
+    struct Compat(tokio::io::DuplexStream);
+
+    impl AsyncRead for Compat {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let mut read_buf = tokio::io::ReadBuf::new(buf);
+            match tokio::io::AsyncRead::poll_read(Pin::new(&mut self.0), cx, &mut read_buf) {
+                Poll::Ready(Ok(())) => Poll::Ready(Ok(read_buf.filled().len())),
+                Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+                Poll::Pending => Poll::Pending,
+            }
+        }
+    }
+
+    impl AsyncWrite for Compat {
+        fn poll_write(
+            mut self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+            buf: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            tokio::io::AsyncWrite::poll_write(Pin::new(&mut self.0), cx, buf)
+        }
+
+        fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            tokio::io::AsyncWrite::poll_flush(Pin::new(&mut self.0), cx)
+        }
+
+        fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+            tokio::io::AsyncWrite::poll_shutdown(Pin::new(&mut self.0), cx)
+        }
+    }
+}
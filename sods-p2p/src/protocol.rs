@@ -1,41 +1,202 @@
 //! Protocol types for P2P proof exchange with cryptographic signing.
 
+use std::sync::Arc;
+
+use futures::{StreamExt, TryStreamExt};
 use k256::ecdsa::{signature::Signer, signature::Verifier, Signature, SigningKey, VerifyingKey};
+use libp2p::PeerId;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use sods_verifier::{QueryParser, SymbolVerifier, VerificationResult};
 
 /// Protocol name for SODS proof exchange.
 pub const PROTOCOL_NAME: &str = "/sods/proof/1.0.0";
 
 /// Protocol name for SODS Proof-of-Behavior puzzles.
-pub const PUZZLE_PROTOCOL_NAME: &str = "/sods/puzzle/1.0.0";
+pub const PUZZLE_PROTOCOL_NAME: &str = "/sods/puzzle/2.0.0";
+
+/// Protocol name for batched proof requests.
+pub const BATCH_PROTOCOL_NAME: &str = "/sods/proof-batch/1.0.0";
 
 /// Request for a behavioral proof.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProofRequest {
-    /// The symbol to verify (e.g., "Tf", "Dep")
+    /// The symbol to verify (e.g., "Tf", "Dep"), or a pattern for peers
+    /// with the `patterns` capability (e.g., "Tf -> Sw")
     pub symbol: String,
     /// The block number to query
     pub block_number: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Whether `query` is a pattern rather than a single symbol.
+pub fn is_pattern_query(query: &str) -> bool {
+    !QueryParser::new().is_supported(query)
+}
+
+/// Most steps a peer matches in a pattern it was sent.
+pub const MAX_REMOTE_PATTERN_STEPS: usize = 4;
+
+/// Checks that a pattern sent by another node is cheap enough to serve
+/// and means the same here as there: at most `MAX_REMOTE_PATTERN_STEPS`
+/// steps, and accepted by `BehavioralPattern::parse_untrusted`.
+pub fn check_remote_pattern(query: &str) -> Result<(), String> {
+    let pattern =
+        sods_core::pattern::BehavioralPattern::parse_untrusted(query).map_err(|e| e.to_string())?;
+    if pattern.steps().len() > MAX_REMOTE_PATTERN_STEPS {
+        return Err(format!(
+            "Pattern too complex to serve (max {} steps)",
+            MAX_REMOTE_PATTERN_STEPS
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies a symbol or pattern query in `block_number`, trustlessly for
+/// patterns so every peer sees the same symbols.
+pub(crate) async fn verify_query(
+    verifier: &dyn SymbolVerifier,
+    query: &str,
+    block_number: u64,
+) -> sods_verifier::error::Result<VerificationResult> {
+    if is_pattern_query(query) {
+        verifier.verify_pattern_trustless(query, block_number).await
+    } else {
+        verifier.verify_symbol_in_block(query, block_number).await
+    }
+}
+
+/// Most blocks a solver accepts in one puzzle.
+pub const MAX_PUZZLE_BLOCKS: usize = 16;
+
+/// Most symbols a solver accepts in one puzzle.
+pub const MAX_PUZZLE_SYMBOLS: usize = 4;
+
+/// Most symbol lookups a puzzle runs at once, so a `High` puzzle does not
+/// wait on its cells one by one nor flood the RPC endpoint.
+pub const PUZZLE_RPC_CONCURRENCY: usize = 8;
+
+/// Symbols puzzles ask about.
+const PUZZLE_SYMBOLS: &[&str] = &["Tf", "Sw", "Dep", "Wdw", "LP+", "LP-"];
+
+/// Domain separator for the signed puzzle solution.
+const SOLUTION_DOMAIN: &[u8] = b"sods-puzzle-solution/v1";
+
+/// What the client has riding on a peer; higher stakes get harder puzzles.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Stake {
+    /// Discovery and routine queries
+    #[default]
+    Low,
+    Medium,
+    /// Decisions that move funds or trigger alerts
+    High,
+}
+
+impl Stake {
+    /// Blocks and symbols per block a puzzle covers at this stake.
+    pub fn difficulty(self) -> (usize, usize) {
+        match self {
+            Stake::Low => (1, 1),
+            Stake::Medium => (4, 2),
+            Stake::High => (MAX_PUZZLE_BLOCKS, MAX_PUZZLE_SYMBOLS),
+        }
+    }
+}
+
+/// Proof-of-Behavior challenge: count `symbols` in each of `blocks`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PuzzleChallenge {
     pub chain_id: u64,
-    pub block_number: u64,
-    pub symbol: String,
+    pub blocks: Vec<u64>,
+    pub symbols: Vec<String>,
+    /// Fresh for every challenge and signed into the solution, so a
+    /// solution answers this challenge only
+    pub nonce: [u8; 32],
 }
 
 impl PuzzleChallenge {
+    /// A low-stake challenge.
     pub fn random() -> Self {
+        Self::for_stake(Stake::Low)
+    }
+
+    /// A challenge sized for `stake`, on distinct random blocks.
+    pub fn for_stake(stake: Stake) -> Self {
+        use rand::seq::{index, SliceRandom};
         use rand::Rng;
+
         let mut rng = rand::thread_rng();
+        let (blocks, symbols) = stake.difficulty();
+        let mut blocks: Vec<u64> = index::sample(&mut rng, 1000, blocks)
+            .into_iter()
+            .map(|offset| 10002000 + offset as u64)
+            .collect();
+        blocks.sort_unstable();
         Self {
             chain_id: 11155111, // Sepolia
-            block_number: 10002000 + rng.gen_range(0..1000),
-            symbol: "Tf".to_string(),
+            blocks,
+            symbols: PUZZLE_SYMBOLS
+                .choose_multiple(&mut rng, symbols)
+                .map(|s| s.to_string())
+                .collect(),
+            nonce: rng.gen(),
         }
     }
+
+    /// Whether a solver should take this on; bounds the RPC work a
+    /// challenger can demand.
+    pub fn is_within_limits(&self) -> bool {
+        (1..=MAX_PUZZLE_BLOCKS).contains(&self.blocks.len())
+            && (1..=MAX_PUZZLE_SYMBOLS).contains(&self.symbols.len())
+    }
+
+    /// The (block, symbol) pairs to count, in solution order.
+    pub fn cells(&self) -> impl Iterator<Item = (u64, &str)> + '_ {
+        self.blocks.iter().flat_map(move |&block| {
+            self.symbols
+                .iter()
+                .map(move |symbol| (block, symbol.as_str()))
+        })
+    }
+
+    /// Occurrences of each cell in `cells` order, looked up with at most
+    /// `PUZZLE_RPC_CONCURRENCY` requests in flight.
+    pub(crate) async fn count_cells(
+        &self,
+        verifier: &Arc<dyn SymbolVerifier>,
+    ) -> sods_verifier::error::Result<Vec<u32>> {
+        let cells: Vec<(u64, String)> = self
+            .cells()
+            .map(|(block, symbol)| (block, symbol.to_string()))
+            .collect();
+        futures::stream::iter(cells)
+            .map(|(block, symbol)| {
+                let verifier = Arc::clone(verifier);
+                async move {
+                    verifier
+                        .verify_symbol_in_block(&symbol, block)
+                        .await
+                        .map(|result| result.occurrences as u32)
+                }
+            })
+            .buffered(PUZZLE_RPC_CONCURRENCY)
+            .try_collect()
+            .await
+    }
+
+    fn hash_into(&self, hasher: &mut Sha256) {
+        hasher.update(self.chain_id.to_le_bytes());
+        hasher.update((self.blocks.len() as u64).to_le_bytes());
+        for block in &self.blocks {
+            hasher.update(block.to_le_bytes());
+        }
+        hasher.update((self.symbols.len() as u64).to_le_bytes());
+        for symbol in &self.symbols {
+            hasher.update((symbol.len() as u64).to_le_bytes());
+            hasher.update(symbol.as_bytes());
+        }
+        hasher.update(self.nonce);
+    }
 }
 
 /// A behavioral puzzle with timing information.
@@ -47,32 +208,126 @@ pub struct BehavioralPuzzle {
 }
 
 impl BehavioralPuzzle {
+    /// Allows 30 seconds, plus one for every count beyond the first.
     pub fn new(challenge: PuzzleChallenge) -> Self {
+        Self::issued_at(challenge, std::time::SystemTime::now())
+    }
+
+    /// A puzzle issued at `issued_at` rather than now.
+    pub fn issued_at(challenge: PuzzleChallenge, issued_at: std::time::SystemTime) -> Self {
+        let extra = challenge.cells().count().saturating_sub(1) as u64;
         Self {
             challenge,
-            issued_at: std::time::SystemTime::now(),
-            expires_after: std::time::Duration::from_secs(30),
+            issued_at,
+            expires_after: std::time::Duration::from_secs(30 + extra),
         }
     }
 
     pub fn is_expired(&self) -> bool {
-        self.issued_at
-            .elapsed()
+        self.is_expired_at(std::time::SystemTime::now())
+    }
+
+    /// Whether the puzzle has expired as of `now`.
+    pub fn is_expired_at(&self, now: std::time::SystemTime) -> bool {
+        now.duration_since(self.issued_at)
             .map(|elapsed| elapsed > self.expires_after)
             .unwrap_or(true) // Treat clock errors as expired
     }
 }
 
-/// A solution to a Proof-of-Behavior puzzle.
+/// A solution to a Proof-of-Behavior puzzle, signed by the solver.
+///
+/// The signature covers the solver's peer id and the whole challenge,
+/// nonce included, so a solution cannot be replayed against another
+/// challenge or passed off by another peer.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PuzzleSolution {
-    pub occurrences: u32,
+    /// Occurrences per (block, symbol), in `PuzzleChallenge::cells` order
+    pub counts: Vec<u32>,
     /// Success indicator
     pub success: bool,
+    /// Peer id of the solver
+    pub solver: Vec<u8>,
+    /// Nonce of the challenge answered
+    pub nonce: [u8; 32],
+    /// ECDSA signature (64 bytes) over the solution hash
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+    /// Compressed public key (33 bytes) of the signer
+    #[serde(with = "serde_bytes")]
+    pub public_key: Vec<u8>,
+}
+
+impl PuzzleSolution {
+    /// Create a solution to `challenge` signed by `solver`'s key.
+    pub fn signed(
+        challenge: &PuzzleChallenge,
+        solver: &PeerId,
+        counts: Vec<u32>,
+        signing_key: &SigningKey,
+    ) -> Self {
+        let mut solution = Self {
+            counts,
+            success: true,
+            solver: solver.to_bytes(),
+            nonce: challenge.nonce,
+            signature: Vec::new(),
+            public_key: Vec::new(),
+        };
+        solution.sign(challenge, signing_key);
+        solution
+    }
+
+    /// Create a signed refusal, e.g. when rate limited.
+    pub fn failed(challenge: &PuzzleChallenge, solver: &PeerId, signing_key: &SigningKey) -> Self {
+        let mut solution = Self::signed(challenge, solver, Vec::new(), signing_key);
+        solution.success = false;
+        solution.sign(challenge, signing_key);
+        solution
+    }
+
+    fn compute_hash(&self, challenge: &PuzzleChallenge) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(SOLUTION_DOMAIN);
+        challenge.hash_into(&mut hasher);
+        hasher.update((self.solver.len() as u64).to_le_bytes());
+        hasher.update(&self.solver);
+        hasher.update(self.nonce);
+        hasher.update([self.success as u8]);
+        for count in &self.counts {
+            hasher.update(count.to_le_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    fn sign(&mut self, challenge: &PuzzleChallenge, signing_key: &SigningKey) {
+        let signature: Signature = signing_key.sign(&self.compute_hash(challenge));
+        self.signature = signature.to_bytes().to_vec();
+        self.public_key = signing_key.verifying_key().to_sec1_bytes().to_vec();
+    }
+
+    /// Whether this answers `challenge`, names `solver` and is signed by
+    /// `signer` (compressed public key), e.g. from the solver's
+    /// `SignerBinding`.
+    pub fn verify(&self, challenge: &PuzzleChallenge, solver: &PeerId, signer: &[u8]) -> bool {
+        if self.nonce != challenge.nonce
+            || self.solver != solver.to_bytes()
+            || self.public_key != signer
+        {
+            return false;
+        }
+        let Ok(sig) = Signature::from_slice(&self.signature) else {
+            return false;
+        };
+        let Ok(pubkey) = VerifyingKey::from_sec1_bytes(&self.public_key) else {
+            return false;
+        };
+        pubkey.verify(&self.compute_hash(challenge), &sig).is_ok()
+    }
 }
 
 /// Response containing a behavioral proof with cryptographic signature.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ProofResponse {
     /// Serialized sods_core::Proof bytes
     pub proof_bytes: Vec<u8>,
@@ -237,6 +492,14 @@ mod tests {
         assert_eq!(decoded.block_number, 10002322);
     }
 
+    #[test]
+    fn test_remote_pattern_limits() {
+        assert!(check_remote_pattern("Tf -> Sw -> Tf").is_ok());
+        assert!(check_remote_pattern("Tf -> Sw -> Tf -> Sw -> Tf").is_err());
+        assert!(check_remote_pattern("Tf($a -> $b) -> Tf($b -> $a)").is_ok());
+        assert!(check_remote_pattern("Tf($a -> $b) -> Tf($c -> $d)").is_err());
+    }
+
     #[test]
     fn test_response_success() {
         let resp = ProofResponse::success(vec![1, 2, 3], [0xAB; 32], 5);
@@ -276,6 +539,55 @@ mod tests {
         assert!(!resp.verify_signature());
     }
 
+    #[test]
+    fn test_puzzle_difficulty_scales_with_stake() {
+        let low = PuzzleChallenge::random();
+        assert_eq!((low.blocks.len(), low.symbols.len()), (1, 1));
+
+        let high = PuzzleChallenge::for_stake(Stake::High);
+        assert!(high.is_within_limits());
+        assert_eq!(high.cells().count(), MAX_PUZZLE_BLOCKS * MAX_PUZZLE_SYMBOLS);
+        assert!(high.blocks.windows(2).all(|w| w[0] < w[1]));
+        assert_ne!(high.nonce, low.nonce);
+        assert!(
+            BehavioralPuzzle::new(high).expires_after > BehavioralPuzzle::new(low).expires_after
+        );
+
+        let mut oversized = PuzzleChallenge::for_stake(Stake::High);
+        oversized.blocks.push(1);
+        assert!(!oversized.is_within_limits());
+    }
+
+    #[test]
+    fn test_solution_bound_to_solver_and_challenge() {
+        let mut seed = [0u8; 32];
+        rand::thread_rng().fill(&mut seed);
+        let signing_key = SigningKey::from_slice(&seed).unwrap();
+        let signer = signing_key.verifying_key().to_sec1_bytes().to_vec();
+        let solver = PeerId::random();
+        let challenge = PuzzleChallenge::for_stake(Stake::Medium);
+
+        let solution = PuzzleSolution::signed(&challenge, &solver, vec![3; 8], &signing_key);
+        assert!(solution.verify(&challenge, &solver, &signer));
+
+        // Replayed by another peer, or against a new challenge
+        assert!(!solution.verify(&challenge, &PeerId::random(), &signer));
+        let mut renewed = challenge.clone();
+        renewed.nonce = rand::thread_rng().gen();
+        assert!(!solution.verify(&renewed, &solver, &signer));
+
+        // Re-signed by a sybil's own key, but still naming the solver
+        let mut reseed = [0u8; 32];
+        rand::thread_rng().fill(&mut reseed);
+        let sybil_key = SigningKey::from_slice(&reseed).unwrap();
+        let resigned = PuzzleSolution::signed(&challenge, &solver, vec![3; 8], &sybil_key);
+        assert!(!resigned.verify(&challenge, &solver, &signer));
+
+        let mut tampered = solution;
+        tampered.counts[0] = 4;
+        assert!(!tampered.verify(&challenge, &solver, &signer));
+    }
+
     #[test]
     fn test_wrong_key_fails_verification() {
         let mut seed1 = [0u8; 32];
@@ -0,0 +1,369 @@
+//! Per-peer request rate limiting.
+//!
+//! Every proof request or puzzle challenge a peer sends costs the serving
+//! node an RPC call. Each remote peer gets one token bucket per request
+//! kind; a request without a token is refused, and a peer that keeps
+//! hitting an empty bucket is banned for a while. A batch takes one token
+//! per item from its own bucket in a single check, so a full batch counts
+//! as one request, not one violation per item.
+
+use libp2p::PeerId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Size and refill rate of a token bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BucketLimit {
+    /// Maximum burst size
+    pub capacity: u32,
+    /// Tokens added per second
+    pub refill_per_sec: f64,
+}
+
+impl BucketLimit {
+    pub fn new(capacity: u32, refill_per_sec: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+        }
+    }
+}
+
+/// Limits applied to every remote peer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitConfig {
+    pub proof_requests: BucketLimit,
+    pub puzzle_challenges: BucketLimit,
+    /// Items across batched requests
+    pub batch_items: BucketLimit,
+    /// Refused requests tolerated within `violation_window` before a ban
+    pub max_violations: u32,
+    pub violation_window: Duration,
+    pub ban_duration: Duration,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            proof_requests: BucketLimit::new(20, 2.0),
+            puzzle_challenges: BucketLimit::new(5, 0.1),
+            batch_items: BucketLimit::new(crate::batch::MAX_BATCH_SIZE as u32, 4.0),
+            max_violations: 10,
+            violation_window: Duration::from_secs(60),
+            ban_duration: Duration::from_secs(600),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Sets the proof request bucket (Builder pattern).
+    pub fn with_proof_requests(mut self, limit: BucketLimit) -> Self {
+        self.proof_requests = limit;
+        self
+    }
+
+    /// Sets the puzzle challenge bucket (Builder pattern).
+    pub fn with_puzzle_challenges(mut self, limit: BucketLimit) -> Self {
+        self.puzzle_challenges = limit;
+        self
+    }
+
+    /// Sets the batch item bucket (Builder pattern).
+    pub fn with_batch_items(mut self, limit: BucketLimit) -> Self {
+        self.batch_items = limit;
+        self
+    }
+
+    /// Sets how many refusals within `window` trigger a ban of
+    /// `duration` (Builder pattern).
+    pub fn with_ban(mut self, max_violations: u32, window: Duration, duration: Duration) -> Self {
+        self.max_violations = max_violations;
+        self.violation_window = window;
+        self.ban_duration = duration;
+        self
+    }
+}
+
+/// Request kinds with separate buckets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestKind {
+    Proof,
+    Puzzle,
+    /// One item of a batch
+    BatchItem,
+}
+
+/// Outcome of a rate limit check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDecision {
+    /// Serve the request
+    Allowed,
+    /// Refuse the request
+    Limited,
+    /// Refuse the request; the peer was just banned until the given instant
+    Banned(Instant),
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn full(limit: &BucketLimit, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(limit.capacity),
+            last_refill: now,
+        }
+    }
+
+    fn try_take(&mut self, limit: &BucketLimit, cost: u32, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * limit.refill_per_sec).min(f64::from(limit.capacity));
+        self.last_refill = now;
+
+        if self.tokens >= f64::from(cost) {
+            self.tokens -= f64::from(cost);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct PeerState {
+    proofs: TokenBucket,
+    puzzles: TokenBucket,
+    batch_items: TokenBucket,
+    violations: u32,
+    window_start: Instant,
+}
+
+/// Token buckets and bans for all remote peers.
+#[derive(Debug, Clone)]
+pub struct PeerRateLimiter {
+    config: RateLimitConfig,
+    peers: HashMap<PeerId, PeerState>,
+    bans: HashMap<PeerId, Instant>,
+}
+
+impl Default for PeerRateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitConfig::default())
+    }
+}
+
+impl PeerRateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            peers: HashMap::new(),
+            bans: HashMap::new(),
+        }
+    }
+
+    /// Takes a token for a `kind` request from `peer`.
+    pub fn check(&mut self, peer: &PeerId, kind: RequestKind, now: Instant) -> RateDecision {
+        self.check_n(peer, kind, 1, now)
+    }
+
+    /// Takes `cost` tokens at once for `peer`, e.g. one per item of a
+    /// batch; a refusal counts as a single violation.
+    pub fn check_n(
+        &mut self,
+        peer: &PeerId,
+        kind: RequestKind,
+        cost: u32,
+        now: Instant,
+    ) -> RateDecision {
+        if self.is_banned(peer, now) {
+            return RateDecision::Limited;
+        }
+
+        let config = &self.config;
+        let state = self.peers.entry(*peer).or_insert_with(|| PeerState {
+            proofs: TokenBucket::full(&config.proof_requests, now),
+            puzzles: TokenBucket::full(&config.puzzle_challenges, now),
+            batch_items: TokenBucket::full(&config.batch_items, now),
+            violations: 0,
+            window_start: now,
+        });
+
+        let allowed = match kind {
+            RequestKind::Proof => state.proofs.try_take(&config.proof_requests, cost, now),
+            RequestKind::Puzzle => state.puzzles.try_take(&config.puzzle_challenges, cost, now),
+            RequestKind::BatchItem => state.batch_items.try_take(&config.batch_items, cost, now),
+        };
+        if allowed {
+            return RateDecision::Allowed;
+        }
+
+        if now.saturating_duration_since(state.window_start) > config.violation_window {
+            state.violations = 0;
+            state.window_start = now;
+        }
+        state.violations += 1;
+        if state.violations < config.max_violations {
+            return RateDecision::Limited;
+        }
+
+        // Start from scratch once the ban is lifted
+        self.peers.remove(peer);
+        let until = now + self.config.ban_duration;
+        self.bans.insert(*peer, until);
+        RateDecision::Banned(until)
+    }
+
+    /// Whether `peer` is banned at `now`.
+    pub fn is_banned(&self, peer: &PeerId, now: Instant) -> bool {
+        self.bans.get(peer).is_some_and(|until| now < *until)
+    }
+
+    /// Removes bans that have run out and returns the affected peers.
+    pub fn expire_bans(&mut self, now: Instant) -> Vec<PeerId> {
+        let expired: Vec<PeerId> = self
+            .bans
+            .iter()
+            .filter(|(_, until)| now >= **until)
+            .map(|(peer, _)| *peer)
+            .collect();
+        for peer in &expired {
+            self.bans.remove(peer);
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter() -> PeerRateLimiter {
+        PeerRateLimiter::new(
+            RateLimitConfig::default()
+                .with_proof_requests(BucketLimit::new(2, 1.0))
+                .with_puzzle_challenges(BucketLimit::new(1, 0.0))
+                .with_ban(3, Duration::from_secs(60), Duration::from_secs(300)),
+        )
+    }
+
+    #[test]
+    fn test_bucket_refills() {
+        let mut limiter = limiter();
+        let peer = PeerId::random();
+        let start = Instant::now();
+
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Proof, start),
+            RateDecision::Allowed
+        );
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Proof, start),
+            RateDecision::Allowed
+        );
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Proof, start),
+            RateDecision::Limited
+        );
+
+        // Buckets are independent per kind and per peer
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Puzzle, start),
+            RateDecision::Allowed
+        );
+        let other = PeerId::random();
+        assert_eq!(
+            limiter.check(&other, RequestKind::Proof, start),
+            RateDecision::Allowed
+        );
+
+        let later = start + Duration::from_secs(1);
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Proof, later),
+            RateDecision::Allowed
+        );
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Proof, later),
+            RateDecision::Limited
+        );
+    }
+
+    #[test]
+    fn test_batch_charged_once_by_size() {
+        let mut limiter = PeerRateLimiter::new(
+            RateLimitConfig::default()
+                .with_batch_items(BucketLimit::new(100, 10.0))
+                .with_ban(3, Duration::from_secs(60), Duration::from_secs(300)),
+        );
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        // A batch far larger than the proof bucket is one request
+        assert_eq!(
+            limiter.check_n(&peer, RequestKind::BatchItem, 80, now),
+            RateDecision::Allowed
+        );
+        assert_eq!(
+            limiter.check_n(&peer, RequestKind::BatchItem, 80, now),
+            RateDecision::Limited
+        );
+        assert!(!limiter.is_banned(&peer, now));
+        // Single proofs have their own bucket
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Proof, now),
+            RateDecision::Allowed
+        );
+        assert_eq!(
+            limiter.check_n(
+                &peer,
+                RequestKind::BatchItem,
+                80,
+                now + Duration::from_secs(6)
+            ),
+            RateDecision::Allowed
+        );
+    }
+
+    #[test]
+    fn test_repeated_violations_ban() {
+        let mut limiter = limiter();
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Puzzle, now),
+            RateDecision::Allowed
+        );
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Puzzle, now),
+            RateDecision::Limited
+        );
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Puzzle, now),
+            RateDecision::Limited
+        );
+        let until = now + Duration::from_secs(300);
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Puzzle, now),
+            RateDecision::Banned(until)
+        );
+
+        // Banned peers are refused even with tokens available
+        assert!(limiter.is_banned(&peer, now));
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Proof, now),
+            RateDecision::Limited
+        );
+
+        assert!(limiter.expire_bans(now).is_empty());
+        assert_eq!(limiter.expire_bans(until), vec![peer]);
+        assert_eq!(
+            limiter.check(&peer, RequestKind::Proof, until),
+            RateDecision::Allowed
+        );
+    }
+}
@@ -0,0 +1,178 @@
+//! Replay protection for gossiped threat rules.
+//!
+//! Gossipsub only remembers message ids for a few heartbeats, and the
+//! daemon's rule file deduplicates by rule id alone, so an old rule
+//! re-published after expiry cleanup would be accepted again. A rule is
+//! therefore only accepted while it is fresh (created within `max_age`,
+//! allowing for clock skew), and the `SeenCache` remembers the content hash
+//! of every accepted rule until it could no longer pass that check anyway.
+//! The cache is persisted so a restart does not reopen the window.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::threats::ThreatRule;
+
+/// How far in the future a rule's timestamp may be.
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(300);
+
+/// Outcome of a replay check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayVerdict {
+    /// Not seen before and within its lifetime; now remembered
+    Fresh,
+    /// Already accepted earlier
+    Duplicate,
+    /// Created longer than `max_age` ago
+    Expired,
+    /// Timestamp too far in the future
+    FromFuture,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SeenFile {
+    /// Message id -> unix time after which it can be forgotten
+    entries: HashMap<String, u64>,
+}
+
+/// Message ids of accepted threat rules.
+#[derive(Debug)]
+pub struct SeenCache {
+    max_age: Duration,
+    entries: HashMap<String, u64>,
+    path: Option<PathBuf>,
+}
+
+impl SeenCache {
+    /// In-memory cache accepting rules up to `max_age` old.
+    pub fn new(max_age: Duration) -> Self {
+        Self {
+            max_age,
+            entries: HashMap::new(),
+            path: None,
+        }
+    }
+
+    /// Cache backed by `path`; a missing file starts empty.
+    pub fn load(path: &Path, max_age: Duration) -> io::Result<Self> {
+        let entries = match fs::read_to_string(path) {
+            Ok(json) => {
+                serde_json::from_str::<SeenFile>(&json)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+                    .entries
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+        Ok(Self {
+            max_age,
+            entries,
+            path: Some(path.to_path_buf()),
+        })
+    }
+
+    /// Checks `rule` at unix time `now`, remembering it if fresh.
+    pub fn check(&mut self, rule: &ThreatRule, now: u64) -> ReplayVerdict {
+        if rule.timestamp > now.saturating_add(MAX_CLOCK_SKEW.as_secs()) {
+            return ReplayVerdict::FromFuture;
+        }
+        let forget_after = rule.timestamp.saturating_add(self.max_age.as_secs());
+        if forget_after < now {
+            return ReplayVerdict::Expired;
+        }
+        let id = rule.message_id();
+        if self.entries.contains_key(&id) {
+            return ReplayVerdict::Duplicate;
+        }
+        self.entries.insert(id, forget_after);
+        ReplayVerdict::Fresh
+    }
+
+    /// Forgets ids whose rules would now be rejected as expired; returns
+    /// how many were dropped.
+    pub fn prune(&mut self, now: u64) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, forget_after| *forget_after >= now);
+        before - self.entries.len()
+    }
+
+    /// Writes the cache to its backing file, if any.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = SeenFile {
+            entries: self.entries.clone(),
+        };
+        let json =
+            serde_json::to_string(&file).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        fs::write(path, json)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+
+    const DAY: u64 = 86_400;
+
+    fn rule_at(timestamp: u64) -> ThreatRule {
+        let key = SigningKey::from_slice(&[3u8; 32]).unwrap();
+        let mut rule = ThreatRule::new("rug-1", "Rug", "LP+ -> Sw", "base", "high", &key);
+        rule.timestamp = timestamp;
+        rule.sign(&key);
+        rule
+    }
+
+    #[test]
+    fn test_replays_are_rejected() {
+        let now = 10 * DAY;
+        let mut seen = SeenCache::new(Duration::from_secs(DAY));
+        let rule = rule_at(now - 60);
+
+        assert_eq!(seen.check(&rule, now), ReplayVerdict::Fresh);
+        assert_eq!(seen.check(&rule, now + 30), ReplayVerdict::Duplicate);
+
+        // Once forgotten, the rule is too old to be accepted again
+        assert_eq!(seen.prune(now + DAY), 1);
+        assert_eq!(seen.check(&rule, now + DAY), ReplayVerdict::Expired);
+
+        assert_eq!(
+            seen.check(&rule_at(now + 3600), now),
+            ReplayVerdict::FromFuture
+        );
+    }
+
+    #[test]
+    fn test_seen_cache_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("seen.json");
+        let now = 10 * DAY;
+        let rule = rule_at(now);
+
+        let mut seen = SeenCache::load(&path, Duration::from_secs(DAY)).unwrap();
+        assert!(seen.is_empty());
+        assert_eq!(seen.check(&rule, now), ReplayVerdict::Fresh);
+        seen.save().unwrap();
+
+        let mut reloaded = SeenCache::load(&path, Duration::from_secs(DAY)).unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded.check(&rule, now), ReplayVerdict::Duplicate);
+    }
+}
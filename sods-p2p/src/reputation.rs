@@ -1,10 +1,27 @@
 //! Peer reputation tracking with decay.
-
+//!
+//! A fresh node knows nothing about its peers, so an attacker surrounding
+//! it looks as good as anyone else. A node can export a signed
+//! `ReputationSnapshot` of what it has observed, and a new node can import
+//! snapshots from operators it trusts as a starting point. Only snapshots
+//! signed by a configured author and younger than `MAX_SNAPSHOT_AGE` are
+//! accepted. Imported scores are discounted by a trust factor and capped
+//! below the reliability threshold, so they order peers without vouching
+//! for them; they never override the importer's own evidence and decay
+//! like any other score unless confirmed.
+
+use k256::ecdsa::{signature::Signer, signature::Verifier, Signature, SigningKey, VerifyingKey};
 use libp2p::PeerId;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sods_core::clock::{SharedClock, SystemClock};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
 use std::time::{Duration, Instant};
 use tracing::warn;
 
+use crate::error::{Result, SodsP2pError};
+
 /// Initial score for new peers.
 const INITIAL_SCORE: f32 = 0.0;
 
@@ -14,12 +31,101 @@ const MIN_RELIABLE_SCORE: f32 = 0.4; // Must solve puzzle + 1 good response
 /// Rate at which reputation decays (every DECAY_INTERVAL).
 const DECAY_FACTOR: f32 = 0.95;
 
+/// Most peers accepted from one snapshot.
+pub const MAX_SNAPSHOT_PEERS: usize = 10_000;
+
+/// Highest score an imported peer starts with, below `MIN_RELIABLE_SCORE`
+/// so it still has to answer correctly before it counts as reliable.
+pub const MAX_IMPORTED_SCORE: f32 = 0.3;
+
+/// Oldest snapshot accepted for import.
+pub const MAX_SNAPSHOT_AGE: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Clock skew tolerated for a snapshot dated in the future.
+const SNAPSHOT_CLOCK_SKEW: Duration = Duration::from_secs(300);
+
+/// How often a peer was rewarded and penalized by this node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Evidence {
+    pub rewards: u32,
+    pub penalties: u32,
+}
+
+impl Evidence {
+    fn is_empty(&self) -> bool {
+        self.rewards == 0 && self.penalties == 0
+    }
+}
+
+/// One peer in a reputation snapshot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerReputation {
+    /// Base58 peer ID
+    pub peer: String,
+    pub score: f32,
+    pub rewards: u32,
+    pub penalties: u32,
+}
+
+/// Scores a node observed itself, signed by the node's secp256k1 key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReputationSnapshot {
+    pub peers: Vec<PeerReputation>,
+    /// Creation time (seconds since epoch)
+    pub timestamp: u64,
+    /// ECDSA signature (64 bytes)
+    pub signature: Vec<u8>,
+    /// Public key of the exporting node (33 bytes compressed)
+    pub author_pubkey: Vec<u8>,
+}
+
+impl ReputationSnapshot {
+    /// Hash of everything except the signature.
+    fn compute_hash(&self) -> [u8; 32] {
+        let content = serde_json::json!({
+            "peers": self.peers,
+            "timestamp": self.timestamp,
+            "author_pubkey": self.author_pubkey,
+        });
+        let mut hasher = Sha256::new();
+        hasher.update(serde_json::to_vec(&content).unwrap_or_default());
+        hasher.finalize().into()
+    }
+
+    pub fn sign(&mut self, signing_key: &SigningKey) {
+        self.author_pubkey = signing_key.verifying_key().to_sec1_bytes().to_vec();
+        let hash = self.compute_hash();
+        let signature: Signature = signing_key.sign(&hash);
+        self.signature = signature.to_bytes().to_vec();
+    }
+
+    pub fn verify(&self) -> bool {
+        let Ok(sig) = Signature::from_slice(&self.signature) else {
+            return false;
+        };
+        let Ok(pubkey) = VerifyingKey::from_sec1_bytes(&self.author_pubkey) else {
+            return false;
+        };
+        pubkey.verify(&self.compute_hash(), &sig).is_ok()
+    }
+}
+
 /// Tracks peer reliability based on response consistency.
 #[derive(Debug, Clone)]
 pub struct ReputationTracker {
     scores: HashMap<PeerId, f32>,
     last_validation: HashMap<PeerId, Instant>,
     last_decay: Instant,
+    /// Rewards and penalties this node handed out itself
+    evidence: HashMap<PeerId, Evidence>,
+    /// Discounted imported scores per peer: (sum, count)
+    imported: HashMap<PeerId, (f32, u32)>,
+    /// Authors of the imported snapshots
+    imported_from: HashSet<Vec<u8>>,
+    /// Snapshot authors whose scores may be imported
+    trusted_authors: HashSet<Vec<u8>>,
+    /// Times decay, validation staleness and snapshot ages
+    clock: SharedClock,
 }
 
 impl Default for ReputationTracker {
@@ -35,23 +141,41 @@ impl ReputationTracker {
             scores: HashMap::new(),
             last_validation: HashMap::new(),
             last_decay: Instant::now(),
+            evidence: HashMap::new(),
+            imported: HashMap::new(),
+            imported_from: HashSet::new(),
+            trusted_authors: HashSet::new(),
+            clock: SystemClock::shared(),
         }
     }
 
+    /// Reads the time from `clock` instead of the system clock; validation
+    /// times recorded so far are kept (Builder pattern).
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.last_decay = clock.instant();
+        self.clock = clock;
+        self
+    }
+
+    /// Accept snapshots signed by this public key (33 bytes compressed).
+    pub fn add_trusted_author(&mut self, pubkey: Vec<u8>) {
+        self.trusted_authors.insert(pubkey);
+    }
+
     /// Mark a peer as validated (solved a fresh puzzle).
     pub fn validate_peer(&mut self, peer: PeerId) {
-        self.last_validation.insert(peer, Instant::now());
+        self.last_validation.insert(peer, self.clock.instant());
         // Boosting score as a reward for successful validation
         self.reward(&peer);
     }
 
     /// Reset reputation for peers that haven't been validated within the last 24h.
     pub fn reset_stale_validations(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.instant();
         let stale_threshold = Duration::from_secs(86400); // 24 hours
 
         for (peer, last_time) in self.last_validation.iter() {
-            if now.duration_since(*last_time) > stale_threshold {
+            if now.saturating_duration_since(*last_time) > stale_threshold {
                 if let Some(score) = self.scores.get_mut(peer) {
                     warn!(
                         "Resetting reputation for peer {} due to stale validation (24h+)",
@@ -65,9 +189,10 @@ impl ReputationTracker {
 
     /// Apply decay if enough time has passed.
     pub fn decay_if_needed(&mut self) {
-        if self.last_decay.elapsed() >= Duration::from_secs(60) {
+        let now = self.clock.instant();
+        if now.saturating_duration_since(self.last_decay) >= Duration::from_secs(60) {
             self.decay_all();
-            self.last_decay = Instant::now();
+            self.last_decay = now;
         }
     }
 
@@ -83,6 +208,7 @@ impl ReputationTracker {
     pub fn reward(&mut self, peer: &PeerId) {
         let score = self.scores.entry(*peer).or_insert(INITIAL_SCORE);
         *score = (*score * 1.1 + 0.05).min(1.0);
+        self.evidence.entry(*peer).or_default().rewards += 1;
     }
 
     /// Penalize a peer for conflicting behavior.
@@ -90,6 +216,7 @@ impl ReputationTracker {
     pub fn penalize(&mut self, peer: &PeerId) {
         let score = self.scores.entry(*peer).or_insert(INITIAL_SCORE);
         *score = (*score * 0.7 - 0.1).max(0.0);
+        self.evidence.entry(*peer).or_default().penalties += 1;
     }
 
     /// Get a peer's current score.
@@ -130,17 +257,139 @@ impl ReputationTracker {
     pub fn is_empty(&self) -> bool {
         self.scores.is_empty()
     }
+
+    /// Rewards and penalties this node gave `peer`.
+    pub fn evidence(&self, peer: &PeerId) -> Evidence {
+        self.evidence.get(peer).copied().unwrap_or_default()
+    }
+
+    /// Signed snapshot of the peers this node has evidence about.
+    ///
+    /// Peers known only from imported snapshots are left out, so scores
+    /// are not passed on second-hand.
+    pub fn snapshot(&self, signing_key: &SigningKey) -> ReputationSnapshot {
+        let mut peers: Vec<PeerReputation> = self
+            .evidence
+            .iter()
+            .filter(|(_, evidence)| !evidence.is_empty())
+            .map(|(peer, evidence)| PeerReputation {
+                peer: peer.to_base58(),
+                score: self.get_score(peer),
+                rewards: evidence.rewards,
+                penalties: evidence.penalties,
+            })
+            .collect();
+        peers.sort_by(|a, b| a.peer.cmp(&b.peer));
+
+        let mut snapshot = ReputationSnapshot {
+            peers,
+            timestamp: self.clock.unix_secs(),
+            signature: Vec::new(),
+            author_pubkey: Vec::new(),
+        };
+        snapshot.sign(signing_key);
+        snapshot
+    }
+
+    /// Seed scores from another node's snapshot, returning how many peers
+    /// were seeded.
+    ///
+    /// The snapshot must be signed by a trusted author (see
+    /// `add_trusted_author`) and be at most `MAX_SNAPSHOT_AGE` old. Each
+    /// imported score is multiplied by `trust` (0.0 to 1.0) and capped at
+    /// `MAX_IMPORTED_SCORE`. Peers this node has its own evidence about are
+    /// skipped, and a peer named in several snapshots gets the mean of their
+    /// discounted scores. Each author's snapshot is accepted once.
+    pub fn import_snapshot(&mut self, snapshot: &ReputationSnapshot, trust: f32) -> Result<usize> {
+        if !(0.0..=1.0).contains(&trust) {
+            return Err(SodsP2pError::VerificationFailed(format!(
+                "Trust must be between 0 and 1, got {}",
+                trust
+            )));
+        }
+        if snapshot.peers.len() > MAX_SNAPSHOT_PEERS {
+            return Err(SodsP2pError::VerificationFailed(format!(
+                "Snapshot lists {} peers (max {})",
+                snapshot.peers.len(),
+                MAX_SNAPSHOT_PEERS
+            )));
+        }
+        if !snapshot.verify() {
+            return Err(SodsP2pError::VerificationFailed(
+                "Invalid reputation snapshot signature".into(),
+            ));
+        }
+        if !self.trusted_authors.contains(&snapshot.author_pubkey) {
+            return Err(SodsP2pError::VerificationFailed(format!(
+                "Reputation snapshot author {} is not trusted",
+                hex::encode(&snapshot.author_pubkey)
+            )));
+        }
+        let now = self.clock.unix_secs();
+        if snapshot.timestamp > now + SNAPSHOT_CLOCK_SKEW.as_secs() {
+            return Err(SodsP2pError::VerificationFailed(
+                "Reputation snapshot is dated in the future".into(),
+            ));
+        }
+        if now.saturating_sub(snapshot.timestamp) > MAX_SNAPSHOT_AGE.as_secs() {
+            return Err(SodsP2pError::VerificationFailed(format!(
+                "Reputation snapshot is older than {} days",
+                MAX_SNAPSHOT_AGE.as_secs() / 86_400
+            )));
+        }
+        if !self.imported_from.insert(snapshot.author_pubkey.clone()) {
+            return Err(SodsP2pError::VerificationFailed(
+                "A snapshot from this author was already imported".into(),
+            ));
+        }
+
+        let mut seeded = 0;
+        for entry in &snapshot.peers {
+            let Ok(peer) = PeerId::from_str(&entry.peer) else {
+                continue;
+            };
+            if !entry.score.is_finite() || !self.evidence(&peer).is_empty() {
+                continue;
+            }
+            let (sum, count) = self.imported.entry(peer).or_insert((0.0, 0));
+            *sum += (entry.score.clamp(0.0, 1.0) * trust).min(MAX_IMPORTED_SCORE);
+            *count += 1;
+            self.scores.insert(peer, *sum / *count as f32);
+            seeded += 1;
+        }
+        Ok(seeded)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sods_core::clock::{Clock, MockClock};
 
     fn random_peer() -> PeerId {
         let keypair = libp2p::identity::Keypair::generate_ed25519();
         PeerId::from(keypair.public())
     }
 
+    #[test]
+    fn test_decay_and_staleness_follow_the_clock() {
+        let clock = MockClock::default();
+        let mut tracker = ReputationTracker::new().with_clock(clock.shared());
+        let peer = random_peer();
+        tracker.validate_peer(peer);
+        let score = tracker.get_score(&peer);
+
+        tracker.decay_if_needed();
+        assert_eq!(tracker.get_score(&peer), score);
+        clock.advance(Duration::from_secs(60));
+        tracker.decay_if_needed();
+        assert!(tracker.get_score(&peer) < score);
+
+        clock.advance(Duration::from_secs(86_400));
+        tracker.reset_stale_validations();
+        assert_eq!(tracker.get_score(&peer), INITIAL_SCORE);
+    }
+
     #[test]
     fn test_reward_growth() {
         let mut tracker = ReputationTracker::new();
@@ -197,4 +446,104 @@ mod tests {
         assert!(tracker.get_score(&peer) >= MIN_RELIABLE_SCORE);
         assert!(tracker.is_reliable(&peer));
     }
+
+    fn importer(keys: &[&SigningKey]) -> ReputationTracker {
+        let mut tracker = ReputationTracker::new();
+        for key in keys {
+            tracker.add_trusted_author(key.verifying_key().to_sec1_bytes().to_vec());
+        }
+        tracker
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip_and_discount() {
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let (good, bad) = (random_peer(), random_peer());
+        let mut exporter = ReputationTracker::new();
+        for _ in 0..20 {
+            exporter.reward(&good);
+        }
+        exporter.penalize(&bad);
+
+        let snapshot = exporter.snapshot(&key);
+        assert_eq!(snapshot.peers.len(), 2);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed: ReputationSnapshot = serde_json::from_str(&json).unwrap();
+        assert!(parsed.verify());
+
+        let other = SigningKey::from_slice(&[8u8; 32]).unwrap();
+        let mut fresh = importer(&[&key, &other]);
+        assert_eq!(fresh.import_snapshot(&parsed, 0.25).unwrap(), 2);
+        assert_eq!(fresh.get_score(&good), 0.25);
+        assert!(fresh.get_score(&good) > fresh.get_score(&bad));
+        assert!(fresh.evidence(&good) == Evidence::default());
+
+        // Once per author, and imported scores are not re-exported
+        assert!(fresh.import_snapshot(&parsed, 0.5).is_err());
+        assert!(fresh.snapshot(&key).peers.is_empty());
+
+        // A second author's view is averaged in
+        let mut second = ReputationTracker::new();
+        second.penalize(&good);
+        fresh
+            .import_snapshot(&second.snapshot(&other), 1.0)
+            .unwrap();
+        assert_eq!(fresh.get_score(&good), 0.125);
+    }
+
+    #[test]
+    fn test_imported_peers_start_below_reliable() {
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let peer = random_peer();
+        let mut exporter = ReputationTracker::new();
+        for _ in 0..20 {
+            exporter.reward(&peer);
+        }
+
+        let mut fresh = importer(&[&key]);
+        fresh
+            .import_snapshot(&exporter.snapshot(&key), 1.0)
+            .unwrap();
+        assert_eq!(fresh.get_score(&peer), MAX_IMPORTED_SCORE);
+        assert!(!fresh.is_reliable(&peer));
+
+        // Confirmed by this node's own observations
+        fresh.reward(&peer);
+        fresh.reward(&peer);
+        assert!(fresh.is_reliable(&peer));
+    }
+
+    #[test]
+    fn test_snapshot_import_rejections() {
+        let key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let peer = random_peer();
+        let mut exporter = ReputationTracker::new();
+        exporter.reward(&peer);
+        let snapshot = exporter.snapshot(&key);
+
+        let mut tampered = snapshot.clone();
+        tampered.peers[0].score = 1.0;
+        assert!(importer(&[&key]).import_snapshot(&tampered, 0.5).is_err());
+        assert!(importer(&[&key]).import_snapshot(&snapshot, 1.5).is_err());
+
+        // Signed by a key nobody configured
+        assert!(ReputationTracker::new()
+            .import_snapshot(&snapshot, 0.5)
+            .is_err());
+
+        // Too old, or dated ahead of the clock
+        let now = SystemClock.unix_secs();
+        for timestamp in [now - MAX_SNAPSHOT_AGE.as_secs() - 60, now + 3600] {
+            let mut dated = snapshot.clone();
+            dated.timestamp = timestamp;
+            dated.sign(&key);
+            assert!(importer(&[&key]).import_snapshot(&dated, 0.5).is_err());
+        }
+
+        // Local evidence wins over imported scores
+        let mut local = importer(&[&key]);
+        local.penalize(&peer);
+        assert_eq!(local.import_snapshot(&snapshot, 1.0).unwrap(), 0);
+        assert_eq!(local.get_score(&peer), 0.0);
+    }
 }
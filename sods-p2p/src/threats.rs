@@ -6,9 +6,9 @@ use k256::ecdsa::{signature::Signer, signature::Verifier, Signature, SigningKey,
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
 
 use ethers_core::types::Address;
+use sods_core::clock::{Clock, SystemClock};
 use sods_core::pattern::BehavioralPattern;
 
 /// Topic for threat intelligence gossip.
@@ -62,10 +62,7 @@ pub struct RegistryUpdate {
 
 impl RegistryUpdate {
     pub fn new(contracts: Vec<ContractEntry>, signing_key: &SigningKey) -> Self {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = SystemClock.unix_secs();
 
         let mut update = Self {
             contracts,
@@ -128,6 +125,9 @@ mod serde_bytes {
 
 impl ThreatRule {
     /// Create a new signed rule.
+    ///
+    /// Presets in `pattern` are expanded first, since other nodes do not
+    /// share the installed ones.
     pub fn new(
         id: &str,
         name: &str,
@@ -136,15 +136,13 @@ impl ThreatRule {
         severity: &str,
         signing_key: &SigningKey,
     ) -> Self {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let timestamp = SystemClock.unix_secs();
 
         let mut rule = Self {
             id: id.to_string(),
             name: name.to_string(),
-            pattern: pattern.to_string(),
+            pattern: BehavioralPattern::parse(pattern)
+                .map_or_else(|_| pattern.to_string(), |p| p.to_string()),
             chain: chain.to_string(),
             severity: severity.to_string(),
             timestamp,
@@ -174,10 +172,25 @@ impl ThreatRule {
         self.signature = signature.to_bytes().to_vec();
     }
 
-    /// Verify the signature and pattern syntax.
+    /// Date the rule `timestamp` instead of now by the system clock, e.g.
+    /// a `Clock`'s `unix_secs`, and sign it again (Builder pattern).
+    pub fn dated(mut self, timestamp: u64, signing_key: &SigningKey) -> Self {
+        self.timestamp = timestamp;
+        self.sign(signing_key);
+        self
+    }
+
+    /// Hex hash of the signed content, identifying the rule across
+    /// re-publications.
+    pub fn message_id(&self) -> String {
+        hex::encode(self.compute_hash())
+    }
+
+    /// Verify the signature and that the pattern parses the same on every
+    /// node (`BehavioralPattern::parse_untrusted`).
     pub fn verify(&self) -> bool {
         // 1. Syntax check
-        if BehavioralPattern::parse(&self.pattern).is_err() {
+        if BehavioralPattern::parse_untrusted(&self.pattern).is_err() {
             return false;
         }
 
@@ -197,6 +210,154 @@ impl ThreatRule {
     }
 }
 
+/// An address of interest shipped in a rule pack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchlistEntry {
+    pub address: Address,
+    /// Human readable label (e.g. "Inferno Drainer")
+    pub label: String,
+    /// Chain the entry applies to (all chains if `None`)
+    pub chain: Option<String>,
+}
+
+/// A named behavioral pattern shipped in a rule pack.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternPreset {
+    pub name: String,
+    /// Pattern DSL (e.g. "Tf -> Sw -> Tf")
+    pub pattern: String,
+}
+
+/// Another pack a rule pack builds on.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackDependency {
+    pub name: String,
+    /// Minimum compatible version (dotted numeric, e.g. "1.2.0")
+    pub min_version: String,
+}
+
+/// A signed bundle of curated monitoring content (e.g. "DeFi-Drainer-Pack-2025").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePack {
+    pub name: String,
+    /// Dotted numeric version (e.g. "1.0.0")
+    pub version: String,
+    pub description: String,
+    #[serde(default)]
+    pub dependencies: Vec<PackDependency>,
+    #[serde(default)]
+    pub rules: Vec<ThreatRule>,
+    #[serde(default)]
+    pub watchlist: Vec<WatchlistEntry>,
+    #[serde(default)]
+    pub plugins: Vec<sods_core::plugins::SymbolPlugin>,
+    #[serde(default)]
+    pub presets: Vec<PatternPreset>,
+    /// Timestamp of creation (seconds since epoch)
+    pub timestamp: u64,
+    /// ECDSA signature (64 bytes)
+    #[serde(with = "serde_bytes")]
+    pub signature: Vec<u8>,
+    /// Public key of the author (33 bytes compressed)
+    #[serde(with = "serde_bytes")]
+    pub author_pubkey: Vec<u8>,
+}
+
+impl RulePack {
+    /// Create a new signed pack.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: &str,
+        version: &str,
+        description: &str,
+        dependencies: Vec<PackDependency>,
+        rules: Vec<ThreatRule>,
+        watchlist: Vec<WatchlistEntry>,
+        plugins: Vec<sods_core::plugins::SymbolPlugin>,
+        presets: Vec<PatternPreset>,
+        signing_key: &SigningKey,
+    ) -> Self {
+        let timestamp = SystemClock.unix_secs();
+
+        let mut pack = Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            description: description.to_string(),
+            dependencies,
+            rules,
+            watchlist,
+            plugins,
+            presets,
+            timestamp,
+            signature: Vec::new(),
+            author_pubkey: signing_key.verifying_key().to_sec1_bytes().to_vec(),
+        };
+        pack.sign(signing_key);
+        pack
+    }
+
+    /// Compute hash of the pack content (everything except the signature).
+    fn compute_hash(&self) -> [u8; 32] {
+        let content = serde_json::json!({
+            "name": self.name,
+            "version": self.version,
+            "description": self.description,
+            "dependencies": self.dependencies,
+            "rules": self.rules,
+            "watchlist": self.watchlist,
+            "plugins": self.plugins,
+            "presets": self.presets,
+            "timestamp": self.timestamp,
+            "author_pubkey": self.author_pubkey,
+        });
+        let mut hasher = Sha256::new();
+        hasher.update(serde_json::to_vec(&content).unwrap_or_default());
+        hasher.finalize().into()
+    }
+
+    /// Sign the pack.
+    pub fn sign(&mut self, signing_key: &SigningKey) {
+        let hash = self.compute_hash();
+        let signature: Signature = signing_key.sign(&hash);
+        self.signature = signature.to_bytes().to_vec();
+    }
+
+    /// Verify the pack signature, every bundled rule and every preset pattern.
+    pub fn verify(&self) -> bool {
+        if parse_version(&self.version).is_none() {
+            return false;
+        }
+        if !self.rules.iter().all(ThreatRule::verify) {
+            return false;
+        }
+        if self
+            .presets
+            .iter()
+            .any(|p| BehavioralPattern::parse_untrusted(&p.pattern).is_err())
+        {
+            return false;
+        }
+
+        if self.signature.len() != 64 || self.author_pubkey.is_empty() {
+            return false;
+        }
+        let Ok(sig) = Signature::from_slice(&self.signature) else {
+            return false;
+        };
+        let Ok(pubkey) = VerifyingKey::from_sec1_bytes(&self.author_pubkey) else {
+            return false;
+        };
+
+        let hash = self.compute_hash();
+        pubkey.verify(&hash, &sig).is_ok()
+    }
+}
+
+/// Parse a dotted numeric version ("1.2.0") into comparable components.
+pub fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version.split('.').map(|part| part.parse().ok()).collect()
+}
+
 /// Registry of validated threat rules.
 #[derive(Debug, Default)]
 pub struct ThreatRegistry {
@@ -294,6 +455,82 @@ mod tests {
         // Tamper with severity
         rule.severity = "low".to_string();
         assert!(!rule.verify());
+
+        // Re-dating signs again
+        let mut rule = rule.dated(1_700_000_000, &signing_key);
+        assert_eq!(rule.timestamp, 1_700_000_000);
+        assert!(rule.verify());
+        rule.timestamp += 1;
+        assert!(!rule.verify());
+    }
+
+    #[test]
+    fn test_rule_patterns_do_not_depend_on_local_presets() {
+        let signing_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let rule = ThreatRule::new("r", "R", "@Sandwich -> Wdw", "base", "high", &signing_key);
+        assert_eq!(rule.pattern, "Tf -> Sw -> Tf -> Wdw");
+        assert!(rule.verify());
+
+        let mut unexpanded = rule.clone();
+        unexpanded.pattern = "@Sandwich -> Wdw".into();
+        unexpanded.sign(&signing_key);
+        assert!(!unexpanded.verify());
+    }
+
+    fn test_pack(signing_key: &SigningKey) -> RulePack {
+        let rule = ThreatRule::new(
+            "drainer-1",
+            "Drainer",
+            "Tf{3,}",
+            "base",
+            "high",
+            signing_key,
+        );
+        RulePack::new(
+            "DeFi-Drainer-Pack",
+            "1.0.0",
+            "Drainer heuristics",
+            vec![],
+            vec![rule],
+            vec![WatchlistEntry {
+                address: Address::repeat_byte(0xDD),
+                label: "Known drainer".to_string(),
+                chain: None,
+            }],
+            vec![],
+            vec![PatternPreset {
+                name: "Drain".to_string(),
+                pattern: "Tf -> Tf -> Tf".to_string(),
+            }],
+            signing_key,
+        )
+    }
+
+    #[test]
+    fn test_rule_pack_verification() {
+        let signing_key = SigningKey::from_slice(&[7u8; 32]).unwrap();
+        let pack = test_pack(&signing_key);
+        assert!(pack.verify());
+
+        let json = serde_json::to_string(&pack).unwrap();
+        let decoded: RulePack = serde_json::from_str(&json).unwrap();
+        assert!(decoded.verify());
+
+        let mut tampered = pack.clone();
+        tampered.watchlist.clear();
+        assert!(!tampered.verify());
+
+        let mut bad_preset = pack;
+        bad_preset.presets[0].pattern = "Tf{".to_string();
+        bad_preset.sign(&signing_key);
+        assert!(!bad_preset.verify());
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("1.2.0"), Some(vec![1, 2, 0]));
+        assert!(parse_version("1.10") > parse_version("1.9.9"));
+        assert_eq!(parse_version("v1"), None);
     }
 
     #[test]
@@ -0,0 +1,216 @@
+//! Consensus transcripts for auditing P2P verifications.
+//!
+//! A transcript records everything `SodsClient::verify_via_p2p` based its
+//! decision on: the peers it queried and their reputation at the time, each
+//! signed `ProofResponse` exactly as received, signature checks, how every
+//! peer was judged, and the final outcome. Transcripts are written as JSON
+//! files so a disputed verification can be re-checked after the fact; the
+//! signatures remain verifiable offline with `ProofResponse::verify_signature`,
+//! and each peer's `SignerBinding` ties its signing key to its `PeerId`.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::binding::SignerBinding;
+use crate::protocol::ProofResponse;
+
+/// How a queried peer was judged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerOutcome {
+    /// Sided with the consensus result
+    Agreed,
+    /// Disagreed with a failed or unsuccessful consensus (penalized)
+    Conflicting,
+    /// Returned a different root than a successful consensus (slashed)
+    Slashed,
+    /// Response signature missing or invalid (penalized)
+    InvalidSignature,
+    /// Response signed by a key the peer has not bound to its identity
+    UnboundSigner,
+    /// No response before the request timeout
+    NoResponse,
+}
+
+/// One queried peer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptEntry {
+    pub peer_id: String,
+    /// Reputation score when the peer was selected
+    pub reputation: f32,
+    /// The peer's signer binding, if it advertised a valid one
+    #[serde(default)]
+    pub signer_binding: Option<SignerBinding>,
+    /// The signed response as received
+    pub response: Option<ProofResponse>,
+    pub outcome: PeerOutcome,
+}
+
+/// Which path produced the final result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionSource {
+    /// Local RPC verification succeeded; P2P was not consulted
+    LocalTruth,
+    /// Weighted peer consensus
+    Consensus,
+    /// RPC fallback after P2P could not decide
+    Fallback,
+    /// No result could be reached
+    Failed,
+}
+
+/// The final decision.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decision {
+    pub source: DecisionSource,
+    pub is_verified: bool,
+    pub agreeing_peers: usize,
+    pub total_peers: usize,
+    /// Hex-encoded agreed BMT root
+    pub bmt_root: Option<String>,
+    /// Error message when the verification failed
+    pub error: Option<String>,
+    /// Machine-readable code of that error
+    #[serde(default)]
+    pub error_code: Option<String>,
+}
+
+/// Full record of one P2P verification.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConsensusTranscript {
+    pub symbol: String,
+    pub block_number: u64,
+    /// Unix timestamp the verification started
+    pub started_at: u64,
+    /// Unix timestamp the decision was made
+    pub finished_at: u64,
+    /// Required share of reputation weight
+    pub threshold: f64,
+    pub entries: Vec<TranscriptEntry>,
+    pub decision: Option<Decision>,
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl ConsensusTranscript {
+    /// Starts a transcript for `symbol` in `block_number`.
+    pub fn new(symbol: &str, block_number: u64, threshold: f64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            block_number,
+            started_at: unix_now(),
+            finished_at: 0,
+            threshold,
+            entries: Vec::new(),
+            decision: None,
+        }
+    }
+
+    /// Records a peer selected for querying.
+    pub fn record_query(
+        &mut self,
+        peer_id: &libp2p::PeerId,
+        reputation: f32,
+        signer_binding: Option<&SignerBinding>,
+    ) {
+        self.entries.push(TranscriptEntry {
+            peer_id: peer_id.to_string(),
+            reputation,
+            signer_binding: signer_binding.cloned(),
+            response: None,
+            outcome: PeerOutcome::NoResponse,
+        });
+    }
+
+    /// Records a peer's response and how it was judged.
+    pub fn record_response(
+        &mut self,
+        peer_id: &libp2p::PeerId,
+        response: &ProofResponse,
+        outcome: PeerOutcome,
+    ) {
+        let peer_id = peer_id.to_string();
+        if let Some(entry) = self.entries.iter_mut().find(|e| e.peer_id == peer_id) {
+            entry.response = Some(response.clone());
+            entry.outcome = outcome;
+        }
+    }
+
+    /// Closes the transcript with the final decision.
+    pub fn decide(&mut self, decision: Decision) {
+        self.finished_at = unix_now();
+        self.decision = Some(decision);
+    }
+
+    /// Content hash identifying this transcript (hex SHA-256 of its JSON).
+    pub fn id(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        hex::encode(Sha256::digest(json))
+    }
+
+    /// Writes the transcript to `<dir>/<block>-<id prefix>.json`.
+    pub fn save(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}.json", self.block_number, &self.id()[..16]));
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    /// Reads a transcript written by `save`.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let json = fs::read_to_string(path)?;
+        serde_json::from_str(&json).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::ecdsa::SigningKey;
+    use libp2p::PeerId;
+
+    #[test]
+    fn test_transcript_roundtrip_keeps_signatures() {
+        let key = SigningKey::from_slice(&[5u8; 32]).unwrap();
+        let good = PeerId::random();
+        let silent = PeerId::random();
+
+        let mut transcript = ConsensusTranscript::new("Tf", 42, 0.66);
+        transcript.record_query(&good, 0.8, None);
+        transcript.record_query(&silent, 0.5, None);
+        let response = ProofResponse::success_signed(vec![1], [0xAA; 32], 2, &key);
+        transcript.record_response(&good, &response, PeerOutcome::Agreed);
+        transcript.decide(Decision {
+            source: DecisionSource::Consensus,
+            is_verified: true,
+            agreeing_peers: 1,
+            total_peers: 1,
+            bmt_root: Some(hex::encode([0xAA; 32])),
+            error: None,
+            error_code: None,
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = transcript.save(dir.path()).unwrap();
+        let loaded = ConsensusTranscript::load(&path).unwrap();
+
+        assert_eq!(loaded, transcript);
+        assert_eq!(loaded.entries[1].outcome, PeerOutcome::NoResponse);
+        assert!(loaded.entries[0]
+            .response
+            .as_ref()
+            .is_some_and(|r| r.verify_signature()));
+    }
+}
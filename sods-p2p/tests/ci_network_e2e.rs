@@ -0,0 +1,257 @@
+//! End-to-end proof exchange: three peers and a client in one process,
+//! connected over the memory transport, each peer reading its own mocked
+//! JSON-RPC endpoint. One peer serves a forged block and must be slashed.
+
+use ethers_core::types::{
+    Address, Block, Bloom, Log, Transaction, TransactionReceipt, H256, U256, U64,
+};
+use ethers_core::utils::keccak256;
+use serde_json::{json, Value};
+use sods_core::ErrorCode;
+use sods_p2p::transcript::PeerOutcome;
+use sods_p2p::{NetworkConfig, ReputationTracker, SodsClient, SodsPeer};
+use sods_verifier::header_anchor::compute_receipts_root;
+use sods_verifier::BlockVerifier;
+use std::collections::HashMap;
+use std::time::Duration;
+use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+const BLOCK: u64 = 100;
+
+/// A one-block chain served over JSON-RPC.
+struct MockChain {
+    header: Value,
+    full: Value,
+    receipts: HashMap<String, Value>,
+}
+
+impl MockChain {
+    /// Block `BLOCK` with a transfer and a swap, in separate transactions,
+    /// logged from `first_log` on.
+    fn new(first_log: u64) -> Self {
+        let token = Address::repeat_byte(0x11);
+        let pair = Address::repeat_byte(0x22);
+        let trader = Address::repeat_byte(0x33);
+        let topic = |sig: &str| H256::from(keccak256(sig.as_bytes()));
+        let padded = |addr: Address| H256::from(addr);
+
+        let transfer = Log {
+            address: token,
+            topics: vec![
+                topic("Transfer(address,address,uint256)"),
+                padded(trader),
+                padded(pair),
+            ],
+            data: ethers_core::abi::encode(&[ethers_core::abi::Token::Uint(1_000.into())]).into(),
+            ..Default::default()
+        };
+        let swap = Log {
+            address: pair,
+            topics: vec![
+                topic("Swap(address,uint256,uint256,uint256,uint256,address)"),
+                padded(trader),
+                padded(trader),
+            ],
+            data: vec![0u8; 128].into(),
+            ..Default::default()
+        };
+
+        let block_hash = H256::repeat_byte(0xB0);
+        let mut transactions = Vec::new();
+        let mut receipts = Vec::new();
+        for (index, log) in [transfer, swap].into_iter().enumerate() {
+            let hash = H256::from_low_u64_be(index as u64 + 1);
+            transactions.push(Transaction {
+                hash,
+                from: trader,
+                nonce: U256::from(index),
+                block_hash: Some(block_hash),
+                block_number: Some(U64::from(BLOCK)),
+                transaction_index: Some(U64::from(index)),
+                ..Default::default()
+            });
+            receipts.push(TransactionReceipt {
+                transaction_hash: hash,
+                transaction_index: U64::from(index),
+                block_hash: Some(block_hash),
+                block_number: Some(U64::from(BLOCK)),
+                from: trader,
+                cumulative_gas_used: U256::from(50_000 * (index + 1)),
+                status: Some(U64::from(1)),
+                logs_bloom: Bloom::zero(),
+                logs: vec![Log {
+                    block_hash: Some(block_hash),
+                    block_number: Some(U64::from(BLOCK)),
+                    transaction_hash: Some(hash),
+                    transaction_index: Some(U64::from(index)),
+                    log_index: Some(U256::from(first_log + index as u64)),
+                    ..log
+                }],
+                ..Default::default()
+            });
+        }
+
+        let full: Block<Transaction> = Block {
+            hash: Some(block_hash),
+            number: Some(U64::from(BLOCK)),
+            receipts_root: compute_receipts_root(&receipts),
+            timestamp: U256::from(1_700_000_000u64),
+            transactions,
+            ..Default::default()
+        };
+        let header: Block<H256> = full.clone().into();
+
+        Self {
+            header: json!(header),
+            full: json!(full),
+            receipts: receipts
+                .iter()
+                .map(|r| (format!("{:?}", r.transaction_hash), json!(r)))
+                .collect(),
+        }
+    }
+
+    fn result(&self, method: &str, params: &[Value]) -> Option<Value> {
+        match method {
+            "eth_getBlockByNumber" => {
+                let number = params.first()?.as_str()?;
+                if u64::from_str_radix(number.trim_start_matches("0x"), 16).ok()? != BLOCK {
+                    return Some(Value::Null);
+                }
+                Some(match params.get(1).and_then(Value::as_bool) {
+                    Some(true) => self.full.clone(),
+                    _ => self.header.clone(),
+                })
+            }
+            "eth_getTransactionReceipt" => {
+                let hash = params.first()?.as_str()?;
+                Some(self.receipts.get(hash).cloned().unwrap_or(Value::Null))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Respond for MockChain {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        let body: Value = serde_json::from_slice(&request.body).unwrap_or_default();
+        let method = body["method"].as_str().unwrap_or_default();
+        let params = body["params"].as_array().cloned().unwrap_or_default();
+        let reply = match self.result(method, &params) {
+            Some(result) => json!({ "jsonrpc": "2.0", "id": body["id"], "result": result }),
+            None => json!({
+                "jsonrpc": "2.0",
+                "id": body["id"],
+                "error": { "code": -32601, "message": format!("{} not mocked", method) }
+            }),
+        };
+        ResponseTemplate::new(200).set_body_json(reply)
+    }
+}
+
+async fn rpc(chain: MockChain) -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(wiremock::matchers::method("POST"))
+        .respond_with(chain)
+        .mount(&server)
+        .await;
+    server
+}
+
+/// Starts a peer on a fresh memory address.
+async fn start_peer(rpc_url: &str, config: &NetworkConfig) -> (libp2p::PeerId, libp2p::Multiaddr) {
+    let mut peer = SodsPeer::with_network_config(rpc_url, config).unwrap();
+    let id = *peer.peer_id();
+    let addr: libp2p::Multiaddr = format!("/memory/{}", rand::random::<u64>() | 1)
+        .parse()
+        .unwrap();
+    let listen = addr.to_string();
+    tokio::spawn(async move { peer.listen(&listen).await });
+    (id, addr)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_consensus_across_memory_network_slashes_liar() {
+    let honest = rpc(MockChain::new(0)).await;
+    // Same events at other positions, so the forged root differs
+    let forged = rpc(MockChain::new(7)).await;
+    let config = NetworkConfig::new().with_memory_transport();
+
+    let mut peers = Vec::new();
+    for url in [honest.uri(), honest.uri(), forged.uri()] {
+        peers.push(start_peer(&url, &config).await);
+    }
+    let liar = peers[2].0;
+    // Let the listeners come up before dialing
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // All three peers passed Proof-of-Behavior earlier
+    let mut reputation = ReputationTracker::new();
+    for (id, _) in &peers {
+        for _ in 0..10 {
+            reputation.reward(id);
+        }
+    }
+    let mut client = SodsClient::with_network_config(&config)
+        .unwrap()
+        .with_reputation(reputation);
+    let bootstrap: Vec<_> = peers.iter().map(|(_, addr)| addr.clone()).collect();
+    // A peer whose listener was not up yet is dialed again
+    for _ in 0..3 {
+        client.connect_bootstrap(&bootstrap).await.unwrap();
+        if client.known_peer_count() == 3 {
+            break;
+        }
+    }
+    assert_eq!(client.known_peer_count(), 3);
+
+    let expected = BlockVerifier::new(&[honest.uri()])
+        .unwrap()
+        .verify_symbol_in_block("Tf", BLOCK)
+        .await
+        .unwrap();
+    assert!(expected.is_verified);
+
+    // Symbol: the honest majority wins and the forged root is slashed
+    let result = client.verify_via_p2p("Tf", BLOCK).await.unwrap();
+    assert!(result.is_verified);
+    assert!(!result.used_fallback);
+    assert_eq!((result.agreeing_peers, result.total_peers), (2, 3));
+    assert_eq!(
+        result.bmt_root.map(|root| root.to_vec()),
+        expected.merkle_root
+    );
+
+    let transcript = client.last_transcript().unwrap();
+    let outcome = |peer: &libp2p::PeerId| {
+        transcript
+            .entries
+            .iter()
+            .find(|e| e.peer_id == peer.to_string())
+            .map(|e| e.outcome)
+    };
+    assert_eq!(outcome(&liar), Some(PeerOutcome::Slashed));
+    assert_eq!(outcome(&peers[0].0), Some(PeerOutcome::Agreed));
+    assert_eq!(client.known_peer_count(), 2);
+    assert!(client.reputation().get_score(&liar) < client.reputation().get_score(&peers[0].0));
+
+    // Pattern: served by the remaining honest peers
+    let result = client.verify_via_p2p("Tf -> Sw", BLOCK).await.unwrap();
+    assert!(result.is_verified);
+    assert!(!result.used_fallback);
+    assert_eq!((result.agreeing_peers, result.total_peers), (2, 2));
+
+    // A pattern absent from the block fails without a split
+    let err = client.verify_via_p2p("Sw -> Tf", BLOCK).await.unwrap_err();
+    assert_eq!(err.code(), "not_found");
+
+    // A batch larger than the single-proof bucket is charged as one request
+    let queries: Vec<_> = (0..40).map(|_| ("Tf".to_string(), BLOCK)).collect();
+    let results = client.verify_batch_via_p2p(&queries).await;
+    assert_eq!(results.len(), 40);
+    for result in results {
+        let result = result.unwrap();
+        assert!(result.is_verified);
+        assert_eq!(result.agreeing_peers, 2);
+    }
+}
@@ -0,0 +1,434 @@
+//! Deterministic sampling for probabilistic range audits.
+//!
+//! Verifying every block of a large range trustlessly means fetching every
+//! block's receipts, which public RPCs throttle long before the range is
+//! done. `AuditSampler` instead picks a fixed number of blocks to verify
+//! against their headers' receipts roots and only spot-checks the rest
+//! against the header's logs bloom.
+//!
+//! The sample is a pure function of the seed, range and sample size: each
+//! block is ranked by `keccak256("sods-audit-v1" || seed || block)` (both
+//! big-endian `u64`) and the lowest-ranked blocks are taken. The seed is
+//! recorded in the `AuditReport`, so a third party can recompute the same
+//! sample and re-run it.
+//!
+//! So that the auditor cannot steer the sample, the seed is not chosen but
+//! derived from the hash of block `end + SEED_DELAY`, which is unknown until
+//! after the range is committed to. The report records that block, and
+//! `AuditReport::seed_is_derived` checks the seed against it.
+
+use std::collections::BinaryHeap;
+
+use ethers_core::types::H256;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+use crate::error::Result;
+use crate::verifier::BlockVerifier;
+
+/// Sampling method recorded in reports, versioned with the ranking hash.
+pub const SAMPLING_METHOD: &str = "keccak256-bottom-k/v1";
+
+/// Blocks past the end of an audited range whose hash seeds the sample.
+pub const SEED_DELAY: u64 = 2;
+
+const RANK_DOMAIN: &[u8] = b"sods-audit-v1";
+const SEED_DOMAIN: &[u8] = b"sods-audit-seed-v1";
+
+/// Picks which blocks of a range get full verification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuditSampler {
+    seed: u64,
+    sample_size: usize,
+    seed_block: Option<SeedBlock>,
+}
+
+/// The block whose hash an audit's seed was derived from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SeedBlock {
+    pub number: u64,
+    pub hash: H256,
+}
+
+/// Outcome of fully verifying one sampled block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampledBlock {
+    pub block_number: u64,
+    pub matched: bool,
+    pub occurrences: usize,
+    /// Why the block could not be verified, e.g. a receipts root mismatch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A spot check that could not be completed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpotCheckFailure {
+    pub block_number: u64,
+    pub error: String,
+}
+
+/// Everything needed to reproduce and review an audit.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditReport {
+    pub pattern: String,
+    pub start_block: u64,
+    pub end_block: u64,
+    pub seed: u64,
+    /// Block the seed was derived from; absent if the seed was chosen.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed_block: Option<SeedBlock>,
+    pub sample_size: usize,
+    pub method: String,
+    /// Fully verified blocks, ascending.
+    pub sampled: Vec<SampledBlock>,
+    /// Spot-checked blocks whose bloom admits the pattern.
+    pub bloom_hits: Vec<u64>,
+    /// Spot-checked blocks the bloom rules out.
+    pub bloom_misses: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spot_check_failures: Vec<SpotCheckFailure>,
+}
+
+impl AuditSampler {
+    /// Sample `sample_size` blocks per audit, chosen by `seed`.
+    pub fn new(seed: u64, sample_size: usize) -> Self {
+        Self {
+            seed,
+            sample_size,
+            seed_block: None,
+        }
+    }
+
+    /// Sampler for auditing `pattern` over `start..=end`, seeded by the hash
+    /// of `seed_block` (see [`AuditSampler::seed_block_number`]).
+    ///
+    /// The seed also covers the pattern, range and sample size, so one block
+    /// hash cannot be reused to pick the sample of a different audit.
+    pub fn from_seed_block(
+        seed_block: SeedBlock,
+        pattern: &str,
+        start: u64,
+        end: u64,
+        sample_size: usize,
+    ) -> Self {
+        let mut hasher = Keccak256::new();
+        hasher.update(SEED_DOMAIN);
+        hasher.update(seed_block.hash.as_bytes());
+        hasher.update(start.to_be_bytes());
+        hasher.update(end.to_be_bytes());
+        hasher.update((sample_size as u64).to_be_bytes());
+        hasher.update(pattern.as_bytes());
+        let digest: [u8; 32] = hasher.finalize().into();
+        let mut seed = [0u8; 8];
+        seed.copy_from_slice(&digest[..8]);
+        Self {
+            seed: u64::from_be_bytes(seed),
+            sample_size,
+            seed_block: Some(seed_block),
+        }
+    }
+
+    /// Number of the block whose hash seeds an audit of a range ending at `end`.
+    pub fn seed_block_number(end: u64) -> u64 {
+        end.saturating_add(SEED_DELAY)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn seed_block(&self) -> Option<SeedBlock> {
+        self.seed_block
+    }
+
+    pub fn sample_size(&self) -> usize {
+        self.sample_size
+    }
+
+    /// Blocks of `start..=end` to verify fully, ascending.
+    ///
+    /// Returns the whole range if it has no more than `sample_size` blocks.
+    pub fn sample(&self, start: u64, end: u64) -> Vec<u64> {
+        if start > end || self.sample_size == 0 {
+            return Vec::new();
+        }
+        // Max-heap holding the lowest ranks seen so far
+        let mut lowest: BinaryHeap<([u8; 32], u64)> = BinaryHeap::with_capacity(self.sample_size);
+        for block in start..=end {
+            let rank = self.rank(block);
+            if lowest.len() < self.sample_size {
+                lowest.push((rank, block));
+            } else if lowest.peek().is_some_and(|(top, _)| rank < *top) {
+                lowest.pop();
+                lowest.push((rank, block));
+            }
+        }
+        let mut blocks: Vec<u64> = lowest.into_iter().map(|(_, block)| block).collect();
+        blocks.sort_unstable();
+        blocks
+    }
+
+    fn rank(&self, block: u64) -> [u8; 32] {
+        let mut hasher = Keccak256::new();
+        hasher.update(RANK_DOMAIN);
+        hasher.update(self.seed.to_be_bytes());
+        hasher.update(block.to_be_bytes());
+        hasher.finalize().into()
+    }
+
+    /// Audit `pattern` over `start..=end`.
+    ///
+    /// Sampled blocks go through `verify_pattern_trustless`; every other
+    /// block through `bloom_check_pattern`. Per-block failures are recorded
+    /// in the report rather than aborting the audit. `on_block` is called
+    /// with the number of blocks done after each one.
+    pub async fn run(
+        &self,
+        verifier: &BlockVerifier,
+        pattern: &str,
+        start: u64,
+        end: u64,
+        mut on_block: impl FnMut(u64),
+    ) -> Result<AuditReport> {
+        // Reject a bad pattern once instead of once per block
+        verifier.parse_pattern(pattern)?;
+
+        let sample = self.sample(start, end);
+        let mut report = AuditReport {
+            pattern: pattern.to_string(),
+            start_block: start,
+            end_block: end,
+            seed: self.seed,
+            seed_block: self.seed_block,
+            sample_size: self.sample_size,
+            method: SAMPLING_METHOD.to_string(),
+            sampled: Vec::with_capacity(sample.len()),
+            bloom_hits: Vec::new(),
+            bloom_misses: 0,
+            spot_check_failures: Vec::new(),
+        };
+
+        let mut sampled = sample.iter().peekable();
+        for (done, block) in (start..=end).enumerate() {
+            if sampled.next_if_eq(&&block).is_some() {
+                let entry = match verifier.verify_pattern_trustless(pattern, block).await {
+                    Ok(result) => SampledBlock {
+                        block_number: block,
+                        matched: result.is_verified,
+                        occurrences: result.occurrences,
+                        error: None,
+                    },
+                    Err(e) => SampledBlock {
+                        block_number: block,
+                        matched: false,
+                        occurrences: 0,
+                        error: Some(e.to_string()),
+                    },
+                };
+                report.sampled.push(entry);
+            } else {
+                match verifier.bloom_check_pattern(pattern, block).await {
+                    Ok(true) => report.bloom_hits.push(block),
+                    Ok(false) => report.bloom_misses += 1,
+                    Err(e) => report.spot_check_failures.push(SpotCheckFailure {
+                        block_number: block,
+                        error: e.to_string(),
+                    }),
+                }
+            }
+            on_block(done as u64 + 1);
+        }
+
+        Ok(report)
+    }
+}
+
+impl AuditReport {
+    /// The sampler that produced this report.
+    pub fn sampler(&self) -> AuditSampler {
+        AuditSampler {
+            seed_block: self.seed_block,
+            ..AuditSampler::new(self.seed, self.sample_size)
+        }
+    }
+
+    /// Whether the seed is the one derived from the recorded seed block.
+    ///
+    /// This does not check that the block hash is the chain's; callers with
+    /// an RPC should compare it against the block's header.
+    pub fn seed_is_derived(&self) -> bool {
+        self.seed_block.is_some_and(|block| {
+            block.number == AuditSampler::seed_block_number(self.end_block)
+                && AuditSampler::from_seed_block(
+                    block,
+                    &self.pattern,
+                    self.start_block,
+                    self.end_block,
+                    self.sample_size,
+                )
+                .seed
+                    == self.seed
+        })
+    }
+
+    /// Whether the sampled blocks are exactly those the seed selects.
+    ///
+    /// A report failing this check was not produced by honest sampling.
+    pub fn sample_is_reproducible(&self) -> bool {
+        self.method == SAMPLING_METHOD
+            && self
+                .sampled
+                .iter()
+                .map(|s| s.block_number)
+                .eq(self.sampler().sample(self.start_block, self.end_block))
+    }
+
+    /// Sampled blocks the pattern was verified in.
+    pub fn matched_blocks(&self) -> Vec<u64> {
+        self.sampled
+            .iter()
+            .filter(|s| s.matched)
+            .map(|s| s.block_number)
+            .collect()
+    }
+
+    /// Sampled blocks that could not be verified.
+    pub fn failed_samples(&self) -> Vec<&SampledBlock> {
+        self.sampled.iter().filter(|s| s.error.is_some()).collect()
+    }
+
+    /// Share of verified samples containing the pattern, if any were verified.
+    pub fn sample_match_rate(&self) -> Option<f64> {
+        let verified = self.sampled.iter().filter(|s| s.error.is_none()).count();
+        if verified == 0 {
+            return None;
+        }
+        Some(self.matched_blocks().len() as f64 / verified as f64)
+    }
+
+    /// Whether every block was checked without error.
+    pub fn is_complete(&self) -> bool {
+        self.failed_samples().is_empty() && self.spot_check_failures.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_is_deterministic() {
+        let sampler = AuditSampler::new(42, 10);
+        let sample = sampler.sample(1_000, 1_999);
+
+        assert_eq!(sample.len(), 10);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert!(sample.iter().all(|b| (1_000..=1_999).contains(b)));
+        assert_eq!(sample, AuditSampler::new(42, 10).sample(1_000, 1_999));
+        assert_ne!(sample, AuditSampler::new(43, 10).sample(1_000, 1_999));
+
+        // A larger sample extends a smaller one with the same seed
+        let larger = AuditSampler::new(42, 20).sample(1_000, 1_999);
+        assert!(sample.iter().all(|b| larger.contains(b)));
+    }
+
+    #[test]
+    fn test_sample_small_and_empty_ranges() {
+        let sampler = AuditSampler::new(7, 10);
+        assert_eq!(sampler.sample(5, 9), vec![5, 6, 7, 8, 9]);
+        assert!(sampler.sample(9, 5).is_empty());
+        assert!(AuditSampler::new(7, 0).sample(0, 100).is_empty());
+    }
+
+    #[test]
+    fn test_report_reproducibility_and_rates() {
+        let sampler = AuditSampler::new(9, 3);
+        let sampled = sampler
+            .sample(100, 199)
+            .into_iter()
+            .enumerate()
+            .map(|(i, block_number)| SampledBlock {
+                block_number,
+                matched: i == 0,
+                occurrences: usize::from(i == 0),
+                error: (i == 2).then(|| "receipts root mismatch".to_string()),
+            })
+            .collect();
+        let mut report = AuditReport {
+            pattern: "Sandwich".to_string(),
+            start_block: 100,
+            end_block: 199,
+            seed: 9,
+            seed_block: None,
+            sample_size: 3,
+            method: SAMPLING_METHOD.to_string(),
+            sampled,
+            bloom_hits: vec![],
+            bloom_misses: 97,
+            spot_check_failures: vec![],
+        };
+
+        assert!(report.sample_is_reproducible());
+        assert_eq!(report.matched_blocks().len(), 1);
+        assert_eq!(report.sample_match_rate(), Some(0.5));
+        assert!(!report.is_complete());
+
+        // Swapping in a hand-picked block is detectable
+        report.sampled[1].block_number = report.sampled[1].block_number.wrapping_add(1);
+        assert!(!report.sample_is_reproducible());
+    }
+
+    #[test]
+    fn test_seed_is_derived_from_the_seed_block() {
+        let block = SeedBlock {
+            number: AuditSampler::seed_block_number(199),
+            hash: H256::repeat_byte(0xab),
+        };
+        let sampler = AuditSampler::from_seed_block(block, "Sandwich", 100, 199, 3);
+        assert_eq!(block.number, 201);
+        assert_eq!(sampler.seed_block(), Some(block));
+
+        // The seed depends on the hash and on what is audited
+        let other = SeedBlock {
+            hash: H256::repeat_byte(0xac),
+            ..block
+        };
+        assert_ne!(
+            sampler.seed(),
+            AuditSampler::from_seed_block(other, "Sandwich", 100, 199, 3).seed()
+        );
+        assert_ne!(
+            sampler.seed(),
+            AuditSampler::from_seed_block(block, "Sandwich", 101, 199, 3).seed()
+        );
+
+        let mut report = AuditReport {
+            pattern: "Sandwich".to_string(),
+            start_block: 100,
+            end_block: 199,
+            seed: sampler.seed(),
+            seed_block: Some(block),
+            sample_size: 3,
+            method: SAMPLING_METHOD.to_string(),
+            sampled: vec![],
+            bloom_hits: vec![],
+            bloom_misses: 0,
+            spot_check_failures: vec![],
+        };
+        assert!(report.seed_is_derived());
+        assert_eq!(report.sampler(), sampler);
+
+        // A chosen seed, or one claimed for another block, is not derived
+        report.seed ^= 1;
+        assert!(!report.seed_is_derived());
+        report.seed ^= 1;
+        report.seed_block = Some(SeedBlock {
+            number: 250,
+            ..block
+        });
+        assert!(!report.seed_is_derived());
+        report.seed_block = None;
+        assert!(!report.seed_is_derived());
+    }
+}
@@ -0,0 +1,192 @@
+//! EIP-4844 blob transaction context.
+//!
+//! Type-3 transactions carry blobs that never show up in logs, which is
+//! how rollups post their batches. Two things are derived from them here:
+//! the blob context (`blob_versioned_hashes`, `blob_gas_used`) on every
+//! symbol of such a transaction, and one synthetic `BlobTx` symbol per
+//! transaction so batch posting can be matched by patterns.
+//!
+//! `BlobTx` takes the log index at which its transaction's logs start (the
+//! next transaction's, if it has none), so it orders before the logs of its
+//! own transaction and after those of earlier ones. As with any two symbols
+//! sharing an index, ties are broken by symbol name.
+
+use std::collections::HashMap;
+
+use ethers_core::types::{Log, Transaction, H256};
+use sods_core::{BehavioralSymbol, BLOB_TX_SYMBOL};
+
+/// EIP-2718 type of blob transactions.
+pub const BLOB_TX_TYPE: u64 = 3;
+
+/// Versioned hashes of a blob transaction; None for other transaction types.
+pub fn blob_versioned_hashes(tx: &Transaction) -> Option<Vec<H256>> {
+    if tx.transaction_type.map(|t| t.as_u64()) != Some(BLOB_TX_TYPE) {
+        return None;
+    }
+    // ethers keeps fields it does not model in `other`
+    Some(
+        tx.other
+            .get_deserialized::<Vec<H256>>("blobVersionedHashes")
+            .and_then(|hashes| hashes.ok())
+            .unwrap_or_default(),
+    )
+}
+
+/// Attach blob context to the symbols of blob transactions and, when
+/// `with_blob_tx` is set, add a `BlobTx` symbol per blob transaction.
+///
+/// `logs` must be the logs `symbols` were parsed from. The symbols are
+/// left in canonical order.
+pub fn apply_blob_context(
+    symbols: &mut Vec<BehavioralSymbol>,
+    txs: &[Transaction],
+    logs: &[Log],
+    with_blob_tx: bool,
+) {
+    // In block order, so equal-index `BlobTx` symbols stay in block order
+    let blob_txs: Vec<(&Transaction, Vec<H256>)> = txs
+        .iter()
+        .filter_map(|tx| Some((tx, blob_versioned_hashes(tx)?)))
+        .collect();
+    if blob_txs.is_empty() {
+        return;
+    }
+
+    let by_hash: HashMap<H256, &Vec<H256>> = blob_txs
+        .iter()
+        .map(|(tx, hashes)| (tx.hash, hashes))
+        .collect();
+    for sym in symbols.iter_mut() {
+        if let Some(hashes) = by_hash.get(&sym.tx_hash) {
+            *sym = sym.clone().with_blob_context(hashes.to_vec());
+        }
+    }
+
+    if with_blob_tx {
+        symbols.extend(
+            blob_txs
+                .iter()
+                .map(|(tx, hashes)| blob_tx_symbol(tx, hashes, logs)),
+        );
+        symbols.sort();
+    }
+}
+
+fn blob_tx_symbol(tx: &Transaction, hashes: &[H256], logs: &[Log]) -> BehavioralSymbol {
+    let tx_index = tx.transaction_index.map(|i| i.as_u64()).unwrap_or(0);
+    let log_index = logs
+        .iter()
+        .filter(|log| {
+            log.transaction_index
+                .is_some_and(|i| i.as_u64() >= tx_index)
+        })
+        .filter_map(|log| log.log_index)
+        .min()
+        .or_else(|| {
+            // Nothing from here on logged: place it after the last log
+            logs.iter()
+                .filter_map(|log| log.log_index)
+                .max()
+                .map(|i| i + 1)
+        })
+        .map(|i| i.as_u32())
+        .unwrap_or(0);
+
+    BehavioralSymbol::new(BLOB_TX_SYMBOL, log_index)
+        .with_context(tx.from, tx.to.unwrap_or_default(), tx.value, None)
+        .with_causality(tx.hash, tx.nonce.as_u64(), 0)
+        .with_blob_context(hashes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::U64;
+    use sods_core::GAS_PER_BLOB;
+
+    fn blob_tx(hash: u8, index: u64, blobs: usize) -> Transaction {
+        let mut tx = Transaction {
+            hash: H256::repeat_byte(hash),
+            transaction_index: Some(U64::from(index)),
+            transaction_type: Some(U64::from(BLOB_TX_TYPE)),
+            ..Default::default()
+        };
+        let hashes: Vec<H256> = (0..blobs)
+            .map(|i| H256::repeat_byte(0xb0 + i as u8))
+            .collect();
+        tx.other.insert(
+            "blobVersionedHashes".to_string(),
+            serde_json::to_value(hashes).unwrap(),
+        );
+        tx
+    }
+
+    fn log(tx: u8, tx_index: u64, log_index: u64) -> Log {
+        Log {
+            transaction_hash: Some(H256::repeat_byte(tx)),
+            transaction_index: Some(U64::from(tx_index)),
+            log_index: Some(log_index.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_versioned_hashes_only_for_type_3() {
+        assert_eq!(blob_versioned_hashes(&blob_tx(1, 0, 2)).unwrap().len(), 2);
+
+        let mut legacy = blob_tx(1, 0, 2);
+        legacy.transaction_type = Some(U64::from(2));
+        assert_eq!(blob_versioned_hashes(&legacy), None);
+    }
+
+    #[test]
+    fn test_blob_context_and_blob_tx_symbols() {
+        // tx 1 (index 0) logs 0-1, tx 2 (index 1) is a blob tx with log 2,
+        // tx 3 (index 2) a blob tx without logs, tx 4 (index 3) logs 3
+        let logs = vec![log(1, 0, 0), log(1, 0, 1), log(2, 1, 2), log(4, 3, 3)];
+        let txs = vec![
+            Transaction {
+                hash: H256::repeat_byte(1),
+                ..Default::default()
+            },
+            blob_tx(2, 1, 3),
+            blob_tx(3, 2, 1),
+        ];
+        let mut symbols: Vec<BehavioralSymbol> = logs
+            .iter()
+            .map(|l| {
+                BehavioralSymbol::new("Tf", l.log_index.unwrap().as_u32()).with_causality(
+                    l.transaction_hash.unwrap(),
+                    0,
+                    0,
+                )
+            })
+            .collect();
+
+        let mut without = symbols.clone();
+        apply_blob_context(&mut without, &txs, &logs, false);
+        assert_eq!(without.len(), 4);
+        assert_eq!(without[2].blob_gas_used, Some(3 * GAS_PER_BLOB));
+        assert_eq!(without[0].blob_gas_used, None);
+
+        apply_blob_context(&mut symbols, &txs, &logs, true);
+        let order: Vec<(&str, u32)> = symbols
+            .iter()
+            .map(|s| (s.symbol(), s.log_index()))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("Tf", 0),
+                ("Tf", 1),
+                ("BlobTx", 2),
+                ("Tf", 2),
+                ("BlobTx", 3),
+                ("Tf", 3),
+            ]
+        );
+        assert_eq!(symbols[4].tx_hash, H256::repeat_byte(3));
+        assert_eq!(symbols[4].blob_versioned_hashes.len(), 1);
+    }
+}
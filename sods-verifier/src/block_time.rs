@@ -0,0 +1,139 @@
+//! Block time estimation for adaptive polling.
+//!
+//! A fixed poll interval suits no chain in particular: 30 seconds lags
+//! fifteen blocks behind a 2-second L2, while polling that often on
+//! Ethereum mostly finds no new block. `PollSchedule::for_block_time`
+//! derives the interval from the chain's block time, estimated from the
+//! timestamps of two recent headers, so the head is asked for about once
+//! per block. It also sizes the catch-up batch, the most blocks scanned
+//! per poll, to roughly `CATCH_UP_WINDOW` worth of blocks, so a long
+//! outage on a fast chain is worked off in steps instead of one pass.
+
+use std::time::Duration;
+
+use crate::header_anchor::BlockHeader;
+
+/// Blocks between the headers a block time is estimated from.
+pub const BLOCK_TIME_SPAN: u64 = 32;
+
+/// Shortest poll interval, whatever the block time.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest poll interval, whatever the block time.
+pub const MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Chain time a catch-up batch covers.
+pub const CATCH_UP_WINDOW: Duration = Duration::from_secs(60);
+
+/// Fewest and most blocks in a catch-up batch.
+pub const CATCH_UP_BATCH_RANGE: (u64, u64) = (4, 256);
+
+/// Average time between blocks from `older` to `newer`, or `None` if they
+/// are not in order.
+pub fn block_time_between(older: &BlockHeader, newer: &BlockHeader) -> Option<Duration> {
+    let blocks = newer.number.checked_sub(older.number).filter(|&n| n > 0)?;
+    let secs = newer.timestamp.checked_sub(older.timestamp)?;
+    Some(Duration::from_millis(secs.saturating_mul(1000) / blocks))
+}
+
+/// How often to poll for the head and how far to scan per poll.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PollSchedule {
+    /// Time between head polls
+    pub interval: Duration,
+    /// Most blocks scanned per poll
+    pub batch: u64,
+    /// Estimated block time, when the schedule was derived from one
+    pub block_time: Option<Duration>,
+}
+
+impl PollSchedule {
+    /// Poll every `interval` and scan up to the head each time.
+    pub fn fixed(interval: Duration) -> Self {
+        Self {
+            interval,
+            batch: u64::MAX,
+            block_time: None,
+        }
+    }
+
+    /// Poll about once per block and catch up `CATCH_UP_WINDOW` of blocks
+    /// per poll.
+    pub fn for_block_time(block_time: Duration) -> Self {
+        let (min_batch, max_batch) = CATCH_UP_BATCH_RANGE;
+        let per_window = CATCH_UP_WINDOW
+            .as_millis()
+            .checked_div(block_time.as_millis())
+            .map_or(max_batch, |n| n.min(u128::from(max_batch)) as u64);
+        Self {
+            interval: block_time.clamp(MIN_POLL_INTERVAL, MAX_POLL_INTERVAL),
+            batch: per_window.clamp(min_batch, max_batch),
+            block_time: Some(block_time),
+        }
+    }
+
+    /// Last block to scan in a poll that starts after `last_scanned` and
+    /// sees `head`.
+    pub fn batch_end(&self, last_scanned: u64, head: u64) -> u64 {
+        head.min(last_scanned.saturating_add(self.batch))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Bloom, H256};
+
+    fn header(number: u64, timestamp: u64) -> BlockHeader {
+        BlockHeader {
+            number,
+            hash: H256::zero(),
+            receipts_root: H256::zero(),
+            parent_beacon_block_root: None,
+            timestamp,
+            logs_bloom: Bloom::zero(),
+        }
+    }
+
+    #[test]
+    fn test_block_time_between_headers() {
+        let old = header(100, 1_700_000_000);
+        // Two missed slots over 32 blocks
+        let new = header(132, 1_700_000_000 + 34 * 12);
+        assert_eq!(
+            block_time_between(&old, &new),
+            Some(Duration::from_millis(12_750))
+        );
+        assert_eq!(block_time_between(&new, &old), None);
+        assert_eq!(block_time_between(&old, &old), None);
+    }
+
+    #[test]
+    fn test_schedule_follows_block_time() {
+        let ethereum = PollSchedule::for_block_time(Duration::from_secs(12));
+        assert_eq!(ethereum.interval, Duration::from_secs(12));
+        assert_eq!(ethereum.batch, 5);
+
+        let base = PollSchedule::for_block_time(Duration::from_secs(2));
+        assert_eq!(base.interval, Duration::from_secs(2));
+        assert_eq!(base.batch, 30);
+
+        // Sub-second and same-timestamp blocks are clamped
+        let arbitrum = PollSchedule::for_block_time(Duration::from_millis(250));
+        assert_eq!(
+            (arbitrum.interval, arbitrum.batch),
+            (MIN_POLL_INTERVAL, 240)
+        );
+        let devnet = PollSchedule::for_block_time(Duration::ZERO);
+        assert_eq!(devnet.batch, 256);
+        let slow = PollSchedule::for_block_time(Duration::from_secs(120));
+        assert_eq!((slow.interval, slow.batch), (MAX_POLL_INTERVAL, 4));
+
+        assert_eq!(base.batch_end(100, 500), 130);
+        assert_eq!(base.batch_end(100, 110), 110);
+        assert_eq!(
+            PollSchedule::fixed(Duration::from_secs(30)).batch_end(100, 500),
+            500
+        );
+    }
+}
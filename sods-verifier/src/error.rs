@@ -3,6 +3,8 @@
 //! This module defines all error types that can occur during
 //! RPC operations, symbol verification, and proof generation.
 
+use sods_core::{ErrorClass, ErrorCode};
+use std::time::Duration;
 use thiserror::Error;
 
 /// Errors that can occur during verification operations.
@@ -19,6 +21,25 @@ pub enum SodsVerifierError {
         attempts: u32,
     },
 
+    /// The RPC provider is throttling requests.
+    #[error("Rate limited by RPC provider{}", retry_hint(retry_after))]
+    RateLimited {
+        /// Wait suggested by the provider, if it gave one
+        retry_after: Option<Duration>,
+    },
+
+    /// A single RPC request timed out.
+    #[error("RPC request timed out")]
+    Timeout,
+
+    /// The RPC provider could not be reached or is temporarily failing.
+    #[error("RPC provider unavailable: {0}")]
+    ProviderUnavailable(String),
+
+    /// The provider returned data that contradicts itself.
+    #[error("Inconsistent RPC data: {0}")]
+    DataInconsistent(String),
+
     /// The requested symbol was not found in the block.
     #[error("Symbol '{symbol}' not found in block {block_number}")]
     SymbolNotFound {
@@ -36,6 +57,11 @@ pub enum SodsVerifierError {
     #[error("Block {0} is out of range or does not exist")]
     BlockOutOfRange(u64),
 
+    /// The provider has no receipt for the transaction: it is unknown or
+    /// not mined yet.
+    #[error("Transaction {0} not found")]
+    TransactionNotFound(String),
+
     /// Block has no logs (empty block).
     #[error("Block {0} has no logs")]
     EmptyBlock(u64),
@@ -77,6 +103,80 @@ pub enum SodsVerifierError {
     },
 }
 
+fn retry_hint(retry_after: &Option<Duration>) -> String {
+    retry_after
+        .map(|d| format!(", retry after {}s", d.as_secs().max(1)))
+        .unwrap_or_default()
+}
+
+impl ErrorCode for SodsVerifierError {
+    fn code(&self) -> &'static str {
+        match self {
+            SodsVerifierError::RpcError(_) => "rpc_error",
+            SodsVerifierError::RpcTimeout { .. } => "rpc_timeout",
+            SodsVerifierError::RateLimited { .. } => "rate_limited",
+            SodsVerifierError::Timeout => "timeout",
+            SodsVerifierError::ProviderUnavailable(_) => "provider_unavailable",
+            SodsVerifierError::DataInconsistent(_) => "data_inconsistent",
+            SodsVerifierError::SymbolNotFound { .. } => "symbol_not_found",
+            SodsVerifierError::UnsupportedSymbol(_) => "unsupported_symbol",
+            SodsVerifierError::BlockOutOfRange(_) => "block_out_of_range",
+            SodsVerifierError::TransactionNotFound(_) => "transaction_not_found",
+            SodsVerifierError::EmptyBlock(_) => "empty_block",
+            SodsVerifierError::Core(e) => e.code(),
+            SodsVerifierError::InvalidReceiptProof { .. } => "invalid_receipt_proof",
+            SodsVerifierError::BloomFilterMismatch => "bloom_filter_mismatch",
+            SodsVerifierError::ProofUnsupported => "proof_unsupported",
+            SodsVerifierError::HeaderFetchFailed(_) => "header_fetch_failed",
+            SodsVerifierError::ReceiptFetchFailed(_) => "receipt_fetch_failed",
+            SodsVerifierError::AllRpcEndpointsFailed { .. } => "all_rpc_endpoints_failed",
+        }
+    }
+
+    fn class(&self) -> ErrorClass {
+        match self {
+            SodsVerifierError::RpcError(_)
+            | SodsVerifierError::RpcTimeout { .. }
+            | SodsVerifierError::RateLimited { .. }
+            | SodsVerifierError::Timeout
+            | SodsVerifierError::ProviderUnavailable(_)
+            | SodsVerifierError::ProofUnsupported
+            | SodsVerifierError::HeaderFetchFailed(_)
+            | SodsVerifierError::ReceiptFetchFailed(_)
+            | SodsVerifierError::AllRpcEndpointsFailed { .. } => ErrorClass::Unavailable,
+            SodsVerifierError::DataInconsistent(_)
+            | SodsVerifierError::InvalidReceiptProof { .. }
+            | SodsVerifierError::BloomFilterMismatch => ErrorClass::VerificationFailed,
+            SodsVerifierError::SymbolNotFound { .. }
+            | SodsVerifierError::BlockOutOfRange(_)
+            | SodsVerifierError::TransactionNotFound(_)
+            | SodsVerifierError::EmptyBlock(_) => ErrorClass::NotFound,
+            SodsVerifierError::UnsupportedSymbol(_) => ErrorClass::InvalidInput,
+            SodsVerifierError::Core(e) => e.class(),
+        }
+    }
+
+    fn is_retryable(&self) -> bool {
+        // Plain RPC errors and missing proof support need a different
+        // provider, not another attempt
+        matches!(
+            self,
+            SodsVerifierError::RateLimited { .. }
+                | SodsVerifierError::Timeout
+                | SodsVerifierError::ProviderUnavailable(_)
+                | SodsVerifierError::RpcTimeout { .. }
+                | SodsVerifierError::AllRpcEndpointsFailed { .. }
+        )
+    }
+
+    fn retry_after(&self) -> Option<Duration> {
+        match self {
+            SodsVerifierError::RateLimited { retry_after } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
 /// Result type alias for verifier operations.
 pub type Result<T> = std::result::Result<T, SodsVerifierError>;
 
@@ -106,4 +206,39 @@ mod tests {
         let err = SodsVerifierError::RpcTimeout { attempts: 3 };
         assert!(err.to_string().contains("3 attempts"));
     }
+
+    #[test]
+    fn test_retry_classification() {
+        let limited = SodsVerifierError::RateLimited {
+            retry_after: Some(Duration::from_secs(12)),
+        };
+        assert!(limited.is_retryable());
+        assert_eq!(limited.retry_after(), Some(Duration::from_secs(12)));
+        assert_eq!(limited.code(), "rate_limited");
+        assert!(limited.to_string().contains("retry after 12s"));
+
+        assert!(SodsVerifierError::Timeout.is_retryable());
+        assert!(SodsVerifierError::ProviderUnavailable("503".into()).is_retryable());
+        assert!(!SodsVerifierError::DataInconsistent("x".into()).is_retryable());
+        assert!(!SodsVerifierError::BlockOutOfRange(1).is_retryable());
+        assert_eq!(SodsVerifierError::Timeout.retry_after(), None);
+    }
+
+    #[test]
+    fn test_core_errors_keep_their_code() {
+        let err = SodsVerifierError::from(sods_core::SodsError::PatternError("x".into()));
+        assert_eq!(err.code(), "pattern_error");
+        assert_eq!(err.class(), ErrorClass::InvalidInput);
+        let source = std::error::Error::source(&err).unwrap();
+        assert!(source.downcast_ref::<sods_core::SodsError>().is_some());
+
+        assert_eq!(
+            SodsVerifierError::EmptyBlock(1).class(),
+            ErrorClass::NotFound
+        );
+        let missing = SodsVerifierError::TransactionNotFound("0xab".into());
+        assert_eq!(missing.class(), ErrorClass::NotFound);
+        assert_eq!(missing.code(), "transaction_not_found");
+        assert!(!SodsVerifierError::RpcError("x".into()).is_retryable());
+    }
 }
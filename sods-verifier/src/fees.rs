@@ -0,0 +1,247 @@
+//! Fee context for matched behavior.
+//!
+//! Whether an MEV alert matters economically depends on what the block
+//! looked like: the base fee, how full it was, and how much the matched
+//! transactions paid above the base fee to be included. All of it comes
+//! from the block that is fetched for verification anyway, so attaching
+//! it costs no extra RPC calls.
+
+use std::collections::HashMap;
+
+use ethers_core::types::{Block, Transaction, H256, U256};
+use serde::{Deserialize, Serialize};
+use sods_core::BehavioralSymbol;
+
+/// Fee conditions of a block, with the priority fee paid by each of its
+/// transactions. Built once per block and queried per match.
+#[derive(Debug, Clone, Default)]
+pub struct BlockFees {
+    base_fee_per_gas: Option<U256>,
+    gas_used: U256,
+    gas_limit: U256,
+    priority_fees: HashMap<H256, U256>,
+}
+
+impl BlockFees {
+    /// Read fee data from a block fetched with full transactions.
+    pub fn from_block(block: &Block<Transaction>) -> Self {
+        let base_fee = block.base_fee_per_gas;
+        Self {
+            base_fee_per_gas: base_fee,
+            gas_used: block.gas_used,
+            gas_limit: block.gas_limit,
+            priority_fees: block
+                .transactions
+                .iter()
+                .map(|tx| (tx.hash, effective_priority_fee(tx, base_fee)))
+                .collect(),
+        }
+    }
+
+    /// Fee context for the transactions behind `symbols`.
+    ///
+    /// Each transaction is listed once, in order of first appearance.
+    pub fn context_for<'a>(
+        &self,
+        symbols: impl IntoIterator<Item = &'a BehavioralSymbol>,
+    ) -> FeeContext {
+        let mut priority_fees: Vec<TxPriorityFee> = Vec::new();
+        for sym in symbols {
+            if priority_fees.iter().any(|f| f.tx_hash == sym.tx_hash) {
+                continue;
+            }
+            if let Some(fee) = self.priority_fees.get(&sym.tx_hash) {
+                priority_fees.push(TxPriorityFee {
+                    tx_hash: sym.tx_hash,
+                    priority_fee_per_gas: saturating_u128(*fee),
+                });
+            }
+        }
+
+        let utilization = if self.gas_limit.is_zero() {
+            0.0
+        } else {
+            // Basis points keep the division in integers
+            let bps = self.gas_used.saturating_mul(U256::from(10_000)) / self.gas_limit;
+            bps.min(U256::from(10_000)).as_u64() as f64 / 10_000.0
+        };
+
+        FeeContext {
+            base_fee_per_gas: self.base_fee_per_gas.map(saturating_u128),
+            gas_used: saturating_u64(self.gas_used),
+            gas_limit: saturating_u64(self.gas_limit),
+            utilization,
+            priority_fees,
+        }
+    }
+}
+
+/// Fee conditions around a match. Fees are in wei per gas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeContext {
+    /// EIP-1559 base fee; None on chains or blocks without one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_fee_per_gas: Option<u128>,
+    pub gas_used: u64,
+    pub gas_limit: u64,
+    /// `gas_used / gas_limit`, between 0.0 and 1.0.
+    pub utilization: f64,
+    /// Priority fee of each matched transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub priority_fees: Vec<TxPriorityFee>,
+}
+
+/// What one transaction paid per gas on top of the base fee.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxPriorityFee {
+    pub tx_hash: H256,
+    pub priority_fee_per_gas: u128,
+}
+
+impl FeeContext {
+    /// Priority fee paid by `tx_hash`, if it was part of the match.
+    pub fn priority_fee(&self, tx_hash: H256) -> Option<u128> {
+        self.priority_fees
+            .iter()
+            .find(|f| f.tx_hash == tx_hash)
+            .map(|f| f.priority_fee_per_gas)
+    }
+
+    /// Highest priority fee among the matched transactions.
+    pub fn max_priority_fee(&self) -> Option<u128> {
+        self.priority_fees
+            .iter()
+            .map(|f| f.priority_fee_per_gas)
+            .max()
+    }
+
+    /// One-line summary, e.g. `base fee 12.50 gwei, tip 2.00 gwei, 87% full`.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(base) = self.base_fee_per_gas {
+            parts.push(format!("base fee {}", format_gwei(base)));
+        }
+        if let Some(tip) = self.max_priority_fee() {
+            parts.push(format!("tip {}", format_gwei(tip)));
+        }
+        parts.push(format!("{:.0}% full", self.utilization * 100.0));
+        parts.join(", ")
+    }
+}
+
+/// Priority fee per gas that `tx` actually paid under `base_fee`.
+///
+/// For EIP-1559 transactions this is the tip cap, limited by what the fee
+/// cap leaves above the base fee. Legacy transactions pay their whole gas
+/// price, so their tip is whatever exceeds the base fee (all of it before
+/// London).
+pub fn effective_priority_fee(tx: &Transaction, base_fee: Option<U256>) -> U256 {
+    let base = base_fee.unwrap_or_default();
+    match (tx.max_priority_fee_per_gas, tx.max_fee_per_gas) {
+        (Some(tip), Some(cap)) => tip.min(cap.saturating_sub(base)),
+        _ => tx.gas_price.unwrap_or_default().saturating_sub(base),
+    }
+}
+
+/// Render a wei amount as gwei with two decimals.
+pub fn format_gwei(wei: u128) -> String {
+    format!(
+        "{}.{:02} gwei",
+        wei / 1_000_000_000,
+        wei % 1_000_000_000 / 10_000_000
+    )
+}
+
+fn saturating_u128(value: U256) -> u128 {
+    if value > U256::from(u128::MAX) {
+        u128::MAX
+    } else {
+        value.as_u128()
+    }
+}
+
+fn saturating_u64(value: U256) -> u64 {
+    if value > U256::from(u64::MAX) {
+        u64::MAX
+    } else {
+        value.as_u64()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn tx(
+        hash: u8,
+        gas_price: Option<u64>,
+        tip_cap: Option<u64>,
+        fee_cap: Option<u64>,
+    ) -> Transaction {
+        Transaction {
+            hash: H256::repeat_byte(hash),
+            gas_price: gas_price.map(U256::from),
+            max_priority_fee_per_gas: tip_cap.map(U256::from),
+            max_fee_per_gas: fee_cap.map(U256::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_effective_priority_fee() {
+        let base = Some(U256::from(10 * GWEI));
+        // Tip fits under the fee cap
+        let dynamic = tx(1, None, Some(2 * GWEI), Some(30 * GWEI));
+        assert_eq!(effective_priority_fee(&dynamic, base), U256::from(2 * GWEI));
+        // Fee cap leaves less room than the tip cap
+        let capped = tx(2, None, Some(5 * GWEI), Some(11 * GWEI));
+        assert_eq!(effective_priority_fee(&capped, base), U256::from(GWEI));
+        // Legacy pays its gas price above the base fee
+        let legacy = tx(3, Some(13 * GWEI), None, None);
+        assert_eq!(effective_priority_fee(&legacy, base), U256::from(3 * GWEI));
+        assert_eq!(effective_priority_fee(&legacy, None), U256::from(13 * GWEI));
+    }
+
+    #[test]
+    fn test_context_for_matched_transactions() {
+        let block = Block {
+            base_fee_per_gas: Some(U256::from(10 * GWEI)),
+            gas_used: U256::from(27_000_000u64),
+            gas_limit: U256::from(30_000_000u64),
+            transactions: vec![
+                tx(1, None, Some(2 * GWEI), Some(30 * GWEI)),
+                tx(2, Some(15 * GWEI), None, None),
+                tx(3, Some(11 * GWEI), None, None),
+            ],
+            ..Default::default()
+        };
+        let fees = BlockFees::from_block(&block);
+
+        let symbols = [
+            BehavioralSymbol::new("Sw", 0).with_causality(H256::repeat_byte(2), 0, 0),
+            BehavioralSymbol::new("Tf", 1).with_causality(H256::repeat_byte(2), 0, 1),
+            BehavioralSymbol::new("Sw", 2).with_causality(H256::repeat_byte(1), 0, 0),
+        ];
+        let context = fees.context_for(&symbols);
+
+        assert_eq!(context.base_fee_per_gas, Some(10 * GWEI as u128));
+        assert_eq!(context.utilization, 0.9);
+        assert_eq!(context.priority_fees.len(), 2);
+        assert_eq!(
+            context.priority_fee(H256::repeat_byte(2)),
+            Some(5 * GWEI as u128)
+        );
+        assert_eq!(context.priority_fee(H256::repeat_byte(3)), None);
+        assert_eq!(context.max_priority_fee(), Some(5 * GWEI as u128));
+        assert_eq!(
+            context.summary(),
+            "base fee 10.00 gwei, tip 5.00 gwei, 90% full"
+        );
+
+        let json = serde_json::to_value(&context).unwrap();
+        assert_eq!(json["gas_used"], 27_000_000);
+        assert_eq!(json["priority_fees"][0]["priority_fee_per_gas"], 5 * GWEI);
+    }
+}
@@ -0,0 +1,228 @@
+//! Shared chain head tracking.
+//!
+//! The daemon loop, the block monitor and its shadows, and mempool
+//! reconciliation all need the current head. Instead of each polling
+//! `eth_blockNumber` on its own schedule, they share one `HeadTracker`,
+//! which follows the head by polling a `SymbolVerifier` or from a
+//! WebSocket `newHeads` subscription and publishes it over a `watch`
+//! channel. All consumers see the same head, and the chain is asked once
+//! per interval however many consumers there are.
+//!
+//! The published head never moves backwards, so a fallback provider that
+//! lags behind the previous one does not make consumers rescan blocks.
+//! Failed polls are logged and counted, so a consumer can report them.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ethers_providers::{Middleware, Provider, StreamExt, Ws};
+use tokio::sync::watch;
+use tokio::time::MissedTickBehavior;
+
+use crate::error::Result;
+use crate::symbol_verifier::SymbolVerifier;
+
+/// Follows the chain head and shares it with any number of consumers.
+///
+/// Clones observe the same head. The background task stops once the
+/// tracker, its clones and all subscriptions are dropped.
+#[derive(Debug, Clone)]
+pub struct HeadTracker {
+    head: watch::Receiver<Option<u64>>,
+    /// Polls that failed so far
+    failed_polls: Arc<AtomicU64>,
+}
+
+impl HeadTracker {
+    /// Polls `verifier` for the latest block every `interval`.
+    ///
+    /// A failed poll is logged, counted in `failed_polls`, and leaves the
+    /// head at its last known value.
+    pub fn polling(verifier: Arc<dyn SymbolVerifier>, interval: Duration) -> Self {
+        let (tx, head) = watch::channel(None);
+        let failed_polls = Arc::new(AtomicU64::new(0));
+        let failures = failed_polls.clone();
+        tokio::spawn(async move {
+            let mut timer = tokio::time::interval(interval);
+            timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                tokio::select! {
+                    _ = timer.tick() => {}
+                    _ = tx.closed() => return,
+                }
+                match verifier.get_latest_block().await {
+                    Ok(number) => advance(&tx, number),
+                    Err(e) => {
+                        failures.fetch_add(1, Ordering::Relaxed);
+                        eprintln!("RPC Error: failed to fetch the chain head: {}", e);
+                    }
+                }
+            }
+        });
+        Self { head, failed_polls }
+    }
+
+    /// Follows `newHeads` notifications from a WebSocket provider.
+    ///
+    /// The head stops advancing if the subscription fails or ends.
+    pub fn from_ws(provider: Arc<Provider<Ws>>) -> Self {
+        let (tx, head) = watch::channel(None);
+        tokio::spawn(async move {
+            let mut stream = match provider.subscribe_blocks().await {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("Failed to subscribe to new blocks: {}", e);
+                    return;
+                }
+            };
+            loop {
+                let header = tokio::select! {
+                    header = stream.next() => header,
+                    _ = tx.closed() => return,
+                };
+                match header {
+                    Some(header) => {
+                        if let Some(number) = header.number {
+                            advance(&tx, number.as_u64());
+                        }
+                    }
+                    None => return,
+                }
+            }
+        });
+        Self {
+            head,
+            failed_polls: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Connects to a WebSocket endpoint and follows its `newHeads`.
+    pub async fn connect(ws_url: &str) -> Result<Self> {
+        let provider = Provider::<Ws>::connect(ws_url)
+            .await
+            .map_err(|e| crate::rpc::classify_provider_error(&e, None))?;
+        Ok(Self::from_ws(Arc::new(provider)))
+    }
+
+    /// The latest head seen, if any.
+    pub fn head(&self) -> Option<u64> {
+        *self.head.borrow()
+    }
+
+    /// Number of polls that failed since the tracker started; always 0
+    /// when following a subscription.
+    pub fn failed_polls(&self) -> u64 {
+        self.failed_polls.load(Ordering::Relaxed)
+    }
+
+    /// A receiver notified whenever the head advances from now on.
+    pub fn subscribe(&self) -> watch::Receiver<Option<u64>> {
+        let mut head = self.head.clone();
+        head.borrow_and_update();
+        head
+    }
+
+    /// Waits up to `timeout` for the first head.
+    pub async fn wait_for_head(&self, timeout: Duration) -> Option<u64> {
+        let mut head = self.head.clone();
+        let known = tokio::time::timeout(timeout, head.wait_for(Option::is_some))
+            .await
+            .ok()?
+            .ok()?;
+        *known
+    }
+}
+
+/// Publishes `number` if it is ahead of the current head.
+fn advance(tx: &watch::Sender<Option<u64>>, number: u64) {
+    tx.send_if_modified(|head| {
+        if head.map_or(true, |current| number > current) {
+            *head = Some(number);
+            true
+        } else {
+            false
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::SodsVerifierError;
+    use crate::result::VerificationResult;
+    use sods_core::BehavioralSymbol;
+    use std::sync::atomic::AtomicUsize;
+
+    /// A chain that only knows its head.
+    #[derive(Default)]
+    struct FakeHead {
+        number: AtomicU64,
+        polls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl SymbolVerifier for FakeHead {
+        async fn verify_symbol_in_block(&self, _: &str, block: u64) -> Result<VerificationResult> {
+            Err(SodsVerifierError::BlockOutOfRange(block))
+        }
+
+        async fn verify_pattern_trustless(
+            &self,
+            _: &str,
+            block: u64,
+        ) -> Result<VerificationResult> {
+            Err(SodsVerifierError::BlockOutOfRange(block))
+        }
+
+        async fn fetch_block_symbols(&self, block: u64) -> Result<Vec<BehavioralSymbol>> {
+            Err(SodsVerifierError::BlockOutOfRange(block))
+        }
+
+        async fn get_latest_block(&self) -> Result<u64> {
+            self.polls.fetch_add(1, Ordering::SeqCst);
+            match self.number.load(Ordering::SeqCst) {
+                0 => Err(SodsVerifierError::Timeout),
+                n => Ok(n),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_polled_head_is_shared_and_monotonic() {
+        let chain = Arc::new(FakeHead::default());
+        let tracker = HeadTracker::polling(chain.clone(), Duration::from_millis(10));
+        assert_eq!(tracker.wait_for_head(Duration::from_millis(50)).await, None);
+        assert!(tracker.failed_polls() > 0);
+
+        chain.number.store(5, Ordering::SeqCst);
+        assert_eq!(tracker.wait_for_head(Duration::from_secs(1)).await, Some(5));
+
+        let mut first = tracker.subscribe();
+        let mut second = tracker.clone().subscribe();
+        chain.number.store(7, Ordering::SeqCst);
+        first.changed().await.unwrap();
+        second.changed().await.unwrap();
+        assert_eq!((*first.borrow(), *second.borrow()), (Some(7), Some(7)));
+
+        // A lagging provider does not rewind the head
+        chain.number.store(6, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(tracker.head(), Some(7));
+        assert!(!first.has_changed().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_polling_stops_when_unobserved() {
+        let chain = Arc::new(FakeHead::default());
+        chain.number.store(1, Ordering::SeqCst);
+        let tracker = HeadTracker::polling(chain.clone(), Duration::from_millis(10));
+        tracker.wait_for_head(Duration::from_secs(1)).await;
+        drop(tracker);
+
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        let polls = chain.polls.load(Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(chain.polls.load(Ordering::SeqCst), polls);
+    }
+}
@@ -33,19 +33,43 @@
 //! }
 //! ```
 
+#![warn(clippy::unwrap_used)]
+
+pub mod audit;
+pub mod blob;
+pub mod block_time;
 pub mod error;
+pub mod fees;
+pub mod head;
 pub mod header_anchor;
 pub mod mempool;
+pub mod prefetch;
 pub mod query;
+pub mod reorg;
 pub mod result;
 pub mod rpc;
+pub mod snapshot;
+pub mod symbol_verifier;
 pub mod verifier;
+pub mod withdrawal;
 
 // Re-export main types for convenience
+pub use audit::{AuditReport, AuditSampler};
+pub use block_time::PollSchedule;
 pub use error::SodsVerifierError;
+pub use fees::{BlockFees, FeeContext, TxPriorityFee};
+pub use head::HeadTracker;
 pub use header_anchor::{AnchorValidation, BlockHeader, VerificationMode};
-pub use mempool::{MempoolMonitor, PendingAlert};
+pub use mempool::{
+    MatchSource, MempoolMonitor, PendingAlert, PendingSwap, SandwichGuard, SandwichPrediction,
+};
+pub use prefetch::BlockPrefetcher;
 pub use query::QueryParser;
+pub use reorg::{ReorgContext, ReorgEvent, ReorgTracker};
 pub use result::VerificationResult;
 pub use rpc::RpcClient;
-pub use verifier::BlockVerifier;
+pub use snapshot::{MempoolSnapshot, SnapshotStats, VanishedTx};
+pub use symbol_verifier::SymbolVerifier;
+pub use verifier::{
+    BlockVerifier, PatternOutcome, PrefetchedBlock, SymbolCursor, SymbolPage, MAX_SYMBOLS_PER_PAGE,
+};
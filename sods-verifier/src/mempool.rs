@@ -3,15 +3,49 @@
 //! Subscribes to pending transactions via WebSocket, simulates their execution,
 //! and checks for behavioral patterns in real-time.
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::sync::mpsc;
 
+use ethers_core::types::{
+    Address, BlockId, BlockNumber, CallConfig, CallFrame, GethDebugBuiltInTracerConfig,
+    GethDebugBuiltInTracerType, GethDebugTracerConfig, GethDebugTracerType,
+    GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, GethTraceFrame, Log,
+    Transaction, TransactionRequest, H256, U256,
+};
 use ethers_providers::{Middleware, Provider, StreamExt, Ws};
-// use ethers_core::types::{Transaction, TransactionReceipt};
 
-use crate::error::{Result, SodsVerifierError};
+use crate::error::Result;
+use crate::head::HeadTracker;
+use crate::snapshot::MempoolSnapshot;
 use sods_core::pattern::BehavioralPattern;
-use sods_core::BehavioralSymbol;
+use sods_core::{BehavioralSymbol, SymbolDictionary, SymbolProvenance};
+
+/// Confidence assigned to selector-based matches.
+const HEURISTIC_CONFIDENCE: f32 = 0.7;
+
+/// Confidence assigned to matches confirmed by simulation.
+const SIMULATED_CONFIDENCE: f32 = 0.95;
+
+/// How the symbols behind a pending alert were obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchSource {
+    /// Inferred from function selectors in the transaction input
+    SelectorHeuristic,
+    /// Derived from logs emitted while simulating the transaction
+    Simulated,
+}
+
+impl fmt::Display for MatchSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchSource::SelectorHeuristic => write!(f, "selector heuristic"),
+            MatchSource::Simulated => write!(f, "simulated behavioral match"),
+        }
+    }
+}
 
 /// Alert generated when a pattern is matched in the mempool.
 #[derive(Debug, Clone)]
@@ -21,12 +55,15 @@ pub struct PendingAlert {
     pub confidence: f32,
     pub estimated_inclusion: String,
     pub matched_sequence: String,
+    pub source: MatchSource,
 }
 
 /// Real-time mempool monitor.
 pub struct MempoolMonitor {
     provider: Arc<Provider<Ws>>,
-    // dictionary: SymbolDictionary, // Unused for now
+    dictionary: Arc<SymbolDictionary>,
+    simulate: bool,
+    snapshot: Option<Arc<Mutex<MempoolSnapshot>>>,
 }
 
 impl MempoolMonitor {
@@ -34,14 +71,38 @@ impl MempoolMonitor {
     pub async fn connect(ws_url: &str) -> Result<Self> {
         let provider = Provider::<Ws>::connect(ws_url)
             .await
-            .map_err(|e| SodsVerifierError::RpcError(e.to_string()))?;
+            .map_err(|e| crate::rpc::classify_provider_error(&e, None))?;
 
         Ok(Self {
             provider: Arc::new(provider),
-            // dictionary: SymbolDictionary::default(), // Unused
+            dictionary: Arc::new(SymbolDictionary::default()),
+            simulate: false,
+            snapshot: None,
         })
     }
 
+    /// Re-check heuristic matches by simulating the transaction with
+    /// `debug_traceCall` on the latest state (Builder pattern).
+    ///
+    /// Matches confirmed by simulation are upgraded to `MatchSource::Simulated`;
+    /// matches the simulation contradicts are dropped. If the endpoint does not
+    /// support tracing, the heuristic alert is kept.
+    pub fn with_simulation(mut self, enabled: bool) -> Self {
+        self.simulate = enabled;
+        self
+    }
+
+    /// Record every pending transaction and its predicted symbols in
+    /// `snapshot`, and reconcile them against new blocks as they arrive
+    /// (Builder pattern).
+    ///
+    /// The caller keeps a handle to read accuracy stats and vanished
+    /// transactions while the monitor runs.
+    pub fn with_snapshot(mut self, snapshot: Arc<Mutex<MempoolSnapshot>>) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
+
     /// Monitor pending transactions for a specific pattern.
     ///
     /// Returns a receiver for alerts.
@@ -52,7 +113,19 @@ impl MempoolMonitor {
     ) -> Result<mpsc::Receiver<PendingAlert>> {
         let (tx, rx) = mpsc::channel(100);
         let provider = self.provider.clone();
-        // let dictionary = self.dictionary.clone(); // Unused for now as we do heuristic simulation
+        let dictionary = self.dictionary.clone();
+        let simulate = self.simulate;
+        let snapshot = self.snapshot.clone();
+
+        if let Some(snapshot) = &self.snapshot {
+            let heads = HeadTracker::from_ws(provider.clone());
+            tokio::spawn(reconcile_blocks(
+                provider.clone(),
+                dictionary.clone(),
+                snapshot.clone(),
+                heads,
+            ));
+        }
 
         tokio::spawn(async move {
             // Subscribe to pending transactions
@@ -114,49 +187,136 @@ impl MempoolMonitor {
                 }
 
                 // 2. WETH Deposit: deposit() -> d0e30db0
-                if input.starts_with(&hex::decode("d0e30db0").unwrap()) {
-                    symbols
-                        .push(BehavioralSymbol::new("Dep", 0).with_context(from, to, value, None));
+                if input.starts_with(&[0xd0, 0xe3, 0x0d, 0xb0]) {
+                    symbols.push(
+                        BehavioralSymbol::new("Dep", 0)
+                            .with_context(from, to, value, None)
+                            .with_provenance(SymbolProvenance::MempoolPredicted),
+                    );
                 }
 
                 // 3. WETH Withdrawal: withdraw(uint) -> 2e1a7d4d
-                if input.starts_with(&hex::decode("2e1a7d4d").unwrap()) {
-                    symbols
-                        .push(BehavioralSymbol::new("Wdw", 0).with_context(from, to, value, None));
+                if input.starts_with(&[0x2e, 0x1a, 0x7d, 0x4d]) {
+                    symbols.push(
+                        BehavioralSymbol::new("Wdw", 0)
+                            .with_context(from, to, value, None)
+                            .with_provenance(SymbolProvenance::MempoolPredicted),
+                    );
                 }
 
                 // 4. ERC20 Transfer: transfer(address,uint256) -> a9059cbb
-                if input.starts_with(&hex::decode("a9059cbb").unwrap()) {
+                if input.starts_with(&[0xa9, 0x05, 0x9c, 0xbb]) {
                     // Decode args if possible, or just mark as Transfer
-                    symbols
-                        .push(BehavioralSymbol::new("Tf", 0).with_context(from, to, value, None));
+                    symbols.push(
+                        BehavioralSymbol::new("Tf", 0)
+                            .with_context(from, to, value, None)
+                            .with_provenance(SymbolProvenance::MempoolPredicted),
+                    );
                 }
 
                 // 5. Uniswap Swap (heuristic: selector check)
                 // swapExactTokensForTokens -> 38ed1739
                 // swapTokensForExactTokens -> 8803dbee
                 // exactInput -> b858183f
-                if input.starts_with(&hex::decode("38ed1739").unwrap())
-                    || input.starts_with(&hex::decode("b858183f").unwrap())
-                {
-                    symbols
-                        .push(BehavioralSymbol::new("Sw", 1).with_context(from, to, value, None));
+                if is_swap_call(input) {
+                    symbols.push(
+                        BehavioralSymbol::new("Sw", 1)
+                            .with_context(from, to, value, None)
+                            .with_provenance(SymbolProvenance::MempoolPredicted),
+                    );
+                }
+
+                if let Some(snapshot) = &snapshot {
+                    lock_snapshot(snapshot).record(tx_hash, symbol_codes(&symbols), unix_now());
                 }
 
                 // Check Pattern
-                if let Some(matched) = pattern.matches(&symbols, None) {
-                    let seq_str: Vec<String> = matched.iter().map(|s| s.symbol.clone()).collect();
-
-                    let alert = PendingAlert {
-                        tx_hash: format!("{:?}", tx_hash),
-                        pattern_name: pattern_name.clone(),
-                        confidence: 0.7, // Heuristic confidence
-                        estimated_inclusion: "next block".into(),
-                        matched_sequence: seq_str.join(" -> "),
-                    };
-
-                    if tx.send(alert).await.is_err() {
-                        break;
+                let Some(matched) = pattern.matches(&symbols, None) else {
+                    continue;
+                };
+                let mut seq_str: Vec<String> = matched.iter().map(|s| s.symbol.clone()).collect();
+                let mut confidence = HEURISTIC_CONFIDENCE;
+                let mut source = MatchSource::SelectorHeuristic;
+
+                // Optional simulation pass: replace guessed symbols with real ones
+                if simulate {
+                    match simulate_transaction(&provider, &tx_data).await {
+                        Some(frame) => {
+                            let simulated = symbols_from_trace(&dictionary, &frame, &tx_data);
+                            if let Some(snapshot) = &snapshot {
+                                lock_snapshot(snapshot).record(
+                                    tx_hash,
+                                    symbol_codes(&simulated),
+                                    unix_now(),
+                                );
+                            }
+                            match pattern.matches(&simulated, None) {
+                                Some(m) => {
+                                    seq_str = m.iter().map(|s| s.symbol.clone()).collect();
+                                    confidence = SIMULATED_CONFIDENCE;
+                                    source = MatchSource::Simulated;
+                                }
+                                // Execution does not produce the pattern
+                                None => continue,
+                            }
+                        }
+                        None => {
+                            // Tracing unavailable; keep the heuristic match
+                        }
+                    }
+                }
+
+                let alert = PendingAlert {
+                    tx_hash: format!("{:?}", tx_hash),
+                    pattern_name: pattern_name.clone(),
+                    confidence,
+                    estimated_inclusion: "next block".into(),
+                    matched_sequence: seq_str.join(" -> "),
+                    source,
+                };
+
+                if tx.send(alert).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(rx)
+    }
+
+    /// Watch the mempool for sandwiches targeting the given addresses' swaps.
+    ///
+    /// Returns a receiver that yields one prediction per threatened transaction.
+    pub async fn protect(
+        self,
+        protected: Vec<Address>,
+    ) -> Result<mpsc::Receiver<SandwichPrediction>> {
+        let (tx, rx) = mpsc::channel(100);
+        let provider = self.provider.clone();
+        let mut guard = SandwichGuard::new(protected);
+
+        tokio::spawn(async move {
+            let mut stream = match provider.subscribe_pending_txs().await {
+                Ok(s) => s,
+                Err(e) => {
+                    eprintln!("Failed to subscribe to pending txs: {}", e);
+                    return;
+                }
+            };
+
+            while let Some(tx_hash) = stream.next().await {
+                let tx_data = match provider.get_transaction(tx_hash).await {
+                    Ok(Some(t)) => t,
+                    _ => continue,
+                };
+
+                let Some(swap) = PendingSwap::from_transaction(&tx_data) else {
+                    continue;
+                };
+
+                for prediction in guard.observe(swap) {
+                    if tx.send(prediction).await.is_err() {
+                        return;
                     }
                 }
             }
@@ -165,3 +325,454 @@ impl MempoolMonitor {
         Ok(rx)
     }
 }
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn symbol_codes(symbols: &[BehavioralSymbol]) -> Vec<String> {
+    symbols.iter().map(|s| s.symbol().to_string()).collect()
+}
+
+/// Locks the snapshot, recovering it if a holder panicked; every update
+/// leaves it consistent.
+fn lock_snapshot(snapshot: &Mutex<MempoolSnapshot>) -> std::sync::MutexGuard<'_, MempoolSnapshot> {
+    snapshot
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Reconcile `snapshot` against each new block until the head stops
+/// advancing.
+///
+/// Receipts are only fetched for included transactions with predictions.
+async fn reconcile_blocks(
+    provider: Arc<Provider<Ws>>,
+    dictionary: Arc<SymbolDictionary>,
+    snapshot: Arc<Mutex<MempoolSnapshot>>,
+    heads: HeadTracker,
+) {
+    let mut head = heads.subscribe();
+    let mut last = heads.head();
+    drop(heads);
+
+    while head.changed().await.is_ok() {
+        let Some(current) = *head.borrow_and_update() else {
+            continue;
+        };
+        // Heads arriving together are reconciled in order
+        let first = last.map_or(current, |last| last + 1);
+        last = Some(current);
+        for number in first..=current {
+            reconcile_block(&provider, &dictionary, &snapshot, number).await;
+        }
+    }
+}
+
+/// Reconcile `snapshot` against block `number`.
+async fn reconcile_block(
+    provider: &Provider<Ws>,
+    dictionary: &SymbolDictionary,
+    snapshot: &Mutex<MempoolSnapshot>,
+    number: u64,
+) {
+    let block = match provider.get_block(number).await {
+        Ok(Some(b)) => b,
+        _ => return,
+    };
+
+    let awaiting = lock_snapshot(snapshot).awaiting_receipts(&block.transactions);
+    let mut realized = HashMap::new();
+    for hash in awaiting {
+        if let Ok(Some(receipt)) = provider.get_transaction_receipt(hash).await {
+            let symbols: Vec<String> = receipt
+                .logs
+                .iter()
+                .flat_map(|log| dictionary.parse_log_all(log))
+                .map(|sym| sym.symbol().to_string())
+                .collect();
+            realized.insert(hash, symbols);
+        }
+    }
+
+    let timestamp = block.timestamp.as_u64();
+    let mut snapshot = lock_snapshot(snapshot);
+    snapshot.reconcile_block(number, timestamp, &block.transactions, &realized);
+    snapshot.expire(timestamp);
+}
+
+/// Returns true if the calldata invokes a known router swap selector.
+///
+/// `swapExactTokensForTokens` (38ed1739) and `exactInput` (b858183f).
+fn is_swap_call(input: &[u8]) -> bool {
+    input.starts_with(&[0x38, 0xed, 0x17, 0x39]) || input.starts_with(&[0xb8, 0x58, 0x18, 0x3f])
+}
+
+/// A pending swap observed in the mempool.
+#[derive(Debug, Clone)]
+pub struct PendingSwap {
+    pub tx_hash: H256,
+    pub from: Address,
+    /// Router or pool the swap is sent to
+    pub target: Address,
+    /// Priority fee (EIP-1559) or legacy gas price
+    pub priority_fee: U256,
+    pub seen_at: Instant,
+}
+
+impl PendingSwap {
+    /// Build from a pending transaction, if it is a swap.
+    pub fn from_transaction(tx: &Transaction) -> Option<Self> {
+        if !is_swap_call(&tx.input) {
+            return None;
+        }
+        Some(Self {
+            tx_hash: tx.hash,
+            from: tx.from,
+            target: tx.to?,
+            priority_fee: tx
+                .max_priority_fee_per_gas
+                .or(tx.gas_price)
+                .unwrap_or_default(),
+            seen_at: Instant::now(),
+        })
+    }
+}
+
+/// A predicted sandwich against a protected transaction.
+#[derive(Debug, Clone)]
+pub struct SandwichPrediction {
+    /// The protected (victim) transaction
+    pub victim_tx: H256,
+    /// Attacker transactions expected to wrap the victim, front leg first
+    pub attacker_bundle: Vec<H256>,
+    /// Router or pool shared by victim and attacker
+    pub target: Address,
+    /// When the threat was detected
+    pub detected_at: Instant,
+}
+
+/// Predicts sandwiches of protected senders' pending swaps.
+///
+/// A sandwich is predicted when another sender has a swap pending on the same
+/// target with a higher priority fee than the victim (the front leg). Any of
+/// that sender's lower-priced swaps on the target are reported as the back leg.
+pub struct SandwichGuard {
+    protected: HashSet<Address>,
+    window: Duration,
+    pending: Vec<PendingSwap>,
+    warned: HashSet<H256>,
+}
+
+impl SandwichGuard {
+    /// Default time a pending swap is considered live (about one L1 slot).
+    pub const DEFAULT_WINDOW: Duration = Duration::from_secs(12);
+
+    /// Create a guard for the given sender addresses.
+    pub fn new(protected: impl IntoIterator<Item = Address>) -> Self {
+        Self {
+            protected: protected.into_iter().collect(),
+            window: Self::DEFAULT_WINDOW,
+            pending: Vec::new(),
+            warned: HashSet::new(),
+        }
+    }
+
+    /// Set how long pending swaps are kept (Builder pattern).
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Record a pending swap and return any new predictions it completes.
+    pub fn observe(&mut self, swap: PendingSwap) -> Vec<SandwichPrediction> {
+        let now = swap.seen_at;
+        let window = self.window;
+        self.pending
+            .retain(|p| now.saturating_duration_since(p.seen_at) <= window);
+        if self.pending.iter().any(|p| p.tx_hash == swap.tx_hash) {
+            return Vec::new();
+        }
+        let target = swap.target;
+        self.pending.push(swap);
+
+        let victims: Vec<&PendingSwap> = self
+            .pending
+            .iter()
+            .filter(|p| p.target == target && self.protected.contains(&p.from))
+            .filter(|p| !self.warned.contains(&p.tx_hash))
+            .collect();
+
+        let mut predictions = Vec::new();
+        for victim in victims {
+            let mut by_sender: HashMap<Address, Vec<&PendingSwap>> = HashMap::new();
+            for p in self.pending.iter().filter(|p| {
+                p.target == target && p.from != victim.from && !self.protected.contains(&p.from)
+            }) {
+                by_sender.entry(p.from).or_default().push(p);
+            }
+
+            let bundle = by_sender.into_values().find_map(|legs| {
+                let front = legs
+                    .iter()
+                    .filter(|p| p.priority_fee > victim.priority_fee)
+                    .max_by_key(|p| p.priority_fee)?;
+                let mut bundle = vec![front.tx_hash];
+                bundle.extend(
+                    legs.iter()
+                        .filter(|p| p.priority_fee < victim.priority_fee)
+                        .map(|p| p.tx_hash),
+                );
+                Some(bundle)
+            });
+
+            if let Some(attacker_bundle) = bundle {
+                predictions.push(SandwichPrediction {
+                    victim_tx: victim.tx_hash,
+                    attacker_bundle,
+                    target,
+                    detected_at: now,
+                });
+            }
+        }
+
+        for p in &predictions {
+            self.warned.insert(p.victim_tx);
+        }
+        predictions
+    }
+}
+
+/// Simulate a pending transaction with the `callTracer` on the latest state.
+///
+/// Returns `None` if the endpoint rejects `debug_traceCall` or returns an
+/// unexpected trace shape.
+async fn simulate_transaction(provider: &Provider<Ws>, tx: &Transaction) -> Option<CallFrame> {
+    let mut request = TransactionRequest::new()
+        .from(tx.from)
+        .value(tx.value)
+        .data(tx.input.clone())
+        .gas(tx.gas);
+    if let Some(to) = tx.to {
+        request = request.to(to);
+    }
+
+    let options = GethDebugTracingCallOptions {
+        tracing_options: GethDebugTracingOptions {
+            tracer: Some(GethDebugTracerType::BuiltInTracer(
+                GethDebugBuiltInTracerType::CallTracer,
+            )),
+            tracer_config: Some(GethDebugTracerConfig::BuiltInTracer(
+                GethDebugBuiltInTracerConfig::CallTracer(CallConfig {
+                    only_top_call: Some(false),
+                    with_log: Some(true),
+                }),
+            )),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    match provider
+        .debug_trace_call(request, Some(BlockId::Number(BlockNumber::Latest)), options)
+        .await
+    {
+        Ok(GethTrace::Known(GethTraceFrame::CallTracer(frame))) => Some(frame),
+        _ => None,
+    }
+}
+
+/// Convert the logs of a simulated call tree into behavioral symbols.
+fn symbols_from_trace(
+    dictionary: &SymbolDictionary,
+    frame: &CallFrame,
+    tx: &Transaction,
+) -> Vec<BehavioralSymbol> {
+    let mut logs = Vec::new();
+    collect_trace_logs(frame, tx.hash, &mut logs);
+
+    logs.iter()
+        .flat_map(|log| dictionary.parse_log_all(log))
+        .map(|sym| {
+            let log_index = sym.log_index();
+            let mut sym = sym.with_causality(tx.hash, tx.nonce.as_u64(), log_index);
+            sym.provenance = sym.provenance.min(SymbolProvenance::TraceDerived);
+            if sym.from.is_zero() {
+                sym.from = tx.from;
+            }
+            sym
+        })
+        .collect()
+}
+
+/// Flatten call-frame logs depth-first, skipping reverted frames.
+///
+/// The call tracer does not report a frame's logs relative to its subcalls,
+/// so a frame's own logs are placed before those of its children. Log indices
+/// are assigned sequentially in that order.
+fn collect_trace_logs(frame: &CallFrame, tx_hash: H256, out: &mut Vec<Log>) {
+    if frame.error.is_some() {
+        return;
+    }
+
+    for log in frame.logs.iter().flatten() {
+        out.push(Log {
+            address: log.address.unwrap_or_default(),
+            topics: log.topics.clone().unwrap_or_default(),
+            data: log.data.clone().unwrap_or_default(),
+            transaction_hash: Some(tx_hash),
+            log_index: Some(out.len().into()),
+            ..Default::default()
+        });
+    }
+
+    for call in frame.calls.iter().flatten() {
+        collect_trace_logs(call, tx_hash, out);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Bytes, CallLogFrame};
+    use sods_core::dictionary::event_signature_to_topic0;
+
+    fn transfer_log(from: Address, to: Address, amount: u64) -> CallLogFrame {
+        let mut data = [0u8; 32];
+        U256::from(amount).to_big_endian(&mut data);
+        CallLogFrame {
+            address: Some(Address::repeat_byte(0xEE)),
+            topics: Some(vec![
+                event_signature_to_topic0("Transfer(address,address,uint256)"),
+                H256::from(from),
+                H256::from(to),
+            ]),
+            data: Some(Bytes::from(data.to_vec())),
+        }
+    }
+
+    fn frame(logs: Vec<CallLogFrame>, calls: Vec<CallFrame>, error: Option<&str>) -> CallFrame {
+        CallFrame {
+            typ: "CALL".into(),
+            from: Address::zero(),
+            to: None,
+            value: None,
+            gas: U256::zero(),
+            gas_used: U256::zero(),
+            input: Bytes::default(),
+            output: None,
+            error: error.map(String::from),
+            calls: Some(calls),
+            logs: Some(logs),
+        }
+    }
+
+    #[test]
+    fn test_trace_logs_skip_reverted_frames() {
+        let a = Address::repeat_byte(1);
+        let b = Address::repeat_byte(2);
+        let root = frame(
+            vec![transfer_log(a, b, 1)],
+            vec![
+                frame(vec![transfer_log(b, a, 2)], vec![], None),
+                frame(
+                    vec![transfer_log(a, a, 3)],
+                    vec![],
+                    Some("execution reverted"),
+                ),
+            ],
+            None,
+        );
+
+        let mut logs = Vec::new();
+        collect_trace_logs(&root, H256::zero(), &mut logs);
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[1].log_index, Some(U256::from(1)));
+    }
+
+    #[test]
+    fn test_symbols_from_trace() {
+        let sender = Address::repeat_byte(9);
+        let tx = Transaction {
+            hash: H256::repeat_byte(7),
+            from: sender,
+            nonce: U256::from(4),
+            ..Default::default()
+        };
+        let root = frame(
+            vec![transfer_log(sender, Address::repeat_byte(2), 5)],
+            vec![],
+            None,
+        );
+
+        let symbols = symbols_from_trace(&SymbolDictionary::default(), &root, &tx);
+        assert_eq!(symbols.len(), 1);
+        assert_eq!(symbols[0].symbol(), "Tf");
+        assert_eq!(symbols[0].tx_hash, tx.hash);
+        assert_eq!(symbols[0].nonce, 4);
+        assert_eq!(symbols[0].provenance, SymbolProvenance::TraceDerived);
+
+        let pattern = BehavioralPattern::parse("Tf").unwrap();
+        assert!(pattern.matches(&symbols, None).is_some());
+    }
+
+    fn swap(hash: u8, from: u8, fee: u64, at: Instant) -> PendingSwap {
+        PendingSwap {
+            tx_hash: H256::repeat_byte(hash),
+            from: Address::repeat_byte(from),
+            target: Address::repeat_byte(0xAA),
+            priority_fee: U256::from(fee),
+            seen_at: at,
+        }
+    }
+
+    #[test]
+    fn test_sandwich_guard_predicts_once() {
+        let now = Instant::now();
+        let mut guard = SandwichGuard::new([Address::repeat_byte(1)]);
+
+        assert!(guard.observe(swap(1, 1, 10, now)).is_empty());
+        // Same-priced swap from someone else is not a front leg
+        assert!(guard.observe(swap(2, 2, 10, now)).is_empty());
+
+        let predictions = guard.observe(swap(3, 2, 50, now));
+        assert_eq!(predictions.len(), 1);
+        assert_eq!(predictions[0].victim_tx, H256::repeat_byte(1));
+        assert_eq!(predictions[0].attacker_bundle[0], H256::repeat_byte(3));
+
+        // Back leg arriving later does not re-fire
+        assert!(guard.observe(swap(4, 2, 1, now)).is_empty());
+    }
+
+    #[test]
+    fn test_sandwich_guard_window_expiry() {
+        let start = Instant::now();
+        let mut guard =
+            SandwichGuard::new([Address::repeat_byte(1)]).with_window(Duration::from_secs(5));
+
+        guard.observe(swap(1, 1, 10, start));
+        let later = start + Duration::from_secs(10);
+        assert!(guard.observe(swap(2, 2, 50, later)).is_empty());
+    }
+
+    #[test]
+    fn test_is_swap_call() {
+        assert!(is_swap_call(&[0x38, 0xed, 0x17, 0x39, 0x00]));
+        assert!(!is_swap_call(&[0xa9, 0x05, 0x9c, 0xbb]));
+    }
+
+    #[test]
+    fn test_match_source_display() {
+        assert_eq!(
+            MatchSource::SelectorHeuristic.to_string(),
+            "selector heuristic"
+        );
+        assert_eq!(
+            MatchSource::Simulated.to_string(),
+            "simulated behavioral match"
+        );
+    }
+}
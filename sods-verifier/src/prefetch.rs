@@ -0,0 +1,170 @@
+//! Block prefetching for sequential scans.
+//!
+//! A scan that verifies blocks one after another spends most of its time
+//! waiting on RPC round trips. `BlockPrefetcher` keeps the fetches for the
+//! next few blocks running as background tasks while the caller matches,
+//! and acts on, the current one. Matching still happens one block at a
+//! time and in order, so reorg tracking sees the blocks as a plain scan
+//! would.
+
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use sods_core::BehavioralSymbol;
+use tokio::task::JoinHandle;
+
+use crate::error::{Result, SodsVerifierError};
+use crate::verifier::{BlockVerifier, PatternOutcome, PrefetchedBlock};
+
+/// Blocks fetched ahead of the one being matched, unless configured
+/// otherwise.
+pub const DEFAULT_PREFETCH_DEPTH: usize = 2;
+
+/// All symbols of a block, if the prefetcher was asked for them.
+type BlockSymbols = Option<Result<Vec<BehavioralSymbol>>>;
+
+/// Verifies a range of blocks in order, fetching up to `depth` blocks
+/// ahead.
+///
+/// Fetches still in flight are cancelled when the prefetcher is dropped.
+pub struct BlockPrefetcher {
+    verifier: Arc<BlockVerifier>,
+    patterns: Arc<[String]>,
+    blocks: RangeInclusive<u64>,
+    depth: usize,
+    all_logs: bool,
+    in_flight: VecDeque<(u64, JoinHandle<Result<PrefetchedBlock>>)>,
+}
+
+impl BlockPrefetcher {
+    /// Verify `patterns` in each of `blocks`.
+    pub fn new(
+        verifier: Arc<BlockVerifier>,
+        patterns: Vec<String>,
+        blocks: RangeInclusive<u64>,
+    ) -> Self {
+        Self {
+            verifier,
+            patterns: patterns.into(),
+            blocks,
+            depth: DEFAULT_PREFETCH_DEPTH,
+            all_logs: false,
+            in_flight: VecDeque::new(),
+        }
+    }
+
+    /// Fetch up to `depth` blocks ahead of the one being matched; zero
+    /// fetches each block only when it is its turn (Builder pattern).
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// Fetch every log of each block, so `next_with_symbols` can also
+    /// return all of its symbols without fetching it again (Builder
+    /// pattern).
+    pub fn with_block_symbols(mut self) -> Self {
+        self.all_logs = true;
+        self
+    }
+
+    /// Verify the next block, returning its number and the outcomes in
+    /// pattern order, or `None` once the range is done.
+    pub async fn next(&mut self) -> Option<(u64, Result<Vec<PatternOutcome>>)> {
+        let (number, outcomes, _) = self.next_with_symbols().await?;
+        Some((number, outcomes))
+    }
+
+    /// Like `next`, but also returns all of the block's symbols when
+    /// configured `with_block_symbols`, as `fetch_block_symbols` would.
+    pub async fn next_with_symbols(
+        &mut self,
+    ) -> Option<(u64, Result<Vec<PatternOutcome>>, BlockSymbols)> {
+        self.fill(1);
+        let (number, fetch) = self.in_flight.pop_front()?;
+        // Later blocks download while this one is matched and handled
+        self.fill(self.depth);
+
+        let patterns: Vec<&str> = self.patterns.iter().map(String::as_str).collect();
+        let fetched = match fetch.await {
+            Ok(fetched) => fetched,
+            Err(e) => match e.try_into_panic() {
+                Ok(panic) => std::panic::resume_unwind(panic),
+                Err(e) => Err(SodsVerifierError::RpcError(e.to_string())),
+            },
+        };
+        let (outcomes, symbols) = match fetched {
+            Ok(block) => {
+                let symbols = if self.all_logs {
+                    self.verifier.prefetched_symbols(&block).await
+                } else {
+                    None
+                };
+                let outcomes = self
+                    .verifier
+                    .verify_patterns_in_prefetched(&patterns, block)
+                    .await;
+                (outcomes, symbols)
+            }
+            Err(e) => {
+                let symbols = self
+                    .all_logs
+                    .then(|| Err(SodsVerifierError::RpcError(e.to_string())));
+                (Err(e), symbols)
+            }
+        };
+        Some((number, outcomes, symbols))
+    }
+
+    /// Start fetches until `count` are in flight or the range is exhausted.
+    fn fill(&mut self, count: usize) {
+        while self.in_flight.len() < count {
+            let Some(number) = self.blocks.next() else {
+                return;
+            };
+            let verifier = self.verifier.clone();
+            let patterns = self.patterns.clone();
+            let all_logs = self.all_logs;
+            let fetch = tokio::spawn(async move {
+                let patterns: Vec<&str> = patterns.iter().map(String::as_str).collect();
+                if all_logs {
+                    verifier.prefetch_all_logs(&patterns, number).await
+                } else {
+                    verifier.prefetch_block(&patterns, number).await
+                }
+            });
+            self.in_flight.push_back((number, fetch));
+        }
+    }
+}
+
+impl Drop for BlockPrefetcher {
+    fn drop(&mut self) {
+        for (_, fetch) in &self.in_flight {
+            fetch.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_blocks_come_out_in_order() {
+        // Nothing to fetch when no pattern parses, so no RPC is needed
+        let verifier =
+            Arc::new(BlockVerifier::new(&["http://127.0.0.1:1".to_string()]).expect("verifier"));
+        let mut blocks =
+            BlockPrefetcher::new(verifier, vec!["Tf ->".to_string()], 10..=14).with_depth(3);
+        let mut seen = Vec::new();
+        while let Some((number, outcomes)) = blocks.next().await {
+            let outcomes = outcomes.expect("outcomes");
+            assert!(outcomes[0].result.is_err());
+            assert!(blocks.in_flight.len() <= 3);
+            seen.push(number);
+        }
+        assert_eq!(seen, vec![10, 11, 12, 13, 14]);
+    }
+}
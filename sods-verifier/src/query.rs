@@ -2,6 +2,8 @@
 //!
 //! Validates that query symbols are in the supported registry.
 
+use sods_core::aliases::SymbolAliases;
+
 use crate::error::{Result, SodsVerifierError};
 
 /// Supported symbols in the SODS core registry.
@@ -17,19 +19,42 @@ const SUPPORTED_SYMBOLS: &[&str] = &[
     "ListNFT",
     "BridgeIn",
     "BridgeOut",
+    "Stake",
+    "Unstake",
+    "BlobTx",
+    "ClWdw",
 ];
 
 /// Query parser for validating symbol queries.
 ///
 /// Currently supports only simple single-symbol queries.
 /// Future versions will support pattern sequences (e.g., `LP+ → LP-`).
+/// Symbols may be given by a configured alias (e.g., `Swap` for `Sw`).
 #[derive(Debug, Clone, Default)]
-pub struct QueryParser;
+pub struct QueryParser {
+    aliases: SymbolAliases,
+}
 
 impl QueryParser {
     /// Create a new query parser.
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Accept the given aliases in place of canonical symbols (Builder pattern).
+    pub fn with_aliases(mut self, aliases: SymbolAliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Aliases this parser resolves.
+    pub fn aliases(&self) -> &SymbolAliases {
+        &self.aliases
+    }
+
+    /// Resolve an alias to its canonical symbol; other names are unchanged.
+    pub fn resolve_symbol<'a>(&'a self, symbol: &'a str) -> &'a str {
+        self.aliases.resolve(symbol)
     }
 
     /// Validate that a symbol is supported.
@@ -52,7 +77,7 @@ impl QueryParser {
     /// assert!(parser.validate_symbol("BadSymbol").is_err());
     /// ```
     pub fn validate_symbol(&self, symbol: &str) -> Result<()> {
-        if SUPPORTED_SYMBOLS.contains(&symbol) {
+        if self.is_supported(symbol) {
             Ok(())
         } else {
             Err(SodsVerifierError::UnsupportedSymbol(symbol.to_string()))
@@ -61,7 +86,7 @@ impl QueryParser {
 
     /// Check if a symbol is supported without returning an error.
     pub fn is_supported(&self, symbol: &str) -> bool {
-        SUPPORTED_SYMBOLS.contains(&symbol)
+        SUPPORTED_SYMBOLS.contains(&self.resolve_symbol(symbol))
     }
 
     /// Get list of all supported symbols.
@@ -99,8 +124,21 @@ mod tests {
         let parser = QueryParser::new();
         let symbols = parser.supported_symbols();
 
-        assert_eq!(symbols.len(), 11);
+        assert_eq!(symbols.len(), 15);
         assert!(symbols.contains(&"Tf"));
         assert!(symbols.contains(&"LP+"));
+        assert!(symbols.contains(&"BlobTx"));
+        assert!(symbols.contains(&"ClWdw"));
+        assert!(symbols.contains(&"Stake"));
+    }
+
+    #[test]
+    fn test_aliases_resolve_to_supported_symbols() {
+        let parser = QueryParser::new().with_aliases(SymbolAliases::new().with_alias("Swap", "Sw"));
+
+        assert_eq!(parser.resolve_symbol("Swap"), "Sw");
+        assert!(parser.validate_symbol("Swap").is_ok());
+        assert!(parser.validate_symbol("Sw").is_ok());
+        assert!(!QueryParser::new().is_supported("Swap"));
     }
 }
@@ -0,0 +1,256 @@
+//! Reorg telemetry for verified blocks.
+//!
+//! A match in a block that is about to be orphaned may not survive, and a
+//! pattern that coincides with a reorg (e.g. a time-bandit sandwich) is
+//! worth a closer look. `ReorgTracker` keeps the block hash last seen at
+//! each recent height and compares it with what every configured provider
+//! reports now. Two kinds of disagreement count as a reorg:
+//!
+//! - providers (or an earlier observation) report different blocks at the
+//!   verified height, i.e. the block has siblings;
+//! - the verified block's parent is not the block previously seen one
+//!   height below, i.e. the chain was replaced underneath it.
+//!
+//! The depth is measured by walking down until all views agree again, up
+//! to `MAX_REORG_DEPTH` blocks. Each reorg is kept in a short per-chain
+//! history that travels with every result as `ReorgContext`.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use ethers_core::types::H256;
+use serde::{Deserialize, Serialize};
+
+/// Deepest reorg that is measured; deeper ones are reported at this depth.
+pub const MAX_REORG_DEPTH: u64 = 64;
+
+/// Blocks within this distance of an observed reorg count as near it.
+pub const NEAR_REORG_BLOCKS: u64 = 3;
+
+/// Heights whose hashes are remembered.
+const TRACKED_HEIGHTS: u64 = 256;
+
+/// Reorgs kept in the history.
+const HISTORY_LEN: usize = 32;
+
+/// A block as one provider reports it: `(hash, parent_hash)`.
+pub type BlockView = (H256, H256);
+
+/// One observed reorg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReorgEvent {
+    /// Highest height whose block was replaced.
+    pub height: u64,
+    /// Number of consecutive heights replaced, ending at `height`.
+    pub depth: u64,
+}
+
+impl ReorgEvent {
+    /// Whether `block` lies within `NEAR_REORG_BLOCKS` of the replaced range.
+    pub fn is_near(&self, block: u64) -> bool {
+        let lowest = (self.height + 1).saturating_sub(self.depth.max(1));
+        block + NEAR_REORG_BLOCKS >= lowest && block <= self.height + NEAR_REORG_BLOCKS
+    }
+}
+
+/// Reorg conditions around a verified block.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReorgContext {
+    /// Other blocks seen at this height, from other providers or earlier
+    /// observations.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sibling_hashes: Vec<H256>,
+    /// Providers that returned a block at this height.
+    pub providers_queried: usize,
+    /// Whether the block has siblings or lies near an observed reorg.
+    pub near_reorg: bool,
+    /// Reorgs observed on this chain, oldest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<ReorgEvent>,
+}
+
+impl ReorgContext {
+    /// Depth of the deepest reorg in the history.
+    pub fn max_depth(&self) -> u64 {
+        self.history.iter().map(|e| e.depth).max().unwrap_or(0)
+    }
+
+    /// Factor applied to the confidence score of a result.
+    ///
+    /// A block with siblings may be orphaned, so its matches count half;
+    /// a block near a reorg that it was not part of loses a fifth.
+    pub fn confidence_factor(&self) -> f32 {
+        if !self.sibling_hashes.is_empty() {
+            0.5
+        } else if self.near_reorg {
+            0.8
+        } else {
+            1.0
+        }
+    }
+
+    /// One-line summary, e.g. `2 sibling blocks, deepest reorg 3`.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if !self.sibling_hashes.is_empty() {
+            parts.push(format!("{} sibling blocks", self.sibling_hashes.len()));
+        } else if self.near_reorg {
+            parts.push("near a reorg".to_string());
+        }
+        if self.history.is_empty() {
+            parts.push("no reorgs observed".to_string());
+        } else {
+            parts.push(format!("deepest reorg {}", self.max_depth()));
+        }
+        parts.join(", ")
+    }
+}
+
+/// Per-chain record of recent block hashes and reorgs.
+#[derive(Debug, Default)]
+pub struct ReorgTracker {
+    seen: BTreeMap<u64, H256>,
+    events: VecDeque<ReorgEvent>,
+}
+
+impl ReorgTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hashes at `height` other than the primary view's: those reported by
+    /// other providers and the one seen there before, each once.
+    ///
+    /// The primary view is the first provider that answered.
+    pub fn siblings(&self, height: u64, views: &[Option<BlockView>]) -> Vec<H256> {
+        let mut hashes = views.iter().flatten().map(|(hash, _)| *hash);
+        let Some(primary) = hashes.next() else {
+            return Vec::new();
+        };
+        let mut siblings: Vec<H256> = Vec::new();
+        for hash in hashes.chain(self.seen.get(&height).copied()) {
+            if hash != primary && !siblings.contains(&hash) {
+                siblings.push(hash);
+            }
+        }
+        siblings
+    }
+
+    /// Whether the block seen one height below `height` is not `parent_hash`.
+    pub fn parent_replaced(&self, height: u64, parent_hash: H256) -> bool {
+        height > 0
+            && self
+                .seen
+                .get(&(height - 1))
+                .is_some_and(|seen| *seen != parent_hash)
+    }
+
+    /// Remember `hash` as the block at `height`.
+    pub fn record(&mut self, height: u64, hash: H256) {
+        self.seen.insert(height, hash);
+        let newest = self.seen.keys().next_back().copied().unwrap_or(height);
+        let oldest_kept = newest.saturating_sub(TRACKED_HEIGHTS - 1);
+        self.seen = self.seen.split_off(&oldest_kept);
+    }
+
+    /// Add a reorg to the history; a repeat at the same height keeps the
+    /// deeper measurement.
+    pub fn record_reorg(&mut self, event: ReorgEvent) {
+        if let Some(known) = self.events.iter_mut().find(|e| e.height == event.height) {
+            known.depth = known.depth.max(event.depth);
+            return;
+        }
+        self.events.push_back(event);
+        if self.events.len() > HISTORY_LEN {
+            self.events.pop_front();
+        }
+    }
+
+    /// Context for a block at `height` with the given siblings.
+    pub fn context(
+        &self,
+        height: u64,
+        sibling_hashes: Vec<H256>,
+        providers_queried: usize,
+    ) -> ReorgContext {
+        let near_reorg =
+            !sibling_hashes.is_empty() || self.events.iter().any(|e| e.is_near(height));
+        ReorgContext {
+            sibling_hashes,
+            providers_queried,
+            near_reorg,
+            history: self.events.iter().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(hash: u8, parent: u8) -> Option<BlockView> {
+        Some((H256::repeat_byte(hash), H256::repeat_byte(parent)))
+    }
+
+    #[test]
+    fn test_siblings_from_providers_and_history() {
+        let mut tracker = ReorgTracker::new();
+        assert!(tracker.siblings(10, &[view(1, 0), view(1, 0)]).is_empty());
+        assert!(tracker.siblings(10, &[None, None]).is_empty());
+
+        // A lagging provider is not a disagreement
+        assert!(tracker.siblings(10, &[view(1, 0), None]).is_empty());
+        assert_eq!(
+            tracker.siblings(10, &[view(1, 0), view(2, 0), view(2, 0)]),
+            vec![H256::repeat_byte(2)]
+        );
+
+        // A single provider changing its answer over time
+        tracker.record(10, H256::repeat_byte(1));
+        assert_eq!(
+            tracker.siblings(10, &[view(3, 0)]),
+            vec![H256::repeat_byte(1)]
+        );
+        assert!(tracker.parent_replaced(11, H256::repeat_byte(3)));
+        assert!(!tracker.parent_replaced(11, H256::repeat_byte(1)));
+        assert!(!tracker.parent_replaced(12, H256::repeat_byte(3)));
+    }
+
+    #[test]
+    fn test_history_and_context() {
+        let mut tracker = ReorgTracker::new();
+        assert!(!tracker.context(100, vec![], 2).near_reorg);
+
+        tracker.record_reorg(ReorgEvent {
+            height: 100,
+            depth: 2,
+        });
+        tracker.record_reorg(ReorgEvent {
+            height: 100,
+            depth: 3,
+        });
+        let context = tracker.context(101, vec![], 2);
+        assert_eq!(context.history.len(), 1);
+        assert_eq!(context.max_depth(), 3);
+        assert!(context.near_reorg);
+        assert_eq!(context.confidence_factor(), 0.8);
+        assert_eq!(context.summary(), "near a reorg, deepest reorg 3");
+
+        // Replaced range is 98..=100
+        assert!(tracker.context(95, vec![], 2).near_reorg);
+        assert!(!tracker.context(94, vec![], 2).near_reorg);
+        assert!(!tracker.context(104, vec![], 2).near_reorg);
+
+        let forked = tracker.context(500, vec![H256::repeat_byte(9)], 2);
+        assert!(forked.near_reorg);
+        assert_eq!(forked.confidence_factor(), 0.5);
+    }
+
+    #[test]
+    fn test_record_prunes_old_heights() {
+        let mut tracker = ReorgTracker::new();
+        tracker.record(0, H256::repeat_byte(1));
+        tracker.record(TRACKED_HEIGHTS, H256::repeat_byte(2));
+        assert!(tracker.siblings(0, &[view(5, 0)]).is_empty());
+        assert_eq!(tracker.seen.len(), 1);
+    }
+}
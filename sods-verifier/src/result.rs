@@ -5,7 +5,10 @@
 
 use serde::{Deserialize, Serialize};
 
+use crate::fees::FeeContext;
 use crate::header_anchor::VerificationMode;
+use crate::reorg::ReorgContext;
+use sods_core::SymbolProvenance;
 use std::time::Duration;
 
 /// Result of a symbol verification attempt.
@@ -33,6 +36,10 @@ use std::time::Duration;
 ///     rpc_fetch_time: Duration::from_millis(150),
 ///     total_time: Duration::from_millis(200),
 ///     error: None,
+///     dictionary_fingerprint: None,
+///     provenance: None,
+///     fees: None,
+///     reorg: None,
 /// };
 ///
 /// println!("Verified: {}", result.is_verified);
@@ -80,6 +87,26 @@ pub struct VerificationResult {
     /// Error message if verification failed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+
+    /// Fingerprint of the symbol dictionary the block was parsed with.
+    /// Results built under a different vocabulary are not comparable.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dictionary_fingerprint: Option<[u8; 32]>,
+
+    /// Least trustworthy provenance among the matched symbols.
+    /// None if nothing matched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<SymbolProvenance>,
+
+    /// Base fee, utilization, and the matched transactions' priority fees.
+    /// None if nothing matched or the block was not fetched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fees: Option<FeeContext>,
+
+    /// Sibling blocks at this height and the chain's recent reorgs.
+    /// None unless the verifier tracks reorgs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reorg: Option<ReorgContext>,
 }
 
 impl VerificationResult {
@@ -110,6 +137,10 @@ impl VerificationResult {
             rpc_fetch_time,
             total_time,
             error: None,
+            dictionary_fingerprint: None,
+            provenance: None,
+            fees: None,
+            reorg: None,
         }
     }
 
@@ -135,6 +166,10 @@ impl VerificationResult {
             rpc_fetch_time,
             total_time,
             error: Some(format!("Symbol '{}' not found in block", symbol)),
+            dictionary_fingerprint: None,
+            provenance: None,
+            fees: None,
+            reorg: None,
         }
     }
 
@@ -160,8 +195,38 @@ impl VerificationResult {
             rpc_fetch_time,
             total_time,
             error: Some(error),
+            dictionary_fingerprint: None,
+            provenance: None,
+            fees: None,
+            reorg: None,
         }
     }
+
+    /// Record the fingerprint of the dictionary used for parsing (Builder pattern).
+    pub fn with_dictionary_fingerprint(mut self, fingerprint: [u8; 32]) -> Self {
+        self.dictionary_fingerprint = Some(fingerprint);
+        self
+    }
+
+    /// Record how the matched symbols were derived (Builder pattern).
+    pub fn with_provenance(mut self, provenance: SymbolProvenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Attach the fee context of the match (Builder pattern).
+    pub fn with_fees(mut self, fees: FeeContext) -> Self {
+        self.fees = Some(fees);
+        self
+    }
+
+    /// Attach reorg telemetry, scaling the confidence score by
+    /// `ReorgContext::confidence_factor` (Builder pattern).
+    pub fn with_reorg(mut self, reorg: ReorgContext) -> Self {
+        self.confidence_score *= reorg.confidence_factor();
+        self.reorg = Some(reorg);
+        self
+    }
 }
 
 /// Custom serialization for Duration as milliseconds.
@@ -228,6 +293,16 @@ mod tests {
         assert_eq!(result.occurrences, 2);
         assert_eq!(result.confidence_score, 0.85);
         assert!(result.error.is_none());
+
+        let orphan_risk = ReorgContext {
+            sibling_hashes: vec![ethers_core::types::H256::repeat_byte(1)],
+            providers_queried: 2,
+            near_reorg: true,
+            history: vec![],
+        };
+        let result = result.with_reorg(orphan_risk);
+        assert!((result.confidence_score - 0.425).abs() < f32::EPSILON);
+        assert!(result.reorg.is_some_and(|r| r.near_reorg));
     }
 
     #[test]
@@ -3,9 +3,10 @@
 //! Wraps `ethers_providers::Provider` with LRU caching,
 //! exponential backoff retry logic, and rate limit handling.
 
-use ethers_core::types::{Address, BlockNumber, EIP1186ProofResponse, Filter, Log, H256};
-use ethers_providers::{Http, Middleware, Provider};
+use ethers_core::types::{Address, BlockNumber, EIP1186ProofResponse, Filter, Log, H160, H256};
+use ethers_providers::{Http, Middleware, Provider, ProviderError, RpcError};
 use lru::LruCache;
+use sods_core::ErrorCode;
 use std::num::NonZeroUsize;
 use std::sync::Arc;
 use std::time::Duration;
@@ -16,6 +17,24 @@ const MIN_ADAPTIVE_DELAY_MS: u64 = 100;
 const MAX_ADAPTIVE_DELAY_MS: u64 = 5000;
 const JITTER_PERCENT: f64 = 0.1;
 
+/// Blocks whose logs are kept in the LRU cache.
+const LOG_CACHE_BLOCKS: NonZeroUsize = match NonZeroUsize::new(100) {
+    Some(n) => n,
+    None => panic!("cache capacity must be non-zero"),
+};
+
+/// EIP-4788 beacon roots contract (0x000F3df6D732807Ef1319fB7B8bB8522d0Beac02).
+const BEACON_ROOTS_ADDRESS: Address = H160([
+    0x00, 0x0f, 0x3d, 0xf6, 0xd7, 0x32, 0x80, 0x7e, 0xf1, 0x31, 0x9f, 0xb7, 0xb8, 0xbb, 0x85, 0x22,
+    0xd0, 0xbe, 0xac, 0x02,
+]);
+
+/// EIP-1967 implementation slot, `keccak256("eip1967.proxy.implementation") - 1`.
+pub const EIP1967_IMPLEMENTATION_SLOT: H256 = H256([
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+]);
+
 use crate::error::{Result, SodsVerifierError};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -65,7 +84,7 @@ impl RpcClient {
             providers,
             urls: rpc_urls.to_vec(),
             current_provider_index: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
-            cache: Arc::new(RwLock::new(LruCache::new(NonZeroUsize::new(100).unwrap()))),
+            cache: Arc::new(RwLock::new(LruCache::new(LOG_CACHE_BLOCKS))),
             adaptive_delay: Arc::new(std::sync::atomic::AtomicU64::new(MIN_ADAPTIVE_DELAY_MS)),
             backoff_profile: BackoffProfile::Ethereum,
             fetch_count: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
@@ -108,26 +127,20 @@ impl RpcClient {
     }
 
     /// Update adaptive delay based on request outcome.
-    fn update_adaptive_delay(&self, success: bool, error_kind: Option<&str>) {
+    fn update_adaptive_delay(&self, success: bool, error: Option<&SodsVerifierError>) {
         let current = self
             .adaptive_delay
             .load(std::sync::atomic::Ordering::Relaxed);
 
-        let new_delay = if success {
-            std::cmp::max(MIN_ADAPTIVE_DELAY_MS, (current as f64 * 0.9) as u64)
-        } else if let Some(kind) = error_kind {
-            if kind.contains("rate limit")
-                || kind.contains("too many requests")
-                || kind.contains("429")
-            {
+        let new_delay = match error {
+            _ if success => std::cmp::max(MIN_ADAPTIVE_DELAY_MS, (current as f64 * 0.9) as u64),
+            Some(SodsVerifierError::RateLimited { .. }) => {
                 std::cmp::min(MAX_ADAPTIVE_DELAY_MS, current * 2)
-            } else if kind.contains("timeout") {
+            }
+            Some(SodsVerifierError::Timeout) => {
                 std::cmp::min(MAX_ADAPTIVE_DELAY_MS, (current as f64 * 1.5) as u64)
-            } else {
-                current
             }
-        } else {
-            current
+            _ => current,
         };
 
         if new_delay != current {
@@ -136,17 +149,25 @@ impl RpcClient {
         }
     }
 
+    /// Classifies a failed call, adjusts the adaptive delay and fails over
+    /// to the next provider.
+    fn provider_failed(
+        &self,
+        error: &ProviderError,
+        block_number: Option<u64>,
+    ) -> SodsVerifierError {
+        let error = classify_provider_error(error, block_number);
+        self.update_adaptive_delay(false, Some(&error));
+        self.switch_to_next_provider();
+        error
+    }
+
     /// Probe for EIP-4788 Beacon Root support.
     pub async fn check_beacon_support(&self) -> bool {
         // Attempt to call getBeaconRoot(0) on the precompile address
-        // Address: 0x000F3df6D732807Ef1319fB7B8bB8522d0Beac02
-        let beacon_roots_address: Address = "0x000F3df6D732807Ef1319fB7B8bB8522d0Beac02"
-            .parse()
-            .unwrap();
-
         // Calldata for getBeaconRoot(0): 00...00 (32 bytes of zeros)
         let tx = ethers_core::types::TransactionRequest::default()
-            .to(beacon_roots_address)
+            .to(BEACON_ROOTS_ADDRESS)
             .data(ethers_core::types::Bytes::from(vec![0u8; 32]));
 
         match self.current_provider().call(&tx.into(), None).await {
@@ -168,15 +189,37 @@ impl RpcClient {
                     self.update_adaptive_delay(true, None);
                     return Ok(n.as_u64());
                 }
-                Err(e) => {
-                    let err_str = e.to_string().to_lowercase();
-                    self.update_adaptive_delay(false, Some(&err_str));
-                    last_err = Some(SodsVerifierError::RpcError(e.to_string()));
-                    self.switch_to_next_provider();
+                Err(e) => last_err = Some(self.provider_failed(&e, None)),
+            }
+        }
+        Err(last_err.unwrap_or_else(no_provider_responded))
+    }
+
+    /// Implementation address in `proxy`'s EIP-1967 implementation slot as
+    /// of `block_number`, or `None` if the slot is empty, i.e. `proxy` is
+    /// not such a proxy at that block.
+    pub async fn fetch_proxy_implementation(
+        &self,
+        proxy: Address,
+        block_number: u64,
+    ) -> Result<Option<Address>> {
+        let block = Some(ethers_core::types::BlockId::Number(block_number.into()));
+        let mut last_err = None;
+        for _ in 0..self.providers.len() {
+            match self
+                .current_provider()
+                .get_storage_at(proxy, EIP1967_IMPLEMENTATION_SLOT, block)
+                .await
+            {
+                Ok(word) => {
+                    self.update_adaptive_delay(true, None);
+                    let implementation = Address::from(word);
+                    return Ok((!implementation.is_zero()).then_some(implementation));
                 }
+                Err(e) => last_err = Some(self.provider_failed(&e, None)),
             }
         }
-        Err(last_err.unwrap())
+        Err(last_err.unwrap_or_else(no_provider_responded))
     }
 
     pub async fn get_proof(
@@ -197,15 +240,10 @@ impl RpcClient {
                     self.update_adaptive_delay(true, None);
                     return Ok(p);
                 }
-                Err(e) => {
-                    let err_str = e.to_string().to_lowercase();
-                    self.update_adaptive_delay(false, Some(&err_str));
-                    last_err = Some(SodsVerifierError::RpcError(e.to_string()));
-                    self.switch_to_next_provider();
-                }
+                Err(e) => last_err = Some(self.provider_failed(&e, None)),
             }
         }
-        Err(last_err.unwrap())
+        Err(last_err.unwrap_or_else(no_provider_responded))
     }
 
     pub async fn fetch_transaction_receipt(
@@ -223,16 +261,16 @@ impl RpcClient {
                     self.update_adaptive_delay(true, None);
                     return Ok(r);
                 }
-                Ok(None) => return Err(SodsVerifierError::RpcError("Receipt not found".into())),
-                Err(e) => {
-                    let err_str = e.to_string().to_lowercase();
-                    self.update_adaptive_delay(false, Some(&err_str));
-                    last_err = Some(SodsVerifierError::RpcError(e.to_string()));
-                    self.switch_to_next_provider();
+                Ok(None) => {
+                    return Err(SodsVerifierError::TransactionNotFound(format!(
+                        "{:?}",
+                        tx_hash
+                    )))
                 }
+                Err(e) => last_err = Some(self.provider_failed(&e, None)),
             }
         }
-        Err(last_err.unwrap())
+        Err(last_err.unwrap_or_else(no_provider_responded))
     }
 
     pub async fn fetch_logs_for_block(&self, block_number: u64) -> Result<Vec<Log>> {
@@ -275,24 +313,30 @@ impl RpcClient {
         &self,
         block_number: u64,
     ) -> Result<Vec<ethers_core::types::Transaction>> {
+        self.fetch_block_with_transactions(block_number)
+            .await
+            .map(|b| b.transactions)
+    }
+
+    /// Fetch a block with full transactions, keeping its header fields
+    /// (base fee, gas used and limit) alongside.
+    pub async fn fetch_block_with_transactions(
+        &self,
+        block_number: u64,
+    ) -> Result<ethers_core::types::Block<ethers_core::types::Transaction>> {
         let mut last_err = None;
         for _ in 0..self.providers.len() {
             let provider = self.current_provider();
             match provider.get_block_with_txs(block_number).await {
                 Ok(Some(b)) => {
                     self.update_adaptive_delay(true, None);
-                    return Ok(b.transactions);
+                    return Ok(b);
                 }
                 Ok(None) => return Err(SodsVerifierError::BlockOutOfRange(block_number)),
-                Err(e) => {
-                    let err_str = e.to_string().to_lowercase();
-                    self.update_adaptive_delay(false, Some(&err_str));
-                    last_err = Some(SodsVerifierError::RpcError(e.to_string()));
-                    self.switch_to_next_provider();
-                }
+                Err(e) => last_err = Some(self.provider_failed(&e, Some(block_number))),
             }
         }
-        Err(last_err.unwrap())
+        Err(last_err.unwrap_or_else(no_provider_responded))
     }
 
     pub async fn fetch_block_header(
@@ -315,15 +359,30 @@ impl RpcClient {
                     });
                 }
                 Ok(None) => return Err(SodsVerifierError::HeaderFetchFailed(block_number)),
-                Err(e) => {
-                    let err_str = e.to_string().to_lowercase();
-                    self.update_adaptive_delay(false, Some(&err_str));
-                    last_err = Some(SodsVerifierError::RpcError(e.to_string()));
-                    self.switch_to_next_provider();
-                }
+                Err(e) => last_err = Some(self.provider_failed(&e, Some(block_number))),
             }
         }
-        Err(last_err.unwrap())
+        Err(last_err.unwrap_or_else(no_provider_responded))
+    }
+
+    /// Ask every provider for the block at `block_number`, in provider
+    /// order, without retries or failover.
+    ///
+    /// Each entry is `(hash, parent_hash)`, or `None` if that provider
+    /// failed or does not have the block yet.
+    pub async fn fetch_block_views(
+        &self,
+        block_number: u64,
+    ) -> Vec<Option<crate::reorg::BlockView>> {
+        let mut views = Vec::with_capacity(self.providers.len());
+        for provider in &self.providers {
+            let view = match provider.get_block(block_number).await {
+                Ok(Some(block)) => block.hash.map(|hash| (hash, block.parent_hash)),
+                _ => None,
+            };
+            views.push(view);
+        }
+        views
     }
 
     pub async fn fetch_contract_deployer(
@@ -343,17 +402,12 @@ impl RpcClient {
                     code_fetched = true;
                     break;
                 }
-                Err(e) => {
-                    let err_str = e.to_string().to_lowercase();
-                    self.update_adaptive_delay(false, Some(&err_str));
-                    last_err = Some(SodsVerifierError::RpcError(e.to_string()));
-                    self.switch_to_next_provider();
-                }
+                Err(e) => last_err = Some(self.provider_failed(&e, None)),
             }
         }
 
         if !code_fetched {
-            return Err(last_err.unwrap());
+            return Err(last_err.unwrap_or_else(no_provider_responded));
         }
 
         // Search for deployment tx (expensive, limited to first 1000 blocks)
@@ -416,12 +470,7 @@ impl RpcClient {
                     break;
                 }
                 Ok(None) => return Err(SodsVerifierError::BlockOutOfRange(block_number)),
-                Err(e) => {
-                    let err_str = e.to_string().to_lowercase();
-                    self.update_adaptive_delay(false, Some(&err_str));
-                    last_err = Some(SodsVerifierError::RpcError(e.to_string()));
-                    self.switch_to_next_provider();
-                }
+                Err(e) => last_err = Some(self.provider_failed(&e, Some(block_number))),
             }
         }
 
@@ -438,6 +487,7 @@ impl RpcClient {
         let mut receipts = Vec::with_capacity(block_transactions.len());
         for tx_hash in block_transactions {
             let mut matched_receipt = None;
+            let mut receipt_err = None;
             for _ in 0..self.providers.len() {
                 let provider = self.current_provider();
                 match provider.get_transaction_receipt(tx_hash).await {
@@ -445,13 +495,22 @@ impl RpcClient {
                         matched_receipt = Some(r);
                         break;
                     }
-                    Ok(None) => return Err(SodsVerifierError::ReceiptFetchFailed(block_number)),
-                    Err(_) => self.switch_to_next_provider(),
+                    Ok(None) => {
+                        return Err(SodsVerifierError::DataInconsistent(format!(
+                            "block {} lists transaction {:?} but no receipt was returned",
+                            block_number, tx_hash
+                        )))
+                    }
+                    Err(e) => receipt_err = Some(self.provider_failed(&e, None)),
                 }
             }
             match matched_receipt {
                 Some(r) => receipts.push(r),
-                None => return Err(SodsVerifierError::ReceiptFetchFailed(block_number)),
+                None => {
+                    return Err(
+                        receipt_err.unwrap_or(SodsVerifierError::ReceiptFetchFailed(block_number))
+                    )
+                }
             }
         }
 
@@ -497,31 +556,35 @@ impl RpcClient {
                         return Ok(logs);
                     }
                     Err(e) => {
-                        let error = self.classify_error(e, block_number);
-                        let msg = match &error {
-                            SodsVerifierError::RpcError(m) => Some(m.as_str()),
-                            _ => None,
-                        };
-                        self.update_adaptive_delay(false, msg);
-
-                        if !Self::is_transient_error(&error) {
+                        let error = classify_provider_error(&e, Some(block_number));
+                        self.update_adaptive_delay(false, Some(&error));
+
+                        let retry_after = error.retry_after();
+                        let is_rate_limit = matches!(error, SodsVerifierError::RateLimited { .. });
+                        let retryable = error.is_retryable();
+                        last_error = Some(error);
+
+                        if !retryable {
                             break; // Try next provider immediately
                         }
 
                         // If it's a timeout or connection error (not a rate limit),
                         // we switch provider earlier instead of exhausting all retries on a broken one.
-                        let is_rate_limit = msg.map(|m| m.contains("rate")).unwrap_or(false);
                         if !is_rate_limit && attempt >= 1 {
                             break; // Failover sooner for connection issues
                         }
 
-                        last_error = Some(error);
-
                         if attempt < max_retries - 1 {
                             let jitter =
                                 rand::random::<f64>() * JITTER_PERCENT * 2.0 - JITTER_PERCENT;
                             let delay = (profile_delays[attempt] as f64 * (1.0 + jitter)) as u64;
-                            sleep(Duration::from_millis(delay)).await;
+                            // Honour the provider's own Retry-After when it asks for longer
+                            let delay = Duration::from_millis(delay).max(
+                                retry_after
+                                    .unwrap_or_default()
+                                    .min(Duration::from_millis(MAX_ADAPTIVE_DELAY_MS)),
+                            );
+                            sleep(delay).await;
                         }
                     }
                 }
@@ -555,43 +618,87 @@ impl RpcClient {
     pub async fn clear_cache(&self) {
         self.cache.write().await.clear();
     }
+}
 
-    /// Classify an ethers error into our error type.
-    fn classify_error(
-        &self,
-        error: ethers_providers::ProviderError,
-        block_number: u64,
-    ) -> SodsVerifierError {
-        let error_str = error.to_string().to_lowercase();
+/// Error for a failover loop that ended without any provider answering.
+fn no_provider_responded() -> SodsVerifierError {
+    SodsVerifierError::ProviderUnavailable("no RPC provider responded".into())
+}
 
-        // Common error patterns
-        if error_str.contains("block not found")
-            || error_str.contains("unknown block")
-            || error_str.contains("header not found")
-        {
-            return SodsVerifierError::BlockOutOfRange(block_number);
+/// Maps a provider error onto a typed verifier error.
+///
+/// JSON-RPC error codes are checked first (429 and -32005 are the usual
+/// "limit exceeded" codes); everything else falls back to the message, since
+/// HTTP-level failures only surface as text. `block_number` lets "unknown
+/// block" replies become `BlockOutOfRange`.
+pub(crate) fn classify_provider_error(
+    error: &ProviderError,
+    block_number: Option<u64>,
+) -> SodsVerifierError {
+    let message = error.to_string();
+    let lower = message.to_lowercase();
+
+    if let Some(response) = error.as_error_response() {
+        if response.code == 429 || response.code == -32005 {
+            return SodsVerifierError::RateLimited {
+                retry_after: parse_retry_after(&response.message.to_lowercase()),
+            };
         }
+    }
 
-        if error_str.contains("rate limit") || error_str.contains("too many requests") {
-            return SodsVerifierError::RpcError(
-                "Rate limited by RPC provider. Try again later.".to_string(),
-            );
+    if let Some(block) = block_number {
+        if lower.contains("block not found")
+            || lower.contains("unknown block")
+            || lower.contains("header not found")
+        {
+            return SodsVerifierError::BlockOutOfRange(block);
         }
+    }
 
-        SodsVerifierError::RpcError(error.to_string())
+    if lower.contains("rate limit")
+        || lower.contains("too many requests")
+        || lower.contains("429")
+        || lower.contains("limit exceeded")
+    {
+        return SodsVerifierError::RateLimited {
+            retry_after: parse_retry_after(&lower),
+        };
     }
 
-    /// Check if an error is transient and should be retried.
-    fn is_transient_error(error: &SodsVerifierError) -> bool {
-        match error {
-            SodsVerifierError::RpcError(msg) => {
-                msg.contains("timeout")
-                    || msg.contains("connection")
-                    || msg.contains("rate limit")
-                    || msg.contains("temporarily")
-            }
-            _ => false,
-        }
+    if lower.contains("timed out") || lower.contains("timeout") {
+        return SodsVerifierError::Timeout;
+    }
+
+    if lower.contains("connection")
+        || lower.contains("error sending request")
+        || lower.contains("temporarily")
+        || lower.contains("unavailable")
+        || lower.contains("bad gateway")
+        || lower.contains("502")
+        || lower.contains("503")
+        || lower.contains("504")
+    {
+        return SodsVerifierError::ProviderUnavailable(message);
+    }
+
+    SodsVerifierError::RpcError(message)
+}
+
+/// Best-effort parse of "retry after 3s" / "try again in 500ms" style hints.
+fn parse_retry_after(message: &str) -> Option<Duration> {
+    let rest = ["retry after", "retry-after", "try again in", "retry in"]
+        .iter()
+        .find_map(|marker| message.find(marker).map(|i| &message[i + marker.len()..]))?;
+    let rest = rest.trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+    let digits = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let value: u64 = rest[..digits].parse().ok()?;
+    let unit = rest[digits..].trim_start();
+    if unit.starts_with("ms") || unit.starts_with("milli") {
+        Some(Duration::from_millis(value))
+    } else {
+        Some(Duration::from_secs(value))
     }
 }
 
@@ -599,6 +706,17 @@ impl RpcClient {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_eip1967_implementation_slot() {
+        use ethers_core::types::U256;
+        let hash = U256::from(ethers_core::utils::keccak256(
+            "eip1967.proxy.implementation",
+        ));
+        let mut slot = [0u8; 32];
+        (hash - 1).to_big_endian(&mut slot);
+        assert_eq!(EIP1967_IMPLEMENTATION_SLOT, H256(slot));
+    }
+
     #[test]
     fn test_invalid_url() {
         let urls = vec!["not-a-valid-url".to_string()];
@@ -613,6 +731,14 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_beacon_roots_address() {
+        let parsed: Address = "0x000F3df6D732807Ef1319fB7B8bB8522d0Beac02"
+            .parse()
+            .unwrap();
+        assert_eq!(BEACON_ROOTS_ADDRESS, parsed);
+    }
+
     #[test]
     fn test_backoff_profiles() {
         let eth = BackoffProfile::Ethereum;
@@ -644,4 +770,50 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
     }
+
+    #[test]
+    fn test_provider_errors_are_typed() {
+        let limited = ProviderError::JsonRpcClientError(Box::new(
+            ethers_providers::HttpClientError::JsonRpcError(ethers_providers::JsonRpcError {
+                code: -32005,
+                message: "daily request count exceeded, try again in 30 seconds".into(),
+                data: None,
+            }),
+        ));
+        let err = classify_provider_error(&limited, None);
+        assert!(matches!(err, SodsVerifierError::RateLimited { .. }));
+        assert_eq!(err.retry_after(), Some(Duration::from_secs(30)));
+
+        let custom = |msg: &str| ProviderError::CustomError(msg.to_string());
+        assert!(matches!(
+            classify_provider_error(&custom("429 Too Many Requests"), None),
+            SodsVerifierError::RateLimited { retry_after: None }
+        ));
+        assert!(matches!(
+            classify_provider_error(&custom("operation timed out"), None),
+            SodsVerifierError::Timeout
+        ));
+        assert!(matches!(
+            classify_provider_error(&custom("error sending request: connection refused"), None),
+            SodsVerifierError::ProviderUnavailable(_)
+        ));
+        assert!(matches!(
+            classify_provider_error(&custom("header not found"), Some(7)),
+            SodsVerifierError::BlockOutOfRange(7)
+        ));
+        assert!(!classify_provider_error(&custom("execution reverted"), None).is_retryable());
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(
+            parse_retry_after("rate limited, retry after 2s"),
+            Some(Duration::from_secs(2))
+        );
+        assert_eq!(
+            parse_retry_after("try again in 250ms"),
+            Some(Duration::from_millis(250))
+        );
+        assert_eq!(parse_retry_after("too many requests"), None);
+    }
 }
@@ -0,0 +1,371 @@
+//! Mempool snapshots for post-hoc forensics.
+//!
+//! A `MempoolSnapshot` remembers every pending transaction a
+//! `MempoolMonitor` saw, together with the symbols it predicted for it.
+//! As blocks arrive, included transactions are reconciled against the
+//! symbols their receipts actually produced, which yields
+//! predicted-vs-realized accuracy. Transactions that stay unseen in blocks
+//! for longer than `vanish_after` are moved to a forensic record: they were
+//! dropped, replaced, or (commonly) landed through a private relay under a
+//! different hash.
+//!
+//! Times are unix seconds supplied by the caller, so the store itself does
+//! no I/O and can be driven from recorded data.
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use ethers_core::types::H256;
+
+/// Forensic records kept for vanished transactions.
+const VANISHED_RECORDS: usize = 1000;
+
+#[derive(Debug, Clone)]
+struct SnapshotEntry {
+    first_seen: u64,
+    predicted: Vec<String>,
+}
+
+/// A pending transaction that never made it into an observed block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VanishedTx {
+    pub tx_hash: H256,
+    /// Unix time the transaction was first seen pending
+    pub first_seen: u64,
+    /// Unix time it was declared vanished
+    pub vanished_at: u64,
+    pub predicted: Vec<String>,
+}
+
+/// Result of reconciling one block.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BlockReconciliation {
+    pub block_number: u64,
+    /// Block transactions that had been seen pending
+    pub seen_included: usize,
+    /// Of those, how many had all predicted symbols realized exactly
+    pub exact_predictions: usize,
+}
+
+/// Accumulated predicted-vs-realized statistics.
+///
+/// Symbol counts only cover included transactions for which at least one
+/// symbol was predicted; their receipts are the only ones fetched.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SnapshotStats {
+    /// Pending transactions recorded
+    pub seen: u64,
+    /// Seen transactions later found in a block
+    pub included: u64,
+    /// Seen transactions declared vanished
+    pub vanished: u64,
+    /// Pending entries dropped because the store was full
+    pub evicted: u64,
+    /// Included transactions that had predictions
+    pub predicted_included: u64,
+    /// Of those, how many realized exactly the predicted symbols
+    pub exact_matches: u64,
+    pub predicted_symbols: u64,
+    pub realized_symbols: u64,
+    /// Symbols both predicted and realized (counted per occurrence)
+    pub matched_symbols: u64,
+    /// Sum of seen-to-inclusion delays, in seconds
+    pub inclusion_delay_secs: u64,
+}
+
+impl SnapshotStats {
+    /// Share of predicted symbols that were realized.
+    pub fn precision(&self) -> Option<f64> {
+        (self.predicted_symbols > 0)
+            .then(|| self.matched_symbols as f64 / self.predicted_symbols as f64)
+    }
+
+    /// Share of realized symbols that were predicted.
+    pub fn recall(&self) -> Option<f64> {
+        (self.realized_symbols > 0)
+            .then(|| self.matched_symbols as f64 / self.realized_symbols as f64)
+    }
+
+    /// Mean time from first sighting to inclusion.
+    pub fn mean_inclusion_delay(&self) -> Option<Duration> {
+        (self.included > 0).then(|| Duration::from_secs(self.inclusion_delay_secs / self.included))
+    }
+}
+
+/// Rolling store of pending transactions and their predicted symbols.
+#[derive(Debug)]
+pub struct MempoolSnapshot {
+    capacity: usize,
+    vanish_after: Duration,
+    entries: HashMap<H256, SnapshotEntry>,
+    /// Hashes in first-seen order; may hold hashes already reconciled
+    order: VecDeque<H256>,
+    vanished: VecDeque<VanishedTx>,
+    stats: SnapshotStats,
+}
+
+impl MempoolSnapshot {
+    /// Default number of pending transactions tracked at once.
+    pub const DEFAULT_CAPACITY: usize = 50_000;
+
+    /// Default time after which an unincluded transaction counts as vanished.
+    pub const DEFAULT_VANISH_AFTER: Duration = Duration::from_secs(120);
+
+    /// Create an empty snapshot tracking at most `capacity` transactions.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            vanish_after: Self::DEFAULT_VANISH_AFTER,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            vanished: VecDeque::new(),
+            stats: SnapshotStats::default(),
+        }
+    }
+
+    /// Set how long a transaction may stay pending before it is declared
+    /// vanished (Builder pattern).
+    pub fn with_vanish_after(mut self, vanish_after: Duration) -> Self {
+        self.vanish_after = vanish_after;
+        self
+    }
+
+    /// Record a pending transaction at unix time `now`.
+    ///
+    /// Recording a known hash again replaces its predicted symbols (e.g.
+    /// after simulation) but keeps the first-seen time.
+    pub fn record(&mut self, tx_hash: H256, predicted: Vec<String>, now: u64) {
+        if let Some(entry) = self.entries.get_mut(&tx_hash) {
+            entry.predicted = predicted;
+            return;
+        }
+
+        while self.entries.len() >= self.capacity {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            if self.entries.remove(&oldest).is_some() {
+                self.stats.evicted += 1;
+            }
+        }
+
+        self.entries.insert(
+            tx_hash,
+            SnapshotEntry {
+                first_seen: now,
+                predicted,
+            },
+        );
+        self.order.push_back(tx_hash);
+        self.stats.seen += 1;
+    }
+
+    /// Hashes among `block_txs` whose receipts are needed for reconciliation,
+    /// i.e. tracked transactions with at least one predicted symbol.
+    pub fn awaiting_receipts(&self, block_txs: &[H256]) -> Vec<H256> {
+        block_txs
+            .iter()
+            .filter(|hash| {
+                self.entries
+                    .get(hash)
+                    .is_some_and(|entry| !entry.predicted.is_empty())
+            })
+            .copied()
+            .collect()
+    }
+
+    /// Reconcile a block's transactions.
+    ///
+    /// `realized` maps hashes returned by `awaiting_receipts` to the symbols
+    /// their receipts produced; a missing hash is skipped for accuracy but
+    /// still counted as included.
+    pub fn reconcile_block(
+        &mut self,
+        block_number: u64,
+        timestamp: u64,
+        block_txs: &[H256],
+        realized: &HashMap<H256, Vec<String>>,
+    ) -> BlockReconciliation {
+        let mut result = BlockReconciliation {
+            block_number,
+            ..Default::default()
+        };
+
+        for hash in block_txs {
+            let Some(entry) = self.entries.remove(hash) else {
+                continue;
+            };
+            result.seen_included += 1;
+            self.stats.included += 1;
+            self.stats.inclusion_delay_secs += timestamp.saturating_sub(entry.first_seen);
+
+            let Some(actual) = realized.get(hash).filter(|_| !entry.predicted.is_empty()) else {
+                continue;
+            };
+            let matched = matched_count(&entry.predicted, actual);
+            self.stats.predicted_included += 1;
+            self.stats.predicted_symbols += entry.predicted.len() as u64;
+            self.stats.realized_symbols += actual.len() as u64;
+            self.stats.matched_symbols += matched as u64;
+            if matched == entry.predicted.len() && matched == actual.len() {
+                self.stats.exact_matches += 1;
+                result.exact_predictions += 1;
+            }
+        }
+
+        result
+    }
+
+    /// Declare transactions pending for longer than `vanish_after` vanished,
+    /// returning the new forensic records.
+    pub fn expire(&mut self, now: u64) -> Vec<VanishedTx> {
+        let cutoff = now.saturating_sub(self.vanish_after.as_secs());
+        let mut expired = Vec::new();
+
+        while let Some(hash) = self.order.front().copied() {
+            match self.entries.get(&hash) {
+                Some(entry) if entry.first_seen >= cutoff => break,
+                Some(_) => {
+                    if let Some(entry) = self.entries.remove(&hash) {
+                        expired.push(VanishedTx {
+                            tx_hash: hash,
+                            first_seen: entry.first_seen,
+                            vanished_at: now,
+                            predicted: entry.predicted,
+                        });
+                    }
+                }
+                // Already reconciled
+                None => {}
+            }
+            self.order.pop_front();
+        }
+
+        self.stats.vanished += expired.len() as u64;
+        for record in &expired {
+            if self.vanished.len() == VANISHED_RECORDS {
+                self.vanished.pop_front();
+            }
+            self.vanished.push_back(record.clone());
+        }
+        expired
+    }
+
+    /// Most recent vanished transactions, oldest first.
+    pub fn vanished(&self) -> impl Iterator<Item = &VanishedTx> {
+        self.vanished.iter()
+    }
+
+    pub fn stats(&self) -> &SnapshotStats {
+        &self.stats
+    }
+
+    /// Transactions currently awaiting inclusion.
+    pub fn pending(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl Default for MempoolSnapshot {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
+
+/// Symbols common to both lists, counting repeats.
+fn matched_count(predicted: &[String], realized: &[String]) -> usize {
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for symbol in realized {
+        *remaining.entry(symbol.as_str()).or_default() += 1;
+    }
+    predicted
+        .iter()
+        .filter(|symbol| match remaining.get_mut(symbol.as_str()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                true
+            }
+            _ => false,
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_reconcile_predicted_vs_realized() {
+        let mut snapshot = MempoolSnapshot::default();
+        let exact = H256::repeat_byte(1);
+        let partial = H256::repeat_byte(2);
+        let unpredicted = H256::repeat_byte(3);
+        snapshot.record(exact, symbols(&["Tf"]), 100);
+        snapshot.record(partial, symbols(&["Sw", "Tf"]), 100);
+        snapshot.record(unpredicted, Vec::new(), 100);
+
+        let block = [exact, partial, unpredicted, H256::repeat_byte(9)];
+        assert_eq!(snapshot.awaiting_receipts(&block), vec![exact, partial]);
+
+        let realized = HashMap::from([
+            (exact, symbols(&["Tf"])),
+            (partial, symbols(&["Tf", "Tf", "Tf"])),
+        ]);
+        let result = snapshot.reconcile_block(7, 112, &block, &realized);
+        assert_eq!(result.seen_included, 3);
+        assert_eq!(result.exact_predictions, 1);
+
+        let stats = snapshot.stats();
+        assert_eq!(stats.included, 3);
+        assert_eq!(stats.predicted_included, 2);
+        assert_eq!(stats.precision(), Some(2.0 / 3.0));
+        assert_eq!(stats.recall(), Some(2.0 / 4.0));
+        assert_eq!(stats.mean_inclusion_delay(), Some(Duration::from_secs(12)));
+        assert_eq!(snapshot.pending(), 0);
+    }
+
+    #[test]
+    fn test_unincluded_transactions_vanish() {
+        let mut snapshot = MempoolSnapshot::new(10).with_vanish_after(Duration::from_secs(60));
+        let early = H256::repeat_byte(1);
+        let mined = H256::repeat_byte(2);
+        let late = H256::repeat_byte(3);
+        snapshot.record(early, symbols(&["Sw"]), 0);
+        snapshot.record(mined, Vec::new(), 10);
+        snapshot.record(late, Vec::new(), 50);
+        snapshot.reconcile_block(1, 12, &[mined], &HashMap::new());
+
+        let vanished = snapshot.expire(100);
+        assert_eq!(vanished.len(), 1);
+        assert_eq!(vanished[0].tx_hash, early);
+        assert_eq!(vanished[0].predicted, symbols(&["Sw"]));
+        assert_eq!(snapshot.pending(), 1);
+
+        assert_eq!(snapshot.expire(111).len(), 1);
+        assert_eq!(snapshot.stats().vanished, 2);
+        assert_eq!(snapshot.vanished().count(), 2);
+    }
+
+    #[test]
+    fn test_capacity_evicts_oldest() {
+        let mut snapshot = MempoolSnapshot::new(2);
+        for i in 1..=3u8 {
+            snapshot.record(H256::repeat_byte(i), Vec::new(), i as u64);
+        }
+        // Re-recording keeps the entry without counting it twice
+        snapshot.record(H256::repeat_byte(3), symbols(&["Tf"]), 9);
+
+        assert_eq!(snapshot.pending(), 2);
+        assert_eq!(snapshot.stats().seen, 3);
+        assert_eq!(snapshot.stats().evicted, 1);
+        assert_eq!(
+            snapshot.awaiting_receipts(&[H256::repeat_byte(1), H256::repeat_byte(3)]),
+            vec![H256::repeat_byte(3)]
+        );
+    }
+}
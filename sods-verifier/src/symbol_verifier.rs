@@ -0,0 +1,65 @@
+//! The verification interface other layers depend on.
+//!
+//! `sods-p2p` takes a `SymbolVerifier` instead of a concrete
+//! `BlockVerifier`, so tests can drive consensus and puzzles against an
+//! in-memory chain instead of a live RPC endpoint. The daemon and the CLI
+//! commands use `BlockVerifier` directly, and only hand it to a
+//! `HeadTracker` as a `SymbolVerifier`.
+
+use async_trait::async_trait;
+use sods_core::BehavioralSymbol;
+
+use crate::error::Result;
+use crate::result::VerificationResult;
+use crate::verifier::BlockVerifier;
+
+/// Verifies behavioral symbols and patterns in blocks.
+#[async_trait]
+pub trait SymbolVerifier: Send + Sync {
+    /// Verify that `symbol` occurs in `block_number`.
+    async fn verify_symbol_in_block(
+        &self,
+        symbol: &str,
+        block_number: u64,
+    ) -> Result<VerificationResult>;
+
+    /// Verify a pattern against logs checked against the block header.
+    async fn verify_pattern_trustless(
+        &self,
+        pattern: &str,
+        block_number: u64,
+    ) -> Result<VerificationResult>;
+
+    /// All behavioral symbols in `block_number`, in log order.
+    async fn fetch_block_symbols(&self, block_number: u64) -> Result<Vec<BehavioralSymbol>>;
+
+    /// Number of the latest block.
+    async fn get_latest_block(&self) -> Result<u64>;
+}
+
+#[async_trait]
+impl SymbolVerifier for BlockVerifier {
+    async fn verify_symbol_in_block(
+        &self,
+        symbol: &str,
+        block_number: u64,
+    ) -> Result<VerificationResult> {
+        BlockVerifier::verify_symbol_in_block(self, symbol, block_number).await
+    }
+
+    async fn verify_pattern_trustless(
+        &self,
+        pattern: &str,
+        block_number: u64,
+    ) -> Result<VerificationResult> {
+        BlockVerifier::verify_pattern_trustless(self, pattern, block_number).await
+    }
+
+    async fn fetch_block_symbols(&self, block_number: u64) -> Result<Vec<BehavioralSymbol>> {
+        BlockVerifier::fetch_block_symbols(self, block_number).await
+    }
+
+    async fn get_latest_block(&self) -> Result<u64> {
+        BlockVerifier::get_latest_block(self).await
+    }
+}
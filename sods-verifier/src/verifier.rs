@@ -5,16 +5,135 @@
 
 use std::collections::HashMap;
 use std::num::NonZeroUsize;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard};
 use std::time::Instant;
 
-use ethers_core::types::{Address, H256};
-use sods_core::{BehavioralMerkleTree, BehavioralSymbol, ContractRegistry, SymbolDictionary};
+use ethers_core::types::{Address, Block, Bloom, Log, Transaction, Withdrawal, H256};
+use serde::{Deserialize, Serialize};
+use sods_core::pattern::{BehavioralPattern, CompiledPattern, MatchExplanation};
+use sods_core::{
+    BehavioralMerkleTree, BehavioralSymbol, ContractRegistry, SymbolDictionary, SymbolProvenance,
+    BLOB_TX_SYMBOL, CL_WITHDRAWAL_SYMBOL,
+};
 
+use crate::blob::apply_blob_context;
 use crate::error::{Result, SodsVerifierError};
+use crate::fees::BlockFees;
 use crate::query::QueryParser;
+use crate::reorg::{ReorgContext, ReorgEvent, ReorgTracker, MAX_REORG_DEPTH};
 use crate::result::VerificationResult;
 use crate::rpc::RpcClient;
+use crate::withdrawal::{apply_withdrawals, verify_withdrawals_root};
+
+/// Capacity of the pattern result cache.
+const PATTERN_CACHE_ENTRIES: NonZeroUsize = match NonZeroUsize::new(500) {
+    Some(n) => n,
+    None => panic!("cache capacity must be non-zero"),
+};
+
+/// Capacity of the compiled pattern cache.
+const COMPILED_PATTERN_ENTRIES: NonZeroUsize = match NonZeroUsize::new(256) {
+    Some(n) => n,
+    None => panic!("cache capacity must be non-zero"),
+};
+
+/// Capacity of the proxy implementation cache.
+const PROXY_CACHE_ENTRIES: NonZeroUsize = match NonZeroUsize::new(4096) {
+    Some(n) => n,
+    None => panic!("cache capacity must be non-zero"),
+};
+
+/// Implementation behind each (contract, block), `None` for non-proxies.
+type ProxyCache = lru::LruCache<(Address, u64), Option<Address>>;
+
+/// Blocks whose logs and transactions are kept for symbol pagination.
+const PAGED_BLOCKS: NonZeroUsize = match NonZeroUsize::new(8) {
+    Some(n) => n,
+    None => panic!("cache capacity must be non-zero"),
+};
+
+/// What pages of a block's symbols are built from.
+struct PageSource {
+    logs: Vec<Log>,
+    tx_map: HashMap<H256, (ethers_core::types::U256, Address)>,
+}
+
+/// Most proxy slots read at once while resolving one block's symbols.
+const PROXY_LOOKUP_CONCURRENCY: usize = 8;
+
+/// Locks a cache, recovering it if another thread panicked while holding it.
+///
+/// Entries are inserted whole, so a poisoned cache is still consistent.
+fn lock_cache<T>(cache: &Mutex<T>) -> MutexGuard<'_, T> {
+    cache
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Result for one pattern of `BlockVerifier::verify_patterns_in_block`.
+#[derive(Debug)]
+pub struct PatternOutcome {
+    /// The pattern as given.
+    pub pattern: String,
+    /// Verification result, or why this pattern could not be verified.
+    pub result: Result<VerificationResult>,
+    /// Symbols of the first match in order; empty if there was none.
+    pub matched: Vec<BehavioralSymbol>,
+    /// The verified symbols the pattern was matched against, stamped with
+    /// the block's number and timestamp, for feeding a stream matcher.
+    /// Only kept for patterns with a `within` window; empty otherwise.
+    pub symbols: Vec<BehavioralSymbol>,
+}
+
+/// A block's logs and transactions fetched by
+/// `BlockVerifier::prefetch_block`, waiting to be matched.
+#[derive(Debug)]
+pub struct PrefetchedBlock {
+    number: u64,
+    /// Topics the logs were filtered to, sorted; empty for all logs
+    topics: Vec<H256>,
+    /// `None` if no pattern parsed, so nothing was fetched
+    data: Option<(Vec<Log>, Block<Transaction>)>,
+    fetch_time: std::time::Duration,
+}
+
+impl PrefetchedBlock {
+    /// The block's number.
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+}
+
+/// Upper bound on the symbols returned in one `SymbolPage`.
+pub const MAX_SYMBOLS_PER_PAGE: usize = 10_000;
+
+/// Position in a block's logs where the next `SymbolPage` starts.
+///
+/// Offsets count logs, not symbols, since logs without a known symbol are
+/// skipped. The cursor remembers the block's log count so a page requested
+/// after the block changed (e.g. a reorg) is refused instead of silently
+/// skipping or repeating symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolCursor {
+    pub block_number: u64,
+    pub log_offset: usize,
+    pub total_logs: usize,
+}
+
+/// One page of a block's behavioral symbols.
+#[derive(Debug, Clone)]
+pub struct SymbolPage {
+    pub block_number: u64,
+    pub symbols: Vec<BehavioralSymbol>,
+    /// Where the next page starts; `None` once the block is exhausted
+    pub next_cursor: Option<SymbolCursor>,
+    /// Logs in the block, including ones that produced no symbol
+    pub total_logs: usize,
+    /// Effective page size after applying `MAX_SYMBOLS_PER_PAGE`
+    pub limit: usize,
+    /// Whether the block holds more symbols than this page returned
+    pub truncated: bool,
+}
 
 /// Network support level for EIP-4788 Beacon Roots.
 #[derive(Debug, Clone, PartialEq)]
@@ -82,6 +201,16 @@ pub struct BlockVerifier {
     registry: ContractRegistry,
     /// Cache for pattern verification results (block_number, pattern -> result)
     pattern_cache: Arc<Mutex<lru::LruCache<(u64, String), VerificationResult>>>,
+    /// Patterns compiled so far, so each is parsed and compiled only once
+    compiled_patterns: Arc<Mutex<lru::LruCache<String, Arc<CompiledPattern>>>>,
+    /// Block hashes and reorgs seen so far; None unless reorg tracking is on.
+    reorg_tracker: Option<Arc<Mutex<ReorgTracker>>>,
+    /// Whether symbols record the implementation behind EIP-1967 proxies.
+    proxy_resolution: bool,
+    /// Cache for proxy implementations ((contract_address, block) -> implementation).
+    proxy_cache: Arc<Mutex<ProxyCache>>,
+    /// Logs and transactions of recently paged blocks, by block number.
+    page_sources: Arc<Mutex<lru::LruCache<u64, Arc<PageSource>>>>,
 }
 
 impl BlockVerifier {
@@ -118,9 +247,12 @@ impl BlockVerifier {
             verification_mode: crate::header_anchor::VerificationMode::Trustless,
             deployer_cache: Arc::new(Mutex::new(HashMap::new())),
             registry: ContractRegistry::load_local().unwrap_or_else(|_| ContractRegistry::new()),
-            pattern_cache: Arc::new(Mutex::new(lru::LruCache::new(
-                NonZeroUsize::new(500).unwrap(),
-            ))),
+            pattern_cache: Arc::new(Mutex::new(lru::LruCache::new(PATTERN_CACHE_ENTRIES))),
+            compiled_patterns: Arc::new(Mutex::new(lru::LruCache::new(COMPILED_PATTERN_ENTRIES))),
+            reorg_tracker: None,
+            proxy_resolution: false,
+            proxy_cache: Arc::new(Mutex::new(lru::LruCache::new(PROXY_CACHE_ENTRIES))),
+            page_sources: Arc::new(Mutex::new(lru::LruCache::new(PAGED_BLOCKS))),
         })
     }
 
@@ -135,9 +267,12 @@ impl BlockVerifier {
             verification_mode: crate::header_anchor::VerificationMode::ZeroRpc,
             deployer_cache: Arc::new(Mutex::new(HashMap::new())),
             registry: ContractRegistry::load_local().unwrap_or_else(|_| ContractRegistry::new()),
-            pattern_cache: Arc::new(Mutex::new(lru::LruCache::new(
-                NonZeroUsize::new(500).unwrap(),
-            ))),
+            pattern_cache: Arc::new(Mutex::new(lru::LruCache::new(PATTERN_CACHE_ENTRIES))),
+            compiled_patterns: Arc::new(Mutex::new(lru::LruCache::new(COMPILED_PATTERN_ENTRIES))),
+            reorg_tracker: None,
+            proxy_resolution: false,
+            proxy_cache: Arc::new(Mutex::new(lru::LruCache::new(PROXY_CACHE_ENTRIES))),
+            page_sources: Arc::new(Mutex::new(lru::LruCache::new(PAGED_BLOCKS))),
         })
     }
 
@@ -152,9 +287,12 @@ impl BlockVerifier {
             verification_mode: crate::header_anchor::VerificationMode::RpcOnly,
             deployer_cache: Arc::new(Mutex::new(HashMap::new())),
             registry: ContractRegistry::load_local().unwrap_or_else(|_| ContractRegistry::new()),
-            pattern_cache: Arc::new(Mutex::new(lru::LruCache::new(
-                NonZeroUsize::new(500).unwrap(),
-            ))),
+            pattern_cache: Arc::new(Mutex::new(lru::LruCache::new(PATTERN_CACHE_ENTRIES))),
+            compiled_patterns: Arc::new(Mutex::new(lru::LruCache::new(COMPILED_PATTERN_ENTRIES))),
+            reorg_tracker: None,
+            proxy_resolution: false,
+            proxy_cache: Arc::new(Mutex::new(lru::LruCache::new(PROXY_CACHE_ENTRIES))),
+            page_sources: Arc::new(Mutex::new(lru::LruCache::new(PAGED_BLOCKS))),
         })
     }
 
@@ -174,6 +312,41 @@ impl BlockVerifier {
         self
     }
 
+    /// Resolve symbol aliases in queries and patterns (Builder pattern).
+    ///
+    /// Aliases are resolved before hashing, so results and proofs carry
+    /// canonical symbols only.
+    pub fn with_aliases(mut self, aliases: sods_core::aliases::SymbolAliases) -> Self {
+        self.query_parser = self.query_parser.with_aliases(aliases);
+        lock_cache(&self.pattern_cache).clear();
+        lock_cache(&self.compiled_patterns).clear();
+        self
+    }
+
+    /// Attach reorg telemetry to results (Builder pattern).
+    ///
+    /// Every verification then asks each provider for the block's hash,
+    /// and walks down the chain to measure the depth when they disagree
+    /// with each other or with what was seen before. See `crate::reorg`.
+    pub fn with_reorg_tracking(mut self) -> Self {
+        self.reorg_tracker = Some(Arc::new(Mutex::new(ReorgTracker::new())));
+        lock_cache(&self.pattern_cache).clear();
+        self
+    }
+
+    /// Resolve EIP-1967 proxies while parsing logs (Builder pattern).
+    ///
+    /// Each contract's implementation slot is read as of the verified block
+    /// and cached per contract and block, so upgrades are seen from the
+    /// block they happen in. Symbols from a proxy record the implementation
+    /// in `implementation`, and deployer checks fall back to it when the
+    /// proxy is not in the registry.
+    pub fn with_proxy_resolution(mut self) -> Self {
+        self.proxy_resolution = true;
+        lock_cache(&self.pattern_cache).clear();
+        self
+    }
+
     /// Detect if the current network supports EIP-4788 beacon roots.
     pub async fn detect_beacon_support(&self) -> BeaconRootSupport {
         if self.rpc_client.check_beacon_support().await {
@@ -198,6 +371,23 @@ impl BlockVerifier {
         self.rpc_client.fetch_block_header(block_number).await
     }
 
+    /// Estimate the chain's block time from the latest header and the one
+    /// `span` blocks before it.
+    pub async fn estimate_block_time(&self, span: u64) -> Result<std::time::Duration> {
+        let head = self.get_latest_block().await?;
+        let older = head.saturating_sub(span.max(1));
+        let (older, newer) = tokio::try_join!(
+            self.fetch_block_header(older),
+            self.fetch_block_header(head)
+        )?;
+        crate::block_time::block_time_between(&older, &newer).ok_or_else(|| {
+            SodsVerifierError::DataInconsistent(format!(
+                "cannot estimate block time from blocks {} and {}",
+                older.number, newer.number
+            ))
+        })
+    }
+
     /// Fetch a transaction receipt via Ethereum storage proofs (Zero-RPC).
     ///
     /// This method eliminates reliance on eth_getLogs by fetching a single receipt
@@ -216,9 +406,10 @@ impl BlockVerifier {
 
         // 3. Verify it belongs to this block
         if receipt.block_number.map(|n| n.as_u64()) != Some(block_number) {
-            return Err(SodsVerifierError::RpcError(
-                "Receipt block number mismatch".into(),
-            ));
+            return Err(SodsVerifierError::DataInconsistent(format!(
+                "receipt for {:?} is not in block {}",
+                tx_hash, block_number
+            )));
         }
 
         // 4. Cryptographic Validation
@@ -273,11 +464,12 @@ impl BlockVerifier {
 
         // Step 1: Validate symbol
         self.query_parser.validate_symbol(symbol)?;
+        let symbol = self.query_parser.resolve_symbol(symbol);
 
         let rpc_start = Instant::now();
 
         // Determine verification mode and fetch data accordingly
-        let (logs, txs, actual_mode) = match self.verification_mode {
+        let (logs, block, actual_mode) = match self.verification_mode {
             VerificationMode::Trustless => {
                 // Step 2a: Fetch block header
                 let header = self.rpc_client.fetch_block_header(block_number).await?;
@@ -296,12 +488,12 @@ impl BlockVerifier {
                 }
 
                 let logs = extract_logs_from_receipts(&receipts);
-                let txs = self
+                let block = self
                     .rpc_client
-                    .fetch_block_transactions(block_number)
+                    .fetch_block_with_transactions(block_number)
                     .await?;
 
-                (logs, txs, VerificationMode::Trustless)
+                (logs, block, VerificationMode::Trustless)
             }
             VerificationMode::ZeroRpc => {
                 // Step 2a: Fetch block header
@@ -327,19 +519,19 @@ impl BlockVerifier {
                 }
 
                 let logs = extract_logs_from_receipts(&receipts);
-                let txs = self
+                let block = self
                     .rpc_client
-                    .fetch_block_transactions(block_number)
+                    .fetch_block_with_transactions(block_number)
                     .await?;
 
-                (logs, txs, VerificationMode::ZeroRpc)
+                (logs, block, VerificationMode::ZeroRpc)
             }
             VerificationMode::RpcOnly => {
                 let logs_fut = self.rpc_client.fetch_logs_for_block(block_number);
-                let txs_fut = self.rpc_client.fetch_block_transactions(block_number);
-                let (logs, txs) = tokio::try_join!(logs_fut, txs_fut)?;
+                let block_fut = self.rpc_client.fetch_block_with_transactions(block_number);
+                let (logs, block) = tokio::try_join!(logs_fut, block_fut)?;
 
-                (logs, txs, VerificationMode::RpcOnly)
+                (logs, block, VerificationMode::RpcOnly)
             }
         };
 
@@ -347,14 +539,32 @@ impl BlockVerifier {
 
         // Build Tx Lookup Map: TxHash -> (Nonce, From)
         use std::collections::HashMap;
-        let tx_map: HashMap<_, _> = txs
+        let tx_map: HashMap<_, _> = block
+            .transactions
             .iter()
             .map(|tx| (tx.hash, (tx.nonce, tx.from)))
             .collect();
 
         // Step 3: Parse logs to symbols
         let verify_start = Instant::now();
-        let symbols = self.parse_logs_to_symbols(&logs, &tx_map);
+        let mut symbols = self
+            .parse_logs_to_symbols(block_number, &logs, &tx_map)
+            .await;
+        if actual_mode != VerificationMode::RpcOnly {
+            // Logs came from receipts checked against the header
+            for sym in &mut symbols {
+                if sym.provenance == SymbolProvenance::RpcLog {
+                    sym.provenance = SymbolProvenance::ReceiptLog;
+                }
+            }
+        }
+        // `BlobTx` and `ClWdw` leaves change the root, so only a query for
+        // the symbol adds them, as a pattern mentioning it does
+        let with_blob_tx = symbol == BLOB_TX_SYMBOL;
+        apply_blob_context(&mut symbols, &block.transactions, &logs, with_blob_tx);
+        if symbol == CL_WITHDRAWAL_SYMBOL {
+            apply_withdrawals(&mut symbols, verified_withdrawals(&block)?, &logs);
+        }
 
         // Handle empty block
         if symbols.is_empty() {
@@ -365,7 +575,8 @@ impl BlockVerifier {
                 actual_mode,
                 rpc_fetch_time,
                 total_start.elapsed(),
-            ));
+            )
+            .with_dictionary_fingerprint(self.dictionary.fingerprint()));
         }
 
         // Step 4: Build BMT
@@ -383,7 +594,8 @@ impl BlockVerifier {
                 actual_mode,
                 rpc_fetch_time,
                 total_start.elapsed(),
-            ));
+            )
+            .with_dictionary_fingerprint(self.dictionary.fingerprint()));
         }
 
         // Find first occurrence and generate proof
@@ -419,7 +631,7 @@ impl BlockVerifier {
         let verification_time = verify_start.elapsed();
         let total_time = total_start.elapsed();
 
-        Ok(VerificationResult::success(
+        let mut result = VerificationResult::success(
             symbol.to_string(),
             block_number,
             proof.size(),
@@ -430,7 +642,14 @@ impl BlockVerifier {
             verification_time,
             rpc_fetch_time,
             total_time,
-        ))
+        )
+        .with_dictionary_fingerprint(self.dictionary.fingerprint())
+        .with_provenance(first_match.provenance)
+        .with_fees(BlockFees::from_block(&block).context_for([first_match]));
+        if let Some(reorg) = self.reorg_context(block_number).await {
+            result = result.with_reorg(reorg);
+        }
+        Ok(result)
     }
 
     /// Verify a behavioral pattern in a block using performance optimizations.
@@ -448,65 +667,657 @@ impl BlockVerifier {
 
         // Check cache first
         {
-            let mut cache = self.pattern_cache.lock().unwrap();
+            let mut cache = lock_cache(&self.pattern_cache);
             if let Some(cached) = cache.get(&(block_number, pattern_str.to_string())) {
                 return Ok(cached.clone());
             }
         }
 
         // 1. Parse Pattern and map to topics
-        use sods_core::pattern::BehavioralPattern;
-        let pattern = BehavioralPattern::parse(pattern_str)?;
-        let topics = self.dictionary.pattern_to_required_topics(&pattern);
+        let compiled = self.compile_pattern(pattern_str)?;
+        let pattern = compiled.pattern();
 
+        // 2-3. Fetch filtered logs and parse symbols
         let rpc_start = Instant::now();
+        let (symbols, block) = self.fetch_pattern_symbols(pattern, block_number).await?;
+        let rpc_fetch_time = rpc_start.elapsed();
+
+        // 4-5. Build Incremental BMT and match
+        let (result, matched) = self.evaluate_pattern(
+            pattern_str,
+            &compiled,
+            symbols,
+            block_number,
+            rpc_fetch_time,
+            total_start,
+        );
+        let mut result = result?;
+        if !matched.is_empty() {
+            result = result.with_fees(BlockFees::from_block(&block).context_for(&matched));
+        }
+        if let Some(reorg) = self.reorg_context(block_number).await {
+            result = result.with_reorg(reorg);
+        }
+
+        // Cache result
+        {
+            let mut cache = lock_cache(&self.pattern_cache);
+            cache.put((block_number, pattern_str.to_string()), result.clone());
+        }
+
+        Ok(result)
+    }
+
+    /// Explain step by step why `pattern_str` does or does not match in
+    /// `block_number`.
+    ///
+    /// Fetches the same symbols `verify_pattern_in_block` matches against,
+    /// so a failing step can be traced to the block's actual events.
+    pub async fn explain_pattern_in_block(
+        &self,
+        pattern_str: &str,
+        block_number: u64,
+    ) -> Result<MatchExplanation> {
+        let compiled = self.compile_pattern(pattern_str)?;
+        let pattern = compiled.pattern();
+        let (symbols, _) = self.fetch_pattern_symbols(pattern, block_number).await?;
+        Ok(pattern.match_explain(&symbols, Some(&self.registry)))
+    }
+
+    /// Fetch the logs `pattern` needs and the block, and parse the symbols.
+    async fn fetch_pattern_symbols(
+        &self,
+        pattern: &BehavioralPattern,
+        block_number: u64,
+    ) -> Result<(Vec<BehavioralSymbol>, Block<Transaction>)> {
+        let topics = self.dictionary.pattern_to_required_topics(pattern);
+        let logs_fut = self.rpc_client.fetch_filtered_logs(block_number, topics);
+        let block_fut = self.rpc_client.fetch_block_with_transactions(block_number);
+        let (logs, block) = tokio::try_join!(logs_fut, block_fut)?;
+
+        let tx_map: HashMap<_, _> = block
+            .transactions
+            .iter()
+            .map(|tx| (tx.hash, (tx.nonce, tx.from)))
+            .collect();
+
+        let mut symbols = self
+            .parse_logs_to_symbols(block_number, &logs, &tx_map)
+            .await;
+        let with_blob_tx = pattern.mentions(BLOB_TX_SYMBOL);
+        apply_blob_context(&mut symbols, &block.transactions, &logs, with_blob_tx);
+        if pattern.mentions(CL_WITHDRAWAL_SYMBOL) {
+            apply_withdrawals(&mut symbols, verified_withdrawals(&block)?, &logs);
+        }
+        if pattern.has_gas_condition() {
+            self.fill_gas_used(&mut symbols).await?;
+        }
+        Ok((symbols, block))
+    }
+
+    /// Set `gas_used` on each symbol from its transaction's receipt, for
+    /// patterns with `gas_used` conditions.
+    ///
+    /// Receipts are fetched one transaction at a time, once per
+    /// transaction; symbols without a transaction are left unset.
+    pub async fn fill_gas_used(&self, symbols: &mut [BehavioralSymbol]) -> Result<()> {
+        let mut gas_used = HashMap::new();
+        for sym in symbols.iter() {
+            if sym.tx_hash.is_zero() || gas_used.contains_key(&sym.tx_hash) {
+                continue;
+            }
+            let receipt = self
+                .rpc_client
+                .fetch_transaction_receipt(sym.tx_hash)
+                .await?;
+            gas_used.insert(sym.tx_hash, receipt.gas_used);
+        }
+        apply_gas_used(symbols, &gas_used);
+        Ok(())
+    }
+
+    /// Verify several patterns in one block with a single fetch.
+    ///
+    /// Logs are fetched once, filtered to the union of the patterns' event
+    /// topics, and parsed into one shared symbol set. Each pattern is then
+    /// matched against the symbols of its own topics, so its result is the
+    /// one `verify_pattern_in_block` would return; patterns are evaluated
+    /// on scoped worker threads. Outcomes are in input order. A pattern that
+    /// fails to parse or prove only fails its own entry, while an RPC error
+    /// fails the whole call. Results are written to the pattern cache but
+    /// not read from it, since cached results lack the matched symbols.
+    pub async fn verify_patterns_in_block(
+        &self,
+        patterns: &[&str],
+        block_number: u64,
+    ) -> Result<Vec<PatternOutcome>> {
+        let prefetched = self.prefetch_block(patterns, block_number).await?;
+        self.verify_patterns_in_prefetched(patterns, prefetched)
+            .await
+    }
+
+    /// Fetch the logs and transactions `verify_patterns_in_block` needs
+    /// for `patterns`, without matching them yet.
+    ///
+    /// Lets a scan fetch later blocks while it matches earlier ones (see
+    /// `BlockPrefetcher`). Nothing is fetched if no pattern parses.
+    pub async fn prefetch_block(
+        &self,
+        patterns: &[&str],
+        block_number: u64,
+    ) -> Result<PrefetchedBlock> {
+        self.prefetch_block_with(patterns, block_number, false)
+            .await
+    }
+
+    /// Like `prefetch_block`, but fetches every log of the block, even if
+    /// no pattern parses, so `prefetched_symbols` can build all of its
+    /// symbols from the same fetch.
+    pub async fn prefetch_all_logs(
+        &self,
+        patterns: &[&str],
+        block_number: u64,
+    ) -> Result<PrefetchedBlock> {
+        self.prefetch_block_with(patterns, block_number, true).await
+    }
+
+    async fn prefetch_block_with(
+        &self,
+        patterns: &[&str],
+        block_number: u64,
+        all_logs: bool,
+    ) -> Result<PrefetchedBlock> {
+        let jobs = self.pattern_jobs(patterns);
+        let needs: Vec<&Vec<H256>> = jobs.iter().flatten().map(|(_, topics)| topics).collect();
+        if needs.is_empty() && !all_logs {
+            return Ok(PrefetchedBlock {
+                number: block_number,
+                topics: Vec::new(),
+                data: None,
+                fetch_time: std::time::Duration::ZERO,
+            });
+        }
+        let topics = if all_logs {
+            Vec::new()
+        } else {
+            topic_union(&needs)
+        };
+        let (logs, block, fetch_time) = self.fetch_block_data(block_number, topics.clone()).await?;
+        Ok(PrefetchedBlock {
+            number: block_number,
+            topics,
+            data: Some((logs, block)),
+            fetch_time,
+        })
+    }
+
+    /// All behavioral symbols of a block fetched by `prefetch_all_logs`,
+    /// as `fetch_block_symbols` returns them; `None` if the block was
+    /// fetched for fewer logs.
+    pub async fn prefetched_symbols(
+        &self,
+        prefetched: &PrefetchedBlock,
+    ) -> Option<Result<Vec<BehavioralSymbol>>> {
+        match &prefetched.data {
+            Some((logs, block)) if prefetched.topics.is_empty() => Some(
+                self.block_symbols(prefetched.number, logs, block, false, false)
+                    .await,
+            ),
+            _ => None,
+        }
+    }
+
+    /// Verify several patterns in a block fetched by `prefetch_block`.
+    ///
+    /// Same results as `verify_patterns_in_block`. If the block was
+    /// fetched for patterns needing fewer logs, it is fetched again.
+    pub async fn verify_patterns_in_prefetched(
+        &self,
+        patterns: &[&str],
+        prefetched: PrefetchedBlock,
+    ) -> Result<Vec<PatternOutcome>> {
+        let block_number = prefetched.number;
+        // Timings include the fetch, however long ago it happened
+        let total_start = Instant::now()
+            .checked_sub(prefetched.fetch_time)
+            .unwrap_or_else(Instant::now);
+
+        // Parse failures are settled up front; the rest become jobs
+        let mut outcomes: Vec<Option<PatternOutcome>> = Vec::with_capacity(patterns.len());
+        let mut jobs = Vec::new();
+        for (index, (pattern_str, job)) in
+            patterns.iter().zip(self.pattern_jobs(patterns)).enumerate()
+        {
+            match job {
+                Ok((compiled, topics)) => {
+                    jobs.push((index, compiled, topics));
+                    outcomes.push(None);
+                }
+                Err(e) => outcomes.push(Some(PatternOutcome {
+                    pattern: pattern_str.to_string(),
+                    result: Err(e),
+                    matched: Vec::new(),
+                    symbols: Vec::new(),
+                })),
+            }
+        }
+
+        if !jobs.is_empty() {
+            let union = topic_union(&jobs.iter().map(|(_, _, t)| t).collect::<Vec<_>>());
+            // Logs fetched for a superset of the topics serve as well
+            let covered = prefetched.topics.is_empty()
+                || (!union.is_empty()
+                    && union
+                        .iter()
+                        .all(|t| prefetched.topics.binary_search(t).is_ok()));
+            let (logs, block, rpc_fetch_time) = match prefetched.data {
+                Some((logs, block)) if covered => (logs, block, prefetched.fetch_time),
+                _ => self.fetch_block_data(block_number, union).await?,
+            };
+            let fees = BlockFees::from_block(&block);
+            let reorg = self.reorg_context(block_number).await;
+
+            let tx_map: HashMap<_, _> = block
+                .transactions
+                .iter()
+                .map(|tx| (tx.hash, (tx.nonce, tx.from)))
+                .collect();
+            let mut tagged: Vec<(H256, BehavioralSymbol)> = logs
+                .iter()
+                .filter_map(|log| Some((*log.topics.first()?, log)))
+                .flat_map(|(topic, log)| {
+                    self.parse_log_to_symbols(log, &tx_map)
+                        .into_iter()
+                        .map(move |sym| (topic, sym))
+                })
+                .collect();
+            self.resolve_proxies(block_number, tagged.iter_mut().map(|(_, sym)| sym))
+                .await;
+            let withdrawals = if jobs
+                .iter()
+                .any(|(_, c, _)| c.pattern().mentions(CL_WITHDRAWAL_SYMBOL))
+            {
+                verified_withdrawals(&block)?
+            } else {
+                &[]
+            };
+            // Gas is not part of the leaf hash, so setting it for every
+            // pattern changes no other pattern's result
+            if jobs.iter().any(|(_, c, _)| c.pattern().has_gas_condition()) {
+                let mut symbols: Vec<BehavioralSymbol> =
+                    tagged.iter().map(|(_, sym)| sym.clone()).collect();
+                self.fill_gas_used(&mut symbols).await?;
+                for ((_, tagged), sym) in tagged.iter_mut().zip(symbols) {
+                    tagged.gas_used = sym.gas_used;
+                }
+            }
+
+            let evaluate =
+                |(index, compiled, topics): &(usize, Arc<CompiledPattern>, Vec<H256>)| {
+                    let pattern = compiled.pattern();
+                    let pattern_str = patterns[*index];
+                    let mut symbols = tagged
+                        .iter()
+                        .filter(|(topic, _)| topics.is_empty() || topics.contains(topic))
+                        .map(|(_, sym)| sym.clone())
+                        .collect();
+                    // `BlobTx` and `ClWdw` are placed by the logs this pattern
+                    // alone would fetch
+                    let with_blob_tx = pattern.mentions(BLOB_TX_SYMBOL);
+                    let with_withdrawals = pattern.mentions(CL_WITHDRAWAL_SYMBOL);
+                    let pattern_logs: Vec<Log> = if with_blob_tx || with_withdrawals {
+                        logs.iter()
+                            .filter(|log| {
+                                topics.is_empty()
+                                    || log.topics.first().is_some_and(|t| topics.contains(t))
+                            })
+                            .cloned()
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    apply_blob_context(
+                        &mut symbols,
+                        &block.transactions,
+                        &pattern_logs,
+                        with_blob_tx,
+                    );
+                    if with_withdrawals {
+                        apply_withdrawals(&mut symbols, withdrawals, &pattern_logs);
+                    }
+                    let mut streamed = Vec::new();
+                    if pattern.window().is_some() {
+                        streamed = symbols.clone();
+                        apply_block_position(&mut streamed, block_number, &block);
+                    }
+                    let (result, matched) = self.evaluate_pattern(
+                        pattern_str,
+                        compiled,
+                        symbols,
+                        block_number,
+                        rpc_fetch_time,
+                        total_start,
+                    );
+                    let result = match result {
+                        Ok(result) if !matched.is_empty() => {
+                            Ok(result.with_fees(fees.context_for(&matched)))
+                        }
+                        other => other,
+                    };
+                    let result = match (result, &reorg) {
+                        (Ok(result), Some(reorg)) => Ok(result.with_reorg(reorg.clone())),
+                        (other, _) => other,
+                    };
+                    let outcome = PatternOutcome {
+                        pattern: pattern_str.to_string(),
+                        result,
+                        matched,
+                        symbols: streamed,
+                    };
+                    (*index, outcome)
+                };
+
+            let workers = std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+                .min(jobs.len());
+            let evaluated: Vec<(usize, PatternOutcome)> = if workers <= 1 {
+                jobs.iter().map(evaluate).collect()
+            } else {
+                let chunk_size = (jobs.len() + workers - 1) / workers;
+                std::thread::scope(|scope| {
+                    let handles: Vec<_> = jobs
+                        .chunks(chunk_size)
+                        .map(|chunk| {
+                            let evaluate = &evaluate;
+                            scope.spawn(move || chunk.iter().map(evaluate).collect::<Vec<_>>())
+                        })
+                        .collect();
+                    handles
+                        .into_iter()
+                        .flat_map(|handle| {
+                            handle
+                                .join()
+                                .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                        })
+                        .collect()
+                })
+            };
+
+            let mut cache = lock_cache(&self.pattern_cache);
+            for (index, outcome) in evaluated {
+                if let Ok(result) = &outcome.result {
+                    cache.put((block_number, outcome.pattern.clone()), result.clone());
+                }
+                outcomes[index] = Some(outcome);
+            }
+        }
+
+        Ok(outcomes.into_iter().flatten().collect())
+    }
+
+    /// Parse a pattern, resolving the verifier's symbol aliases.
+    pub fn parse_pattern(&self, pattern_str: &str) -> Result<BehavioralPattern> {
+        Ok(BehavioralPattern::parse_with_aliases(
+            pattern_str,
+            self.query_parser.aliases(),
+        )?)
+    }
 
-        // 2. Fetch Filtered Logs
+    /// Forget compiled patterns and cached pattern results, e.g. after the
+    /// installed presets change what a pattern string means.
+    pub fn clear_pattern_cache(&self) {
+        lock_cache(&self.compiled_patterns).clear();
+        lock_cache(&self.pattern_cache).clear();
+    }
+
+    /// Compile each pattern, with the event topics its symbols need.
+    fn pattern_jobs(&self, patterns: &[&str]) -> Vec<Result<(Arc<CompiledPattern>, Vec<H256>)>> {
+        patterns
+            .iter()
+            .map(|pattern_str| {
+                let compiled = self.compile_pattern(pattern_str)?;
+                let topics = self
+                    .dictionary
+                    .pattern_to_required_topics(compiled.pattern());
+                Ok((compiled, topics))
+            })
+            .collect()
+    }
+
+    /// Fetch a block's logs for `topics` and its transactions together.
+    async fn fetch_block_data(
+        &self,
+        block_number: u64,
+        topics: Vec<H256>,
+    ) -> Result<(Vec<Log>, Block<Transaction>, std::time::Duration)> {
+        let rpc_start = Instant::now();
         let logs_fut = self.rpc_client.fetch_filtered_logs(block_number, topics);
-        let txs_fut = self.rpc_client.fetch_block_transactions(block_number);
+        let block_fut = self.rpc_client.fetch_block_with_transactions(block_number);
+        let (logs, block) = tokio::try_join!(logs_fut, block_fut)?;
+        Ok((logs, block, rpc_start.elapsed()))
+    }
+
+    /// Parse and compile a pattern, reusing the result for later blocks.
+    fn compile_pattern(&self, pattern_str: &str) -> Result<Arc<CompiledPattern>> {
+        if let Some(compiled) = lock_cache(&self.compiled_patterns).get(pattern_str) {
+            return Ok(compiled.clone());
+        }
+        let compiled = Arc::new(self.parse_pattern(pattern_str)?.compile());
+        lock_cache(&self.compiled_patterns).put(pattern_str.to_string(), compiled.clone());
+        Ok(compiled)
+    }
+
+    /// Verify a pattern against receipts checked against the block header.
+    ///
+    /// Same matching as `verify_pattern_in_block`, but the logs come from
+    /// the block's receipts, whose trie root must equal the header's
+    /// `receipts_root`. Costs a header, receipts and block fetch. The
+    /// result is not cached, since it must not be confused with an
+    /// RPC-only one.
+    pub async fn verify_pattern_trustless(
+        &self,
+        pattern_str: &str,
+        block_number: u64,
+    ) -> Result<VerificationResult> {
+        use crate::header_anchor::{
+            extract_logs_from_receipts, verify_receipts_against_header, VerificationMode,
+        };
 
-        let (logs, txs) = tokio::try_join!(logs_fut, txs_fut)?;
+        let total_start = Instant::now();
+        let compiled = self.compile_pattern(pattern_str)?;
+        let pattern = compiled.pattern();
+        let topics = self.dictionary.pattern_to_required_topics(pattern);
+
+        let rpc_start = Instant::now();
+        let (header, receipts, block) = tokio::try_join!(
+            self.rpc_client.fetch_block_header(block_number),
+            self.rpc_client.fetch_block_receipts(block_number),
+            self.rpc_client.fetch_block_with_transactions(block_number),
+        )?;
         let rpc_fetch_time = rpc_start.elapsed();
 
-        // 3. Build Tx Lookup Map and parse symbols
-        let tx_map: HashMap<_, _> = txs
+        let validation = verify_receipts_against_header(&receipts, &header);
+        if !validation.is_valid {
+            return Err(SodsVerifierError::InvalidReceiptProof {
+                computed: format!("0x{}", hex::encode(validation.computed_root)),
+                expected: format!("0x{}", hex::encode(validation.expected_root)),
+            });
+        }
+
+        let tx_map: HashMap<_, _> = block
+            .transactions
             .iter()
             .map(|tx| (tx.hash, (tx.nonce, tx.from)))
             .collect();
+        // Same topic filter as the RPC path, so both see the same symbols
+        let logs: Vec<Log> = extract_logs_from_receipts(&receipts)
+            .into_iter()
+            .filter(|log| {
+                topics.is_empty() || log.topics.first().is_some_and(|t| topics.contains(t))
+            })
+            .collect();
+        let mut symbols: Vec<BehavioralSymbol> = logs
+            .iter()
+            .flat_map(|log| self.parse_log_to_symbols(log, &tx_map))
+            .map(|mut sym| {
+                if sym.provenance == SymbolProvenance::RpcLog {
+                    sym.provenance = SymbolProvenance::ReceiptLog;
+                }
+                sym
+            })
+            .collect();
+        self.resolve_proxies(block_number, &mut symbols).await;
+        let with_blob_tx = pattern.mentions(BLOB_TX_SYMBOL);
+        apply_blob_context(&mut symbols, &block.transactions, &logs, with_blob_tx);
+        if pattern.mentions(CL_WITHDRAWAL_SYMBOL) {
+            apply_withdrawals(&mut symbols, verified_withdrawals(&block)?, &logs);
+        }
+        let gas_used: HashMap<H256, Option<ethers_core::types::U256>> = receipts
+            .iter()
+            .map(|r| (r.transaction_hash, r.gas_used))
+            .collect();
+        apply_gas_used(&mut symbols, &gas_used);
+
+        let (result, matched) = self.evaluate_pattern(
+            pattern_str,
+            &compiled,
+            symbols,
+            block_number,
+            rpc_fetch_time,
+            total_start,
+        );
+        let mut result = result?;
+        result.verification_mode = VerificationMode::Trustless;
+        if !matched.is_empty() {
+            result = result.with_fees(BlockFees::from_block(&block).context_for(&matched));
+        }
+        if let Some(reorg) = self.reorg_context(block_number).await {
+            result = result.with_reorg(reorg);
+        }
+        Ok(result)
+    }
+
+    /// Check a pattern against the block's logs bloom alone.
+    ///
+    /// Costs one header fetch. `false` means the pattern cannot occur in
+    /// the block; `true` only means it may, as blooms have false positives.
+    pub async fn bloom_check_pattern(&self, pattern_str: &str, block_number: u64) -> Result<bool> {
+        let pattern = self.parse_pattern(pattern_str)?;
+        let header = self.rpc_client.fetch_block_header(block_number).await?;
+
+        // Every required step needs one of its symbol's topics in the bloom
+        Ok(pattern
+            .steps()
+            .iter()
+            .all(|step| self.step_may_occur(step, &header.logs_bloom)))
+    }
+
+    /// Whether the bloom admits `step`; an alternation needs any alternative
+    /// and a group or same-transaction chain all of its steps. A bloom can only show a symbol may be
+    /// present, so absences always pass.
+    fn step_may_occur(&self, step: &sods_core::pattern::PatternStep, bloom: &Bloom) -> bool {
+        use sods_core::pattern::PatternStep;
+
+        let symbol = match step {
+            PatternStep::Exact(s, _) => s,
+            PatternStep::AtLeast(_, 0, _)
+            | PatternStep::Range(_, 0, _, _)
+            | PatternStep::Group(_, 0, _)
+            | PatternStep::Absent(..)
+            | PatternStep::Any(_) => return true,
+            PatternStep::AtLeast(s, _, _) | PatternStep::Range(s, _, _, _) => s,
+            PatternStep::AnyOf(alternatives) => {
+                return alternatives
+                    .iter()
+                    .any(|alt| self.step_may_occur(alt, bloom))
+            }
+            PatternStep::Group(steps, _, _) | PatternStep::SameTx(steps) => {
+                return steps.iter().all(|step| self.step_may_occur(step, bloom))
+            }
+            PatternStep::MaxGap(step, _) => return self.step_may_occur(step, bloom),
+        };
+        let topics = self.dictionary.topics_for_symbol(symbol);
+        // Symbols without a known topic cannot be ruled out
+        topics.is_empty() || crate::header_anchor::bloom_contains_any_topic(bloom, &topics)
+    }
+
+    /// Reorg telemetry for `block_number`, updating the tracker.
+    ///
+    /// None if tracking is off or no provider returned the block; telemetry
+    /// never fails a verification.
+    async fn reorg_context(&self, block_number: u64) -> Option<ReorgContext> {
+        let tracker = self.reorg_tracker.as_ref()?;
+        let views = self.rpc_client.fetch_block_views(block_number).await;
+        let (hash, parent_hash) = views.iter().flatten().next().copied()?;
+        let providers_queried = views.iter().flatten().count();
+
+        let (siblings, parent_replaced) = {
+            let tracker = lock_cache(tracker);
+            (
+                tracker.siblings(block_number, &views),
+                tracker.parent_replaced(block_number, parent_hash),
+            )
+        };
+        lock_cache(tracker).record(block_number, hash);
+
+        // Highest replaced height, if any, then walk down until views agree
+        let fork_top = if !siblings.is_empty() {
+            Some(block_number)
+        } else if parent_replaced {
+            Some(block_number - 1)
+        } else {
+            None
+        };
+        if let Some(top) = fork_top {
+            let mut depth = u64::from(top == block_number);
+            let mut height = top + 1 - depth;
+            while depth < MAX_REORG_DEPTH && height > 0 {
+                height -= 1;
+                let views = self.rpc_client.fetch_block_views(height).await;
+                let mut tracker = lock_cache(tracker);
+                let diverged = !tracker.siblings(height, &views).is_empty();
+                if let Some((hash, _)) = views.iter().flatten().next() {
+                    tracker.record(height, *hash);
+                }
+                if !diverged {
+                    break;
+                }
+                depth += 1;
+            }
+            if depth > 0 {
+                lock_cache(tracker).record_reorg(ReorgEvent { height: top, depth });
+            }
+        }
 
+        Some(lock_cache(tracker).context(block_number, siblings, providers_queried))
+    }
+
+    /// Builds the BMT over `symbols` and matches `pattern` against them.
+    ///
+    /// Returns the result and the symbols of the first match, if any.
+    fn evaluate_pattern(
+        &self,
+        pattern_str: &str,
+        pattern: &CompiledPattern,
+        symbols: Vec<BehavioralSymbol>,
+        block_number: u64,
+        rpc_fetch_time: std::time::Duration,
+        total_start: Instant,
+    ) -> (Result<VerificationResult>, Vec<BehavioralSymbol>) {
         let verify_start = Instant::now();
-        let symbols = self.parse_logs_to_symbols(&logs, &tx_map);
 
-        // 4. Build Incremental BMT over filtered symbols
+        // Build Incremental BMT over filtered symbols
         let bmt = BehavioralMerkleTree::from_filtered(symbols.clone());
         let root = bmt.root();
 
-        // 5. Match Pattern
-        let matched = pattern.matches(&symbols, Some(&self.registry));
-        let result = if let Some(matched_seq) = matched {
-            // Find first symbol of match to generate proof
-            let first_sym = matched_seq[0];
-            let proof = bmt
-                .generate_proof(first_sym.symbol(), first_sym.log_index())
-                .ok_or_else(|| SodsVerifierError::SymbolNotFound {
-                    symbol: first_sym.symbol().to_string(),
-                    block_number,
-                })?;
-
-            VerificationResult::success(
-                pattern_str.to_string(),
-                block_number,
-                proof.size(),
-                root,
-                matched_seq.len(),
-                1.0, // Multi-symbol pattern matches are high confidence
-                crate::header_anchor::VerificationMode::RpcOnly, // Filtered mode is currently RPC-only
-                verify_start.elapsed(),
-                rpc_fetch_time,
-                total_start.elapsed(),
-            )
-        } else {
-            VerificationResult::not_found(
+        // Match Pattern
+        let matched = match pattern.try_matches(&symbols, Some(&self.registry)) {
+            Ok(matched) => matched,
+            Err(e) => return (Err(e.into()), Vec::new()),
+        };
+        let Some(matched_seq) = matched else {
+            let result = VerificationResult::not_found(
                 pattern_str.to_string(),
                 block_number,
                 Some(root),
@@ -514,69 +1325,190 @@ impl BlockVerifier {
                 rpc_fetch_time,
                 total_start.elapsed(),
             )
+            .with_dictionary_fingerprint(self.dictionary.fingerprint());
+            return (Ok(result), Vec::new());
         };
+        let matched: Vec<BehavioralSymbol> = matched_seq.into_iter().cloned().collect();
 
-        // Cache result
-        {
-            let mut cache = self.pattern_cache.lock().unwrap();
-            cache.put((block_number, pattern_str.to_string()), result.clone());
-        }
+        // Find first symbol of match to generate proof
+        let first_sym = &matched[0];
+        let Some(proof) = bmt.generate_proof(first_sym.symbol(), first_sym.log_index()) else {
+            let error = SodsVerifierError::SymbolNotFound {
+                symbol: first_sym.symbol().to_string(),
+                block_number,
+            };
+            return (Err(error), matched);
+        };
 
-        Ok(result)
+        let result = VerificationResult::success(
+            pattern_str.to_string(),
+            block_number,
+            proof.size(),
+            root,
+            matched.len(),
+            1.0, // Multi-symbol pattern matches are high confidence
+            crate::header_anchor::VerificationMode::RpcOnly, // Filtered mode is currently RPC-only
+            verify_start.elapsed(),
+            rpc_fetch_time,
+            total_start.elapsed(),
+        )
+        .with_dictionary_fingerprint(self.dictionary.fingerprint());
+        let result = match SymbolProvenance::weakest(&matched) {
+            Some(provenance) => result.with_provenance(provenance),
+            None => result,
+        };
+        (Ok(result), matched)
     }
 
-    /// Parse RPC logs into behavioral symbols.
-    fn parse_logs_to_symbols(
+    /// Parse RPC logs into behavioral symbols, resolving proxies if enabled.
+    async fn parse_logs_to_symbols(
         &self,
+        block_number: u64,
         logs: &[ethers_core::types::Log],
         tx_map: &std::collections::HashMap<
             ethers_core::types::H256,
             (ethers_core::types::U256, ethers_core::types::Address),
         >,
     ) -> Vec<BehavioralSymbol> {
-        logs.iter()
-            .filter_map(|log| {
-                let mut sym = self.dictionary.parse_log(log)?;
-
-                // Enrich with causal data if tx exists
-                if let Some(tx_hash) = log.transaction_hash {
-                    if let Some((nonce, from)) = tx_map.get(&tx_hash) {
-                        // Use log_index as call_sequence for intra-tx ordering
-                        sym = sym.with_causality(
-                            tx_hash,
-                            nonce.as_u64(),
-                            log.log_index.map(|i| i.as_u32()).unwrap_or(0),
-                        );
-                        // If the symbol context 'from' is 0x0 (not extracted from log topics),
-                        // we can fallback to tx.origin (though semantically different, helpful for causality grouping)
-                        // But for now, let's keep 'from' as event-specific.
-                        // Actually, Causal Tree sorts by sym.from. If sym.from is 0x0, it breaks grouping.
-                        // So we should probably set sym.from to tx.from if it's empty?
-                        // The user said: "Group symbols by transaction origin (from address)"
-                        // If the event doesn't explicitly have a 'from' (like Swap), we should attr it to the tx sender.
-                        if sym.from == ethers_core::types::Address::zero() {
-                            sym.from = *from;
-                        }
+        let mut symbols: Vec<BehavioralSymbol> = logs
+            .iter()
+            .flat_map(|log| self.parse_log_to_symbols(log, tx_map))
+            .collect();
+        self.resolve_proxies(block_number, &mut symbols).await;
+        symbols
+    }
 
-                        // Enrich with deployer flag from registry
-                        if let Some(deployer) = self.registry.get_deployer(&sym.contract_address) {
-                            sym.is_from_deployer = sym.from == deployer;
-                        }
-                    }
-                }
-                Some(sym)
-            })
+    /// Record the EIP-1967 implementation behind each symbol's contract as
+    /// of `block_number`, if proxy resolution is on, and check the deployer
+    /// against it when the registry does not know the proxy. Each contract
+    /// is looked up once, `PROXY_LOOKUP_CONCURRENCY` at a time.
+    async fn resolve_proxies<'a>(
+        &self,
+        block_number: u64,
+        symbols: impl IntoIterator<Item = &'a mut BehavioralSymbol>,
+    ) {
+        if !self.proxy_resolution {
+            return;
+        }
+        let symbols: Vec<&mut BehavioralSymbol> = symbols
+            .into_iter()
+            .filter(|sym| !sym.contract_address.is_zero())
+            .collect();
+        let mut contracts: Vec<Address> = symbols.iter().map(|sym| sym.contract_address).collect();
+        contracts.sort_unstable();
+        contracts.dedup();
+        let mut implementations = HashMap::new();
+        for batch in contracts.chunks(PROXY_LOOKUP_CONCURRENCY) {
+            let found = futures::future::join_all(
+                batch
+                    .iter()
+                    .map(|&contract| self.proxy_implementation(contract, block_number)),
+            )
+            .await;
+            implementations.extend(batch.iter().zip(found).filter_map(|(c, i)| Some((*c, i?))));
+        }
+
+        for sym in symbols {
+            let Some(&implementation) = implementations.get(&sym.contract_address) else {
+                continue;
+            };
+            sym.implementation = Some(implementation);
+            if sym.tx_hash.is_zero() || self.registry.get_deployer(&sym.contract_address).is_some()
+            {
+                continue;
+            }
+            if let Some(deployer) = self.registry.get_deployer(&implementation) {
+                sym.is_from_deployer = sym.from == deployer;
+            }
+        }
+    }
+
+    /// Implementation behind `contract` at `block_number` if it is an
+    /// EIP-1967 proxy there.
+    ///
+    /// Uses cache to avoid repeated RPC calls. Returns `None` if lookup
+    /// fails; failures are not cached.
+    pub async fn proxy_implementation(
+        &self,
+        contract: Address,
+        block_number: u64,
+    ) -> Option<Address> {
+        if let Some(cached) = lock_cache(&self.proxy_cache).get(&(contract, block_number)) {
+            return *cached;
+        }
+        let implementation = self
+            .rpc_client
+            .fetch_proxy_implementation(contract, block_number)
+            .await
+            .ok()?;
+        lock_cache(&self.proxy_cache).put((contract, block_number), implementation);
+        implementation
+    }
+
+    /// Parse one log, enriched with its transaction's causal data. An
+    /// ERC-1155 batch transfer yields one symbol per token id.
+    fn parse_log_to_symbols(
+        &self,
+        log: &ethers_core::types::Log,
+        tx_map: &HashMap<H256, (ethers_core::types::U256, Address)>,
+    ) -> Vec<BehavioralSymbol> {
+        self.dictionary
+            .parse_log_all(log)
+            .into_iter()
+            .map(|sym| self.enrich_symbol(sym, log, tx_map))
             .collect()
     }
 
+    /// Add the causal data of `log`'s transaction to `sym`.
+    fn enrich_symbol(
+        &self,
+        mut sym: BehavioralSymbol,
+        log: &ethers_core::types::Log,
+        tx_map: &HashMap<H256, (ethers_core::types::U256, Address)>,
+    ) -> BehavioralSymbol {
+        // Enrich with causal data if tx exists
+        if let Some(tx_hash) = log.transaction_hash {
+            if let Some((nonce, from)) = tx_map.get(&tx_hash) {
+                // Use log_index as call_sequence for intra-tx ordering
+                sym = sym.with_causality(
+                    tx_hash,
+                    nonce.as_u64(),
+                    log.log_index.map(|i| i.as_u32()).unwrap_or(0),
+                );
+                // If the symbol context 'from' is 0x0 (not extracted from log topics),
+                // we can fallback to tx.origin (though semantically different, helpful for causality grouping)
+                // But for now, let's keep 'from' as event-specific.
+                // Actually, Causal Tree sorts by sym.from. If sym.from is 0x0, it breaks grouping.
+                // So we should probably set sym.from to tx.from if it's empty?
+                // The user said: "Group symbols by transaction origin (from address)"
+                // If the event doesn't explicitly have a 'from' (like Swap), we should attr it to the tx sender.
+                if sym.from == ethers_core::types::Address::zero() {
+                    sym.from = *from;
+                }
+
+                // Enrich with deployer flag from registry
+                if let Some(deployer) = self.registry.get_deployer(&sym.contract_address) {
+                    sym.is_from_deployer = sym.from == deployer;
+                }
+            }
+        }
+        sym
+    }
+
     /// Get the symbol dictionary used for parsing.
     pub fn dictionary(&self) -> &SymbolDictionary {
         &self.dictionary
     }
 
     /// Register a dynamic symbol plugin.
-    pub fn register_plugin(&mut self, plugin: sods_core::plugins::SymbolPlugin) {
-        self.dictionary.register_plugin(plugin);
+    ///
+    /// Fails if the plugin's topic is already mapped to another symbol and
+    /// the plugin is not an override. Cached pattern results were computed
+    /// under the previous vocabulary and are dropped.
+    pub fn register_plugin(&mut self, plugin: sods_core::plugins::SymbolPlugin) -> Result<()> {
+        self.dictionary.register_plugin(plugin)?;
+        lock_cache(&self.pattern_cache).clear();
+        Ok(())
     }
 
     /// Get the current RPC adaptive delay in milliseconds.
@@ -591,19 +1523,170 @@ impl BlockVerifier {
 
     /// Fetch all behavioral symbols for a block.
     ///
-    /// Useful for pattern matching or manual inspection.
+    /// Useful for manual inspection. The symbols are the leaves
+    /// `verify_symbol_in_block` commits to for a log-derived symbol, so
+    /// they carry blob context but no `BlobTx` or `ClWdw` leaves; pattern
+    /// matchers should use `fetch_block_symbols_for`. Blocks with many logs can be
+    /// read in bounded pages with `fetch_block_symbols_page`.
     pub async fn fetch_block_symbols(&self, block_number: u64) -> Result<Vec<BehavioralSymbol>> {
+        self.fetch_block_symbols_with(block_number, false, false)
+            .await
+    }
+
+    /// Fetch the behavioral symbols `pattern` is matched against: those of
+    /// `fetch_block_symbols`, plus `BlobTx` and `ClWdw` leaves if the
+    /// pattern mentions them, the same leaf set `verify_pattern` commits to.
+    pub async fn fetch_block_symbols_for(
+        &self,
+        block_number: u64,
+        pattern: &BehavioralPattern,
+    ) -> Result<Vec<BehavioralSymbol>> {
+        self.fetch_block_symbols_with(
+            block_number,
+            pattern.mentions(BLOB_TX_SYMBOL),
+            pattern.mentions(CL_WITHDRAWAL_SYMBOL),
+        )
+        .await
+    }
+
+    async fn fetch_block_symbols_with(
+        &self,
+        block_number: u64,
+        with_blob_tx: bool,
+        with_withdrawals: bool,
+    ) -> Result<Vec<BehavioralSymbol>> {
         let logs_fut = self.rpc_client.fetch_logs_for_block(block_number);
-        let txs_fut = self.rpc_client.fetch_block_transactions(block_number);
+        let block_fut = self.rpc_client.fetch_block_with_transactions(block_number);
 
-        let (logs, txs) = tokio::try_join!(logs_fut, txs_fut)?;
+        let (logs, block) = tokio::try_join!(logs_fut, block_fut)?;
+        self.block_symbols(block_number, &logs, &block, with_blob_tx, with_withdrawals)
+            .await
+    }
 
-        let tx_map: std::collections::HashMap<_, _> = txs
+    /// Build a block's behavioral symbols from all of its logs.
+    async fn block_symbols(
+        &self,
+        block_number: u64,
+        logs: &[Log],
+        block: &Block<Transaction>,
+        with_blob_tx: bool,
+        with_withdrawals: bool,
+    ) -> Result<Vec<BehavioralSymbol>> {
+        let tx_map: std::collections::HashMap<_, _> = block
+            .transactions
             .iter()
             .map(|tx| (tx.hash, (tx.nonce, tx.from)))
             .collect();
 
-        Ok(self.parse_logs_to_symbols(&logs, &tx_map))
+        let mut symbols = self
+            .parse_logs_to_symbols(block_number, logs, &tx_map)
+            .await;
+        apply_blob_context(&mut symbols, &block.transactions, logs, with_blob_tx);
+        if with_withdrawals {
+            apply_withdrawals(&mut symbols, verified_withdrawals(block)?, logs);
+        }
+        apply_block_position(&mut symbols, block_number, block);
+        Ok(symbols)
+    }
+
+    /// Fetch one page of a block's behavioral symbols.
+    ///
+    /// Starts at `cursor` (or the first log when `None`) and returns at most
+    /// `limit` symbols, capped at `MAX_SYMBOLS_PER_PAGE`. Only the page's
+    /// symbols are built. The first page fetches the block's logs and
+    /// transactions, and the pages after it reuse them while the block is
+    /// among the last few paged.
+    /// Pages hold log-derived symbols only: no `BlobTx`, `ClWdw` or blob
+    /// context.
+    /// Fails with `DataInconsistent` if the cursor belongs to another block
+    /// or the block's log count changed since it was issued.
+    pub async fn fetch_block_symbols_page(
+        &self,
+        block_number: u64,
+        cursor: Option<SymbolCursor>,
+        limit: usize,
+    ) -> Result<SymbolPage> {
+        // A listing starts afresh; its later pages read what it fetched
+        let cached =
+            cursor.and_then(|_| lock_cache(&self.page_sources).get(&block_number).cloned());
+        let source = match cached {
+            Some(source) => source,
+            None => {
+                let logs_fut = self.rpc_client.fetch_logs_for_block(block_number);
+                let txs_fut = self.rpc_client.fetch_block_transactions(block_number);
+                let (logs, txs) = tokio::try_join!(logs_fut, txs_fut)?;
+                let tx_map = txs
+                    .iter()
+                    .map(|tx| (tx.hash, (tx.nonce, tx.from)))
+                    .collect();
+                let source = Arc::new(PageSource { logs, tx_map });
+                lock_cache(&self.page_sources).put(block_number, source.clone());
+                source
+            }
+        };
+
+        let mut page =
+            self.symbol_page(block_number, &source.logs, &source.tx_map, cursor, limit)?;
+        self.resolve_proxies(block_number, &mut page.symbols).await;
+        Ok(page)
+    }
+
+    /// Builds the page of `logs` starting at `cursor`.
+    fn symbol_page(
+        &self,
+        block_number: u64,
+        logs: &[Log],
+        tx_map: &HashMap<H256, (ethers_core::types::U256, Address)>,
+        cursor: Option<SymbolCursor>,
+        limit: usize,
+    ) -> Result<SymbolPage> {
+        let total_logs = logs.len();
+        let start = match cursor {
+            None => 0,
+            Some(c) if c.block_number != block_number => {
+                return Err(SodsVerifierError::DataInconsistent(format!(
+                    "cursor is for block {}, not {}",
+                    c.block_number, block_number
+                )));
+            }
+            Some(c) if c.total_logs != total_logs || c.log_offset > total_logs => {
+                return Err(SodsVerifierError::DataInconsistent(format!(
+                    "block {} now has {} logs, cursor expected {}",
+                    block_number, total_logs, c.total_logs
+                )));
+            }
+            Some(c) => c.log_offset,
+        };
+
+        let limit = limit.clamp(1, MAX_SYMBOLS_PER_PAGE);
+        let mut symbols = Vec::new();
+        let mut offset = start;
+        // Pages end on a log boundary, so a batch transfer may run past `limit`
+        while offset < total_logs && symbols.len() < limit {
+            symbols.extend(self.parse_log_to_symbols(&logs[offset], tx_map));
+            offset += 1;
+        }
+
+        // Trailing logs may yield no symbols; only report more if some do
+        let truncated = logs[offset..].iter().any(|log| {
+            log.topics
+                .first()
+                .is_some_and(|topic| self.dictionary.symbol_for_topic(*topic).is_some())
+        });
+        let next_cursor = truncated.then_some(SymbolCursor {
+            block_number,
+            log_offset: offset,
+            total_logs,
+        });
+
+        Ok(SymbolPage {
+            block_number,
+            symbols,
+            next_cursor,
+            total_logs,
+            limit,
+            truncated,
+        })
     }
 
     /// Check if `from_address` is the deployer of `contract_address`.
@@ -617,7 +1700,7 @@ impl BlockVerifier {
 
         // 1. Check in-memory cache
         {
-            let cache = self.deployer_cache.lock().unwrap();
+            let cache = lock_cache(&self.deployer_cache);
             if let Some(cached_deployer) = cache.get(&contract_address) {
                 return *cached_deployer == Some(from_address);
             }
@@ -632,7 +1715,7 @@ impl BlockVerifier {
 
         // Cache the result
         {
-            let mut cache = self.deployer_cache.lock().unwrap();
+            let mut cache = lock_cache(&self.deployer_cache);
             cache.insert(contract_address, deployer);
         }
 
@@ -647,16 +1730,68 @@ impl BlockVerifier {
         block_number: u64,
     ) -> Result<Vec<BehavioralSymbol>> {
         let logs_fut = self.rpc_client.fetch_logs_for_block(block_number);
-        let txs_fut = self.rpc_client.fetch_block_transactions(block_number);
+        let block_fut = self.rpc_client.fetch_block_with_transactions(block_number);
 
-        let (logs, txs) = tokio::try_join!(logs_fut, txs_fut)?;
+        let (logs, block) = tokio::try_join!(logs_fut, block_fut)?;
 
-        let tx_map: std::collections::HashMap<_, _> = txs
+        let tx_map: std::collections::HashMap<_, _> = block
+            .transactions
             .iter()
             .map(|tx| (tx.hash, (tx.nonce, tx.from)))
             .collect();
 
-        Ok(self.parse_logs_to_symbols(&logs, &tx_map))
+        let mut symbols = self
+            .parse_logs_to_symbols(block_number, &logs, &tx_map)
+            .await;
+        apply_blob_context(&mut symbols, &block.transactions, &logs, false);
+        apply_block_position(&mut symbols, block_number, &block);
+        Ok(symbols)
+    }
+}
+
+/// Withdrawals in a block body, checked against the header's
+/// `withdrawalsRoot`; empty before Shanghai.
+fn verified_withdrawals(block: &Block<Transaction>) -> Result<&[Withdrawal]> {
+    let withdrawals = block.withdrawals.as_deref().unwrap_or_default();
+    verify_withdrawals_root(withdrawals, block.withdrawals_root)?;
+    Ok(withdrawals)
+}
+
+/// Topics selecting the logs of all `needs`, sorted; empty (every log) if
+/// any of them needs every log.
+fn topic_union(needs: &[&Vec<H256>]) -> Vec<H256> {
+    if needs.iter().any(|topics| topics.is_empty()) {
+        return Vec::new();
+    }
+    let mut union: Vec<H256> = needs.iter().flat_map(|t| t.iter().copied()).collect();
+    union.sort_unstable();
+    union.dedup();
+    union
+}
+
+/// Set each symbol's `gas_used` from its transaction's entry in `gas_used`.
+fn apply_gas_used(
+    symbols: &mut [BehavioralSymbol],
+    gas_used: &HashMap<H256, Option<ethers_core::types::U256>>,
+) {
+    for sym in symbols {
+        if let Some(Some(gas)) = gas_used.get(&sym.tx_hash) {
+            sym.gas_used = Some(gas.low_u64());
+        }
+    }
+}
+
+/// Stamp symbols with their block and its timestamp, so symbols fetched
+/// from several blocks can be matched against a `within` window.
+fn apply_block_position(
+    symbols: &mut [BehavioralSymbol],
+    block_number: u64,
+    block: &Block<Transaction>,
+) {
+    let timestamp = block.timestamp.as_u64();
+    for sym in symbols {
+        sym.block_number = Some(block_number);
+        sym.timestamp = Some(timestamp);
     }
 }
 
@@ -677,4 +1812,145 @@ mod tests {
         let result = BlockVerifier::new(&urls);
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn test_unparseable_patterns_fail_individually() {
+        // Nothing is fetched when no pattern parses, so the URL is never hit
+        let urls = vec!["http://127.0.0.1:1".to_string()];
+        let verifier = BlockVerifier::new(&urls).unwrap();
+
+        let outcomes = verifier
+            .verify_patterns_in_block(&["", "Sw}"], 1)
+            .await
+            .unwrap();
+
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0].pattern, "");
+        assert_eq!(outcomes[1].pattern, "Sw}");
+        assert!(outcomes
+            .iter()
+            .all(|o| o.result.is_err() && o.matched.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn test_proxy_symbols_record_implementation() {
+        let urls = vec!["http://127.0.0.1:1".to_string()];
+        let mut verifier = BlockVerifier::new(&urls).unwrap().with_proxy_resolution();
+        let (proxy, implementation, plain) = (
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            Address::repeat_byte(3),
+        );
+        let deployer = Address::repeat_byte(9);
+        verifier.registry = ContractRegistry::new();
+        verifier.registry.add(implementation, deployer, 1, None);
+        // Cached lookups, so the URL is never hit
+        lock_cache(&verifier.proxy_cache).put((proxy, 7), Some(implementation));
+        lock_cache(&verifier.proxy_cache).put((plain, 7), None);
+
+        let sym = |contract: Address| {
+            let mut sym = BehavioralSymbol::new("Tf", 0).with_context(
+                deployer,
+                Address::zero(),
+                ethers_core::types::U256::zero(),
+                None,
+            );
+            sym.contract_address = contract;
+            sym.tx_hash = H256::repeat_byte(5);
+            sym
+        };
+        let mut symbols = vec![sym(proxy), sym(plain), sym(proxy)];
+        verifier.resolve_proxies(7, &mut symbols).await;
+        assert_eq!(symbols[0].implementation, Some(implementation));
+        assert!(symbols[0].is_from_deployer);
+        assert_eq!(symbols[1].implementation, None);
+        assert!(!symbols[1].is_from_deployer);
+        assert_eq!(symbols[2].implementation, Some(implementation));
+
+        // The slot is read per block, so a later upgrade is not missed
+        // (the URL is unreachable, leaving the symbol unresolved)
+        let mut later = vec![sym(proxy)];
+        verifier.resolve_proxies(8, &mut later).await;
+        assert_eq!(later[0].implementation, None);
+    }
+
+    #[test]
+    fn test_symbol_pages_resume_from_cursor() {
+        let urls = vec!["http://127.0.0.1:1".to_string()];
+        let verifier = BlockVerifier::new(&urls).unwrap();
+        let transfer = verifier.dictionary().topics_for_symbol("Tf")[0];
+        let log = |topic: H256, index: u64| Log {
+            topics: vec![topic],
+            log_index: Some(index.into()),
+            ..Default::default()
+        };
+        // Five transfers, with an unknown event after each
+        let logs: Vec<Log> = (0..10u64)
+            .map(|i| log(if i % 2 == 0 { transfer } else { H256::zero() }, i))
+            .collect();
+        let tx_map = HashMap::new();
+
+        let mut cursor = None;
+        let mut pages = Vec::new();
+        loop {
+            let page = verifier.symbol_page(7, &logs, &tx_map, cursor, 2).unwrap();
+            assert_eq!(page.truncated, page.next_cursor.is_some());
+            pages.push(page.symbols.len());
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(pages, vec![2, 2, 1]);
+
+        // Cursors from another block or a changed block are refused
+        let cursor = verifier
+            .symbol_page(7, &logs, &tx_map, None, 2)
+            .unwrap()
+            .next_cursor;
+        assert!(verifier.symbol_page(8, &logs, &tx_map, cursor, 2).is_err());
+        assert!(verifier
+            .symbol_page(7, &logs[..9], &tx_map, cursor, 2)
+            .is_err());
+
+        let page = verifier
+            .symbol_page(7, &logs, &tx_map, None, usize::MAX)
+            .unwrap();
+        assert_eq!(page.limit, MAX_SYMBOLS_PER_PAGE);
+        assert_eq!(page.symbols.len(), 5);
+        assert!(!page.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_later_pages_reuse_the_block() {
+        let urls = vec!["http://127.0.0.1:1".to_string()];
+        let verifier = BlockVerifier::new(&urls).unwrap();
+        let transfer = verifier.dictionary().topics_for_symbol("Tf")[0];
+        let logs: Vec<Log> = (0..4u64)
+            .map(|i| Log {
+                topics: vec![transfer],
+                log_index: Some(i.into()),
+                ..Default::default()
+            })
+            .collect();
+        let cursor = verifier
+            .symbol_page(7, &logs, &HashMap::new(), None, 2)
+            .unwrap()
+            .next_cursor;
+        lock_cache(&verifier.page_sources).put(
+            7,
+            Arc::new(PageSource {
+                logs,
+                tx_map: HashMap::new(),
+            }),
+        );
+
+        // The RPC is unreachable, so only the kept block can serve the page
+        let page = verifier
+            .fetch_block_symbols_page(7, cursor, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.symbols.len(), 2);
+        assert!(verifier.fetch_block_symbols_page(7, None, 2).await.is_err());
+    }
 }
@@ -0,0 +1,171 @@
+//! Consensus-layer withdrawals (EIP-4895).
+//!
+//! Since Shanghai, validator withdrawals are credited from the block body
+//! rather than by a transaction, so they never show up in logs. Each one
+//! becomes a `ClWdw` symbol with the recipient in `to`, the amount in wei
+//! in `value` and the validator in `validator_index`, so staking exits can
+//! be matched like any other behavior (e.g. `ClWdw{8,} where to == 0x...`).
+//!
+//! Withdrawals are processed after every transaction of the block, so their
+//! symbols take the log indices following the last log, in body order. The
+//! body's withdrawals must hash to the header's `withdrawalsRoot` before
+//! they become leaves (`verify_withdrawals_root`). They are not receipt
+//! logs, so they keep `RpcLog` provenance on every path.
+
+use ethers_core::types::{Log, Withdrawal, H256, U256};
+use ethers_core::utils::rlp;
+use sods_core::{BehavioralSymbol, CL_WITHDRAWAL_SYMBOL};
+
+use crate::error::{Result, SodsVerifierError};
+
+/// Wei per gwei; withdrawal amounts are denominated in gwei.
+const WEI_PER_GWEI: u64 = 1_000_000_000;
+
+/// `ClWdw` symbols for `withdrawals`, placed after `logs`.
+pub fn withdrawal_symbols(withdrawals: &[Withdrawal], logs: &[Log]) -> Vec<BehavioralSymbol> {
+    let first_index = logs
+        .iter()
+        .filter_map(|log| log.log_index)
+        .max()
+        .map(|i| i.as_u32() + 1)
+        .unwrap_or(0);
+
+    withdrawals
+        .iter()
+        .zip(first_index..)
+        .map(|(withdrawal, log_index)| {
+            // The JSON-RPC `amount` is in gwei, whatever ethers' docs say
+            let value = withdrawal.amount.saturating_mul(U256::from(WEI_PER_GWEI));
+            BehavioralSymbol::new(CL_WITHDRAWAL_SYMBOL, log_index)
+                .with_context(Default::default(), withdrawal.address, value, None)
+                .with_validator(withdrawal.validator_index.as_u64())
+        })
+        .collect()
+}
+
+/// Root of the withdrawals trie: `RLP(index)` to
+/// `RLP([index, validator_index, address, amount])`, as in the header.
+pub fn compute_withdrawals_root(withdrawals: &[Withdrawal]) -> H256 {
+    let encoded: Vec<Vec<u8>> = withdrawals
+        .iter()
+        .map(|w| rlp::encode(w).to_vec())
+        .collect();
+    sods_core::ordered_trie::ordered_trie_root(&encoded)
+}
+
+/// Check `withdrawals` against the header's `withdrawalsRoot`.
+///
+/// A block without a root (before Shanghai) must have no withdrawals.
+pub fn verify_withdrawals_root(withdrawals: &[Withdrawal], expected: Option<H256>) -> Result<()> {
+    let Some(expected) = expected else {
+        if withdrawals.is_empty() {
+            return Ok(());
+        }
+        return Err(SodsVerifierError::DataInconsistent(format!(
+            "Block lists {} withdrawals but its header has no withdrawalsRoot",
+            withdrawals.len()
+        )));
+    };
+    let computed = compute_withdrawals_root(withdrawals);
+    if computed != expected {
+        return Err(SodsVerifierError::DataInconsistent(format!(
+            "Withdrawals root 0x{} does not match header withdrawalsRoot 0x{}",
+            hex::encode(computed),
+            hex::encode(expected)
+        )));
+    }
+    Ok(())
+}
+
+/// Add the `ClWdw` symbols of `withdrawals` to `symbols`, keeping them in
+/// canonical order. `logs` must be the logs `symbols` were parsed from.
+pub fn apply_withdrawals(
+    symbols: &mut Vec<BehavioralSymbol>,
+    withdrawals: &[Withdrawal],
+    logs: &[Log],
+) {
+    if withdrawals.is_empty() {
+        return;
+    }
+    symbols.extend(withdrawal_symbols(withdrawals, logs));
+    symbols.sort();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::types::{Address, U64};
+
+    fn withdrawal(validator: u64, recipient: u8, gwei: u64) -> Withdrawal {
+        Withdrawal {
+            index: U64::from(validator),
+            validator_index: U64::from(validator),
+            address: Address::repeat_byte(recipient),
+            amount: U256::from(gwei),
+        }
+    }
+
+    #[test]
+    fn test_withdrawal_symbols_follow_logs() {
+        let logs = vec![
+            Log {
+                log_index: Some(U256::from(0)),
+                ..Default::default()
+            },
+            Log {
+                log_index: Some(U256::from(4)),
+                ..Default::default()
+            },
+        ];
+        let withdrawals = vec![withdrawal(7, 0xaa, 32_000_000_000), withdrawal(9, 0xaa, 15)];
+        let symbols = withdrawal_symbols(&withdrawals, &logs);
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].symbol(), CL_WITHDRAWAL_SYMBOL);
+        assert_eq!(symbols[0].log_index(), 5);
+        assert_eq!(symbols[1].log_index(), 6);
+        assert_eq!(symbols[0].to, Address::repeat_byte(0xaa));
+        assert_eq!(symbols[0].validator_index, Some(7));
+        // 32 ETH, credited in gwei
+        assert_eq!(symbols[0].value, U256::from(32u64) * U256::exp10(18));
+        assert_eq!(symbols[1].value, U256::from(15 * WEI_PER_GWEI));
+
+        // A block without logs starts at index 0
+        assert_eq!(withdrawal_symbols(&withdrawals, &[])[0].log_index(), 0);
+    }
+
+    #[test]
+    fn test_apply_withdrawals_keeps_order() {
+        let logs = vec![Log {
+            log_index: Some(U256::from(1)),
+            ..Default::default()
+        }];
+        let mut symbols = vec![BehavioralSymbol::new("Tf", 1)];
+        apply_withdrawals(&mut symbols, &[withdrawal(1, 0xbb, 1)], &logs);
+        let order: Vec<(&str, u32)> = symbols
+            .iter()
+            .map(|s| (s.symbol(), s.log_index()))
+            .collect();
+        assert_eq!(order, vec![("Tf", 1), ("ClWdw", 2)]);
+    }
+
+    #[test]
+    fn test_withdrawals_checked_against_root() {
+        let withdrawals = vec![withdrawal(7, 0xaa, 32_000_000_000), withdrawal(9, 0xaa, 15)];
+        let root = compute_withdrawals_root(&withdrawals);
+        assert!(verify_withdrawals_root(&withdrawals, Some(root)).is_ok());
+
+        // keccak256(RLP("")), the root of a block without withdrawals
+        let empty: H256 = "0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+            .parse()
+            .unwrap();
+        assert_eq!(compute_withdrawals_root(&[]), empty);
+        assert!(verify_withdrawals_root(&[], None).is_ok());
+
+        let mut inflated = withdrawals.clone();
+        inflated[1].amount = U256::from(32_000_000_000u64);
+        assert!(verify_withdrawals_root(&inflated, Some(root)).is_err());
+        assert!(verify_withdrawals_root(&withdrawals[..1], Some(root)).is_err());
+        assert!(verify_withdrawals_root(&withdrawals, None).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use risc0_zkvm::guest::env;
+use sods_core::pattern::BehavioralPattern;
 use sods_core::symbol::BehavioralProofInput;
 
 risc0_zkvm::guest::entry!(main);
@@ -8,9 +9,12 @@ fn main() {
     let input: BehavioralProofInput = env::read();
     
     // Run SODS core verification INSIDE the zkVM
-    let is_valid = sods_core::pattern::matches_str(&input.symbols, &input.pattern);
+    let pattern = BehavioralPattern::parse(&input.pattern).expect("host sends a valid pattern");
+    let is_valid = pattern.matches(&input.symbols, None).is_some();
     
-    // Commit rich metadata to the journal for on-chain verification
-    // Tuple: (blockNumber, chainId, pattern, result)
-    env::commit(&(input.block_number, input.chain_id, input.pattern, is_valid));
+    // Commit rich metadata to the journal for on-chain verification.
+    // The pattern is committed by its canonical hash, so every spelling of
+    // it proves the same claim.
+    // Tuple: (blockNumber, chainId, patternHash, result)
+    env::commit(&(input.block_number, input.chain_id, pattern.canonical_hash(), is_valid));
 }
@@ -56,4 +56,15 @@ mod tests {
         assert!(sods_core::pattern::matches_str(&symbols, "Sandwich", None));
         assert!(!sods_core::pattern::matches_str(&symbols, "Dep", None));
     }
+
+    #[test]
+    fn test_journal_pattern_hash() {
+        // The journal commits to the canonical hash, not the string
+        let hash = |p: &str| {
+            sods_core::pattern::BehavioralPattern::parse(p)
+                .unwrap()
+                .canonical_hash()
+        };
+        assert_eq!(hash("Sandwich"), hash("Tf->Sw->Tf"));
+    }
 }